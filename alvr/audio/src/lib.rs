@@ -1,3 +1,5 @@
+#[cfg(target_os = "linux")]
+mod linux;
 #[cfg(windows)]
 mod windows;
 
@@ -11,6 +13,7 @@ use alvr_common::{
     parking_lot::Mutex,
     ConnectionError, ToAny,
 };
+use alvr_packets::{AudioDeviceInfo, AudioDevicesList};
 use alvr_session::{
     AudioBufferingConfig, CustomAudioDeviceConfig, LinuxAudioBackend, MicrophoneDevicesConfig,
 };
@@ -24,9 +27,24 @@ use std::{
     collections::{HashMap, VecDeque},
     sync::Arc,
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+const AUDIO_STATISTICS_INTERVAL: Duration = Duration::from_secs(60);
+
+// If no packet is received for longer than this, the stream has been paused and any sample still in
+// the buffer is stale.
+const AUDIO_RESYNC_INTERVAL: Duration = Duration::from_millis(200);
+
+// Smoothing factor used to estimate the buffer fill level
+const FILL_LEVEL_AVERAGING_FACTOR: f32 = 0.05;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AudioBufferStatistics {
+    pub underruns_per_minute: f32,
+    pub overruns_per_minute: f32,
+}
+
 static VIRTUAL_MICROPHONE_PAIRS: Lazy<HashMap<&str, &str>> = Lazy::new(|| {
     [
         ("CABLE Input", "CABLE Output"),
@@ -38,7 +56,146 @@ static VIRTUAL_MICROPHONE_PAIRS: Lazy<HashMap<&str, &str>> = Lazy::new(|| {
     .collect()
 });
 
-fn device_from_custom_config(host: &Host, config: &CustomAudioDeviceConfig) -> Result<Device> {
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+fn get_host(linux_backend: Option<LinuxAudioBackend>) -> Result<Host> {
+    #[cfg(target_os = "linux")]
+    let host = match linux_backend {
+        Some(LinuxAudioBackend::Alsa) => cpal::host_from_id(cpal::HostId::Alsa)?,
+        Some(LinuxAudioBackend::Jack) => cpal::host_from_id(cpal::HostId::Jack)?,
+        None => cpal::default_host(),
+    };
+    #[cfg(not(target_os = "linux"))]
+    let host = cpal::default_host();
+
+    Ok(host)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct DeviceIdentity {
+    id: String,
+    name: String,
+}
+
+// platform_ids contains the IDs of the devices with each name, in enumeration order. Devices
+// without one use their name, followed by their position among the devices with the same name
+fn assign_device_ids(
+    names: Vec<String>,
+    platform_ids: &HashMap<String, Vec<String>>,
+) -> Vec<DeviceIdentity> {
+    let mut occurrences = HashMap::<String, usize>::new();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let occurrence = occurrences.entry(name.clone()).or_default();
+            let index = *occurrence;
+            *occurrence += 1;
+
+            let id = platform_ids
+                .get(&name)
+                .and_then(|ids| ids.get(index))
+                .cloned()
+                .unwrap_or_else(|| {
+                    if index == 0 {
+                        name.clone()
+                    } else {
+                        format!("{name} #{}", index + 1)
+                    }
+                });
+
+            DeviceIdentity { id, name }
+        })
+        .collect()
+}
+
+// An endpoint ID changes when the device is plugged into another port, and an ID made from the name
+// changes when the device is renamed. In both cases the device is found again by name
+fn find_device_index(devices: &[DeviceIdentity], id: &str, name: &str) -> Option<usize> {
+    devices
+        .iter()
+        .position(|device| device.id == id)
+        .or_else(|| devices.iter().position(|device| device.name == name))
+}
+
+fn device_entries(host: &Host, is_output: bool) -> Result<Vec<(Device, DeviceIdentity)>> {
+    let devices = if is_output {
+        host.output_devices()?.collect::<Vec<_>>()
+    } else {
+        host.input_devices()?.collect::<Vec<_>>()
+    };
+    let (devices, names): (Vec<_>, Vec<_>) = devices
+        .into_iter()
+        .filter_map(|device| {
+            let name = device.name().ok()?;
+            Some((device, name))
+        })
+        .unzip();
+
+    #[cfg(windows)]
+    let platform_ids = crate::windows::endpoint_ids_by_name(is_output).unwrap_or_default();
+    #[cfg(target_os = "linux")]
+    let platform_ids = crate::linux::device_ids_by_name(&names);
+    #[cfg(not(any(windows, target_os = "linux")))]
+    let platform_ids = HashMap::new();
+
+    Ok(devices
+        .into_iter()
+        .zip(assign_device_ids(names, &platform_ids))
+        .collect())
+}
+
+fn default_device_id(host: &Host, is_output: bool, devices: &[DeviceIdentity]) -> Option<String> {
+    #[cfg(windows)]
+    if let Ok(id) = crate::windows::default_endpoint_id(is_output) {
+        return Some(id);
+    }
+
+    let default_device = if is_output {
+        host.default_output_device()
+    } else {
+        host.default_input_device()
+    };
+    let name = default_device?.name().ok()?;
+
+    // cpal does not tell which of the devices with this name is the default one
+    devices
+        .iter()
+        .find(|device| device.name == name)
+        .map(|device| device.id.clone())
+}
+
+pub fn list_devices(linux_backend: Option<LinuxAudioBackend>) -> Result<AudioDevicesList> {
+    let host = get_host(linux_backend)?;
+
+    let list_infos = |is_output| -> Result<Vec<AudioDeviceInfo>> {
+        let devices = device_entries(&host, is_output)?
+            .into_iter()
+            .map(|(_, identity)| identity)
+            .collect::<Vec<_>>();
+        let default_id = default_device_id(&host, is_output, &devices);
+
+        Ok(devices
+            .into_iter()
+            .map(|DeviceIdentity { id, name }| AudioDeviceInfo {
+                is_default: Some(&id) == default_id.as_ref(),
+                id,
+                name,
+            })
+            .collect())
+    };
+
+    Ok(AudioDevicesList {
+        output: list_infos(true)?,
+        input: list_infos(false)?,
+    })
+}
+
+// Devices selected by ID are searched among the devices of the preferred direction first
+fn device_from_custom_config(
+    host: &Host,
+    config: &CustomAudioDeviceConfig,
+    is_output: bool,
+) -> Result<Device> {
     Ok(match config {
         CustomAudioDeviceConfig::NameSubstring(name_substring) => host
             .devices()?
@@ -54,6 +211,17 @@ fn device_from_custom_config(host: &Host, config: &CustomAudioDeviceConfig) -> R
             .devices()?
             .nth(*index)
             .with_context(|| format!("Cannot find audio device at index {index}"))?,
+        CustomAudioDeviceConfig::Id { id, name } => {
+            let (mut devices, identities): (Vec<_>, Vec<_>) = device_entries(host, is_output)?
+                .into_iter()
+                .chain(device_entries(host, !is_output)?)
+                .unzip();
+
+            let index = find_device_index(&identities, id, name)
+                .with_context(|| format!("Cannot find audio device \"{name}\" (ID: {id})"))?;
+
+            devices.swap_remove(index)
+        }
     })
 }
 
@@ -85,26 +253,18 @@ pub struct AudioDevice {
     is_output: bool,
 }
 
-#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
 impl AudioDevice {
     pub fn new_output(
         linux_backend: Option<LinuxAudioBackend>,
         config: Option<&CustomAudioDeviceConfig>,
     ) -> Result<Self> {
-        #[cfg(target_os = "linux")]
-        let host = match linux_backend {
-            Some(LinuxAudioBackend::Alsa) => cpal::host_from_id(cpal::HostId::Alsa).unwrap(),
-            Some(LinuxAudioBackend::Jack) => cpal::host_from_id(cpal::HostId::Jack).unwrap(),
-            None => cpal::default_host(),
-        };
-        #[cfg(not(target_os = "linux"))]
-        let host = cpal::default_host();
+        let host = get_host(linux_backend)?;
 
         let device = match config {
             None => host
                 .default_output_device()
                 .context("No output audio device found")?,
-            Some(config) => device_from_custom_config(&host, config)?,
+            Some(config) => device_from_custom_config(&host, config, true)?,
         };
 
         Ok(Self {
@@ -120,7 +280,7 @@ impl AudioDevice {
             None => host
                 .default_input_device()
                 .context("No input audio device found")?,
-            Some(config) => device_from_custom_config(&host, &config)?,
+            Some(config) => device_from_custom_config(&host, &config, false)?,
         };
 
         Ok(Self {
@@ -134,14 +294,7 @@ impl AudioDevice {
         linux_backend: Option<LinuxAudioBackend>,
         config: MicrophoneDevicesConfig,
     ) -> Result<(Self, Self)> {
-        #[cfg(target_os = "linux")]
-        let host = match linux_backend {
-            Some(LinuxAudioBackend::Alsa) => cpal::host_from_id(cpal::HostId::Alsa).unwrap(),
-            Some(LinuxAudioBackend::Jack) => cpal::host_from_id(cpal::HostId::Jack).unwrap(),
-            None => cpal::default_host(),
-        };
-        #[cfg(not(target_os = "linux"))]
-        let host = cpal::default_host();
+        let host = get_host(linux_backend)?;
 
         let (sink, source) = match config {
             MicrophoneDevicesConfig::Automatic => {
@@ -168,8 +321,8 @@ impl AudioDevice {
                 microphone_pair_from_sink_name(&host, "VoiceMeeter VAIO3 Input")?
             }
             MicrophoneDevicesConfig::Custom { sink, source } => (
-                device_from_custom_config(&host, &sink)?,
-                device_from_custom_config(&host, &source)?,
+                device_from_custom_config(&host, &sink, true)?,
+                device_from_custom_config(&host, &source, false)?,
             ),
         };
 
@@ -317,12 +470,36 @@ fn downmix_audio(data: Vec<u8>, in_channels: u16, out_channels: u16) -> Vec<u8>
     }
 }
 
+// Scales the samples of a batch. The gain is ramped from the previous value over the whole batch,
+// so volume changes don't produce clicks. A gain of 0 mutes the audio completely.
+pub fn apply_gain(batch: &mut [f32], channels_count: usize, last_gain: &mut f32, gain: f32) {
+    let frames_count = batch.len() / channels_count;
+    for f in 0..frames_count {
+        let frame_gain = *last_gain + (gain - *last_gain) * (f + 1) as f32 / frames_count as f32;
+        for c in 0..channels_count {
+            batch[f * channels_count + c] *= frame_gain;
+        }
+    }
+
+    *last_gain = gain;
+}
+
+// Scales 16 bit PCM samples, saturating on overflow
+pub fn apply_pcm_gain(data: &mut [u8], gain: f32) {
+    for sample in data.chunks_exact_mut(2) {
+        let value = i16::from_ne_bytes([sample[0], sample[1]]) as f32 * gain;
+        sample
+            .copy_from_slice(&(value.clamp(i16::MIN as f32, i16::MAX as f32) as i16).to_ne_bytes());
+    }
+}
+
 #[allow(unused_variables)]
 pub fn record_audio_blocking(
     is_running: Arc<dyn Fn() -> bool + Send + Sync>,
     mut sender: StreamSender<()>,
     device: &AudioDevice,
     channels_count: u16,
+    gain: f32,
     mute: bool,
 ) -> Result<()> {
     let config = device
@@ -372,7 +549,10 @@ pub fn record_audio_blocking(
                     data.bytes().to_vec()
                 };
 
-                let data = downmix_audio(data, config.channels(), channels_count);
+                let mut data = downmix_audio(data, config.channels(), channels_count);
+                if gain != 1.0 {
+                    apply_pcm_gain(&mut data, gain);
+                }
 
                 if is_running() {
                     let mut buffer = sender.get_buffer(&()).unwrap();
@@ -445,6 +625,31 @@ pub fn get_next_frame_batch(
     }
 }
 
+// Drop or duplicate one frame in the middle of a packet to slowly converge to the target buffer
+// size. This is inaudible, compared to a cross-fade.
+fn correct_buffer_drift(
+    samples: &mut Vec<f32>,
+    channels_count: usize,
+    average_fill_frames: f32,
+    target_frames_count: usize,
+    tolerance_frames_count: usize,
+) {
+    let frames_count = samples.len() / channels_count;
+    if frames_count < 2 {
+        return;
+    }
+    let middle = frames_count / 2 * channels_count;
+
+    if average_fill_frames > (target_frames_count + tolerance_frames_count) as f32 {
+        samples.drain(middle..middle + channels_count);
+    } else if average_fill_frames
+        < target_frames_count.saturating_sub(tolerance_frames_count) as f32
+    {
+        let frame = samples[middle..middle + channels_count].to_vec();
+        samples.splice(middle..middle, frame);
+    }
+}
+
 // The receive loop is resposible for ensuring smooth transitions in case of disruptions (buffer
 // underflow, overflow, packet loss). In case the computation takes too much time, the audio
 // callback will gracefully handle an interruption, and the callback timing and sound wave
@@ -456,9 +661,30 @@ pub fn receive_samples_loop(
     channels_count: usize,
     batch_frames_count: usize,
     average_buffer_frames_count: usize,
+    mut report_statistics: impl FnMut(AudioBufferStatistics),
 ) -> Result<()> {
     let mut recovery_sample_buffer = vec![];
+    let mut average_fill_frames = average_buffer_frames_count as f32;
+    let mut last_packet_instant = Instant::now();
+    // The buffer starts empty, this is not counted as an underrun
+    let mut in_underrun = true;
+    let mut underruns_count = 0;
+    let mut overruns_count = 0;
+    let mut last_statistics_instant = Instant::now();
     while is_running() {
+        let now = Instant::now();
+        if now > last_statistics_instant + AUDIO_STATISTICS_INTERVAL {
+            let minutes = (now - last_statistics_instant).as_secs_f32() / 60.0;
+            report_statistics(AudioBufferStatistics {
+                underruns_per_minute: underruns_count as f32 / minutes,
+                overruns_per_minute: overruns_count as f32 / minutes,
+            });
+
+            underruns_count = 0;
+            overruns_count = 0;
+            last_statistics_instant = now;
+        }
+
         let data = match receiver.recv(Duration::from_millis(500)) {
             Ok(data) => data,
             Err(ConnectionError::TryAgain(_)) => continue,
@@ -466,13 +692,24 @@ pub fn receive_samples_loop(
         };
         let (_, packet) = data.get()?;
 
-        let new_samples = packet
+        let mut new_samples = packet
             .chunks_exact(2)
             .map(|c| i16::from_ne_bytes([c[0], c[1]]).to_sample::<f32>())
             .collect::<Vec<_>>();
 
         let mut sample_buffer_ref = sample_buffer.lock();
 
+        if last_packet_instant.elapsed() > AUDIO_RESYNC_INTERVAL {
+            info!("Audio stream resumed, resynchronizing");
+
+            // The callback already rendered a fade-out if the buffer ran out
+            sample_buffer_ref.clear();
+            recovery_sample_buffer.clear();
+            average_fill_frames = average_buffer_frames_count as f32;
+            in_underrun = true;
+        }
+        last_packet_instant = Instant::now();
+
         if data.had_packet_loss() {
             info!("Audio packet loss!");
 
@@ -487,6 +724,11 @@ pub fn receive_samples_loop(
         }
 
         if sample_buffer_ref.len() / channels_count < batch_frames_count {
+            if !in_underrun {
+                underruns_count += 1;
+                in_underrun = true;
+            }
+
             recovery_sample_buffer.extend(sample_buffer_ref.drain(..));
         }
 
@@ -520,16 +762,29 @@ pub fn receive_samples_loop(
                 }
 
                 sample_buffer_ref.extend(recovery_sample_buffer.drain(..));
+                in_underrun = false;
                 info!("Audio recovered");
             }
         } else {
+            let fill_frames = (sample_buffer_ref.len() / channels_count) as f32;
+            average_fill_frames +=
+                (fill_frames - average_fill_frames) * FILL_LEVEL_AVERAGING_FACTOR;
+
+            correct_buffer_drift(
+                &mut new_samples,
+                channels_count,
+                average_fill_frames,
+                average_buffer_frames_count,
+                batch_frames_count / 2,
+            );
+
             sample_buffer_ref.extend(&new_samples);
         }
 
-        // todo: use smarter policy with EventTiming
         let buffer_frames_size = sample_buffer_ref.len() / channels_count;
         if buffer_frames_size > 2 * average_buffer_frames_count + batch_frames_count {
             info!("Audio buffer overflow! size: {buffer_frames_size}");
+            overruns_count += 1;
 
             let drained_samples = sample_buffer_ref
                 .drain(0..(buffer_frames_size - average_buffer_frames_count) * channels_count)
@@ -544,6 +799,8 @@ pub fn receive_samples_loop(
                         sample_buffer_ref[index] * volume + drained_samples[index] * (1. - volume);
                 }
             }
+
+            average_fill_frames = average_buffer_frames_count as f32;
         }
     }
 
@@ -552,6 +809,8 @@ pub fn receive_samples_loop(
 
 struct StreamingSource {
     sample_buffer: Arc<Mutex<VecDeque<f32>>>,
+    gain: Arc<Mutex<f32>>,
+    last_gain: f32,
     current_batch: Vec<f32>,
     current_batch_cursor: usize,
     channels_count: usize,
@@ -588,6 +847,12 @@ impl Iterator for StreamingSource {
                 self.channels_count,
                 self.batch_frames_count,
             );
+            apply_gain(
+                &mut self.current_batch,
+                self.channels_count,
+                &mut self.last_gain,
+                *self.gain.lock(),
+            );
         }
 
         let sample = self.current_batch[self.current_batch_cursor];
@@ -605,7 +870,9 @@ pub fn play_audio_loop(
     channels_count: u16,
     sample_rate: u32,
     config: AudioBufferingConfig,
+    gain: Arc<Mutex<f32>>,
     receiver: &mut StreamReceiver<()>,
+    report_statistics: impl FnMut(AudioBufferStatistics),
 ) -> Result<()> {
     // Size of a chunk of frames. It corresponds to the duration if a fade-in/out in frames.
     let batch_frames_count = sample_rate as usize * config.batch_ms as usize / 1000;
@@ -618,8 +885,11 @@ pub fn play_audio_loop(
 
     let (_stream, handle) = OutputStream::try_from_device(&device.inner)?;
 
+    let last_gain = *gain.lock();
     handle.play_raw(StreamingSource {
         sample_buffer: Arc::clone(&sample_buffer),
+        gain,
+        last_gain,
         current_batch: vec![],
         current_batch_cursor: 0,
         channels_count: channels_count as _,
@@ -634,8 +904,62 @@ pub fn play_audio_loop(
         channels_count as _,
         batch_frames_count,
         average_buffer_frames_count,
+        report_statistics,
     )
     .ok();
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| (*name).to_owned()).collect()
+    }
+
+    #[test]
+    fn test_duplicate_names() {
+        let devices =
+            assign_device_ids(names(&["USB DAC", "Speakers", "USB DAC"]), &HashMap::new());
+        let ids = devices.iter().map(|d| d.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, ["USB DAC", "Speakers", "USB DAC #2"]);
+
+        assert_eq!(
+            find_device_index(&devices, "USB DAC #2", "USB DAC"),
+            Some(2)
+        );
+        assert_eq!(find_device_index(&devices, "USB DAC", "USB DAC"), Some(0));
+    }
+
+    #[test]
+    fn test_platform_ids() {
+        let platform_ids = [(
+            "USB DAC".to_owned(),
+            vec!["{endpoint-a}".to_owned(), "{endpoint-b}".to_owned()],
+        )]
+        .into_iter()
+        .collect();
+        let mut devices = assign_device_ids(names(&["USB DAC", "USB DAC"]), &platform_ids);
+        assert_eq!(devices[1].id, "{endpoint-b}");
+        assert_eq!(
+            find_device_index(&devices, "{endpoint-b}", "USB DAC"),
+            Some(1)
+        );
+
+        // Still found after being renamed
+        devices[1].name = "Headphones".into();
+        assert_eq!(
+            find_device_index(&devices, "{endpoint-b}", "USB DAC"),
+            Some(1)
+        );
+
+        // A new ID after replugging falls back to the name
+        assert_eq!(
+            find_device_index(&devices, "{endpoint-c}", "Headphones"),
+            Some(1)
+        );
+        assert_eq!(find_device_index(&devices, "{endpoint-c}", "Other"), None);
+    }
+}
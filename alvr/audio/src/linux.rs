@@ -0,0 +1,75 @@
+use std::{collections::HashMap, fs};
+
+// sysfs path of each sound card, by card ID. The card IDs and numbers follow the order in which the
+// cards are detected, while the path only depends on the port the card is connected to
+fn card_paths() -> HashMap<String, String> {
+    let Ok(entries) = fs::read_dir("/sys/class/sound") else {
+        return HashMap::new();
+    };
+
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with("card"))
+        .filter_map(|entry| {
+            let card_dir = entry.path();
+            let id = fs::read_to_string(card_dir.join("id")).ok()?;
+            let path = fs::canonicalize(card_dir.join("device")).ok()?;
+
+            Some((id.trim().to_owned(), path.to_string_lossy().into_owned()))
+        })
+        .collect()
+}
+
+// ALSA device names have the form "hw:CARD=DAC,DEV=0". The card ID is replaced by the path of the
+// card, devices not bound to a card, like "pipewire" or "default", have no ID
+fn alsa_device_id(name: &str, card_paths: &HashMap<String, String>) -> Option<String> {
+    let card_start = name.find("CARD=")? + "CARD=".len();
+    let card_end = name[card_start..]
+        .find(',')
+        .map_or(name.len(), |end| card_start + end);
+    let path = card_paths.get(&name[card_start..card_end])?;
+
+    Some(format!(
+        "{}{path}{}",
+        &name[..card_start],
+        &name[card_end..]
+    ))
+}
+
+pub(crate) fn device_ids_by_name(names: &[String]) -> HashMap<String, Vec<String>> {
+    let card_paths = card_paths();
+
+    names
+        .iter()
+        .filter_map(|name| Some((name.clone(), vec![alsa_device_id(name, &card_paths)?])))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alsa_device_id() {
+        let card_paths = [
+            ("DAC".to_owned(), "/sys/devices/usb1/1-1/1-1:1.0".to_owned()),
+            (
+                "DAC_1".to_owned(),
+                "/sys/devices/usb1/1-2/1-2:1.0".to_owned(),
+            ),
+        ]
+        .into_iter()
+        .collect();
+
+        assert_eq!(
+            alsa_device_id("hw:CARD=DAC_1,DEV=0", &card_paths).as_deref(),
+            Some("hw:CARD=/sys/devices/usb1/1-2/1-2:1.0,DEV=0")
+        );
+        assert_eq!(
+            alsa_device_id("sysdefault:CARD=DAC", &card_paths).as_deref(),
+            Some("sysdefault:CARD=/sys/devices/usb1/1-1/1-1:1.0")
+        );
+        assert_eq!(alsa_device_id("pipewire", &card_paths), None);
+        assert_eq!(alsa_device_id("hw:CARD=Unplugged,DEV=0", &card_paths), None);
+    }
+}
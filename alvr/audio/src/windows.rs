@@ -1,64 +1,117 @@
 use crate::AudioDevice;
 use alvr_common::anyhow::{bail, Result};
 use rodio::DeviceTrait;
+use std::collections::HashMap;
+use windows::Win32::Media::Audio::{EDataFlow, IMMDevice, IMMDeviceEnumerator};
 
-fn get_windows_device(device: &AudioDevice) -> Result<windows::Win32::Media::Audio::IMMDevice> {
-    use widestring::U16CStr;
+fn device_enumerator() -> Result<IMMDeviceEnumerator> {
     use windows::Win32::{
-        Devices::FunctionDiscovery::PKEY_Device_FriendlyName,
-        Media::Audio::{eAll, IMMDeviceEnumerator, MMDeviceEnumerator, DEVICE_STATE_ACTIVE},
-        System::Com::{self, StructuredStorage, CLSCTX_ALL, COINIT_MULTITHREADED, STGM_READ},
+        Media::Audio::MMDeviceEnumerator,
+        System::Com::{self, CLSCTX_ALL, COINIT_MULTITHREADED},
     };
 
-    let device_name = device.inner.name()?;
-
     unsafe {
         // This will fail the second time is called, ignore the error
         Com::CoInitializeEx(None, COINIT_MULTITHREADED).ok();
 
-        let imm_device_enumerator: IMMDeviceEnumerator =
-            Com::CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL)?;
-
-        let imm_device_collection =
-            imm_device_enumerator.EnumAudioEndpoints(eAll, DEVICE_STATE_ACTIVE)?;
-
-        let count = imm_device_collection.GetCount()?;
-
-        for i in 0..count {
-            let imm_device = imm_device_collection.Item(i)?;
+        Ok(Com::CoCreateInstance(
+            &MMDeviceEnumerator,
+            None,
+            CLSCTX_ALL,
+        )?)
+    }
+}
 
-            let property_store = imm_device.OpenPropertyStore(STGM_READ)?;
+fn friendly_name(imm_device: &IMMDevice) -> Result<String> {
+    use widestring::U16CStr;
+    use windows::Win32::{
+        Devices::FunctionDiscovery::PKEY_Device_FriendlyName,
+        System::Com::{StructuredStorage, STGM_READ},
+    };
 
-            let mut prop_variant = property_store.GetValue(&PKEY_Device_FriendlyName)?;
-            let utf16_name =
-                U16CStr::from_ptr_str(prop_variant.Anonymous.Anonymous.Anonymous.pwszVal.0);
-            StructuredStorage::PropVariantClear(&mut prop_variant)?;
+    unsafe {
+        let property_store = imm_device.OpenPropertyStore(STGM_READ)?;
 
-            let imm_device_name = utf16_name.to_string()?;
-            if imm_device_name == device_name {
-                return Ok(imm_device);
-            }
-        }
+        let mut prop_variant = property_store.GetValue(&PKEY_Device_FriendlyName)?;
+        let utf16_name =
+            U16CStr::from_ptr_str(prop_variant.Anonymous.Anonymous.Anonymous.pwszVal.0);
+        let name = utf16_name.to_string();
+        StructuredStorage::PropVariantClear(&mut prop_variant)?;
 
-        bail!("No device found with specified name")
+        Ok(name?)
     }
 }
 
-pub fn get_windows_device_id(device: &AudioDevice) -> Result<String> {
+fn endpoint_id(imm_device: &IMMDevice) -> Result<String> {
     use widestring::U16CStr;
     use windows::Win32::System::Com;
 
     unsafe {
-        let imm_device = get_windows_device(device)?;
-
         let id_str_ptr = imm_device.GetId()?;
-        let id_str = U16CStr::from_ptr_str(id_str_ptr.0).to_string()?;
+        let id_str = U16CStr::from_ptr_str(id_str_ptr.0).to_string();
         Com::CoTaskMemFree(Some(id_str_ptr.0 as _));
 
-        Ok(id_str)
+        Ok(id_str?)
     }
 }
 
+fn active_devices(data_flow: EDataFlow) -> Result<Vec<IMMDevice>> {
+    use windows::Win32::Media::Audio::DEVICE_STATE_ACTIVE;
+
+    unsafe {
+        let imm_device_collection =
+            device_enumerator()?.EnumAudioEndpoints(data_flow, DEVICE_STATE_ACTIVE)?;
+
+        (0..imm_device_collection.GetCount()?)
+            .map(|i| Ok(imm_device_collection.Item(i)?))
+            .collect()
+    }
+}
+
+fn get_windows_device(device: &AudioDevice) -> Result<IMMDevice> {
+    use windows::Win32::Media::Audio::eAll;
+
+    let device_name = device.inner.name()?;
+
+    for imm_device in active_devices(eAll)? {
+        if friendly_name(&imm_device)? == device_name {
+            return Ok(imm_device);
+        }
+    }
+
+    bail!("No device found with specified name")
+}
+
+pub fn get_windows_device_id(device: &AudioDevice) -> Result<String> {
+    endpoint_id(&get_windows_device(device)?)
+}
+
+// Endpoint IDs of the active devices, grouped by name. Devices with the same name are in
+// enumeration order, which is the order used by cpal
+pub(crate) fn endpoint_ids_by_name(is_output: bool) -> Result<HashMap<String, Vec<String>>> {
+    use windows::Win32::Media::Audio::{eCapture, eRender};
+
+    let mut ids = HashMap::<_, Vec<_>>::new();
+    for imm_device in active_devices(if is_output { eRender } else { eCapture })? {
+        ids.entry(friendly_name(&imm_device)?)
+            .or_default()
+            .push(endpoint_id(&imm_device)?);
+    }
+
+    Ok(ids)
+}
+
+pub(crate) fn default_endpoint_id(is_output: bool) -> Result<String> {
+    use windows::Win32::Media::Audio::{eCapture, eConsole, eRender};
+
+    let imm_device = unsafe {
+        device_enumerator()?
+            .GetDefaultAudioEndpoint(if is_output { eRender } else { eCapture }, eConsole)?
+    };
+
+    endpoint_id(&imm_device)
+}
+
 // device must be an output device
 pub fn set_mute_windows_device(device: &AudioDevice, mute: bool) -> Result<()> {
     use windows::{
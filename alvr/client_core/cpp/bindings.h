@@ -32,6 +32,13 @@ extern "C" const unsigned char *LOBBY_ROOM_GLTF_PTR;
 extern "C" unsigned int LOBBY_ROOM_GLTF_LEN;
 extern "C" const unsigned char *LOBBY_ROOM_BIN_PTR;
 extern "C" unsigned int LOBBY_ROOM_BIN_LEN;
+// User provided lobby assets. A null pointer means the asset is not present
+extern "C" const unsigned char *CUSTOM_LOBBY_GLTF_PTR;
+extern "C" unsigned int CUSTOM_LOBBY_GLTF_LEN;
+extern "C" const unsigned char *CUSTOM_LOBBY_BIN_PTR;
+extern "C" unsigned int CUSTOM_LOBBY_BIN_LEN;
+extern "C" const unsigned char *LOBBY_SKYBOX_PTR; // Encoded equirectangular image
+extern "C" unsigned int LOBBY_SKYBOX_LEN;
 
 // graphics.h
 extern "C" void initGraphicsNative();
@@ -44,6 +51,10 @@ extern "C" void prepareLobbyRoom(int viewWidth,
 extern "C" void destroyRenderers();
 extern "C" void streamStartNative(FfiStreamConfig config);
 extern "C" void updateLobbyHudTexture(const unsigned char *data);
+extern "C" void updateOverlayTextureNative(unsigned int texture,
+                                           const unsigned char *data,
+                                           int width,
+                                           int height);
 extern "C" void renderLobbyNative(const FfiViewInput eyeInputs[2]);
 extern "C" void renderStreamNative(void *streamHardwareBuffer,
                                    const unsigned int swapchainIndices[2]);
@@ -20,11 +20,22 @@ std::string AssetExpandFilePath(const std::string &path, void *) {
     return path;
 }
 
+const unsigned char *CUSTOM_LOBBY_GLTF_PTR;
+unsigned int CUSTOM_LOBBY_GLTF_LEN;
+const unsigned char *CUSTOM_LOBBY_BIN_PTR;
+unsigned int CUSTOM_LOBBY_BIN_LEN;
+
+// Any buffer uri resolves to the single binary buffer passed to GltfModel::load()
 bool AssetReadWholeFile(std::vector<unsigned char> *out,
                                std::string *, const std::string &path,
-                               void *) {
-    out->resize(LOBBY_ROOM_BIN_LEN);
-    memcpy(&(*out)[0], LOBBY_ROOM_BIN_PTR, LOBBY_ROOM_BIN_LEN);
+                               void *userData) {
+    auto buffer = (GltfBuffer *) userData;
+    if (buffer->data == nullptr || buffer->length == 0) {
+        return false;
+    }
+
+    out->resize(buffer->length);
+    memcpy(&(*out)[0], buffer->data, buffer->length);
 
     return true;
 }
@@ -39,18 +50,32 @@ tinygltf::FsCallbacks gAssetFsCallbacks {.FileExists=AssetFileExists,
         .ReadWholeFile=AssetReadWholeFile,
         .WriteWholeFile=AssetWriteWholeFile};
 
-void GltfModel::load() {
+bool GltfModel::load(const unsigned char *gltfData,
+                     unsigned int gltfLength,
+                     const unsigned char *binData,
+                     unsigned int binLength) {
     tinygltf::TinyGLTF loader;
     std::string err, warn;
 
-    loader.SetFsCallbacks(gAssetFsCallbacks);
+    m_binBuffer = {binData, binLength};
 
-    auto buffer = std::vector<unsigned char>(LOBBY_ROOM_GLTF_LEN);
-    memcpy(&buffer[0], LOBBY_ROOM_GLTF_PTR, LOBBY_ROOM_GLTF_LEN);
+    auto callbacks = gAssetFsCallbacks;
+    callbacks.user_data = &m_binBuffer;
+    loader.SetFsCallbacks(callbacks);
+
+    auto buffer = std::vector<unsigned char>(gltfLength);
+    memcpy(&buffer[0], gltfData, gltfLength);
     bool ret = loader.LoadASCIIFromString(&m_model, &err, &warn, (char *) &buffer[0], buffer.size(), "");
 
     LOGI("GltfModel loaded. ret=%d scenes=%lu defaultScene=%d err=%s.\nwarn=%s", ret, m_model.scenes.size(), m_model.defaultScene, err.c_str(), warn.c_str());
 
+    if (!ret || !validate()) {
+        LOGE("Invalid glTF model");
+        m_model = tinygltf::Model();
+
+        return false;
+    }
+
     m_vbs.resize(m_model.bufferViews.size());
 
     GL(glGenVertexArrays(1, &m_vao));
@@ -68,15 +93,77 @@ void GltfModel::load() {
     }
 
     GL(glBindVertexArray(0));
+
+    return true;
+}
+
+// Checks all indices and ranges used while drawing, so a broken model cannot crash the renderer
+bool GltfModel::validate() {
+    if (m_model.scenes.empty()) {
+        return false;
+    }
+    if (m_model.defaultScene < 0 || m_model.defaultScene >= (int) m_model.scenes.size()) {
+        m_model.defaultScene = 0;
+    }
+
+    for (auto &bufferView : m_model.bufferViews) {
+        if (bufferView.buffer < 0 || bufferView.buffer >= (int) m_model.buffers.size() ||
+            bufferView.byteLength == 0 ||
+            bufferView.byteOffset + bufferView.byteLength >
+                m_model.buffers[bufferView.buffer].data.size()) {
+            return false;
+        }
+    }
+    for (auto &accessor : m_model.accessors) {
+        if (accessor.bufferView < 0 || accessor.bufferView >= (int) m_model.bufferViews.size()) {
+            return false;
+        }
+    }
+    for (auto &mesh : m_model.meshes) {
+        for (auto &prim : mesh.primitives) {
+            if (prim.indices >= (int) m_model.accessors.size() ||
+                prim.material >= (int) m_model.materials.size()) {
+                return false;
+            }
+            for (auto &att : prim.attributes) {
+                if (att.second < 0 || att.second >= (int) m_model.accessors.size()) {
+                    return false;
+                }
+            }
+        }
+    }
+    for (auto &node : m_model.nodes) {
+        if (node.mesh >= (int) m_model.meshes.size()) {
+            return false;
+        }
+        for (auto child : node.children) {
+            if (child < 0 || child >= (int) m_model.nodes.size()) {
+                return false;
+            }
+        }
+    }
+    for (auto &scene : m_model.scenes) {
+        for (auto node : scene.nodes) {
+            if (node < 0 || node >= (int) m_model.nodes.size()) {
+                return false;
+            }
+        }
+    }
+
+    return true;
 }
 
 void GltfModel::drawScene(int position, int uv,
-                          int normal, GLint color, GLint mMatrix, GLint mode) {
+                          int normal, GLint color, GLint mMatrix, GLint mode,
+                          int surfaceMode, bool drawGround) {
     if(m_model.scenes.size() == 0) {
         return;
     }
     auto &scene = m_model.scenes[m_model.defaultScene];
 
+    m_surfaceMode = surfaceMode;
+    m_drawGround = drawGround;
+
     m_position = position;
     m_uv = uv;
     m_normal = normal;
@@ -118,6 +205,14 @@ void GltfModel::drawNode(int node_i, const ovrMatrix4f &transform) {
         if (prim.indices < 0) {
             continue;
         }
+
+        tinygltf::Material defaultMaterial;
+        auto &material = prim.material >= 0 ? m_model.materials[prim.material] : defaultMaterial;
+
+        if (material.name == "Plane" && !m_drawGround) {
+            continue;
+        }
+
         for (auto &att : prim.attributes) {
             const std::string &name = att.first;
             int att_i = att.second;
@@ -160,8 +255,6 @@ void GltfModel::drawNode(int node_i, const ovrMatrix4f &transform) {
             }
         }
 
-        auto &material = m_model.materials[prim.material];
-
         tinygltf::ColorValue colorValue = {1.0, 1.0, 1.0, 1.0};
 
         auto it = material.values.find("baseColorFactor");
@@ -173,11 +266,11 @@ void GltfModel::drawNode(int node_i, const ovrMatrix4f &transform) {
         GL(glUniformMatrix4fv(m_mMatrix, 1, true, (float *) &transform));
 
         if(material.name == "Plane") {
-            GL(glUniform1i(m_mode, 0));
+            GL(glUniform1i(m_mode, LOBBY_MODE_GROUND));
         }else if(material.name == "Message") {
-            GL(glUniform1i(m_mode, 1));
+            GL(glUniform1i(m_mode, LOBBY_MODE_TEXT));
         }else{
-            GL(glUniform1i(m_mode, 2));
+            GL(glUniform1i(m_mode, m_surfaceMode));
         }
 
         auto &indexAccessor = m_model.accessors[prim.indices];
@@ -10,8 +10,23 @@
 #define TINYGLTF_NO_STB_IMAGE_WRITE
 #include "tinygltf/tiny_gltf.h"
 
+// Values of the Mode uniform of the lobby fragment shader
+enum LobbyShadingMode {
+    LOBBY_MODE_GROUND = 0,
+    LOBBY_MODE_TEXT = 1,
+    LOBBY_MODE_SKY = 2,
+    LOBBY_MODE_SKYBOX = 3,
+    LOBBY_MODE_LIT = 4,
+};
+
+struct GltfBuffer {
+    const unsigned char *data;
+    unsigned int length;
+};
+
 class GltfModel {
     std::vector<GLuint> m_vbs;
+    GltfBuffer m_binBuffer;
     tinygltf::Model m_model;
     GLuint m_vao;
 
@@ -21,13 +36,29 @@ class GltfModel {
     GLint m_color;
     GLint m_mMatrix;
     GLint m_mode;
+    int m_surfaceMode;
+    bool m_drawGround;
 
     void drawNodeTree(int node_i, const ovrMatrix4f &transform);
     void drawNode(int node_i, const ovrMatrix4f &transform);
     ovrMatrix4f createNodeTransform(const ovrMatrix4f &baseTransform, const tinygltf::Node &node);
+    bool validate();
 public:
-    void load();
-    void drawScene(int position, int uv, int normal, GLint color, GLint mMatrix, GLint mode);
+    // Returns false if the model could not be parsed or references missing data. In this case
+    // nothing is drawn.
+    bool load(const unsigned char *gltfData,
+              unsigned int gltfLength,
+              const unsigned char *binData,
+              unsigned int binLength);
+    // Materials other than "Plane" (ground) and "Message" (HUD text) are shaded with surfaceMode
+    void drawScene(int position,
+                   int uv,
+                   int normal,
+                   GLint color,
+                   GLint mMatrix,
+                   GLint mode,
+                   int surfaceMode = LOBBY_MODE_SKY,
+                   bool drawGround = true);
 };
 
 
@@ -2,6 +2,7 @@
 #include "ffr.h"
 #include "gltf_model.h"
 #include "srgb_correction_pass.h"
+#include "tinygltf/stb_image.h"
 #include "utils.h"
 #include <EGL/egl.h>
 #include <EGL/eglext.h>
@@ -17,6 +18,9 @@ const int MAX_PROGRAM_TEXTURES = 8;
 const int HUD_TEXTURE_WIDTH = 1280;
 const int HUD_TEXTURE_HEIGHT = 720;
 
+const unsigned char *LOBBY_SKYBOX_PTR;
+unsigned int LOBBY_SKYBOX_LEN;
+
 /// Integer version of ovrRectf
 typedef struct Recti_ {
     int x;
@@ -74,6 +78,8 @@ typedef struct {
     gl_render_utils::Texture *streamTexture;
     GLuint hudTexture;
     GltfModel *lobbyScene;
+    GltfModel *customLobbyScene; // nullptr if not provided or invalid
+    GLuint skyboxTexture;        // 0 if not provided or invalid
     std::unique_ptr<FFR> ffr;
     std::unique_ptr<SrgbCorrectionPass> srgbCorrectionPass;
     bool enableFFE;
@@ -248,6 +254,7 @@ in lowp vec3 normal;
 in lowp vec3 position;
 out lowp vec4 outColor;
 uniform sampler2D sTexture;
+uniform sampler2D Texture1;
 uniform lowp int Mode;
 void main()
 {
@@ -297,6 +304,17 @@ void main()
 
         outColor.rgb = textColor;
         outColor.a = texture(sTexture, uv).a;
+    } else if(Mode == 3) {                             // skybox
+        // Equirectangular mapping
+        mediump vec3 direction = normalize(position);
+        mediump vec2 skyUv = vec2(atan(direction.z, direction.x) / 6.2831853 + 0.5,
+                                  acos(clamp(direction.y, -1.0, 1.0)) / 3.1415927);
+
+        outColor.rgb = texture(Texture1, skyUv).rgb;
+        outColor.a = 1.0;
+    } else if(Mode == 4) {                             // custom scene
+        outColor.rgb = fragmentColor.rgb * fragmentLight;
+        outColor.a = fragmentColor.a;
     } else {                                           // sky
         lowp vec3 skyCenter = vec3(0.0, 0.0, 0.0);
         lowp vec3 skyHorizon = vec3(0.0, 0.0, 0.02);
@@ -609,7 +627,60 @@ void ovrRenderer_Create(ovrRenderer *renderer,
     renderer->hudTexture = hudTexture;
     renderer->SceneCreated = false;
     renderer->lobbyScene = new GltfModel();
-    renderer->lobbyScene->load();
+    renderer->lobbyScene->load(
+        LOBBY_ROOM_GLTF_PTR, LOBBY_ROOM_GLTF_LEN, LOBBY_ROOM_BIN_PTR, LOBBY_ROOM_BIN_LEN);
+
+    // A broken custom asset falls back to the built-in scene
+    renderer->customLobbyScene = nullptr;
+    if (isLobby && CUSTOM_LOBBY_GLTF_PTR != nullptr) {
+        renderer->customLobbyScene = new GltfModel();
+        if (!renderer->customLobbyScene->load(CUSTOM_LOBBY_GLTF_PTR,
+                                              CUSTOM_LOBBY_GLTF_LEN,
+                                              CUSTOM_LOBBY_BIN_PTR,
+                                              CUSTOM_LOBBY_BIN_LEN)) {
+            LOGE("Failed to load the custom lobby scene");
+            delete renderer->customLobbyScene;
+            renderer->customLobbyScene = nullptr;
+        }
+    }
+
+    renderer->skyboxTexture = 0;
+    if (isLobby && LOBBY_SKYBOX_PTR != nullptr) {
+        int skyboxWidth, skyboxHeight, channels;
+        unsigned char *pixels = stbi_load_from_memory(
+            LOBBY_SKYBOX_PTR, LOBBY_SKYBOX_LEN, &skyboxWidth, &skyboxHeight, &channels, 4);
+
+        GLint maxTextureSize;
+        GL(glGetIntegerv(GL_MAX_TEXTURE_SIZE, &maxTextureSize));
+
+        if (pixels == nullptr) {
+            LOGE("Failed to decode the lobby skybox: %s", stbi_failure_reason());
+        } else if (skyboxWidth > maxTextureSize || skyboxHeight > maxTextureSize) {
+            LOGE("Lobby skybox is too large: %dx%d, max size: %d",
+                 skyboxWidth,
+                 skyboxHeight,
+                 maxTextureSize);
+        } else {
+            GL(glGenTextures(1, &renderer->skyboxTexture));
+            GL(glBindTexture(GL_TEXTURE_2D, renderer->skyboxTexture));
+            GL(glTexImage2D(GL_TEXTURE_2D,
+                            0,
+                            GL_RGBA8,
+                            skyboxWidth,
+                            skyboxHeight,
+                            0,
+                            GL_RGBA,
+                            GL_UNSIGNED_BYTE,
+                            pixels));
+            GL(glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MIN_FILTER, GL_LINEAR));
+            GL(glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_MAG_FILTER, GL_LINEAR));
+            GL(glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_S, GL_REPEAT));
+            GL(glTexParameteri(GL_TEXTURE_2D, GL_TEXTURE_WRAP_T, GL_CLAMP_TO_EDGE));
+            GL(glBindTexture(GL_TEXTURE_2D, 0));
+        }
+
+        stbi_image_free(pixels);
+    }
 
     ovrProgram_Create(&renderer->streamProgram, VERTEX_SHADER, FRAGMENT_SHADER, STREAMER_PROG);
 
@@ -622,6 +693,15 @@ void ovrRenderer_Create(ovrRenderer *renderer,
 }
 
 void ovrRenderer_Destroy(ovrRenderer *renderer) {
+    if (renderer->customLobbyScene != nullptr) {
+        delete renderer->customLobbyScene;
+        renderer->customLobbyScene = nullptr;
+    }
+    if (renderer->skyboxTexture != 0) {
+        GL(glDeleteTextures(1, &renderer->skyboxTexture));
+        renderer->skyboxTexture = 0;
+    }
+
     ovrProgram_Destroy(&renderer->streamProgram);
     ovrProgram_Destroy(&renderer->lobbyProgram);
     ovrGeometry_DestroyVAO(&renderer->Panel);
@@ -674,13 +754,40 @@ void renderEye(
         GL(glActiveTexture(GL_TEXTURE0));
 
         GL(glBindTexture(GL_TEXTURE_2D, renderer->hudTexture));
+        if (renderer->skyboxTexture != 0) {
+            GL(glActiveTexture(GL_TEXTURE1));
+            GL(glBindTexture(GL_TEXTURE_2D, renderer->skyboxTexture));
+            GL(glActiveTexture(GL_TEXTURE0));
+        }
+
+        if (renderer->customLobbyScene != nullptr) {
+            renderer->customLobbyScene->drawScene(
+                VERTEX_ATTRIBUTE_LOCATION_POSITION,
+                VERTEX_ATTRIBUTE_LOCATION_UV,
+                VERTEX_ATTRIBUTE_LOCATION_NORMAL,
+                renderer->lobbyProgram.UniformLocation[UNIFORM_COLOR],
+                renderer->lobbyProgram.UniformLocation[UNIFORM_M_MATRIX],
+                renderer->lobbyProgram.UniformLocation[UNIFORM_MODE],
+                LOBBY_MODE_LIT);
+        }
+
+        // The built-in scene always provides the sky and the HUD text, drawn last to stay on top of
+        // the custom scene. The custom scene replaces the ground.
         renderer->lobbyScene->drawScene(VERTEX_ATTRIBUTE_LOCATION_POSITION,
                                         VERTEX_ATTRIBUTE_LOCATION_UV,
                                         VERTEX_ATTRIBUTE_LOCATION_NORMAL,
                                         renderer->lobbyProgram.UniformLocation[UNIFORM_COLOR],
                                         renderer->lobbyProgram.UniformLocation[UNIFORM_M_MATRIX],
-                                        renderer->lobbyProgram.UniformLocation[UNIFORM_MODE]);
+                                        renderer->lobbyProgram.UniformLocation[UNIFORM_MODE],
+                                        renderer->skyboxTexture != 0 ? LOBBY_MODE_SKYBOX
+                                                                     : LOBBY_MODE_SKY,
+                                        renderer->customLobbyScene == nullptr);
         GL(glBindVertexArray(0));
+        if (renderer->skyboxTexture != 0) {
+            GL(glActiveTexture(GL_TEXTURE1));
+            GL(glBindTexture(GL_TEXTURE_2D, 0));
+            GL(glActiveTexture(GL_TEXTURE0));
+        }
         GL(glBindTexture(GL_TEXTURE_2D, 0));
     } else {
         GL(glClear(GL_DEPTH_BUFFER_BIT));
@@ -875,6 +982,15 @@ void updateLobbyHudTexture(const unsigned char *data) {
     memcpy(&g_ctx.hudTextureBitmap[0], data, HUD_TEXTURE_WIDTH * HUD_TEXTURE_HEIGHT * 4);
 }
 
+void updateOverlayTextureNative(unsigned int texture,
+                                const unsigned char *data,
+                                int width,
+                                int height) {
+    GL(glBindTexture(GL_TEXTURE_2D, texture));
+    GL(glTexSubImage2D(GL_TEXTURE_2D, 0, 0, 0, width, height, GL_RGBA, GL_UNSIGNED_BYTE, data));
+    GL(glBindTexture(GL_TEXTURE_2D, 0));
+}
+
 void renderLobbyNative(const FfiViewInput eyeInputs[2]) {
     // update text image
     {
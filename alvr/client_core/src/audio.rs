@@ -1,4 +1,4 @@
-use alvr_audio::{AudioDevice, AudioRecordState};
+use alvr_audio::{AudioBufferStatistics, AudioDevice, AudioRecordState};
 use alvr_common::{
     anyhow::{bail, Result},
     parking_lot::Mutex,
@@ -16,6 +16,7 @@ use std::{collections::VecDeque, mem, sync::Arc, thread, time::Duration};
 struct RecorderCallback {
     is_running: Arc<dyn Fn() -> bool + Send + Sync>,
     sender: StreamSender<()>,
+    gain: f32,
     state: Arc<Mutex<AudioRecordState>>,
 }
 
@@ -33,6 +34,10 @@ impl AudioInputCallback for RecorderCallback {
             sample_buffer.extend(&frame.to_ne_bytes());
         }
 
+        if self.gain != 1.0 {
+            alvr_audio::apply_pcm_gain(&mut sample_buffer, self.gain);
+        }
+
         if (self.is_running)() {
             let mut buffer = self.sender.get_buffer(&()).unwrap();
             buffer
@@ -63,6 +68,7 @@ pub fn record_audio_blocking(
     sender: StreamSender<()>,
     device: &AudioDevice,
     channels_count: u16,
+    gain: f32,
     mute: bool,
 ) -> Result<()> {
     let sample_rate = device.input_sample_rate()?;
@@ -82,6 +88,7 @@ pub fn record_audio_blocking(
         .set_callback(RecorderCallback {
             is_running: Arc::clone(&is_running),
             sender,
+            gain,
             state: Arc::clone(&state),
         })
         .open_stream()?;
@@ -110,6 +117,8 @@ pub enum AudioPlaybackState {
 
 struct PlayerCallback {
     sample_buffer: Arc<Mutex<VecDeque<f32>>>,
+    gain: Arc<Mutex<f32>>,
+    last_gain: f32,
     state: Arc<Mutex<AudioPlaybackState>>,
     batch_frames_count: usize,
 }
@@ -124,11 +133,12 @@ impl AudioOutputCallback for PlayerCallback {
     ) -> DataCallbackResult {
         assert!(self.batch_frames_count == out_frames.len());
 
-        let samples = alvr_audio::get_next_frame_batch(
+        let mut samples = alvr_audio::get_next_frame_batch(
             &mut *self.sample_buffer.lock(),
             2,
             self.batch_frames_count,
         );
+        alvr_audio::apply_gain(&mut samples, 2, &mut self.last_gain, *self.gain.lock());
 
         for f in 0..out_frames.len() {
             out_frames[f] = (samples[f * 2], samples[f * 2 + 1]);
@@ -161,7 +171,9 @@ pub fn play_audio_loop(
     channels_count: u16,
     sample_rate: u32,
     config: AudioBufferingConfig,
+    gain: Arc<Mutex<f32>>,
     receiver: &mut StreamReceiver<()>,
+    report_statistics: impl FnMut(AudioBufferStatistics),
 ) -> Result<()> {
     // the client sends invalid sample rates sometimes, and we crash if we try and use one
     // (batch_frames_count ends up zero and the audio callback gets confused)
@@ -188,6 +200,8 @@ pub fn play_audio_loop(
         .set_usage(Usage::Game)
         .set_callback(PlayerCallback {
             sample_buffer: Arc::clone(&sample_buffer),
+            last_gain: *gain.lock(),
+            gain,
             batch_frames_count,
             state: state.clone(),
         })
@@ -202,6 +216,7 @@ pub fn play_audio_loop(
         2,
         batch_frames_count,
         average_buffer_frames_count,
+        report_statistics,
     )
     .ok();
 
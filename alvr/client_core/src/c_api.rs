@@ -39,6 +39,7 @@ pub struct AlvrClientCapabilities {
     encoder_high_profile: bool,
     encoder_10_bits: bool,
     encoder_av1: bool,
+    multi_slice_decoding: bool,
 }
 
 #[repr(u8)]
@@ -64,11 +65,22 @@ pub enum AlvrEvent {
         frequency: f32,
         amplitude: f32,
     },
-    /// Note: All subsequent DecoderConfig events should be ignored until reconnection
+    /// Note: All subsequent DecoderConfig events should be ignored until reconnection, unless the
+    /// codec is different
     DecoderConfig {
         codec: AlvrCodec,
     },
     FrameReady,
+    /// Answer with alvr_submit_pairing_code()
+    PairingCodeRequested {
+        attempts_left: u32,
+    },
+    /// Create the reference space again, then call alvr_send_playspace()
+    RecenterRequested,
+    /// Show the lobby while paused, keep sending the tracking
+    StreamPaused {
+        paused: bool,
+    },
 }
 
 #[repr(C)]
@@ -216,6 +228,13 @@ pub extern "C" fn alvr_hostname(hostname_buffer: *mut c_char) -> u64 {
     string_to_c_str(hostname_buffer, &storage::Config::load().hostname)
 }
 
+/// Port to advertise with the mDNS service. To make sure the value is correct, call after
+/// alvr_initialize()
+#[no_mangle]
+pub extern "C" fn alvr_control_port() -> u16 {
+    storage::Config::load().control_port
+}
+
 /// To make sure the value is correct, call after alvr_initialize()
 #[no_mangle]
 pub extern "C" fn alvr_protocol_id(protocol_buffer: *mut c_char) -> u64 {
@@ -260,6 +279,10 @@ pub unsafe extern "C" fn alvr_initialize(capabilities: AlvrClientCapabilities) {
         encoder_high_profile: capabilities.encoder_high_profile,
         encoder_10_bits: capabilities.encoder_10_bits,
         encoder_av1: capabilities.encoder_av1,
+        multi_slice_decoding: capabilities.multi_slice_decoding,
+        // AlvrEvent::Haptics has no envelope
+        haptics_envelopes: false,
+        device_model: None,
     };
     *CLIENT_CORE_CONTEXT.lock() = Some(ClientCoreContext::new(capabilities));
 }
@@ -290,7 +313,7 @@ pub extern "C" fn alvr_pause() {
 #[no_mangle]
 pub extern "C" fn alvr_poll_event(out_event: *mut AlvrEvent) -> bool {
     if let Some(context) = &*CLIENT_CORE_CONTEXT.lock() {
-        if let Some(event) = context.poll_event() {
+        while let Some(event) = context.poll_event() {
             let event = match event {
                 ClientCoreEvent::UpdateHudMessage(message) => {
                     *HUD_MESSAGE.lock() = message;
@@ -316,6 +339,7 @@ pub extern "C" fn alvr_poll_event(out_event: *mut AlvrEvent) -> bool {
                     duration,
                     frequency,
                     amplitude,
+                    ..
                 } => AlvrEvent::Haptics {
                     device_id,
                     duration_s: duration.as_secs_f32(),
@@ -346,17 +370,22 @@ pub extern "C" fn alvr_poll_event(out_event: *mut AlvrEvent) -> bool {
 
                     AlvrEvent::FrameReady
                 }
+                ClientCoreEvent::PairingCodeRequested { attempts_left } => {
+                    AlvrEvent::PairingCodeRequested { attempts_left }
+                }
+                ClientCoreEvent::RecenterRequested => AlvrEvent::RecenterRequested,
+                ClientCoreEvent::StreamPaused(paused) => AlvrEvent::StreamPaused { paused },
+                // Not exposed through the C API
+                ClientCoreEvent::Notification(_) | ClientCoreEvent::SettingsChanged(_) => continue,
             };
 
             unsafe { *out_event = event };
 
-            true
-        } else {
-            false
+            return true;
         }
-    } else {
-        false
     }
+
+    false
 }
 
 /// Settings will be updated after receiving StreamingStarted event
@@ -443,6 +472,35 @@ pub extern "C" fn alvr_send_active_interaction_profile(device_id: u64, profile_i
     }
 }
 
+/// The code entered by the user, as shown in the dashboard
+#[no_mangle]
+pub unsafe extern "C" fn alvr_submit_pairing_code(code: *const c_char) {
+    if let Some(context) = &*CLIENT_CORE_CONTEXT.lock() {
+        context.submit_pairing_code(CStr::from_ptr(code).to_str().unwrap().to_owned());
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn alvr_send_proximity_state(headset_worn: bool) {
+    if let Some(context) = &*CLIENT_CORE_CONTEXT.lock() {
+        context.send_proximity_state(headset_worn);
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn alvr_send_recenter() {
+    if let Some(context) = &*CLIENT_CORE_CONTEXT.lock() {
+        context.send_recenter();
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn alvr_request_stream_paused(paused: bool) {
+    if let Some(context) = &*CLIENT_CORE_CONTEXT.lock() {
+        context.request_stream_paused(paused);
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn alvr_send_custom_interaction_profile(
     device_id: u64,
@@ -1,36 +1,50 @@
 #![allow(clippy::if_same_then_else)]
 
 use crate::{
-    decoder::{self, DecoderConfig, DecoderSink, DecoderSource},
-    logging_backend::{LogMirrorData, LOG_CHANNEL_SENDER},
+    decoder::{
+        self, DecoderConfig, DecoderErrorAction, DecoderRecovery, DecoderSink, DecoderSource,
+    },
+    jitter_buffer::JitterBuffer,
+    logging_backend::{LogMirrorData, LOG_BUFFER_SIZE, LOG_CHANNEL_SENDER},
     platform,
     sockets::AnnouncerSocket,
     statistics::StatisticsManager,
-    storage::Config,
+    storage::{self, Config},
+    tracking_recording::TrackingRecorder,
+    video_slices::{ReceivedFrame, SliceReassembler},
     ClientCapabilities, ClientCoreEvent,
 };
 use alvr_audio::AudioDevice;
 use alvr_common::{
+    anyhow::Result,
     debug, error, info,
-    parking_lot::{Condvar, Mutex, RwLock},
-    wait_rwlock, warn, AnyhowToCon, ConResult, ConnectionError, ConnectionState, LifecycleState,
-    ALVR_VERSION,
+    parking_lot::{Condvar, Mutex, RwLock, RwLockWriteGuard},
+    show_err, wait_rwlock, warn, AnyhowToCon, ConResult, ConnectionError, ConnectionState,
+    LifecycleState, ALVR_VERSION,
 };
 use alvr_packets::{
-    ClientConnectionResult, ClientControlPacket, ClientStatistics, Haptics, ServerControlPacket,
-    StreamConfigPacket, Tracking, VideoPacketHeader, VideoStreamingCapabilities, ViewParams, AUDIO,
-    HAPTICS, STATISTICS, TRACKING, VIDEO,
+    ClientConnectionResult, ClientControlPacket, ClientStatistics, Haptics, HapticsEnvelopePoint,
+    HapticsWithEnvelope, ReservedClientControlPacket, ReservedServerControlPacket,
+    ServerControlPacket, ServerResponse, ServerStatistics, StreamConfigPacket, Tracking,
+    TrackingDeltaEncoder, TrackingDeltaPacket, VideoPacketHeader, VideoStreamingCapabilities,
+    ViewParams, AUDIO, HAPTICS, STATISTICS, TRACKING, TRACKING_DELTA, TRACKING_FIXED, VIDEO,
 };
 use alvr_session::settings_schema::Switch;
 use alvr_sockets::{
-    ControlSocketSender, PeerType, ProtoControlSocket, StreamSender, StreamSocketBuilder,
+    ControlSocketSender, DatagramReceiver, OverflowPolicy, PeerType, ProtoControlSocket,
+    RequestError, RequestTracker, SocketError, StreamReceiver, StreamSender, StreamSocketBuilder,
     KEEPALIVE_INTERVAL, KEEPALIVE_TIMEOUT,
 };
+use rand::Rng;
 use std::{
     collections::VecDeque,
-    sync::{mpsc, Arc},
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
     thread,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 #[cfg(target_os = "android")]
@@ -55,29 +69,191 @@ const LOCAL_TRY_MESSAGE: &str = "Trying to connect to localhost...";
 const STREAM_STARTING_MESSAGE: &str = "The stream will begin soon\nPlease wait...";
 const SERVER_RESTART_MESSAGE: &str = "The streamer is restarting\nPlease wait...";
 const SERVER_DISCONNECTED_MESSAGE: &str = "The streamer has disconnected.";
+const SERVER_SHUTDOWN_MESSAGE: &str = "The streamer has shut down.";
 const CONNECTION_TIMEOUT_MESSAGE: &str = "Connection timeout.";
+const RECONNECTING_MESSAGE: &str = "Connection lost, reconnecting...";
+const PAIRING_CHECK_MESSAGE: &str = "Checking the code\nPlease wait...";
+const PAIRING_TIMEOUT_MESSAGE: &str =
+    "No code was entered.\nTrust the client again in the dashboard.";
 
 const DISCOVERY_RETRY_PAUSE: Duration = Duration::from_millis(500);
+const DISCOVERY_BROADCAST_INTERVAL: Duration = Duration::from_secs(1);
+// (time without an answer, broadcast interval)
+const DISCOVERY_BROADCAST_BACKOFF: [(Duration, Duration); 2] = [
+    (Duration::from_secs(60), Duration::from_secs(2)),
+    (Duration::from_secs(180), Duration::from_secs(5)),
+];
+const DISCOVERY_BROADCAST_MAX_JITTER_MS: u64 = 200;
 const RETRY_CONNECT_MIN_INTERVAL: Duration = Duration::from_secs(1);
 const CONNECTION_RETRY_INTERVAL: Duration = Duration::from_secs(1);
 const HANDSHAKE_ACTION_TIMEOUT: Duration = Duration::from_secs(2);
+// Same as the validity of the code on the streamer
+const PAIRING_CODE_TIMEOUT: Duration = Duration::from_secs(60);
+const PAIRING_CODE_POLL_INTERVAL: Duration = Duration::from_millis(100);
 const STREAMING_RECV_TIMEOUT: Duration = Duration::from_millis(500);
+// The streamer sends probe datagrams while there is no video, this covers a few of them
+const UDP_VIDEO_FALLBACK_TIMEOUT: Duration = Duration::from_secs(2);
 
 const MAX_UNREAD_PACKETS: usize = 10; // Applies per stream
 
+// Forwarded log lines are spaced out so that a burst of logs does not saturate the network
+const LOG_SEND_INTERVAL: Duration = Duration::from_millis(20);
+#[cfg(target_os = "android")]
+const VOLUME_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+pub enum TrackingSender {
+    Full(StreamSender<Tracking>),
+    Delta(StreamSender<TrackingDeltaPacket>, TrackingDeltaEncoder),
+    // The packets are encoded in place, the buffers are recycled by the sender
+    Fixed(StreamSender<()>),
+}
+
+impl TrackingSender {
+    pub fn send(&mut self, tracking: Tracking) -> Result<()> {
+        match self {
+            TrackingSender::Full(sender) => sender.send_header(&tracking),
+            TrackingSender::Delta(sender, encoder) => sender.send_header(&encoder.encode(tracking)),
+            TrackingSender::Fixed(sender) => {
+                let mut buffer = sender.get_buffer(&())?;
+                alvr_packets::encode_tracking(
+                    &tracking,
+                    buffer.get_range_mut(0, alvr_packets::encoded_tracking_size(&tracking)),
+                );
+
+                sender.send(buffer)
+            }
+        }
+    }
+}
+
+enum HapticsReceiver {
+    Plain(StreamReceiver<Haptics>),
+    Envelope(StreamReceiver<HapticsWithEnvelope>),
+}
+
+impl HapticsReceiver {
+    fn recv(&mut self, timeout: Duration) -> ConResult<(Haptics, Vec<HapticsEnvelopePoint>)> {
+        match self {
+            HapticsReceiver::Plain(receiver) => {
+                Ok((receiver.recv(timeout)?.get_header().to_con()?, vec![]))
+            }
+            HapticsReceiver::Envelope(receiver) => {
+                let packet = receiver.recv(timeout)?.get_header().to_con()?;
+
+                Ok((packet.haptics, packet.envelope))
+            }
+        }
+    }
+
+    fn dropped_packets(&self) -> usize {
+        match self {
+            HapticsReceiver::Plain(receiver) => receiver.dropped_packets(),
+            HapticsReceiver::Envelope(receiver) => receiver.dropped_packets(),
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct ConnectionContext {
     pub state: RwLock<ConnectionState>,
     pub disconnected_notif: Condvar,
     pub control_sender: Mutex<Option<ControlSocketSender<ClientControlPacket>>>,
-    pub tracking_sender: Mutex<Option<StreamSender<Tracking>>>,
+    pub tracking_sender: Mutex<Option<TrackingSender>>,
     pub statistics_sender: Mutex<Option<StreamSender<ClientStatistics>>>,
     pub statistics_manager: Mutex<Option<StatisticsManager>>,
+    pub tracking_recorder: Mutex<Option<TrackingRecorder>>,
+    // Received only if the statistics overlay is enabled
+    pub server_statistics: Mutex<Option<ServerStatistics>>,
     pub decoder_sink: Mutex<Option<DecoderSink>>,
     pub decoder_source: Mutex<Option<DecoderSource>>,
+    pub decoder_recovery: Mutex<Option<DecoderRecovery>>,
     // todo: the server is supposed to receive and send view configs for each frame
     pub view_params_queue: RwLock<VecDeque<(Duration, [ViewParams; 2])>>,
     pub last_good_view_params: RwLock<[ViewParams; 2]>,
+    pub requests: RequestTracker<ServerResponse>,
+    // Set by ClientCoreContext::submit_pairing_code()
+    pub pairing_code: Mutex<Option<String>>,
+}
+
+// After the streamer announced it is quitting, the discovery starts at the slowest broadcast
+// interval, as it will not be back soon
+const SHUTDOWN_DISCOVERY_BACKOFF: Duration = DISCOVERY_BROADCAST_BACKOFF[1].0;
+
+// Kept after an unexpected disconnection. While the grace period lasts, the client announces itself
+// only to the last streamer and the stream is not torn down on the client side.
+struct Reconnection {
+    server_ip: IpAddr,
+    // Raw session and negotiated config, used to check if the stream can be resumed as is
+    stream_config: (String, String),
+    deadline: Instant,
+}
+
+fn end_reconnection(
+    reconnection: &mut Option<Reconnection>,
+    event_queue: &Mutex<VecDeque<ClientCoreEvent>>,
+) {
+    if reconnection.take().is_some() {
+        event_queue
+            .lock()
+            .push_back(ClientCoreEvent::StreamingStopped);
+    }
+}
+
+fn request_idr(ctx: &ConnectionContext) {
+    if let Some(sender) = &mut *ctx.control_sender.lock() {
+        sender.send(&ClientControlPacket::RequestIdr).ok();
+    }
+}
+
+// If the decoder is recreated, the new one is created when the streamer answers the IDR request with
+// the DecoderConfig. decoder_lost: the decoder stopped working altogether
+pub fn handle_decoder_error(ctx: &ConnectionContext, decoder_lost: bool, message: &str) {
+    let Some((action, idr_request_due)) = ctx.decoder_recovery.lock().as_mut().map(|recovery| {
+        let now = Instant::now();
+        let action = if decoder_lost {
+            recovery.report_decoder_lost(now)
+        } else {
+            recovery.report_decoder_error(now)
+        };
+
+        (action, recovery.idr_request_due(now))
+    }) else {
+        return;
+    };
+
+    match action {
+        DecoderErrorAction::Flush => {
+            warn!("Decoder error, waiting for the next IDR frame: {message}");
+
+            if let Some(sink) = &mut *ctx.decoder_sink.lock() {
+                show_err(sink.flush());
+            }
+        }
+        DecoderErrorAction::Recreate { report_failure } => {
+            warn!("Decoder error, recreating the decoder: {message}");
+
+            *ctx.decoder_sink.lock() = None;
+            *ctx.decoder_source.lock() = None;
+
+            if report_failure {
+                error!("Decoder keeps failing: {message}");
+
+                if let Some(sender) = &mut *ctx.control_sender.lock() {
+                    sender
+                        .send(&alvr_packets::encode_reserved_client_control_packet(
+                            &ReservedClientControlPacket::DecoderFailing {
+                                message: message.to_owned(),
+                            },
+                        ))
+                        .ok();
+                }
+            }
+        }
+    }
+
+    if idr_request_due {
+        request_idr(ctx);
+    }
 }
 
 fn set_hud_message(event_queue: &Mutex<VecDeque<ClientCoreEvent>>, message: &str) {
@@ -93,6 +269,24 @@ fn set_hud_message(event_queue: &Mutex<VecDeque<ClientCoreEvent>>, message: &str
         .push_back(ClientCoreEvent::UpdateHudMessage(message));
 }
 
+// Broadcasting is slowed down the longer no streamer answers, to reduce network traffic. The jitter
+// avoids many clients broadcasting in lockstep
+fn discovery_broadcast_interval(config: &Config, time_without_answer: Duration) -> Duration {
+    if let Some(interval_ms) = config.discovery_broadcast_interval_ms {
+        return Duration::from_millis(interval_ms);
+    }
+
+    let interval = DISCOVERY_BROADCAST_BACKOFF
+        .iter()
+        .rev()
+        .find(|(threshold, _)| time_without_answer >= *threshold)
+        .map(|(_, interval)| *interval)
+        .unwrap_or(DISCOVERY_BROADCAST_INTERVAL);
+
+    interval
+        + Duration::from_millis(rand::thread_rng().gen_range(0..=DISCOVERY_BROADCAST_MAX_JITTER_MS))
+}
+
 fn is_streaming(ctx: &ConnectionContext) -> bool {
     *ctx.state.read() == ConnectionState::Streaming
 }
@@ -105,53 +299,143 @@ pub fn connection_lifecycle_loop(
 ) {
     set_hud_message(&event_queue, INITIAL_MESSAGE);
 
+    let mut reconnection = None;
+    let mut server_shut_down = false;
+    let mut consecutive_failures = 0;
     while *lifecycle_state.read() != LifecycleState::ShuttingDown {
+        let mut retry_interval = CONNECTION_RETRY_INTERVAL;
+
         if *lifecycle_state.read() == LifecycleState::Resumed {
             if let Err(e) = connection_pipeline(
                 capabilities.clone(),
                 Arc::clone(&ctx),
                 Arc::clone(&lifecycle_state),
                 Arc::clone(&event_queue),
+                &mut reconnection,
+                &mut server_shut_down,
             ) {
                 let message = format!("Connection error:\n{e}\nCheck the PC for more details");
                 set_hud_message(&event_queue, &message);
                 error!("Connection error: {e}");
+
+                if let Some(socket_error) = SocketError::find_in(&e) {
+                    retry_interval = socket_error
+                        .reconnect_delay(CONNECTION_RETRY_INTERVAL, consecutive_failures);
+                }
+                consecutive_failures += 1;
+            } else {
+                consecutive_failures = 0;
             }
         } else {
+            end_reconnection(&mut reconnection, &event_queue);
+
             debug!("Skip try connection because the device is sleeping");
         }
 
         *ctx.state.write() = ConnectionState::Disconnected;
         ctx.disconnected_notif.notify_all();
 
-        thread::sleep(CONNECTION_RETRY_INTERVAL);
+        thread::sleep(retry_interval);
     }
 }
 
+// Returns None if the client was paused or no code was entered in time
+fn wait_for_pairing_code(
+    ctx: &ConnectionContext,
+    lifecycle_state: &RwLock<LifecycleState>,
+    event_queue: &Mutex<VecDeque<ClientCoreEvent>>,
+    attempts_left: u32,
+) -> Option<String> {
+    info!("The streamer requested the pairing code");
+
+    *ctx.pairing_code.lock() = None;
+    set_hud_message(
+        event_queue,
+        &format!(
+            "Enter the code shown in the dashboard\nof the streamer ({attempts_left} attempts left)"
+        ),
+    );
+    event_queue
+        .lock()
+        .push_back(ClientCoreEvent::PairingCodeRequested { attempts_left });
+
+    let deadline = Instant::now() + PAIRING_CODE_TIMEOUT;
+    while Instant::now() < deadline {
+        if *lifecycle_state.read() != LifecycleState::Resumed {
+            return None;
+        }
+
+        if let Some(code) = ctx.pairing_code.lock().take() {
+            return Some(code);
+        }
+
+        thread::sleep(PAIRING_CODE_POLL_INTERVAL);
+    }
+
+    set_hud_message(event_queue, PAIRING_TIMEOUT_MESSAGE);
+
+    None
+}
+
 fn connection_pipeline(
     capabilities: ClientCapabilities,
     ctx: Arc<ConnectionContext>,
     lifecycle_state: Arc<RwLock<LifecycleState>>,
     event_queue: Arc<Mutex<VecDeque<ClientCoreEvent>>>,
+    reconnection: &mut Option<Reconnection>,
+    server_shut_down: &mut bool,
 ) -> ConResult {
     let (mut proto_control_socket, server_ip) = {
         let config = Config::load();
-        let announcer_socket = AnnouncerSocket::new(&config.hostname).to_con()?;
+        let announcer_socket =
+            AnnouncerSocket::new(&config.hostname, config.control_port).to_con()?;
         let listener_socket =
-            alvr_sockets::get_server_listener(HANDSHAKE_ACTION_TIMEOUT).to_con()?;
-
+            alvr_sockets::get_server_listener(HANDSHAKE_ACTION_TIMEOUT, config.control_port)
+                .to_con()?;
+
+        // The discovery starts over after each connection, so after a disconnection the
+        // streamer is found again quickly
+        let discovery_start = Instant::now();
+        let discovery_backoff = if *server_shut_down {
+            SHUTDOWN_DISCOVERY_BACKOFF
+        } else {
+            Duration::ZERO
+        };
+        let mut next_broadcast = discovery_start;
         loop {
             if *lifecycle_state.write() != LifecycleState::Resumed {
                 return Ok(());
             }
 
-            let mut is_broadcast_ok = false;
-            if let Err(e) = announcer_socket.announce_broadcast() {
-                debug!("Couldn't announce to localhost, retrying on local... {e:}");
+            if reconnection
+                .as_ref()
+                .map(|reconnection| Instant::now() > reconnection.deadline)
+                .unwrap_or(false)
+            {
+                info!("Could not reconnect to the streamer, falling back to discovery");
+                end_reconnection(reconnection, &event_queue);
+                set_hud_message(&event_queue, INITIAL_MESSAGE);
+            }
 
-                set_hud_message(&event_queue, LOCAL_TRY_MESSAGE);
-            } else {
-                is_broadcast_ok = true;
+            let mut is_broadcast_ok = true;
+            if let Some(reconnection) = reconnection {
+                if let Err(e) = announcer_socket.announce_unicast(reconnection.server_ip) {
+                    debug!("Couldn't announce to the last streamer: {e:}");
+                }
+            } else if Instant::now() >= next_broadcast {
+                if let Err(e) = announcer_socket.announce_broadcast() {
+                    debug!("Couldn't announce to localhost, retrying on local... {e:}");
+
+                    set_hud_message(&event_queue, LOCAL_TRY_MESSAGE);
+
+                    is_broadcast_ok = false;
+                }
+
+                next_broadcast = Instant::now()
+                    + discovery_broadcast_interval(
+                        &config,
+                        discovery_backoff + discovery_start.elapsed(),
+                    );
             }
 
             if let Ok(pair) = ProtoControlSocket::connect_to(
@@ -159,6 +443,7 @@ fn connection_pipeline(
                 PeerType::Server(&listener_socket),
             ) {
                 set_hud_message(&event_queue, SUCCESS_CONNECT_MESSAGE);
+                *server_shut_down = false;
                 break pair;
             }
 
@@ -184,20 +469,33 @@ fn connection_pipeline(
         .input_sample_rate()
         .to_con()?;
 
+    let device_model = capabilities
+        .device_model
+        .clone()
+        .unwrap_or_else(|| platform::platform().to_string());
+
     proto_control_socket
         .send(&ClientConnectionResult::ConnectionAccepted {
-            client_protocol_id: alvr_common::protocol_id_u64(),
+            client_protocol_id: alvr_packets::protocol_id_u64(),
             display_name: platform::platform().to_string(),
             server_ip,
             streaming_capabilities: Some(
                 alvr_packets::encode_video_streaming_capabilities(&VideoStreamingCapabilities {
                     default_view_resolution: capabilities.default_view_resolution,
-                    supported_refresh_rates: capabilities.refresh_rates,
+                    supported_refresh_rates: capabilities.refresh_rates.clone(),
                     microphone_sample_rate,
                     supports_foveated_encoding: capabilities.foveated_encoding,
                     encoder_high_profile: capabilities.encoder_high_profile,
                     encoder_10_bits: capabilities.encoder_10_bits,
                     encoder_av1: capabilities.encoder_av1,
+                    multi_slice_decoding: capabilities.multi_slice_decoding,
+                    haptics_envelopes: capabilities.haptics_envelopes,
+                    udp_video: true,
+                    fixed_layout_tracking: true,
+                    tracking_delta_encoding: true,
+                    device_model: Some(device_model.clone()),
+                    device_manufacturer: platform::manufacturer(),
+                    hostname: Some(Config::load().hostname),
                 })
                 .to_con()?,
             ),
@@ -209,6 +507,25 @@ fn connection_pipeline(
     let (settings, negotiated_config) =
         alvr_packets::decode_stream_config(&config_packet).to_con()?;
 
+    // Used from the next discovery
+    let mut config = Config::load();
+    if config.control_port != settings.connection.control_port {
+        info!(
+            "The streamer uses the control port {}",
+            settings.connection.control_port
+        );
+        config.control_port = settings.connection.control_port;
+        config.store();
+    }
+
+    let fast_reconnection_timeout = settings
+        .connection
+        .fast_reconnection_timeout_s
+        .as_option()
+        .map(|timeout_s| Duration::from_secs_f32(*timeout_s));
+    let server_restarting = Arc::new(AtomicBool::new(false));
+    let server_shutting_down = Arc::new(AtomicBool::new(false));
+
     let streaming_start_event = ClientCoreEvent::StreamingStarted {
         settings: Box::new(settings.clone()),
         negotiated_config: negotiated_config.clone(),
@@ -223,33 +540,93 @@ fn connection_pipeline(
             0.0
         },
     ));
+    *ctx.server_statistics.lock() = None;
+    *ctx.decoder_recovery.lock() = Some(DecoderRecovery::new(
+        settings.video.decoder_recovery.clone(),
+        settings.connection.avoid_video_glitching,
+    ));
 
     let (mut control_sender, mut control_receiver) = proto_control_socket
         .split(STREAMING_RECV_TIMEOUT)
         .to_con()?;
 
-    match control_receiver.recv(HANDSHAKE_ACTION_TIMEOUT) {
-        Ok(ServerControlPacket::StartStream) => {
-            info!("Stream starting");
-            set_hud_message(&event_queue, STREAM_STARTING_MESSAGE);
-        }
-        Ok(ServerControlPacket::Restarting) => {
-            info!("Server restarting");
-            set_hud_message(&event_queue, SERVER_RESTART_MESSAGE);
-            return Ok(());
-        }
-        Err(e) => {
-            info!("Server disconnected. Cause: {e}");
-            set_hud_message(&event_queue, SERVER_DISCONNECTED_MESSAGE);
-            return Ok(());
-        }
-        _ => {
-            info!("Unexpected packet");
-            set_hud_message(&event_queue, "Unexpected packet");
-            return Ok(());
+    // A client that is not trusted yet is asked for the pairing code first, again after a wrong code
+    loop {
+        match control_receiver.recv(HANDSHAKE_ACTION_TIMEOUT) {
+            Ok(ServerControlPacket::StartStream) => {
+                info!("Stream starting");
+                set_hud_message(&event_queue, STREAM_STARTING_MESSAGE);
+                break;
+            }
+            Ok(ServerControlPacket::Restarting) => {
+                info!("Server restarting");
+                set_hud_message(&event_queue, SERVER_RESTART_MESSAGE);
+                return Ok(());
+            }
+            Ok(ServerControlPacket::Reserved(json_string)) => {
+                let Ok(ReservedServerControlPacket::PairingChallenge {
+                    nonce,
+                    attempts_left,
+                }) = serde_json::from_str(&json_string)
+                else {
+                    continue;
+                };
+
+                // pause() must not wait for the user to enter the code
+                let maybe_code = RwLockWriteGuard::unlocked(&mut connection_state_lock, || {
+                    wait_for_pairing_code(&ctx, &lifecycle_state, &event_queue, attempts_left)
+                });
+                let Some(code) = maybe_code else {
+                    return Ok(());
+                };
+
+                control_sender
+                    .send(&alvr_packets::encode_reserved_client_control_packet(
+                        &ReservedClientControlPacket::PairingResponse {
+                            proof: alvr_packets::pairing_proof(nonce, &code),
+                        },
+                    ))
+                    .to_con()?;
+                set_hud_message(&event_queue, PAIRING_CHECK_MESSAGE);
+            }
+            Err(e) => {
+                info!("Server disconnected. Cause: {e}");
+                set_hud_message(&event_queue, SERVER_DISCONNECTED_MESSAGE);
+                return Ok(());
+            }
+            _ => {
+                info!("Unexpected packet");
+                set_hud_message(&event_queue, "Unexpected packet");
+                return Ok(());
+            }
         }
     }
 
+    // Bound before StreamReady, so that no datagram is missed
+    let mut video_datagram_receiver = match &settings.connection.udp_video {
+        Switch::Enabled(config) if negotiated_config.udp_video => {
+            match DatagramReceiver::<VideoPacketHeader>::bind(
+                config.port,
+                server_ip,
+                settings.connection.client_recv_buffer_bytes,
+                config.max_datagram_size as _,
+            ) {
+                Ok(receiver) => Some(receiver),
+                Err(e) => {
+                    warn!("Failed to bind the video UDP socket: {e}");
+                    control_sender
+                        .send(&alvr_packets::encode_reserved_client_control_packet(
+                            &ReservedClientControlPacket::UdpVideoUnavailable,
+                        ))
+                        .ok();
+
+                    None
+                }
+            }
+        }
+        _ => None,
+    };
+
     let stream_socket_builder = StreamSocketBuilder::listen_for_server(
         Duration::from_secs(1),
         settings.connection.stream_port,
@@ -266,6 +643,12 @@ fn connection_pipeline(
         return Ok(());
     }
 
+    control_sender
+        .send(&alvr_packets::encode_reserved_client_control_packet(
+            &ReservedClientControlPacket::ClientVersion(ALVR_VERSION.clone()),
+        ))
+        .ok();
+
     let mut stream_socket = stream_socket_builder.accept_from_server(
         server_ip,
         settings.connection.stream_port,
@@ -275,84 +658,300 @@ fn connection_pipeline(
 
     info!("Connected to server");
 
-    let mut video_receiver =
-        stream_socket.subscribe_to_stream::<VideoPacketHeader>(VIDEO, MAX_UNREAD_PACKETS);
-    let mut game_audio_receiver = stream_socket.subscribe_to_stream(AUDIO, MAX_UNREAD_PACKETS);
-    let tracking_sender = stream_socket.request_stream(TRACKING);
-    let mut haptics_receiver =
-        stream_socket.subscribe_to_stream::<Haptics>(HAPTICS, MAX_UNREAD_PACKETS);
+    // A missing video packet corrupts the stream until the next IDR. The payload is read straight
+    // into the input buffer of the decoder when possible
+    let mut video_receiver = stream_socket.subscribe_to_direct_stream::<VideoPacketHeader>(VIDEO);
+    let mut game_audio_receiver =
+        stream_socket.subscribe_to_stream(AUDIO, MAX_UNREAD_PACKETS, OverflowPolicy::DropNewest);
+    let tracking_sender = if negotiated_config.tracking_delta_encoding {
+        TrackingSender::Delta(
+            stream_socket.request_stream(TRACKING_DELTA),
+            TrackingDeltaEncoder::default(),
+        )
+    } else if negotiated_config.fixed_layout_tracking {
+        TrackingSender::Fixed(stream_socket.request_stream(TRACKING_FIXED))
+    } else {
+        TrackingSender::Full(stream_socket.request_stream(TRACKING))
+    };
+    let mut haptics_receiver = if negotiated_config.haptics_envelopes {
+        HapticsReceiver::Envelope(stream_socket.subscribe_to_stream(
+            HAPTICS,
+            MAX_UNREAD_PACKETS,
+            OverflowPolicy::DropNewest,
+        ))
+    } else {
+        HapticsReceiver::Plain(stream_socket.subscribe_to_stream(
+            HAPTICS,
+            MAX_UNREAD_PACKETS,
+            OverflowPolicy::DropNewest,
+        ))
+    };
     let statistics_sender = stream_socket.request_stream(STATISTICS);
 
     let video_receive_thread = thread::spawn({
         let ctx = Arc::clone(&ctx);
         let event_queue = Arc::clone(&event_queue);
+        let jitter_buffer_depth = if let Switch::Enabled(depth) = &settings.video.jitter_buffer {
+            Some(*depth)
+        } else {
+            None
+        };
         move || {
-            let mut stream_corrupted = false;
-            while is_streaming(&ctx) {
-                let data = match video_receiver.recv(STREAMING_RECV_TIMEOUT) {
-                    Ok(data) => data,
-                    Err(ConnectionError::TryAgain(_)) => continue,
-                    Err(ConnectionError::Other(_)) => return,
+            let accept_frame = |is_idr: bool, had_packet_loss: bool| {
+                let mut recovery_lock = ctx.decoder_recovery.lock();
+                let Some(recovery) = &mut *recovery_lock else {
+                    return false;
                 };
-                let Ok((header, nal)) = data.get() else {
-                    return;
+
+                if had_packet_loss && !is_idr {
+                    recovery.report_stream_corrupted();
+                    warn!("Network dropped video packet");
+                }
+
+                let accepted = recovery.accept_frame(is_idr);
+                if !accepted {
+                    warn!("Dropped video packet. Reason: Waiting for IDR frame");
+                }
+
+                accepted
+            };
+
+            let report_push_result = |push_result: Option<Result<bool>>| match push_result {
+                Some(Ok(true)) => (),
+                Some(Ok(false)) | None => {
+                    if let Some(recovery) = &mut *ctx.decoder_recovery.lock() {
+                        recovery.report_stream_corrupted();
+                    }
+                    warn!("Dropped video packet. Reason: Decoder saturation")
+                }
+                Some(Err(e)) => handle_decoder_error(&ctx, false, &format!("{e}")),
+            };
+
+            let decode_frame = |timestamp: Duration, nal: &[u8]| {
+                if capabilities.external_decoder {
+                    let mut view_params = *ctx.last_good_view_params.read();
+                    for (view_timestamp, views) in &*ctx.view_params_queue.read() {
+                        if *view_timestamp == timestamp {
+                            view_params = *views;
+                            break;
+                        }
+                    }
+                    event_queue.lock().push_back(ClientCoreEvent::FrameReady {
+                        timestamp,
+                        view_params,
+                        nal: nal.to_vec(),
+                    });
+                } else {
+                    report_push_result(
+                        ctx.decoder_sink
+                            .lock()
+                            .as_mut()
+                            .map(|sink| sink.push_nal(timestamp, nal)),
+                    );
+                }
+            };
+
+            let process_frame =
+                |timestamp: Duration, is_idr: bool, nal: &[u8], had_packet_loss: bool| {
+                    if accept_frame(is_idr, had_packet_loss) {
+                        decode_frame(timestamp, nal);
+                    }
                 };
 
+            let report_frame_received = |timestamp| {
                 if let Some(stats) = &mut *ctx.statistics_manager.lock() {
-                    stats.report_video_packet_received(header.timestamp);
+                    stats.report_video_packet_received(timestamp);
                 }
+            };
+
+            let udp_video_deadline = Instant::now() + UDP_VIDEO_FALLBACK_TIMEOUT;
+
+            // Used for the frames which cannot be read straight into the decoder
+            let mut nal_buffer = vec![];
+
+            let mut slice_reassembler = SliceReassembler::default();
+            let mut jitter_buffer = jitter_buffer_depth.map(|depth| {
+                JitterBuffer::new(
+                    depth,
+                    Duration::from_secs_f32(1.0 / negotiated_config.refresh_rate_hint),
+                    Instant::now(),
+                )
+            });
+            while is_streaming(&ctx) {
+                let timeout = jitter_buffer
+                    .as_ref()
+                    .and_then(|buffer| buffer.next_release_delay(Instant::now()))
+                    .map(|delay| delay.min(STREAMING_RECV_TIMEOUT))
+                    .unwrap_or(STREAMING_RECV_TIMEOUT);
+
+                if video_datagram_receiver.as_ref().is_some_and(|receiver| {
+                    !receiver.has_received_datagrams() && Instant::now() > udp_video_deadline
+                }) {
+                    warn!("No video datagram received, the video will be sent over TCP");
+                    video_datagram_receiver = None;
 
-                if header.is_idr {
-                    stream_corrupted = false;
-                } else if data.had_packet_loss() {
-                    stream_corrupted = true;
                     if let Some(sender) = &mut *ctx.control_sender.lock() {
-                        sender.send(&ClientControlPacket::RequestIdr).ok();
+                        sender
+                            .send(&alvr_packets::encode_reserved_client_control_packet(
+                                &ReservedClientControlPacket::UdpVideoUnavailable,
+                            ))
+                            .ok();
                     }
-                    warn!("Network dropped video packet");
                 }
 
-                if !stream_corrupted || !settings.connection.avoid_video_glitching {
-                    if capabilities.external_decoder {
-                        let mut view_params = *ctx.last_good_view_params.read();
-                        for (timestamp, views) in &*ctx.view_params_queue.read() {
-                            if *timestamp == header.timestamp {
-                                view_params = *views;
-                                break;
+                let datagram_data;
+                let received = if let Some(receiver) = &mut video_datagram_receiver {
+                    match receiver.recv(timeout) {
+                        Ok(data) => {
+                            datagram_data = data;
+                            let Ok((header, nal)) = datagram_data.get() else {
+                                return;
+                            };
+
+                            Some((header, nal, datagram_data.had_packet_loss()))
+                        }
+                        Err(ConnectionError::TryAgain(_)) => None,
+                        Err(ConnectionError::Other(_)) => return,
+                    }
+                } else {
+                    match video_receiver.recv_header(timeout) {
+                        Ok(packet)
+                            if packet.header.slice_count <= 1
+                                && jitter_buffer.is_none()
+                                && !capabilities.external_decoder =>
+                        {
+                            let header = packet.header;
+                            report_frame_received(header.timestamp);
+
+                            let accepted = accept_frame(header.is_idr, packet.had_packet_loss);
+
+                            let mut payload_result = None;
+                            let push_result = if accepted {
+                                ctx.decoder_sink.lock().as_mut().map(|sink| {
+                                    sink.push_nal_with(
+                                        header.timestamp,
+                                        packet.max_payload_size,
+                                        |buffer| {
+                                            let res = video_receiver
+                                                .recv_payload_into(buffer, STREAMING_RECV_TIMEOUT);
+                                            let size = *res.as_ref().unwrap_or(&0);
+                                            payload_result = Some(res);
+
+                                            size
+                                        },
+                                    )
+                                })
+                            } else {
+                                None
+                            };
+
+                            let payload_result = if let Some(res) = payload_result {
+                                res.map(|_| report_push_result(push_result))
+                            } else {
+                                // The decoder has no free input buffer or the frame is dropped
+                                nal_buffer.resize(packet.max_payload_size, 0);
+                                video_receiver
+                                    .recv_payload_into(&mut nal_buffer, STREAMING_RECV_TIMEOUT)
+                                    .map(|size| {
+                                        if accepted {
+                                            decode_frame(header.timestamp, &nal_buffer[..size]);
+                                        }
+                                    })
+                            };
+
+                            // Lost shards are reported with the next packet
+                            if let Err(ConnectionError::Other(_)) = payload_result {
+                                return;
                             }
+
+                            None
                         }
-                        event_queue.lock().push_back(ClientCoreEvent::FrameReady {
-                            timestamp: header.timestamp,
-                            view_params,
-                            nal: nal.to_vec(),
-                        });
-                    } else if !ctx
-                        .decoder_sink
-                        .lock()
-                        .as_mut()
-                        .map(|sink| sink.push_nal(header.timestamp, nal))
-                        .unwrap_or(false)
-                    {
-                        stream_corrupted = true;
-                        if let Some(sender) = &mut *ctx.control_sender.lock() {
-                            sender.send(&ClientControlPacket::RequestIdr).ok();
+                        Ok(packet) => {
+                            nal_buffer.resize(packet.max_payload_size, 0);
+                            match video_receiver
+                                .recv_payload_into(&mut nal_buffer, STREAMING_RECV_TIMEOUT)
+                            {
+                                Ok(size) => Some((
+                                    packet.header,
+                                    &nal_buffer[..size],
+                                    packet.had_packet_loss,
+                                )),
+                                Err(ConnectionError::TryAgain(_)) => None,
+                                Err(ConnectionError::Other(_)) => return,
+                            }
                         }
-                        warn!("Dropped video packet. Reason: Decoder saturation")
+                        Err(ConnectionError::TryAgain(_)) => None,
+                        Err(ConnectionError::Other(_)) => return,
                     }
-                } else {
-                    if let Some(sender) = &mut *ctx.control_sender.lock() {
-                        sender.send(&ClientControlPacket::RequestIdr).ok();
+                };
+
+                if let Some((header, nal, had_packet_loss)) = received {
+                    if header.slice_count <= 1 && jitter_buffer.is_none() {
+                        report_frame_received(header.timestamp);
+                        process_frame(header.timestamp, header.is_idr, nal, had_packet_loss);
+                    } else {
+                        let frames = if header.slice_count <= 1 {
+                            vec![ReceivedFrame {
+                                timestamp: header.timestamp,
+                                is_idr: header.is_idr,
+                                had_packet_loss,
+                                nal: nal.to_vec(),
+                            }]
+                        } else {
+                            slice_reassembler.push(&header, nal, had_packet_loss)
+                        };
+
+                        for frame in frames {
+                            report_frame_received(frame.timestamp);
+                            if let Some(buffer) = &mut jitter_buffer {
+                                buffer.push(frame, Instant::now());
+                            } else {
+                                process_frame(
+                                    frame.timestamp,
+                                    frame.is_idr,
+                                    &frame.nal,
+                                    frame.had_packet_loss,
+                                );
+                            }
+                        }
                     }
-                    warn!("Dropped video packet. Reason: Waiting for IDR frame")
+                }
+
+                if let Some(buffer) = &mut jitter_buffer {
+                    for (frame, hold) in buffer.pop_ready(Instant::now()) {
+                        if let Some(stats) = &mut *ctx.statistics_manager.lock() {
+                            stats.report_jitter_buffer_released(frame.timestamp, hold);
+                        }
+                        process_frame(
+                            frame.timestamp,
+                            frame.is_idr,
+                            &frame.nal,
+                            frame.had_packet_loss,
+                        );
+                    }
+                }
+
+                // Also repeats the request if the IDR never arrived
+                if ctx
+                    .decoder_recovery
+                    .lock()
+                    .as_mut()
+                    .map(|recovery| recovery.idr_request_due(Instant::now()))
+                    .unwrap_or(false)
+                {
+                    request_idr(&ctx);
                 }
             }
         }
     });
 
+    let game_audio_gain = Arc::new(Mutex::new(Config::load().game_audio_gain));
+
     let game_audio_thread = if let Switch::Enabled(config) = settings.audio.game_audio {
         let device = AudioDevice::new_output(None, None).to_con()?;
         thread::spawn({
             let ctx = Arc::clone(&ctx);
+            let game_audio_gain = Arc::clone(&game_audio_gain);
             move || {
                 while is_streaming(&ctx) {
                     alvr_common::show_err(audio::play_audio_loop(
@@ -361,7 +960,20 @@ fn connection_pipeline(
                         2,
                         negotiated_config.game_audio_sample_rate,
                         config.buffering.clone(),
+                        Arc::clone(&game_audio_gain),
                         &mut game_audio_receiver,
+                        |stats| {
+                            if let Some(sender) = &mut *ctx.control_sender.lock() {
+                                sender
+                                    .send(&alvr_packets::encode_reserved_client_control_packet(
+                                        &ReservedClientControlPacket::GameAudioBufferStatistics {
+                                            underruns_per_minute: stats.underruns_per_minute,
+                                            overruns_per_minute: stats.overruns_per_minute,
+                                        },
+                                    ))
+                                    .ok();
+                            }
+                        },
                     ));
                 }
             }
@@ -370,7 +982,8 @@ fn connection_pipeline(
         thread::spawn(|| ())
     };
 
-    let microphone_thread = if matches!(settings.audio.microphone, Switch::Enabled(_)) {
+    let microphone_thread = if let Switch::Enabled(config) = &settings.audio.microphone {
+        let gain = config.gain;
         let device = AudioDevice::new_input(None).to_con()?;
 
         let microphone_sender = stream_socket.request_stream(AUDIO);
@@ -385,6 +998,7 @@ fn connection_pipeline(
                         microphone_sender.clone(),
                         &device,
                         1,
+                        gain,
                         false,
                     ) {
                         Ok(()) => break,
@@ -405,27 +1019,34 @@ fn connection_pipeline(
         let ctx = Arc::clone(&ctx);
         let event_queue = Arc::clone(&event_queue);
         move || {
+            let mut dropped_packets = 0;
             while is_streaming(&ctx) {
-                let data = match haptics_receiver.recv(STREAMING_RECV_TIMEOUT) {
+                let (haptics, envelope) = match haptics_receiver.recv(STREAMING_RECV_TIMEOUT) {
                     Ok(packet) => packet,
                     Err(ConnectionError::TryAgain(_)) => continue,
                     Err(ConnectionError::Other(_)) => return,
                 };
-                let Ok(haptics) = data.get_header() else {
-                    return;
-                };
+
+                let new_dropped_packets = haptics_receiver.dropped_packets();
+                if new_dropped_packets != dropped_packets {
+                    if let Some(stats) = &mut *ctx.statistics_manager.lock() {
+                        stats.report_stream_packets_dropped(new_dropped_packets - dropped_packets);
+                    }
+                    dropped_packets = new_dropped_packets;
+                }
 
                 event_queue.lock().push_back(ClientCoreEvent::Haptics {
                     device_id: haptics.device_id,
                     duration: haptics.duration,
                     frequency: haptics.frequency,
                     amplitude: haptics.amplitude,
+                    envelope,
                 });
             }
         }
     });
 
-    let (log_channel_sender, log_channel_receiver) = mpsc::channel();
+    let (log_channel_sender, log_channel_receiver) = mpsc::sync_channel(LOG_BUFFER_SIZE);
 
     let control_send_thread = thread::spawn({
         let ctx = Arc::clone(&ctx);
@@ -433,11 +1054,18 @@ fn connection_pipeline(
         let disconnect_notif = Arc::clone(&disconnect_notif);
         move || {
             let mut keepalive_deadline = Instant::now();
+            let mut log_deadline = Instant::now();
 
             #[cfg(target_os = "android")]
             let mut battery_deadline = Instant::now();
+            #[cfg(target_os = "android")]
+            let mut volume_deadline = Instant::now();
+            #[cfg(target_os = "android")]
+            let mut last_media_volume = platform::get_media_volume();
 
             while is_streaming(&ctx) && *lifecycle_state.read() == LifecycleState::Resumed {
+                thread::sleep(log_deadline.saturating_duration_since(Instant::now()));
+
                 if let (Ok(packet), Some(sender)) = (
                     log_channel_receiver.recv_timeout(STREAMING_RECV_TIMEOUT),
                     &mut *ctx.control_sender.lock(),
@@ -448,6 +1076,8 @@ fn connection_pipeline(
 
                         break;
                     }
+
+                    log_deadline = Instant::now() + LOG_SEND_INTERVAL;
                 }
 
                 if Instant::now() > keepalive_deadline {
@@ -471,8 +1101,46 @@ fn connection_pipeline(
                             .ok();
                     }
 
+                    if let (Some(thermal_status), Some(sender)) = (
+                        platform::get_thermal_status(),
+                        &mut *ctx.control_sender.lock(),
+                    ) {
+                        sender
+                            .send(&alvr_packets::encode_reserved_client_control_packet(
+                                &ReservedClientControlPacket::ThermalStatus(thermal_status),
+                            ))
+                            .ok();
+                    }
+
                     battery_deadline = Instant::now() + Duration::from_secs(5);
                 }
+
+                // The volume buttons change the system media volume, which is mirrored on the game
+                // audio gain. There is no callback for it without a Java receiver
+                #[cfg(target_os = "android")]
+                if Instant::now() > volume_deadline {
+                    let media_volume = platform::get_media_volume();
+                    if media_volume != last_media_volume {
+                        info!("Game audio volume set to {:.0}%", media_volume * 100.0);
+
+                        *game_audio_gain.lock() = media_volume;
+
+                        let mut config = Config::load();
+                        config.game_audio_gain = media_volume;
+                        config.store();
+
+                        event_queue
+                            .lock()
+                            .push_back(ClientCoreEvent::Notification(format!(
+                                "Volume {:.0}%",
+                                media_volume * 100.0
+                            )));
+
+                        last_media_volume = media_volume;
+                    }
+
+                    volume_deadline = Instant::now() + VOLUME_POLL_INTERVAL;
+                }
             }
 
             disconnect_notif.notify_one();
@@ -483,10 +1151,13 @@ fn connection_pipeline(
         let ctx = Arc::clone(&ctx);
         let event_queue = Arc::clone(&event_queue);
         let disconnect_notif = Arc::clone(&disconnect_notif);
+        let server_restarting = Arc::clone(&server_restarting);
+        let server_shutting_down = Arc::clone(&server_shutting_down);
         move || {
             let mut disconnection_deadline = Instant::now() + KEEPALIVE_TIMEOUT;
             while is_streaming(&ctx) {
                 let maybe_packet = control_receiver.recv(STREAMING_RECV_TIMEOUT);
+                ctx.requests.expire(Instant::now());
 
                 match maybe_packet {
                     Ok(ServerControlPacket::DecoderConfig(config)) => {
@@ -524,9 +1195,83 @@ fn connection_pipeline(
                             }
                         }
                     }
+                    Ok(ServerControlPacket::Reserved(json_string)) => {
+                        // Unknown packets from newer servers are ignored
+                        match serde_json::from_str(&json_string) {
+                            Ok(ReservedServerControlPacket::CodecChanged(codec)) => {
+                                info!("Server switched codec to {codec:?}");
+
+                                // The decoder is recreated when the next DecoderConfig arrives
+                                if !capabilities.external_decoder {
+                                    *ctx.decoder_sink.lock() = None;
+                                    *ctx.decoder_source.lock() = None;
+                                }
+                            }
+                            Ok(ReservedServerControlPacket::Statistics(statistics)) => {
+                                *ctx.server_statistics.lock() = Some(statistics);
+                            }
+                            Ok(ReservedServerControlPacket::RequestTrackingKeyframe) => {
+                                if let Some(TrackingSender::Delta(_, encoder)) =
+                                    &mut *ctx.tracking_sender.lock()
+                                {
+                                    encoder.request_keyframe();
+                                }
+                            }
+                            Ok(ReservedServerControlPacket::KeepAlivePing(send_time)) => {
+                                if let Some(sender) = &mut *ctx.control_sender.lock() {
+                                    sender
+                                        .send(&alvr_packets::encode_reserved_client_control_packet(
+                                            &ReservedClientControlPacket::KeepAlivePong(send_time),
+                                        ))
+                                        .ok();
+                                }
+                            }
+                            Ok(ReservedServerControlPacket::Response { id, response }) => {
+                                let result = match serde_json::from_str(&response) {
+                                    Ok(ServerResponse::Unsupported) | Err(_) => {
+                                        Err(RequestError::Unsupported)
+                                    }
+                                    Ok(ServerResponse::Failed(message)) => {
+                                        Err(RequestError::Failed(message))
+                                    }
+                                    Ok(response) => Ok(response),
+                                };
+                                ctx.requests.complete(id, result);
+                            }
+                            Ok(ReservedServerControlPacket::Notification(message)) => {
+                                event_queue
+                                    .lock()
+                                    .push_back(ClientCoreEvent::Notification(message));
+                            }
+                            Ok(ReservedServerControlPacket::Recenter) => {
+                                event_queue
+                                    .lock()
+                                    .push_back(ClientCoreEvent::RecenterRequested);
+                            }
+                            Ok(ReservedServerControlPacket::StreamPaused(paused)) => {
+                                event_queue
+                                    .lock()
+                                    .push_back(ClientCoreEvent::StreamPaused(paused));
+                            }
+                            Ok(ReservedServerControlPacket::SettingsChanged(settings)) => {
+                                event_queue
+                                    .lock()
+                                    .push_back(ClientCoreEvent::SettingsChanged(settings));
+                            }
+                            Ok(ReservedServerControlPacket::ShuttingDown) => {
+                                info!("{SERVER_SHUTDOWN_MESSAGE}");
+                                set_hud_message(&event_queue, SERVER_SHUTDOWN_MESSAGE);
+                                server_shutting_down.store(true, Ordering::Relaxed);
+                                disconnect_notif.notify_one();
+                            }
+                            // Expected only before the stream starts
+                            Ok(ReservedServerControlPacket::PairingChallenge { .. }) | Err(_) => (),
+                        }
+                    }
                     Ok(ServerControlPacket::Restarting) => {
                         info!("{SERVER_RESTART_MESSAGE}");
                         set_hud_message(&event_queue, SERVER_RESTART_MESSAGE);
+                        server_restarting.store(true, Ordering::Relaxed);
                         disconnect_notif.notify_one();
                     }
                     Ok(_) => (),
@@ -551,35 +1296,74 @@ fn connection_pipeline(
         }
     });
 
-    let stream_receive_thread = thread::spawn({
-        let ctx = Arc::clone(&ctx);
-        let event_queue = Arc::clone(&event_queue);
-        let disconnect_notif = Arc::clone(&disconnect_notif);
-        move || {
-            while is_streaming(&ctx) {
-                match stream_socket.recv() {
-                    Ok(()) => (),
-                    Err(ConnectionError::TryAgain(_)) => continue,
-                    Err(e) => {
-                        info!("Client disconnected. Cause: {e}");
-                        set_hud_message(&event_queue, SERVER_DISCONNECTED_MESSAGE);
-                        disconnect_notif.notify_one();
-                    }
-                }
+    let stream_receive_thread = stream_socket.spawn_receive_thread(
+        {
+            let ctx = Arc::clone(&ctx);
+            move || is_streaming(&ctx)
+        },
+        {
+            let event_queue = Arc::clone(&event_queue);
+            let disconnect_notif = Arc::clone(&disconnect_notif);
+            move |e| {
+                info!("Client disconnected. Cause: {e}");
+                set_hud_message(&event_queue, SERVER_DISCONNECTED_MESSAGE);
+                disconnect_notif.notify_one();
             }
-        }
-    });
+        },
+    );
 
     *ctx.control_sender.lock() = Some(control_sender);
     *ctx.tracking_sender.lock() = Some(tracking_sender);
     *ctx.statistics_sender.lock() = Some(statistics_sender);
+    if settings.capture.client_tracking_recording {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let path = storage::tracking_recordings_dir().join(format!("tracking.{timestamp}.jsonl"));
+        let capabilities = ClientCapabilities {
+            device_model: Some(device_model),
+            ..capabilities.clone()
+        };
+
+        match TrackingRecorder::new(&path, capabilities) {
+            Ok(recorder) => *ctx.tracking_recorder.lock() = Some(recorder),
+            Err(e) => warn!("Failed to start the tracking recording: {e}"),
+        }
+    }
     if let Switch::Enabled(filter_level) = settings.logging.client_log_report_level {
         *LOG_CHANNEL_SENDER.lock() = Some(LogMirrorData {
             sender: log_channel_sender,
             filter_level,
+            dropped_count: 0,
         });
     }
-    event_queue.lock().push_back(streaming_start_event);
+
+    let stream_config = (config_packet.session, config_packet.negotiated);
+    let resumed = if let Some(reconnection) = reconnection.take() {
+        if reconnection.stream_config == stream_config {
+            true
+        } else {
+            event_queue
+                .lock()
+                .push_back(ClientCoreEvent::StreamingStopped);
+
+            false
+        }
+    } else {
+        false
+    };
+
+    if resumed {
+        info!("Stream resumed after reconnection");
+
+        // The decoder was destroyed, the streamer resends the DecoderConfig together with the IDR
+        if let Some(sender) = &mut *ctx.control_sender.lock() {
+            sender.send(&ClientControlPacket::RequestIdr).ok();
+        }
+    } else {
+        event_queue.lock().push_back(streaming_start_event);
+    }
 
     *connection_state_lock = ConnectionState::Streaming;
 
@@ -595,14 +1379,39 @@ fn connection_pipeline(
     *ctx.control_sender.lock() = None;
     *ctx.tracking_sender.lock() = None;
     *ctx.statistics_sender.lock() = None;
+    *ctx.tracking_recorder.lock() = None;
     *LOG_CHANNEL_SENDER.lock() = None;
-
-    event_queue
-        .lock()
-        .push_back(ClientCoreEvent::StreamingStopped);
+    ctx.requests.cancel_all();
+
+    let shutting_down = server_shutting_down.load(Ordering::Relaxed);
+    *server_shut_down = shutting_down;
+
+    match fast_reconnection_timeout {
+        Some(timeout)
+            if !server_restarting.load(Ordering::Relaxed)
+                && !shutting_down
+                && *lifecycle_state.read() == LifecycleState::Resumed =>
+        {
+            info!("Connection lost, trying to reconnect");
+            // The stream is still shown, the HUD message is only visible in the lobby
+            event_queue
+                .lock()
+                .push_back(ClientCoreEvent::Notification(RECONNECTING_MESSAGE.into()));
+
+            *reconnection = Some(Reconnection {
+                server_ip,
+                stream_config,
+                deadline: Instant::now() + timeout,
+            });
+        }
+        _ => event_queue
+            .lock()
+            .push_back(ClientCoreEvent::StreamingStopped),
+    }
 
     *ctx.decoder_sink.lock() = None;
     *ctx.decoder_source.lock() = None;
+    *ctx.decoder_recovery.lock() = None;
 
     // Remove lock to allow threads to properly exit:
     drop(connection_state_lock);
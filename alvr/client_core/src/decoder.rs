@@ -1,6 +1,9 @@
 use alvr_common::anyhow::{bail, Result};
-use alvr_session::{CodecType, MediacodecDataType};
-use std::time::Duration;
+use alvr_session::{CodecType, DecoderRecoveryConfig, MediacodecDataType};
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
 
 #[derive(Clone, Default)]
 pub struct DecoderConfig {
@@ -20,13 +23,168 @@ pub struct DecoderSink {
 impl DecoderSink {
     // returns true if frame has been successfully enqueued
     #[allow(unused_variables)]
-    pub fn push_nal(&mut self, timestamp: Duration, nal: &[u8]) -> bool {
+    pub fn push_nal(&mut self, timestamp: Duration, nal: &[u8]) -> Result<bool> {
         #[cfg(target_os = "android")]
         {
-            alvr_common::show_err(self.inner.push_frame_nal(timestamp, nal)).unwrap_or(false)
+            self.inner.push_frame_nal(timestamp, nal)
         }
         #[cfg(not(target_os = "android"))]
-        false
+        Ok(false)
+    }
+
+    // Like push_nal(), but write_nal() writes the NAL straight into a decoder input buffer of at
+    // least max_size bytes and returns its size. Returns false if nothing was written, write_nal()
+    // is not called if no such buffer is available
+    #[allow(unused_variables)]
+    pub fn push_nal_with(
+        &mut self,
+        timestamp: Duration,
+        max_size: usize,
+        write_nal: impl FnOnce(&mut [u8]) -> usize,
+    ) -> Result<bool> {
+        #[cfg(target_os = "android")]
+        {
+            self.inner
+                .push_frame_nal_with(timestamp, max_size, write_nal)
+        }
+        #[cfg(not(target_os = "android"))]
+        Ok(false)
+    }
+
+    // Discards all frames queued in the decoder. Decoding can resume only from an IDR frame
+    pub fn flush(&mut self) -> Result<()> {
+        #[cfg(target_os = "android")]
+        {
+            self.inner.flush()
+        }
+        #[cfg(not(target_os = "android"))]
+        Ok(())
+    }
+}
+
+pub enum DecoderErrorAction {
+    Flush,
+    Recreate { report_failure: bool },
+}
+
+// Keeps track of the stream state after packet loss and decoder errors. IDR requests are throttled
+// and repeated decoder errors escalate to the recreation of the decoder.
+pub struct DecoderRecovery {
+    config: DecoderRecoveryConfig,
+    avoid_video_glitching: bool,
+    // Frames are discarded until the next IDR only if avoid_video_glitching is set
+    stream_corrupted: bool,
+    // Frames are always discarded until the next IDR
+    decoder_reset: bool,
+    last_idr_request: Option<Instant>,
+    recent_errors: VecDeque<Instant>,
+    last_error: Option<Instant>,
+    consecutive_recreations: u32,
+    failure_reported: bool,
+}
+
+impl DecoderRecovery {
+    pub fn new(config: DecoderRecoveryConfig, avoid_video_glitching: bool) -> Self {
+        Self {
+            config,
+            avoid_video_glitching,
+            stream_corrupted: false,
+            decoder_reset: false,
+            last_idr_request: None,
+            recent_errors: VecDeque::new(),
+            last_error: None,
+            consecutive_recreations: 0,
+            failure_reported: false,
+        }
+    }
+
+    fn waiting_for_idr(&self) -> bool {
+        self.stream_corrupted || self.decoder_reset
+    }
+
+    // Returns false if the frame must be discarded
+    pub fn accept_frame(&mut self, is_idr: bool) -> bool {
+        if is_idr {
+            self.stream_corrupted = false;
+            self.decoder_reset = false;
+            self.last_idr_request = None;
+        }
+
+        !self.decoder_reset && !(self.stream_corrupted && self.avoid_video_glitching)
+    }
+
+    pub fn report_stream_corrupted(&mut self) {
+        self.stream_corrupted = true;
+    }
+
+    // Returns true if an IDR should be requested now. Requests are repeated only after the timeout
+    pub fn idr_request_due(&mut self, now: Instant) -> bool {
+        let timeout = Duration::from_millis(self.config.idr_request_timeout_ms);
+        if self.waiting_for_idr()
+            && self
+                .last_idr_request
+                .map(|instant| now.saturating_duration_since(instant) >= timeout)
+                .unwrap_or(true)
+        {
+            self.last_idr_request = Some(now);
+
+            true
+        } else {
+            false
+        }
+    }
+
+    fn recreate(&mut self) -> DecoderErrorAction {
+        self.recent_errors.clear();
+        self.consecutive_recreations += 1;
+
+        let report_failure = !self.failure_reported
+            && self.consecutive_recreations >= self.config.recreations_before_report;
+        self.failure_reported |= report_failure;
+
+        DecoderErrorAction::Recreate { report_failure }
+    }
+
+    pub fn report_decoder_error(&mut self, now: Instant) -> DecoderErrorAction {
+        let window = Duration::from_secs_f32(self.config.error_window_s);
+
+        // Recreations count as consecutive until the decoder works for a whole window
+        if self
+            .last_error
+            .map(|instant| now.saturating_duration_since(instant) > window)
+            .unwrap_or(false)
+        {
+            self.consecutive_recreations = 0;
+            self.failure_reported = false;
+        }
+        self.last_error = Some(now);
+
+        self.recent_errors.push_back(now);
+        while self
+            .recent_errors
+            .front()
+            .map(|instant| now.saturating_duration_since(*instant) > window)
+            .unwrap_or(false)
+        {
+            self.recent_errors.pop_front();
+        }
+
+        self.decoder_reset = true;
+        self.last_idr_request = None;
+
+        if self.recent_errors.len() >= self.config.errors_before_recreation as usize {
+            self.recreate()
+        } else {
+            DecoderErrorAction::Flush
+        }
+    }
+
+    // The decoder stopped working altogether and must be recreated
+    pub fn report_decoder_lost(&mut self, now: Instant) -> DecoderErrorAction {
+        match self.report_decoder_error(now) {
+            DecoderErrorAction::Flush => self.recreate(),
+            action => action,
+        }
     }
 }
 
@@ -67,3 +225,93 @@ pub fn create_decoder(
     #[cfg(not(target_os = "android"))]
     (DecoderSink {}, DecoderSource {})
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recovery() -> DecoderRecovery {
+        DecoderRecovery::new(
+            DecoderRecoveryConfig {
+                idr_request_timeout_ms: 500,
+                error_window_s: 5.0,
+                errors_before_recreation: 3,
+                recreations_before_report: 2,
+            },
+            false,
+        )
+    }
+
+    #[test]
+    fn test_idr_request_after_error() {
+        let mut recovery = recovery();
+        let start = Instant::now();
+
+        assert!(recovery.accept_frame(false));
+        assert!(!recovery.idr_request_due(start));
+
+        assert!(matches!(
+            recovery.report_decoder_error(start),
+            DecoderErrorAction::Flush
+        ));
+        assert!(recovery.idr_request_due(start));
+        assert!(!recovery.accept_frame(false));
+
+        // The request is repeated only if the IDR did not arrive within the timeout
+        assert!(!recovery.idr_request_due(start + Duration::from_millis(499)));
+        assert!(recovery.idr_request_due(start + Duration::from_millis(500)));
+        assert!(!recovery.idr_request_due(start + Duration::from_millis(600)));
+
+        assert!(recovery.accept_frame(true));
+        assert!(recovery.accept_frame(false));
+        assert!(!recovery.idr_request_due(start + Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_error_escalation() {
+        let mut recovery = recovery();
+        let start = Instant::now();
+        let report_error = |recovery: &mut DecoderRecovery, offset_s: f32| {
+            recovery.report_decoder_error(start + Duration::from_secs_f32(offset_s))
+        };
+
+        assert!(matches!(
+            report_error(&mut recovery, 0.0),
+            DecoderErrorAction::Flush
+        ));
+        assert!(matches!(
+            report_error(&mut recovery, 1.0),
+            DecoderErrorAction::Flush
+        ));
+        assert!(matches!(
+            report_error(&mut recovery, 2.0),
+            DecoderErrorAction::Recreate {
+                report_failure: false
+            }
+        ));
+
+        // The failure is reported once after consecutive recreations
+        assert!(matches!(
+            recovery.report_decoder_lost(start + Duration::from_secs(3)),
+            DecoderErrorAction::Recreate {
+                report_failure: true
+            }
+        ));
+        assert!(matches!(
+            recovery.report_decoder_lost(start + Duration::from_secs(4)),
+            DecoderErrorAction::Recreate {
+                report_failure: false
+            }
+        ));
+
+        // Errors older than the window are forgotten, the decoder worked in between
+        assert!(matches!(
+            report_error(&mut recovery, 10.0),
+            DecoderErrorAction::Flush
+        ));
+        assert!(matches!(
+            report_error(&mut recovery, 16.0),
+            DecoderErrorAction::Flush
+        ));
+    }
+}
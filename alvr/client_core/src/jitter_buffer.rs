@@ -0,0 +1,265 @@
+use crate::video_slices::ReceivedFrame;
+use alvr_session::JitterBufferDepth;
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+// Holds complete frames before they are submitted to the decoder. Frames are released one per
+// nominal frame interval, so bursts of frames are spread out over the following gap. The depth
+// limit is enforced on top of this: frames are released early rather than held longer than the
+// configured depth. The number of held frames follows the arrival jitter: it stays near zero on a
+// regular stream and grows during bursts, up to the depth limit.
+pub struct JitterBuffer {
+    depth: JitterBufferDepth,
+    frame_interval: Duration,
+    // Sorted by frame timestamp
+    queue: VecDeque<(Instant, ReceivedFrame)>,
+    last_release: Instant,
+    last_released_timestamp: Option<Duration>,
+    late_frame_dropped: bool,
+    waiting_for_idr: bool,
+    flush: bool,
+}
+
+impl JitterBuffer {
+    pub fn new(depth: JitterBufferDepth, frame_interval: Duration, now: Instant) -> Self {
+        Self {
+            depth,
+            frame_interval,
+            queue: VecDeque::new(),
+            last_release: now,
+            last_released_timestamp: None,
+            late_frame_dropped: false,
+            waiting_for_idr: false,
+            flush: false,
+        }
+    }
+
+    pub fn push(&mut self, frame: ReceivedFrame, now: Instant) {
+        // A frame older than one already released cannot be decoded anymore. It counts as lost
+        if self
+            .last_released_timestamp
+            .is_some_and(|timestamp| frame.timestamp <= timestamp)
+        {
+            self.late_frame_dropped = true;
+            self.waiting_for_idr = true;
+            return;
+        }
+
+        if frame.had_packet_loss {
+            self.waiting_for_idr = true;
+        }
+
+        // The frames before the recovery IDR are no use for smoothing anymore, release them all
+        // at once together with the IDR
+        if frame.is_idr && self.waiting_for_idr {
+            self.waiting_for_idr = false;
+            self.flush = true;
+        }
+
+        // Frames that overtook each other on the network are put back in order
+        let index = self
+            .queue
+            .partition_point(|(_, queued)| queued.timestamp < frame.timestamp);
+        self.queue.insert(index, (now, frame));
+    }
+
+    // Time until the next frame should be released. None if the buffer is empty
+    pub fn next_release_delay(&self, now: Instant) -> Option<Duration> {
+        let oldest_arrival = self.queue.iter().map(|(arrival, _)| *arrival).min()?;
+
+        let mut deadline = self.last_release + self.frame_interval;
+        if self.flush {
+            deadline = now;
+        }
+        if let JitterBufferDepth::Milliseconds(ms) = self.depth {
+            deadline = deadline.min(oldest_arrival + Duration::from_millis(ms));
+        }
+
+        Some(deadline.saturating_duration_since(now))
+    }
+
+    // Returns the frames that should be submitted to the decoder now, together with the time each
+    // of them spent in the buffer
+    pub fn pop_ready(&mut self, now: Instant) -> Vec<(ReceivedFrame, Duration)> {
+        let mut release_count = 0;
+
+        if self.flush {
+            self.flush = false;
+            release_count = self.queue.len();
+        }
+
+        match self.depth {
+            JitterBufferDepth::Frames(count) => {
+                release_count = release_count.max(self.queue.len().saturating_sub(count as usize));
+            }
+            JitterBufferDepth::Milliseconds(ms) => {
+                // Frames that were reordered before an expired frame are released with it
+                let max_hold = Duration::from_millis(ms);
+                let expired_count = self
+                    .queue
+                    .iter()
+                    .rposition(|(arrival, _)| now.saturating_duration_since(*arrival) >= max_hold)
+                    .map_or(0, |index| index + 1);
+                release_count = release_count.max(expired_count);
+            }
+        }
+
+        if release_count == 0
+            && !self.queue.is_empty()
+            && now >= self.last_release + self.frame_interval
+        {
+            release_count = 1;
+        }
+
+        if release_count > 0 {
+            self.last_release = now;
+        }
+
+        let mut frames = self
+            .queue
+            .drain(..release_count)
+            .map(|(arrival, frame)| (frame, now.saturating_duration_since(arrival)))
+            .collect::<Vec<_>>();
+
+        if let Some((frame, _)) = frames.last() {
+            self.last_released_timestamp = Some(frame.timestamp);
+        }
+        // The decoder recovery requests an IDR for the frame that follows the dropped one
+        if let Some((frame, _)) = frames.first_mut() {
+            if self.late_frame_dropped {
+                frame.had_packet_loss = true;
+                self.late_frame_dropped = false;
+            }
+        }
+
+        frames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME_INTERVAL: Duration = Duration::from_millis(10);
+
+    fn frame(timestamp_ms: u64, is_idr: bool) -> ReceivedFrame {
+        ReceivedFrame {
+            timestamp: Duration::from_millis(timestamp_ms),
+            is_idr,
+            had_packet_loss: false,
+            nal: vec![],
+        }
+    }
+
+    fn timestamps(frames: &[(ReceivedFrame, Duration)]) -> Vec<u64> {
+        frames
+            .iter()
+            .map(|(frame, _)| frame.timestamp.as_millis() as u64)
+            .collect()
+    }
+
+    #[test]
+    fn test_reordering() {
+        let start = Instant::now();
+        let mut buffer = JitterBuffer::new(JitterBufferDepth::Frames(1), FRAME_INTERVAL, start);
+
+        buffer.push(frame(2, false), start);
+        buffer.push(frame(1, false), start + Duration::from_millis(1));
+        buffer.push(frame(3, false), start + Duration::from_millis(2));
+
+        let now = start + Duration::from_millis(2);
+        assert_eq!(timestamps(&buffer.pop_ready(now)), [1, 2]);
+
+        let now = now + FRAME_INTERVAL;
+        assert_eq!(timestamps(&buffer.pop_ready(now)), [3]);
+    }
+
+    #[test]
+    fn test_late_frame_drop() {
+        let start = Instant::now();
+        let mut buffer = JitterBuffer::new(JitterBufferDepth::Frames(1), FRAME_INTERVAL, start);
+
+        buffer.push(frame(2, false), start);
+        let now = start + FRAME_INTERVAL;
+        assert_eq!(timestamps(&buffer.pop_ready(now)), [2]);
+
+        // Frame 1 arrives after frame 2 was released
+        buffer.push(frame(1, false), now);
+        buffer.push(frame(3, false), now);
+
+        let now = now + FRAME_INTERVAL;
+        let frames = buffer.pop_ready(now);
+        assert_eq!(timestamps(&frames), [3]);
+        assert!(frames[0].0.had_packet_loss);
+
+        // The recovery IDR is released at once with the frames before it
+        buffer.push(frame(4, false), now);
+        buffer.push(frame(5, true), now);
+        let frames = buffer.pop_ready(now);
+        assert_eq!(timestamps(&frames), [4, 5]);
+        assert!(!frames[0].0.had_packet_loss);
+    }
+
+    #[test]
+    fn test_depth_adaptation() {
+        let start = Instant::now();
+        let mut buffer = JitterBuffer::new(JitterBufferDepth::Frames(3), FRAME_INTERVAL, start);
+        let mut held_count = 0;
+
+        // A regular stream is held for at most one frame
+        let mut now = start;
+        for timestamp in 0..5 {
+            buffer.push(frame(timestamp, false), now);
+            held_count += 1;
+            held_count -= buffer.pop_ready(now).len();
+            assert_eq!(held_count, 1);
+            now += FRAME_INTERVAL;
+        }
+
+        // A burst fills the buffer up to the depth limit
+        for timestamp in 5..10 {
+            buffer.push(frame(timestamp, false), now);
+            held_count += 1;
+        }
+        let frames = buffer.pop_ready(now);
+        held_count -= frames.len();
+        assert_eq!(timestamps(&frames), [4, 5, 6]);
+        assert_eq!(held_count, 3);
+
+        // Then it drains one frame per interval
+        assert_eq!(buffer.next_release_delay(now), Some(FRAME_INTERVAL));
+        for timestamp in 7..10 {
+            now += FRAME_INTERVAL;
+            assert_eq!(timestamps(&buffer.pop_ready(now)), [timestamp]);
+        }
+        assert!(buffer.next_release_delay(now).is_none());
+    }
+
+    #[test]
+    fn test_milliseconds_depth_limit() {
+        let start = Instant::now();
+        let mut buffer =
+            JitterBuffer::new(JitterBufferDepth::Milliseconds(15), FRAME_INTERVAL, start);
+
+        for timestamp in 0..4 {
+            buffer.push(frame(timestamp, false), start);
+        }
+
+        let now = start + FRAME_INTERVAL;
+        assert_eq!(timestamps(&buffer.pop_ready(now)), [0]);
+        assert_eq!(
+            buffer.next_release_delay(now),
+            Some(Duration::from_millis(5))
+        );
+
+        // No frame is held longer than the depth
+        let now = start + Duration::from_millis(15);
+        let frames = buffer.pop_ready(now);
+        assert_eq!(timestamps(&frames), [1, 2, 3]);
+        assert!(frames
+            .iter()
+            .all(|(_, hold)| *hold <= Duration::from_millis(15)));
+    }
+}
@@ -8,11 +8,14 @@
 mod c_api;
 mod connection;
 mod decoder;
+mod jitter_buffer;
 mod logging_backend;
 mod platform;
 mod sockets;
 mod statistics;
 mod storage;
+mod tracking_recording;
+mod video_slices;
 
 pub mod opengl;
 
@@ -20,14 +23,15 @@ pub mod opengl;
 mod audio;
 
 use alvr_common::{
-    error,
     glam::{UVec2, Vec2, Vec3},
     parking_lot::{Mutex, RwLock},
     warn, ConnectionState, DeviceMotion, LifecycleState, Pose, HEAD_ID,
 };
 use alvr_packets::{
-    BatteryPacket, ButtonEntry, ClientControlPacket, FaceData, NegotiatedStreamingConfig,
-    ReservedClientControlPacket, Tracking, ViewParams, ViewsConfig,
+    AudioDevicesList, BatteryPacket, ButtonEntry, ClientControlPacket, ClientRequest,
+    ClientStatistics, ClientStatisticsSnapshot, DeviceDeclaration, FaceData, HapticsEnvelopePoint,
+    NegotiatedStreamingConfig, ReservedClientControlPacket, ServerResponse, ServerStatistics,
+    Tracking, ViewParams, ViewsConfig,
 };
 use alvr_session::{CodecType, Settings};
 use connection::ConnectionContext;
@@ -39,9 +43,12 @@ use std::{
     time::Duration,
 };
 use storage::Config;
+use tracking_recording::RecordedInput;
 
+pub use alvr_sockets::{PendingResponse, RequestError};
 pub use logging_backend::init_logging;
 pub use platform::Platform;
+pub use tracking_recording::TrackingReplay;
 
 #[cfg(target_os = "android")]
 pub use platform::try_get_permission;
@@ -65,8 +72,10 @@ pub enum ClientCoreEvent {
         duration: Duration,
         frequency: f32,
         amplitude: f32,
+        envelope: Vec<HapticsEnvelopePoint>,
     },
-    // Note: All subsequent DecoderConfig events should be ignored until reconnection
+    // Note: All subsequent DecoderConfig events should be ignored until reconnection, unless the
+    // codec is different
     DecoderConfig {
         codec: CodecType,
         config_nal: Vec<u8>,
@@ -76,6 +85,22 @@ pub enum ClientCoreEvent {
         view_params: [ViewParams; 2],
         nal: Vec<u8>,
     },
+    // Short message from the streamer or the client to be shown over the stream
+    Notification(String),
+    // The streamer verifies the client before trusting it. The user must enter the code shown in
+    // the dashboard, to be passed to submit_pairing_code()
+    PairingCodeRequested {
+        attempts_left: u32,
+    },
+    // Requested from the dashboard. The reference space must be created again, then the playspace
+    // sent with send_playspace()
+    RecenterRequested,
+    // While paused no video frame arrives, the lobby should be shown instead. The tracking must
+    // still be sent
+    StreamPaused(bool),
+    // The real-time settings changed while streaming. Other settings keep the values of
+    // StreamingStarted until the stream restarts
+    SettingsChanged(Box<Settings>),
 }
 
 pub struct DecodedFrame {
@@ -85,7 +110,7 @@ pub struct DecodedFrame {
 }
 
 // Note: this struct may change without breaking network protocol changes
-#[derive(Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct ClientCapabilities {
     pub default_view_resolution: UVec2,
     pub external_decoder: bool,
@@ -94,6 +119,11 @@ pub struct ClientCapabilities {
     pub encoder_high_profile: bool,
     pub encoder_10_bits: bool,
     pub encoder_av1: bool,
+    pub multi_slice_decoding: bool,
+    // Whether ClientCoreEvent::Haptics envelopes are played back
+    pub haptics_envelopes: bool,
+    // Reported to the streamer instead of the detected platform, like "Quest 2"
+    pub device_model: Option<String>,
 }
 
 pub struct ClientCoreContext {
@@ -106,7 +136,7 @@ pub struct ClientCoreContext {
 impl ClientCoreContext {
     pub fn new(capabilities: ClientCapabilities) -> Self {
         // Make sure to reset config in case of version compat mismatch.
-        if Config::load().protocol_id != alvr_common::protocol_id() {
+        if Config::load().protocol_id != alvr_packets::protocol_id() {
             // NB: Config::default() sets the current protocol ID
             Config::default().store();
         }
@@ -163,6 +193,11 @@ impl ClientCoreContext {
         self.event_queue.lock().pop_front()
     }
 
+    // The code has alvr_packets::PAIRING_CODE_LENGTH digits
+    pub fn submit_pairing_code(&self, code: String) {
+        *self.connection_context.pairing_code.lock() = Some(code);
+    }
+
     pub fn send_battery(&self, device_id: u64, gauge_value: f32, is_plugged: bool) {
         if let Some(sender) = &mut *self.connection_context.control_sender.lock() {
             sender
@@ -181,6 +216,17 @@ impl ClientCoreContext {
         }
     }
 
+    // Only if NegotiatedStreamingConfig::playspace_boundary is set, otherwise use send_playspace()
+    pub fn send_playspace_boundary(&self, points: Vec<Vec2>) {
+        if let Some(sender) = &mut *self.connection_context.control_sender.lock() {
+            sender
+                .send(&alvr_packets::encode_reserved_client_control_packet(
+                    &ReservedClientControlPacket::PlayspaceBoundary(points),
+                ))
+                .ok();
+        }
+    }
+
     pub fn send_active_interaction_profile(&self, device_id: u64, profile_id: u64) {
         if let Some(sender) = &mut *self.connection_context.control_sender.lock() {
             sender
@@ -192,6 +238,50 @@ impl ClientCoreContext {
         }
     }
 
+    // Must be sent before the tracking of the stream. Without it the streamer uses the legacy
+    // profile of one headset and two controllers
+    pub fn send_device_registration(&self, devices: Vec<DeviceDeclaration>) {
+        if let Some(sender) = &mut *self.connection_context.control_sender.lock() {
+            sender
+                .send(&alvr_packets::encode_reserved_client_control_packet(
+                    &ReservedClientControlPacket::DeviceRegistration(devices),
+                ))
+                .ok();
+        }
+    }
+
+    pub fn send_proximity_state(&self, headset_worn: bool) {
+        if let Some(sender) = &mut *self.connection_context.control_sender.lock() {
+            sender
+                .send(&alvr_packets::encode_reserved_client_control_packet(
+                    &ReservedClientControlPacket::ProximityState { headset_worn },
+                ))
+                .ok();
+        }
+    }
+
+    // The streamer recenters on the current head pose. Repeated requests are ignored for a second
+    pub fn send_recenter(&self) {
+        if let Some(sender) = &mut *self.connection_context.control_sender.lock() {
+            sender
+                .send(&alvr_packets::encode_reserved_client_control_packet(
+                    &ReservedClientControlPacket::Recenter,
+                ))
+                .ok();
+        }
+    }
+
+    // Answered with ClientCoreEvent::StreamPaused
+    pub fn request_stream_paused(&self, paused: bool) {
+        if let Some(sender) = &mut *self.connection_context.control_sender.lock() {
+            sender
+                .send(&alvr_packets::encode_reserved_client_control_packet(
+                    &ReservedClientControlPacket::SetStreamPaused(paused),
+                ))
+                .ok();
+        }
+    }
+
     pub fn send_custom_interaction_profile(&self, device_id: u64, input_ids: HashSet<u64>) {
         if let Some(sender) = &mut *self.connection_context.control_sender.lock() {
             sender
@@ -206,6 +296,10 @@ impl ClientCoreContext {
     }
 
     pub fn send_buttons(&self, entries: Vec<ButtonEntry>) {
+        if let Some(recorder) = &*self.connection_context.tracking_recorder.lock() {
+            recorder.record(RecordedInput::Buttons(entries.clone()));
+        }
+
         if let Some(sender) = &mut *self.connection_context.control_sender.lock() {
             sender.send(&ClientControlPacket::Buttons(entries)).ok();
         }
@@ -219,6 +313,16 @@ impl ClientCoreContext {
         hand_skeletons: [Option<[Pose; 26]>; 2],
         face_data: FaceData,
     ) {
+        if let Some(recorder) = &*self.connection_context.tracking_recorder.lock() {
+            recorder.record(RecordedInput::Tracking {
+                target_timestamp,
+                views,
+                device_motions: device_motions.clone(),
+                hand_skeletons,
+                face_data: face_data.clone(),
+            });
+        }
+
         let last_ipd = {
             let mut view_params_queue_lock = self.connection_context.view_params_queue.write();
 
@@ -266,7 +370,7 @@ impl ClientCoreContext {
             ));
 
             sender
-                .send_header(&Tracking {
+                .send(Tracking {
                     target_timestamp,
                     device_motions,
                     hand_skeletons,
@@ -296,6 +400,79 @@ impl ClientCoreContext {
         }
     }
 
+    // Returns None until the server sends its statistics, which happens only if the statistics
+    // overlay is enabled
+    // Also returns the packets the client dropped because it was not keeping up
+    pub fn get_overlay_statistics(&self) -> Option<(ClientStatistics, ServerStatistics, usize)> {
+        let server_statistics = self.connection_context.server_statistics.lock().clone()?;
+        let statistics_manager_lock = self.connection_context.statistics_manager.lock();
+        let statistics_manager = statistics_manager_lock.as_ref()?;
+
+        Some((
+            statistics_manager.last_summary()?,
+            server_statistics,
+            statistics_manager.stream_packets_dropped(),
+        ))
+    }
+
+    // The response can be awaited, or waited on with PendingResponse::wait(). Fails with
+    // RequestError::Disconnected while not streaming
+    pub fn send_request(
+        &self,
+        request: ClientRequest,
+        timeout: Duration,
+    ) -> PendingResponse<ServerResponse> {
+        let requests = &self.connection_context.requests;
+        let (id, pending) = requests.begin(timeout);
+
+        let sent = if let Some(sender) = &mut *self.connection_context.control_sender.lock() {
+            sender
+                .send(&alvr_packets::encode_client_request(id, request))
+                .is_ok()
+        } else {
+            false
+        };
+        if !sent {
+            requests.complete(id, Err(RequestError::Disconnected));
+        }
+
+        pending
+    }
+
+    // Returns the encoded SessionConfig of the streamer
+    pub fn request_session(&self, timeout: Duration) -> Result<String, RequestError> {
+        match self.send_request(ClientRequest::Session, timeout).wait()? {
+            ServerResponse::Session(session) => Ok(session),
+            _ => Err(RequestError::UnexpectedResponse),
+        }
+    }
+
+    pub fn request_audio_devices(
+        &self,
+        timeout: Duration,
+    ) -> Result<AudioDevicesList, RequestError> {
+        match self
+            .send_request(ClientRequest::AudioDevices, timeout)
+            .wait()?
+        {
+            ServerResponse::AudioDevices(list) => Ok(list),
+            _ => Err(RequestError::UnexpectedResponse),
+        }
+    }
+
+    pub fn request_statistics_snapshot(
+        &self,
+        timeout: Duration,
+    ) -> Result<ClientStatisticsSnapshot, RequestError> {
+        match self
+            .send_request(ClientRequest::ClientStatisticsSnapshot, timeout)
+            .wait()?
+        {
+            ServerResponse::ClientStatisticsSnapshot(snapshot) => Ok(snapshot),
+            _ => Err(RequestError::UnexpectedResponse),
+        }
+    }
+
     pub fn get_frame(&self) -> Option<DecodedFrame> {
         let mut decoder_source_lock = self.connection_context.decoder_source.lock();
         let decoder_source = decoder_source_lock.as_mut()?;
@@ -303,10 +480,9 @@ impl ClientCoreContext {
         let (frame_timestamp, buffer_ptr) = match decoder_source.get_frame() {
             Ok(maybe_pair) => maybe_pair?,
             Err(e) => {
-                error!("Error getting frame, restarting connection: {}", e);
+                drop(decoder_source_lock);
 
-                // The connection loop observes changes on this value
-                *self.connection_context.state.write() = ConnectionState::Disconnecting;
+                connection::handle_decoder_error(&self.connection_context, true, &format!("{e}"));
 
                 return None;
             }
@@ -354,11 +530,14 @@ impl ClientCoreContext {
 
     pub fn report_submit(&self, target_timestamp: Duration, vsync_queue: Duration) {
         if let Some(stats) = &mut *self.connection_context.statistics_manager.lock() {
-            stats.report_submit(target_timestamp, vsync_queue);
+            let measured = stats.report_submit(target_timestamp, vsync_queue);
 
             if let Some(sender) = &mut *self.connection_context.statistics_sender.lock() {
                 if let Some(stats) = stats.summary(target_timestamp) {
-                    sender.send_header(&stats).ok();
+                    if measured {
+                        // Not urgent, written together with the next packets
+                        sender.queue_header(&stats).ok();
+                    }
                 } else {
                     warn!("Statistics summary not ready!");
                 }
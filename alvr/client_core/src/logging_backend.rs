@@ -6,28 +6,57 @@ use alvr_common::{
 };
 use alvr_packets::ClientControlPacket;
 use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
     sync::mpsc,
     time::{Duration, Instant},
 };
 
 const LOG_REPEAT_TIMEOUT: Duration = Duration::from_secs(1);
 
+// Log lines waiting to be sent to the server. When full, new lines are dropped
+pub const LOG_BUFFER_SIZE: usize = 100;
+
 pub struct LogMirrorData {
-    pub sender: mpsc::Sender<ClientControlPacket>,
+    pub sender: mpsc::SyncSender<ClientControlPacket>,
     pub filter_level: LogSeverity,
+    pub dropped_count: usize,
+}
+
+impl LogMirrorData {
+    fn send(&mut self, packet: ClientControlPacket) {
+        if self.dropped_count > 0 {
+            let dropped_report = ClientControlPacket::Log {
+                level: LogSeverity::Warning,
+                message: format!("{} log lines were not forwarded", self.dropped_count),
+            };
+            if self.sender.try_send(dropped_report).is_err() {
+                self.dropped_count += 1;
+
+                return;
+            }
+
+            self.dropped_count = 0;
+        }
+
+        if self.sender.try_send(packet).is_err() {
+            self.dropped_count += 1;
+        }
+    }
 }
 
 pub static LOG_CHANNEL_SENDER: OptLazy<LogMirrorData> = alvr_common::lazy_mut_none();
 
+// The message itself is moved into the recent logs, only its hash is kept for the comparison
 struct RepeatedLogEvent {
-    message: String,
+    message_hash: u64,
     repetition_times: usize,
     initial_timestamp: Instant,
 }
 
 static LAST_LOG_EVENT: Lazy<Mutex<RepeatedLogEvent>> = Lazy::new(|| {
     Mutex::new(RepeatedLogEvent {
-        message: "".into(),
+        message_hash: 0,
         repetition_times: 0,
         initial_timestamp: Instant::now(),
     })
@@ -35,50 +64,55 @@ static LAST_LOG_EVENT: Lazy<Mutex<RepeatedLogEvent>> = Lazy::new(|| {
 
 pub fn init_logging() {
     fn send_log(record: &Record) {
-        let Some(data) = &*LOG_CHANNEL_SENDER.lock() else {
-            return;
-        };
-
         let level = match record.level() {
             Level::Error => LogSeverity::Error,
             Level::Warn => LogSeverity::Warning,
             Level::Info => LogSeverity::Info,
             _ => LogSeverity::Debug,
         };
-        if level < data.filter_level {
-            return;
+        let message = format!("{}", record.args());
+
+        if let Some(data) = &mut *LOG_CHANNEL_SENDER.lock() {
+            if level >= data.filter_level {
+                forward_log(data, level, &message);
+            }
         }
 
-        let message = format!("{}", record.args());
+        alvr_common::push_recent_log(level, message);
+    }
+
+    fn forward_log(data: &mut LogMirrorData, level: LogSeverity, message: &str) {
+        let mut hasher = DefaultHasher::new();
+        message.hash(&mut hasher);
+        let message_hash = hasher.finish();
 
         let mut last_log_event_lock = LAST_LOG_EVENT.lock();
 
-        if last_log_event_lock.message == message
+        if last_log_event_lock.message_hash == message_hash
             && last_log_event_lock.initial_timestamp + LOG_REPEAT_TIMEOUT > Instant::now()
         {
             last_log_event_lock.repetition_times += 1;
         } else {
             if last_log_event_lock.repetition_times > 1 {
-                data.sender
-                    .send(ClientControlPacket::Log {
-                        level: LogSeverity::Info,
-                        message: format!(
-                            "Last log line repeated {} times",
-                            last_log_event_lock.repetition_times
-                        ),
-                    })
-                    .ok();
+                data.send(ClientControlPacket::Log {
+                    level: LogSeverity::Info,
+                    message: format!(
+                        "Last log line repeated {} times",
+                        last_log_event_lock.repetition_times
+                    ),
+                });
             }
 
             *last_log_event_lock = RepeatedLogEvent {
-                message: message.clone(),
+                message_hash,
                 repetition_times: 1,
                 initial_timestamp: Instant::now(),
             };
 
-            data.sender
-                .send(ClientControlPacket::Log { level, message })
-                .ok();
+            data.send(ClientControlPacket::Log {
+                level,
+                message: message.to_owned(),
+            });
         }
     }
 
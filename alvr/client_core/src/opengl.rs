@@ -7,12 +7,17 @@ use glyph_brush_layout::{
     FontId, GlyphPositioner, HorizontalAlign, Layout, SectionGeometry, SectionText, VerticalAlign,
 };
 
+#[cfg(target_os = "android")]
+use alvr_common::once_cell::sync::Lazy;
+
 #[cfg(target_os = "android")]
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
 
 const HUD_TEXTURE_WIDTH: usize = 1280;
 const HUD_TEXTURE_HEIGHT: usize = 720;
 const FONT_SIZE: f32 = 50_f32;
+const OVERLAY_FONT_SIZE: f32 = 28_f32;
+const OVERLAY_BACKGROUND_ALPHA: f32 = 0.6;
 
 pub struct RenderViewInput {
     pub pose: Pose,
@@ -26,11 +31,44 @@ pub fn initialize() {
         pub static LOBBY_ROOM_GLTF: &[u8] = include_bytes!("../resources/loading.gltf");
         pub static LOBBY_ROOM_BIN: &[u8] = include_bytes!("../resources/buffer.bin");
 
+        // User provided assets, they must outlive the renderers
+        fn read_lobby_asset(file_names: &[&str]) -> Option<Vec<u8>> {
+            let dir = crate::storage::lobby_assets_dir();
+
+            file_names.iter().find_map(|name| {
+                let data = std::fs::read(dir.join(name)).ok()?;
+                (!data.is_empty()).then(|| {
+                    alvr_common::info!("Found custom lobby asset {name}");
+
+                    data
+                })
+            })
+        }
+        static CUSTOM_LOBBY_GLTF: Lazy<Option<Vec<u8>>> =
+            Lazy::new(|| read_lobby_asset(&["scene.gltf"]));
+        static CUSTOM_LOBBY_BIN: Lazy<Option<Vec<u8>>> =
+            Lazy::new(|| read_lobby_asset(&["scene.bin"]));
+        static LOBBY_SKYBOX: Lazy<Option<Vec<u8>>> =
+            Lazy::new(|| read_lobby_asset(&["skybox.png", "skybox.jpg"]));
+
         LOBBY_ROOM_GLTF_PTR = LOBBY_ROOM_GLTF.as_ptr();
         LOBBY_ROOM_GLTF_LEN = LOBBY_ROOM_GLTF.len() as _;
         LOBBY_ROOM_BIN_PTR = LOBBY_ROOM_BIN.as_ptr();
         LOBBY_ROOM_BIN_LEN = LOBBY_ROOM_BIN.len() as _;
 
+        if let Some(gltf) = &*CUSTOM_LOBBY_GLTF {
+            CUSTOM_LOBBY_GLTF_PTR = gltf.as_ptr();
+            CUSTOM_LOBBY_GLTF_LEN = gltf.len() as _;
+        }
+        if let Some(bin) = &*CUSTOM_LOBBY_BIN {
+            CUSTOM_LOBBY_BIN_PTR = bin.as_ptr();
+            CUSTOM_LOBBY_BIN_LEN = bin.len() as _;
+        }
+        if let Some(skybox) = &*LOBBY_SKYBOX {
+            LOBBY_SKYBOX_PTR = skybox.as_ptr();
+            LOBBY_SKYBOX_LEN = skybox.len() as _;
+        }
+
         initGraphicsNative();
     }
 }
@@ -124,32 +162,45 @@ pub fn start_stream(
     }
 }
 
-pub fn update_hud_message(message: &str) {
+// Returns the coverage of each pixel. The text is either centered or aligned to the top left
+// corner with a margin
+fn rasterize_text(
+    message: &str,
+    width: usize,
+    height: usize,
+    font_size: f32,
+    centered: bool,
+) -> Vec<f32> {
     let ubuntu_font =
         FontRef::try_from_slice(include_bytes!("../resources/Ubuntu-Medium.ttf")).unwrap();
 
-    let section_glyphs = Layout::default()
-        .h_align(HorizontalAlign::Center)
-        .v_align(VerticalAlign::Center)
-        .calculate_glyphs(
-            &[&ubuntu_font],
-            &SectionGeometry {
-                screen_position: (
-                    HUD_TEXTURE_WIDTH as f32 / 2_f32,
-                    HUD_TEXTURE_HEIGHT as f32 / 2_f32,
-                ),
-                ..Default::default()
-            },
-            &[SectionText {
-                text: message,
-                scale: FONT_SIZE.into(),
-                font_id: FontId(0),
-            }],
-        );
+    let (layout, screen_position) = if centered {
+        (
+            Layout::default()
+                .h_align(HorizontalAlign::Center)
+                .v_align(VerticalAlign::Center),
+            (width as f32 / 2_f32, height as f32 / 2_f32),
+        )
+    } else {
+        (Layout::default(), (font_size / 2_f32, font_size / 2_f32))
+    };
 
-    let scaled_font = ubuntu_font.as_scaled(FONT_SIZE);
+    let section_glyphs = layout.calculate_glyphs(
+        &[&ubuntu_font],
+        &SectionGeometry {
+            screen_position,
+            ..Default::default()
+        },
+        &[SectionText {
+            text: message,
+            scale: font_size.into(),
+            font_id: FontId(0),
+        }],
+    );
 
-    let mut buffer = vec![0_u8; HUD_TEXTURE_WIDTH * HUD_TEXTURE_HEIGHT * 4];
+    let scaled_font = ubuntu_font.as_scaled(font_size);
+
+    let mut coverage = vec![0_f32; width * height];
 
     for section_glyph in section_glyphs {
         if let Some(outlined) = scaled_font.outline_glyph(section_glyph.glyph) {
@@ -157,17 +208,67 @@ pub fn update_hud_message(message: &str) {
             outlined.draw(|x, y, alpha| {
                 let x = x as usize + bounds.min.x as usize;
                 let y = y as usize + bounds.min.y as usize;
-                buffer[(y * HUD_TEXTURE_WIDTH + x) * 4 + 3] = (alpha * 255.0) as u8;
+                if x < width && y < height {
+                    coverage[y * width + x] = alpha;
+                }
             });
         }
     }
 
+    coverage
+}
+
+pub fn update_hud_message(message: &str) {
+    let coverage = rasterize_text(
+        message,
+        HUD_TEXTURE_WIDTH,
+        HUD_TEXTURE_HEIGHT,
+        FONT_SIZE,
+        true,
+    );
+
+    let mut buffer = vec![0_u8; HUD_TEXTURE_WIDTH * HUD_TEXTURE_HEIGHT * 4];
+    for (pixel, alpha) in buffer.chunks_exact_mut(4).zip(coverage) {
+        pixel[3] = (alpha * 255.0) as u8;
+    }
+
     #[cfg(target_os = "android")]
     unsafe {
         updateLobbyHudTexture(buffer.as_ptr());
     }
 }
 
+// Draws white text over a translucent black background into an RGBA texture of the current
+// context. The alpha is premultiplied.
+pub fn update_overlay_texture(texture: u32, resolution: UVec2, message: &str) {
+    let coverage = rasterize_text(
+        message,
+        resolution.x as usize,
+        resolution.y as usize,
+        OVERLAY_FONT_SIZE,
+        false,
+    );
+
+    let mut buffer = vec![0_u8; coverage.len() * 4];
+    for (pixel, alpha) in buffer.chunks_exact_mut(4).zip(coverage) {
+        let color = (alpha * 255.0) as u8;
+        pixel[0] = color;
+        pixel[1] = color;
+        pixel[2] = color;
+        pixel[3] = ((alpha + (1.0 - alpha) * OVERLAY_BACKGROUND_ALPHA) * 255.0) as u8;
+    }
+
+    #[cfg(target_os = "android")]
+    unsafe {
+        updateOverlayTextureNative(
+            texture,
+            buffer.as_ptr(),
+            resolution.x as _,
+            resolution.y as _,
+        );
+    }
+}
+
 pub fn render_lobby(view_inputs: [RenderViewInput; 2]) {
     #[cfg(target_os = "android")]
     unsafe {
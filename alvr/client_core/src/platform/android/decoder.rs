@@ -19,7 +19,7 @@ use std::{
     collections::VecDeque,
     ffi::c_void,
     ops::Deref,
-    ptr,
+    ptr, slice,
     sync::Arc,
     thread::{self, JoinHandle},
     time::Duration,
@@ -76,6 +76,52 @@ impl VideoDecoderSink {
             Err(e) => bail!("{e}"),
         }
     }
+
+    // Like push_frame_nal(), but the NAL is written into the input buffer by write_nal(), which
+    // returns its size. Returns false if nothing was written, write_nal() is not called if no input
+    // buffer of at least max_size bytes is available
+    pub fn push_frame_nal_with(
+        &mut self,
+        timestamp: Duration,
+        max_size: usize,
+        write_nal: impl FnOnce(&mut [u8]) -> usize,
+    ) -> Result<bool> {
+        let Some(decoder) = &*self.inner.lock() else {
+            return Ok(false);
+        };
+
+        match decoder.dequeue_input_buffer(Duration::ZERO) {
+            Ok(DequeuedInputBufferResult::Buffer(mut buffer)) => {
+                let input_buffer = buffer.buffer_mut();
+                let size = if input_buffer.len() >= max_size {
+                    // Safety: the buffer is only written to
+                    write_nal(unsafe {
+                        slice::from_raw_parts_mut(
+                            input_buffer.as_mut_ptr().cast(),
+                            input_buffer.len(),
+                        )
+                    })
+                } else {
+                    0
+                };
+
+                // An empty buffer is returned to the decoder if nothing was written
+                decoder.queue_input_buffer(buffer, 0, size, timestamp.as_nanos() as _, 0)?;
+
+                Ok(size > 0)
+            }
+            Ok(DequeuedInputBufferResult::TryAgainLater) => Ok(false),
+            Err(e) => bail!("{e}"),
+        }
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        if let Some(decoder) = &*self.inner.lock() {
+            decoder.flush()?;
+        }
+
+        Ok(())
+    }
 }
 
 struct QueuedImage {
@@ -3,6 +3,7 @@ mod decoder;
 pub use decoder::*;
 
 use alvr_common::warn;
+use alvr_packets::ThermalStatus;
 use jni::{objects::JObject, sys::jobject, JNIEnv, JavaVM};
 use std::net::{IpAddr, Ipv4Addr};
 
@@ -194,6 +195,73 @@ pub fn set_wifi_lock(enabled: bool) {
     // set_lock(&mut env, &multicast_lock, enabled);
 }
 
+// Volume of the media stream, which is the one changed by the headset volume buttons
+pub fn get_media_volume() -> f32 {
+    const STREAM_MUSIC: i32 = 3;
+
+    let vm = vm();
+    let mut env = vm.attach_current_thread().unwrap();
+
+    let audio_manager = get_system_service(&mut env, "audio");
+    let volume = env
+        .call_method(
+            &audio_manager,
+            "getStreamVolume",
+            "(I)I",
+            &[STREAM_MUSIC.into()],
+        )
+        .unwrap()
+        .i()
+        .unwrap();
+    let max_volume = env
+        .call_method(
+            &audio_manager,
+            "getStreamMaxVolume",
+            "(I)I",
+            &[STREAM_MUSIC.into()],
+        )
+        .unwrap()
+        .i()
+        .unwrap();
+
+    if max_volume > 0 {
+        volume as f32 / max_volume as f32
+    } else {
+        1.0
+    }
+}
+
+// None below API level 29, or if the status cannot be read
+pub fn get_thermal_status() -> Option<ThermalStatus> {
+    if get_api_level() < 29 {
+        return None;
+    }
+
+    let vm = vm();
+    let mut env = vm.attach_current_thread().ok()?;
+
+    let power_manager = get_system_service(&mut env, "power");
+    let Ok(status) = env
+        .call_method(power_manager, "getCurrentThermalStatus", "()I", &[])
+        .and_then(|value| value.i())
+    else {
+        // Otherwise the next JNI calls of this thread fail
+        env.exception_clear().ok();
+
+        return None;
+    };
+
+    Some(match status {
+        0 => ThermalStatus::None,
+        1 => ThermalStatus::Light,
+        2 => ThermalStatus::Moderate,
+        3 => ThermalStatus::Severe,
+        4 => ThermalStatus::Critical,
+        5 => ThermalStatus::Emergency,
+        _ => ThermalStatus::Shutdown,
+    })
+}
+
 pub fn get_battery_status() -> (f32, bool) {
     let vm = vm();
     let mut env = vm.attach_current_thread().unwrap();
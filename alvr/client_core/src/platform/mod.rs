@@ -106,6 +106,18 @@ pub fn platform() -> Platform {
     }
 }
 
+// Sent to the server together with the platform name
+pub fn manufacturer() -> Option<String> {
+    #[cfg(target_os = "android")]
+    {
+        Some(android::manufacturer_name())
+    }
+    #[cfg(not(target_os = "android"))]
+    {
+        None
+    }
+}
+
 #[cfg(not(target_os = "android"))]
 pub fn local_ip() -> std::net::IpAddr {
     use std::net::{IpAddr, Ipv4Addr};
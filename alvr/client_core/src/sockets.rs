@@ -1,28 +1,91 @@
-use alvr_common::{anyhow::Result, ALVR_NAME};
-use alvr_sockets::{CONTROL_PORT, LOCAL_IP};
-use std::net::{Ipv4Addr, UdpSocket};
+use alvr_common::{anyhow::Result, debug, ALVR_NAME, DEFAULT_CONTROL_PORT};
+use alvr_sockets::{HANDSHAKE_PACKET_SIZE_BYTES, LOCAL_IP};
+use if_addrs::IfAddr;
+use std::net::{IpAddr, Ipv4Addr, UdpSocket};
+
+// Directed broadcast addresses of the IPv4 interfaces that are up. Listed again at every call, so
+// that interfaces that appear or disappear are followed. Empty if the interfaces cannot be listed
+fn interface_broadcast_addresses() -> Vec<Ipv4Addr> {
+    let interfaces = match if_addrs::get_if_addrs() {
+        Ok(interfaces) => interfaces,
+        Err(e) => {
+            debug!("Couldn't list the network interfaces: {e}");
+            return vec![];
+        }
+    };
+
+    let mut addresses = vec![];
+    for interface in interfaces {
+        if interface.is_loopback() {
+            continue;
+        }
+
+        if let IfAddr::V4(addr) = interface.addr {
+            let broadcast = Ipv4Addr::from(u32::from(addr.ip) | !u32::from(addr.netmask));
+
+            // Point to point links have no broadcast address
+            if broadcast != addr.ip
+                && broadcast != Ipv4Addr::BROADCAST
+                && !addresses.contains(&broadcast)
+            {
+                addresses.push(broadcast);
+            }
+        }
+    }
+
+    addresses
+}
 
 pub struct AnnouncerSocket {
     socket: UdpSocket,
-    packet: [u8; 56],
+    packet: [u8; HANDSHAKE_PACKET_SIZE_BYTES],
 }
 
 impl AnnouncerSocket {
-    pub fn new(hostname: &str) -> Result<Self> {
-        let socket = UdpSocket::bind((LOCAL_IP, CONTROL_PORT))?;
+    // The packets are always sent to the default control port, where every streamer listens. They
+    // carry the control port the streamer connects to
+    pub fn new(hostname: &str, control_port: u16) -> Result<Self> {
+        let socket = UdpSocket::bind((LOCAL_IP, 0))?;
         socket.set_broadcast(true)?;
 
-        let mut packet = [0; 56];
+        let mut packet = [0; HANDSHAKE_PACKET_SIZE_BYTES];
         packet[0..ALVR_NAME.len()].copy_from_slice(ALVR_NAME.as_bytes());
-        packet[16..24].copy_from_slice(&alvr_common::protocol_id_u64().to_le_bytes());
+        packet[16..24].copy_from_slice(&alvr_packets::protocol_id_u64().to_le_bytes());
         packet[24..24 + hostname.len()].copy_from_slice(hostname.as_bytes());
+        packet[56..58].copy_from_slice(&control_port.to_le_bytes());
 
         Ok(Self { socket, packet })
     }
 
+    // The limited broadcast is sent out of one interface only, chosen by the OS. The directed
+    // broadcasts reach the other networks, like USB tethering next to Wi-Fi. Fails only if no
+    // packet could be sent
     pub fn announce_broadcast(&self) -> Result<()> {
+        let limited_res = self
+            .socket
+            .send_to(&self.packet, (Ipv4Addr::BROADCAST, DEFAULT_CONTROL_PORT));
+
+        let mut any_directed_sent = false;
+        for address in interface_broadcast_addresses() {
+            match self
+                .socket
+                .send_to(&self.packet, (address, DEFAULT_CONTROL_PORT))
+            {
+                Ok(_) => any_directed_sent = true,
+                Err(e) => debug!("Couldn't announce to {address}: {e}"),
+            }
+        }
+
+        if !any_directed_sent {
+            limited_res?;
+        }
+
+        Ok(())
+    }
+
+    pub fn announce_unicast(&self, ip: IpAddr) -> Result<()> {
         self.socket
-            .send_to(&self.packet, (Ipv4Addr::BROADCAST, CONTROL_PORT))?;
+            .send_to(&self.packet, (ip, DEFAULT_CONTROL_PORT))?;
 
         Ok(())
     }
@@ -9,6 +9,10 @@ struct HistoryFrame {
     input_acquired: Instant,
     video_packet_received: Instant,
     client_stats: ClientStatistics,
+    // While tracking is sent at a reduced rate, consecutive frames share the same target timestamp.
+    // Only the first of them is measured, the others would only report the age of the pose
+    video_received: bool,
+    submitted: bool,
 }
 
 pub struct StatisticsManager {
@@ -17,6 +21,8 @@ pub struct StatisticsManager {
     prev_vsync: Instant,
     total_pipeline_latency_average: SlidingWindowAverage<Duration>,
     steamvr_pipeline_latency: Duration,
+    // Packets discarded by the overflow policy of the receive streams
+    stream_packets_dropped: usize,
 }
 
 impl StatisticsManager {
@@ -36,9 +42,16 @@ impl StatisticsManager {
             steamvr_pipeline_latency: Duration::from_secs_f32(
                 steamvr_pipeline_frames * nominal_server_frame_interval.as_secs_f32(),
             ),
+            stream_packets_dropped: 0,
         }
     }
 
+    fn pending_frame_mut(&mut self, target_timestamp: Duration) -> Option<&mut HistoryFrame> {
+        self.history_buffer.iter_mut().find(|frame| {
+            frame.client_stats.target_timestamp == target_timestamp && !frame.submitted
+        })
+    }
+
     pub fn report_input_acquired(&mut self, target_timestamp: Duration) {
         if !self
             .history_buffer
@@ -53,6 +66,8 @@ impl StatisticsManager {
                     target_timestamp,
                     ..Default::default()
                 },
+                video_received: false,
+                submitted: false,
             });
         }
 
@@ -62,50 +77,50 @@ impl StatisticsManager {
     }
 
     pub fn report_video_packet_received(&mut self, target_timestamp: Duration) {
-        if let Some(frame) = self
-            .history_buffer
-            .iter_mut()
-            .find(|frame| frame.client_stats.target_timestamp == target_timestamp)
-        {
-            frame.video_packet_received = Instant::now();
+        if let Some(frame) = self.pending_frame_mut(target_timestamp) {
+            if !frame.video_received {
+                frame.video_packet_received = Instant::now();
+                frame.video_received = true;
+            }
+        }
+    }
+
+    pub fn report_jitter_buffer_released(&mut self, target_timestamp: Duration, hold: Duration) {
+        if let Some(frame) = self.pending_frame_mut(target_timestamp) {
+            frame.client_stats.video_jitter_buffer = hold;
         }
     }
 
     pub fn report_frame_decoded(&mut self, target_timestamp: Duration) {
-        if let Some(frame) = self
-            .history_buffer
-            .iter_mut()
-            .find(|frame| frame.client_stats.target_timestamp == target_timestamp)
-        {
-            frame.client_stats.video_decode =
-                Instant::now().saturating_duration_since(frame.video_packet_received);
+        if let Some(frame) = self.pending_frame_mut(target_timestamp) {
+            frame.client_stats.video_decode = Instant::now().saturating_duration_since(
+                frame.video_packet_received + frame.client_stats.video_jitter_buffer,
+            );
         }
     }
 
     pub fn report_compositor_start(&mut self, target_timestamp: Duration) {
-        if let Some(frame) = self
-            .history_buffer
-            .iter_mut()
-            .find(|frame| frame.client_stats.target_timestamp == target_timestamp)
-        {
+        if let Some(frame) = self.pending_frame_mut(target_timestamp) {
             frame.client_stats.video_decoder_queue = Instant::now().saturating_duration_since(
-                frame.video_packet_received + frame.client_stats.video_decode,
+                frame.video_packet_received
+                    + frame.client_stats.video_jitter_buffer
+                    + frame.client_stats.video_decode,
             );
         }
     }
 
     // vsync_queue is the latency between this call and the vsync. it cannot be measured by ALVR and
-    // should be reported by the VR runtime
-    pub fn report_submit(&mut self, target_timestamp: Duration, vsync_queue: Duration) {
+    // should be reported by the VR runtime. Returns false if the frame was not measured
+    pub fn report_submit(&mut self, target_timestamp: Duration, vsync_queue: Duration) -> bool {
         let now = Instant::now();
+        let vsync = now + vsync_queue;
+
+        if let Some(frame) = self.pending_frame_mut(target_timestamp) {
+            frame.submitted = true;
 
-        if let Some(frame) = self
-            .history_buffer
-            .iter_mut()
-            .find(|frame| frame.client_stats.target_timestamp == target_timestamp)
-        {
             frame.client_stats.rendering = now.saturating_duration_since(
                 frame.video_packet_received
+                    + frame.client_stats.video_jitter_buffer
                     + frame.client_stats.video_decode
                     + frame.client_stats.video_decoder_queue,
             );
@@ -115,12 +130,21 @@ impl StatisticsManager {
             self.total_pipeline_latency_average
                 .submit_sample(frame.client_stats.total_pipeline_latency);
 
-            let vsync = now + vsync_queue;
             frame.client_stats.frame_interval = vsync.saturating_duration_since(self.prev_vsync);
             self.prev_vsync = vsync;
+
+            true
+        } else {
+            self.prev_vsync = vsync;
+
+            false
         }
     }
 
+    pub fn report_stream_packets_dropped(&mut self, count: usize) {
+        self.stream_packets_dropped += count;
+    }
+
     pub fn summary(&self, target_timestamp: Duration) -> Option<ClientStatistics> {
         self.history_buffer
             .iter()
@@ -128,6 +152,14 @@ impl StatisticsManager {
             .map(|frame| frame.client_stats.clone())
     }
 
+    // Statistics of the most recent frame that has been submitted
+    pub fn last_summary(&self) -> Option<ClientStatistics> {
+        self.history_buffer
+            .iter()
+            .find(|frame| frame.client_stats.total_pipeline_latency != Duration::ZERO)
+            .map(|frame| frame.client_stats.clone())
+    }
+
     // latency used for head prediction
     pub fn average_total_pipeline_latency(&self) -> Duration {
         self.total_pipeline_latency_average.get_average()
@@ -139,4 +171,8 @@ impl StatisticsManager {
             .get_average()
             .saturating_sub(self.steamvr_pipeline_latency)
     }
+
+    pub fn stream_packets_dropped(&self) -> usize {
+        self.stream_packets_dropped
+    }
 }
@@ -4,7 +4,7 @@ use rand::Rng;
 use serde::{Deserialize, Serialize};
 use std::{fs, path::PathBuf};
 
-fn config_path() -> PathBuf {
+fn config_dir() -> PathBuf {
     app_dirs2::app_root(
         AppDataType::UserConfig,
         &AppInfo {
@@ -13,13 +13,46 @@ fn config_path() -> PathBuf {
         },
     )
     .unwrap()
-    .join("session.json")
+}
+
+fn config_path() -> PathBuf {
+    config_dir().join("session.json")
+}
+
+// Written by the tracking recorder, see tracking_recording.rs
+pub fn tracking_recordings_dir() -> PathBuf {
+    config_dir().join("recordings")
+}
+
+// User provided lobby environment: skybox.png or skybox.jpg (equirectangular), scene.gltf and
+// scene.bin
+#[cfg(target_os = "android")]
+pub fn lobby_assets_dir() -> PathBuf {
+    config_dir().join("lobby")
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct Config {
     pub hostname: String,
     pub protocol_id: String,
+    // Fixed discovery broadcast interval, replaces the default backoff. Useful to debug discovery
+    #[serde(default)]
+    pub discovery_broadcast_interval_ms: Option<u64>,
+    // Last volume set with the headset buttons, applied to the game audio
+    #[serde(default = "default_game_audio_gain")]
+    pub game_audio_gain: f32,
+    // Control port of the last streamer, announced in the discovery packets and listened on for
+    // the control connection. The discovery itself always uses DEFAULT_CONTROL_PORT
+    #[serde(default = "default_control_port")]
+    pub control_port: u16,
+}
+
+fn default_game_audio_gain() -> f32 {
+    1.0
+}
+
+fn default_control_port() -> u16 {
+    alvr_common::DEFAULT_CONTROL_PORT
 }
 
 impl Default for Config {
@@ -34,7 +67,10 @@ impl Default for Config {
                 rng.gen_range(0..10),
                 rng.gen_range(0..10),
             ),
-            protocol_id: alvr_common::protocol_id(),
+            protocol_id: alvr_packets::protocol_id(),
+            discovery_broadcast_interval_ms: None,
+            game_audio_gain: 1.0,
+            control_port: alvr_common::DEFAULT_CONTROL_PORT,
         }
     }
 }
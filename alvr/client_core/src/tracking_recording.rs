@@ -0,0 +1,284 @@
+// Debug tool to test the tracking path of the streamer without wearing the headset. The recorder
+// writes the inputs sent by the client to a file, one JSON object per line, after a header with
+// the capabilities of the headset. The replay connects like a normal client with the recorded
+// capabilities, then sends the inputs again with the original pacing.
+use crate::{ClientCapabilities, ClientCoreContext};
+use alvr_common::{anyhow::Result, error, info, warn, DeviceMotion, Pose, RelaxedAtomic};
+use alvr_packets::{ButtonEntry, FaceData, ViewParams};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, SyncSender, TrySendError},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+const CHANNEL_CAPACITY: usize = 256;
+
+#[derive(Serialize, Deserialize)]
+struct RecordingHeader {
+    capabilities: ClientCapabilities,
+}
+
+// The arguments of ClientCoreContext::send_tracking() and send_buttons()
+#[derive(Serialize, Deserialize)]
+pub enum RecordedInput {
+    Tracking {
+        target_timestamp: Duration,
+        views: [ViewParams; 2],
+        device_motions: Vec<(u64, DeviceMotion)>,
+        hand_skeletons: [Option<[Pose; 26]>; 2],
+        face_data: FaceData,
+    },
+    Buttons(Vec<ButtonEntry>),
+}
+
+#[derive(Serialize, Deserialize)]
+struct RecordedEntry {
+    // Since the start of the recording
+    time_offset: Duration,
+    input: RecordedInput,
+}
+
+// The file is written by a separate thread, inputs are dropped if it does not keep up. The queued
+// inputs are written before the recorder is dropped
+pub struct TrackingRecorder {
+    start: Instant,
+    sender: Option<SyncSender<RecordedEntry>>,
+    writer: Option<JoinHandle<()>>,
+    dropped_entries: Arc<AtomicUsize>,
+}
+
+impl TrackingRecorder {
+    pub fn new(path: &Path, capabilities: ClientCapabilities) -> Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut file, &RecordingHeader { capabilities })?;
+        writeln!(file)?;
+
+        let (sender, receiver) = mpsc::sync_channel::<RecordedEntry>(CHANNEL_CAPACITY);
+        let writer = thread::spawn(move || {
+            let mut write_entries = || -> Result<()> {
+                while let Ok(entry) = receiver.recv() {
+                    let mut entry = Some(entry);
+                    while let Some(entry) = entry.take().or_else(|| receiver.try_recv().ok()) {
+                        serde_json::to_writer(&mut file, &entry)?;
+                        writeln!(file)?;
+                    }
+
+                    // Flushed whenever the queue is empty, so that the recording survives a crash
+                    file.flush()?;
+                }
+
+                Ok(())
+            };
+
+            if let Err(e) = write_entries() {
+                error!("Failed to write the tracking recording: {e}");
+            }
+        });
+
+        info!("Recording the tracking to {}", path.display());
+
+        Ok(Self {
+            start: Instant::now(),
+            sender: Some(sender),
+            writer: Some(writer),
+            dropped_entries: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    pub fn record(&self, input: RecordedInput) {
+        let entry = RecordedEntry {
+            time_offset: self.start.elapsed(),
+            input,
+        };
+
+        if let Some(Err(TrySendError::Full(_))) =
+            self.sender.as_ref().map(|sender| sender.try_send(entry))
+        {
+            self.dropped_entries.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl Drop for TrackingRecorder {
+    fn drop(&mut self) {
+        // The writer thread exits once the channel is closed
+        self.sender.take();
+        if let Some(writer) = self.writer.take() {
+            writer.join().ok();
+        }
+
+        let dropped_entries = self.dropped_entries.load(Ordering::Relaxed);
+        if dropped_entries > 0 {
+            warn!("Tracking recording stopped, {dropped_entries} inputs were dropped");
+        }
+    }
+}
+
+pub struct TrackingReplay {
+    capabilities: ClientCapabilities,
+    entries: Vec<RecordedEntry>,
+}
+
+impl TrackingReplay {
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut lines = BufReader::new(File::open(path)?).lines();
+
+        let header: RecordingHeader =
+            serde_json::from_str(&lines.next().transpose()?.unwrap_or_default())?;
+
+        // A recording interrupted by a crash can end with a partial line
+        let entries = lines
+            .map_while(|line| serde_json::from_str(&line.ok()?).ok())
+            .collect();
+
+        Ok(Self {
+            capabilities: header.capabilities,
+            entries,
+        })
+    }
+
+    // Used to create the ClientCoreContext, so that the streamer negotiates the stream like for the
+    // recorded headset
+    pub fn capabilities(&self) -> ClientCapabilities {
+        self.capabilities.clone()
+    }
+
+    // Loops over the recording while running is set. The target timestamps keep the recorded
+    // spacing but are rebased on timestamp_origin, like the ones of a live client
+    pub fn run(
+        &self,
+        context: &ClientCoreContext,
+        running: &RelaxedAtomic,
+        timestamp_origin: Instant,
+    ) {
+        let Some(first_target_timestamp) = self.entries.iter().find_map(|entry| {
+            if let RecordedInput::Tracking {
+                target_timestamp, ..
+            } = &entry.input
+            {
+                Some(*target_timestamp)
+            } else {
+                None
+            }
+        }) else {
+            warn!("The tracking recording is empty");
+            return;
+        };
+        let first_time_offset = self.entries[0].time_offset;
+
+        while running.value() {
+            let loop_start = Instant::now();
+            let timestamp_base =
+                loop_start - timestamp_origin + context.get_head_prediction_offset();
+
+            for entry in &self.entries {
+                if !running.value() {
+                    return;
+                }
+
+                thread::sleep(
+                    (loop_start + entry.time_offset.saturating_sub(first_time_offset))
+                        .saturating_duration_since(Instant::now()),
+                );
+
+                match &entry.input {
+                    RecordedInput::Tracking {
+                        target_timestamp,
+                        views,
+                        device_motions,
+                        hand_skeletons,
+                        face_data,
+                    } => context.send_tracking(
+                        timestamp_base + target_timestamp.saturating_sub(first_target_timestamp),
+                        *views,
+                        device_motions.clone(),
+                        *hand_skeletons,
+                        face_data.clone(),
+                    ),
+                    RecordedInput::Buttons(entries) => context.send_buttons(entries.clone()),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alvr_common::glam::UVec2;
+    use alvr_packets::ButtonValue;
+    use std::fs::OpenOptions;
+
+    fn capabilities() -> ClientCapabilities {
+        ClientCapabilities {
+            default_view_resolution: UVec2::new(1832, 1920),
+            external_decoder: false,
+            refresh_rates: vec![72.0, 90.0],
+            foveated_encoding: true,
+            encoder_high_profile: false,
+            encoder_10_bits: false,
+            encoder_av1: false,
+            multi_slice_decoding: false,
+            haptics_envelopes: false,
+            device_model: Some("Quest 2".into()),
+        }
+    }
+
+    #[test]
+    fn test_recording_round_trip() {
+        let path = std::env::temp_dir().join(format!(
+            "alvr_tracking_recording_{}.jsonl",
+            std::process::id()
+        ));
+
+        let recorder = TrackingRecorder::new(&path, capabilities()).unwrap();
+        recorder.record(RecordedInput::Tracking {
+            target_timestamp: Duration::from_millis(10),
+            views: [ViewParams::default(); 2],
+            device_motions: vec![],
+            hand_skeletons: [None, None],
+            face_data: FaceData::default(),
+        });
+        recorder.record(RecordedInput::Buttons(vec![ButtonEntry {
+            path_id: 1,
+            value: ButtonValue::Binary(true),
+        }]));
+        drop(recorder);
+
+        // Simulate a crash while writing the next input
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        write!(file, "{{\"time_offset\":").unwrap();
+        drop(file);
+
+        let replay = TrackingReplay::load(&path).unwrap();
+        assert_eq!(
+            replay.capabilities().device_model.as_deref(),
+            Some("Quest 2")
+        );
+        assert_eq!(replay.entries.len(), 2);
+        assert!(matches!(
+            replay.entries[0].input,
+            RecordedInput::Tracking { target_timestamp, .. }
+                if target_timestamp == Duration::from_millis(10)
+        ));
+        assert!(matches!(
+            &replay.entries[1].input,
+            RecordedInput::Buttons(entries) if entries.len() == 1 && entries[0].path_id == 1
+        ));
+        assert!(replay.entries[0].time_offset <= replay.entries[1].time_offset);
+
+        fs::remove_file(path).ok();
+    }
+}
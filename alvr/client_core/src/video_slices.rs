@@ -0,0 +1,177 @@
+use alvr_packets::VideoPacketHeader;
+use std::{collections::BTreeMap, time::Duration};
+
+// Incomplete frames older than this are discarded even if no newer frame has completed yet
+const MAX_PENDING_FRAMES: usize = 8;
+
+struct PendingFrame {
+    is_idr: bool,
+    had_packet_loss: bool,
+    slices: Vec<Option<Vec<u8>>>,
+    received_count: usize,
+}
+
+pub struct ReceivedFrame {
+    pub timestamp: Duration,
+    pub is_idr: bool,
+    // Either some slices of this frame or whole previous frames have been lost
+    pub had_packet_loss: bool,
+    pub nal: Vec<u8>,
+}
+
+// Collects the slices of each frame and releases complete frames in timestamp order. Slices of
+// different frames can be interleaved.
+#[derive(Default)]
+pub struct SliceReassembler {
+    pending_frames: BTreeMap<Duration, PendingFrame>,
+    last_released_timestamp: Option<Duration>,
+    frames_dropped: bool,
+}
+
+impl SliceReassembler {
+    pub fn push(
+        &mut self,
+        header: &VideoPacketHeader,
+        slice: &[u8],
+        had_packet_loss: bool,
+    ) -> Vec<ReceivedFrame> {
+        if self
+            .last_released_timestamp
+            .map(|timestamp| header.timestamp <= timestamp)
+            .unwrap_or(false)
+        {
+            // The frame was already released or discarded
+            return vec![];
+        }
+
+        let frame = self
+            .pending_frames
+            .entry(header.timestamp)
+            .or_insert_with(|| PendingFrame {
+                is_idr: header.is_idr,
+                had_packet_loss: false,
+                slices: vec![None; header.slice_count as usize],
+                received_count: 0,
+            });
+        frame.had_packet_loss |= had_packet_loss;
+
+        if let Some(entry @ None) = frame.slices.get_mut(header.slice_index as usize) {
+            *entry = Some(slice.to_vec());
+            frame.received_count += 1;
+        }
+
+        while self.pending_frames.len() > MAX_PENDING_FRAMES {
+            self.pending_frames.pop_first();
+            self.frames_dropped = true;
+        }
+
+        let mut frames = vec![];
+        while let Some(timestamp) = self
+            .pending_frames
+            .iter()
+            .find(|(_, frame)| frame.received_count == frame.slices.len())
+            .map(|(timestamp, _)| *timestamp)
+        {
+            // Older incomplete frames cannot be decoded after a newer frame
+            while let Some((&oldest_timestamp, _)) = self.pending_frames.first_key_value() {
+                if oldest_timestamp == timestamp {
+                    break;
+                }
+                self.pending_frames.pop_first();
+                self.frames_dropped = true;
+            }
+
+            let frame = self.pending_frames.remove(&timestamp).unwrap();
+            frames.push(ReceivedFrame {
+                timestamp,
+                is_idr: frame.is_idr,
+                had_packet_loss: frame.had_packet_loss || self.frames_dropped,
+                nal: frame.slices.into_iter().flatten().flatten().collect(),
+            });
+
+            self.last_released_timestamp = Some(timestamp);
+            self.frames_dropped = false;
+        }
+
+        frames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push(
+        reassembler: &mut SliceReassembler,
+        timestamp_ms: u64,
+        slice_index: u32,
+        slice_count: u32,
+        slice: &[u8],
+    ) -> Vec<ReceivedFrame> {
+        let header = VideoPacketHeader {
+            timestamp: Duration::from_millis(timestamp_ms),
+            is_idr: false,
+            slice_index,
+            slice_count,
+        };
+
+        reassembler.push(&header, slice, false)
+    }
+
+    #[test]
+    fn test_out_of_order_slices() {
+        let mut reassembler = SliceReassembler::default();
+
+        assert!(push(&mut reassembler, 1, 2, 3, b"c").is_empty());
+        // Slices of the next frame are interleaved
+        assert!(push(&mut reassembler, 2, 1, 2, b"e").is_empty());
+        assert!(push(&mut reassembler, 1, 0, 3, b"a").is_empty());
+
+        let frames = push(&mut reassembler, 1, 1, 3, b"b");
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].timestamp, Duration::from_millis(1));
+        assert_eq!(frames[0].nal, b"abc");
+        assert!(!frames[0].had_packet_loss);
+
+        let frames = push(&mut reassembler, 2, 0, 2, b"d");
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].nal, b"de");
+        assert!(!frames[0].had_packet_loss);
+    }
+
+    #[test]
+    fn test_missing_slice() {
+        let mut reassembler = SliceReassembler::default();
+
+        // The second slice of frame 1 is lost
+        assert!(push(&mut reassembler, 1, 0, 2, b"a").is_empty());
+        assert!(push(&mut reassembler, 2, 0, 2, b"c").is_empty());
+
+        let frames = push(&mut reassembler, 2, 1, 2, b"d");
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].timestamp, Duration::from_millis(2));
+        assert!(frames[0].had_packet_loss);
+
+        // It arrives after a newer frame was released
+        assert!(push(&mut reassembler, 1, 1, 2, b"b").is_empty());
+
+        let frames = push(&mut reassembler, 3, 0, 1, b"e");
+        assert_eq!(frames.len(), 1);
+        assert!(!frames[0].had_packet_loss);
+    }
+
+    #[test]
+    fn test_duplicate_slices() {
+        let mut reassembler = SliceReassembler::default();
+
+        assert!(push(&mut reassembler, 1, 0, 2, b"a").is_empty());
+        assert!(push(&mut reassembler, 1, 0, 2, b"x").is_empty());
+
+        let frames = push(&mut reassembler, 1, 1, 2, b"b");
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].nal, b"ab");
+
+        // Duplicates of a released frame are ignored too
+        assert!(push(&mut reassembler, 1, 1, 2, b"b").is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use alvr_client_core::{ClientCapabilities, ClientCoreContext, ClientCoreEvent};
+use alvr_client_core::{ClientCapabilities, ClientCoreContext, ClientCoreEvent, TrackingReplay};
 use alvr_common::{
     glam::{Quat, UVec2, Vec3},
     parking_lot::RwLock,
@@ -11,6 +11,7 @@ use eframe::{
     Frame, NativeOptions,
 };
 use std::{
+    env,
     f32::consts::{FRAC_PI_2, PI},
     sync::{
         mpsc::{self, TryRecvError},
@@ -57,6 +58,8 @@ struct WindowOutput {
     resolution: UVec2,
     decoder_codec: Option<CodecType>,
     current_frame_timestamp: Duration,
+    // Set while the streamer waits for the pairing code
+    pairing_attempts_left: Option<u32>,
 }
 
 impl Default for WindowOutput {
@@ -68,6 +71,7 @@ impl Default for WindowOutput {
             resolution: UVec2::ZERO,
             decoder_codec: None,
             current_frame_timestamp: Duration::ZERO,
+            pairing_attempts_left: None,
         }
     }
 }
@@ -77,18 +81,23 @@ pub struct Window {
     input_sender: mpsc::Sender<WindowInput>,
     output: WindowOutput,
     output_receiver: mpsc::Receiver<WindowOutput>,
+    pairing_code: String,
+    pairing_code_sender: mpsc::Sender<String>,
 }
 
 impl Window {
     fn new(
         input_sender: mpsc::Sender<WindowInput>,
         output_receiver: mpsc::Receiver<WindowOutput>,
+        pairing_code_sender: mpsc::Sender<String>,
     ) -> Self {
         Self {
             input: WindowInput::default(),
             input_sender,
             output: WindowOutput::default(),
             output_receiver,
+            pairing_code: String::new(),
+            pairing_code_sender,
         }
     }
 }
@@ -105,6 +114,18 @@ impl eframe::App for Window {
             ui.vertical_centered(|ui| {
                 ui.heading(RichText::new(&self.output.hud_message));
             });
+            if let Some(attempts_left) = self.output.pairing_attempts_left {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Pairing code ({attempts_left} attempts left):"));
+                    ui.text_edit_singleline(&mut self.pairing_code);
+                    if ui.button("Submit").clicked() {
+                        self.pairing_code_sender
+                            .send(self.pairing_code.trim().to_owned())
+                            .ok();
+                        self.pairing_code.clear();
+                    }
+                });
+            }
             ui.label(format!("FPS: {}", self.output.fps));
             ui.label(format!("Connected: {}", self.output.connected));
             ui.label(format!("View resolution: {}", self.output.resolution));
@@ -235,15 +256,28 @@ fn tracking_thread(
 fn client_thread(
     output_sender: mpsc::Sender<WindowOutput>,
     input_receiver: mpsc::Receiver<WindowInput>,
+    pairing_code_receiver: mpsc::Receiver<String>,
+    replay: Option<Arc<TrackingReplay>>,
 ) {
-    let capabilities = ClientCapabilities {
-        default_view_resolution: UVec2::new(1920, 1832),
-        external_decoder: true,
-        refresh_rates: vec![60.0, 72.0, 80.0, 90.0, 120.0],
-        foveated_encoding: false,
-        encoder_high_profile: false,
-        encoder_10_bits: false,
-        encoder_av1: false,
+    // A replay connects with the capabilities of the recorded headset. The video is not decoded
+    let capabilities = if let Some(replay) = &replay {
+        ClientCapabilities {
+            external_decoder: true,
+            ..replay.capabilities()
+        }
+    } else {
+        ClientCapabilities {
+            default_view_resolution: UVec2::new(1920, 1832),
+            external_decoder: true,
+            refresh_rates: vec![60.0, 72.0, 80.0, 90.0, 120.0],
+            foveated_encoding: false,
+            encoder_high_profile: false,
+            encoder_10_bits: false,
+            encoder_av1: false,
+            multi_slice_decoding: false,
+            haptics_envelopes: false,
+            device_model: None,
+        }
     };
     let client_core_context = Arc::new(ClientCoreContext::new(capabilities));
 
@@ -263,6 +297,10 @@ fn client_thread(
             match event {
                 ClientCoreEvent::UpdateHudMessage(message) => {
                     window_output.hud_message = message;
+                    window_output.pairing_attempts_left = None;
+                }
+                ClientCoreEvent::PairingCodeRequested { attempts_left } => {
+                    window_output.pairing_attempts_left = Some(attempts_left);
                 }
                 ClientCoreEvent::StreamingStarted {
                     negotiated_config, ..
@@ -274,13 +312,18 @@ fn client_thread(
                     let context = Arc::clone(&client_core_context);
                     let streaming = Arc::clone(&streaming);
                     let input = Arc::clone(&window_input);
+                    let replay = replay.clone();
                     maybe_tracking_thread = Some(thread::spawn(move || {
-                        tracking_thread(
-                            context,
-                            streaming,
-                            negotiated_config.refresh_rate_hint,
-                            input,
-                        )
+                        if let Some(replay) = replay {
+                            replay.run(&context, &streaming, Instant::now())
+                        } else {
+                            tracking_thread(
+                                context,
+                                streaming,
+                                negotiated_config.refresh_rate_hint,
+                                input,
+                            )
+                        }
                     }));
                 }
                 ClientCoreEvent::StreamingStopped => {
@@ -289,7 +332,12 @@ fn client_thread(
                         thread.join().ok();
                     }
                 }
-                ClientCoreEvent::Haptics { .. } => (),
+                ClientCoreEvent::Haptics { .. }
+                | ClientCoreEvent::Notification(_)
+                | ClientCoreEvent::StreamPaused(_)
+                | ClientCoreEvent::SettingsChanged(_) => (),
+                // The mock has no reference space, the default playspace is sent again
+                ClientCoreEvent::RecenterRequested => client_core_context.send_playspace(None),
                 ClientCoreEvent::DecoderConfig { codec, .. } => {
                     window_output.decoder_codec = Some(codec)
                 }
@@ -323,6 +371,10 @@ fn client_thread(
             Err(TryRecvError::Empty) => (),
         }
 
+        if let Ok(code) = pairing_code_receiver.try_recv() {
+            client_core_context.submit_pairing_code(code);
+        }
+
         deadline += Duration::from_secs_f32(1.0 / window_output.fps);
         thread::sleep(deadline.saturating_duration_since(Instant::now()));
     }
@@ -340,11 +392,33 @@ fn client_thread(
 fn main() {
     env_logger::init();
 
+    // --replay <file>: sends the inputs of a tracking recording made on a headset instead of the
+    // pose set in the window
+    let args = env::args().collect::<Vec<_>>();
+    let replay = match args.iter().position(|arg| arg == "--replay") {
+        Some(index) => {
+            let Some(path) = args.get(index + 1) else {
+                eprintln!("Missing the path of the recording after --replay");
+                return;
+            };
+
+            match TrackingReplay::load(path.as_ref()) {
+                Ok(replay) => Some(Arc::new(replay)),
+                Err(e) => {
+                    eprintln!("Failed to load the tracking recording {path}: {e}");
+                    return;
+                }
+            }
+        }
+        None => None,
+    };
+
     let (input_sender, input_receiver) = mpsc::channel::<WindowInput>();
     let (output_sender, output_receiver) = mpsc::channel::<WindowOutput>();
+    let (pairing_code_sender, pairing_code_receiver) = mpsc::channel::<String>();
 
     let client_thread = thread::spawn(|| {
-        client_thread(output_sender, input_receiver);
+        client_thread(output_sender, input_receiver, pairing_code_receiver, replay);
     });
 
     eframe::run_native(
@@ -353,7 +427,13 @@ fn main() {
             viewport: ViewportBuilder::default().with_inner_size((400.0, 400.0)),
             ..Default::default()
         },
-        Box::new(|_| Box::new(Window::new(input_sender, output_receiver))),
+        Box::new(|_| {
+            Box::new(Window::new(
+                input_sender,
+                output_receiver,
+                pairing_code_sender,
+            ))
+        }),
     )
     .ok();
 
@@ -0,0 +1,169 @@
+use crate::interaction::InteractionContext;
+use alvr_common::{info, HAND_LEFT_ID};
+use alvr_packets::{Haptics, HapticsEnvelopePoint};
+use openxr as xr;
+use std::time::{Duration, Instant};
+
+// Some runtimes cut long vibrations short, so they are rendered as a sequence of overlapping
+// pulses. Each pulse lasts two steps so that a late frame does not leave a gap.
+const HAPTICS_STEP: Duration = Duration::from_millis(50);
+
+// Pulses are shorter when following an envelope, so that the amplitude is updated every frame
+const ENVELOPE_STEP: Duration = Duration::from_millis(10);
+
+// Shorter pulses are not rendered by some controllers
+const MIN_PULSE_DURATION: Duration = Duration::from_millis(10);
+
+struct HapticsPlayback {
+    start: Instant,
+    end: Instant,
+    next_pulse: Instant,
+    frequency: f32,
+    amplitude: f32,
+    envelope: Vec<HapticsEnvelopePoint>,
+}
+
+// Linear interpolation between the points, holding the first and last amplitudes outside of them
+fn envelope_amplitude(envelope: &[HapticsEnvelopePoint], time: Duration) -> Option<f32> {
+    let next_index = envelope
+        .iter()
+        .position(|point| point.time_offset > time)
+        .unwrap_or(envelope.len());
+
+    if next_index == 0 {
+        return envelope.first().map(|point| point.amplitude);
+    } else if next_index == envelope.len() {
+        return envelope.last().map(|point| point.amplitude);
+    }
+
+    let previous = envelope[next_index - 1];
+    let next = envelope[next_index];
+    let t = (time - previous.time_offset).as_secs_f32()
+        / (next.time_offset - previous.time_offset).as_secs_f32();
+
+    Some(previous.amplitude + (next.amplitude - previous.amplitude) * t)
+}
+
+// Plays back the haptics events of each hand for their full duration. Like in OpenVR, a new event
+// replaces the one currently playing on the same hand.
+#[derive(Default)]
+pub struct HapticsScheduler {
+    playbacks: [Option<HapticsPlayback>; 2],
+    log_haptics: bool,
+}
+
+impl HapticsScheduler {
+    pub fn set_logging(&mut self, enabled: bool) {
+        self.log_haptics = enabled;
+    }
+
+    pub fn push(
+        &mut self,
+        xr_session: &xr::Session<xr::OpenGlEs>,
+        interaction_context: &InteractionContext,
+        haptics: Haptics,
+        envelope: Vec<HapticsEnvelopePoint>,
+    ) {
+        let Haptics {
+            device_id,
+            duration,
+            frequency,
+            amplitude,
+        } = haptics;
+
+        let hand_index = if device_id == *HAND_LEFT_ID { 0 } else { 1 };
+
+        if amplitude <= 0.0 {
+            if self.playbacks[hand_index].take().is_some() {
+                interaction_context.hands_interaction[hand_index]
+                    .vibration_action
+                    .stop_feedback(xr_session, xr::Path::NULL)
+                    .ok();
+            }
+
+            return;
+        }
+
+        let duration = Duration::max(duration, MIN_PULSE_DURATION);
+
+        if self.log_haptics {
+            info!(
+                "Haptics: hand {hand_index}, duration {duration:?}, frequency {frequency}Hz, amplitude {amplitude}, envelope points {}",
+                envelope.len()
+            );
+        }
+
+        let now = Instant::now();
+        self.playbacks[hand_index] = Some(HapticsPlayback {
+            start: now,
+            end: now + duration,
+            next_pulse: now,
+            frequency,
+            amplitude,
+            envelope,
+        });
+    }
+
+    // Must be called once per frame
+    pub fn update(
+        &mut self,
+        xr_session: &xr::Session<xr::OpenGlEs>,
+        interaction_context: &InteractionContext,
+    ) {
+        let now = Instant::now();
+        for (playback_ref, hand) in self
+            .playbacks
+            .iter_mut()
+            .zip(&interaction_context.hands_interaction)
+        {
+            let Some(playback) = playback_ref else {
+                continue;
+            };
+
+            if now >= playback.end {
+                // The last pulse already ends at the right time
+                *playback_ref = None;
+            } else if now >= playback.next_pulse {
+                let (amplitude, step) = if let Some(amplitude) =
+                    envelope_amplitude(&playback.envelope, now - playback.start)
+                {
+                    (amplitude, ENVELOPE_STEP)
+                } else {
+                    (playback.amplitude, HAPTICS_STEP)
+                };
+                let pulse_duration = Duration::min(playback.end - now, step * 2);
+
+                hand.vibration_action
+                    .apply_feedback(
+                        xr_session,
+                        xr::Path::NULL,
+                        &xr::HapticVibration::new()
+                            .amplitude(amplitude)
+                            .frequency(playback.frequency)
+                            .duration(xr::Duration::from_nanos(pulse_duration.as_nanos() as _)),
+                    )
+                    .ok();
+
+                playback.next_pulse = now + step;
+            }
+        }
+    }
+
+    pub fn stop_all(
+        &mut self,
+        xr_session: &xr::Session<xr::OpenGlEs>,
+        interaction_context: &InteractionContext,
+    ) {
+        for (playback, hand) in self
+            .playbacks
+            .iter_mut()
+            .zip(&interaction_context.hands_interaction)
+        {
+            if playback.take().is_some() {
+                hand.vibration_action
+                    .stop_feedback(xr_session, xr::Path::NULL)
+                    .ok();
+            }
+        }
+    }
+}
@@ -1,9 +1,12 @@
 use crate::{from_xr_pose, from_xr_quat, from_xr_vec3, Platform, XrContext};
-use alvr_common::{glam::Vec3, *};
-use alvr_packets::{ButtonEntry, ButtonValue};
+use alvr_common::{
+    glam::{Vec2, Vec3},
+    *,
+};
+use alvr_packets::{ButtonEntry, ButtonValue, DeviceDeclaration, DeviceRole};
 use alvr_session::{BodyTrackingSourcesConfig, FaceTrackingSourcesConfig};
 use openxr as xr;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use xr::sys::FullBodyJointMETA;
 use xr::SpaceLocationFlags;
 
@@ -30,6 +33,13 @@ pub struct FaceSources {
     pub lip_tracker_htc: Option<xr::FacialTrackerHTC>,
 }
 
+pub struct GamepadInteraction {
+    pub button_actions: HashMap<u64, ButtonAction>,
+    // Updated when the interaction profile of /user/gamepad changes. The actions are not read
+    // while no gamepad is connected
+    pub connected: RelaxedAtomic,
+}
+
 pub struct BodySources {
     pub body_tracker_full_body_meta: Option<xr::BodyTrackerFullBodyMETA>,
     pub enable_full_body: bool,
@@ -39,10 +49,52 @@ pub struct InteractionContext {
     pub action_set: xr::ActionSet,
     pub button_actions: HashMap<u64, ButtonAction>,
     pub hands_interaction: [HandInteraction; 2],
+    // None if the runtime does not support the Xbox controller profile
+    pub gamepad: Option<GamepadInteraction>,
     pub face_sources: FaceSources,
     pub body_sources: BodySources,
 }
 
+fn create_button_action(action_set: &xr::ActionSet, info: &ButtonInfo) -> Option<ButtonAction> {
+    let name = info.path[1..].replace('/', "_");
+    let display_name = format!(
+        "{}{}",
+        name[0..1].to_uppercase(),
+        name[1..].replace('_', " ")
+    );
+
+    match info.button_type {
+        ButtonType::Binary => Some(ButtonAction::Binary(
+            action_set.create_action(&name, &display_name, &[]).unwrap(),
+        )),
+        ButtonType::Scalar => Some(ButtonAction::Scalar(
+            action_set.create_action(&name, &display_name, &[]).unwrap(),
+        )),
+        // Read through the x and y components, see update_buttons()
+        ButtonType::Vector2 => None,
+    }
+}
+
+fn button_bindings<'a>(
+    button_actions: &'a HashMap<u64, ButtonAction>,
+    button_info: &HashMap<u64, ButtonInfo>,
+) -> Vec<xr::Binding<'a>> {
+    button_actions
+        .iter()
+        .map(|(id, action)| {
+            let path = button_info.get(id).unwrap().path;
+            match action {
+                ButtonAction::Binary(action) => binding(action, path),
+                ButtonAction::Scalar(action) => binding(action, path),
+            }
+        })
+        .collect()
+}
+
+fn binding<'a, T: xr::ActionTy>(action: &'a xr::Action<T>, path: &str) -> xr::Binding<'a> {
+    xr::Binding::new(action, action.instance().string_to_path(path).unwrap())
+}
+
 pub fn initialize_interaction(
     xr_ctx: &XrContext,
     platform: Platform,
@@ -56,10 +108,6 @@ pub fn initialize_interaction(
 
     let mut bindings = vec![];
 
-    fn binding<'a, T: xr::ActionTy>(action: &'a xr::Action<T>, path: &str) -> xr::Binding<'a> {
-        xr::Binding::new(action, action.instance().string_to_path(path).unwrap())
-    }
-
     let controllers_profile_path = match platform {
         Platform::Quest1
         | Platform::Quest2
@@ -84,26 +132,17 @@ pub fn initialize_interaction(
         .unwrap()
         .button_set
     {
-        let info = BUTTON_INFO.get(button_id).unwrap();
-
-        let name = info.path[1..].replace('/', "_");
-        let display_name = format!(
-            "{}{}",
-            name[0..1].to_uppercase(),
-            name[1..].replace('_', " ")
-        );
-
-        let action = match info.button_type {
-            ButtonType::Binary => {
-                ButtonAction::Binary(action_set.create_action(&name, &display_name, &[]).unwrap())
-            }
-            ButtonType::Scalar => {
-                ButtonAction::Scalar(action_set.create_action(&name, &display_name, &[]).unwrap())
-            }
-        };
-        button_actions.insert(*button_id, action);
+        if let Some(action) = create_button_action(&action_set, BUTTON_INFO.get(button_id).unwrap())
+        {
+            button_actions.insert(*button_id, action);
+        }
     }
 
+    let gamepad_button_actions = GAMEPAD_BUTTON_INFO
+        .iter()
+        .filter_map(|(id, info)| Some((*id, create_button_action(&action_set, info)?)))
+        .collect::<HashMap<_, _>>();
+
     let left_grip_action = action_set
         .create_action("left_grip_pose", "Left grip pose", &[])
         .unwrap();
@@ -127,17 +166,7 @@ pub fn initialize_interaction(
 
     // Create action bindings:
 
-    for (id, action) in &button_actions {
-        let path = &BUTTON_INFO.get(id).unwrap().path;
-        match action {
-            ButtonAction::Binary(action) => {
-                bindings.push(binding(action, path));
-            }
-            ButtonAction::Scalar(action) => {
-                bindings.push(binding(action, path));
-            }
-        }
-    }
+    bindings.extend(button_bindings(&button_actions, &BUTTON_INFO));
 
     bindings.push(binding(
         &left_grip_action,
@@ -175,6 +204,23 @@ pub fn initialize_interaction(
         )
         .unwrap();
 
+    let gamepad = match xr_ctx.instance.suggest_interaction_profile_bindings(
+        xr_ctx
+            .instance
+            .string_to_path(XBOX_CONTROLLER_PROFILE_PATH)
+            .unwrap(),
+        &button_bindings(&gamepad_button_actions, &GAMEPAD_BUTTON_INFO),
+    ) {
+        Ok(()) => Some(GamepadInteraction {
+            button_actions: gamepad_button_actions,
+            connected: RelaxedAtomic::new(false),
+        }),
+        Err(e) => {
+            warn!("Gamepads are not supported by the runtime: {e}");
+            None
+        }
+    };
+
     let combined_eyes_source = (face_tracking_sources
         .as_ref()
         .map(|s| s.combined_eye_gaze)
@@ -344,6 +390,7 @@ pub fn initialize_interaction(
                 skeleton_tracker: right_hand_tracker,
             },
         ],
+        gamepad,
         face_sources: FaceSources {
             combined_eyes_source,
             eye_tracker_fb,
@@ -358,6 +405,91 @@ pub fn initialize_interaction(
     }
 }
 
+// The controllers declare the inputs of their interaction profile, and the Vector2 inputs of
+// update_buttons(). The gamepad is declared even when it is not connected, it can be paired at any
+// time
+pub fn device_declarations(context: &InteractionContext, gamepad: bool) -> Vec<DeviceDeclaration> {
+    let controller = |hand: &HandInteraction, device_id: u64, role, display_name: &str| {
+        let mut input_ids = CONTROLLER_PROFILE_INFO
+            .get(&hand.controllers_profile_id)
+            .map(|info| {
+                info.button_set
+                    .iter()
+                    .copied()
+                    .filter(|id| BUTTON_INFO.get(id).map(|info| info.device_id) == Some(device_id))
+                    .collect::<HashSet<_>>()
+            })
+            .unwrap_or_default();
+        for (id, components) in &*VECTOR2_COMPONENTS {
+            if components
+                .iter()
+                .all(|component| input_ids.contains(component))
+            {
+                input_ids.insert(*id);
+            }
+        }
+
+        DeviceDeclaration {
+            device_id,
+            role,
+            display_name: display_name.into(),
+            input_ids,
+        }
+    };
+
+    let mut declarations = vec![
+        DeviceDeclaration {
+            device_id: *HEAD_ID,
+            role: DeviceRole::Hmd,
+            display_name: "Headset".into(),
+            input_ids: HashSet::new(),
+        },
+        controller(
+            &context.hands_interaction[0],
+            *HAND_LEFT_ID,
+            DeviceRole::LeftController,
+            "Left controller",
+        ),
+        controller(
+            &context.hands_interaction[1],
+            *HAND_RIGHT_ID,
+            DeviceRole::RightController,
+            "Right controller",
+        ),
+    ];
+
+    if gamepad && context.gamepad.is_some() {
+        declarations.push(DeviceDeclaration {
+            device_id: *GAMEPAD_ID,
+            role: DeviceRole::Gamepad,
+            display_name: "Gamepad".into(),
+            input_ids: GAMEPAD_BUTTON_INFO.keys().copied().collect(),
+        });
+    }
+
+    if context.body_sources.body_tracker_full_body_meta.is_some() {
+        for (device_id, display_name) in [
+            (*BODY_CHEST_ID, "Chest"),
+            (*BODY_HIPS_ID, "Waist"),
+            (*BODY_LEFT_ELBOW_ID, "Left elbow"),
+            (*BODY_RIGHT_ELBOW_ID, "Right elbow"),
+            (*BODY_LEFT_KNEE_ID, "Left knee"),
+            (*BODY_LEFT_FOOT_ID, "Left foot"),
+            (*BODY_RIGHT_KNEE_ID, "Right knee"),
+            (*BODY_RIGHT_FOOT_ID, "Right foot"),
+        ] {
+            declarations.push(DeviceDeclaration {
+                device_id,
+                role: DeviceRole::GenericTracker,
+                display_name: display_name.into(),
+                input_ids: HashSet::new(),
+            });
+        }
+    }
+
+    declarations
+}
+
 pub fn get_stage_reference_space(xr_session: &xr::Session<xr::OpenGlEs>) -> xr::Space {
     xr_session
         .create_reference_space(xr::ReferenceSpaceType::STAGE, xr::Posef::IDENTITY)
@@ -442,11 +574,47 @@ pub fn get_hand_motion(
     (Some(hand_motion), None)
 }
 
+fn scalar_state(
+    xr_session: &xr::Session<xr::OpenGlEs>,
+    button_actions: &HashMap<u64, ButtonAction>,
+    id: u64,
+) -> Option<xr::ActionState<f32>> {
+    match button_actions.get(&id)? {
+        ButtonAction::Scalar(action) => action.state(xr_session, xr::Path::NULL).ok(),
+        ButtonAction::Binary(_) => None,
+    }
+}
+
+// vector2_button_values: send each pair of x and y components as one Vector2 entry, if the server
+// supports it. The values are left raw, the deadzone is applied by the server
 pub fn update_buttons(
     xr_session: &xr::Session<xr::OpenGlEs>,
     button_actions: &HashMap<u64, ButtonAction>,
+    vector2_button_values: bool,
 ) -> Vec<ButtonEntry> {
     let mut button_entries = Vec::with_capacity(2);
+
+    if vector2_button_values {
+        for (id, [x_id, y_id]) in &*VECTOR2_COMPONENTS {
+            let (Some(x_state), Some(y_state)) = (
+                scalar_state(xr_session, button_actions, *x_id),
+                scalar_state(xr_session, button_actions, *y_id),
+            ) else {
+                continue;
+            };
+
+            if x_state.changed_since_last_sync || y_state.changed_since_last_sync {
+                button_entries.push(ButtonEntry {
+                    path_id: *id,
+                    value: ButtonValue::Vector2(Vec2::new(
+                        x_state.current_state,
+                        y_state.current_state,
+                    )),
+                });
+            }
+        }
+    }
+
     for (id, action) in button_actions {
         match action {
             ButtonAction::Binary(action) => {
@@ -466,7 +634,12 @@ pub fn update_buttons(
                     continue;
                 };
 
-                if state.changed_since_last_sync {
+                let sent_as_vector2 = vector2_button_values
+                    && VECTOR2_COMPONENTS
+                        .values()
+                        .any(|components| components.contains(id));
+
+                if state.changed_since_last_sync && !sent_as_vector2 {
                     button_entries.push(ButtonEntry {
                         path_id: *id,
                         value: ButtonValue::Scalar(state.current_state),
@@ -479,6 +652,63 @@ pub fn update_buttons(
     button_entries
 }
 
+pub fn update_gamepad_connection(
+    xr_session: &xr::Session<xr::OpenGlEs>,
+    context: &InteractionContext,
+) {
+    let Some(gamepad) = &context.gamepad else {
+        return;
+    };
+
+    let connected = xr_session
+        .instance()
+        .string_to_path(GAMEPAD_PATH)
+        .and_then(|path| xr_session.current_interaction_profile(path))
+        .is_ok_and(|profile| profile != xr::Path::NULL);
+    if connected != gamepad.connected.value() {
+        info!(
+            "Gamepad {}",
+            if connected {
+                "connected"
+            } else {
+                "disconnected"
+            }
+        );
+        gamepad.connected.set(connected);
+    }
+}
+
+// Nothing is read while no gamepad is connected. After a disconnection all the inputs are reported
+// released once, so that none stays pressed on the streamer
+pub fn update_gamepad_buttons(
+    xr_session: &xr::Session<xr::OpenGlEs>,
+    gamepad: &GamepadInteraction,
+    vector2_button_values: bool,
+    was_connected: &mut bool,
+) -> Vec<ButtonEntry> {
+    let connected = gamepad.connected.value();
+    let entries = if connected {
+        update_buttons(xr_session, &gamepad.button_actions, vector2_button_values)
+    } else if *was_connected {
+        GAMEPAD_BUTTON_INFO
+            .iter()
+            .map(|(id, info)| ButtonEntry {
+                path_id: *id,
+                value: match info.button_type {
+                    ButtonType::Binary => ButtonValue::Binary(false),
+                    ButtonType::Scalar => ButtonValue::Scalar(0.0),
+                    ButtonType::Vector2 => ButtonValue::Vector2(Vec2::ZERO),
+                },
+            })
+            .collect()
+    } else {
+        vec![]
+    };
+    *was_connected = connected;
+
+    entries
+}
+
 pub fn get_eye_gazes(
     xr_session: &xr::Session<xr::OpenGlEs>,
     sources: &FaceSources,
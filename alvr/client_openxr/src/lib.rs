@@ -1,7 +1,9 @@
 mod c_api;
 mod graphics;
+mod haptics;
 mod interaction;
 mod lobby;
+mod overlay;
 mod stream;
 
 use crate::stream::StreamConfig;
@@ -9,10 +11,13 @@ use alvr_client_core::{ClientCapabilities, ClientCoreContext, ClientCoreEvent, P
 use alvr_common::{
     error,
     glam::{Quat, UVec2, Vec3},
-    info, Fov, Pose, HAND_LEFT_ID,
+    info, Fov, Pose,
 };
-use lobby::Lobby;
+use alvr_packets::Haptics;
+use haptics::HapticsScheduler;
+use lobby::{Lobby, PairingCodeInput};
 use openxr as xr;
+use overlay::{NotificationOverlay, StatisticsOverlay};
 use std::{
     path::Path,
     sync::Arc,
@@ -24,6 +29,8 @@ use xr::ColorSpaceFB;
 
 const DECODER_MAX_TIMEOUT_MULTIPLIER: f32 = 0.8;
 
+const STREAM_PAUSED_MESSAGE: &str = "Stream paused";
+
 fn from_xr_vec3(v: xr::Vector3f) -> Vec3 {
     Vec3::new(v.x, v.y, v.z)
 }
@@ -144,6 +151,7 @@ pub fn entry_point() {
     exts.bd_controller_interaction = available_extensions.bd_controller_interaction;
     exts.ext_eye_gaze_interaction = available_extensions.ext_eye_gaze_interaction;
     exts.ext_hand_tracking = available_extensions.ext_hand_tracking;
+    exts.ext_user_presence = available_extensions.ext_user_presence;
     exts.fb_color_space = available_extensions.fb_color_space;
     exts.fb_display_refresh_rate = available_extensions.fb_display_refresh_rate;
     exts.fb_eye_tracking_social = available_extensions.fb_eye_tracking_social;
@@ -234,6 +242,9 @@ pub fn entry_point() {
             encoder_high_profile: platform != Platform::Unknown,
             encoder_10_bits: platform != Platform::Unknown,
             encoder_av1: platform == Platform::Quest3,
+            multi_slice_decoding: platform != Platform::Unknown,
+            haptics_envelopes: true,
+            device_model: None,
         };
         let core_context = Arc::new(ClientCoreContext::new(capabilities));
 
@@ -254,6 +265,11 @@ pub fn entry_point() {
         let mut lobby = Lobby::new(xr_session.clone(), default_view_resolution);
         let mut session_running = false;
         let mut stream_context = None::<StreamContext>;
+        let mut haptics_scheduler = HapticsScheduler::default();
+        let mut statistics_overlay = None::<StatisticsOverlay>;
+        let mut notification_overlay = NotificationOverlay::new(&xr_session);
+        let mut pairing_code_input = None::<PairingCodeInput>;
+        let mut user_present = None::<bool>;
 
         let mut event_storage = xr::EventDataBuffer::new();
         'render_loop: loop {
@@ -280,6 +296,15 @@ pub fn entry_point() {
 
                             xr_session.end().unwrap();
                         }
+                        // Without the proximity event, removing the headset is detected from the
+                        // loss of focus. Focus is also lost briefly when the system menu is open,
+                        // the streamer waits for a timeout before pausing the stream.
+                        xr::SessionState::FOCUSED if !exts.ext_user_presence => {
+                            core_context.send_proximity_state(true)
+                        }
+                        xr::SessionState::VISIBLE if !exts.ext_user_presence => {
+                            core_context.send_proximity_state(false)
+                        }
                         xr::SessionState::EXITING => break 'render_loop,
                         xr::SessionState::LOSS_PENDING => break 'render_loop,
                         _ => (),
@@ -296,6 +321,10 @@ pub fn entry_point() {
                             context.update_reference_space();
                         }
                     }
+                    xr::Event::UserPresenceChangedEXT(event) => {
+                        user_present = Some(event.is_user_present());
+                        core_context.send_proximity_state(event.is_user_present());
+                    }
                     xr::Event::PerfSettingsEXT(event) => {
                         info!(
                             "Perf: from {:?} to {:?}, domain: {:?}/{:?}",
@@ -306,7 +335,7 @@ pub fn entry_point() {
                         );
                     }
                     xr::Event::InteractionProfileChanged(_) => {
-                        // todo
+                        interaction::update_gamepad_connection(&xr_session, &interaction_context);
                     }
                     xr::Event::PassthroughStateChangedFB(_) => {
                         // todo
@@ -325,6 +354,15 @@ pub fn entry_point() {
                     ClientCoreEvent::UpdateHudMessage(message) => {
                         last_lobby_message = message.clone();
                         alvr_client_core::opengl::update_hud_message(&message);
+                        pairing_code_input = None;
+                    }
+                    ClientCoreEvent::PairingCodeRequested { attempts_left } => {
+                        let input = PairingCodeInput::new(attempts_left);
+                        alvr_client_core::opengl::update_hud_message(&format!(
+                            "{last_lobby_message}\n\n{}",
+                            input.text()
+                        ));
+                        pairing_code_input = Some(input);
                     }
                     ClientCoreEvent::StreamingStarted {
                         settings,
@@ -347,6 +385,17 @@ pub fn entry_point() {
                             continue;
                         }
 
+                        haptics_scheduler.set_logging(settings.logging.log_haptics);
+
+                        let overlay_config = settings.logging.statistics_overlay.as_option();
+                        statistics_overlay = overlay_config.map(|config| {
+                            StatisticsOverlay::new(
+                                xr_session.clone(),
+                                config,
+                                new_config.refresh_rate_hint,
+                            )
+                        });
+
                         stream_context = Some(StreamContext::new(
                             Arc::clone(&core_context),
                             xr_context.clone(),
@@ -355,33 +404,64 @@ pub fn entry_point() {
                             &new_config,
                         ));
 
+                        // The headset may have been removed before the connection
+                        if let Some(present) = user_present {
+                            core_context.send_proximity_state(present);
+                        }
+
                         stream_config = Some(new_config);
                     }
                     ClientCoreEvent::StreamingStopped => {
-                        stream_context = None;
+                        if stream_context
+                            .take()
+                            .is_some_and(|context| context.is_paused())
+                        {
+                            alvr_client_core::opengl::update_hud_message(&last_lobby_message);
+                        }
+                        statistics_overlay = None;
+                        notification_overlay.hide();
+
+                        haptics_scheduler.stop_all(&xr_session, &interaction_context);
                     }
                     ClientCoreEvent::Haptics {
                         device_id,
                         duration,
                         frequency,
                         amplitude,
-                    } => {
-                        let action = if device_id == *HAND_LEFT_ID {
-                            &interaction_context.hands_interaction[0].vibration_action
+                        envelope,
+                    } => haptics_scheduler.push(
+                        &xr_session,
+                        &interaction_context,
+                        Haptics {
+                            device_id,
+                            duration,
+                            frequency,
+                            amplitude,
+                        },
+                        envelope,
+                    ),
+                    ClientCoreEvent::Notification(message) => notification_overlay.show(&message),
+                    ClientCoreEvent::RecenterRequested => {
+                        lobby.update_reference_space();
+
+                        if let Some(context) = &mut stream_context {
+                            context.update_reference_space();
+                        }
+                    }
+                    ClientCoreEvent::StreamPaused(paused) => {
+                        if let Some(context) = &stream_context {
+                            context.set_paused(paused);
+                        }
+
+                        let message = if paused {
+                            STREAM_PAUSED_MESSAGE
                         } else {
-                            &interaction_context.hands_interaction[1].vibration_action
+                            &last_lobby_message
                         };
-
-                        action
-                            .apply_feedback(
-                                &xr_session,
-                                xr::Path::NULL,
-                                &xr::HapticVibration::new()
-                                    .amplitude(amplitude)
-                                    .frequency(frequency)
-                                    .duration(xr::Duration::from_nanos(duration.as_nanos() as _)),
-                            )
-                            .unwrap();
+                        alvr_client_core::opengl::update_hud_message(message);
+                    }
+                    ClientCoreEvent::SettingsChanged(settings) => {
+                        haptics_scheduler.set_logging(settings.logging.log_haptics);
                     }
                     ClientCoreEvent::DecoderConfig { .. } | ClientCoreEvent::FrameReady { .. } => {
                         panic!()
@@ -389,6 +469,34 @@ pub fn entry_point() {
                 }
             }
 
+            haptics_scheduler.update(&xr_session, &interaction_context);
+
+            // While streaming, the actions are synced by the tracking thread
+            if let Some(input) = pairing_code_input
+                .as_mut()
+                .filter(|_| stream_context.is_none())
+            {
+                if let Err(e) = xr_session.sync_actions(&[(&interaction_context.action_set).into()])
+                {
+                    error!("{e}");
+                }
+
+                let previous_text = input.text();
+                if let Some(code) = input.update(&xr_session, &interaction_context.button_actions) {
+                    core_context.submit_pairing_code(code);
+                    pairing_code_input = None;
+                } else if input.text() != previous_text {
+                    alvr_client_core::opengl::update_hud_message(&format!(
+                        "{last_lobby_message}\n\n{}",
+                        input.text()
+                    ));
+                }
+            }
+
+            if let Some(overlay) = &mut statistics_overlay {
+                overlay.update(&core_context, &interaction_context);
+            }
+
             let frame_state = match xr_frame_waiter.wait() {
                 Ok(state) => state,
                 Err(e) => {
@@ -416,7 +524,10 @@ pub fn entry_point() {
             }
 
             // todo: allow rendering lobby and stream layers at the same time and add cross fade
-            let (layer, display_time) = if let Some(context) = &mut stream_context {
+            let (layer, display_time) = if let Some(context) = stream_context
+                .as_mut()
+                .filter(|context| !context.is_paused())
+            {
                 let frame_poll_deadline = Instant::now()
                     + Duration::from_secs_f32(
                         frame_interval.as_secs_f32() * DECODER_MAX_TIMEOUT_MULTIPLIER,
@@ -441,10 +552,25 @@ pub fn entry_point() {
                 (layer, vsync_time)
             };
 
+            let projection_layer = layer.build();
+            let overlay_layer = statistics_overlay
+                .as_ref()
+                .and_then(|overlay| overlay.layer(&interaction_context));
+            let notification_layer = notification_overlay.layer();
+
+            let mut layers = Vec::<&xr::CompositionLayerBase<xr::OpenGlEs>>::with_capacity(3);
+            layers.push(&projection_layer);
+            if let Some(overlay_layer) = &overlay_layer {
+                layers.push(overlay_layer);
+            }
+            if let Some(notification_layer) = &notification_layer {
+                layers.push(notification_layer);
+            }
+
             let res = xr_frame_stream.end(
                 to_xr_time(display_time),
                 xr::EnvironmentBlendMode::OPAQUE,
-                &[&layer.build()],
+                &layers,
             );
 
             if let Err(e) = res {
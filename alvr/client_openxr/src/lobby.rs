@@ -1,10 +1,16 @@
 use crate::{
     graphics::{self, CompositionLayerBuilder},
-    interaction,
+    interaction::{self, ButtonAction},
 };
 use alvr_client_core::opengl::RenderViewInput;
-use alvr_common::glam::UVec2;
+use alvr_common::{glam::UVec2, RIGHT_A_CLICK_ID, RIGHT_THUMBSTICK_X_ID, RIGHT_THUMBSTICK_Y_ID};
+use alvr_packets::PAIRING_CODE_LENGTH;
 use openxr as xr;
+use std::collections::HashMap;
+
+// The thumbstick must go back near the center between two steps
+const STICK_PUSH_THRESHOLD: f32 = 0.7;
+const STICK_RELEASE_THRESHOLD: f32 = 0.3;
 
 // todo: add interaction?
 pub struct Lobby {
@@ -129,3 +135,90 @@ impl Lobby {
         )
     }
 }
+
+// Entry of the pairing code with the right controller, as the lobby has no pointer. The thumbstick
+// moves between the digits and changes them, A submits the code
+pub struct PairingCodeInput {
+    digits: [u8; PAIRING_CODE_LENGTH],
+    cursor: usize,
+    attempts_left: u32,
+    stick_released: bool,
+}
+
+impl PairingCodeInput {
+    pub fn new(attempts_left: u32) -> Self {
+        Self {
+            digits: [0; PAIRING_CODE_LENGTH],
+            cursor: 0,
+            attempts_left,
+            stick_released: true,
+        }
+    }
+
+    // Call after syncing the actions. Returns the code once submitted
+    pub fn update(
+        &mut self,
+        xr_session: &xr::Session<xr::OpenGlEs>,
+        button_actions: &HashMap<u64, ButtonAction>,
+    ) -> Option<String> {
+        let scalar_value = |id| match button_actions.get(&id) {
+            Some(ButtonAction::Scalar(action)) => action
+                .state(xr_session, xr::Path::NULL)
+                .map(|state| state.current_state)
+                .unwrap_or(0.0),
+            _ => 0.0,
+        };
+        let x = scalar_value(*RIGHT_THUMBSTICK_X_ID);
+        let y = scalar_value(*RIGHT_THUMBSTICK_Y_ID);
+
+        let magnitude = f32::max(x.abs(), y.abs());
+        if magnitude < STICK_RELEASE_THRESHOLD {
+            self.stick_released = true;
+        } else if self.stick_released && magnitude > STICK_PUSH_THRESHOLD {
+            self.stick_released = false;
+
+            if y.abs() > x.abs() {
+                let digit = &mut self.digits[self.cursor];
+                *digit = if y > 0.0 {
+                    (*digit + 1) % 10
+                } else {
+                    (*digit + 9) % 10
+                };
+            } else if x > 0.0 {
+                self.cursor = usize::min(self.cursor + 1, PAIRING_CODE_LENGTH - 1);
+            } else {
+                self.cursor = self.cursor.saturating_sub(1);
+            }
+        }
+
+        let submitted = match button_actions.get(&*RIGHT_A_CLICK_ID) {
+            Some(ButtonAction::Binary(action)) => action
+                .state(xr_session, xr::Path::NULL)
+                .is_ok_and(|state| state.changed_since_last_sync && state.current_state),
+            _ => false,
+        };
+
+        submitted.then(|| self.digits.iter().map(|digit| digit.to_string()).collect())
+    }
+
+    // Shown under the lobby message, with the selected digit in brackets
+    pub fn text(&self) -> String {
+        let digits = self
+            .digits
+            .iter()
+            .enumerate()
+            .map(|(index, digit)| {
+                if index == self.cursor {
+                    format!("[{digit}]")
+                } else {
+                    format!(" {digit} ")
+                }
+            })
+            .collect::<String>();
+
+        format!(
+            "{digits}\nThumbstick: select and change the digits, A: submit\n{} attempts left",
+            self.attempts_left
+        )
+    }
+}
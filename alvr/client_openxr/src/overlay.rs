@@ -0,0 +1,326 @@
+use crate::interaction::{ButtonAction, InteractionContext};
+use alvr_client_core::ClientCoreContext;
+use alvr_common::{
+    glam::{Quat, UVec2, Vec3},
+    Pose, LEFT_THUMBSTICK_CLICK_ID, RIGHT_THUMBSTICK_CLICK_ID,
+};
+use alvr_session::{StatisticsOverlayAnchor, StatisticsOverlayConfig};
+use openxr as xr;
+use std::{
+    f32::consts::FRAC_PI_4,
+    time::{Duration, Instant},
+};
+
+const OVERLAY_RESOLUTION: UVec2 = UVec2::new(640, 320);
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+const WRIST_OVERLAY_WIDTH_M: f32 = 0.12;
+const VIEW_OVERLAY_WIDTH_M: f32 = 0.25;
+const NOTIFICATION_RESOLUTION: UVec2 = UVec2::new(640, 64);
+const NOTIFICATION_WIDTH_M: f32 = 0.3;
+const NOTIFICATION_DURATION: Duration = Duration::from_secs(5);
+
+// Floats over the back of the wrist, tilted towards the eyes
+fn wrist_pose() -> xr::Posef {
+    crate::to_xr_pose(Pose {
+        orientation: Quat::from_rotation_x(-FRAC_PI_4),
+        position: Vec3::new(0.0, 0.06, 0.12),
+    })
+}
+
+// Bottom left corner of the field of view
+fn view_corner_pose() -> xr::Posef {
+    crate::to_xr_pose(Pose {
+        orientation: Quat::IDENTITY,
+        position: Vec3::new(-0.2, -0.15, -0.6),
+    })
+}
+
+// Top center of the field of view
+fn view_top_pose() -> xr::Posef {
+    crate::to_xr_pose(Pose {
+        orientation: Quat::IDENTITY,
+        position: Vec3::new(0.0, 0.15, -0.6),
+    })
+}
+
+fn create_swapchain(
+    xr_session: &xr::Session<xr::OpenGlEs>,
+    resolution: UVec2,
+) -> (xr::Swapchain<xr::OpenGlEs>, Vec<u32>) {
+    let swapchain = xr_session
+        .create_swapchain(&xr::SwapchainCreateInfo {
+            create_flags: xr::SwapchainCreateFlags::EMPTY,
+            usage_flags: xr::SwapchainUsageFlags::SAMPLED | xr::SwapchainUsageFlags::TRANSFER_DST,
+            format: glow::SRGB8_ALPHA8,
+            sample_count: 1,
+            width: resolution.x,
+            height: resolution.y,
+            face_count: 1,
+            array_size: 1,
+            mip_count: 1,
+        })
+        .unwrap();
+    let swapchain_images = swapchain.enumerate_images().unwrap();
+
+    (swapchain, swapchain_images)
+}
+
+fn draw_text(
+    swapchain: &mut xr::Swapchain<xr::OpenGlEs>,
+    swapchain_images: &[u32],
+    resolution: UVec2,
+    message: &str,
+) {
+    let index = swapchain.acquire_image().unwrap();
+    swapchain.wait_image(xr::Duration::INFINITE).unwrap();
+
+    alvr_client_core::opengl::update_overlay_texture(
+        swapchain_images[index as usize],
+        resolution,
+        message,
+    );
+
+    swapchain.release_image().unwrap();
+}
+
+fn quad_layer<'a>(
+    space: &'a xr::Space,
+    swapchain: &'a xr::Swapchain<xr::OpenGlEs>,
+    resolution: UVec2,
+    pose: xr::Posef,
+    width: f32,
+) -> xr::CompositionLayerQuad<'a, xr::OpenGlEs> {
+    xr::CompositionLayerQuad::new()
+        .layer_flags(xr::CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA)
+        .space(space)
+        .eye_visibility(xr::EyeVisibility::BOTH)
+        .sub_image(
+            xr::SwapchainSubImage::new()
+                .swapchain(swapchain)
+                .image_array_index(0)
+                .image_rect(xr::Rect2Di {
+                    offset: xr::Offset2Di { x: 0, y: 0 },
+                    extent: xr::Extent2Di {
+                        width: resolution.x as _,
+                        height: resolution.y as _,
+                    },
+                }),
+        )
+        .pose(pose)
+        .size(xr::Extent2Df {
+            width,
+            height: width * resolution.y as f32 / resolution.x as f32,
+        })
+}
+
+fn is_pressed(
+    xr_session: &xr::Session<xr::OpenGlEs>,
+    interaction_context: &InteractionContext,
+    id: u64,
+) -> bool {
+    match interaction_context.button_actions.get(&id) {
+        Some(ButtonAction::Binary(action)) => action
+            .state(xr_session, xr::Path::NULL)
+            .map(|state| state.current_state)
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+// Statistics shown in a quad layer on top of the stream. Pressing both thumbsticks toggles it.
+// While hidden, the texture is not updated and no layer is submitted.
+pub struct StatisticsOverlay {
+    xr_session: xr::Session<xr::OpenGlEs>,
+    anchor: StatisticsOverlayAnchor,
+    view_space: xr::Space,
+    swapchain: xr::Swapchain<xr::OpenGlEs>,
+    swapchain_images: Vec<u32>,
+    refresh_rate: f32,
+    visible: bool,
+    chord_was_pressed: bool,
+    next_refresh: Instant,
+    // A swapchain image must have been released before it can be submitted
+    has_content: bool,
+}
+
+impl StatisticsOverlay {
+    pub fn new(
+        xr_session: xr::Session<xr::OpenGlEs>,
+        config: &StatisticsOverlayConfig,
+        refresh_rate: f32,
+    ) -> Self {
+        let view_space = xr_session
+            .create_reference_space(xr::ReferenceSpaceType::VIEW, xr::Posef::IDENTITY)
+            .unwrap();
+
+        let (swapchain, swapchain_images) = create_swapchain(&xr_session, OVERLAY_RESOLUTION);
+
+        Self {
+            xr_session,
+            anchor: config.anchor,
+            view_space,
+            swapchain,
+            swapchain_images,
+            refresh_rate,
+            visible: config.visible_at_start,
+            chord_was_pressed: false,
+            next_refresh: Instant::now(),
+            has_content: false,
+        }
+    }
+
+    // Must be called once per frame
+    pub fn update(
+        &mut self,
+        core_context: &ClientCoreContext,
+        interaction_context: &InteractionContext,
+    ) {
+        let chord_pressed = [*LEFT_THUMBSTICK_CLICK_ID, *RIGHT_THUMBSTICK_CLICK_ID]
+            .into_iter()
+            .all(|id| is_pressed(&self.xr_session, interaction_context, id));
+        if chord_pressed && !self.chord_was_pressed {
+            self.visible = !self.visible;
+            self.next_refresh = Instant::now();
+        }
+        self.chord_was_pressed = chord_pressed;
+
+        if !self.visible || Instant::now() < self.next_refresh {
+            return;
+        }
+        self.next_refresh = Instant::now() + REFRESH_INTERVAL;
+
+        let Some((client, server, dropped_packets)) = core_context.get_overlay_statistics() else {
+            return;
+        };
+
+        let ms = |duration: Duration| duration.as_secs_f32() * 1000.0;
+        let client_fps = 1.0
+            / client
+                .frame_interval
+                .max(Duration::from_millis(1))
+                .as_secs_f32();
+        let message = format!(
+            "Latency: {:.0} ms\n\
+            Encode {:.1} ms | Network {:.1} ms\n\
+            Decode {:.1} ms | Compose {:.1} ms\n\
+            Bitrate: {:.1} Mbps\n\
+            Refresh rate: {client_fps:.0}/{:.0} Hz\n\
+            Packet loss: {:.1}/s | Dropped: {dropped_packets}\n\
+            Battery: {:.0}%{}",
+            ms(client.total_pipeline_latency),
+            ms(server.encode_latency),
+            ms(server.network_latency),
+            ms(client.video_decode + client.video_decoder_queue),
+            ms(client.rendering),
+            server.video_mbits_per_sec,
+            self.refresh_rate,
+            server.packets_lost_per_sec,
+            server.battery_hmd * 100.0,
+            if server.hmd_plugged {
+                " (charging)"
+            } else {
+                ""
+            },
+        );
+
+        draw_text(
+            &mut self.swapchain,
+            &self.swapchain_images,
+            OVERLAY_RESOLUTION,
+            &message,
+        );
+
+        self.has_content = true;
+    }
+
+    pub fn layer<'a>(
+        &'a self,
+        interaction_context: &'a InteractionContext,
+    ) -> Option<xr::CompositionLayerQuad<'a, xr::OpenGlEs>> {
+        if !self.visible || !self.has_content {
+            return None;
+        }
+
+        let (space, pose, width) = match self.anchor {
+            StatisticsOverlayAnchor::LeftWrist => (
+                &interaction_context.hands_interaction[0].grip_space,
+                wrist_pose(),
+                WRIST_OVERLAY_WIDTH_M,
+            ),
+            StatisticsOverlayAnchor::RightWrist => (
+                &interaction_context.hands_interaction[1].grip_space,
+                wrist_pose(),
+                WRIST_OVERLAY_WIDTH_M,
+            ),
+            StatisticsOverlayAnchor::ViewCorner => {
+                (&self.view_space, view_corner_pose(), VIEW_OVERLAY_WIDTH_M)
+            }
+        };
+
+        Some(quad_layer(
+            space,
+            &self.swapchain,
+            OVERLAY_RESOLUTION,
+            pose,
+            width,
+        ))
+    }
+}
+
+// Short messages from the streamer or the client, shown at the top of the view for a few seconds. A
+// new message replaces the current one
+pub struct NotificationOverlay {
+    view_space: xr::Space,
+    swapchain: xr::Swapchain<xr::OpenGlEs>,
+    swapchain_images: Vec<u32>,
+    hide_deadline: Option<Instant>,
+}
+
+impl NotificationOverlay {
+    pub fn new(xr_session: &xr::Session<xr::OpenGlEs>) -> Self {
+        let view_space = xr_session
+            .create_reference_space(xr::ReferenceSpaceType::VIEW, xr::Posef::IDENTITY)
+            .unwrap();
+
+        let (swapchain, swapchain_images) = create_swapchain(xr_session, NOTIFICATION_RESOLUTION);
+
+        Self {
+            view_space,
+            swapchain,
+            swapchain_images,
+            hide_deadline: None,
+        }
+    }
+
+    pub fn show(&mut self, message: &str) {
+        draw_text(
+            &mut self.swapchain,
+            &self.swapchain_images,
+            NOTIFICATION_RESOLUTION,
+            message,
+        );
+
+        self.hide_deadline = Some(Instant::now() + NOTIFICATION_DURATION);
+    }
+
+    pub fn hide(&mut self) {
+        self.hide_deadline = None;
+    }
+
+    pub fn layer(&self) -> Option<xr::CompositionLayerQuad<xr::OpenGlEs>> {
+        if !self
+            .hide_deadline
+            .is_some_and(|deadline| Instant::now() < deadline)
+        {
+            return None;
+        }
+
+        Some(quad_layer(
+            &self.view_space,
+            &self.swapchain,
+            NOTIFICATION_RESOLUTION,
+            view_top_pose(),
+            NOTIFICATION_WIDTH_M,
+        ))
+    }
+}
@@ -7,13 +7,15 @@ use crate::{
 use alvr_client_core::{ClientCoreContext, DecodedFrame, Platform};
 use alvr_common::{
     error,
-    glam::{UVec2, Vec2, Vec3},
-    RelaxedAtomic, HAND_LEFT_ID, HAND_RIGHT_ID,
+    glam::{Quat, UVec2, Vec2, Vec3},
+    Pose, RelaxedAtomic, HAND_LEFT_ID, HAND_RIGHT_ID, HEAD_ID, LEFT_MENU_CLICK_ID,
+    LEFT_THUMBSTICK_CLICK_ID, RIGHT_THUMBSTICK_CLICK_ID,
 };
-use alvr_packets::{FaceData, NegotiatedStreamingConfig, ViewParams};
+use alvr_packets::{ButtonEntry, ButtonValue, FaceData, NegotiatedStreamingConfig, ViewParams};
 use alvr_session::{
-    BodyTrackingSourcesConfig, ClientsideFoveationConfig, ClientsideFoveationMode, EncoderConfig,
-    FaceTrackingSourcesConfig, FoveatedEncodingConfig, Settings,
+    BodyTrackingSourcesConfig, ClientPosePredictionConfig, ClientsideFoveationConfig,
+    ClientsideFoveationMode, EncoderConfig, FaceTrackingSourcesConfig, FoveatedEncodingConfig,
+    IdleTrackingConfig, Settings,
 };
 use openxr as xr;
 use std::{
@@ -25,6 +27,9 @@ use std::{
 // When the latency goes too high, if prediction offset is not capped tracking poll will fail.
 const MAX_PREDICTION: Duration = Duration::from_millis(70);
 
+const RECENTER_HOLD_DURATION: Duration = Duration::from_secs(1);
+const PAUSE_HOLD_DURATION: Duration = Duration::from_secs(2);
+
 #[derive(PartialEq)]
 pub struct StreamConfig {
     pub view_resolution: UVec2,
@@ -34,6 +39,14 @@ pub struct StreamConfig {
     pub encoder_config: EncoderConfig,
     pub face_sources_config: Option<FaceTrackingSourcesConfig>,
     pub body_sources_config: Option<BodyTrackingSourcesConfig>,
+    pub pose_prediction_config: Option<ClientPosePredictionConfig>,
+    pub idle_tracking_config: Option<IdleTrackingConfig>,
+    pub tracking_send_rate_hz: Option<f32>,
+    pub vector2_button_values: bool,
+    pub gamepad: bool,
+    pub recenter_gesture: bool,
+    pub pause_gesture: bool,
+    pub playspace_boundary: bool,
 }
 
 impl StreamConfig {
@@ -57,6 +70,14 @@ impl StreamConfig {
                 .body_tracking
                 .as_option()
                 .map(|c| c.sources.clone()),
+            pose_prediction_config: settings.headset.client_pose_prediction.as_option().cloned(),
+            idle_tracking_config: settings.headset.idle_tracking.as_option().cloned(),
+            tracking_send_rate_hz: negotiated_config.tracking_send_rate_hz,
+            vector2_button_values: negotiated_config.vector2_button_values,
+            gamepad: settings.headset.gamepad,
+            recenter_gesture: settings.headset.recenter_gesture,
+            pause_gesture: settings.headset.pause_gesture,
+            playspace_boundary: negotiated_config.playspace_boundary,
         }
     }
 }
@@ -68,10 +89,35 @@ pub struct StreamContext {
     reference_space: Arc<xr::Space>,
     swapchains: [xr::Swapchain<xr::OpenGlEs>; 2],
     view_resolution: UVec2,
-    refresh_rate: f32,
+    input_loop_config: InputLoopConfig,
     last_good_view_params: [ViewParams; 2],
     input_thread: Option<JoinHandle<()>>,
     input_thread_running: Arc<RelaxedAtomic>,
+    stream_paused: Arc<RelaxedAtomic>,
+    playspace_boundary: bool,
+}
+
+// OpenXR only reports the bounds of the stage as a rectangle centered on its origin. Its corners
+// are sent as the boundary if the streamer supports it
+fn send_playspace(core_ctx: &ClientCoreContext, xr_ctx: &XrContext, playspace_boundary: bool) {
+    let area = xr_ctx
+        .session
+        .reference_space_bounds_rect(xr::ReferenceSpaceType::STAGE)
+        .unwrap()
+        .map(|a| Vec2::new(a.width, a.height));
+
+    match area {
+        Some(area) if playspace_boundary => {
+            let half = area / 2.0;
+            core_ctx.send_playspace_boundary(vec![
+                Vec2::new(-half.x, -half.y),
+                Vec2::new(half.x, -half.y),
+                Vec2::new(half.x, half.y),
+                Vec2::new(-half.x, half.y),
+            ]);
+        }
+        _ => core_ctx.send_playspace(area),
+    }
 }
 
 impl StreamContext {
@@ -188,13 +234,12 @@ impl StreamContext {
             config.encoder_config.encoding_gamma,
         );
 
-        core_ctx.send_playspace(
-            xr_ctx
-                .session
-                .reference_space_bounds_rect(xr::ReferenceSpaceType::STAGE)
-                .unwrap()
-                .map(|a| Vec2::new(a.width, a.height)),
-        );
+        core_ctx.send_device_registration(interaction::device_declarations(
+            &interaction_ctx,
+            config.gamepad,
+        ));
+
+        send_playspace(&core_ctx, &xr_ctx, config.playspace_boundary);
 
         core_ctx.send_active_interaction_profile(
             *HAND_LEFT_ID,
@@ -206,24 +251,47 @@ impl StreamContext {
         );
 
         let input_thread_running = Arc::new(RelaxedAtomic::new(true));
+        let stream_paused = Arc::new(RelaxedAtomic::new(false));
 
         let reference_space = Arc::new(interaction::get_stage_reference_space(&xr_ctx.session));
 
+        let max_client_prediction = config
+            .pose_prediction_config
+            .as_ref()
+            .map(|config| Duration::from_secs_f32(config.max_prediction_ms / 1000.0));
+
+        let tracking_interval = match config.tracking_send_rate_hz {
+            Some(rate) => Duration::from_secs_f32(1.0 / rate),
+            None => Duration::from_secs_f32(1.0 / config.refresh_rate_hint) / 3,
+        };
+
+        let input_loop_config = InputLoopConfig {
+            tracking_interval,
+            max_client_prediction,
+            idle_tracking_config: config.idle_tracking_config.clone(),
+            vector2_button_values: config.vector2_button_values,
+            gamepad: config.gamepad,
+            recenter_gesture: config.recenter_gesture,
+            pause_gesture: config.pause_gesture,
+        };
+
         let input_thread = thread::spawn({
             let core_ctx = Arc::clone(&core_ctx);
             let xr_ctx = xr_ctx.clone();
             let interaction_ctx = Arc::clone(&interaction_ctx);
             let reference_space = Arc::clone(&reference_space);
-            let refresh_rate = config.refresh_rate_hint;
+            let input_loop_config = input_loop_config.clone();
             let running = Arc::clone(&input_thread_running);
+            let stream_paused = Arc::clone(&stream_paused);
             move || {
                 stream_input_loop(
                     &core_ctx,
                     xr_ctx,
                     &interaction_ctx,
                     Arc::clone(&reference_space),
-                    refresh_rate,
+                    input_loop_config,
                     running,
+                    stream_paused,
                 )
             }
         });
@@ -235,13 +303,24 @@ impl StreamContext {
             reference_space,
             swapchains,
             view_resolution: config.view_resolution,
-            refresh_rate: config.refresh_rate_hint,
+            input_loop_config,
             last_good_view_params: [ViewParams::default(); 2],
             input_thread: Some(input_thread),
             input_thread_running,
+            stream_paused,
+            playspace_boundary: config.playspace_boundary,
         }
     }
 
+    // While paused the lobby is rendered instead, the tracking is still sent
+    pub fn is_paused(&self) -> bool {
+        self.stream_paused.value()
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.stream_paused.set(paused);
+    }
+
     pub fn update_reference_space(&mut self) {
         self.input_thread_running.set(false);
 
@@ -249,12 +328,10 @@ impl StreamContext {
             &self.xr_context.session,
         ));
 
-        self.core_context.send_playspace(
-            self.xr_context
-                .session
-                .reference_space_bounds_rect(xr::ReferenceSpaceType::STAGE)
-                .unwrap()
-                .map(|a| Vec2::new(a.width, a.height)),
+        send_playspace(
+            &self.core_context,
+            &self.xr_context,
+            self.playspace_boundary,
         );
 
         if let Some(running) = self.input_thread.take() {
@@ -268,16 +345,18 @@ impl StreamContext {
             let xr_ctx = self.xr_context.clone();
             let interaction_ctx = Arc::clone(&self.interaction_context);
             let reference_space = Arc::clone(&self.reference_space);
-            let refresh_rate = self.refresh_rate;
+            let input_loop_config = self.input_loop_config.clone();
             let running = Arc::clone(&self.input_thread_running);
+            let stream_paused = Arc::clone(&self.stream_paused);
             move || {
                 stream_input_loop(
                     &core_ctx,
                     xr_ctx,
                     &interaction_ctx,
                     Arc::clone(&reference_space),
-                    refresh_rate,
+                    input_loop_config,
                     running,
+                    stream_paused,
                 )
             }
         }));
@@ -369,18 +448,185 @@ impl Drop for StreamContext {
     }
 }
 
+// All the buttons clicked and held. Triggers once per hold
+struct HoldGesture {
+    path_ids: Vec<u64>,
+    hold_duration: Duration,
+    pressed: Vec<bool>,
+    hold_start: Option<Instant>,
+    triggered: bool,
+}
+
+impl HoldGesture {
+    fn new(path_ids: Vec<u64>, hold_duration: Duration) -> Self {
+        Self {
+            pressed: vec![false; path_ids.len()],
+            path_ids,
+            hold_duration,
+            hold_start: None,
+            triggered: false,
+        }
+    }
+
+    // Both thumbsticks
+    fn recenter() -> Self {
+        Self::new(
+            vec![*LEFT_THUMBSTICK_CLICK_ID, *RIGHT_THUMBSTICK_CLICK_ID],
+            RECENTER_HOLD_DURATION,
+        )
+    }
+
+    // The left menu button
+    fn pause() -> Self {
+        Self::new(vec![*LEFT_MENU_CLICK_ID], PAUSE_HOLD_DURATION)
+    }
+
+    // The entries contain only the changed buttons
+    fn update(&mut self, entries: &[ButtonEntry], now: Instant) -> bool {
+        for entry in entries {
+            if let Some(index) = self.path_ids.iter().position(|id| *id == entry.path_id) {
+                self.pressed[index] = matches!(entry.value, ButtonValue::Binary(true));
+            }
+        }
+
+        if self.pressed.contains(&false) {
+            self.hold_start = None;
+            self.triggered = false;
+
+            return false;
+        }
+
+        let hold_start = *self.hold_start.get_or_insert(now);
+        if !self.triggered && now >= hold_start + self.hold_duration {
+            self.triggered = true;
+
+            return true;
+        }
+
+        false
+    }
+}
+
+// Detects when the headset and controllers are still and no button is used, and limits the rate at
+// which tracking is sent from then on. Poses are compared with the ones at the start of the still
+// period, so that slow drift is eventually detected as movement.
+struct IdleDetector {
+    config: IdleTrackingConfig,
+    reference_poses: Vec<(u64, Pose)>,
+    still_since: Instant,
+    last_send: Instant,
+}
+
+impl IdleDetector {
+    fn new(config: IdleTrackingConfig) -> Self {
+        Self {
+            config,
+            reference_poses: vec![],
+            still_since: Instant::now(),
+            last_send: Instant::now(),
+        }
+    }
+
+    // Returns true if tracking should be sent in this iteration
+    fn should_send(&mut self, poses: &[(u64, Pose)], input_active: bool) -> bool {
+        let now = Instant::now();
+
+        let position_threshold = self.config.position_threshold_mm / 1000.0;
+        let rotation_threshold = self.config.rotation_threshold_deg.to_radians();
+        let moved = input_active
+            || poses.len() != self.reference_poses.len()
+            || poses.iter().zip(&self.reference_poses).any(
+                |((id, pose), (reference_id, reference_pose))| {
+                    id != reference_id
+                        || pose.position.distance(reference_pose.position) > position_threshold
+                        || pose.orientation.angle_between(reference_pose.orientation)
+                            > rotation_threshold
+                },
+            );
+        if moved {
+            self.reference_poses = poses.to_vec();
+            self.still_since = now;
+        }
+
+        let idle = now
+            .saturating_duration_since(self.still_since)
+            .as_secs_f32()
+            > self.config.idle_timeout_s;
+        if !idle
+            || now.saturating_duration_since(self.last_send).as_secs_f32()
+                >= 1.0 / self.config.idle_send_rate_hz
+        {
+            self.last_send = now;
+
+            true
+        } else {
+            false
+        }
+    }
+}
+
+// Advance a pose in time, rotating it around the pivot. Velocities are expressed in the reference
+// space, as reported by the runtime
+fn extrapolate_pose(
+    pose: Pose,
+    pivot: Vec3,
+    linear_velocity: Vec3,
+    angular_velocity: Vec3,
+    delta_s: f32,
+) -> Pose {
+    let rotation = Quat::from_scaled_axis(angular_velocity * delta_s);
+
+    Pose {
+        orientation: (rotation * pose.orientation).normalize(),
+        position: pivot + linear_velocity * delta_s + rotation * (pose.position - pivot),
+    }
+}
+
+#[derive(Clone)]
+struct InputLoopConfig {
+    tracking_interval: Duration,
+    // If set, poses are sampled at the current time and extrapolated on the client instead of
+    // being predicted by the runtime
+    max_client_prediction: Option<Duration>,
+    idle_tracking_config: Option<IdleTrackingConfig>,
+    vector2_button_values: bool,
+    gamepad: bool,
+    recenter_gesture: bool,
+    pause_gesture: bool,
+}
+
 fn stream_input_loop(
     core_ctx: &ClientCoreContext,
     xr_ctx: XrContext,
     interaction_ctx: &InteractionContext,
     reference_space: Arc<xr::Space>,
-    refresh_rate: f32,
+    config: InputLoopConfig,
     running: Arc<RelaxedAtomic>,
+    stream_paused: Arc<RelaxedAtomic>,
 ) {
+    let InputLoopConfig {
+        tracking_interval,
+        max_client_prediction,
+        idle_tracking_config,
+        vector2_button_values,
+        gamepad,
+        recenter_gesture,
+        pause_gesture,
+    } = config;
+
     let mut last_hand_positions = [Vec3::ZERO; 2];
+    let mut idle_detector = idle_tracking_config.map(IdleDetector::new);
+    let mut gamepad_connected = false;
+    let mut recenter_gesture = recenter_gesture.then(HoldGesture::recenter);
+    let mut pause_gesture = pause_gesture.then(HoldGesture::pause);
+
+    // Used only to query the head velocity
+    let view_space = xr_ctx
+        .session
+        .create_reference_space(xr::ReferenceSpaceType::VIEW, xr::Posef::IDENTITY)
+        .unwrap();
 
     let mut deadline = Instant::now();
-    let frame_interval = Duration::from_secs_f32(1.0 / refresh_rate);
     while running.value() {
         // Streaming related inputs are updated here. Make sure every input poll is done in this
         // thread
@@ -397,12 +643,26 @@ fn stream_input_loop(
             return;
         };
 
-        let target_timestamp =
-            now + Duration::min(core_ctx.get_head_prediction_offset(), MAX_PREDICTION);
+        let mut head_prediction =
+            Duration::min(core_ctx.get_head_prediction_offset(), MAX_PREDICTION);
+        let mut tracker_prediction =
+            Duration::min(core_ctx.get_tracker_prediction_offset(), MAX_PREDICTION);
+        if let Some(max_prediction) = max_client_prediction {
+            head_prediction = Duration::min(head_prediction, max_prediction);
+            tracker_prediction = Duration::min(tracker_prediction, max_prediction);
+        }
+
+        let target_timestamp = now + head_prediction;
+
+        let (views_time, tracker_time) = if max_client_prediction.is_some() {
+            (now, now)
+        } else {
+            (target_timestamp, now + tracker_prediction)
+        };
 
         let Ok((view_flags, views)) = xr_ctx.session.locate_views(
             xr::ViewConfigurationType::PRIMARY_STEREO,
-            crate::to_xr_time(target_timestamp),
+            crate::to_xr_time(views_time),
             &reference_space,
         ) else {
             error!("Cannot locate views");
@@ -415,7 +675,7 @@ fn stream_input_loop(
             continue;
         }
 
-        let view_params = [
+        let mut view_params = [
             ViewParams {
                 pose: from_xr_pose(views[0].pose),
                 fov: crate::from_xr_fov(views[0].fov),
@@ -426,11 +686,43 @@ fn stream_input_loop(
             },
         ];
 
+        // If the runtime does not report velocities, they are zero and the poses are left as is
+        if max_client_prediction.is_some() {
+            if let Ok((_, velocity)) = view_space.relate(&reference_space, crate::to_xr_time(now)) {
+                let linear_velocity = if velocity
+                    .velocity_flags
+                    .contains(xr::SpaceVelocityFlags::LINEAR_VALID)
+                {
+                    crate::from_xr_vec3(velocity.linear_velocity)
+                } else {
+                    Vec3::ZERO
+                };
+                let angular_velocity = if velocity
+                    .velocity_flags
+                    .contains(xr::SpaceVelocityFlags::ANGULAR_VALID)
+                {
+                    crate::from_xr_vec3(velocity.angular_velocity)
+                } else {
+                    Vec3::ZERO
+                };
+
+                let head_position =
+                    (view_params[0].pose.position + view_params[1].pose.position) / 2.0;
+                for view in &mut view_params {
+                    view.pose = extrapolate_pose(
+                        view.pose,
+                        head_position,
+                        linear_velocity,
+                        angular_velocity,
+                        head_prediction.as_secs_f32(),
+                    );
+                }
+            }
+        }
+
         let mut device_motions = Vec::with_capacity(3);
 
-        let tracker_time = crate::to_xr_time(
-            now + Duration::min(core_ctx.get_tracker_prediction_offset(), MAX_PREDICTION),
-        );
+        let tracker_time = crate::to_xr_time(tracker_time);
 
         let (left_hand_motion, left_hand_skeleton) = crate::interaction::get_hand_motion(
             &xr_ctx.session,
@@ -447,11 +739,23 @@ fn stream_input_loop(
             &mut last_hand_positions[1],
         );
 
-        if let Some(motion) = left_hand_motion {
-            device_motions.push((*HAND_LEFT_ID, motion));
-        }
-        if let Some(motion) = right_hand_motion {
-            device_motions.push((*HAND_RIGHT_ID, motion));
+        for (device_id, motion) in [
+            (*HAND_LEFT_ID, left_hand_motion),
+            (*HAND_RIGHT_ID, right_hand_motion),
+        ] {
+            if let Some(mut motion) = motion {
+                if max_client_prediction.is_some() {
+                    motion.pose = extrapolate_pose(
+                        motion.pose,
+                        motion.pose.position,
+                        motion.linear_velocity,
+                        motion.angular_velocity,
+                        tracker_prediction.as_secs_f32(),
+                    );
+                }
+
+                device_motions.push((device_id, motion));
+            }
         }
 
         let face_data = FaceData {
@@ -480,21 +784,97 @@ fn stream_input_loop(
             ));
         }
 
-        core_ctx.send_tracking(
-            target_timestamp,
-            view_params,
-            device_motions,
-            [left_hand_skeleton, right_hand_skeleton],
-            face_data,
+        let mut button_entries = interaction::update_buttons(
+            &xr_ctx.session,
+            &interaction_ctx.button_actions,
+            vector2_button_values,
         );
+        if let Some(gamepad_interaction) = interaction_ctx.gamepad.as_ref().filter(|_| gamepad) {
+            button_entries.append(&mut interaction::update_gamepad_buttons(
+                &xr_ctx.session,
+                gamepad_interaction,
+                vector2_button_values,
+                &mut gamepad_connected,
+            ));
+        }
+
+        let send_tracking = if let Some(detector) = &mut idle_detector {
+            let poses = [(*HEAD_ID, view_params[0].pose)]
+                .into_iter()
+                .chain(device_motions.iter().map(|(id, motion)| (*id, motion.pose)))
+                .collect::<Vec<_>>();
+
+            detector.should_send(&poses, !button_entries.is_empty())
+        } else {
+            true
+        };
+
+        if send_tracking {
+            core_ctx.send_tracking(
+                target_timestamp,
+                view_params,
+                device_motions,
+                [left_hand_skeleton, right_hand_skeleton],
+                face_data,
+            );
+        }
+
+        if recenter_gesture
+            .as_mut()
+            .is_some_and(|gesture| gesture.update(&button_entries, Instant::now()))
+        {
+            core_ctx.send_recenter();
+        }
+
+        // The state is updated when the streamer answers
+        if pause_gesture
+            .as_mut()
+            .is_some_and(|gesture| gesture.update(&button_entries, Instant::now()))
+        {
+            core_ctx.request_stream_paused(!stream_paused.value());
+        }
 
-        let button_entries =
-            interaction::update_buttons(&xr_ctx.session, &interaction_ctx.button_actions);
         if !button_entries.is_empty() {
             core_ctx.send_buttons(button_entries);
         }
 
-        deadline += frame_interval / 3;
+        deadline += tracking_interval;
         thread::sleep(deadline.saturating_duration_since(Instant::now()));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn button(path_id: u64, pressed: bool) -> ButtonEntry {
+        ButtonEntry {
+            path_id,
+            value: ButtonValue::Binary(pressed),
+        }
+    }
+
+    #[test]
+    fn test_hold_gesture() {
+        let mut gesture = HoldGesture::new(vec![1, 2], Duration::from_secs(1));
+        let start = Instant::now();
+        let after = |millis| start + Duration::from_millis(millis);
+
+        // Both buttons must be held
+        assert!(!gesture.update(&[button(1, true)], start));
+        assert!(!gesture.update(&[], after(1500)));
+
+        assert!(!gesture.update(&[button(2, true)], after(2000)));
+        assert!(!gesture.update(&[], after(2900)));
+        assert!(gesture.update(&[], after(3000)));
+
+        // Only once per hold
+        assert!(!gesture.update(&[], after(5000)));
+
+        // Releasing a button restarts the hold
+        assert!(!gesture.update(&[button(1, false)], after(5100)));
+        assert!(!gesture.update(&[button(1, true)], after(5200)));
+        assert!(!gesture.update(&[], after(6100)));
+        assert!(gesture.update(&[], after(6200)));
+    }
+}
@@ -0,0 +1,170 @@
+// Cells for values that are published by one thread and read by others, where a slightly stale
+// value is fine. All accesses use Ordering::Relaxed: no other memory is synchronized through them.
+
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+// Stored as the bits of the float
+pub struct RelaxedAtomicF32(AtomicU32);
+
+impl RelaxedAtomicF32 {
+    pub fn new(initial_value: f32) -> Self {
+        Self(AtomicU32::new(initial_value.to_bits()))
+    }
+
+    pub fn get(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    pub fn set(&self, value: f32) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+}
+
+pub struct RelaxedAtomicU64(AtomicU64);
+
+impl RelaxedAtomicU64 {
+    pub const fn new(initial_value: u64) -> Self {
+        Self(AtomicU64::new(initial_value))
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    pub fn set(&self, value: u64) {
+        self.0.store(value, Ordering::Relaxed);
+    }
+
+    // Returns the new value. Wraps around on overflow
+    pub fn add(&self, value: u64) -> u64 {
+        self.0
+            .fetch_add(value, Ordering::Relaxed)
+            .wrapping_add(value)
+    }
+}
+
+// Exponential moving average that weights each sample as one of the last window_size samples.
+// The first samples are averaged uniformly, so the average does not start biased towards 0.
+// Samples submitted at the same time by two threads can be partially lost, which does not matter
+// for an average; use one submitting thread if every sample must count.
+pub struct SlidingAverage {
+    average: RelaxedAtomicF32,
+    sample_count: RelaxedAtomicU64,
+    window_size: u64,
+}
+
+impl SlidingAverage {
+    pub const fn new(window_size: u64) -> Self {
+        Self {
+            // The bits of 0.0 are all zero, but f32::to_bits() is not const
+            average: RelaxedAtomicF32(AtomicU32::new(0)),
+            sample_count: RelaxedAtomicU64::new(0),
+            window_size: if window_size == 0 { 1 } else { window_size },
+        }
+    }
+
+    pub fn submit_sample(&self, sample: f32) {
+        let count = self.sample_count.add(1).min(self.window_size);
+
+        let average = self.average.get();
+        self.average
+            .set(average + (sample - average) / count as f32);
+    }
+
+    // None until the first sample
+    pub fn get_average(&self) -> Option<f32> {
+        (self.sample_count.get() > 0).then(|| self.average.get())
+    }
+
+    pub fn reset(&self) {
+        self.sample_count.set(0);
+        self.average.set(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{sync::Arc, thread};
+
+    #[test]
+    fn test_f32_round_trip() {
+        let cell = RelaxedAtomicF32::new(1.5);
+        assert_eq!(cell.get(), 1.5);
+
+        for value in [0.0, -0.0, -3.25, f32::MAX, f32::MIN_POSITIVE, f32::INFINITY] {
+            cell.set(value);
+            assert_eq!(cell.get().to_bits(), value.to_bits());
+        }
+
+        cell.set(f32::NAN);
+        assert!(cell.get().is_nan());
+    }
+
+    #[test]
+    fn test_u64_add() {
+        let cell = RelaxedAtomicU64::new(5);
+        assert_eq!(cell.add(3), 8);
+        assert_eq!(cell.get(), 8);
+
+        cell.set(u64::MAX);
+        assert_eq!(cell.add(2), 1);
+    }
+
+    #[test]
+    fn test_sliding_average() {
+        let average = SlidingAverage::new(4);
+        assert_eq!(average.get_average(), None);
+
+        // Uniform average while the window fills up
+        for sample in [2.0, 4.0, 6.0] {
+            average.submit_sample(sample);
+        }
+        assert_eq!(average.get_average(), Some(4.0));
+
+        // Then each sample moves the average by a quarter of the difference
+        average.submit_sample(8.0);
+        average.submit_sample(10.0);
+        assert_eq!(average.get_average(), Some(6.25));
+
+        average.reset();
+        assert_eq!(average.get_average(), None);
+        average.submit_sample(1.0);
+        assert_eq!(average.get_average(), Some(1.0));
+    }
+
+    #[test]
+    fn test_multithreaded_smoke() {
+        const THREADS: u64 = 8;
+        const ADDS_PER_THREAD: u64 = 10_000;
+
+        let counter = Arc::new(RelaxedAtomicU64::new(0));
+        let value = Arc::new(RelaxedAtomicF32::new(0.0));
+        let average = Arc::new(SlidingAverage::new(16));
+
+        let handles = (0..THREADS)
+            .map(|index| {
+                let counter = Arc::clone(&counter);
+                let value = Arc::clone(&value);
+                let average = Arc::clone(&average);
+                thread::spawn(move || {
+                    for _ in 0..ADDS_PER_THREAD {
+                        counter.add(1);
+                        value.set(index as f32);
+                        average.submit_sample(10.0);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        // Additions are never lost
+        assert_eq!(counter.get(), THREADS * ADDS_PER_THREAD);
+        // Floats are never torn
+        assert!((0..THREADS).any(|index| value.get() == index as f32));
+        // Lost samples do not move a constant average, apart from rounding
+        assert!((average.get_average().unwrap() - 10.0).abs() < 1e-3);
+    }
+}
@@ -20,6 +20,7 @@ interaction_profile!(PICO_NEO3, "bytedance/pico_neo3");
 interaction_profile!(PICO4, "bytedance/pico4");
 interaction_profile!(FOCUS3, "htc/vive_focus3");
 interaction_profile!(YVR, "yvr/touch");
+interaction_profile!(XBOX, "microsoft/xbox");
 
 macro_rules! devices {
     ($(($name:ident, $path:expr),)*) => {
@@ -52,11 +53,13 @@ devices! {
     (BODY_LEFT_FOOT, "/user/body/left_foot"),
     (BODY_RIGHT_KNEE, "/user/body/right_knee"),
     (BODY_RIGHT_FOOT, "/user/body/right_foot"),
+    (GAMEPAD, "/user/gamepad"),
 }
 
 pub enum ButtonType {
     Binary,
     Scalar,
+    Vector2,
 }
 
 pub struct ButtonInfo {
@@ -125,10 +128,12 @@ controller_inputs! {
     (TRIGGER_CLICK, "trigger/click", Binary),
     (TRIGGER_VALUE, "trigger/value", Scalar),
     (TRIGGER_TOUCH, "trigger/touch", Binary),
+    (THUMBSTICK, "thumbstick", Vector2),
     (THUMBSTICK_X, "thumbstick/x", Scalar),
     (THUMBSTICK_Y, "thumbstick/y", Scalar),
     (THUMBSTICK_CLICK, "thumbstick/click", Binary),
     (THUMBSTICK_TOUCH, "thumbstick/touch", Binary),
+    (TRACKPAD, "trackpad", Vector2),
     (TRACKPAD_X, "trackpad/x", Scalar),
     (TRACKPAD_Y, "trackpad/y", Scalar),
     (TRACKPAD_CLICK, "trackpad/click", Binary),
@@ -137,6 +142,97 @@ controller_inputs! {
     (THUMBREST_TOUCH, "thumbrest/touch", Binary),
 }
 
+macro_rules! gamepad_inputs {
+    ($(($inputs:ident, $paths:literal, $ty:ident),)*) => {
+        paste::paste! {
+            $(
+                pub const [<GAMEPAD_ $inputs _PATH>]: &str =
+                    concat!("/user/gamepad/input/", $paths);
+                pub static [<GAMEPAD_ $inputs _ID>]: Lazy<u64> =
+                    Lazy::new(|| hash_string([<GAMEPAD_ $inputs _PATH>]));
+            )*
+
+            pub static GAMEPAD_BUTTON_INFO: Lazy<HashMap<u64, ButtonInfo>> = Lazy::new(|| {
+                [
+                    $((
+                        *[<GAMEPAD_ $inputs _ID>],
+                        ButtonInfo {
+                            path: [<GAMEPAD_ $inputs _PATH>],
+                            button_type: ButtonType::$ty,
+                            device_id: *GAMEPAD_ID,
+                        },
+                    ),)*
+                ]
+                .into_iter()
+                .collect()
+            });
+        }
+    };
+}
+
+// Canonical gamepad layout, the paths of the OpenXR Xbox controller profile. Buttons are binary.
+// Triggers range from 0 (released) to 1. Thumbstick axes range from -1 to 1, with x pointing right
+// and y pointing up. A gamepad that is disconnected reports all inputs released
+gamepad_inputs! {
+    (MENU_CLICK, "menu/click", Binary),
+    (VIEW_CLICK, "view/click", Binary),
+    (A_CLICK, "a/click", Binary),
+    (B_CLICK, "b/click", Binary),
+    (X_CLICK, "x/click", Binary),
+    (Y_CLICK, "y/click", Binary),
+    (DPAD_UP_CLICK, "dpad_up/click", Binary),
+    (DPAD_DOWN_CLICK, "dpad_down/click", Binary),
+    (DPAD_LEFT_CLICK, "dpad_left/click", Binary),
+    (DPAD_RIGHT_CLICK, "dpad_right/click", Binary),
+    (SHOULDER_LEFT_CLICK, "shoulder_left/click", Binary),
+    (SHOULDER_RIGHT_CLICK, "shoulder_right/click", Binary),
+    (THUMBSTICK_LEFT_CLICK, "thumbstick_left/click", Binary),
+    (THUMBSTICK_RIGHT_CLICK, "thumbstick_right/click", Binary),
+    (TRIGGER_LEFT_VALUE, "trigger_left/value", Scalar),
+    (TRIGGER_RIGHT_VALUE, "trigger_right/value", Scalar),
+    (THUMBSTICK_LEFT, "thumbstick_left", Vector2),
+    (THUMBSTICK_LEFT_X, "thumbstick_left/x", Scalar),
+    (THUMBSTICK_LEFT_Y, "thumbstick_left/y", Scalar),
+    (THUMBSTICK_RIGHT, "thumbstick_right", Vector2),
+    (THUMBSTICK_RIGHT_X, "thumbstick_right/x", Scalar),
+    (THUMBSTICK_RIGHT_Y, "thumbstick_right/y", Scalar),
+}
+
+// Vector2 inputs and their x and y components
+pub static VECTOR2_COMPONENTS: Lazy<HashMap<u64, [u64; 2]>> = Lazy::new(|| {
+    [
+        (
+            *LEFT_THUMBSTICK_ID,
+            [*LEFT_THUMBSTICK_X_ID, *LEFT_THUMBSTICK_Y_ID],
+        ),
+        (
+            *RIGHT_THUMBSTICK_ID,
+            [*RIGHT_THUMBSTICK_X_ID, *RIGHT_THUMBSTICK_Y_ID],
+        ),
+        (
+            *LEFT_TRACKPAD_ID,
+            [*LEFT_TRACKPAD_X_ID, *LEFT_TRACKPAD_Y_ID],
+        ),
+        (
+            *RIGHT_TRACKPAD_ID,
+            [*RIGHT_TRACKPAD_X_ID, *RIGHT_TRACKPAD_Y_ID],
+        ),
+        (
+            *GAMEPAD_THUMBSTICK_LEFT_ID,
+            [*GAMEPAD_THUMBSTICK_LEFT_X_ID, *GAMEPAD_THUMBSTICK_LEFT_Y_ID],
+        ),
+        (
+            *GAMEPAD_THUMBSTICK_RIGHT_ID,
+            [
+                *GAMEPAD_THUMBSTICK_RIGHT_X_ID,
+                *GAMEPAD_THUMBSTICK_RIGHT_Y_ID,
+            ],
+        ),
+    ]
+    .into_iter()
+    .collect()
+});
+
 pub struct InteractionProfileInfo {
     pub path: &'static str,
     pub button_set: HashSet<u64>,
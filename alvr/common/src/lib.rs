@@ -1,3 +1,4 @@
+mod atomics;
 mod average;
 mod connection_result;
 mod inputs;
@@ -17,6 +18,7 @@ pub use parking_lot;
 pub use semver;
 pub use settings_schema;
 
+pub use atomics::*;
 pub use average::*;
 pub use connection_result::*;
 pub use inputs::*;
@@ -26,6 +28,9 @@ pub use primitives::*;
 pub use version::*;
 
 pub const ALVR_NAME: &str = "ALVR";
+// Discovery always uses this UDP port, so that clients find the streamer whatever its control
+// port. Also the control port of a client that never connected to a streamer
+pub const DEFAULT_CONTROL_PORT: u16 = 9943;
 
 pub type OptLazy<T> = Lazy<Mutex<Option<T>>>;
 
@@ -69,6 +74,30 @@ pub enum ConnectionState {
     Disconnecting,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GpuVendor {
+    Nvidia,
+    Amd,
+    Intel,
+    Unknown,
+}
+
+impl GpuVendor {
+    pub fn from_pci_vendor_id(id: u32) -> Self {
+        match id {
+            0x10de => Self::Nvidia,
+            0x1002 => Self::Amd,
+            0x8086 => Self::Intel,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+// The encoder works on blocks of 32 pixels, the streamed resolution is rounded down
+pub fn align32(value: f32) -> u32 {
+    ((value / 32.).floor() * 32.) as u32
+}
+
 pub fn wait_rwlock<T>(condvar: &Condvar, guard: &mut RwLockWriteGuard<'_, T>) {
     let staging_mutex = Mutex::<()>::new(());
     let mut inner_guard = staging_mutex.lock();
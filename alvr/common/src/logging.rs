@@ -1,8 +1,23 @@
 use anyhow::Result;
 use backtrace::Backtrace;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use settings_schema::SettingsSchema;
-use std::{error::Error, fmt::Display};
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt::{Display, Write},
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const MAX_RECENT_LOG_RECORDS: usize = 1000;
+const MAX_RECENT_LOGS_BYTES: usize = 256 * 1024;
+// Longer messages are truncated, so that a single one cannot evict all the others
+const MAX_RECENT_LOG_RECORD_BYTES: usize = 16 * 1024;
+// While panicking, the buffer may be held by the panicking thread itself
+const PANIC_LOCK_TIMEOUT: Duration = Duration::from_millis(100);
 
 #[derive(
     SettingsSchema, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord,
@@ -40,6 +55,96 @@ pub struct LogEntry {
     pub content: String,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RecentLogRecord {
+    pub timestamp_ms: u64,
+    pub severity: LogSeverity,
+    pub content: String,
+}
+
+struct RecentLogs {
+    records: VecDeque<RecentLogRecord>,
+    size_bytes: usize,
+}
+
+// Allocated once with the maximum number of records, pushing only moves the formatted message in
+static RECENT_LOGS: Lazy<Mutex<RecentLogs>> = Lazy::new(|| {
+    Mutex::new(RecentLogs {
+        records: VecDeque::with_capacity(MAX_RECENT_LOG_RECORDS),
+        size_bytes: 0,
+    })
+});
+
+// Called by the logging backends for each record they let through
+pub fn push_recent_log(severity: LogSeverity, mut content: String) {
+    if content.len() > MAX_RECENT_LOG_RECORD_BYTES {
+        let mut end = MAX_RECENT_LOG_RECORD_BYTES;
+        while !content.is_char_boundary(end) {
+            end -= 1;
+        }
+        content.truncate(end);
+    }
+
+    let timestamp_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or_default();
+
+    // The panic hook logs too, the record is dropped rather than waiting for the lock forever
+    let mut logs = if thread::panicking() {
+        let Some(logs) = RECENT_LOGS.try_lock_for(PANIC_LOCK_TIMEOUT) else {
+            return;
+        };
+        logs
+    } else {
+        RECENT_LOGS.lock()
+    };
+    while logs.records.len() == MAX_RECENT_LOG_RECORDS
+        || (!logs.records.is_empty() && logs.size_bytes + content.len() > MAX_RECENT_LOGS_BYTES)
+    {
+        if let Some(record) = logs.records.pop_front() {
+            logs.size_bytes -= record.content.len();
+        }
+    }
+    logs.size_bytes += content.len();
+    logs.records.push_back(RecentLogRecord {
+        timestamp_ms,
+        severity,
+        content,
+    });
+}
+
+// Oldest first
+pub fn get_recent_logs() -> Vec<RecentLogRecord> {
+    RECENT_LOGS.lock().records.iter().cloned().collect()
+}
+
+// None if the buffer is empty, or if the panic happened while the buffer was locked
+fn recent_logs_dump() -> Option<String> {
+    let logs = RECENT_LOGS.try_lock_for(PANIC_LOCK_TIMEOUT)?;
+    if logs.records.is_empty() {
+        return None;
+    }
+
+    let mut dump = String::with_capacity(logs.size_bytes + logs.records.len() * 32);
+    for record in &logs.records {
+        let ms_of_day = record.timestamp_ms % (24 * 3600 * 1000);
+        writeln!(
+            dump,
+            "{:02}:{:02}:{:02}.{:03} UTC [{:?}] {}",
+            ms_of_day / 3_600_000,
+            ms_of_day / 60_000 % 60,
+            ms_of_day / 1000 % 60,
+            ms_of_day % 1000,
+            record.severity,
+            record.content
+        )
+        .ok();
+    }
+
+    Some(dump)
+}
+
 pub fn set_panic_hook() {
     std::panic::set_hook(Box::new(|panic_info| {
         let err_str = format!(
@@ -47,6 +152,11 @@ pub fn set_panic_hook() {
             Backtrace::new()
         );
 
+        // Logged as an error so that the records also end up in the crash log
+        if let Some(dump) = recent_logs_dump() {
+            log::error!("Last log records before the panic:\n{dump}");
+        }
+
         log::error!("{err_str}");
 
         #[cfg(all(not(target_os = "android"), feature = "enable-messagebox"))]
@@ -85,9 +195,6 @@ fn show_e_block<E: Display>(e: E, blocking: bool) {
     {
         // Store the last error shown in a message box. Do not open a new message box if the content
         // of the error has not changed
-        use once_cell::sync::Lazy;
-        use parking_lot::Mutex;
-
         static LAST_MESSAGEBOX_ERROR: Lazy<Mutex<String>> = Lazy::new(|| Mutex::new("".into()));
 
         let err_string = e.to_string();
@@ -23,10 +23,8 @@ pub fn is_stable() -> bool {
     ALVR_VERSION.pre.is_empty() && !is_nightly()
 }
 
-// Semver compatible versions will produce the same protocol ID. Protocol IDs are not ordered
-// As a convention, encode/decode the protocol ID bytes as little endian.
-// Only makor and
-pub fn protocol_id() -> String {
+// Part of the protocol ID, together with the hash of the packet definitions, see alvr_packets
+pub fn version_protocol_id() -> String {
     if ALVR_VERSION.pre.is_empty() {
         ALVR_VERSION.major.to_string()
     } else {
@@ -34,10 +32,6 @@ pub fn protocol_id() -> String {
     }
 }
 
-pub fn protocol_id_u64() -> u64 {
-    hash_string(&protocol_id())
-}
-
 // deprecated
 pub fn is_version_compatible(other_version: &Version) -> bool {
     let protocol_string = if other_version.pre.is_empty() {
@@ -46,5 +40,5 @@ pub fn is_version_compatible(other_version: &Version) -> bool {
         format!("{}-{}", other_version.major, other_version.pre)
     };
 
-    protocol_id_u64() == hash_string(&protocol_string)
+    hash_string(&version_protocol_id()) == hash_string(&protocol_string)
 }
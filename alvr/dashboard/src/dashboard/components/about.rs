@@ -1,24 +1,144 @@
-use alvr_common::ALVR_VERSION;
-use eframe::egui::{RichText, Ui};
-
-pub fn about_tab_ui(ui: &mut Ui) {
-    ui.label(RichText::new(format!("ALVR streamer v{}", *ALVR_VERSION)).size(30.0));
-    ui.label(
-r#"Stream VR games from your PC to your headset via Wi-Fi.
-ALVR uses technologies like Asynchronous TimeWarp (ATW) and Fixed Foveated Rendering (FFR) for a smoother experience.
-All games that work with an Oculus Rift(s) should work with ALVR.
-This is a fork of ALVR that works with Meta Quest and other standalone headsets.
-"#
-    );
-    ui.add_space(10.0);
-    ui.hyperlink_to("Visit us on GitHub", "https://github.com/alvr-org/ALVR");
-    ui.hyperlink_to("Join us on Discord", "https://discord.gg/ALVR");
-    ui.hyperlink_to(
-        "Latest release",
-        "https://github.com/alvr-org/ALVR/releases/latest",
-    );
-    ui.hyperlink_to(
-        "Donate to ALVR on Open Collective",
-        "https://opencollective.com/alvr",
-    );
+use crate::dashboard::localization::{tr, tr_args};
+use alvr_common::{parking_lot::Mutex, semver::Version, ConnectionState, ALVR_VERSION};
+use alvr_gui_common::theme;
+use alvr_session::{SessionConfig, Settings};
+use eframe::egui::{Grid, RichText, Ui};
+use std::sync::Arc;
+
+#[cfg(not(target_arch = "wasm32"))]
+const LATEST_RELEASE_API_URL: &str = "https://api.github.com/repos/alvr-org/ALVR/releases/latest";
+#[cfg(not(target_arch = "wasm32"))]
+const UPDATE_CHECK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(3);
+
+struct ReleaseInfo {
+    version: Version,
+    url: String,
+}
+
+// Any error, including being offline, is ignored
+#[cfg(not(target_arch = "wasm32"))]
+fn fetch_latest_release() -> Option<ReleaseInfo> {
+    let response = ureq::AgentBuilder::new()
+        .timeout(UPDATE_CHECK_TIMEOUT)
+        .build()
+        .get(LATEST_RELEASE_API_URL)
+        .set("User-Agent", "ALVR-dashboard")
+        .call()
+        .ok()?
+        .into_json::<serde_json::Value>()
+        .ok()?;
+
+    Some(ReleaseInfo {
+        version: Version::parse(response["tag_name"].as_str()?.trim_start_matches('v')).ok()?,
+        url: response["html_url"].as_str()?.to_owned(),
+    })
+}
+
+struct ClientEntry {
+    hostname: String,
+    display_name: String,
+    version: Option<Version>,
+}
+
+pub struct AboutTab {
+    connected_clients: Vec<ClientEntry>,
+    update_check_started: bool,
+    latest_release: Arc<Mutex<Option<ReleaseInfo>>>,
+}
+
+impl AboutTab {
+    pub fn new() -> Self {
+        Self {
+            connected_clients: vec![],
+            update_check_started: false,
+            latest_release: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn update_client_list(&mut self, session: &SessionConfig) {
+        self.connected_clients = session
+            .client_connections
+            .iter()
+            .filter(|(_, config)| config.connection_state != ConnectionState::Disconnected)
+            .map(|(hostname, config)| ClientEntry {
+                hostname: hostname.clone(),
+                display_name: config.display_name.clone(),
+                version: config.version.clone(),
+            })
+            .collect();
+        self.connected_clients
+            .sort_by(|a, b| a.hostname.cmp(&b.hostname));
+    }
+
+    // The check runs at most once per launch, the first time it is found enabled
+    pub fn update_settings(&mut self, settings: &Settings) {
+        if !settings.check_for_updates || self.update_check_started {
+            return;
+        }
+        self.update_check_started = true;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        std::thread::spawn({
+            let latest_release = Arc::clone(&self.latest_release);
+            move || {
+                *latest_release.lock() = fetch_latest_release();
+            }
+        });
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) {
+        ui.label(RichText::new(format!("ALVR streamer v{}", *ALVR_VERSION)).size(30.0));
+        ui.label(tr("about.description"));
+
+        if let Some(release) = &*self.latest_release.lock() {
+            // Prerelease versions are ordered before the release with the same number
+            if release.version > *ALVR_VERSION {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(tr_args(
+                            "about.update_available",
+                            &[("version", &release.version.to_string())],
+                        ))
+                        .color(theme::palette().ok_green),
+                    );
+                    ui.hyperlink_to(tr("about.download"), &release.url);
+                });
+            }
+        }
+
+        ui.add_space(10.0);
+        Grid::new("about_versions").num_columns(2).show(ui, |ui| {
+            ui.label(tr("about.streamer_version"));
+            ui.label(ALVR_VERSION.to_string());
+            ui.end_row();
+
+            ui.label(tr("about.protocol_id"));
+            ui.label(alvr_packets::protocol_id());
+            ui.end_row();
+
+            if self.connected_clients.is_empty() {
+                ui.label(tr("about.client_version"));
+                ui.label(tr("about.no_client_connected"));
+                ui.end_row();
+            }
+            for client in &self.connected_clients {
+                ui.label(format!("{} ({}):", client.display_name, client.hostname));
+                if let Some(version) = &client.version {
+                    ui.label(version.to_string());
+                } else {
+                    ui.label(tr("about.unknown_version"));
+                }
+                ui.end_row();
+            }
+        });
+
+        ui.add_space(10.0);
+        ui.hyperlink_to(tr("about.github"), "https://github.com/alvr-org/ALVR");
+        ui.hyperlink_to(tr("about.discord"), "https://discord.gg/ALVR");
+        ui.hyperlink_to(
+            tr("about.latest_release"),
+            "https://github.com/alvr-org/ALVR/releases/latest",
+        );
+        ui.hyperlink_to(tr("about.donate"), "https://opencollective.com/alvr");
+    }
 }
@@ -1,24 +1,278 @@
-use crate::dashboard::ServerRequest;
-use alvr_common::ConnectionState;
-use alvr_gui_common::theme::{self, log_colors};
+use crate::dashboard::{
+    localization::{tr, tr_args},
+    ServerRequest,
+};
+use alvr_common::{glam::UVec2, ConnectionState};
+use alvr_events::{
+    ClientDisconnectedEvent, ClientPairingEvent, DisconnectionReason, GraphStatistics,
+    PairingState, StreamSetupEvent, StreamStartedEvent,
+};
+use alvr_gui_common::theme;
 use alvr_packets::ClientListAction;
-use alvr_session::{ClientConnectionConfig, SessionConfig};
+use alvr_session::{ClientConnectionConfig, CodecType, HeadsetInfo, SessionConfig, Settings};
+use chrono::{DateTime, Local};
 use eframe::{
-    egui::{self, Frame, Grid, Layout, RichText, TextEdit, Ui, Window},
+    egui::{self, Button, CollapsingHeader, Frame, Grid, Layout, RichText, TextEdit, Ui, Window},
     emath::{Align, Align2},
     epaint::Color32,
 };
+use std::{collections::HashMap, net::IpAddr, time::Duration};
+
+#[cfg(target_arch = "wasm32")]
+use instant::Instant;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
 
 struct EditPopupState {
     new_client: bool,
     hostname: String,
+    // Used only for new clients
+    display_name: String,
     ips: Vec<String>,
 }
 
+fn time_text(unix_secs: u64) -> String {
+    (unix_secs != 0)
+        .then(|| DateTime::from_timestamp(unix_secs as i64, 0))
+        .flatten()
+        .map(|time| {
+            time.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_else(|| tr("connections.unknown_time"))
+}
+
+fn last_seen_text(last_seen: u64) -> String {
+    tr_args("connections.last_seen", &[("time", &time_text(last_seen))])
+}
+
+fn resolution_text(resolution: UVec2) -> String {
+    format!("{}x{}", resolution.x, resolution.y)
+}
+
+fn codec_name(codec: CodecType) -> &'static str {
+    match codec {
+        CodecType::H264 => "H264",
+        CodecType::Hevc => "HEVC",
+        CodecType::AV1 => "AV1",
+    }
+}
+
+fn yes_no_text(value: bool) -> String {
+    tr(if value {
+        "connections.yes"
+    } else {
+        "connections.no"
+    })
+}
+
+// The streamer reaches the clients connected via USB through localhost
+fn address_text(ip: Option<IpAddr>) -> String {
+    match ip {
+        Some(ip) if ip.is_loopback() => tr("connections.via_usb"),
+        Some(ip) => ip.to_string(),
+        None => tr("connections.unknown_ip"),
+    }
+}
+
+// Capabilities sent by older clients may be missing
+fn capability_text(info: &HeadsetInfo, key: &str) -> String {
+    info.extra_capabilities
+        .get(key)
+        .and_then(|value| value.as_bool())
+        .map(yes_no_text)
+        .unwrap_or_else(|| tr("connections.not_advertised"))
+}
+
+// Rows where the negotiated value differs from the advertised one are highlighted
+fn headset_info_ui(ui: &mut Ui, hostname: &str, info: &HeadsetInfo, state: &ConnectionState) {
+    CollapsingHeader::new(tr("connections.headset_info"))
+        .id_source(format!("{hostname}-headset-info"))
+        .show(ui, |ui| {
+            if !matches!(
+                state,
+                ConnectionState::Connected | ConnectionState::Streaming
+            ) {
+                ui.label(tr_args(
+                    "connections.headset_info_captured",
+                    &[("time", &time_text(info.captured_at))],
+                ));
+            }
+
+            if let Some(model) = info
+                .extra_capabilities
+                .get("device_model")
+                .and_then(|value| value.as_str())
+            {
+                let manufacturer = info
+                    .extra_capabilities
+                    .get("device_manufacturer")
+                    .and_then(|value| value.as_str())
+                    .unwrap_or_default();
+                ui.label(tr_args(
+                    "connections.device_model",
+                    &[("model", model), ("manufacturer", manufacturer)],
+                ));
+            }
+
+            let refresh_rates = info
+                .supported_refresh_rates
+                .iter()
+                .map(|rate| rate.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let capability = |key| capability_text(info, key);
+            let rows = [
+                (
+                    "connections.view_resolution",
+                    resolution_text(info.default_view_resolution),
+                    resolution_text(info.negotiated_view_resolution),
+                    info.default_view_resolution != info.negotiated_view_resolution,
+                ),
+                (
+                    "connections.refresh_rate",
+                    format!("{refresh_rates} Hz"),
+                    format!("{} Hz", info.negotiated_refresh_rate),
+                    info.preferred_refresh_rate
+                        .map(|rate| rate != info.negotiated_refresh_rate)
+                        .unwrap_or(false),
+                ),
+                (
+                    "connections.preferred_refresh_rate",
+                    info.preferred_refresh_rate
+                        .map(|rate| format!("{rate} Hz"))
+                        .unwrap_or_else(|| tr("connections.not_advertised")),
+                    String::new(),
+                    false,
+                ),
+                (
+                    "connections.microphone_sample_rate",
+                    format!("{} Hz", info.microphone_sample_rate),
+                    String::new(),
+                    false,
+                ),
+                (
+                    "connections.codec",
+                    tr_args(
+                        "connections.av1_support",
+                        &[("support", &capability("encoder_av1"))],
+                    ),
+                    codec_name(info.negotiated_codec).into(),
+                    false,
+                ),
+                (
+                    "connections.foveated_encoding",
+                    capability("supports_foveated_encoding"),
+                    yes_no_text(info.negotiated_foveated_encoding),
+                    info.negotiated_foveated_encoding
+                        && info.extra_capabilities.get("supports_foveated_encoding")
+                            == Some(&false.into()),
+                ),
+                (
+                    "connections.10_bits_encoding",
+                    capability("encoder_10_bits"),
+                    yes_no_text(info.negotiated_10_bits_encoding),
+                    false,
+                ),
+                (
+                    "connections.slice_count",
+                    capability("multi_slice_decoding"),
+                    info.negotiated_slice_count.to_string(),
+                    false,
+                ),
+            ];
+
+            Grid::new(format!("{hostname}-headset-info-grid"))
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("");
+                    ui.strong(tr("connections.advertised"));
+                    ui.strong(tr("connections.negotiated"));
+                    ui.end_row();
+
+                    for (key, advertised, negotiated, mismatch) in rows {
+                        ui.label(tr(key));
+                        ui.label(advertised);
+                        if mismatch {
+                            ui.colored_label(theme::palette().log.warning, negotiated);
+                        } else {
+                            ui.label(negotiated);
+                        }
+                        ui.end_row();
+                    }
+                });
+
+            // Shown as received, so that fields added by newer clients are visible too
+            let known_keys = [
+                "encoder_av1",
+                "supports_foveated_encoding",
+                "encoder_10_bits",
+                "multi_slice_decoding",
+                "device_model",
+                "device_manufacturer",
+            ];
+            for (key, value) in &info.extra_capabilities {
+                if !known_keys.contains(&key.as_str()) {
+                    ui.label(format!("{key}: {value}"));
+                }
+            }
+        });
+}
+
+fn uptime_text(uptime: Duration) -> String {
+    let seconds = uptime.as_secs();
+
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+fn disconnection_reason_text(reason: &DisconnectionReason) -> String {
+    match reason {
+        DisconnectionReason::KeepaliveTimeout => tr("connections.reason_keepalive_timeout"),
+        DisconnectionReason::ClientRequested => tr("connections.reason_client_requested"),
+        DisconnectionReason::ServerRequested => tr("connections.reason_server_requested"),
+        DisconnectionReason::ServerRestarting => tr("connections.reason_server_restarting"),
+        DisconnectionReason::SocketError(message) => {
+            tr_args("connections.reason_socket_error", &[("message", message)])
+        }
+        DisconnectionReason::HandshakeFailed(message) => tr_args(
+            "connections.reason_handshake_failed",
+            &[("message", message)],
+        ),
+    }
+}
+
+// The client that is connecting, streaming or restarting. Only one client can stream at a time
+struct ActiveClient {
+    hostname: String,
+    display_name: String,
+    ip: Option<IpAddr>,
+    state: ConnectionState,
+}
+
 pub struct ConnectionsTab {
     new_clients: Option<Vec<(String, ClientConnectionConfig)>>,
     trusted_clients: Option<Vec<(String, ClientConnectionConfig)>>,
     edit_popup_state: Option<EditPopupState>,
+    // Hostname of the streaming client that is about to be removed
+    remove_confirmation: Option<String>,
+    active_client: Option<ActiveClient>,
+    stream_setup: Option<StreamSetupEvent>,
+    stream_start_instant: Option<Instant>,
+    stream_paused: bool,
+    target_bitrate_bps: Option<f32>,
+    // None if the discovery is disabled
+    discovery_port: Option<u16>,
+    // Only the disconnections seen since the dashboard was opened
+    last_disconnections: HashMap<String, DisconnectionReason>,
+    // Codes of the pending pairings, with their expiration
+    pairing_codes: HashMap<String, (String, Instant)>,
 }
 
 impl ConnectionsTab {
@@ -27,16 +281,116 @@ impl ConnectionsTab {
             new_clients: None,
             trusted_clients: None,
             edit_popup_state: None,
+            remove_confirmation: None,
+            active_client: None,
+            stream_setup: None,
+            stream_start_instant: None,
+            stream_paused: false,
+            target_bitrate_bps: None,
+            discovery_port: Some(alvr_common::DEFAULT_CONTROL_PORT),
+            last_disconnections: HashMap::new(),
+            pairing_codes: HashMap::new(),
+        }
+    }
+
+    pub fn update_settings(&mut self, settings: &Settings) {
+        self.discovery_port = settings
+            .connection
+            .client_discovery
+            .as_option()
+            .map(|_| settings.connection.discovery_port);
+    }
+
+    pub fn update_stream_setup(&mut self, stream_setup: StreamSetupEvent) {
+        self.stream_setup = Some(stream_setup);
+    }
+
+    pub fn update_stream_started(&mut self, event: &StreamStartedEvent) {
+        self.stream_start_instant = Some(Instant::now());
+        self.last_disconnections.remove(&event.hostname);
+    }
+
+    pub fn update_stream_paused(&mut self, paused: bool) {
+        self.stream_paused = paused;
+    }
+
+    pub fn update_client_disconnected(&mut self, event: ClientDisconnectedEvent) {
+        self.stream_start_instant = None;
+        self.stream_paused = false;
+        self.target_bitrate_bps = None;
+        self.last_disconnections
+            .insert(event.hostname, event.reason);
+    }
+
+    pub fn update_client_pairing(&mut self, event: ClientPairingEvent) {
+        if let PairingState::WaitingForCode { code, valid_for } = event.state {
+            self.pairing_codes
+                .insert(event.hostname, (code, Instant::now() + valid_for));
+        } else {
+            self.pairing_codes.remove(&event.hostname);
+        }
+    }
+
+    // The target bitrate changes over time with the adaptive bitrate
+    pub fn update_graph_statistics(&mut self, statistics: &GraphStatistics) {
+        if self
+            .active_client
+            .as_ref()
+            .map(|client| client.state == ConnectionState::Streaming)
+            .unwrap_or(false)
+        {
+            self.target_bitrate_bps = Some(statistics.nominal_bitrate.requested_bps);
         }
     }
 
     pub fn update_client_list(&mut self, session: &SessionConfig) {
-        let (trusted_clients, untrusted_clients) =
-            session
-                .client_connections
-                .clone()
-                .into_iter()
-                .partition::<Vec<_>, _>(|(_, data)| data.trusted);
+        let mut clients = session
+            .client_connections
+            .clone()
+            .into_iter()
+            .collect::<Vec<_>>();
+        clients.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let (trusted_clients, untrusted_clients) = clients
+            .into_iter()
+            .partition::<Vec<_>, _>(|(_, data)| data.trusted);
+
+        self.active_client = trusted_clients
+            .iter()
+            .find(|(_, data)| {
+                matches!(
+                    data.connection_state,
+                    ConnectionState::Connecting
+                        | ConnectionState::Streaming
+                        | ConnectionState::Disconnecting
+                )
+            })
+            .map(|(hostname, data)| ActiveClient {
+                hostname: hostname.clone(),
+                display_name: data.display_name.clone(),
+                ip: data.current_ip,
+                state: data.connection_state.clone(),
+            });
+
+        if self
+            .active_client
+            .as_ref()
+            .map(|client| client.state == ConnectionState::Streaming)
+            .unwrap_or(false)
+        {
+            // The stream started event is missed if the dashboard is opened during the stream
+            self.stream_start_instant.get_or_insert_with(Instant::now);
+        } else {
+            self.stream_start_instant = None;
+            self.target_bitrate_bps = None;
+        }
+
+        // Keep the setup of the previous connection attempt only while the client is restarting
+        if self.active_client.as_ref().map(|client| &client.hostname)
+            != self.stream_setup.as_ref().map(|setup| &setup.hostname)
+        {
+            self.stream_setup = None;
+        }
 
         self.trusted_clients = Some(trusted_clients);
         self.new_clients = Some(untrusted_clients);
@@ -51,22 +405,20 @@ impl ConnectionsTab {
 
         if !connected_to_server {
             Frame::group(ui.style())
-                .fill(log_colors::WARNING_LIGHT)
+                .fill(theme::palette().log.warning)
                 .show(ui, |ui| {
                     Grid::new(0).num_columns(2).show(ui, |ui| {
                         ui.horizontal(|ui| {
                             ui.add_space(10.0);
                             ui.heading(
-                                RichText::new(
-                                    "The streamer is not connected! Clients will not be discovered",
-                                )
-                                .color(Color32::BLACK),
+                                RichText::new(tr("connections.streamer_not_connected"))
+                                    .color(Color32::BLACK),
                             );
                         });
 
                         #[cfg(not(target_arch = "wasm32"))]
                         ui.with_layout(Layout::right_to_left(eframe::emath::Align::Center), |ui| {
-                            if ui.button("Launch SteamVR").clicked() {
+                            if ui.button(tr("button.launch_steamvr")).clicked() {
                                 crate::steamvr_launcher::LAUNCHER.lock().launch_steamvr();
                             }
                         });
@@ -75,8 +427,16 @@ impl ConnectionsTab {
         }
 
         ui.vertical_centered_justified(|ui| {
+            if connected_to_server {
+                if let Some(request) = self.stream_status_section(ui) {
+                    requests.push(request);
+                }
+
+                ui.add_space(10.0);
+            }
+
             if let Some(clients) = &self.new_clients {
-                if let Some(request) = new_clients_section(ui, clients) {
+                if let Some(request) = new_clients_section(ui, clients, &self.pairing_codes) {
                     requests.push(request);
                 }
             }
@@ -84,102 +444,414 @@ impl ConnectionsTab {
             ui.add_space(10.0);
 
             if let Some(clients) = &mut self.trusted_clients {
-                if let Some(request) =
-                    trusted_clients_section(ui, clients, &mut self.edit_popup_state)
-                {
+                if let Some(request) = trusted_clients_section(
+                    ui,
+                    clients,
+                    &self.last_disconnections,
+                    &mut self.edit_popup_state,
+                    &mut self.remove_confirmation,
+                ) {
                     requests.push(request);
                 }
             }
         });
 
-        if let Some(mut state) = self.edit_popup_state.take() {
-            Window::new("Edit connection")
+        if let Some(hostname) = self.remove_confirmation.take() {
+            Window::new(tr("connections.remove_client_title"))
                 .anchor(Align2::CENTER_CENTER, (0.0, 0.0))
                 .resizable(false)
                 .collapsible(false)
                 .show(ui.ctx(), |ui| {
+                    ui.label(tr_args(
+                        "connections.remove_streaming_client",
+                        &[("hostname", &hostname)],
+                    ));
                     ui.columns(2, |ui| {
-                        ui[0].label("Hostname:");
-                        ui[1].add_enabled(
+                        if ui[0].button(tr("button.cancel")).clicked() {
+                            return;
+                        }
+
+                        if ui[1].button(tr("button.remove")).clicked() {
+                            requests.push(ServerRequest::UpdateClientList {
+                                hostname,
+                                action: ClientListAction::RemoveEntry,
+                            });
+                        } else {
+                            self.remove_confirmation = Some(hostname);
+                        }
+                    })
+                });
+        }
+
+        if let Some(mut state) = self.edit_popup_state.take() {
+            Window::new(tr(if state.new_client {
+                "connections.add_client_title"
+            } else {
+                "connections.edit_connection_title"
+            }))
+            .anchor(Align2::CENTER_CENTER, (0.0, 0.0))
+            .resizable(false)
+            .collapsible(false)
+            .show(ui.ctx(), |ui| {
+                Grid::new("edit-connection")
+                    .num_columns(2)
+                    .spacing(egui::vec2(8.0, 8.0))
+                    .show(ui, |ui| {
+                        ui.label(tr("connections.hostname"));
+                        ui.add_enabled(
                             state.new_client,
-                            TextEdit::singleline(&mut state.hostname),
+                            TextEdit::singleline(&mut state.hostname).hint_text("XXXX.client.alvr"),
                         );
-                        ui[0].label("IP Addresses:");
-                        for address in &mut state.ips {
-                            ui[1].text_edit_singleline(address);
-                        }
-                        if ui[1].button("Add new").clicked() {
-                            state.ips.push("192.168.X.X".to_string());
+                        ui.end_row();
+
+                        if state.new_client {
+                            ui.label(tr("connections.display_name"));
+                            ui.add(
+                                TextEdit::singleline(&mut state.display_name)
+                                    .hint_text(tr("connections.optional_hint")),
+                            );
+                            ui.end_row();
                         }
+
+                        ui.label(tr("connections.ip_addresses"));
+                        ui.vertical(|ui| {
+                            let mut removed_index = None;
+                            for (index, address) in state.ips.iter_mut().enumerate() {
+                                ui.horizontal(|ui| {
+                                    ui.add(TextEdit::singleline(address).hint_text("192.168.X.X"));
+                                    if ui.small_button("✖").clicked() {
+                                        removed_index = Some(index);
+                                    }
+                                });
+                                if !address.trim().is_empty()
+                                    && address.trim().parse::<IpAddr>().is_err()
+                                {
+                                    ui.colored_label(
+                                        theme::palette().log.error,
+                                        tr("connections.invalid_ip"),
+                                    );
+                                }
+                            }
+                            if let Some(index) = removed_index {
+                                state.ips.remove(index);
+                            }
+
+                            if ui.button(tr("connections.add_ip")).clicked() {
+                                state.ips.push(String::new());
+                            }
+                        });
+                        ui.end_row();
                     });
-                    ui.columns(2, |ui| {
-                        if ui[0].button("Cancel").clicked() {
-                            return;
-                        }
 
-                        if ui[1].button("Save").clicked() {
-                            let manual_ips =
-                                state.ips.iter().filter_map(|s| s.parse().ok()).collect();
+                let hostname = state.hostname.trim().to_owned();
+                // Empty fields are ignored
+                let manual_ips = state
+                    .ips
+                    .iter()
+                    .map(|address| address.trim())
+                    .filter(|address| !address.is_empty())
+                    .map(|address| address.parse::<IpAddr>())
+                    .collect::<Result<Vec<_>, _>>();
+
+                if hostname.is_empty() {
+                    ui.colored_label(theme::palette().log.error, tr("connections.empty_hostname"));
+                }
+
+                ui.columns(2, |ui| {
+                    if ui[0].button(tr("button.cancel")).clicked() {
+                        return;
+                    }
 
+                    let save_clicked = ui[1]
+                        .add_enabled(
+                            !hostname.is_empty() && manual_ips.is_ok(),
+                            Button::new(tr("button.save")),
+                        )
+                        .clicked();
+                    match manual_ips {
+                        Ok(manual_ips) if save_clicked => {
                             if state.new_client {
+                                // If a client with the same hostname exists already, it is kept
+                                // and the addresses are added to it
                                 requests.push(ServerRequest::UpdateClientList {
-                                    hostname: state.hostname,
+                                    hostname: hostname.clone(),
                                     action: ClientListAction::AddIfMissing {
                                         trusted: true,
-                                        manual_ips,
+                                        manual_ips: vec![],
                                     },
                                 });
+
+                                let display_name = state.display_name.trim();
+                                if !display_name.is_empty() {
+                                    requests.push(ServerRequest::UpdateClientList {
+                                        hostname: hostname.clone(),
+                                        action: ClientListAction::SetDisplayName(
+                                            display_name.to_owned(),
+                                        ),
+                                    });
+                                }
+
+                                if manual_ips.is_empty() {
+                                    requests.push(ServerRequest::UpdateClientList {
+                                        hostname,
+                                        action: ClientListAction::TrustAndMaybeAddIp(None),
+                                    });
+                                } else {
+                                    for ip in manual_ips {
+                                        requests.push(ServerRequest::UpdateClientList {
+                                            hostname: hostname.clone(),
+                                            action: ClientListAction::TrustAndMaybeAddIp(Some(ip)),
+                                        });
+                                    }
+                                }
                             } else {
                                 requests.push(ServerRequest::UpdateClientList {
-                                    hostname: state.hostname,
+                                    hostname,
                                     action: ClientListAction::SetManualIps(manual_ips),
                                 });
                             }
-                        } else {
-                            self.edit_popup_state = Some(state);
                         }
-                    })
-                });
+                        _ => self.edit_popup_state = Some(state),
+                    }
+                })
+            });
         }
 
         requests
     }
+
+    fn stream_status_section(&self, ui: &mut Ui) -> Option<ServerRequest> {
+        let mut request = None;
+
+        Frame::group(ui.style())
+            .fill(theme::palette().section_bg)
+            .show(ui, |ui| {
+                ui.vertical_centered_justified(|ui| {
+                    ui.add_space(5.0);
+                    ui.heading(tr("connections.stream_status"));
+                });
+
+                let Some(client) = &self.active_client else {
+                    ui.horizontal(|ui| {
+                        ui.add_space(10.0);
+                        if let Some(port) = self.discovery_port {
+                            ui.label(tr_args(
+                                "connections.discovery_listening_port",
+                                &[("port", &port.to_string())],
+                            ));
+                        } else {
+                            ui.label(tr("connections.discovery_disabled"));
+                        }
+                    });
+
+                    return;
+                };
+
+                Frame::group(ui.style())
+                    .fill(theme::palette().darker_bg)
+                    .inner_margin(egui::vec2(15.0, 12.0))
+                    .show(ui, |ui| {
+                        Grid::new("stream-status")
+                            .num_columns(2)
+                            .spacing(egui::vec2(8.0, 8.0))
+                            .show(ui, |ui| {
+                                self.stream_status_grid(ui, client);
+                            });
+
+                        ui.horizontal(|ui| {
+                            if ui.button(tr("connections.disconnect_client")).clicked() {
+                                request = Some(ServerRequest::UpdateClientList {
+                                    hostname: client.hostname.clone(),
+                                    action: ClientListAction::SetConnectionState(
+                                        ConnectionState::Disconnecting,
+                                    ),
+                                });
+                            }
+
+                            if ui
+                                .add_enabled(
+                                    client.state == ConnectionState::Streaming,
+                                    Button::new(tr("connections.recenter")),
+                                )
+                                .on_hover_text(tr("connections.recenter_help"))
+                                .clicked()
+                            {
+                                request = Some(ServerRequest::Recenter);
+                            }
+
+                            let (label, help) = if self.stream_paused {
+                                (
+                                    "connections.resume_stream",
+                                    "connections.resume_stream_help",
+                                )
+                            } else {
+                                ("connections.pause_stream", "connections.pause_stream_help")
+                            };
+                            if ui
+                                .add_enabled(
+                                    client.state == ConnectionState::Streaming,
+                                    Button::new(tr(label)),
+                                )
+                                .on_hover_text(tr(help))
+                                .clicked()
+                            {
+                                request = Some(ServerRequest::SetStreamPaused(!self.stream_paused));
+                            }
+                        });
+                    });
+            });
+
+        request
+    }
+
+    fn stream_status_grid(&self, ui: &mut Ui, client: &ActiveClient) {
+        let setup = self.stream_setup.as_ref();
+
+        ui.label(tr("connections.client"));
+        ui.label(format!("{} ({})", client.display_name, client.hostname));
+        ui.end_row();
+
+        ui.label(tr("connections.ip_address"));
+        ui.label(address_text(client.ip));
+        ui.end_row();
+
+        ui.label(tr("connections.state"));
+        let restarting = setup.map(|setup| setup.driver_restart).unwrap_or(false);
+        match client.state {
+            ConnectionState::Streaming if self.stream_paused => {
+                ui.colored_label(theme::palette().log.warning, tr("connections.state_paused"))
+            }
+            ConnectionState::Streaming => {
+                ui.colored_label(theme::palette().ok_green, tr("connections.state_streaming"))
+            }
+            _ if restarting => ui.colored_label(
+                theme::palette().log.warning,
+                tr("connections.state_restarting"),
+            ),
+            ConnectionState::Disconnecting => ui.colored_label(
+                theme::palette().log.warning,
+                tr("connections.state_disconnecting"),
+            ),
+            _ => ui.colored_label(
+                theme::palette().log.warning,
+                tr("connections.state_connecting"),
+            ),
+        };
+        ui.end_row();
+
+        let unknown = || tr("connections.unknown_value");
+
+        ui.label(tr("connections.codec"));
+        ui.label(
+            setup
+                .map(|setup| codec_name(setup.codec).to_owned())
+                .unwrap_or_else(unknown),
+        );
+        ui.end_row();
+
+        ui.label(tr("connections.view_resolution"));
+        ui.label(
+            setup
+                .map(|setup| resolution_text(setup.view_resolution))
+                .unwrap_or_else(unknown),
+        );
+        ui.end_row();
+
+        ui.label(tr("connections.refresh_rate"));
+        ui.label(
+            setup
+                .map(|setup| format!("{} Hz", setup.refresh_rate))
+                .unwrap_or_else(unknown),
+        );
+        ui.end_row();
+
+        ui.label(tr("connections.target_bitrate"));
+        ui.label(
+            self.target_bitrate_bps
+                .map(|bps| format!("{:.1} Mbps", bps / 1e6))
+                .unwrap_or_else(unknown),
+        );
+        ui.end_row();
+
+        ui.label(tr("connections.uptime"));
+        if let Some(instant) = self.stream_start_instant {
+            ui.label(uptime_text(instant.elapsed()));
+            ui.ctx().request_repaint_after(Duration::from_secs(1));
+        } else {
+            ui.label(unknown());
+        }
+        ui.end_row();
+    }
 }
 
 fn new_clients_section(
     ui: &mut Ui,
     clients: &[(String, ClientConnectionConfig)],
+    pairing_codes: &HashMap<String, (String, Instant)>,
 ) -> Option<ServerRequest> {
     let mut request = None;
 
     Frame::group(ui.style())
-        .fill(theme::SECTION_BG)
+        .fill(theme::palette().section_bg)
         .show(ui, |ui| {
             ui.vertical_centered_justified(|ui| {
                 ui.add_space(5.0);
-                ui.heading("New clients");
+                ui.heading(tr("connections.new_clients"));
             });
-            for (hostname, _) in clients {
+            for (hostname, data) in clients {
                 Frame::group(ui.style())
-                    .fill(theme::DARKER_BG)
+                    .fill(theme::palette().darker_bg)
                     .inner_margin(egui::vec2(15.0, 12.0))
                     .show(ui, |ui| {
                         Grid::new(format!("{}-new-clients", hostname))
                             .num_columns(2)
                             .spacing(egui::vec2(8.0, 8.0))
                             .show(ui, |ui| {
-                                ui.horizontal(|ui| {
-                                    ui.label(hostname);
-                                });
+                                ui.label(&data.display_name);
                                 ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                                    if ui.button("Trust").clicked() {
+                                    if ui.button(tr("button.remove")).clicked() {
+                                        request = Some(ServerRequest::UpdateClientList {
+                                            hostname: hostname.clone(),
+                                            action: ClientListAction::RemoveEntry,
+                                        });
+                                    }
+                                    if ui.button(tr("connections.trust")).clicked() {
                                         request = Some(ServerRequest::UpdateClientList {
                                             hostname: hostname.clone(),
-                                            action: ClientListAction::Trust,
+                                            action: ClientListAction::TrustAndMaybeAddIp(None),
                                         });
                                     };
                                 });
                                 ui.end_row();
+
+                                ui.label(format!("{hostname}: {}", address_text(data.current_ip),));
+                                ui.label(last_seen_text(data.last_seen));
+                                ui.end_row();
+
+                                if let Some((code, expiration)) = pairing_codes.get(hostname) {
+                                    let remaining_s = expiration
+                                        .saturating_duration_since(Instant::now())
+                                        .as_secs();
+                                    if remaining_s > 0 {
+                                        ui.label(
+                                            RichText::new(tr_args(
+                                                "connections.pairing_code",
+                                                &[("code", code)],
+                                            ))
+                                            .monospace()
+                                            .strong()
+                                            .size(18.0),
+                                        );
+                                        ui.label(tr_args(
+                                            "connections.pairing_code_hint",
+                                            &[("seconds", &remaining_s.to_string())],
+                                        ));
+                                        ui.end_row();
+
+                                        // Updates the countdown
+                                        ui.ctx().request_repaint_after(Duration::from_secs(1));
+                                    }
+                                }
                             });
                     });
             }
@@ -191,22 +863,24 @@ fn new_clients_section(
 fn trusted_clients_section(
     ui: &mut Ui,
     clients: &mut [(String, ClientConnectionConfig)],
+    last_disconnections: &HashMap<String, DisconnectionReason>,
     edit_popup_state: &mut Option<EditPopupState>,
+    remove_confirmation: &mut Option<String>,
 ) -> Option<ServerRequest> {
     let mut request = None;
 
     Frame::group(ui.style())
-        .fill(theme::SECTION_BG)
+        .fill(theme::palette().section_bg)
         .show(ui, |ui| {
             ui.vertical_centered_justified(|ui| {
                 ui.add_space(5.0);
-                ui.heading("Trusted clients");
+                ui.heading(tr("connections.trusted_clients"));
             });
 
             ui.vertical(|ui| {
                 for (hostname, data) in clients {
                     Frame::group(ui.style())
-                        .fill(theme::DARKER_BG)
+                        .fill(theme::palette().darker_bg)
                         .inner_margin(egui::vec2(15.0, 12.0))
                         .show(ui, |ui| {
                             Grid::new(format!("{}-clients", hostname))
@@ -218,23 +892,26 @@ fn trusted_clients_section(
                                         ui.with_layout(
                                             Layout::right_to_left(Align::Center),
                                             |ui| match data.connection_state {
-                                                ConnectionState::Disconnected => {
-                                                    ui.colored_label(Color32::GRAY, "Disconnected")
-                                                }
+                                                ConnectionState::Disconnected => ui.colored_label(
+                                                    Color32::GRAY,
+                                                    tr("connections.state_disconnected"),
+                                                ),
                                                 ConnectionState::Connecting => ui.colored_label(
-                                                    log_colors::WARNING_LIGHT,
-                                                    "Connecting",
+                                                    theme::palette().log.warning,
+                                                    tr("connections.state_connecting"),
+                                                ),
+                                                ConnectionState::Connected => ui.colored_label(
+                                                    theme::palette().ok_green,
+                                                    tr("connections.state_connected"),
+                                                ),
+                                                ConnectionState::Streaming => ui.colored_label(
+                                                    theme::palette().ok_green,
+                                                    tr("connections.state_streaming"),
                                                 ),
-                                                ConnectionState::Connected => {
-                                                    ui.colored_label(theme::OK_GREEN, "Connected")
-                                                }
-                                                ConnectionState::Streaming => {
-                                                    ui.colored_label(theme::OK_GREEN, "Streaming")
-                                                }
                                                 ConnectionState::Disconnecting { .. } => ui
                                                     .colored_label(
-                                                        log_colors::WARNING_LIGHT,
-                                                        "Disconnecting",
+                                                        theme::palette().log.warning,
+                                                        tr("connections.state_disconnecting"),
                                                     ),
                                             },
                                         );
@@ -244,21 +921,24 @@ fn trusted_clients_section(
 
                                     ui.label(format!(
                                         "{hostname}: {}",
-                                        data.current_ip
-                                            .map(|ip| ip.to_string())
-                                            .unwrap_or_else(|| "Unknown IP".into()),
+                                        address_text(data.current_ip),
                                     ));
                                     ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                                        if ui.button("Remove").clicked() {
-                                            request = Some(ServerRequest::UpdateClientList {
-                                                hostname: hostname.clone(),
-                                                action: ClientListAction::RemoveEntry,
-                                            });
+                                        if ui.button(tr("button.remove")).clicked() {
+                                            if data.connection_state == ConnectionState::Streaming {
+                                                *remove_confirmation = Some(hostname.clone());
+                                            } else {
+                                                request = Some(ServerRequest::UpdateClientList {
+                                                    hostname: hostname.clone(),
+                                                    action: ClientListAction::RemoveEntry,
+                                                });
+                                            }
                                         }
-                                        if ui.button("Edit").clicked() {
+                                        if ui.button(tr("connections.edit")).clicked() {
                                             *edit_popup_state = Some(EditPopupState {
                                                 new_client: false,
                                                 hostname: hostname.to_owned(),
+                                                display_name: String::new(),
                                                 ips: data
                                                     .manual_ips
                                                     .iter()
@@ -267,16 +947,99 @@ fn trusted_clients_section(
                                             });
                                         }
                                     });
+                                    ui.end_row();
+
+                                    let mut manual_ips =
+                                        data.manual_ips.iter().copied().collect::<Vec<_>>();
+                                    manual_ips.sort();
+                                    for ip in &manual_ips {
+                                        ui.label(tr_args(
+                                            "connections.manual_ip",
+                                            &[("ip", &ip.to_string())],
+                                        ));
+                                        ui.with_layout(
+                                            Layout::right_to_left(Align::Center),
+                                            |ui| {
+                                                if ui
+                                                    .small_button(tr("connections.remove_ip"))
+                                                    .clicked()
+                                                {
+                                                    request =
+                                                        Some(ServerRequest::UpdateClientList {
+                                                            hostname: hostname.clone(),
+                                                            action: ClientListAction::SetManualIps(
+                                                                manual_ips
+                                                                    .iter()
+                                                                    .copied()
+                                                                    .filter(|other| other != ip)
+                                                                    .collect(),
+                                                            ),
+                                                        });
+                                                }
+                                            },
+                                        );
+                                        ui.end_row();
+                                    }
+
+                                    ui.label(last_seen_text(data.last_seen));
+                                    ui.end_row();
+
+                                    if let (ConnectionState::Disconnected, Some(reason)) =
+                                        (&data.connection_state, last_disconnections.get(hostname))
+                                    {
+                                        let color = match reason {
+                                            DisconnectionReason::KeepaliveTimeout
+                                            | DisconnectionReason::SocketError(_)
+                                            | DisconnectionReason::HandshakeFailed(_) => {
+                                                theme::palette().log.warning
+                                            }
+                                            _ => Color32::GRAY,
+                                        };
+                                        ui.colored_label(
+                                            color,
+                                            tr_args(
+                                                "connections.last_disconnection",
+                                                &[("reason", &disconnection_reason_text(reason))],
+                                            ),
+                                        );
+                                        ui.end_row();
+                                    }
                                 });
+
+                            if let Some(info) = &data.headset_info {
+                                headset_info_ui(ui, hostname, info, &data.connection_state);
+
+                                ui.horizontal(|ui| {
+                                    ui.label(tr_args(
+                                        "connections.device_preset",
+                                        &[(
+                                            "preset",
+                                            &data.device_preset.clone().unwrap_or_else(|| {
+                                                tr("connections.no_device_preset")
+                                            }),
+                                        )],
+                                    ));
+                                    if ui
+                                        .button(tr("connections.reapply_device_preset"))
+                                        .on_hover_text(tr("connections.reapply_device_preset_hint"))
+                                        .clicked()
+                                    {
+                                        request = Some(ServerRequest::ApplyDevicePreset(
+                                            hostname.clone(),
+                                        ));
+                                    }
+                                });
+                            }
                         });
                 }
             });
 
-            if ui.button("Add client manually").clicked() {
+            if ui.button(tr("connections.add_client_manually")).clicked() {
                 *edit_popup_state = Some(EditPopupState {
-                    hostname: "XXXX.client.alvr".into(),
+                    hostname: String::new(),
                     new_client: true,
-                    ips: Vec::new(),
+                    display_name: String::new(),
+                    ips: vec![String::new()],
                 });
             }
         });
@@ -1,25 +1,34 @@
+use crate::dashboard::localization::tr;
 use alvr_packets::ServerRequest;
 use eframe::egui::Ui;
 
 pub fn debug_tab_ui(ui: &mut Ui) -> Option<ServerRequest> {
     let mut request = None;
 
-    ui.columns(4, |ui| {
-        if ui[0].button("Capture frame").clicked() {
+    ui.columns(6, |ui| {
+        if ui[0].button(tr("debug.capture_frame")).clicked() {
             request = Some(ServerRequest::CaptureFrame);
         }
 
-        if ui[1].button("Insert IDR").clicked() {
+        if ui[1].button(tr("debug.insert_idr")).clicked() {
             request = Some(ServerRequest::InsertIdr);
         }
 
-        if ui[2].button("Start recording").clicked() {
+        if ui[2].button(tr("debug.start_recording")).clicked() {
             request = Some(ServerRequest::StartRecording);
         }
 
-        if ui[3].button("Stop recording").clicked() {
+        if ui[3].button(tr("debug.stop_recording")).clicked() {
             request = Some(ServerRequest::StopRecording);
         }
+
+        if ui[4].button(tr("debug.start_timing_capture")).clicked() {
+            request = Some(ServerRequest::StartTimingCapture);
+        }
+
+        if ui[5].button(tr("debug.stop_timing_capture")).clicked() {
+            request = Some(ServerRequest::StopTimingCapture);
+        }
     });
 
     request
@@ -0,0 +1,246 @@
+use crate::dashboard::localization::{tr, tr_args};
+use alvr_common::{
+    glam::{EulerRot, Vec3},
+    DeviceMotion,
+};
+use alvr_events::{HapticsEvent, InputHapticsSnapshot, InputSnapshotEvent};
+use alvr_packets::{ButtonValue, PathValuePair, ServerRequest};
+use alvr_session::Settings;
+use eframe::egui::{CollapsingHeader, Grid, RichText, ScrollArea, Ui};
+use std::collections::VecDeque;
+
+// Haptics are shown across snapshots, SteamVR may request them only for short moments
+const MAX_SHOWN_HAPTICS: usize = 20;
+
+fn vec3_text(value: Vec3) -> String {
+    format!("{:7.3} {:7.3} {:7.3}", value.x, value.y, value.z)
+}
+
+// Yaw, pitch, roll in degrees, easier to read than the quaternion
+fn motion_texts(motion: &DeviceMotion) -> [String; 4] {
+    let (yaw, pitch, roll) = motion.pose.orientation.to_euler(EulerRot::YXZ);
+
+    [
+        vec3_text(motion.pose.position),
+        vec3_text(Vec3::new(yaw, pitch, roll) * 180.0 / std::f32::consts::PI),
+        vec3_text(motion.linear_velocity),
+        vec3_text(motion.angular_velocity),
+    ]
+}
+
+fn button_text(value: Option<ButtonValue>) -> String {
+    match value {
+        Some(ButtonValue::Binary(value)) => tr(if value {
+            "input.pressed"
+        } else {
+            "input.released"
+        }),
+        Some(ButtonValue::Scalar(value)) => format!("{value:.3}"),
+        Some(ButtonValue::Vector2(value)) => format!("{:.3} {:.3}", value.x, value.y),
+        None => "-".into(),
+    }
+}
+
+fn haptics_text(haptics: Option<&HapticsEvent>) -> String {
+    haptics
+        .map(|haptics| {
+            tr_args(
+                "input.haptics_value",
+                &[
+                    (
+                        "duration_ms",
+                        &format!("{:.0}", haptics.duration.as_secs_f32() * 1000.0),
+                    ),
+                    ("frequency", &format!("{:.0}", haptics.frequency)),
+                    ("amplitude", &format!("{:.2}", haptics.amplitude)),
+                ],
+            )
+        })
+        .unwrap_or_else(|| "-".into())
+}
+
+pub struct InputTab {
+    enabled: bool,
+    frozen: bool,
+    snapshot: Option<InputSnapshotEvent>,
+    recent_haptics: VecDeque<InputHapticsSnapshot>,
+}
+
+impl InputTab {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            frozen: false,
+            snapshot: None,
+            recent_haptics: VecDeque::new(),
+        }
+    }
+
+    pub fn update_settings(&mut self, settings: &Settings) {
+        self.enabled = settings.logging.input_debug_events;
+    }
+
+    pub fn update_input_snapshot(&mut self, snapshot: InputSnapshotEvent) {
+        if self.frozen {
+            return;
+        }
+
+        for haptics in &snapshot.haptics {
+            if self.recent_haptics.len() == MAX_SHOWN_HAPTICS {
+                self.recent_haptics.pop_front();
+            }
+            self.recent_haptics.push_back(haptics.clone());
+        }
+
+        self.snapshot = Some(snapshot);
+    }
+
+    // The copied snapshot contains the shown haptics, not only the ones of the last snapshot
+    fn snapshot_json(&self) -> Option<String> {
+        let mut snapshot = self.snapshot.clone()?;
+        snapshot.haptics = self.recent_haptics.iter().cloned().collect();
+
+        serde_json::to_string_pretty(&snapshot).ok()
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) -> Option<ServerRequest> {
+        let mut request = None;
+
+        let mut copy_snapshot = false;
+        ui.horizontal(|ui| {
+            let label = if self.enabled {
+                tr("input.disable_events")
+            } else {
+                tr("input.enable_events")
+            };
+            if ui.button(label).clicked() {
+                request = Some(ServerRequest::SetValues(vec![PathValuePair {
+                    path: alvr_packets::parse_path("session_settings.logging.input_debug_events"),
+                    value: (!self.enabled).into(),
+                }]));
+            }
+            ui.toggle_value(&mut self.frozen, tr("input.freeze"));
+            copy_snapshot = ui.button(tr("input.copy_snapshot")).clicked();
+        });
+
+        if copy_snapshot {
+            if let Some(json) = self.snapshot_json() {
+                ui.output_mut(|out| out.copied_text = json);
+            }
+        }
+
+        let Some(snapshot) = &self.snapshot else {
+            ui.label(if self.enabled {
+                tr("input.waiting_for_client")
+            } else {
+                tr("input.events_disabled")
+            });
+
+            return request;
+        };
+
+        ScrollArea::vertical().show(ui, |ui| {
+            CollapsingHeader::new(tr("input.devices"))
+                .default_open(true)
+                .show(ui, |ui| {
+                    Grid::new("input_devices")
+                        .num_columns(6)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for header in [
+                                tr("input.device"),
+                                String::new(),
+                                tr("input.position"),
+                                tr("input.orientation"),
+                                tr("input.linear_velocity"),
+                                tr("input.angular_velocity"),
+                            ] {
+                                ui.label(RichText::new(header).strong());
+                            }
+                            ui.end_row();
+
+                            for device in &snapshot.devices {
+                                let rows = [
+                                    (tr("input.client"), Some(&device.client_motion)),
+                                    (tr("input.processed"), device.processed_motion.as_ref()),
+                                ];
+                                for (index, (source, motion)) in rows.into_iter().enumerate() {
+                                    ui.label(if index == 0 { device.path.as_str() } else { "" });
+                                    ui.label(source);
+                                    if let Some(motion) = motion {
+                                        for text in motion_texts(motion) {
+                                            ui.monospace(text);
+                                        }
+                                    } else {
+                                        ui.label(tr("input.not_sent_to_steamvr"));
+                                    }
+                                    ui.end_row();
+                                }
+                            }
+                        });
+                });
+
+            CollapsingHeader::new(tr("input.buttons"))
+                .default_open(true)
+                .show(ui, |ui| {
+                    Grid::new("input_buttons")
+                        .num_columns(3)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for header in ["input.path", "input.client", "input.processed"] {
+                                ui.label(RichText::new(tr(header)).strong());
+                            }
+                            ui.end_row();
+
+                            for button in &snapshot.buttons {
+                                ui.label(&button.path);
+                                ui.monospace(button_text(button.client_value));
+                                ui.monospace(button_text(button.processed_value));
+                                ui.end_row();
+                            }
+                        });
+                });
+
+            CollapsingHeader::new(tr("input.steamvr_inputs"))
+                .default_open(true)
+                .show(ui, |ui| {
+                    Grid::new("input_steamvr_buttons")
+                        .num_columns(2)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for button in &snapshot.steamvr_buttons {
+                                ui.label(&button.path);
+                                ui.monospace(button_text(Some(button.value)));
+                                ui.end_row();
+                            }
+                        });
+                });
+
+            CollapsingHeader::new(tr("input.haptics"))
+                .default_open(true)
+                .show(ui, |ui| {
+                    Grid::new("input_haptics")
+                        .num_columns(3)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for header in
+                                ["input.device", "input.from_steamvr", "input.sent_to_client"]
+                            {
+                                ui.label(RichText::new(tr(header)).strong());
+                            }
+                            ui.end_row();
+
+                            // Most recent first
+                            for haptics in self.recent_haptics.iter().rev() {
+                                ui.label(&haptics.steamvr.path);
+                                ui.monospace(haptics_text(Some(&haptics.steamvr)));
+                                ui.monospace(haptics_text(haptics.sent.as_ref()));
+                                ui.end_row();
+                            }
+                        });
+                });
+        });
+
+        request
+    }
+}
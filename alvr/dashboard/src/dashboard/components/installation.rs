@@ -1,36 +1,153 @@
+use crate::dashboard::localization::{tr, tr_args};
 use alvr_gui_common::theme;
-use alvr_packets::{FirewallRulesAction, ServerRequest};
+use alvr_packets::{
+    FirewallRuleState, FirewallRulesAction, FirewallStatus, InstallationAction,
+    InstallationActionResult, ServerRequest,
+};
 use eframe::{
-    egui::{Frame, Grid, Layout, RichText, Ui},
+    egui::{Align2, Button, Frame, Grid, Layout, RichText, Ui, Window},
     emath::Align,
 };
 use std::{
+    collections::HashMap,
     path::PathBuf,
     time::{Duration, Instant},
 };
 
 const DRIVER_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
 
+fn action_label(action: InstallationAction) -> String {
+    tr(match action {
+        InstallationAction::RegisterDriver => "installation.action_register_driver",
+        InstallationAction::UnregisterDriver => "installation.action_unregister_driver",
+        InstallationAction::AddFirewallRules => "installation.action_add_firewall_rules",
+        InstallationAction::RemoveFirewallRules => "installation.action_remove_firewall_rules",
+    })
+}
+
+fn status_label(ui: &mut Ui, ok: Option<bool>, ok_key: &str, ko_key: &str) {
+    match ok {
+        Some(true) => ui.label(RichText::new(tr(ok_key)).color(theme::palette().ok_green)),
+        Some(false) => ui.label(RichText::new(tr(ko_key)).color(theme::palette().ko_red)),
+        None => ui.label(tr("installation.checking")),
+    };
+}
+
 pub enum InstallationTabRequest {
     OpenSetupWizard,
     ServerRequest(ServerRequest),
 }
 
 pub struct InstallationTab {
-    drivers: Vec<PathBuf>,
+    alvr_driver_dir: PathBuf,
+    drivers: Option<Vec<PathBuf>>,
+    firewall_status: Option<FirewallStatus>,
+    // The outer option is None until the first check completes
+    steamvr_dir: Option<Option<PathBuf>>,
+    action_results: HashMap<InstallationAction, Option<String>>,
+    // Requests sent only after the user acknowledges the privileges prompt
+    elevation_prompt: Option<Vec<ServerRequest>>,
+    status_check_pending: bool,
     last_update_instant: Instant,
 }
 
 impl InstallationTab {
     pub fn new() -> Self {
         Self {
-            drivers: vec![],
+            alvr_driver_dir: alvr_filesystem::filesystem_layout_from_dashboard_exe(
+                &std::env::current_exe().unwrap(),
+            )
+            .openvr_driver_root_dir,
+            drivers: None,
+            firewall_status: None,
+            steamvr_dir: None,
+            action_results: HashMap::new(),
+            elevation_prompt: None,
+            status_check_pending: true,
             last_update_instant: Instant::now(),
         }
     }
 
     pub fn update_drivers(&mut self, list: Vec<PathBuf>) {
-        self.drivers = list;
+        self.drivers = Some(list);
+    }
+
+    pub fn update_firewall_status(&mut self, status: FirewallStatus) {
+        self.firewall_status = Some(status);
+    }
+
+    pub fn update_steamvr_dir(&mut self, dir: Option<PathBuf>) {
+        self.steamvr_dir = Some(dir);
+    }
+
+    // The displayed state is checked again after every action
+    pub fn update_action_result(&mut self, result: InstallationActionResult) {
+        self.action_results.insert(result.action, result.error);
+        self.status_check_pending = true;
+    }
+
+    fn driver_registered(&self) -> Option<bool> {
+        self.drivers
+            .as_ref()
+            .map(|drivers| drivers.contains(&self.alvr_driver_dir))
+    }
+
+    // Ports that could not be verified do not count as allowed
+    fn firewall_allowed(&self) -> Option<bool> {
+        self.firewall_status.as_ref().map(|status| {
+            status
+                .ports
+                .iter()
+                .all(|port| port.state == FirewallRuleState::Allowed)
+        })
+    }
+
+    fn run_all_requests(&self) -> (Vec<ServerRequest>, bool) {
+        let mut requests = vec![];
+        if self.driver_registered() != Some(true) {
+            requests.push(ServerRequest::RegisterAlvrDriver);
+        }
+
+        let needs_firewall_rules = self.firewall_allowed() != Some(true);
+        if needs_firewall_rules {
+            requests.push(ServerRequest::FirewallRules(FirewallRulesAction::Add));
+        }
+
+        (requests, needs_firewall_rules)
+    }
+
+    fn elevation_prompt_ui(&mut self, ui: &mut Ui, requests: &mut Vec<InstallationTabRequest>) {
+        let Some(prompt_requests) = self.elevation_prompt.take() else {
+            return;
+        };
+
+        let mut keep_open = true;
+        let mut confirmed = false;
+        Window::new(tr("installation.elevation_title"))
+            .anchor(Align2::CENTER_CENTER, (0.0, 0.0))
+            .resizable(false)
+            .collapsible(false)
+            .show(ui.ctx(), |ui| {
+                ui.label(tr("installation.elevation_notice"));
+                ui.columns(2, |ui| {
+                    if ui[0].button(tr("button.cancel")).clicked() {
+                        keep_open = false;
+                    }
+                    if ui[1].button(tr("button.continue")).clicked() {
+                        confirmed = true;
+                    }
+                });
+            });
+
+        if confirmed {
+            requests.extend(
+                prompt_requests
+                    .into_iter()
+                    .map(InstallationTabRequest::ServerRequest),
+            );
+        } else if keep_open {
+            self.elevation_prompt = Some(prompt_requests);
+        }
     }
 
     pub fn ui(&mut self, ui: &mut Ui) -> Vec<InstallationTabRequest> {
@@ -45,32 +162,225 @@ impl InstallationTab {
             self.last_update_instant = now;
         }
 
+        if self.status_check_pending {
+            for request in [
+                ServerRequest::GetDriverList,
+                ServerRequest::CheckFirewall,
+                ServerRequest::CheckSteamvr,
+            ] {
+                requests.push(InstallationTabRequest::ServerRequest(request));
+            }
+
+            self.status_check_pending = false;
+        }
+
+        let mut elevation_prompt = None;
+
         ui.vertical_centered_justified(|ui| {
-            if ui.button("Run setup wizard").clicked() {
+            if ui.button(tr("installation.run_setup_wizard")).clicked() {
                 requests.push(InstallationTabRequest::OpenSetupWizard);
             }
+
+            Frame::group(ui.style())
+                .fill(theme::palette().section_bg)
+                .show(ui, |ui| {
+                    ui.label(RichText::new(tr("installation.status")).size(18.0));
+                    Grid::new(2).num_columns(3).show(ui, |ui| {
+                        ui.label("SteamVR");
+                        match &self.steamvr_dir {
+                            Some(Some(dir)) => {
+                                ui.label(
+                                    RichText::new(tr("installation.steamvr_detected"))
+                                        .color(theme::palette().ok_green),
+                                )
+                                .on_hover_text(dir.to_string_lossy());
+                            }
+                            Some(None) => {
+                                ui.label(
+                                    RichText::new(tr("installation.steamvr_not_found"))
+                                        .color(theme::palette().ko_red),
+                                );
+                            }
+                            None => {
+                                ui.label(tr("installation.checking"));
+                            }
+                        }
+                        ui.label("");
+                        ui.end_row();
+
+                        let driver_registered = self.driver_registered();
+                        ui.label(tr("installation.alvr_driver"));
+                        status_label(
+                            ui,
+                            driver_registered,
+                            "installation.driver_registered",
+                            "installation.driver_not_registered",
+                        );
+                        if ui
+                            .add_enabled(
+                                driver_registered == Some(false),
+                                Button::new(tr("installation.register")),
+                            )
+                            .clicked()
+                        {
+                            requests.push(InstallationTabRequest::ServerRequest(
+                                ServerRequest::RegisterAlvrDriver,
+                            ));
+                        }
+                        ui.end_row();
+
+                        let firewall_allowed = self.firewall_allowed();
+                        ui.label(tr("installation.firewall_rules"));
+                        status_label(
+                            ui,
+                            firewall_allowed,
+                            "installation.firewall_rules_present",
+                            "installation.firewall_rules_missing",
+                        );
+                        if ui
+                            .add_enabled(
+                                firewall_allowed == Some(false),
+                                Button::new(tr("installation.add")),
+                            )
+                            .clicked()
+                        {
+                            elevation_prompt =
+                                Some(vec![ServerRequest::FirewallRules(FirewallRulesAction::Add)]);
+                        }
+                        ui.end_row();
+                    });
+
+                    let (run_all_requests, needs_elevation) = self.run_all_requests();
+                    if ui
+                        .add_enabled(
+                            !run_all_requests.is_empty(),
+                            Button::new(tr("installation.run_all")),
+                        )
+                        .clicked()
+                    {
+                        if needs_elevation {
+                            elevation_prompt = Some(run_all_requests);
+                        } else {
+                            requests.extend(
+                                run_all_requests
+                                    .into_iter()
+                                    .map(InstallationTabRequest::ServerRequest),
+                            );
+                        }
+                    }
+
+                    for action in [
+                        InstallationAction::RegisterDriver,
+                        InstallationAction::UnregisterDriver,
+                        InstallationAction::AddFirewallRules,
+                        InstallationAction::RemoveFirewallRules,
+                    ] {
+                        match self.action_results.get(&action) {
+                            Some(None) => {
+                                ui.label(
+                                    RichText::new(tr_args(
+                                        "installation.action_succeeded",
+                                        &[("action", &action_label(action))],
+                                    ))
+                                    .color(theme::palette().ok_green),
+                                );
+                            }
+                            Some(Some(error)) => {
+                                ui.label(
+                                    RichText::new(tr_args(
+                                        "installation.action_failed",
+                                        &[("action", &action_label(action)), ("error", error)],
+                                    ))
+                                    .color(theme::palette().ko_red),
+                                );
+                            }
+                            None => (),
+                        }
+                    }
+                });
+
             ui.columns(2, |ui| {
-                if ui[0].button("Add firewall rules").clicked() {
-                    requests.push(InstallationTabRequest::ServerRequest(
-                        ServerRequest::FirewallRules(FirewallRulesAction::Add),
-                    ));
+                if ui[0]
+                    .button(tr("installation.add_firewall_rules"))
+                    .clicked()
+                {
+                    elevation_prompt =
+                        Some(vec![ServerRequest::FirewallRules(FirewallRulesAction::Add)]);
                 }
-                if ui[1].button("Remove firewall rules").clicked() {
-                    requests.push(InstallationTabRequest::ServerRequest(
-                        ServerRequest::FirewallRules(FirewallRulesAction::Remove),
-                    ));
+                if ui[1]
+                    .button(tr("installation.remove_firewall_rules"))
+                    .clicked()
+                {
+                    elevation_prompt = Some(vec![ServerRequest::FirewallRules(
+                        FirewallRulesAction::Remove,
+                    )]);
                 }
             });
 
             Frame::group(ui.style())
-                .fill(theme::SECTION_BG)
+                .fill(theme::palette().section_bg)
                 .show(ui, |ui| {
-                    ui.label(RichText::new("Registered drivers").size(18.0));
+                    ui.label(RichText::new(tr("installation.firewall")).size(18.0));
+                    if let Some(status) = &self.firewall_status {
+                        Grid::new(1).num_columns(2).show(ui, |ui| {
+                            for port in &status.ports {
+                                ui.label(tr_args(
+                                    "installation.firewall_port",
+                                    &[
+                                        ("protocol", &format!("{:?}", port.protocol)),
+                                        ("port", &port.port.to_string()),
+                                    ],
+                                ));
+                                let (key, color) = match port.state {
+                                    FirewallRuleState::Allowed => {
+                                        ("installation.port_allowed", theme::palette().ok_green)
+                                    }
+                                    FirewallRuleState::Blocked => {
+                                        ("installation.port_blocked", theme::palette().ko_red)
+                                    }
+                                    FirewallRuleState::Unknown => {
+                                        ("installation.port_unknown", theme::palette().fg)
+                                    }
+                                };
+                                ui.label(RichText::new(tr(key)).color(color));
+                                ui.end_row();
+                            }
+                        });
+                        ui.label(tr_args(
+                            "installation.firewall_backend",
+                            &[("backend", &status.backend)],
+                        ));
+
+                        ui.columns(2, |ui| {
+                            if status.blocked_ports().next().is_some()
+                                && ui[0]
+                                    .button(tr("installation.fix_firewall_rules"))
+                                    .clicked()
+                            {
+                                elevation_prompt = Some(vec![ServerRequest::FirewallRules(
+                                    FirewallRulesAction::Add,
+                                )]);
+                            }
+                            if ui[1].button(tr("installation.check_again")).clicked() {
+                                requests.push(InstallationTabRequest::ServerRequest(
+                                    ServerRequest::CheckFirewall,
+                                ));
+                            }
+                        });
+                    } else {
+                        ui.label(tr("installation.checking_firewall"));
+                    }
+                });
+
+            Frame::group(ui.style())
+                .fill(theme::palette().section_bg)
+                .show(ui, |ui| {
+                    ui.label(RichText::new(tr("installation.registered_drivers")).size(18.0));
                     Grid::new(0).num_columns(2).show(ui, |ui| {
-                        for driver_path in &self.drivers {
+                        for driver_path in self.drivers.iter().flatten() {
                             ui.label(driver_path.to_string_lossy());
                             ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                                if ui.button("Remove").clicked() {
+                                if ui.button(tr("button.remove")).clicked() {
                                     requests.push(InstallationTabRequest::ServerRequest(
                                         ServerRequest::UnregisterDriver(driver_path.clone()),
                                     ));
@@ -80,7 +390,7 @@ impl InstallationTab {
                         }
                     });
 
-                    if ui.button("Register ALVR driver").clicked() {
+                    if ui.button(tr("installation.register_alvr_driver")).clicked() {
                         requests.push(InstallationTabRequest::ServerRequest(
                             ServerRequest::RegisterAlvrDriver,
                         ));
@@ -88,6 +398,11 @@ impl InstallationTab {
                 });
         });
 
+        if elevation_prompt.is_some() {
+            self.elevation_prompt = elevation_prompt;
+        }
+        self.elevation_prompt_ui(ui, &mut requests);
+
         requests
     }
 }
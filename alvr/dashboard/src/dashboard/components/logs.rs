@@ -1,25 +1,86 @@
-use alvr_common::LogSeverity;
+use crate::dashboard::localization::{tr, tr_count};
+use alvr_common::{error, LogSeverity};
 use alvr_events::{Event, EventType};
-use alvr_gui_common::theme::log_colors;
+use alvr_gui_common::theme;
 use alvr_session::{RawEventsConfig, Settings};
 use eframe::{
-    egui::{Grid, OpenUrl, RichText, ScrollArea, Ui},
+    egui::{
+        text::LayoutJob, FontId, Grid, OpenUrl, RichText, ScrollArea, TextEdit, TextFormat, Ui,
+    },
     epaint::Color32,
 };
 use settings_schema::Switch;
 use std::{collections::VecDeque, env};
 
+const FONT_SIZE: f32 = 12.0;
+
 struct Entry {
-    color: Color32,
     timestamp: String,
     ty: String,
+    severity: LogSeverity,
+    is_event: bool,
     message: String,
+    replayed: bool,
+}
+
+impl Entry {
+    // Resolved when drawn, so that the entries follow theme changes
+    fn color(&self) -> Color32 {
+        let colors = theme::palette().log;
+        if self.is_event {
+            return colors.event;
+        }
+
+        match self.severity {
+            LogSeverity::Error => colors.error,
+            LogSeverity::Warning => colors.warning,
+            LogSeverity::Info => colors.info,
+            LogSeverity::Debug => colors.debug,
+        }
+    }
+}
+
+// The highlighted ranges are found on the ASCII lowercase message, which keeps byte offsets valid
+fn highlighted_message(message: &str, filter_lowercase: &str, color: Color32) -> LayoutJob {
+    let format = TextFormat {
+        font_id: FontId::proportional(FONT_SIZE),
+        color,
+        ..Default::default()
+    };
+    let highlight_format = TextFormat {
+        background: theme::palette().accent,
+        color: Color32::WHITE,
+        ..format.clone()
+    };
+
+    let mut job = LayoutJob::default();
+    let message_lowercase = message.to_ascii_lowercase();
+    let mut start = 0;
+    for (match_start, matched) in message_lowercase.match_indices(filter_lowercase) {
+        job.append(&message[start..match_start], 0.0, format.clone());
+        job.append(
+            &message[match_start..match_start + matched.len()],
+            0.0,
+            highlight_format.clone(),
+        );
+        start = match_start + matched.len();
+    }
+    job.append(&message[start..], 0.0, format);
+
+    job
 }
 
 pub struct LogsTab {
     raw_events_config: Switch<RawEventsConfig>,
     entries: VecDeque<Entry>,
     log_limit: usize,
+    dropped_count: usize,
+    show_errors: bool,
+    show_warnings: bool,
+    show_info: bool,
+    show_debug: bool,
+    text_filter: String,
+    pause_auto_scroll: bool,
 }
 
 impl LogsTab {
@@ -29,43 +90,101 @@ impl LogsTab {
                 hide_spammy_events: false,
             }),
             entries: VecDeque::new(),
-            log_limit: 1000,
+            log_limit: 5000,
+            dropped_count: 0,
+            show_errors: true,
+            show_warnings: true,
+            show_info: true,
+            show_debug: true,
+            text_filter: String::new(),
+            pause_auto_scroll: false,
+        }
+    }
+
+    fn trim_entries(&mut self) {
+        while self.entries.len() > self.log_limit {
+            self.entries.pop_front();
+            self.dropped_count += 1;
         }
     }
 
+    // Writes the entries that pass the current filters as JSON lines
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export(&self, filter_lowercase: &str) {
+        let file_name = format!(
+            "alvr_logs_{}.jsonl",
+            chrono::Local::now().format("%Y-%m-%d_%H-%M-%S")
+        );
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name(file_name)
+            .add_filter(tr("logs.json_lines"), &["jsonl"])
+            .save_file()
+        else {
+            return;
+        };
+
+        let content = self
+            .entries
+            .iter()
+            .filter(|entry| self.is_shown(entry, filter_lowercase))
+            .map(|entry| {
+                format!(
+                    "{}\n",
+                    serde_json::json!({
+                        "timestamp": entry.timestamp,
+                        "type": entry.ty,
+                        "message": entry.message,
+                        "replayed": entry.replayed,
+                    })
+                )
+            })
+            .collect::<String>();
+
+        if let Err(e) = std::fs::write(&path, content) {
+            error!("Failed to export logs to {}: {e}", path.display());
+        }
+    }
+
+    fn is_shown(&self, entry: &Entry, filter_lowercase: &str) -> bool {
+        // Raw events are filtered by the severity they were sent with, like log entries
+        let severity_shown = match entry.severity {
+            LogSeverity::Error => self.show_errors,
+            LogSeverity::Warning => self.show_warnings,
+            LogSeverity::Info => self.show_info,
+            LogSeverity::Debug => self.show_debug,
+        };
+
+        severity_shown
+            && (filter_lowercase.is_empty()
+                || entry
+                    .message
+                    .to_ascii_lowercase()
+                    .contains(filter_lowercase))
+    }
+
     pub fn update_settings(&mut self, settings: &Settings) {
         self.raw_events_config = settings.logging.show_raw_events.clone();
+        self.log_limit = settings.logging.dashboard_log_limit;
+        self.trim_entries();
     }
 
     pub fn push_event(&mut self, event: Event) {
         match event.event_type {
             EventType::Log(log_event) => {
-                let color;
-                let ty;
-                match log_event.severity {
-                    LogSeverity::Error => {
-                        color = log_colors::ERROR_LIGHT;
-                        ty = "ERROR";
-                    }
-                    LogSeverity::Warning => {
-                        color = log_colors::WARNING_LIGHT;
-                        ty = "WARN";
-                    }
-                    LogSeverity::Info => {
-                        color = log_colors::INFO_LIGHT;
-                        ty = "INFO";
-                    }
-                    LogSeverity::Debug => {
-                        color = log_colors::DEBUG_LIGHT;
-                        ty = "DEBUG";
-                    }
+                let ty = match log_event.severity {
+                    LogSeverity::Error => "ERROR",
+                    LogSeverity::Warning => "WARN",
+                    LogSeverity::Info => "INFO",
+                    LogSeverity::Debug => "DEBUG",
                 };
 
                 self.entries.push_back(Entry {
-                    color,
                     timestamp: event.timestamp,
                     ty: ty.into(),
+                    severity: log_event.severity,
+                    is_event: false,
                     message: log_event.content,
+                    replayed: event.replayed,
                 });
             }
             event_type => {
@@ -76,27 +195,39 @@ impl LogsTab {
                             EventType::StatisticsSummary(_)
                                 | EventType::GraphStatistics(_)
                                 | EventType::Tracking(_)
+                                | EventType::InputSnapshot(_)
                         )
                     {
                         self.entries.push_back(Entry {
-                            color: log_colors::EVENT_LIGHT,
                             timestamp: event.timestamp,
                             ty: "EVENT".into(),
+                            severity: event.severity,
+                            is_event: true,
                             message: format!("{event_type:?}"),
+                            replayed: event.replayed,
                         });
                     }
                 }
             }
         }
 
-        if self.entries.len() > self.log_limit {
-            self.entries.pop_front();
-        }
+        self.trim_entries();
     }
 
-    pub fn ui(&self, ui: &mut Ui) {
+    pub fn ui(&mut self, ui: &mut Ui) {
         ui.horizontal(|ui| {
-            if ui.button("Copy all").clicked() {
+            ui.toggle_value(&mut self.show_errors, tr("logs.error"));
+            ui.toggle_value(&mut self.show_warnings, tr("logs.warning"));
+            ui.toggle_value(&mut self.show_info, tr("logs.info"));
+            ui.toggle_value(&mut self.show_debug, tr("logs.debug"));
+            ui.add(TextEdit::singleline(&mut self.text_filter).hint_text(tr("logs.filter_hint")));
+            ui.toggle_value(&mut self.pause_auto_scroll, tr("logs.pause_auto_scroll"));
+        });
+
+        let filter_lowercase = self.text_filter.to_ascii_lowercase();
+
+        ui.horizontal(|ui| {
+            if ui.button(tr("logs.copy_all")).clicked() {
                 ui.output_mut(|out| {
                     out.copied_text = self.entries.iter().fold(String::new(), |acc, entry| {
                         format!(
@@ -106,7 +237,15 @@ impl LogsTab {
                     })
                 })
             }
-            if ui.button("Open logs directory").clicked() {
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui.button(tr("logs.export")).clicked() {
+                self.export(&filter_lowercase);
+            }
+            if ui.button(tr("button.clear")).clicked() {
+                self.entries.clear();
+                self.dropped_count = 0;
+            }
+            if ui.button(tr("logs.open_directory")).clicked() {
                 let log_dir = alvr_filesystem::filesystem_layout_from_dashboard_exe(
                     &env::current_exe().unwrap(),
                 )
@@ -118,10 +257,13 @@ impl LogsTab {
                     )))
                 });
             }
+            if self.dropped_count > 0 {
+                ui.label(tr_count("logs.dropped_entries", self.dropped_count));
+            }
         });
 
         ScrollArea::both()
-            .stick_to_bottom(true)
+            .stick_to_bottom(!self.pause_auto_scroll)
             .auto_shrink([false, false])
             .show(ui, |ui| {
                 Grid::new(0)
@@ -129,13 +271,37 @@ impl LogsTab {
                     .num_columns(3)
                     .striped(true)
                     .show(ui, |ui| {
-                        for entry in &self.entries {
-                            ui.colored_label(
-                                entry.color,
-                                RichText::new(&entry.timestamp).size(12.0),
-                            );
-                            ui.colored_label(entry.color, RichText::new(&entry.ty).size(12.0));
-                            ui.colored_label(entry.color, RichText::new(&entry.message).size(12.0));
+                        for entry in self
+                            .entries
+                            .iter()
+                            .filter(|entry| self.is_shown(entry, &filter_lowercase))
+                        {
+                            let color = entry.color();
+                            ui.horizontal(|ui| {
+                                ui.colored_label(
+                                    color,
+                                    RichText::new(&entry.timestamp).size(FONT_SIZE),
+                                );
+                                if entry.replayed {
+                                    ui.label(
+                                        RichText::new(tr("logs.replayed")).size(FONT_SIZE).weak(),
+                                    )
+                                    .on_hover_text(tr("logs.replayed_hint"));
+                                }
+                            });
+                            ui.colored_label(color, RichText::new(&entry.ty).size(FONT_SIZE));
+                            if filter_lowercase.is_empty() {
+                                ui.colored_label(
+                                    color,
+                                    RichText::new(&entry.message).size(FONT_SIZE),
+                                );
+                            } else {
+                                ui.label(highlighted_message(
+                                    &entry.message,
+                                    &filter_lowercase,
+                                    color,
+                                ));
+                            }
 
                             ui.end_row();
                         }
@@ -1,25 +1,35 @@
 mod about;
 mod connections;
 mod debug;
+mod input;
 mod logs;
 mod notifications;
 mod settings;
 mod settings_controls;
 mod setup_wizard;
 mod statistics;
+mod steamvr_restart;
+mod toasts;
 
 #[cfg(not(target_arch = "wasm32"))]
 mod installation;
+#[cfg(not(target_arch = "wasm32"))]
+mod settings_file;
 
 pub use about::*;
 pub use connections::*;
 pub use debug::*;
+pub use input::*;
 pub use logs::*;
 pub use notifications::*;
 pub use settings::*;
 pub use settings_controls::*;
 pub use setup_wizard::*;
 pub use statistics::*;
+pub use steamvr_restart::*;
+pub use toasts::*;
 
 #[cfg(not(target_arch = "wasm32"))]
 pub use installation::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use settings_file::*;
@@ -1,12 +1,23 @@
 use super::{
     notice,
-    presets::{builtin_schema, PresetControl},
+    presets::{
+        builtin_schema::{self, AudioDeviceSelection, StoredAudioDevice},
+        settings_presets::SettingsPresetsControl,
+        PresetControl,
+    },
     NestingInfo, SettingControl, INDENTATION_STEP,
 };
-use crate::dashboard::{DisplayString, ServerRequest};
-use alvr_packets::AudioDevicesList;
+use crate::dashboard::{
+    localization::{self, tr, tr_args},
+    DisplayString, ServerRequest,
+};
+use alvr_events::ApplicationProfileEvent;
+use alvr_gui_common::theme;
+use alvr_packets::{
+    AudioDeviceInfo, AudioDevicesList, PathSegment, PathValuePair, SettingValidationError,
+};
 use alvr_session::{SessionSettings, Settings};
-use eframe::egui::{Grid, Label, RichText, ScrollArea, Ui};
+use eframe::egui::{ComboBox, FontId, Grid, Label, RichText, ScrollArea, Sense, TextEdit, Ui};
 use serde_json as json;
 
 #[cfg(target_arch = "wasm32")]
@@ -18,61 +29,276 @@ use std::time::Instant;
 
 const DATA_UPDATE_INTERVAL: Duration = Duration::from_secs(1);
 
+// The CustomAudioDeviceConfig, if it is not one of the listed devices. Devices are matched like
+// the streamer does: names by substring, IDs exactly with the name as fallback
+fn stored_device(
+    devices: &[AudioDeviceInfo],
+    device_json: &json::Value,
+) -> Option<StoredAudioDevice> {
+    match device_json["variant"].as_str()? {
+        "NameSubstring" => {
+            let name = device_json["NameSubstring"].as_str()?;
+            let lowercase = name.to_lowercase();
+
+            Some(StoredAudioDevice {
+                selection: AudioDeviceSelection::NameSubstring(name.to_owned()),
+                missing: !devices
+                    .iter()
+                    .any(|device| device.name.to_lowercase().contains(&lowercase)),
+            })
+        }
+        "Id" => {
+            let id = device_json["Id"]["id"].as_str()?;
+            let name = device_json["Id"]["name"].as_str()?;
+            if devices.iter().any(|device| device.id == id) {
+                return None;
+            }
+
+            Some(StoredAudioDevice {
+                selection: AudioDeviceSelection::Id {
+                    id: id.to_owned(),
+                    name: name.to_owned(),
+                },
+                missing: !devices.iter().any(|device| device.name == name),
+            })
+        }
+        _ => None,
+    }
+}
+
+fn missing_device_name(stored_device: &Option<StoredAudioDevice>) -> Option<String> {
+    stored_device
+        .as_ref()
+        .filter(|stored| stored.missing)
+        .map(|stored| stored.selection.name().to_owned())
+}
+
+fn missing_device_ui(ui: &mut Ui, missing_device: Option<&str>) {
+    if let Some(name) = missing_device {
+        ui.colored_label(theme::palette().log.warning, "⚠")
+            .on_hover_text(tr_args(
+                "settings_tab.missing_audio_device",
+                &[("name", name)],
+            ));
+    }
+}
+
+// Shortcut for the dashboard appearance settings, with a color picker for the accent color
+fn appearance_ui(
+    ui: &mut Ui,
+    session_settings_json: &json::Value,
+    languages: &[(String, String)],
+) -> Vec<PathValuePair> {
+    let mut pairs = vec![];
+
+    let dashboard_json = &session_settings_json["dashboard"];
+    let current_language = dashboard_json["language"]
+        .as_str()
+        .unwrap_or(localization::ENGLISH_CODE);
+    let current_theme = dashboard_json["theme"]["variant"]
+        .as_str()
+        .unwrap_or("System");
+    let mut accent_color = [0, 1, 2].map(|i| {
+        dashboard_json["accent_color"]["content"][i]
+            .as_u64()
+            .unwrap_or(0) as u8
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(tr("settings_tab.theme"));
+        for (theme, key) in [
+            ("System", "settings_tab.theme_system"),
+            ("Light", "settings_tab.theme_light"),
+            ("Dark", "settings_tab.theme_dark"),
+        ] {
+            if ui
+                .selectable_label(current_theme == theme, tr(key))
+                .clicked()
+            {
+                pairs.push(PathValuePair {
+                    path: alvr_packets::parse_path("session_settings.dashboard.theme.variant"),
+                    value: theme.into(),
+                });
+            }
+        }
+
+        ui.separator();
+
+        ui.label(tr("settings_tab.accent_color"));
+        if ui.color_edit_button_srgb(&mut accent_color).changed() {
+            pairs.push(PathValuePair {
+                path: alvr_packets::parse_path("session_settings.dashboard.accent_color.content"),
+                value: json::to_value(accent_color).unwrap(),
+            });
+        }
+
+        ui.separator();
+
+        ui.label(tr("settings_tab.language"));
+        let current_name = languages
+            .iter()
+            .find(|(code, _)| code == current_language)
+            .map(|(_, name)| name.as_str())
+            .unwrap_or(current_language);
+        ComboBox::from_id_source("language_combobox")
+            .selected_text(current_name)
+            .show_ui(ui, |ui| {
+                for (code, name) in languages {
+                    if ui
+                        .selectable_label(code == current_language, name)
+                        .clicked()
+                    {
+                        pairs.push(PathValuePair {
+                            path: alvr_packets::parse_path("session_settings.dashboard.language"),
+                            value: code.as_str().into(),
+                        });
+                    }
+                }
+            });
+    });
+
+    pairs
+}
+
+// Returns false if nothing is shown
+fn application_profile_ui(ui: &mut Ui, event: &ApplicationProfileEvent) -> bool {
+    if event.app_key.is_none() && event.profile_name.is_none() {
+        return false;
+    }
+
+    ui.horizontal(|ui| {
+        ui.label(tr_args(
+            "settings_tab.running_application",
+            &[(
+                "app_key",
+                event
+                    .app_key
+                    .as_deref()
+                    .unwrap_or(&tr("settings_tab.no_application")),
+            )],
+        ));
+
+        ui.separator();
+
+        ui.label(match &event.profile_name {
+            Some(name) if event.manual => {
+                tr_args("settings_tab.manual_application_profile", &[("name", name)])
+            }
+            Some(name) => tr_args("settings_tab.active_application_profile", &[("name", name)]),
+            None => tr("settings_tab.no_application_profile"),
+        });
+    });
+
+    true
+}
+
 struct TopLevelEntry {
     id: DisplayString,
+    path: Vec<PathSegment>,
+    advanced_flag: bool,
     control: SettingControl,
+    shown_by_search: bool,
+    shown_by_view: bool,
+    modified: bool,
+    invalid_message: Option<String>,
 }
 
 pub struct SettingsTab {
+    settings_presets: SettingsPresetsControl,
+    #[cfg(not(target_arch = "wasm32"))]
+    settings_file: super::SettingsFileControl,
     resolution_preset: PresetControl,
     framerate_preset: PresetControl,
     encoder_preset: PresetControl,
     game_audio_preset: Option<PresetControl>,
     microphone_preset: Option<PresetControl>,
+    audio_devices: Option<AudioDevicesList>,
+    missing_game_audio_device: Option<String>,
+    missing_microphone_device: Option<String>,
     eye_face_tracking_preset: PresetControl,
     top_level_entries: Vec<TopLevelEntry>,
+    languages: Vec<(String, String)>,
+    search_query: String,
+    show_advanced: bool,
+    modified_paths: Vec<Vec<PathSegment>>,
+    validation_errors: Vec<SettingValidationError>,
+    application_profile: Option<ApplicationProfileEvent>,
     session_settings_json: Option<json::Value>,
+    // The web dashboard cannot detect the GPU
+    #[cfg(not(target_arch = "wasm32"))]
+    gpu_vendor: Option<alvr_common::GpuVendor>,
     last_update_instant: Instant,
 }
 
-impl SettingsTab {
-    pub fn new() -> Self {
-        let nesting_info = NestingInfo {
-            path: vec!["session_settings".into()],
-            indentation_level: 0,
-        };
-        let schema = Settings::schema(alvr_session::session_settings_default());
-
-        // Top level node must be a section
-        let SchemaNode::Section { entries, .. } = schema else {
-            unreachable!();
-        };
-
-        let top_level_entries = entries
-            .into_iter()
-            .map(|entry| {
-                let id = entry.name;
-                let display = super::get_display_name(&id, &entry.strings);
-
-                let mut nesting_info = nesting_info.clone();
-                nesting_info.path.push(id.clone().into());
+impl TopLevelEntry {
+    // The basic view is ignored while searching
+    fn shown(&self, searching: bool) -> bool {
+        self.shown_by_search && (searching || self.shown_by_view)
+    }
+}
 
-                TopLevelEntry {
-                    id: DisplayString { id, display },
-                    control: SettingControl::new(nesting_info, entry.content),
-                }
-            })
-            .collect();
+fn top_level_entries() -> Vec<TopLevelEntry> {
+    let nesting_info = NestingInfo {
+        path: vec!["session_settings".into()],
+        indentation_level: 0,
+    };
+    let schema = Settings::schema(alvr_session::session_settings_default());
+
+    // Top level node must be a section
+    let SchemaNode::Section { entries, .. } = schema else {
+        unreachable!();
+    };
+
+    entries
+        .into_iter()
+        .map(|entry| {
+            let id = entry.name;
+
+            let mut nesting_info = nesting_info.clone();
+            nesting_info.path.push(id.clone().into());
+
+            let display = localization::schema_string(&nesting_info.path, "display_name")
+                .unwrap_or_else(|| super::get_display_name(&id, &entry.strings));
+
+            TopLevelEntry {
+                id: DisplayString { id, display },
+                path: nesting_info.path.clone(),
+                advanced_flag: entry.flags.contains("advanced"),
+                control: SettingControl::new(nesting_info, entry.content),
+                shown_by_search: true,
+                shown_by_view: true,
+                modified: false,
+                invalid_message: None,
+            }
+        })
+        .collect()
+}
 
+impl SettingsTab {
+    pub fn new() -> Self {
         Self {
+            settings_presets: SettingsPresetsControl::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            settings_file: super::SettingsFileControl::new(),
             resolution_preset: PresetControl::new(builtin_schema::resolution_schema()),
             framerate_preset: PresetControl::new(builtin_schema::framerate_schema()),
             encoder_preset: PresetControl::new(builtin_schema::encoder_preset_schema()),
             game_audio_preset: None,
             microphone_preset: None,
+            audio_devices: None,
+            missing_game_audio_device: None,
+            missing_microphone_device: None,
             eye_face_tracking_preset: PresetControl::new(builtin_schema::eye_face_tracking_schema()),
-            top_level_entries,
+            top_level_entries: top_level_entries(),
+            languages: localization::available_languages(),
+            search_query: String::new(),
+            show_advanced: false,
+            modified_paths: vec![],
+            validation_errors: vec![],
+            application_profile: None,
             session_settings_json: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            gpu_vendor: alvr_server_io::select_gpu_vendor(&crate::gpu_vendors::GPU_VENDORS),
             last_update_instant: Instant::now(),
         }
     }
@@ -80,35 +306,137 @@ impl SettingsTab {
     pub fn update_session(&mut self, session_settings: &SessionSettings) {
         let settings_json = json::to_value(session_settings).unwrap();
 
+        self.settings_presets
+            .update_session_settings(&settings_json);
+        #[cfg(not(target_arch = "wasm32"))]
+        self.settings_file.update_session_settings(&settings_json);
+
         self.resolution_preset
             .update_session_settings(&settings_json);
         self.framerate_preset
             .update_session_settings(&settings_json);
         self.encoder_preset.update_session_settings(&settings_json);
-        if let Some(preset) = self.game_audio_preset.as_mut() {
-            preset.update_session_settings(&settings_json)
-        }
-        if let Some(preset) = self.microphone_preset.as_mut() {
-            preset.update_session_settings(&settings_json)
-        }
         self.eye_face_tracking_preset
             .update_session_settings(&settings_json);
 
+        self.modified_paths = super::modified_paths(&settings_json, super::default_settings_json());
+        self.update_modified();
+
+        let show_advanced = session_settings.dashboard.show_advanced_settings;
+        if show_advanced != self.show_advanced || self.session_settings_json.is_none() {
+            self.show_advanced = show_advanced;
+            self.update_view();
+        }
+
         self.session_settings_json = Some(settings_json);
+
+        // The stored devices may have changed
+        self.update_audio_presets();
+    }
+
+    pub fn update_validation_errors(&mut self, errors: Vec<SettingValidationError>) {
+        self.validation_errors = errors;
+        self.update_invalid();
+    }
+
+    pub fn update_application_profile(&mut self, event: ApplicationProfileEvent) {
+        self.application_profile = Some(event);
     }
 
     pub fn update_audio_devices(&mut self, list: AudioDevicesList) {
-        let mut all_devices = list.output.clone();
-        all_devices.extend(list.input);
+        self.audio_devices = Some(list);
+        self.update_audio_presets();
+    }
 
-        if let Some(json) = &self.session_settings_json {
-            let mut preset = PresetControl::new(builtin_schema::game_audio_schema(all_devices));
-            preset.update_session_settings(json);
-            self.game_audio_preset = Some(preset);
+    fn update_audio_presets(&mut self) {
+        let (Some(json), Some(list)) = (&self.session_settings_json, &self.audio_devices) else {
+            return;
+        };
+        let audio_json = &json["audio"];
+
+        let mut game_audio_devices = list.output.clone();
+        game_audio_devices.extend(list.input.iter().cloned());
+        let game_audio_json = &audio_json["game_audio"];
+        let stored_game_audio_device = (game_audio_json["enabled"] == true
+            && game_audio_json["content"]["device"]["set"] == true)
+            .then(|| {
+                stored_device(
+                    &game_audio_devices,
+                    &game_audio_json["content"]["device"]["content"],
+                )
+            })
+            .flatten();
+        self.missing_game_audio_device = missing_device_name(&stored_game_audio_device);
+        let mut preset = PresetControl::new(builtin_schema::game_audio_schema(
+            &game_audio_devices,
+            stored_game_audio_device,
+        ));
+        preset.update_session_settings(json);
+        self.game_audio_preset = Some(preset);
+
+        // Only the sink of custom devices is listed, on Windows the virtual cables are used instead
+        let devices_json = &audio_json["microphone"]["content"]["devices"];
+        let stored_microphone_device = (!cfg!(windows)
+            && audio_json["microphone"]["enabled"] == true
+            && devices_json["variant"] == "Custom")
+            .then(|| stored_device(&list.output, &devices_json["Custom"]["sink"]))
+            .flatten();
+        self.missing_microphone_device = missing_device_name(&stored_microphone_device);
+        let mut preset = PresetControl::new(builtin_schema::microphone_schema(
+            &list.output,
+            stored_microphone_device,
+        ));
+        preset.update_session_settings(json);
+        self.microphone_preset = Some(preset);
+    }
+
+    // The controls keep the schema strings of the language they were created with
+    pub fn update_language(&mut self) {
+        self.top_level_entries = top_level_entries();
+        self.languages = localization::available_languages();
+        self.update_search();
+        self.update_view();
+        self.update_modified();
+        self.update_invalid();
+    }
+
+    fn update_view(&mut self) {
+        for entry in &mut self.top_level_entries {
+            let content_shown = entry.control.update_view(self.show_advanced);
+
+            entry.shown_by_view = self.show_advanced || (!entry.advanced_flag && content_shown);
+        }
+    }
+
+    fn update_modified(&mut self) {
+        for entry in &mut self.top_level_entries {
+            entry.modified = self
+                .modified_paths
+                .iter()
+                .any(|path| path.starts_with(&entry.path));
+            entry.control.update_modified(&self.modified_paths);
+        }
+    }
 
-            let mut preset = PresetControl::new(builtin_schema::microphone_schema(list.output));
-            preset.update_session_settings(json);
-            self.microphone_preset = Some(preset);
+    fn update_invalid(&mut self) {
+        for entry in &mut self.top_level_entries {
+            entry.invalid_message = super::invalid_message(&self.validation_errors, &entry.path);
+            entry.control.update_invalid(&self.validation_errors);
+        }
+    }
+
+    fn update_search(&mut self) {
+        let query_lowercase = self.search_query.trim().to_ascii_lowercase();
+
+        for entry in &mut self.top_level_entries {
+            let entry_match = super::search_matches(&entry.id.id, &query_lowercase)
+                || super::search_matches(&entry.id.display, &query_lowercase);
+            let content_match =
+                entry
+                    .control
+                    .update_search(if entry_match { "" } else { &query_lowercase });
+
+            entry.shown_by_search = query_lowercase.is_empty() || entry_match || content_match;
         }
     }
 
@@ -121,7 +449,7 @@ impl SettingsTab {
                 requests.push(ServerRequest::GetSession);
             }
 
-            if self.game_audio_preset.is_none() {
+            if self.audio_devices.is_none() {
                 requests.push(ServerRequest::GetAudioDevices);
             }
 
@@ -130,10 +458,46 @@ impl SettingsTab {
 
         let mut path_value_pairs = vec![];
 
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                TextEdit::singleline(&mut self.search_query)
+                    .hint_text(tr("settings_tab.search_hint"))
+                    .desired_width(300.0),
+            );
+            if ui.button(tr("button.clear")).clicked() {
+                self.search_query.clear();
+                self.update_search();
+            } else if response.changed() {
+                self.update_search();
+            }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            path_value_pairs.extend(self.settings_file.ui(ui));
+        });
+        ui.add_space(5.0);
+
+        let query_lowercase = self.search_query.trim().to_ascii_lowercase();
+        let searching = !query_lowercase.is_empty();
+
         ScrollArea::new([false, true])
             .id_source("settings_tab_scroll")
             .show(ui, |ui| {
-                ui.add(Label::new(RichText::new("Presets").size(20.0)));
+                if let Some(json) = &self.session_settings_json {
+                    path_value_pairs.extend(appearance_ui(ui, json, &self.languages));
+                    ui.add_space(10.0);
+                }
+
+                if let Some(event) = &self.application_profile {
+                    if application_profile_ui(ui, event) {
+                        ui.add_space(10.0);
+                    }
+                }
+
+                ui.add(Label::new(
+                    RichText::new(tr("settings_tab.presets")).size(20.0),
+                ));
+                path_value_pairs.extend(self.settings_presets.ui(ui));
+                ui.add_space(10.0);
                 ScrollArea::new([true, false])
                     .id_source("presets_scroll")
                     .show(ui, |ui| {
@@ -152,16 +516,56 @@ impl SettingsTab {
 
                                 if let Some(preset) = &mut self.game_audio_preset {
                                     path_value_pairs.extend(preset.ui(ui));
+                                    missing_device_ui(
+                                        ui,
+                                        self.missing_game_audio_device.as_deref(),
+                                    );
                                     ui.end_row();
                                 }
 
                                 if let Some(preset) = &mut self.microphone_preset {
                                     path_value_pairs.extend(preset.ui(ui));
+                                    missing_device_ui(
+                                        ui,
+                                        self.missing_microphone_device.as_deref(),
+                                    );
+                                    ui.end_row();
+                                }
+
+                                if self.audio_devices.is_some() {
+                                    ui.label("");
+                                    if ui
+                                        .button(tr("settings_tab.refresh_audio_devices"))
+                                        .clicked()
+                                    {
+                                        requests.push(ServerRequest::GetAudioDevices);
+                                    }
                                     ui.end_row();
                                 }
 
                                 path_value_pairs.extend(self.eye_face_tracking_preset.ui(ui));
                                 ui.end_row();
+
+                                #[cfg(not(target_arch = "wasm32"))]
+                                if let Some(vendor) = self.gpu_vendor {
+                                    ui.label("");
+                                    if ui
+                                        .button(tr_args(
+                                            "settings_tab.gpu_defaults",
+                                            &[(
+                                                "vendor",
+                                                crate::dashboard::gpu_vendor_name(vendor),
+                                            )],
+                                        ))
+                                        .on_hover_text(tr("settings_tab.gpu_defaults_hint"))
+                                        .clicked()
+                                    {
+                                        requests.push(ServerRequest::ApplyGpuDefaults(
+                                            crate::gpu_vendors::GPU_VENDORS.clone(),
+                                        ));
+                                    }
+                                    ui.end_row();
+                                }
                             })
                     });
 
@@ -169,10 +573,49 @@ impl SettingsTab {
 
                 ui.horizontal(|ui| {
                     ui.add(Label::new(
-                        RichText::new("All Settings (Advanced)").size(20.0),
+                        RichText::new(tr("settings_tab.advanced")).size(20.0),
                     ));
-                    notice::notice(ui, "Changing some advanced settings may break ALVR");
+
+                    for (show_advanced, key) in [
+                        (false, "settings_tab.basic_view"),
+                        (true, "settings_tab.advanced_view"),
+                    ] {
+                        if ui
+                            .selectable_label(
+                                self.show_advanced == show_advanced,
+                                RichText::new(tr(key)).size(16.0),
+                            )
+                            .on_hover_text(tr("settings_tab.view_hint"))
+                            .clicked()
+                            && self.show_advanced != show_advanced
+                        {
+                            self.show_advanced = show_advanced;
+                            self.update_view();
+
+                            path_value_pairs.push(PathValuePair {
+                                path: alvr_packets::parse_path(
+                                    "session_settings.dashboard.show_advanced_settings",
+                                ),
+                                value: show_advanced.into(),
+                            });
+                        }
+                    }
+
+                    if self.show_advanced {
+                        notice::notice(ui, &tr("settings_tab.advanced_notice"));
+                    }
                 });
+                if !self
+                    .top_level_entries
+                    .iter()
+                    .any(|entry| entry.shown(searching))
+                {
+                    ui.label(tr(if searching {
+                        "settings_tab.no_search_results"
+                    } else {
+                        "settings_tab.no_basic_settings"
+                    }));
+                }
                 ScrollArea::new([true, false])
                     .id_source("advanced_scroll")
                     .show(ui, |ui| {
@@ -184,14 +627,36 @@ impl SettingsTab {
                                     let session_fragments_mut =
                                         session_fragment.as_object_mut().unwrap();
 
-                                    for entry in self.top_level_entries.iter_mut() {
+                                    let shown_entries = self
+                                        .top_level_entries
+                                        .iter_mut()
+                                        .filter(|entry| entry.shown(searching));
+                                    for entry in shown_entries {
                                         ui.horizontal(|ui| {
                                             ui.add_space(INDENTATION_STEP);
-                                            let label_res = ui.add(Label::new(
-                                                RichText::new(&entry.id.display)
-                                                    .size(18.0)
-                                                    .monospace(),
-                                            ));
+                                            let label_res = ui.add(
+                                                Label::new(super::highlighted_text(
+                                                    &entry.id.display,
+                                                    &query_lowercase,
+                                                    FontId::monospace(18.0),
+                                                    ui.visuals().text_color(),
+                                                ))
+                                                .sense(Sense::click()),
+                                            );
+                                            if let Some(request) = super::modified_marker_ui(
+                                                ui,
+                                                &label_res,
+                                                &entry.path,
+                                                entry.modified,
+                                                true,
+                                                &session_fragments_mut[&entry.id.id],
+                                            ) {
+                                                path_value_pairs.push(request);
+                                            }
+                                            super::invalid_marker_ui(
+                                                ui,
+                                                entry.invalid_message.as_deref(),
+                                            );
                                             if cfg!(debug_assertions) {
                                                 label_res.on_hover_text(&*entry.id);
                                             }
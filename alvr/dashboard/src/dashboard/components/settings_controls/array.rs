@@ -1,5 +1,5 @@
 use super::{collapsible, NestingInfo, SettingControl};
-use alvr_packets::PathValuePair;
+use alvr_packets::{PathSegment, PathValuePair, SettingValidationError};
 use alvr_session::settings_schema::SchemaNode;
 use eframe::egui::Ui;
 use serde_json as json;
@@ -7,6 +7,7 @@ use serde_json as json;
 pub struct Control {
     nesting_info: NestingInfo,
     controls: Vec<SettingControl>,
+    expanded_by_search: bool,
 }
 
 impl Control {
@@ -26,6 +27,35 @@ impl Control {
         Self {
             nesting_info,
             controls,
+            expanded_by_search: false,
+        }
+    }
+
+    pub fn update_search(&mut self, query_lowercase: &str) -> bool {
+        self.expanded_by_search = self.controls.iter_mut().fold(false, |any_match, control| {
+            control.update_search(query_lowercase) || any_match
+        });
+
+        self.expanded_by_search
+    }
+
+    pub fn update_view(&mut self, show_advanced: bool) -> bool {
+        for control in &mut self.controls {
+            control.update_view(show_advanced);
+        }
+
+        true
+    }
+
+    pub fn update_modified(&mut self, modified_paths: &[Vec<PathSegment>]) {
+        for control in &mut self.controls {
+            control.update_modified(modified_paths);
+        }
+    }
+
+    pub fn update_invalid(&mut self, errors: &[SettingValidationError]) {
+        for control in &mut self.controls {
+            control.update_invalid(errors);
         }
     }
 
@@ -39,8 +69,14 @@ impl Control {
 
         let mut request = None;
 
-        let collapsed =
-            collapsible::collapsible_button(ui, &self.nesting_info, session_fragment, &mut request);
+        // The stored state is left untouched while searching
+        let collapsed = !self.expanded_by_search
+            && collapsible::collapsible_button(
+                ui,
+                &self.nesting_info,
+                session_fragment,
+                &mut request,
+            );
 
         if !collapsed {
             let session_array_mut = session_fragment["content"].as_array_mut().unwrap();
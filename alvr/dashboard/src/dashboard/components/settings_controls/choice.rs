@@ -1,6 +1,6 @@
 use super::{reset, NestingInfo, SettingControl};
-use crate::dashboard::{basic_components, get_id, DisplayString};
-use alvr_packets::PathValuePair;
+use crate::dashboard::{basic_components, get_id, localization, DisplayString};
+use alvr_packets::{PathSegment, PathValuePair, SettingValidationError};
 use alvr_session::settings_schema::{ChoiceControlType, SchemaEntry, SchemaNode};
 use eframe::{
     egui::{ComboBox, Layout, Ui},
@@ -48,9 +48,15 @@ impl Control {
     ) -> Self {
         let variant_labels = schema_variants
             .iter()
-            .map(|entry| DisplayString {
-                id: entry.name.clone(),
-                display: get_display_name(&entry.name, &entry.strings),
+            .map(|entry| {
+                let mut path = nesting_info.path.clone();
+                path.push(entry.name.clone().into());
+
+                DisplayString {
+                    id: entry.name.clone(),
+                    display: localization::schema_string(&path, "display_name")
+                        .unwrap_or_else(|| get_display_name(&entry.name, &entry.strings)),
+                }
             })
             .collect::<Vec<_>>();
 
@@ -98,6 +104,41 @@ impl Control {
         }
     }
 
+    // Variant labels are matched too, even if the variant is not the selected one
+    pub fn update_search(&mut self, query_lowercase: &str) -> bool {
+        let label_match = self
+            .variant_labels
+            .iter()
+            .any(|label| super::search_matches(&label.display, query_lowercase));
+
+        self.variant_controls
+            .values_mut()
+            .fold(label_match, |any_match, control| {
+                control.update_search(query_lowercase) || any_match
+            })
+    }
+
+    // The variant selector is always shown
+    pub fn update_view(&mut self, show_advanced: bool) -> bool {
+        for control in self.variant_controls.values_mut() {
+            control.update_view(show_advanced);
+        }
+
+        true
+    }
+
+    pub fn update_modified(&mut self, modified_paths: &[Vec<PathSegment>]) {
+        for control in self.variant_controls.values_mut() {
+            control.update_modified(modified_paths);
+        }
+    }
+
+    pub fn update_invalid(&mut self, errors: &[SettingValidationError]) {
+        for control in self.variant_controls.values_mut() {
+            control.update_invalid(errors);
+        }
+    }
+
     pub fn ui(
         &mut self,
         ui: &mut Ui,
@@ -14,14 +14,26 @@ pub mod text;
 pub mod up_down;
 pub mod vector;
 
-use alvr_packets::{PathSegment, PathValuePair};
+use crate::dashboard::localization::tr;
+use alvr_common::once_cell::sync::Lazy;
+use alvr_gui_common::theme;
+use alvr_packets::{PathSegment, PathValuePair, SettingValidationError};
 use alvr_session::settings_schema::SchemaNode;
-use eframe::egui::Ui;
+use eframe::{
+    egui::{
+        text::{LayoutJob, TextFormat},
+        Button, FontId, Response, Ui,
+    },
+    epaint::Color32,
+};
 use serde_json as json;
 use std::collections::HashMap;
 
 pub const INDENTATION_STEP: f32 = 20.0;
 
+static DEFAULT_SETTINGS_JSON: Lazy<json::Value> =
+    Lazy::new(|| json::to_value(alvr_session::session_settings_default()).unwrap());
+
 fn get_single_value(
     nesting_info: &NestingInfo,
     leaf: PathSegment,
@@ -51,6 +63,221 @@ pub fn get_display_name(id: &str, strings: &HashMap<String, String>) -> String {
     })
 }
 
+// Search queries are compared in ASCII lowercase so that the byte offsets of matches are the same
+// in the original text
+pub fn search_matches(text: &str, query_lowercase: &str) -> bool {
+    !query_lowercase.is_empty() && text.to_ascii_lowercase().contains(query_lowercase)
+}
+
+pub fn highlighted_text(
+    text: &str,
+    query_lowercase: &str,
+    font_id: FontId,
+    color: Color32,
+) -> LayoutJob {
+    let format = TextFormat {
+        font_id,
+        color,
+        ..Default::default()
+    };
+
+    let mut job = LayoutJob::default();
+    if query_lowercase.is_empty() {
+        job.append(text, 0.0, format);
+
+        return job;
+    }
+
+    let highlight_format = TextFormat {
+        background: theme::palette().accent,
+        color: Color32::WHITE,
+        ..format.clone()
+    };
+
+    let text_lowercase = text.to_ascii_lowercase();
+    let mut start = 0;
+    for (match_start, matched) in text_lowercase.match_indices(query_lowercase) {
+        job.append(&text[start..match_start], 0.0, format.clone());
+        job.append(
+            &text[match_start..match_start + matched.len()],
+            0.0,
+            highlight_format.clone(),
+        );
+        start = match_start + matched.len();
+    }
+    job.append(&text[start..], 0.0, format);
+
+    job
+}
+
+// The gui_collapsed fields only store the state of the UI and are not compared
+pub fn collect_leaves(
+    value: &json::Value,
+    path: Vec<PathSegment>,
+    leaves: &mut Vec<PathValuePair>,
+) {
+    if let json::Value::Object(map) = value {
+        for (key, value) in map {
+            if key != "gui_collapsed" {
+                let mut path = path.clone();
+                path.push(key.as_str().into());
+                collect_leaves(value, path, leaves);
+            }
+        }
+    } else {
+        leaves.push(PathValuePair {
+            path,
+            value: value.clone(),
+        });
+    }
+}
+
+// The first path segment is always "session_settings" and it is skipped
+pub fn get_value<'a>(json: &'a json::Value, path: &[PathSegment]) -> Option<&'a json::Value> {
+    path.iter()
+        .skip(1)
+        .try_fold(json, |json, segment| match segment {
+            PathSegment::Name(name) => json.get(name),
+            PathSegment::Index(index) => json.get(index),
+        })
+}
+
+pub fn set_value(json: &mut json::Value, path: &[PathSegment], value: json::Value) {
+    let mut json_ref = json;
+    for segment in path.iter().skip(1) {
+        let next = match segment {
+            PathSegment::Name(name) => json_ref.get_mut(name),
+            PathSegment::Index(index) => json_ref.get_mut(index),
+        };
+        let Some(next) = next else {
+            return;
+        };
+        json_ref = next;
+    }
+
+    *json_ref = value;
+}
+
+pub fn default_settings_json() -> &'static json::Value {
+    &DEFAULT_SETTINGS_JSON
+}
+
+// Leaves are compared in both directions, so that values missing on either side are found too
+pub fn modified_paths(
+    session_settings_json: &json::Value,
+    default_json: &json::Value,
+) -> Vec<Vec<PathSegment>> {
+    let mut session_leaves = vec![];
+    collect_leaves(
+        session_settings_json,
+        vec!["session_settings".into()],
+        &mut session_leaves,
+    );
+    let mut default_leaves = vec![];
+    collect_leaves(
+        default_json,
+        vec!["session_settings".into()],
+        &mut default_leaves,
+    );
+
+    let mut paths = session_leaves
+        .into_iter()
+        .filter(|pair| get_value(default_json, &pair.path) != Some(&pair.value))
+        .map(|pair| pair.path)
+        .collect::<Vec<_>>();
+    paths.extend(
+        default_leaves
+            .into_iter()
+            .filter(|pair| get_value(session_settings_json, &pair.path).is_none())
+            .map(|pair| pair.path),
+    );
+
+    paths
+}
+
+// The gui_collapsed fields of the current value are kept, so that a reset does not fold sections
+fn default_with_ui_state(default: &json::Value, current: &json::Value) -> json::Value {
+    match (default, current) {
+        (json::Value::Object(default_map), json::Value::Object(current_map)) => default_map
+            .iter()
+            .map(|(key, default_value)| {
+                let value = match current_map.get(key) {
+                    Some(current_value) if key == "gui_collapsed" => current_value.clone(),
+                    Some(current_value) => default_with_ui_state(default_value, current_value),
+                    None => default_value.clone(),
+                };
+
+                (key.clone(), value)
+            })
+            .collect::<json::Map<_, _>>()
+            .into(),
+        _ => default.clone(),
+    }
+}
+
+// Shows a marker beside the label of modified entries, and a context menu on the label to reset
+// the entry to its default. Sections are marked if any setting inside them is modified
+pub fn modified_marker_ui(
+    ui: &mut Ui,
+    label_response: &Response,
+    path: &[PathSegment],
+    modified: bool,
+    is_section: bool,
+    session_fragment: &json::Value,
+) -> Option<PathValuePair> {
+    let mut request = None;
+
+    label_response.context_menu(|ui| {
+        let label = if is_section {
+            tr("settings_tab.reset_section")
+        } else {
+            tr("settings_tab.reset_to_default")
+        };
+        if ui.add_enabled(modified, Button::new(label)).clicked() {
+            request = get_value(default_settings_json(), path).map(|default| PathValuePair {
+                path: path.to_vec(),
+                value: default_with_ui_state(default, session_fragment),
+            });
+
+            ui.close_menu();
+        }
+    });
+
+    if modified {
+        ui.colored_label(theme::palette().accent, "●")
+            .on_hover_text(if is_section {
+                tr("settings_tab.modified_section")
+            } else {
+                tr("settings_tab.modified")
+            });
+    }
+
+    request
+}
+
+// The messages of the validation errors that involve the entry or any setting inside it
+pub fn invalid_message(errors: &[SettingValidationError], path: &[PathSegment]) -> Option<String> {
+    let messages = errors
+        .iter()
+        .filter(|error| {
+            error
+                .entries
+                .iter()
+                .any(|entry| entry.path.starts_with(path))
+        })
+        .map(|error| error.message.as_str())
+        .collect::<Vec<_>>();
+
+    (!messages.is_empty()).then(|| messages.join("\n"))
+}
+
+pub fn invalid_marker_ui(ui: &mut Ui, message: Option<&str>) {
+    if let Some(message) = message {
+        ui.colored_label(theme::palette().log.error, "⛔")
+            .on_hover_text(message);
+    }
+}
+
 #[derive(Clone)]
 pub struct NestingInfo {
     pub path: Vec<PathSegment>,
@@ -134,6 +361,57 @@ impl SettingControl {
         }
     }
 
+    // Returns true if any setting inside this control matches the query. Sections hide the entries
+    // without matches and stay expanded while the query is not empty. An empty query shows
+    // everything again, with the expansion state stored in the session
+    pub fn update_search(&mut self, query_lowercase: &str) -> bool {
+        match self {
+            Self::Section(control) => control.update_search(query_lowercase),
+            Self::Choice(control) => control.update_search(query_lowercase),
+            Self::Optional(control) => control.update_search(query_lowercase),
+            Self::Switch(control) => control.update_search(query_lowercase),
+            Self::Array(control) => control.update_search(query_lowercase),
+            _ => false,
+        }
+    }
+
+    // Returns false if nothing inside this control is shown. In the basic view, entries flagged as
+    // advanced are hidden, together with the sections left empty
+    pub fn update_view(&mut self, show_advanced: bool) -> bool {
+        match self {
+            Self::Section(control) => control.update_view(show_advanced),
+            Self::Choice(control) => control.update_view(show_advanced),
+            Self::Optional(control) => control.update_view(show_advanced),
+            Self::Switch(control) => control.update_view(show_advanced),
+            Self::Array(control) => control.update_view(show_advanced),
+            _ => true,
+        }
+    }
+
+    // Marks the entries that contain any of the modified paths
+    pub fn update_modified(&mut self, modified_paths: &[Vec<PathSegment>]) {
+        match self {
+            Self::Section(control) => control.update_modified(modified_paths),
+            Self::Choice(control) => control.update_modified(modified_paths),
+            Self::Optional(control) => control.update_modified(modified_paths),
+            Self::Switch(control) => control.update_modified(modified_paths),
+            Self::Array(control) => control.update_modified(modified_paths),
+            _ => (),
+        }
+    }
+
+    // Marks the entries that contain a setting rejected by the server validation
+    pub fn update_invalid(&mut self, errors: &[SettingValidationError]) {
+        match self {
+            Self::Section(control) => control.update_invalid(errors),
+            Self::Choice(control) => control.update_invalid(errors),
+            Self::Optional(control) => control.update_invalid(errors),
+            Self::Switch(control) => control.update_invalid(errors),
+            Self::Array(control) => control.update_invalid(errors),
+            _ => (),
+        }
+    }
+
     // inline: first field child, could be rendered beside the field label
     pub fn ui(
         &mut self,
@@ -1,4 +1,5 @@
 use super::{reset, NestingInfo};
+use alvr_gui_common::theme;
 use alvr_packets::PathValuePair;
 use alvr_session::settings_schema::{NumberType, NumericGuiType};
 use eframe::{
@@ -16,6 +17,33 @@ fn to_json_value(number: f64, ty: NumberType) -> json::Value {
     }
 }
 
+// Values typed into the field can be outside of the range allowed by the schema. They are reported
+// and not committed
+fn validation_error(
+    value: f64,
+    ty: NumberType,
+    gui_type: &NumericGuiType,
+    suffix: Option<&str>,
+) -> Option<String> {
+    let suffix = suffix.unwrap_or_default();
+
+    if let NumericGuiType::Slider { range, .. } = gui_type {
+        if !range.contains(&value) {
+            return Some(format!(
+                "Must be between {}{suffix} and {}{suffix}",
+                range.start(),
+                range.end()
+            ));
+        }
+    }
+
+    if matches!(ty, NumberType::UnsignedInteger) && value < 0.0 {
+        return Some("Must not be negative".into());
+    }
+
+    None
+}
+
 pub struct Control {
     nesting_info: NestingInfo,
     editing_value_f64: Option<f64>,
@@ -83,8 +111,9 @@ impl Control {
                     step,
                     logarithmic,
                 } => {
-                    let mut slider =
-                        Slider::new(editing_value_mut, range.clone()).logarithmic(*logarithmic);
+                    let mut slider = Slider::new(editing_value_mut, range.clone())
+                        .logarithmic(*logarithmic)
+                        .clamp_to_range(false);
 
                     if let Some(step) = step {
                         slider = slider.step_by(*step);
@@ -113,11 +142,24 @@ impl Control {
                     ui.add(textbox)
                 }
             };
+            let editing_value = *editing_value_mut;
+            let validation_error = validation_error(
+                editing_value,
+                self.ty,
+                &self.gui_type,
+                self.suffix.as_deref(),
+            );
+
+            // An invalid value is kept in the field until it is corrected or reset
             if response.drag_started() || response.gained_focus() {
-                self.editing_value_f64 = Some(session_value)
-            } else if response.drag_stopped() || response.lost_focus() {
-                request = get_request(&self.nesting_info, *editing_value_mut, self.ty);
-                *session_fragment = to_json_value(*editing_value_mut, self.ty);
+                if self.editing_value_f64.is_none() {
+                    self.editing_value_f64 = Some(session_value)
+                }
+            } else if (response.drag_stopped() || response.lost_focus())
+                && validation_error.is_none()
+            {
+                request = get_request(&self.nesting_info, editing_value, self.ty);
+                *session_fragment = to_json_value(editing_value, self.ty);
 
                 self.editing_value_f64 = None;
             }
@@ -126,6 +168,12 @@ impl Control {
                 .clicked()
             {
                 request = get_request(&self.nesting_info, self.default, self.ty);
+
+                self.editing_value_f64 = None;
+            }
+
+            if let Some(error) = validation_error {
+                ui.colored_label(theme::palette().log.error, error);
             }
         });
 
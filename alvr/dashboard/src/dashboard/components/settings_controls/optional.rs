@@ -1,5 +1,5 @@
 use super::{reset, NestingInfo, SettingControl};
-use alvr_packets::PathValuePair;
+use alvr_packets::{PathSegment, PathValuePair, SettingValidationError};
 use alvr_session::settings_schema::SchemaNode;
 use eframe::{
     egui::{Layout, Ui},
@@ -11,6 +11,8 @@ pub struct Control {
     nesting_info: NestingInfo,
     default_set: bool,
     default_string: String,
+    // Numeric content is shown beside the selector and disabled when unset
+    inline_content: bool,
     content_control: Box<SettingControl>,
 }
 
@@ -22,6 +24,8 @@ impl Control {
             "Default".into()
         };
 
+        let inline_content = matches!(schema_content, SchemaNode::Number { .. });
+
         let control = {
             let mut nesting_info = nesting_info.clone();
             nesting_info.path.push("content".into());
@@ -33,10 +37,30 @@ impl Control {
             nesting_info,
             default_set,
             default_string,
+            inline_content,
             content_control: Box::new(control),
         }
     }
 
+    pub fn update_search(&mut self, query_lowercase: &str) -> bool {
+        self.content_control.update_search(query_lowercase)
+    }
+
+    // The toggle is always shown
+    pub fn update_view(&mut self, show_advanced: bool) -> bool {
+        self.content_control.update_view(show_advanced);
+
+        true
+    }
+
+    pub fn update_modified(&mut self, modified_paths: &[Vec<PathSegment>]) {
+        self.content_control.update_modified(modified_paths)
+    }
+
+    pub fn update_invalid(&mut self, errors: &[SettingValidationError]) {
+        self.content_control.update_invalid(errors)
+    }
+
     pub fn ui(
         &mut self,
         ui: &mut Ui,
@@ -47,7 +71,7 @@ impl Control {
 
         let session_switch_mut = session_fragment.as_object_mut().unwrap();
 
-        let json::Value::Bool(set_mut) = &mut session_switch_mut["set"] else {
+        let json::Value::Bool(mut set) = session_switch_mut["set"] else {
             unreachable!()
         };
 
@@ -58,19 +82,30 @@ impl Control {
         }
 
         ui.with_layout(Layout::left_to_right(Align::Center), |ui| {
-            if ui.selectable_value(set_mut, false, "Default").clicked()
-                || ui.selectable_value(set_mut, true, "Set").clicked()
+            if ui.selectable_value(&mut set, false, "Default").clicked()
+                || ui.selectable_value(&mut set, true, "Set").clicked()
             {
-                request = get_request(&self.nesting_info, *set_mut);
+                request = get_request(&self.nesting_info, set);
             }
 
-            if reset::reset_button(ui, *set_mut != self.default_set, &self.default_string).clicked()
-            {
+            if reset::reset_button(ui, set != self.default_set, &self.default_string).clicked() {
                 request = get_request(&self.nesting_info, self.default_set);
             }
+
+            if self.inline_content {
+                request = ui
+                    .add_enabled_ui(set, |ui| {
+                        self.content_control
+                            .ui(ui, &mut session_switch_mut["content"], true)
+                    })
+                    .inner
+                    .or(request.take());
+            }
         });
 
-        if *set_mut {
+        session_switch_mut["set"] = json::Value::Bool(set);
+
+        if set && !self.inline_content {
             ui.end_row();
 
             request = self
@@ -2,6 +2,7 @@ use super::schema::{
     HigherOrderChoiceOption, HigherOrderChoiceSchema, PresetModifier, PresetSchemaNode,
 };
 use crate::dashboard::components::presets::schema::PresetModifierOperation;
+use alvr_packets::AudioDeviceInfo;
 use settings_schema::ChoiceControlType;
 use std::{
     collections::{HashMap, HashSet},
@@ -142,7 +143,98 @@ pub fn encoder_preset_schema() -> PresetSchemaNode {
     })
 }
 
-pub fn game_audio_schema(devices: Vec<String>) -> PresetSchemaNode {
+// The stored device is listed as missing if no device matches it, so that it stays selected
+fn missing_device_option(name: &str) -> String {
+    format!("⚠ Missing: {name}")
+}
+
+// Variants of CustomAudioDeviceConfig the presets can select
+pub enum AudioDeviceSelection {
+    NameSubstring(String),
+    Id { id: String, name: String },
+}
+
+impl AudioDeviceSelection {
+    pub fn name(&self) -> &str {
+        match self {
+            AudioDeviceSelection::NameSubstring(name) | AudioDeviceSelection::Id { name, .. } => {
+                name
+            }
+        }
+    }
+
+    fn modifiers(&self, config_path: &str) -> Vec<PresetModifier> {
+        match self {
+            AudioDeviceSelection::NameSubstring(name) => vec![
+                string_modifier(&format!("{config_path}.variant"), "NameSubstring"),
+                string_modifier(&format!("{config_path}.NameSubstring"), name),
+            ],
+            AudioDeviceSelection::Id { id, name } => vec![
+                string_modifier(&format!("{config_path}.variant"), "Id"),
+                string_modifier(&format!("{config_path}.Id.id"), id),
+                string_modifier(&format!("{config_path}.Id.name"), name),
+            ],
+        }
+    }
+}
+
+// A selection that does not correspond to any listed device, added as an extra entry
+pub struct StoredAudioDevice {
+    pub selection: AudioDeviceSelection,
+    // No device matches the selection, the streamer will fail to open it
+    pub missing: bool,
+}
+
+// Listed devices are selected by ID. Devices with the same name are numbered, in the order of the
+// list
+fn audio_device_entries(
+    devices: &[AudioDeviceInfo],
+    stored_device: Option<StoredAudioDevice>,
+) -> Vec<(String, AudioDeviceSelection)> {
+    let mut name_counts = HashMap::<&str, usize>::new();
+    for device in devices {
+        *name_counts.entry(&device.name).or_default() += 1;
+    }
+
+    let mut occurrences = HashMap::<&str, usize>::new();
+    let mut entries = devices
+        .iter()
+        .map(|device| {
+            let display_name = if name_counts[device.name.as_str()] > 1 {
+                let occurrence = occurrences.entry(&device.name).or_default();
+                *occurrence += 1;
+
+                format!("{} ({occurrence})", device.name)
+            } else {
+                device.name.clone()
+            };
+
+            (
+                display_name,
+                AudioDeviceSelection::Id {
+                    id: device.id.clone(),
+                    name: device.name.clone(),
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    if let Some(stored) = stored_device {
+        let display_name = if stored.missing {
+            missing_device_option(stored.selection.name())
+        } else {
+            stored.selection.name().to_owned()
+        };
+        entries.push((display_name, stored.selection));
+    }
+
+    entries
+}
+
+pub fn game_audio_schema(
+    devices: &[AudioDeviceInfo],
+    stored_device: Option<StoredAudioDevice>,
+) -> PresetSchemaNode {
     let mut game_audio_options = vec![
         HigherOrderChoiceOption {
             display_name: "Disabled".into(),
@@ -165,21 +257,18 @@ pub fn game_audio_schema(devices: Vec<String>) -> PresetSchemaNode {
         },
     ];
 
-    for name in devices {
+    for (display_name, selection) in audio_device_entries(devices, stored_device) {
+        let mut modifiers = vec![
+            bool_modifier("session_settings.audio.game_audio.enabled", true),
+            bool_modifier("session_settings.audio.game_audio.content.device.set", true),
+        ];
+        modifiers.extend(
+            selection.modifiers("session_settings.audio.game_audio.content.device.content"),
+        );
+
         game_audio_options.push(HigherOrderChoiceOption {
-            display_name: name.clone(),
-            modifiers: vec![
-                bool_modifier("session_settings.audio.game_audio.enabled", true),
-                bool_modifier("session_settings.audio.game_audio.content.device.set", true),
-                string_modifier(
-                    "session_settings.audio.game_audio.content.device.content.variant",
-                    "NameSubstring",
-                ),
-                string_modifier(
-                    "session_settings.audio.game_audio.content.device.content.NameSubstring",
-                    &name,
-                ),
-            ],
+            display_name,
+            modifiers,
             content: None,
         })
     }
@@ -199,7 +288,10 @@ pub fn game_audio_schema(devices: Vec<String>) -> PresetSchemaNode {
     })
 }
 
-pub fn microphone_schema(devices: Vec<String>) -> PresetSchemaNode {
+pub fn microphone_schema(
+    devices: &[AudioDeviceInfo],
+    stored_device: Option<StoredAudioDevice>,
+) -> PresetSchemaNode {
     let mut microhone_options = vec![HigherOrderChoiceOption {
         display_name: "Disabled".to_owned(),
         modifiers: vec![bool_modifier(
@@ -231,15 +323,16 @@ pub fn microphone_schema(devices: Vec<String>) -> PresetSchemaNode {
         }
     } else {
         const PREFIX: &str = "session_settings.audio.microphone.content.devices";
-        for name in devices {
+        for (display_name, selection) in audio_device_entries(devices, stored_device) {
+            let mut modifiers = vec![
+                bool_modifier("session_settings.audio.microphone.enabled", true),
+                string_modifier(&format!("{PREFIX}.variant"), "Custom"),
+            ];
+            modifiers.extend(selection.modifiers(&format!("{PREFIX}.Custom.sink")));
+
             microhone_options.push(HigherOrderChoiceOption {
-                display_name: name.clone(),
-                modifiers: vec![
-                    bool_modifier("session_settings.audio.microphone.enabled", true),
-                    string_modifier(&format!("{PREFIX}.variant"), "Custom"),
-                    string_modifier(&format!("{PREFIX}.Custom.sink.variant"), "NameSubstring"),
-                    string_modifier(&format!("{PREFIX}.Custom.sink.NameSubstring"), &name),
-                ],
+                display_name,
+                modifiers,
                 content: None,
             })
         }
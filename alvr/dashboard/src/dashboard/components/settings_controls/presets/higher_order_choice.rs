@@ -2,10 +2,7 @@ use std::collections::{HashMap, HashSet};
 
 use super::schema::{HigherOrderChoiceSchema, PresetModifierOperation};
 use crate::dashboard::components::{self, NestingInfo, SettingControl};
-use alvr_gui_common::theme::{
-    log_colors::{INFO_LIGHT, WARNING_LIGHT},
-    OK_GREEN,
-};
+use alvr_gui_common::theme;
 use alvr_packets::{PathSegment, PathValuePair};
 use eframe::egui::{self, popup, Ui};
 use serde_json as json;
@@ -142,11 +139,15 @@ impl Control {
             ui.label(&self.name);
 
             if let Some(string) = &self.help {
-                if ui.colored_label(INFO_LIGHT, "❓").hovered() {
+                if ui.colored_label(theme::palette().log.info, "❓").hovered() {
                     popup::show_tooltip_text(ui.ctx(), egui::Id::new(POPUP_ID), string);
                 }
             }
-            if self.steamvr_restart_flag && ui.colored_label(WARNING_LIGHT, "⚠").hovered() {
+            if self.steamvr_restart_flag
+                && ui
+                    .colored_label(theme::palette().log.warning, "⚠")
+                    .hovered()
+            {
                 popup::show_tooltip_text(
                     ui.ctx(),
                     egui::Id::new(POPUP_ID),
@@ -158,7 +159,8 @@ impl Control {
             }
 
             // The emoji is blue but it will be green in the UI
-            if self.real_time_flag && ui.colored_label(OK_GREEN, "🔵").hovered() {
+            if self.real_time_flag && ui.colored_label(theme::palette().ok_green, "🔵").hovered()
+            {
                 popup::show_tooltip_text(
                     ui.ctx(),
                     egui::Id::new(POPUP_ID),
@@ -3,6 +3,7 @@ mod mirror;
 
 pub mod builtin_schema;
 pub mod schema;
+pub mod settings_presets;
 
 use self::schema::PresetSchemaNode;
 use alvr_packets::PathValuePair;
@@ -0,0 +1,316 @@
+use crate::dashboard::{
+    components::{self, collect_leaves, get_value, set_value},
+    localization::{tr, tr_args, tr_count},
+};
+use alvr_gui_common::theme;
+use alvr_packets::{PathSegment, PathValuePair};
+use eframe::{
+    egui::{Align2, Button, Grid, RichText, ScrollArea, TextEdit, Ui, Window},
+    epaint::Color32,
+};
+use serde::{Deserialize, Serialize};
+use serde_json as json;
+
+// A named set of values applied on top of the default settings. Paths are session-style, like the
+// ones sent to the server
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SettingsPreset {
+    pub name: String,
+    pub overrides: Vec<PathValuePair>,
+}
+
+struct ValueChange {
+    path: Vec<PathSegment>,
+    old_value: json::Value,
+    new_value: json::Value,
+}
+
+struct PendingPreset {
+    name: String,
+    changes: Vec<ValueChange>,
+}
+
+fn value_pair(path: &str, value: json::Value) -> PathValuePair {
+    PathValuePair {
+        path: alvr_packets::parse_path(path),
+        value,
+    }
+}
+
+fn resolution_overrides(width: u64) -> Vec<PathValuePair> {
+    [
+        "transcoding_view_resolution",
+        "emulated_headset_view_resolution",
+    ]
+    .into_iter()
+    .flat_map(|field| {
+        let path = format!("session_settings.video.{field}");
+        [
+            value_pair(&format!("{path}.variant"), "Absolute".into()),
+            value_pair(&format!("{path}.Absolute.width"), width.into()),
+            value_pair(&format!("{path}.Absolute.height.set"), false.into()),
+        ]
+    })
+    .collect()
+}
+
+fn encoder_overrides(nvenc_preset: &str, amf_preset: &str) -> Vec<PathValuePair> {
+    vec![
+        value_pair(
+            "session_settings.video.encoder_config.nvenc.quality_preset.variant",
+            nvenc_preset.into(),
+        ),
+        value_pair(
+            "session_settings.video.encoder_config.amf.quality_preset.variant",
+            amf_preset.into(),
+        ),
+    ]
+}
+
+fn builtin_presets() -> Vec<SettingsPreset> {
+    [
+        ("Low latency", 1856, "P1", "Speed", true),
+        ("Balanced", 2144, "P3", "Balanced", true),
+        ("High quality", 2592, "P5", "Quality", false),
+    ]
+    .into_iter()
+    .map(
+        |(name, width, nvenc_preset, amf_preset, foveated_encoding)| SettingsPreset {
+            name: name.into(),
+            overrides: resolution_overrides(width)
+                .into_iter()
+                .chain(encoder_overrides(nvenc_preset, amf_preset))
+                .chain([value_pair(
+                    "session_settings.video.foveated_encoding.enabled",
+                    foveated_encoding.into(),
+                )])
+                .collect(),
+        },
+    )
+    .collect()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn user_presets_path() -> std::path::PathBuf {
+    alvr_filesystem::filesystem_layout_from_dashboard_exe(&std::env::current_exe().unwrap())
+        .settings_presets()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_user_presets() -> Vec<SettingsPreset> {
+    std::fs::read_to_string(user_presets_path())
+        .ok()
+        .and_then(|text| json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_user_presets() -> Vec<SettingsPreset> {
+    vec![]
+}
+
+pub struct SettingsPresetsControl {
+    builtin_presets: Vec<SettingsPreset>,
+    user_presets: Vec<SettingsPreset>,
+    session_settings_json: Option<json::Value>,
+    matching_preset: Option<String>,
+    new_preset_name: String,
+    pending_preset: Option<PendingPreset>,
+}
+
+impl SettingsPresetsControl {
+    pub fn new() -> Self {
+        Self {
+            builtin_presets: builtin_presets(),
+            user_presets: load_user_presets(),
+            session_settings_json: None,
+            matching_preset: None,
+            new_preset_name: String::new(),
+            pending_preset: None,
+        }
+    }
+
+    fn changes(&self, preset: &SettingsPreset) -> Vec<ValueChange> {
+        let Some(session_settings_json) = &self.session_settings_json else {
+            return vec![];
+        };
+
+        let mut target_json = components::default_settings_json().clone();
+        for pair in &preset.overrides {
+            set_value(&mut target_json, &pair.path, pair.value.clone());
+        }
+
+        let mut leaves = vec![];
+        collect_leaves(&target_json, vec!["session_settings".into()], &mut leaves);
+
+        leaves
+            .into_iter()
+            .filter_map(|pair| {
+                let old_value = get_value(session_settings_json, &pair.path)?;
+
+                (*old_value != pair.value).then(|| ValueChange {
+                    path: pair.path,
+                    old_value: old_value.clone(),
+                    new_value: pair.value,
+                })
+            })
+            .collect()
+    }
+
+    fn update_matching_preset(&mut self) {
+        self.matching_preset = self
+            .builtin_presets
+            .iter()
+            .chain(&self.user_presets)
+            .find(|preset| self.changes(preset).is_empty())
+            .map(|preset| preset.name.clone());
+    }
+
+    pub fn update_session_settings(&mut self, session_settings_json: &json::Value) {
+        self.session_settings_json = Some(session_settings_json.clone());
+        self.update_matching_preset();
+    }
+
+    // Stores the values that differ from the defaults. On the web dashboard, the preset is kept
+    // only until the page is closed
+    fn save_user_preset(&mut self, name: String) {
+        let Some(session_settings_json) = &self.session_settings_json else {
+            return;
+        };
+
+        let mut leaves = vec![];
+        collect_leaves(
+            session_settings_json,
+            vec!["session_settings".into()],
+            &mut leaves,
+        );
+        let overrides = leaves
+            .into_iter()
+            .filter(|pair| {
+                get_value(components::default_settings_json(), &pair.path) != Some(&pair.value)
+            })
+            .collect();
+
+        self.user_presets.retain(|preset| preset.name != name);
+        self.user_presets.push(SettingsPreset { name, overrides });
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Err(e) = std::fs::write(
+            user_presets_path(),
+            json::to_string_pretty(&self.user_presets).unwrap(),
+        ) {
+            alvr_common::error!("Failed to save the settings presets: {e}");
+        }
+
+        self.update_matching_preset();
+    }
+
+    pub fn ui(&mut self, ui: &mut Ui) -> Vec<PathValuePair> {
+        let mut response = vec![];
+
+        let mut selected_preset = None;
+        ui.horizontal_wrapped(|ui| {
+            for preset in self.builtin_presets.iter().chain(&self.user_presets) {
+                let matching = self.matching_preset.as_ref() == Some(&preset.name);
+                if ui.selectable_label(matching, &preset.name).clicked() {
+                    selected_preset = Some(preset.clone());
+                }
+            }
+        });
+        if let Some(preset) = selected_preset {
+            self.pending_preset = Some(PendingPreset {
+                changes: self.changes(&preset),
+                name: preset.name,
+            });
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.new_preset_name).hint_text(tr("presets.name_hint")),
+            );
+
+            let name = self.new_preset_name.trim().to_owned();
+            let is_builtin = self
+                .builtin_presets
+                .iter()
+                .any(|preset| preset.name == name);
+            if ui
+                .add_enabled(
+                    !name.is_empty() && !is_builtin,
+                    Button::new(tr("presets.save")),
+                )
+                .clicked()
+            {
+                self.save_user_preset(name);
+                self.new_preset_name.clear();
+            }
+        });
+
+        if let Some(pending) = self.pending_preset.take() {
+            let mut keep_open = true;
+
+            Window::new(tr_args("presets.apply_title", &[("name", &pending.name)]))
+                .anchor(Align2::CENTER_CENTER, (0.0, 0.0))
+                .resizable(false)
+                .collapsible(false)
+                .show(ui.ctx(), |ui| {
+                    if pending.changes.is_empty() {
+                        ui.label(tr("presets.already_applied"));
+                    } else {
+                        ui.label(tr_count("presets.changes_count", pending.changes.len()));
+                        ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                            Grid::new("preset_changes")
+                                .num_columns(3)
+                                .striped(true)
+                                .show(ui, |ui| {
+                                    for change in &pending.changes {
+                                        // Skip the "session_settings" prefix
+                                        ui.label(alvr_packets::path_to_string(&change.path[1..]));
+                                        ui.colored_label(
+                                            theme::palette().log.error,
+                                            RichText::new(change.old_value.to_string())
+                                                .strikethrough(),
+                                        );
+                                        ui.colored_label(
+                                            Color32::LIGHT_GREEN,
+                                            change.new_value.to_string(),
+                                        );
+                                        ui.end_row();
+                                    }
+                                });
+                        });
+                    }
+
+                    ui.columns(2, |ui| {
+                        if ui[0].button(tr("button.cancel")).clicked() {
+                            keep_open = false;
+                        }
+
+                        if ui[1]
+                            .add_enabled(
+                                !pending.changes.is_empty(),
+                                Button::new(tr("button.apply")),
+                            )
+                            .clicked()
+                        {
+                            response = pending
+                                .changes
+                                .iter()
+                                .map(|change| PathValuePair {
+                                    path: change.path.clone(),
+                                    value: change.new_value.clone(),
+                                })
+                                .collect();
+                            keep_open = false;
+                        }
+                    });
+                });
+
+            if keep_open {
+                self.pending_preset = Some(pending);
+            }
+        }
+
+        response
+    }
+}
@@ -1,29 +1,43 @@
 use super::{collapsible, NestingInfo, SettingControl, INDENTATION_STEP};
-use crate::dashboard::DisplayString;
-use alvr_gui_common::theme::{
-    log_colors::{INFO_LIGHT, WARNING_LIGHT},
-    OK_GREEN,
+use crate::dashboard::{
+    localization::{self, tr},
+    DisplayString,
 };
-use alvr_packets::PathValuePair;
+use alvr_gui_common::theme;
+use alvr_packets::{PathSegment, PathValuePair, SettingValidationError};
 use alvr_session::settings_schema::{SchemaEntry, SchemaNode};
-use eframe::egui::{self, popup, Ui};
+use eframe::egui::{self, popup, FontSelection, Label, Sense, Ui};
 use serde_json as json;
 
 const POPUP_ID: &str = "setpopup";
 
 struct Entry {
     id: DisplayString,
+    path: Vec<PathSegment>,
     help: Option<String>,
     // notice: Option<String>,
     steamvr_restart_flag: bool,
     real_time_flag: bool,
+    advanced_flag: bool,
     control: SettingControl,
+    shown_by_search: bool,
+    shown_by_view: bool,
+    modified: bool,
+    invalid_message: Option<String>,
+}
+
+impl Entry {
+    // The basic view is ignored while searching
+    fn shown(&self, searching: bool) -> bool {
+        self.shown_by_search && (searching || self.shown_by_view)
+    }
 }
 
 pub struct Control {
     nesting_info: NestingInfo,
     entries: Vec<Entry>,
     gui_collapsible: bool,
+    search_query: String,
 }
 
 impl Control {
@@ -38,22 +52,32 @@ impl Control {
             .into_iter()
             .map(|entry| {
                 let id = entry.name;
-                let display = super::get_display_name(&id, &entry.strings);
-                let help = entry.strings.get("help").cloned();
-                // let notice = entry.strings.get("notice").cloned();
-                let steamvr_restart_flag = entry.flags.contains("steamvr-restart");
-                let real_time_flag = entry.flags.contains("real-time");
 
                 let mut nesting_info = nesting_info.clone();
                 nesting_info.path.push(id.clone().into());
 
+                let display = localization::schema_string(&nesting_info.path, "display_name")
+                    .unwrap_or_else(|| super::get_display_name(&id, &entry.strings));
+                let help = localization::schema_string(&nesting_info.path, "help")
+                    .or_else(|| entry.strings.get("help").cloned());
+                // let notice = entry.strings.get("notice").cloned();
+                let steamvr_restart_flag = entry.flags.contains("steamvr-restart");
+                let real_time_flag = entry.flags.contains("real-time");
+                let advanced_flag = entry.flags.contains("advanced");
+
                 Entry {
                     id: DisplayString { id, display },
+                    path: nesting_info.path.clone(),
                     help,
                     // notice,
                     steamvr_restart_flag,
                     real_time_flag,
+                    advanced_flag,
                     control: SettingControl::new(nesting_info, entry.content),
+                    shown_by_search: true,
+                    shown_by_view: true,
+                    modified: false,
+                    invalid_message: None,
                 }
             })
             .collect();
@@ -62,6 +86,60 @@ impl Control {
             nesting_info,
             entries,
             gui_collapsible,
+            search_query: String::new(),
+        }
+    }
+
+    // The content of an entry that matches is shown in full
+    pub fn update_search(&mut self, query_lowercase: &str) -> bool {
+        let mut any_match = false;
+        for entry in &mut self.entries {
+            let entry_match = super::search_matches(&entry.id.id, query_lowercase)
+                || super::search_matches(&entry.id.display, query_lowercase)
+                || entry
+                    .help
+                    .as_ref()
+                    .is_some_and(|help| super::search_matches(help, query_lowercase));
+
+            let content_match =
+                entry
+                    .control
+                    .update_search(if entry_match { "" } else { query_lowercase });
+
+            entry.shown_by_search = query_lowercase.is_empty() || entry_match || content_match;
+            any_match |= entry_match || content_match;
+        }
+
+        self.search_query = query_lowercase.to_owned();
+
+        any_match
+    }
+
+    pub fn update_view(&mut self, show_advanced: bool) -> bool {
+        let mut any_shown = false;
+        for entry in &mut self.entries {
+            let content_shown = entry.control.update_view(show_advanced);
+
+            entry.shown_by_view = show_advanced || (!entry.advanced_flag && content_shown);
+            any_shown |= entry.shown_by_view;
+        }
+
+        any_shown
+    }
+
+    pub fn update_modified(&mut self, modified_paths: &[Vec<PathSegment>]) {
+        for entry in &mut self.entries {
+            entry.modified = modified_paths
+                .iter()
+                .any(|path| path.starts_with(&entry.path));
+            entry.control.update_modified(modified_paths);
+        }
+    }
+
+    pub fn update_invalid(&mut self, errors: &[SettingValidationError]) {
+        for entry in &mut self.entries {
+            entry.invalid_message = super::invalid_message(errors, &entry.path);
+            entry.control.update_invalid(errors);
         }
     }
 
@@ -71,11 +149,17 @@ impl Control {
         session_fragment: &mut json::Value,
         allow_inline: bool,
     ) -> Option<PathValuePair> {
-        let entries_count = self.entries.len();
+        let searching = !self.search_query.is_empty();
+        let entries_count = self
+            .entries
+            .iter()
+            .filter(|entry| entry.shown(searching))
+            .count();
 
         let mut request = None;
 
-        let collapsed = if self.gui_collapsible {
+        // While searching, the section is shown expanded without changing the stored state
+        let collapsed = if self.gui_collapsible && !searching {
             super::grid_flow_inline(ui, allow_inline);
 
             let collapsed = collapsible::collapsible_button(
@@ -99,37 +183,61 @@ impl Control {
         };
 
         if !collapsed {
-            for (i, entry) in self.entries.iter_mut().enumerate() {
+            let shown_entries = self
+                .entries
+                .iter_mut()
+                .filter(|entry| entry.shown(searching));
+            for (i, entry) in shown_entries.enumerate() {
                 ui.horizontal(|ui| {
                     ui.add_space(INDENTATION_STEP * self.nesting_info.indentation_level as f32);
-                    let label_res = ui.label(&entry.id.display);
+                    let label_res = ui.add(
+                        Label::new(super::highlighted_text(
+                            &entry.id.display,
+                            &self.search_query,
+                            FontSelection::Default.resolve(ui.style()),
+                            ui.visuals().text_color(),
+                        ))
+                        .sense(Sense::click()),
+                    );
+                    request = super::modified_marker_ui(
+                        ui,
+                        &label_res,
+                        &entry.path,
+                        entry.modified,
+                        matches!(entry.control, SettingControl::Section(_)),
+                        &session_fragment[&entry.id.id],
+                    )
+                    .or(request);
+                    super::invalid_marker_ui(ui, entry.invalid_message.as_deref());
                     if cfg!(debug_assertions) {
                         label_res.on_hover_text(&*entry.id);
                     }
 
                     if let Some(string) = &entry.help {
-                        if ui.colored_label(INFO_LIGHT, "❓").hovered() {
+                        if ui.colored_label(theme::palette().log.info, "❓").hovered() {
                             popup::show_tooltip_text(ui.ctx(), egui::Id::new(POPUP_ID), string);
                         }
                     }
-                    if entry.steamvr_restart_flag && ui.colored_label(WARNING_LIGHT, "⚠").hovered()
+                    if entry.steamvr_restart_flag
+                        && ui
+                            .colored_label(theme::palette().log.warning, "⚠")
+                            .hovered()
                     {
                         popup::show_tooltip_text(
                             ui.ctx(),
                             egui::Id::new(POPUP_ID),
-                            format!(
-                                "Changing this setting will make SteamVR restart!\n{}",
-                                "Please save your in-game progress first"
-                            ),
+                            tr("settings_tab.steamvr_restart_tooltip"),
                         );
                     }
 
                     // The emoji is blue but it will be green in the UI
-                    if entry.real_time_flag && ui.colored_label(OK_GREEN, "🔵").hovered() {
+                    if entry.real_time_flag
+                        && ui.colored_label(theme::palette().ok_green, "🔵").hovered()
+                    {
                         popup::show_tooltip_text(
                             ui.ctx(),
                             egui::Id::new(POPUP_ID),
-                            "This setting can be changed in real-time during streaming!",
+                            tr("settings_tab.real_time_tooltip"),
                         );
                     }
                 });
@@ -1,6 +1,6 @@
 use super::{reset, NestingInfo, SettingControl};
 use crate::dashboard::basic_components;
-use alvr_packets::PathValuePair;
+use alvr_packets::{PathSegment, PathValuePair, SettingValidationError};
 use alvr_session::settings_schema::SchemaNode;
 use eframe::{
     egui::{Layout, Ui},
@@ -12,6 +12,8 @@ pub struct Control {
     nesting_info: NestingInfo,
     default_enabled: bool,
     default_string: String,
+    // Numeric content is shown beside the switch and disabled together with it
+    inline_content: bool,
     content_control: Box<SettingControl>,
 }
 
@@ -27,6 +29,8 @@ impl Control {
             "OFF".into()
         };
 
+        let inline_content = matches!(schema_content, SchemaNode::Number { .. });
+
         let control = {
             let mut nesting_info = nesting_info.clone();
             nesting_info.path.push("content".into());
@@ -38,10 +42,30 @@ impl Control {
             nesting_info,
             default_enabled,
             default_string,
+            inline_content,
             content_control: Box::new(control),
         }
     }
 
+    pub fn update_search(&mut self, query_lowercase: &str) -> bool {
+        self.content_control.update_search(query_lowercase)
+    }
+
+    // The toggle is always shown
+    pub fn update_view(&mut self, show_advanced: bool) -> bool {
+        self.content_control.update_view(show_advanced);
+
+        true
+    }
+
+    pub fn update_modified(&mut self, modified_paths: &[Vec<PathSegment>]) {
+        self.content_control.update_modified(modified_paths)
+    }
+
+    pub fn update_invalid(&mut self, errors: &[SettingValidationError]) {
+        self.content_control.update_invalid(errors)
+    }
+
     pub fn ui(
         &mut self,
         ui: &mut Ui,
@@ -52,7 +76,7 @@ impl Control {
 
         let session_switch_mut = session_fragment.as_object_mut().unwrap();
 
-        let json::Value::Bool(enabled_mut) = &mut session_switch_mut["enabled"] else {
+        let json::Value::Bool(mut enabled) = session_switch_mut["enabled"] else {
             unreachable!()
         };
 
@@ -63,22 +87,30 @@ impl Control {
         }
 
         ui.with_layout(Layout::left_to_right(Align::Center), |ui| {
-            if basic_components::switch(ui, enabled_mut).clicked() {
-                request = get_request(&self.nesting_info, *enabled_mut);
+            if basic_components::switch(ui, &mut enabled).clicked() {
+                request = get_request(&self.nesting_info, enabled);
             }
 
-            if reset::reset_button(
-                ui,
-                *enabled_mut != self.default_enabled,
-                &self.default_string,
-            )
-            .clicked()
+            if reset::reset_button(ui, enabled != self.default_enabled, &self.default_string)
+                .clicked()
             {
                 request = get_request(&self.nesting_info, self.default_enabled);
             }
+
+            if self.inline_content {
+                request = ui
+                    .add_enabled_ui(enabled, |ui| {
+                        self.content_control
+                            .ui(ui, &mut session_switch_mut["content"], true)
+                    })
+                    .inner
+                    .or(request.take());
+            }
         });
 
-        if *enabled_mut {
+        session_switch_mut["enabled"] = json::Value::Bool(enabled);
+
+        if enabled && !self.inline_content {
             ui.end_row();
 
             request = self
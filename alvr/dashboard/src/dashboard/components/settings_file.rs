@@ -0,0 +1,303 @@
+use super::{collect_leaves, get_value, set_value};
+use crate::dashboard::localization::{tr, tr_args, tr_count};
+use alvr_common::{
+    anyhow::{anyhow, Result},
+    error, ALVR_VERSION,
+};
+use alvr_gui_common::theme;
+use alvr_packets::{PathSegment, PathValuePair};
+use alvr_session::{SessionSettings, SESSION_SCHEMA_VERSION};
+use eframe::egui::{Align2, Button, Grid, RichText, ScrollArea, Ui, Window};
+use serde_json as json;
+use std::path::Path;
+
+// These values only make sense on the machine they were set on. They are reset to the defaults on
+// export and left untouched on import
+const MACHINE_SPECIFIC_PATHS: &[&str] = &[
+    "session_settings.video.adapter_index",
+    "session_settings.audio.game_audio.content.device",
+    "session_settings.audio.microphone.content.devices",
+    "session_settings.connection.bind_interface",
+    "session_settings.connection.on_connect_script",
+    "session_settings.connection.on_disconnect_script",
+    "session_settings.capture.capture_frame_dir",
+    "session_settings.dashboard",
+    "session_settings.open_setup_wizard",
+];
+
+fn replace_machine_specific_values(target_json: &mut json::Value, source_json: &json::Value) {
+    for path in MACHINE_SPECIFIC_PATHS {
+        let path = alvr_packets::parse_path(path);
+        if let Some(value) = get_value(source_json, &path) {
+            set_value(target_json, &path, value.clone());
+        }
+    }
+}
+
+// Integers can be stored as floats by other tools, they are converted by the extrapolation
+fn same_value(a: &json::Value, b: &json::Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+struct ValueChange {
+    path: Vec<PathSegment>,
+    old_value: json::Value,
+    new_value: json::Value,
+}
+
+struct PendingImport {
+    file_version: String,
+    changes: Vec<ValueChange>,
+    dropped_paths: Vec<Vec<PathSegment>>,
+}
+
+fn export(session_settings_json: &json::Value) {
+    let Some(path) = rfd::FileDialog::new()
+        .set_file_name("alvr_settings.json")
+        .add_filter("JSON", &["json"])
+        .save_file()
+    else {
+        return;
+    };
+
+    let mut exported_json = session_settings_json.clone();
+    replace_machine_specific_values(&mut exported_json, super::default_settings_json());
+
+    let content = json::json!({
+        "alvr_version": ALVR_VERSION.to_string(),
+        "schema_version": SESSION_SCHEMA_VERSION,
+        "session_settings": exported_json,
+    });
+
+    if let Err(e) = std::fs::write(&path, json::to_string_pretty(&content).unwrap()) {
+        error!("Failed to export settings to {}: {e}", path.display());
+    }
+}
+
+// Files from other versions go through the same migrations and extrapolation used when loading
+// the session. The imported entries that did not survive them are reported as dropped
+fn load_import(path: &Path, session_settings_json: &json::Value) -> Result<PendingImport> {
+    let file_json = json::from_str::<json::Value>(&std::fs::read_to_string(path)?)?;
+    let imported_json = file_json
+        .get("session_settings")
+        .cloned()
+        .ok_or_else(|| anyhow!("Not an ALVR settings file"))?;
+    let file_version = file_json["alvr_version"]
+        .as_str()
+        .unwrap_or_default()
+        .to_owned();
+    // Like sessions, files without a schema version come from before the migrations
+    let file_schema_version = file_json["schema_version"]
+        .as_u64()
+        .and_then(|version| u32::try_from(version).ok())
+        .unwrap_or(0);
+
+    let (mut imported_json, _) =
+        alvr_session::migrate_session_settings_json(imported_json, file_schema_version);
+    replace_machine_specific_values(&mut imported_json, session_settings_json);
+
+    let current_settings = json::from_value::<SessionSettings>(session_settings_json.clone())?;
+    let new_settings_json = json::to_value(alvr_session::extrapolate_session_settings(
+        &current_settings,
+        &imported_json,
+    )?)?;
+
+    let mut imported_leaves = vec![];
+    collect_leaves(
+        &imported_json,
+        vec!["session_settings".into()],
+        &mut imported_leaves,
+    );
+    let dropped_paths = imported_leaves
+        .into_iter()
+        .filter(|pair| {
+            !get_value(&new_settings_json, &pair.path)
+                .map(|value| same_value(value, &pair.value))
+                .unwrap_or(false)
+        })
+        .map(|pair| pair.path)
+        .collect();
+
+    let mut new_leaves = vec![];
+    collect_leaves(
+        &new_settings_json,
+        vec!["session_settings".into()],
+        &mut new_leaves,
+    );
+    let changes = new_leaves
+        .into_iter()
+        .filter_map(|pair| {
+            let old_value = get_value(session_settings_json, &pair.path)?;
+
+            (*old_value != pair.value).then(|| ValueChange {
+                path: pair.path,
+                old_value: old_value.clone(),
+                new_value: pair.value,
+            })
+        })
+        .collect();
+
+    Ok(PendingImport {
+        file_version,
+        changes,
+        dropped_paths,
+    })
+}
+
+pub struct SettingsFileControl {
+    session_settings_json: Option<json::Value>,
+    pending_import: Option<PendingImport>,
+}
+
+impl SettingsFileControl {
+    pub fn new() -> Self {
+        Self {
+            session_settings_json: None,
+            pending_import: None,
+        }
+    }
+
+    pub fn update_session_settings(&mut self, session_settings_json: &json::Value) {
+        self.session_settings_json = Some(session_settings_json.clone());
+    }
+
+    fn import(&mut self) {
+        let Some(session_settings_json) = &self.session_settings_json else {
+            return;
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+
+        match load_import(&path, session_settings_json) {
+            Ok(pending) => self.pending_import = Some(pending),
+            Err(e) => error!("Failed to import settings from {}: {e}", path.display()),
+        }
+    }
+
+    // The changes are applied like any other edit, so that the SteamVR restart prompt is shown if
+    // needed
+    pub fn ui(&mut self, ui: &mut Ui) -> Vec<PathValuePair> {
+        let mut response = vec![];
+
+        let loaded = self.session_settings_json.is_some();
+        if ui
+            .add_enabled(loaded, Button::new(tr("settings_file.export")))
+            .on_hover_text(tr("settings_file.export_hint"))
+            .clicked()
+        {
+            if let Some(json) = &self.session_settings_json {
+                export(json);
+            }
+        }
+        if ui
+            .add_enabled(loaded, Button::new(tr("settings_file.import")))
+            .clicked()
+        {
+            self.import();
+        }
+
+        if let Some(pending) = self.pending_import.take() {
+            let mut keep_open = true;
+
+            Window::new(tr("settings_file.import_title"))
+                .anchor(Align2::CENTER_CENTER, (0.0, 0.0))
+                .resizable(false)
+                .collapsible(false)
+                .show(ui.ctx(), |ui| {
+                    if pending.file_version != ALVR_VERSION.to_string() {
+                        ui.label(tr_args(
+                            "settings_file.other_version",
+                            &[("version", &pending.file_version)],
+                        ));
+                    }
+
+                    if pending.changes.is_empty() {
+                        ui.label(tr("settings_file.no_changes"));
+                    } else {
+                        ui.label(tr_count(
+                            "settings_file.changes_count",
+                            pending.changes.len(),
+                        ));
+                        ScrollArea::vertical()
+                            .id_source("import_changes_scroll")
+                            .max_height(300.0)
+                            .show(ui, |ui| {
+                                Grid::new("import_changes")
+                                    .num_columns(3)
+                                    .striped(true)
+                                    .show(ui, |ui| {
+                                        for change in &pending.changes {
+                                            // Skip the "session_settings" prefix
+                                            ui.label(alvr_packets::path_to_string(
+                                                &change.path[1..],
+                                            ));
+                                            ui.colored_label(
+                                                theme::palette().log.error,
+                                                RichText::new(change.old_value.to_string())
+                                                    .strikethrough(),
+                                            );
+                                            ui.colored_label(
+                                                theme::palette().ok_green,
+                                                change.new_value.to_string(),
+                                            );
+                                            ui.end_row();
+                                        }
+                                    });
+                            });
+                    }
+
+                    if !pending.dropped_paths.is_empty() {
+                        ui.colored_label(
+                            theme::palette().log.warning,
+                            tr_count("settings_file.dropped_count", pending.dropped_paths.len()),
+                        );
+                        ScrollArea::vertical()
+                            .id_source("import_dropped_scroll")
+                            .max_height(150.0)
+                            .show(ui, |ui| {
+                                for path in &pending.dropped_paths {
+                                    ui.label(alvr_packets::path_to_string(&path[1..]));
+                                }
+                            });
+                    }
+
+                    ui.columns(2, |ui| {
+                        if ui[0].button(tr("button.cancel")).clicked() {
+                            keep_open = false;
+                        }
+
+                        if ui[1]
+                            .add_enabled(
+                                !pending.changes.is_empty(),
+                                Button::new(tr("button.apply")),
+                            )
+                            .clicked()
+                        {
+                            response = pending
+                                .changes
+                                .iter()
+                                .map(|change| PathValuePair {
+                                    path: change.path.clone(),
+                                    value: change.new_value.clone(),
+                                })
+                                .collect();
+                            keep_open = false;
+                        }
+                    });
+                });
+
+            if keep_open {
+                self.pending_import = Some(pending);
+            }
+        }
+
+        response
+    }
+}
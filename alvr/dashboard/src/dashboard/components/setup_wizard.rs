@@ -1,4 +1,4 @@
-use crate::dashboard::basic_components;
+use crate::dashboard::{basic_components, localization::tr};
 use alvr_packets::{FirewallRulesAction, PathValuePair, ServerRequest};
 use eframe::{
     egui::{Button, Label, Layout, RichText, Ui},
@@ -77,7 +77,7 @@ impl SetupWizard {
             ui.add_space(60.0);
             ui.vertical(|ui| {
                 ui.add_space(30.0);
-                ui.heading(RichText::new("Welcome to ALVR").size(30.0));
+                ui.heading(RichText::new(tr("setup_wizard.title")).size(30.0));
                 ui.add_space(5.0);
             });
             ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
@@ -89,18 +89,13 @@ impl SetupWizard {
         });
         ui.separator();
         match &self.page {
-            Page::Welcome => page_content(
-                ui,
-                "This setup wizard will help you setup ALVR.",
-                "",
-                |_| (),
-            ),
+            Page::Welcome => page_content(ui, &tr("setup_wizard.welcome"), "", |_| ()),
             Page::ResetSettings => page_content(
                 ui,
-                "Reset settings",
-                "It is recommended to reset your settings everytime you update ALVR.",
+                &tr("setup_wizard.reset_settings"),
+                &tr("setup_wizard.reset_settings_text"),
                 |ui| {
-                    if ui.button("Reset settings").clicked() {
+                    if ui.button(tr("setup_wizard.reset_settings")).clicked() {
                         request = Some(SetupWizardRequest::ServerRequest(
                             ServerRequest::UpdateSession(Box::default()),
                         ));
@@ -109,39 +104,31 @@ impl SetupWizard {
             ),
             Page::HardwareRequirements => page_content(
                 ui,
-                "Hardware requirements",
-                r"ALVR requires a dedicated and recent graphics card.
-Make sure you have at least one output audio device.",
+                &tr("setup_wizard.hardware_requirements"),
+                &tr("setup_wizard.hardware_requirements_text"),
                 |_| (),
             ),
             Page::SoftwareRequirements => page_content(
                 ui,
-                "Software requirements",
-                if cfg!(windows) {
-                    r"To stream the headset microphone on Windows you need to install VB-Cable or Voicemeeter."
+                &tr("setup_wizard.software_requirements"),
+                &if cfg!(windows) {
+                    tr("setup_wizard.software_requirements_windows")
                 } else if cfg!(target_os = "linux") {
-                    r"To stream the headset microphone on Linux, you might be required to use pipewire and On connect/On disconnect script.
-Script is not 100% stable and might cause some instability issues with pipewire, but it should work."
+                    tr("setup_wizard.software_requirements_linux")
                 } else {
-                    r"Unsupported OS"
+                    tr("setup_wizard.unsupported_os")
                 },
                 #[allow(unused_variables)]
                 |ui| {
                     #[cfg(windows)]
-                    if ui.button("Download VB-Cable").clicked() {
+                    if ui.button(tr("setup_wizard.download_vb_cable")).clicked() {
                         ui.ctx().open_url(crate::dashboard::egui::OpenUrl::same_tab(
                             "https://vb-audio.com/Cable/",
                         ));
                     }
 
                     #[cfg(target_os = "linux")]
-                    if ui
-                        .button(format!(
-                            "Download and set 'On connect/On disconnect' script, {}",
-                            "set Pipewire audio"
-                        ))
-                        .clicked()
-                    {
+                    if ui.button(tr("setup_wizard.set_pipewire_audio")).clicked() {
                         match download_and_prepare_audio_script() {
                             Ok(audio_script_path) => {
                                 fn bool_path_value_pair(
@@ -224,11 +211,10 @@ Script is not 100% stable and might cause some instability issues with pipewire,
 
             Page::HandGestures => page_content(
                 ui,
-                "Hand Gestures",
-                r"ALVR allows you to use Hand Tracking and emulate controller buttons using it.
-By default, controller button emulation is set to prevent accidental clicks. You can re-enable gestures by disabling slider bellow.",
+                &tr("setup_wizard.hand_gestures"),
+                &tr("setup_wizard.hand_gestures_text"),
                 |ui| {
-                    ui.label("Only touch");
+                    ui.label(tr("setup_wizard.only_touch"));
                     if basic_components::switch(ui, &mut self.only_touch).changed() {
                         request = Some(SetupWizardRequest::ServerRequest(
                             ServerRequest::SetValues(vec![PathValuePair {
@@ -244,11 +230,10 @@ By default, controller button emulation is set to prevent accidental clicks. You
             ),
             Page::Firewall => page_content(
                 ui,
-                "Firewall",
-                r"To communicate with the headset, some firewall rules need to be set.
-This requires administrator rights!",
+                &tr("setup_wizard.firewall"),
+                &tr("setup_wizard.firewall_text"),
                 |ui| {
-                    if ui.button("Add firewall rules").clicked() {
+                    if ui.button(tr("setup_wizard.add_firewall_rules")).clicked() {
                         request = Some(SetupWizardRequest::ServerRequest(
                             ServerRequest::FirewallRules(FirewallRulesAction::Add),
                         ));
@@ -257,14 +242,14 @@ This requires administrator rights!",
             ),
             Page::Recommendations => page_content(
                 ui,
-                "Recommendations",
-                r"ALVR supports multiple types of PC hardware and headsets but not all might work correctly with default settings. Please try tweaking different settings like encoder, bitrate and others if your ALVR experience is great or not optimal.",
+                &tr("setup_wizard.recommendations"),
+                &tr("setup_wizard.recommendations_text"),
                 |_| (),
             ),
             Page::Finished => page_content(
                 ui,
-                "Finished",
-                r#"You can always restart this setup wizard from the "Installation" tab on the left."#,
+                &tr("setup_wizard.finished"),
+                &tr("setup_wizard.finished_text"),
                 |_| (),
             ),
         };
@@ -274,14 +259,17 @@ This requires administrator rights!",
             ui.horizontal(|ui| {
                 ui.add_space(15.0);
                 if self.page == Page::Finished {
-                    if ui.button("Finish").clicked() {
+                    if ui.button(tr("setup_wizard.finish")).clicked() {
                         request = Some(SetupWizardRequest::Close { finished: true });
                     }
-                } else if ui.button("Next").clicked() {
+                } else if ui.button(tr("setup_wizard.next")).clicked() {
                     self.page = index_to_page(self.page as usize + 1);
                 }
                 if ui
-                    .add_visible(self.page != Page::Welcome, Button::new("Back"))
+                    .add_visible(
+                        self.page != Page::Welcome,
+                        Button::new(tr("setup_wizard.back")),
+                    )
                     .clicked()
                 {
                     self.page = index_to_page(self.page as usize - 1);
@@ -1,37 +1,151 @@
-use crate::{dashboard::theme::graph_colors, dashboard::ServerRequest};
+use crate::dashboard::{
+    localization::{tr, tr_args},
+    theme::graph_colors,
+    ServerRequest,
+};
+use alvr_common::ConnectionState;
 use alvr_events::{GraphStatistics, StatisticsSummary};
 use alvr_gui_common::theme;
+use alvr_packets::PathValuePair;
+use alvr_session::{BitrateMode, FrameSize, SessionConfig, Settings};
 use eframe::{
     egui::{
-        popup, pos2, vec2, Align2, Color32, FontId, Frame, Id, Painter, Rect, RichText, Rounding,
-        ScrollArea, Shape, Stroke, Ui,
+        popup, pos2, vec2, Align2, CollapsingHeader, Color32, FontId, Frame, Grid, Id, Painter,
+        Rect, Response, RichText, Rounding, ScrollArea, Shape, Slider, Stroke, Ui,
     },
     emath::RectTransform,
     epaint::Pos2,
 };
 use statrs::statistics::{self, OrderStatistics};
-use std::{collections::VecDeque, ops::RangeInclusive};
-
-const GRAPH_HISTORY_SIZE: usize = 1000;
+use std::{collections::VecDeque, ops::RangeInclusive, time::Duration};
+
+#[cfg(target_arch = "wasm32")]
+use instant::Instant;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+// Graph statistics are sent at the rate set by statistics_event_rate. Faster rates are decimated so
+// that the longest window fits in a fixed size buffer
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(50);
+const MAX_HISTORY_DURATION: Duration = Duration::from_secs(600);
+const HISTORY_CAPACITY: usize =
+    (MAX_HISTORY_DURATION.as_millis() / SAMPLE_INTERVAL.as_millis()) as usize;
+// Longer windows are drawn skipping samples
+const MAX_DRAWN_SAMPLES: usize = 1000;
 const UPPER_QUANTILE: f64 = 0.90;
+const MAX_TUNING_MARKERS: usize = 20;
 
 fn draw_lines(painter: &Painter, points: Vec<Pos2>, color: Color32) {
     painter.add(Shape::line(points, Stroke::new(1.0, color)));
 }
 
+// A request for each value while dragging would restart the encoder or save the session every frame
+fn slider_released(response: &Response) -> bool {
+    response.drag_stopped() || (response.changed() && !response.dragged())
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum HistoryWindow {
+    Seconds30,
+    Minutes2,
+    Minutes10,
+}
+
+impl HistoryWindow {
+    fn duration(self) -> Duration {
+        match self {
+            Self::Seconds30 => Duration::from_secs(30),
+            Self::Minutes2 => Duration::from_secs(120),
+            Self::Minutes10 => MAX_HISTORY_DURATION,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Seconds30 => "30 s",
+            Self::Minutes2 => "2 min",
+            Self::Minutes10 => "10 min",
+        }
+    }
+}
+
+struct Sample {
+    timestamp: Instant,
+    statistics: GraphStatistics,
+}
+
+// A change made with the live tuning panel, drawn on the graphs
+struct TuningMarker {
+    timestamp: Instant,
+    label: String,
+}
+
+// Samples inside the window, with x coordinates in seconds relative to the end of the window
+struct VisibleHistory<'a> {
+    window_s: f32,
+    samples: Vec<(f32, &'a GraphStatistics)>,
+    bar_width_s: f32,
+    markers: Vec<(f32, &'a str)>,
+}
+
 pub struct StatisticsTab {
-    history: VecDeque<GraphStatistics>,
+    history: VecDeque<Sample>,
     last_statistics_summary: Option<StatisticsSummary>,
+    window: HistoryWindow,
+    // The view is frozen at this instant while samples keep being collected
+    paused_at: Option<Instant>,
+    streaming: bool,
+    tuning_bitrate_mbps: u64,
+    // Otherwise the changes are reverted when the stream ends
+    persistent_tuning: bool,
+    // None with an absolute resolution
+    resolution_scale: Option<f32>,
+    // None with the foveated encoding disabled
+    foveation_edge_ratio: Option<f32>,
+    tuning_markers: VecDeque<TuningMarker>,
 }
 
 impl StatisticsTab {
     pub fn new() -> Self {
         Self {
-            history: vec![GraphStatistics::default(); GRAPH_HISTORY_SIZE]
-                .into_iter()
-                .collect(),
+            history: VecDeque::with_capacity(HISTORY_CAPACITY),
             last_statistics_summary: None,
+            window: HistoryWindow::Seconds30,
+            paused_at: None,
+            streaming: false,
+            tuning_bitrate_mbps: 30,
+            persistent_tuning: false,
+            resolution_scale: None,
+            foveation_edge_ratio: None,
+            tuning_markers: VecDeque::new(),
+        }
+    }
+
+    pub fn update_session(&mut self, session: &SessionConfig, settings: &Settings) {
+        self.streaming = session
+            .client_connections
+            .values()
+            .any(|info| info.connection_state == ConnectionState::Streaming);
+
+        // While streaming the slider keeps the last value sent, which may be temporary
+        if !self.streaming {
+            self.tuning_bitrate_mbps = match settings.video.bitrate.mode {
+                BitrateMode::ConstantMbps(mbps) => mbps,
+                BitrateMode::Adaptive { .. } => {
+                    session.session_settings.video.bitrate.mode.ConstantMbps
+                }
+            };
         }
+
+        self.resolution_scale = match settings.video.transcoding_view_resolution {
+            FrameSize::Scale(scale) => Some(scale),
+            FrameSize::Absolute { .. } => None,
+        };
+        self.foveation_edge_ratio = settings
+            .video
+            .foveated_encoding
+            .as_option()
+            .map(|config| config.edge_ratio_x);
     }
 
     pub fn update_statistics(&mut self, statistics: StatisticsSummary) {
@@ -39,30 +153,264 @@ impl StatisticsTab {
     }
 
     pub fn update_graph_statistics(&mut self, statistics: GraphStatistics) {
-        self.history.pop_front();
-        self.history.push_back(statistics);
+        let now = Instant::now();
+        if self
+            .history
+            .back()
+            .is_some_and(|sample| now < sample.timestamp + SAMPLE_INTERVAL)
+        {
+            return;
+        }
+
+        if self.history.len() == HISTORY_CAPACITY {
+            self.history.pop_front();
+        }
+        self.history.push_back(Sample {
+            timestamp: now,
+            statistics,
+        });
+    }
+
+    fn visible_history(&self) -> VisibleHistory {
+        let window = self.window.duration();
+        let end = self.paused_at.unwrap_or_else(Instant::now);
+
+        let samples = self
+            .history
+            .iter()
+            .filter(|sample| sample.timestamp <= end && end - sample.timestamp <= window)
+            .collect::<Vec<_>>();
+        let step = usize::max(samples.len() / MAX_DRAWN_SAMPLES, 1);
+
+        let samples = samples
+            .into_iter()
+            .step_by(step)
+            .map(|sample| (-(end - sample.timestamp).as_secs_f32(), &sample.statistics))
+            .collect::<Vec<_>>();
+
+        // The sample spacing depends on the event rate of the server
+        let mean_spacing_s = match (samples.first(), samples.last()) {
+            (Some((first, _)), Some((last, _))) if samples.len() > 1 => {
+                (last - first) / (samples.len() - 1) as f32
+            }
+            _ => step as f32 * SAMPLE_INTERVAL.as_secs_f32(),
+        };
+
+        let markers = self
+            .tuning_markers
+            .iter()
+            .filter(|marker| marker.timestamp <= end && end - marker.timestamp <= window)
+            .map(|marker| {
+                (
+                    -(end - marker.timestamp).as_secs_f32(),
+                    marker.label.as_str(),
+                )
+            })
+            .collect();
+
+        VisibleHistory {
+            window_s: window.as_secs_f32(),
+            samples,
+            // Bars overlap a bit to avoid gaps between samples
+            bar_width_s: mean_spacing_s * 1.5,
+            markers,
+        }
+    }
+
+    fn bitrate_request(&mut self) -> ServerRequest {
+        let values = vec![
+            PathValuePair {
+                path: alvr_packets::parse_path("session_settings.video.bitrate.mode.variant"),
+                value: "ConstantMbps".into(),
+            },
+            PathValuePair {
+                path: alvr_packets::parse_path("session_settings.video.bitrate.mode.ConstantMbps"),
+                value: self.tuning_bitrate_mbps.into(),
+            },
+        ];
+
+        if self.tuning_markers.len() == MAX_TUNING_MARKERS {
+            self.tuning_markers.pop_front();
+        }
+        self.tuning_markers.push_back(TuningMarker {
+            timestamp: Instant::now(),
+            label: if self.persistent_tuning {
+                format!("{} Mbps", self.tuning_bitrate_mbps)
+            } else {
+                tr_args(
+                    "statistics.temporary_bitrate_marker",
+                    &[("mbps", &self.tuning_bitrate_mbps.to_string())],
+                )
+            },
+        });
+
+        if self.persistent_tuning {
+            ServerRequest::SetValues(values)
+        } else {
+            ServerRequest::SetTemporaryValues(values)
+        }
+    }
+
+    // Only the bitrate can be changed on the running stream, see restart_tuning_ui()
+    fn live_tuning_ui(&mut self, ui: &mut Ui) -> Option<ServerRequest> {
+        let mut request = None;
+
+        CollapsingHeader::new(tr("statistics.live_tuning")).show(ui, |ui| {
+            if !self.streaming {
+                ui.label(tr("statistics.live_tuning_unavailable"));
+            }
+
+            ui.add_enabled_ui(self.streaming, |ui| {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(
+                        &mut self.persistent_tuning,
+                        false,
+                        tr("statistics.temporary"),
+                    );
+                    ui.selectable_value(
+                        &mut self.persistent_tuning,
+                        true,
+                        tr("statistics.saved_in_session"),
+                    );
+                });
+                ui.label(if self.persistent_tuning {
+                    tr("statistics.saved_in_session_note")
+                } else {
+                    tr("statistics.temporary_note")
+                });
+
+                Grid::new("live_tuning").num_columns(3).show(ui, |ui| {
+                    ui.label(tr("statistics.constant_bitrate"));
+                    let response = ui.add(
+                        Slider::new(&mut self.tuning_bitrate_mbps, 5..=1000)
+                            .logarithmic(true)
+                            .suffix(" Mbps"),
+                    );
+                    if slider_released(&response) {
+                        request = Some(self.bitrate_request());
+                    }
+                    if let Some(stats) = &self.last_statistics_summary {
+                        ui.label(tr_args(
+                            "statistics.applied_bitrate",
+                            &[("mbps", &format!("{:.1}", stats.encoder_bitrate_mbps))],
+                        ));
+                    }
+                    ui.end_row();
+                });
+            });
+        });
+
+        request
+    }
+
+    // The encoder resolution and the foveation are set when SteamVR starts, the encoder cannot
+    // change them while streaming. A restart would revert temporary values, so they are saved
+    fn restart_tuning_ui(&mut self, ui: &mut Ui) -> Option<ServerRequest> {
+        let mut request = None;
+
+        CollapsingHeader::new(tr("statistics.restart_tuning")).show(ui, |ui| {
+            ui.label(tr("statistics.restart_tuning_note"));
+
+            Grid::new("restart_tuning").num_columns(2).show(ui, |ui| {
+                ui.label(tr("statistics.resolution_scale"));
+                if let Some(scale) = &mut self.resolution_scale {
+                    let response = ui.add(Slider::new(scale, 0.25..=2.0).step_by(0.01).suffix("x"));
+                    if slider_released(&response) {
+                        request = Some(ServerRequest::SetValues(vec![PathValuePair {
+                            path: alvr_packets::parse_path(
+                                "session_settings.video.transcoding_view_resolution.Scale",
+                            ),
+                            value: (*scale).into(),
+                        }]));
+                    }
+                } else {
+                    ui.label(tr("statistics.absolute_resolution"));
+                }
+                ui.end_row();
+
+                // The same ratio is used horizontally and vertically
+                ui.label(tr("statistics.foveation_edge_ratio"));
+                if let Some(ratio) = &mut self.foveation_edge_ratio {
+                    let response = ui.add(Slider::new(ratio, 1.0..=10.0).step_by(1.0));
+                    if slider_released(&response) {
+                        request = Some(ServerRequest::SetValues(
+                            ["edge_ratio_x", "edge_ratio_y"]
+                                .into_iter()
+                                .map(|field| PathValuePair {
+                                    path: alvr_packets::parse_path(&format!(
+                                        "session_settings.video.foveated_encoding.content.{field}"
+                                    )),
+                                    value: (*ratio).into(),
+                                })
+                                .collect(),
+                        ));
+                    }
+                } else {
+                    ui.label(tr("statistics.foveation_disabled"));
+                }
+                ui.end_row();
+            });
+
+            if ui.button(tr("button.restart_steamvr")).clicked() {
+                request = Some(ServerRequest::RestartSteamvr);
+            }
+        });
+
+        request
     }
 
     pub fn ui(&mut self, ui: &mut Ui) -> Option<ServerRequest> {
+        ui.horizontal(|ui| {
+            ui.label(tr("statistics.window"));
+            for window in [
+                HistoryWindow::Seconds30,
+                HistoryWindow::Minutes2,
+                HistoryWindow::Minutes10,
+            ] {
+                ui.selectable_value(&mut self.window, window, window.label());
+            }
+
+            ui.separator();
+
+            if self.paused_at.is_some() {
+                if ui.button(tr("statistics.resume")).clicked() {
+                    self.paused_at = None;
+                }
+            } else if ui.button(tr("statistics.pause")).clicked() {
+                self.paused_at = Some(Instant::now());
+            }
+        });
+
+        let live_request = self.live_tuning_ui(ui);
+        let request = self.restart_tuning_ui(ui).or(live_request);
+
         if let Some(stats) = &self.last_statistics_summary {
+            let history = self.visible_history();
+
             ScrollArea::new([false, true]).show(ui, |ui| {
-                let available_width = ui.available_width();
-                self.draw_latency_graph(ui, available_width);
-                self.draw_fps_graph(ui, available_width);
-                self.draw_bitrate_graph(ui, available_width);
+                if history.samples.is_empty() {
+                    ui.label(tr("statistics.no_graph_data"));
+                } else {
+                    let available_width = ui.available_width();
+                    self.draw_latency_graph(ui, available_width, &history);
+                    self.draw_fps_graph(ui, available_width, &history);
+                    self.draw_bitrate_graph(ui, available_width, &history);
+                }
                 self.draw_statistics_overview(ui, stats);
             });
         } else {
-            ui.heading("No statistics available");
+            ui.heading(tr("statistics.no_statistics"));
         }
 
-        None
+        request
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn draw_graph(
         &self,
         ui: &mut Ui,
         available_width: f32,
+        history: &VisibleHistory,
         title: &str,
         data_range: RangeInclusive<f32>,
         graph_content: impl FnOnce(&Painter, RectTransform),
@@ -79,13 +427,27 @@ impl StatisticsTab {
 
             let max = *data_range.end();
             let min = *data_range.start();
-            let data_rect = Rect::from_x_y_ranges(0.0..=GRAPH_HISTORY_SIZE as f32, max..=min);
+            let data_rect = Rect::from_x_y_ranges(-history.window_s..=0.0, max..=min);
             let to_screen = RectTransform::from_to(data_rect, canvas_rect);
 
             let painter = ui.painter().with_clip_rect(canvas_rect);
 
             graph_content(&painter, to_screen);
 
+            for &(x, label) in &history.markers {
+                painter.line_segment(
+                    [to_screen * pos2(x, max), to_screen * pos2(x, min)],
+                    Stroke::new(1.0, Color32::GRAY),
+                );
+                painter.text(
+                    to_screen * pos2(x, max),
+                    Align2::RIGHT_TOP,
+                    label,
+                    FontId::proportional(12.0),
+                    Color32::GRAY,
+                );
+            }
+
             ui.painter().text(
                 to_screen * pos2(0.0, min),
                 Align2::LEFT_BOTTOM,
@@ -107,84 +469,125 @@ impl StatisticsTab {
         if let Some(pos) = canvas_response.response.hover_pos() {
             let graph_pos =
                 RectTransform::from_to(canvas_response.response.rect, canvas_response.inner) * pos;
-            let history_index = (graph_pos.x as usize).clamp(0, GRAPH_HISTORY_SIZE - 1);
-
-            popup::show_tooltip(ui.ctx(), Id::new("popup"), |ui| {
-                tooltip_content(ui, self.history.get(history_index).unwrap())
+            let closest_sample = history.samples.iter().min_by(|(a, _), (b, _)| {
+                (a - graph_pos.x).abs().total_cmp(&(b - graph_pos.x).abs())
             });
+
+            if let Some((time_s, stats)) = closest_sample {
+                popup::show_tooltip(ui.ctx(), Id::new("popup"), |ui| {
+                    ui.label(format!("{time_s:.1} s"));
+                    tooltip_content(ui, stats)
+                });
+            }
         }
     }
 
-    fn draw_latency_graph(&self, ui: &mut Ui, available_width: f32) {
+    fn draw_latency_graph(&self, ui: &mut Ui, available_width: f32, history: &VisibleHistory) {
         let mut data = statistics::Data::new(
-            self.history
+            history
+                .samples
                 .iter()
-                .map(|stats| stats.total_pipeline_latency_s as f64)
+                .map(|(_, stats)| stats.total_pipeline_latency_ms as f64)
                 .collect::<Vec<_>>(),
         );
 
         self.draw_graph(
             ui,
             available_width,
-            "Latency",
-            0.0..=(data.quantile(UPPER_QUANTILE) * 1.2) as f32 * 1000.0,
+            history,
+            &tr("statistics.latency"),
+            0.0..=(data.quantile(UPPER_QUANTILE) * 1.2) as f32,
             |painter, to_screen_trans| {
-                for i in 0..GRAPH_HISTORY_SIZE {
-                    let stats = self.history.get(i).unwrap();
+                for &(x, stats) in &history.samples {
                     let mut offset = 0.0;
                     for (value, color) in &[
-                        (stats.game_time_s, graph_colors::RENDER_VARIANT),
-                        (stats.server_compositor_s, graph_colors::RENDER),
-                        (stats.encoder_s, graph_colors::TRANSCODE),
-                        (stats.network_s, graph_colors::NETWORK),
-                        (stats.decoder_s, graph_colors::TRANSCODE),
-                        (stats.decoder_queue_s, graph_colors::IDLE),
-                        (stats.client_compositor_s, graph_colors::RENDER),
-                        (stats.vsync_queue_s, graph_colors::IDLE),
+                        (stats.game_time_ms, graph_colors::RENDER_VARIANT),
+                        (stats.server_compositor_ms, graph_colors::RENDER),
+                        (stats.encoder_ms, graph_colors::TRANSCODE),
+                        (stats.pacing_ms, graph_colors::IDLE),
+                        (stats.network_ms, graph_colors::NETWORK),
+                        (stats.jitter_buffer_ms, graph_colors::IDLE),
+                        (stats.decoder_ms, graph_colors::TRANSCODE),
+                        (stats.decoder_queue_ms, graph_colors::IDLE),
+                        (stats.client_compositor_ms, graph_colors::RENDER),
+                        (stats.vsync_queue_ms, graph_colors::IDLE),
                     ] {
                         painter.rect_filled(
                             Rect {
-                                min: to_screen_trans * pos2(i as f32, offset + value * 1000.0),
-                                max: to_screen_trans * pos2(i as f32 + 2.0, offset),
+                                min: to_screen_trans * pos2(x, offset + value),
+                                max: to_screen_trans * pos2(x + history.bar_width_s, offset),
                             },
                             Rounding::ZERO,
                             *color,
                         );
-                        offset += value * 1000.0;
+                        offset += value;
                     }
                 }
             },
             |ui, stats| {
                 use graph_colors::*;
 
-                fn label(ui: &mut Ui, text: &str, value_s: f32, color: Color32) {
-                    ui.colored_label(color, &format!("{text}: {:.2}ms", value_s * 1000.0));
+                fn label(ui: &mut Ui, text: &str, value_ms: f32, color: Color32) {
+                    ui.colored_label(color, &format!("{text}: {value_ms:.2}ms"));
                 }
 
                 label(
                     ui,
-                    "Total latency",
-                    stats.total_pipeline_latency_s,
-                    theme::FG,
+                    &tr("statistics.total_latency"),
+                    stats.total_pipeline_latency_ms,
+                    theme::palette().fg,
+                );
+                label(
+                    ui,
+                    &tr("statistics.client_vsync"),
+                    stats.vsync_queue_ms,
+                    IDLE,
+                );
+                label(
+                    ui,
+                    &tr("statistics.client_compositor"),
+                    stats.client_compositor_ms,
+                    RENDER,
+                );
+                label(
+                    ui,
+                    &tr("statistics.decoder_queue"),
+                    stats.decoder_queue_ms,
+                    IDLE,
+                );
+                label(ui, &tr("statistics.decode"), stats.decoder_ms, TRANSCODE);
+                label(
+                    ui,
+                    &tr("statistics.jitter_buffer"),
+                    stats.jitter_buffer_ms,
+                    IDLE,
+                );
+                label(ui, &tr("statistics.network"), stats.network_ms, NETWORK);
+                label(ui, &tr("statistics.pacing"), stats.pacing_ms, IDLE);
+                label(ui, &tr("statistics.encode"), stats.encoder_ms, TRANSCODE);
+                label(
+                    ui,
+                    &tr("statistics.streamer_compositor"),
+                    stats.server_compositor_ms,
+                    RENDER,
+                );
+                label(
+                    ui,
+                    &tr("statistics.game_render"),
+                    stats.game_time_ms,
+                    RENDER_VARIANT,
                 );
-                label(ui, "Client VSync", stats.vsync_queue_s, IDLE);
-                label(ui, "Client compositor", stats.client_compositor_s, RENDER);
-                label(ui, "Decoder queue", stats.decoder_queue_s, IDLE);
-                label(ui, "Decode", stats.decoder_s, TRANSCODE);
-                label(ui, "Network", stats.network_s, NETWORK);
-                label(ui, "Encode", stats.encoder_s, TRANSCODE);
-                label(ui, "Streamer compositor", stats.server_compositor_s, RENDER);
-                label(ui, "Game render", stats.game_time_s, RENDER_VARIANT);
             },
         );
     }
 
-    fn draw_fps_graph(&self, ui: &mut Ui, available_width: f32) {
+    fn draw_fps_graph(&self, ui: &mut Ui, available_width: f32, history: &VisibleHistory) {
         let mut data = statistics::Data::new(
-            self.history
+            history
+                .samples
                 .iter()
-                .map(|stats| stats.client_fps)
-                .chain(self.history.iter().map(|stats| stats.server_fps))
+                .map(|(_, stats)| stats.client_fps)
+                .chain(history.samples.iter().map(|(_, stats)| stats.server_fps))
                 .map(|v| v as f64)
                 .collect::<Vec<_>>(),
         );
@@ -197,14 +600,17 @@ impl StatisticsTab {
         self.draw_graph(
             ui,
             available_width,
-            "Framerate",
+            history,
+            &tr("statistics.framerate"),
             min as f32..=max as f32,
             |painter, to_screen_trans| {
-                let (server_fps_points, client_fps_points) = (0..GRAPH_HISTORY_SIZE)
-                    .map(|i| {
+                let (server_fps_points, client_fps_points) = history
+                    .samples
+                    .iter()
+                    .map(|&(x, stats)| {
                         (
-                            to_screen_trans * pos2(i as f32, self.history[i].server_fps),
-                            to_screen_trans * pos2(i as f32, self.history[i].client_fps),
+                            to_screen_trans * pos2(x, stats.server_fps),
+                            to_screen_trans * pos2(x, stats.client_fps),
                         )
                     })
                     .unzip();
@@ -215,63 +621,69 @@ impl StatisticsTab {
             |ui, stats| {
                 ui.colored_label(
                     graph_colors::SERVER_FPS,
-                    format!("Streamer FPS: {:.2}", stats.server_fps),
+                    tr_args(
+                        "statistics.streamer_fps",
+                        &[("fps", &format!("{:.2}", stats.server_fps))],
+                    ),
                 );
                 ui.colored_label(
                     graph_colors::CLIENT_FPS,
-                    format!("Client FPS: {:.2}", stats.client_fps),
+                    tr_args(
+                        "statistics.client_fps",
+                        &[("fps", &format!("{:.2}", stats.client_fps))],
+                    ),
                 );
             },
         );
     }
 
-    fn draw_bitrate_graph(&self, ui: &mut Ui, available_width: f32) {
+    fn draw_bitrate_graph(&self, ui: &mut Ui, available_width: f32, history: &VisibleHistory) {
         let mut data = statistics::Data::new(
-            self.history
+            history
+                .samples
                 .iter()
-                .map(|stats| stats.actual_bitrate_bps as f64)
+                .map(|(_, stats)| stats.actual_bitrate_bps as f64)
                 .collect::<Vec<_>>(),
         );
 
         self.draw_graph(
             ui,
             available_width,
-            "Bitrate",
+            history,
+            &tr("statistics.bitrate"),
             0.0..=(data.quantile(UPPER_QUANTILE) * 2.0) as f32 / 1e6,
             |painter, to_screen_trans| {
-                let mut scaled_calculated = Vec::with_capacity(GRAPH_HISTORY_SIZE);
-                let mut decoder_latency_limiter = Vec::with_capacity(GRAPH_HISTORY_SIZE);
-                let mut network_latency_limiter = Vec::with_capacity(GRAPH_HISTORY_SIZE);
-                let mut encoder_latency_limiter = Vec::with_capacity(GRAPH_HISTORY_SIZE);
-                let mut manual_max = Vec::with_capacity(GRAPH_HISTORY_SIZE);
-                let mut manual_min = Vec::with_capacity(GRAPH_HISTORY_SIZE);
-                let mut requested = Vec::with_capacity(GRAPH_HISTORY_SIZE);
-                let mut actual = Vec::with_capacity(GRAPH_HISTORY_SIZE);
-                for i in 0..GRAPH_HISTORY_SIZE {
-                    let nom_br = &self.history[i].nominal_bitrate;
+                let mut scaled_calculated = Vec::with_capacity(history.samples.len());
+                let mut decoder_latency_limiter = Vec::with_capacity(history.samples.len());
+                let mut network_latency_limiter = Vec::with_capacity(history.samples.len());
+                let mut encoder_latency_limiter = Vec::with_capacity(history.samples.len());
+                let mut manual_max = Vec::with_capacity(history.samples.len());
+                let mut manual_min = Vec::with_capacity(history.samples.len());
+                let mut requested = Vec::with_capacity(history.samples.len());
+                let mut actual = Vec::with_capacity(history.samples.len());
+                for &(x, stats) in &history.samples {
+                    let nom_br = &stats.nominal_bitrate;
 
                     if let Some(value) = nom_br.scaled_calculated_bps {
-                        scaled_calculated.push(to_screen_trans * pos2(i as f32, value / 1e6))
+                        scaled_calculated.push(to_screen_trans * pos2(x, value / 1e6))
                     }
                     if let Some(value) = nom_br.decoder_latency_limiter_bps {
-                        decoder_latency_limiter.push(to_screen_trans * pos2(i as f32, value / 1e6))
+                        decoder_latency_limiter.push(to_screen_trans * pos2(x, value / 1e6))
                     }
                     if let Some(value) = nom_br.network_latency_limiter_bps {
-                        network_latency_limiter.push(to_screen_trans * pos2(i as f32, value / 1e6))
+                        network_latency_limiter.push(to_screen_trans * pos2(x, value / 1e6))
                     }
                     if let Some(value) = nom_br.encoder_latency_limiter_bps {
-                        encoder_latency_limiter.push(to_screen_trans * pos2(i as f32, value / 1e6))
+                        encoder_latency_limiter.push(to_screen_trans * pos2(x, value / 1e6))
                     }
                     if let Some(value) = nom_br.manual_max_bps {
-                        manual_max.push(to_screen_trans * pos2(i as f32, value / 1e6))
+                        manual_max.push(to_screen_trans * pos2(x, value / 1e6))
                     }
                     if let Some(value) = nom_br.manual_min_bps {
-                        manual_min.push(to_screen_trans * pos2(i as f32, value / 1e6))
+                        manual_min.push(to_screen_trans * pos2(x, value / 1e6))
                     }
-                    requested.push(to_screen_trans * pos2(i as f32, nom_br.requested_bps / 1e6));
-                    actual.push(
-                        to_screen_trans * pos2(i as f32, self.history[i].actual_bitrate_bps / 1e6),
-                    );
+                    requested.push(to_screen_trans * pos2(x, nom_br.requested_bps / 1e6));
+                    actual.push(to_screen_trans * pos2(x, stats.actual_bitrate_bps / 1e6));
                 }
 
                 draw_lines(painter, scaled_calculated, Color32::GRAY);
@@ -280,8 +692,8 @@ impl StatisticsTab {
                 draw_lines(painter, decoder_latency_limiter, graph_colors::TRANSCODE);
                 draw_lines(painter, manual_max, graph_colors::RENDER);
                 draw_lines(painter, manual_min, graph_colors::RENDER);
-                draw_lines(painter, requested, theme::OK_GREEN);
-                draw_lines(painter, actual, theme::FG);
+                draw_lines(painter, requested, theme::palette().ok_green);
+                draw_lines(painter, actual, theme::palette().fg);
             },
             |ui, stats| {
                 fn maybe_label(
@@ -299,36 +711,51 @@ impl StatisticsTab {
 
                 maybe_label(
                     ui,
-                    "Initial calculated",
+                    &tr("statistics.initial_calculated"),
                     n.scaled_calculated_bps,
                     Color32::GRAY,
                 );
                 maybe_label(
                     ui,
-                    "Encoder latency limiter",
+                    &tr("statistics.encoder_latency_limiter"),
                     n.encoder_latency_limiter_bps,
                     graph_colors::TRANSCODE,
                 );
                 maybe_label(
                     ui,
-                    "Network latency limiter",
+                    &tr("statistics.network_latency_limiter"),
                     n.network_latency_limiter_bps,
                     graph_colors::NETWORK,
                 );
                 maybe_label(
                     ui,
-                    "Decoder latency limiter",
+                    &tr("statistics.decoder_latency_limiter"),
                     n.decoder_latency_limiter_bps,
                     graph_colors::TRANSCODE,
                 );
-                maybe_label(ui, "Manual max", n.manual_max_bps, graph_colors::RENDER);
-                maybe_label(ui, "Manual min", n.manual_min_bps, graph_colors::RENDER);
-                maybe_label(ui, "Requested", Some(n.requested_bps), theme::OK_GREEN);
                 maybe_label(
                     ui,
-                    "Actual recorded",
+                    &tr("statistics.manual_max"),
+                    n.manual_max_bps,
+                    graph_colors::RENDER,
+                );
+                maybe_label(
+                    ui,
+                    &tr("statistics.manual_min"),
+                    n.manual_min_bps,
+                    graph_colors::RENDER,
+                );
+                maybe_label(
+                    ui,
+                    &tr("statistics.requested"),
+                    Some(n.requested_bps),
+                    theme::palette().ok_green,
+                );
+                maybe_label(
+                    ui,
+                    &tr("statistics.actual_recorded"),
                     Some(stats.actual_bitrate_bps),
-                    theme::FG,
+                    theme::palette().fg,
                 );
             },
         )
@@ -338,50 +765,77 @@ impl StatisticsTab {
         ui.add_space(10.0);
 
         ui.columns(2, |ui| {
-            ui[0].label("Total packets:");
-            ui[1].label(&format!(
-                "{} packets ({} packets/s)",
-                statistics.video_packets_total, statistics.video_packets_per_sec
+            ui[0].label(tr("statistics.total_packets"));
+            ui[1].label(tr_args(
+                "statistics.packets_value",
+                &[
+                    ("total", &statistics.video_packets_total.to_string()),
+                    ("per_sec", &statistics.video_packets_per_sec.to_string()),
+                ],
             ));
 
-            ui[0].label("Total sent:");
+            ui[0].label(tr("statistics.total_sent"));
             ui[1].label(&format!("{} MB", statistics.video_mbytes_total));
 
-            ui[0].label("Bitrate:");
+            ui[0].label(tr("statistics.bitrate_overview"));
             ui[1].label(&format!("{:.1} Mbps", statistics.video_mbits_per_sec));
 
-            ui[0].label("Total latency:");
+            ui[0].label(tr("statistics.encoder_bitrate"));
+            ui[1].label(&format!("{:.1} Mbps", statistics.encoder_bitrate_mbps));
+
+            ui[0].label(tr("statistics.total_latency_overview"));
             ui[1].label(&format!("{:.0} ms", statistics.total_latency_ms));
 
-            ui[0].label("Encoder latency:");
+            ui[0].label(tr("statistics.encoder_latency"));
             ui[1].label(&format!("{:.2} ms", statistics.encode_latency_ms));
 
-            ui[0].label("Transport latency:");
+            ui[0].label(tr("statistics.transport_latency"));
             ui[1].label(&format!("{:.2} ms", statistics.network_latency_ms));
 
-            ui[0].label("Decoder latency:");
+            ui[0].label(tr("statistics.control_rtt"));
+            ui[1].label(&format!("{:.2} ms", statistics.control_rtt_ms));
+
+            ui[0].label(tr("statistics.decoder_latency"));
             ui[1].label(&format!("{:.2} ms", statistics.decode_latency_ms));
 
-            ui[0].label("Total packets lost:");
-            ui[1].label(&format!(
-                "{} packets ({} packets/s)",
-                statistics.packets_lost_total, statistics.packets_lost_per_sec
+            ui[0].label(tr("statistics.total_packets_lost"));
+            ui[1].label(tr_args(
+                "statistics.packets_value",
+                &[
+                    ("total", &statistics.packets_lost_total.to_string()),
+                    ("per_sec", &statistics.packets_lost_per_sec.to_string()),
+                ],
+            ));
+
+            ui[0].label(tr("statistics.total_frames_dropped"));
+            ui[1].label(tr_args(
+                "statistics.frames_value",
+                &[("frames", &statistics.frames_dropped_total.to_string())],
+            ));
+
+            ui[0].label(tr("statistics.total_stream_packets_dropped"));
+            ui[1].label(tr_args(
+                "statistics.dropped_packets_value",
+                &[(
+                    "packets",
+                    &statistics.stream_packets_dropped_total.to_string(),
+                )],
             ));
 
-            ui[0].label("Client FPS:");
+            ui[0].label(tr("statistics.client_fps_overview"));
             ui[1].label(&format!("{} FPS", statistics.client_fps));
 
-            ui[0].label("Streamer FPS:");
+            ui[0].label(tr("statistics.streamer_fps_overview"));
             ui[1].label(&format!("{} FPS", statistics.server_fps));
 
-            ui[0].label("Headset battery");
+            ui[0].label(tr("statistics.headset_battery"));
             ui[1].label(&format!(
                 "{}% ({})",
                 statistics.battery_hmd,
                 if statistics.hmd_plugged {
-                    "plugged"
+                    tr("statistics.plugged")
                 } else {
-                    "unplugged"
+                    tr("statistics.unplugged")
                 }
             ));
         });
@@ -0,0 +1,218 @@
+use crate::dashboard::{
+    localization::{tr, tr_args, tr_count},
+    ServerRequest,
+};
+use alvr_gui_common::theme;
+use alvr_packets::SettingChange;
+use eframe::egui::{
+    self, Align, Align2, Frame, Layout, RichText, Spinner, Stroke, TopBottomPanel, Ui, Window,
+};
+use std::time::Duration;
+
+#[cfg(target_arch = "wasm32")]
+use instant::Instant;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+// Covers the SteamVR shutdown timeout plus a slow startup
+const RESTART_TIMEOUT: Duration = Duration::from_secs(90);
+const SESSION_REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+
+enum RestartState {
+    Idle,
+    Confirming,
+    // The restart is complete once the driver went away and the new one reported its pending
+    // changes
+    Restarting {
+        start_instant: Instant,
+        driver_stopped: bool,
+        last_request_instant: Instant,
+    },
+    Failed(String),
+}
+
+pub struct SteamvrRestart {
+    pending_changes: Vec<SettingChange>,
+    state: RestartState,
+}
+
+impl SteamvrRestart {
+    pub fn new() -> Self {
+        Self {
+            pending_changes: vec![],
+            state: RestartState::Idle,
+        }
+    }
+
+    pub fn update_pending_changes(&mut self, changes: Vec<SettingChange>) {
+        self.pending_changes = changes;
+
+        if let RestartState::Restarting {
+            driver_stopped: true,
+            ..
+        } = self.state
+        {
+            self.state = RestartState::Idle;
+        }
+    }
+
+    // Asks for confirmation first, since the stream drops while SteamVR restarts
+    pub fn request(&mut self, connected_to_server: bool) {
+        self.state = if connected_to_server {
+            RestartState::Confirming
+        } else {
+            RestartState::Failed(tr("steamvr_restart.not_running"))
+        };
+    }
+
+    // The web dashboard is served by the driver itself and cannot follow the restart
+    pub fn set_restarting(&mut self) {
+        self.state = if cfg!(target_arch = "wasm32") {
+            RestartState::Idle
+        } else {
+            let now = Instant::now();
+            RestartState::Restarting {
+                start_instant: now,
+                driver_stopped: false,
+                last_request_instant: now,
+            }
+        };
+    }
+
+    // Called on every frame, also while the SteamVR restarting screen hides the rest of the UI
+    pub fn update_server_connected(
+        &mut self,
+        context: &egui::Context,
+        connected_to_server: bool,
+    ) -> Option<ServerRequest> {
+        let RestartState::Restarting {
+            start_instant,
+            driver_stopped,
+            last_request_instant,
+        } = &mut self.state
+        else {
+            return None;
+        };
+
+        context.request_repaint_after(SESSION_REQUEST_INTERVAL);
+
+        let now = Instant::now();
+        if now > *start_instant + RESTART_TIMEOUT {
+            self.state = RestartState::Failed(tr_args(
+                "steamvr_restart.timeout",
+                &[("seconds", &RESTART_TIMEOUT.as_secs().to_string())],
+            ));
+
+            return None;
+        }
+
+        if !connected_to_server {
+            *driver_stopped = true;
+        } else if *driver_stopped && now > *last_request_instant + SESSION_REQUEST_INTERVAL {
+            *last_request_instant = now;
+
+            // The answer also carries the pending changes of the new driver
+            return Some(ServerRequest::GetSession);
+        }
+
+        None
+    }
+
+    fn banner(context: &egui::Context, fill: egui::Color32, add_contents: impl FnOnce(&mut Ui)) {
+        TopBottomPanel::top("steamvr_restart")
+            .frame(
+                Frame::default()
+                    .inner_margin(egui::vec2(10.0, 5.0))
+                    .fill(fill)
+                    .stroke(Stroke::new(1.0, theme::palette().separator_bg)),
+            )
+            .show(context, |ui| ui.horizontal(add_contents));
+    }
+
+    // Returns true when a restart has been confirmed
+    pub fn ui(&mut self, context: &egui::Context, connected_to_server: bool) -> bool {
+        let mut restart = false;
+        let palette = theme::palette();
+
+        match &mut self.state {
+            RestartState::Idle => {
+                if !self.pending_changes.is_empty() && connected_to_server {
+                    let settings = self
+                        .pending_changes
+                        .iter()
+                        .map(|change| alvr_packets::path_to_string(&change.path[1..]))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let message = tr_count("steamvr_restart.pending", self.pending_changes.len());
+
+                    let mut clicked = false;
+                    Self::banner(context, palette.log.warning, |ui| {
+                        ui.label(RichText::new(message).color(palette.bg).size(12.0));
+                        ui.label(RichText::new(&settings).color(palette.bg).size(12.0));
+
+                        ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                            clicked = ui.small_button(tr("button.restart_steamvr")).clicked();
+                        });
+                    });
+                    if clicked {
+                        self.state = RestartState::Confirming;
+                    }
+                }
+            }
+            RestartState::Confirming => {
+                let mut confirmed = None;
+                Window::new(tr("steamvr_restart.confirm_title"))
+                    .anchor(Align2::CENTER_CENTER, (0.0, 0.0))
+                    .resizable(false)
+                    .collapsible(false)
+                    .show(context, |ui| {
+                        ui.label(tr("steamvr_restart.confirm_message"));
+
+                        ui.columns(2, |ui| {
+                            if ui[0].button(tr("button.cancel")).clicked() {
+                                confirmed = Some(false);
+                            }
+                            if ui[1].button(tr("button.restart_steamvr")).clicked() {
+                                confirmed = Some(true);
+                            }
+                        });
+                    });
+
+                match confirmed {
+                    Some(true) => restart = true,
+                    Some(false) => self.state = RestartState::Idle,
+                    None => (),
+                }
+            }
+            RestartState::Restarting { start_instant, .. } => {
+                let elapsed = start_instant.elapsed().as_secs().to_string();
+                Self::banner(context, palette.log.info, |ui| {
+                    ui.add(Spinner::new().color(palette.bg));
+                    ui.label(
+                        RichText::new(tr_args(
+                            "steamvr_restart.in_progress",
+                            &[("seconds", &elapsed)],
+                        ))
+                        .color(palette.bg)
+                        .size(12.0),
+                    );
+                });
+            }
+            RestartState::Failed(message) => {
+                let mut dismissed = false;
+                Self::banner(context, palette.log.error, |ui| {
+                    ui.label(RichText::new(message.as_str()).color(palette.bg).size(12.0));
+
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        dismissed = ui.small_button(tr("button.dismiss")).clicked();
+                    });
+                });
+                if dismissed {
+                    self.state = RestartState::Idle;
+                }
+            }
+        }
+
+        restart
+    }
+}
@@ -0,0 +1,261 @@
+use crate::dashboard::{
+    localization::{tr, tr_args, tr_count},
+    ServerRequest, Tab,
+};
+use alvr_common::{LogEntry, LogSeverity};
+use alvr_events::{ClientConnectedEvent, ClientDiscoveredEvent, StreamStartedEvent};
+use alvr_gui_common::theme;
+use alvr_packets::PathValuePair;
+use alvr_session::ToastsConfig;
+use eframe::egui::{self, Align2, Area, Frame, Label, Order, RichText, Sense};
+use std::{collections::VecDeque, time::Duration};
+
+#[cfg(target_arch = "wasm32")]
+use instant::Instant;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+const TOAST_DURATION: Duration = Duration::from_secs(5);
+const MAX_VISIBLE_TOASTS: usize = 3;
+// The oldest toasts are dropped when too many events arrive at once
+const MAX_QUEUED_TOASTS: usize = 20;
+const TOAST_WIDTH: f32 = 300.0;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ToastCategory {
+    ClientFound,
+    ClientConnected,
+    StreamStarted,
+    IncompatibleClient,
+    Error,
+}
+
+impl ToastCategory {
+    // Name of the field in ToastsConfig
+    fn setting_name(self) -> &'static str {
+        match self {
+            ToastCategory::ClientFound => "client_found",
+            ToastCategory::ClientConnected => "client_connected",
+            ToastCategory::StreamStarted => "stream_started",
+            ToastCategory::IncompatibleClient => "incompatible_client",
+            ToastCategory::Error => "errors",
+        }
+    }
+
+    fn enabled(self, config: &ToastsConfig) -> bool {
+        match self {
+            ToastCategory::ClientFound => config.client_found,
+            ToastCategory::ClientConnected => config.client_connected,
+            ToastCategory::StreamStarted => config.stream_started,
+            ToastCategory::IncompatibleClient => config.incompatible_client,
+            ToastCategory::Error => config.errors,
+        }
+    }
+
+    fn severity(self) -> LogSeverity {
+        match self {
+            ToastCategory::ClientFound
+            | ToastCategory::ClientConnected
+            | ToastCategory::StreamStarted => LogSeverity::Info,
+            ToastCategory::IncompatibleClient => LogSeverity::Warning,
+            ToastCategory::Error => LogSeverity::Error,
+        }
+    }
+
+    fn target_tab(self) -> Tab {
+        match self {
+            ToastCategory::ClientFound
+            | ToastCategory::ClientConnected
+            | ToastCategory::IncompatibleClient => Tab::Connections,
+            ToastCategory::StreamStarted => Tab::Statistics,
+            ToastCategory::Error => Tab::Logs,
+        }
+    }
+}
+
+struct Toast {
+    category: ToastCategory,
+    message: String,
+    // Set when the toast becomes visible, so that queued toasts are not dismissed unseen
+    shown_instant: Option<Instant>,
+}
+
+pub enum ToastRequest {
+    OpenTab(Tab),
+    ServerRequest(ServerRequest),
+}
+
+pub struct Toasts {
+    queue: VecDeque<Toast>,
+    config: Option<ToastsConfig>,
+}
+
+impl Toasts {
+    pub fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            config: None,
+        }
+    }
+
+    pub fn update_settings(&mut self, config: &ToastsConfig) {
+        self.queue.retain(|toast| toast.category.enabled(config));
+        self.config = Some(config.clone());
+    }
+
+    fn push(&mut self, category: ToastCategory, message: String) {
+        if !self
+            .config
+            .as_ref()
+            .map(|config| category.enabled(config))
+            .unwrap_or(true)
+        {
+            return;
+        }
+
+        if self.queue.len() == MAX_QUEUED_TOASTS {
+            self.queue.pop_front();
+        }
+
+        self.queue.push_back(Toast {
+            category,
+            message,
+            shown_instant: None,
+        });
+    }
+
+    pub fn push_log(&mut self, entry: &LogEntry) {
+        if entry.severity == LogSeverity::Error {
+            self.push(ToastCategory::Error, entry.content.clone());
+        }
+    }
+
+    pub fn push_incompatible_client(&mut self, hostname: &str) {
+        self.push(
+            ToastCategory::IncompatibleClient,
+            tr_args("toasts.incompatible_client", &[("hostname", hostname)]),
+        );
+    }
+
+    pub fn push_client_discovered(&mut self, event: &ClientDiscoveredEvent) {
+        self.push(
+            ToastCategory::ClientFound,
+            tr_args(
+                "toasts.client_found",
+                &[("name", &event.hostname), ("hostname", &event.hostname)],
+            ),
+        );
+    }
+
+    pub fn push_client_connected(&mut self, event: &ClientConnectedEvent) {
+        self.push(
+            ToastCategory::ClientConnected,
+            tr_args(
+                "toasts.client_connected",
+                &[("name", &event.display_name), ("hostname", &event.hostname)],
+            ),
+        );
+    }
+
+    pub fn push_stream_started(&mut self, event: &StreamStartedEvent) {
+        self.push(
+            ToastCategory::StreamStarted,
+            tr_args(
+                "toasts.stream_started",
+                &[("name", &event.display_name), ("hostname", &event.hostname)],
+            ),
+        );
+    }
+
+    fn mute_request(category: ToastCategory) -> ServerRequest {
+        ServerRequest::SetValues(vec![PathValuePair {
+            path: alvr_packets::parse_path(&format!(
+                "session_settings.dashboard.toasts.{}",
+                category.setting_name()
+            )),
+            value: false.into(),
+        }])
+    }
+
+    // Error toasts stay until they are clicked, the others are dismissed after a timeout
+    pub fn ui(&mut self, context: &egui::Context) -> Vec<ToastRequest> {
+        let mut requests = vec![];
+
+        let now = Instant::now();
+        self.queue.retain(|toast| {
+            toast.category.severity() == LogSeverity::Error
+                || toast
+                    .shown_instant
+                    .map(|instant| now < instant + TOAST_DURATION)
+                    .unwrap_or(true)
+        });
+
+        if self.queue.is_empty() {
+            return requests;
+        }
+
+        let palette = theme::palette();
+        let mut dismissed_index = None;
+        let mut muted_category = None;
+
+        Area::new(egui::Id::new("toasts"))
+            .order(Order::Foreground)
+            .anchor(Align2::RIGHT_BOTTOM, egui::vec2(-10.0, -45.0))
+            .show(context, |ui| {
+                for (index, toast) in self.queue.iter_mut().enumerate().take(MAX_VISIBLE_TOASTS) {
+                    let shown_instant = *toast.shown_instant.get_or_insert(now);
+                    if toast.category.severity() != LogSeverity::Error {
+                        context.request_repaint_after(shown_instant + TOAST_DURATION - now);
+                    }
+
+                    let fill = match toast.category.severity() {
+                        LogSeverity::Error => palette.log.error,
+                        LogSeverity::Warning => palette.log.warning,
+                        _ => palette.log.info,
+                    };
+
+                    Frame::popup(ui.style()).fill(fill).show(ui, |ui| {
+                        ui.set_width(TOAST_WIDTH);
+                        ui.horizontal(|ui| {
+                            if ui
+                                .small_button(tr("toasts.mute"))
+                                .on_hover_text(tr("toasts.mute_hint"))
+                                .clicked()
+                            {
+                                muted_category = Some(toast.category);
+                            }
+
+                            let label = Label::new(
+                                RichText::new(&toast.message).color(palette.bg).size(12.0),
+                            )
+                            .wrap(true)
+                            .sense(Sense::click());
+                            if ui.add(label).clicked() {
+                                dismissed_index = Some(index);
+                            }
+                        });
+                    });
+                }
+
+                if self.queue.len() > MAX_VISIBLE_TOASTS {
+                    ui.label(tr_count(
+                        "toasts.more",
+                        self.queue.len() - MAX_VISIBLE_TOASTS,
+                    ));
+                }
+            });
+
+        if let Some(index) = dismissed_index {
+            if let Some(toast) = self.queue.remove(index) {
+                requests.push(ToastRequest::OpenTab(toast.category.target_tab()));
+            }
+        }
+
+        if let Some(category) = muted_category {
+            self.queue.retain(|toast| toast.category != category);
+            requests.push(ToastRequest::ServerRequest(Self::mute_request(category)));
+        }
+
+        requests
+    }
+}
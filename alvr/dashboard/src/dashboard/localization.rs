@@ -0,0 +1,219 @@
+use alvr_common::{
+    info,
+    once_cell::sync::Lazy,
+    parking_lot::{Mutex, RwLock},
+    warn,
+};
+use alvr_packets::PathSegment;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+pub const ENGLISH_CODE: &str = "en";
+
+// English is always available, even when the languages folder is missing
+const ENGLISH_JSON: &str = include_str!("../../languages/en.json");
+
+// Translated schema strings use the setting path as key, for example
+// "schema.video.preferred_codec.display_name". English is taken from the schema itself
+const SCHEMA_KEY_PREFIX: &str = "schema";
+
+// A message can be split by plural category ("one", "other", ...) so that languages with more
+// forms can be supported without changing the keys
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Message {
+    Text(String),
+    Plural(HashMap<String, String>),
+}
+
+impl Message {
+    // Only the English plural rule is implemented for now
+    fn text(&self, count: Option<usize>) -> Option<&str> {
+        match self {
+            Message::Text(text) => Some(text),
+            Message::Plural(forms) => {
+                let category = if count == Some(1) { "one" } else { "other" };
+                forms
+                    .get(category)
+                    .or_else(|| forms.get("other"))
+                    .map(String::as_str)
+            }
+        }
+    }
+}
+
+type Messages = HashMap<String, Message>;
+
+struct Localization {
+    english: Messages,
+    selected: Option<Messages>,
+}
+
+static LOCALIZATION: Lazy<RwLock<Localization>> = Lazy::new(|| {
+    RwLock::new(Localization {
+        english: serde_json::from_str(ENGLISH_JSON).unwrap(),
+        selected: None,
+    })
+});
+
+// Each missing key is logged only once per selected language
+static REPORTED_KEYS: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+#[cfg(not(target_arch = "wasm32"))]
+fn languages_dir() -> std::path::PathBuf {
+    alvr_filesystem::filesystem_layout_from_dashboard_exe(&std::env::current_exe().unwrap())
+        .languages_dir()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_messages(code: &str) -> Option<Messages> {
+    let path = languages_dir().join(format!("{code}.json"));
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) => {
+            warn!("Failed to read {}: {e}", path.display());
+            return None;
+        }
+    };
+
+    match serde_json::from_str(&text) {
+        Ok(messages) => Some(messages),
+        Err(e) => {
+            warn!("Failed to parse {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+// The web dashboard has no access to the languages folder
+#[cfg(target_arch = "wasm32")]
+fn load_messages(_: &str) -> Option<Messages> {
+    None
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn installed_languages() -> Vec<(String, String)> {
+    let Ok(dir) = std::fs::read_dir(languages_dir()) else {
+        return vec![];
+    };
+
+    let mut languages = dir
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "json" {
+                return None;
+            }
+
+            let code = path.file_stem()?.to_str()?.to_owned();
+            if code == ENGLISH_CODE {
+                return None;
+            }
+
+            let messages: Messages =
+                serde_json::from_str(&std::fs::read_to_string(&path).ok()?).ok()?;
+            let name = messages
+                .get("language_name")
+                .and_then(|message| message.text(None))
+                .unwrap_or(&code)
+                .to_owned();
+
+            Some((code, name))
+        })
+        .collect::<Vec<_>>();
+    languages.sort();
+
+    languages
+}
+
+#[cfg(target_arch = "wasm32")]
+fn installed_languages() -> Vec<(String, String)> {
+    vec![]
+}
+
+// Returns the language codes and names, English first
+pub fn available_languages() -> Vec<(String, String)> {
+    [(ENGLISH_CODE.to_owned(), tr("language_name"))]
+        .into_iter()
+        .chain(installed_languages())
+        .collect()
+}
+
+// A language that cannot be loaded is replaced by English
+pub fn set_language(code: &str) {
+    LOCALIZATION.write().selected = if code == ENGLISH_CODE {
+        None
+    } else {
+        load_messages(code)
+    };
+
+    REPORTED_KEYS.lock().clear();
+}
+
+fn report_missing(key: &str, in_english: bool) {
+    if REPORTED_KEYS.lock().insert(key.to_owned()) {
+        if in_english {
+            warn!("Missing text for \"{key}\"");
+        } else {
+            info!("Missing translation for \"{key}\", English is used instead");
+        }
+    }
+}
+
+fn lookup(key: &str, count: Option<usize>) -> String {
+    let localization = LOCALIZATION.read();
+
+    if let Some(selected) = &localization.selected {
+        if let Some(text) = selected.get(key).and_then(|message| message.text(count)) {
+            return text.to_owned();
+        }
+
+        report_missing(key, false);
+    }
+
+    if let Some(text) = localization
+        .english
+        .get(key)
+        .and_then(|message| message.text(count))
+    {
+        text.to_owned()
+    } else {
+        report_missing(key, true);
+
+        key.to_owned()
+    }
+}
+
+pub fn tr(key: &str) -> String {
+    lookup(key, None)
+}
+
+// Replaces each "{name}" placeholder with its value
+pub fn tr_args(key: &str, args: &[(&str, &str)]) -> String {
+    args.iter().fold(lookup(key, None), |text, (name, value)| {
+        text.replace(&format!("{{{name}}}"), value)
+    })
+}
+
+// The count is available to the text as "{count}"
+pub fn tr_count(key: &str, count: usize) -> String {
+    lookup(key, Some(count)).replace("{count}", &count.to_string())
+}
+
+// Returns None if the selected language does not translate this schema string, in which case the
+// caller uses the English text of the schema. The first path segment ("session_settings") is
+// skipped
+pub fn schema_string(path: &[PathSegment], name: &str) -> Option<String> {
+    let localization = LOCALIZATION.read();
+
+    let key = format!(
+        "{SCHEMA_KEY_PREFIX}.{}.{name}",
+        alvr_packets::path_to_string(&path[1..])
+    );
+
+    localization
+        .selected
+        .as_ref()?
+        .get(&key)?
+        .text(None)
+        .map(str::to_owned)
+}
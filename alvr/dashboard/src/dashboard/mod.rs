@@ -1,18 +1,31 @@
 mod basic_components;
 mod components;
+mod localization;
 
 use self::components::{
-    ConnectionsTab, LogsTab, NotificationBar, SettingsTab, SetupWizard, SetupWizardRequest,
+    AboutTab, ConnectionsTab, InputTab, LogsTab, NotificationBar, SettingsTab, SetupWizard,
+    SetupWizardRequest, SteamvrRestart, ToastRequest, Toasts,
 };
+use self::localization::{tr, tr_args, tr_count};
 use crate::{dashboard::components::StatisticsTab, DataSources};
-use alvr_common::parking_lot::{Condvar, Mutex};
-use alvr_events::EventType;
+use alvr_common::{
+    parking_lot::{Condvar, Mutex},
+    ConnectionState, GpuVendor, LogEntry,
+};
+use alvr_events::{
+    DevicePresetEvent, EventType, GpuDefaultsEvent, PairingState, PortConflictEvent, PortUsage,
+};
 use alvr_gui_common::theme;
-use alvr_packets::{PathValuePair, ServerRequest};
-use alvr_session::SessionConfig;
-use eframe::egui::{self, Align, CentralPanel, Frame, Layout, Margin, RichText, SidePanel, Stroke};
+use alvr_gui_common::theme::Palette;
+use alvr_packets::{
+    ClientListAction, PathValuePair, ServerRequest, SettingChange, SettingChangeEffect,
+    SettingValidationError,
+};
+use alvr_session::{DashboardConfig, DashboardTheme, MigrationReport, SessionConfig};
+use eframe::egui::{
+    self, Align, CentralPanel, Frame, Layout, Margin, RichText, SidePanel, Stroke, TopBottomPanel,
+};
 use std::{
-    collections::BTreeMap,
     ops::Deref,
     sync::{atomic::AtomicUsize, Arc},
 };
@@ -37,6 +50,125 @@ impl Deref for DisplayString {
     }
 }
 
+// A missing configuration, like before the session is received, follows the system theme
+fn theme_palette(config: Option<&DashboardConfig>, system_theme: Option<eframe::Theme>) -> Palette {
+    let dark_mode = match config.map(|config| config.theme) {
+        Some(DashboardTheme::Light) => false,
+        Some(DashboardTheme::Dark) => true,
+        Some(DashboardTheme::System) | None => system_theme != Some(eframe::Theme::Light),
+    };
+
+    let mut palette = if dark_mode {
+        theme::DARK_PALETTE
+    } else {
+        theme::LIGHT_PALETTE
+    };
+    if let Some(config) = config {
+        let [r, g, b] = config.accent_color;
+        palette.accent = egui::Color32::from_rgb(r, g, b);
+    }
+
+    palette
+}
+
+// The details are in the event itself, shown in the logs tab with the raw events
+fn session_migration_message(report: &MigrationReport) -> String {
+    if let Some(error) = &report.error {
+        tr_args("session_migration.failed", &[("error", error)])
+    } else {
+        tr_args(
+            "session_migration.migrated",
+            &[
+                ("migrated", &report.migrated.len().to_string()),
+                ("dropped", &report.dropped.len().to_string()),
+            ],
+        )
+    }
+}
+
+fn settings_validation_message(errors: &[SettingValidationError]) -> String {
+    let messages = errors
+        .iter()
+        .map(|error| error.message.as_str())
+        .collect::<Vec<_>>();
+
+    format!(
+        "{} {}",
+        tr_count("settings_tab.invalid_settings", errors.len()),
+        messages.join(", ")
+    )
+}
+
+fn port_conflict_message(event: &PortConflictEvent) -> String {
+    let usage = tr(match event.usage {
+        PortUsage::Discovery => "port_conflict.discovery",
+        PortUsage::Stream => "port_conflict.stream",
+        PortUsage::WebServer => "port_conflict.web_server",
+    });
+    let owner = event
+        .owner
+        .clone()
+        .unwrap_or_else(|| tr("port_conflict.unknown_owner"));
+
+    tr_args(
+        "port_conflict.message",
+        &[
+            ("protocol", &format!("{:?}", event.protocol).to_uppercase()),
+            ("port", &event.port.to_string()),
+            ("usage", &usage),
+            ("owner", &owner),
+        ],
+    )
+}
+
+pub fn gpu_vendor_name(vendor: GpuVendor) -> &'static str {
+    match vendor {
+        GpuVendor::Nvidia => "NVIDIA",
+        GpuVendor::Amd => "AMD",
+        GpuVendor::Intel => "Intel",
+        GpuVendor::Unknown => "unknown",
+    }
+}
+
+// None if there are no recommendations for the detected GPUs
+fn gpu_defaults_message(event: &GpuDefaultsEvent) -> Option<String> {
+    let vendor = gpu_vendor_name(event.selected_vendor?);
+
+    let mut message = if event.applied.is_empty() {
+        tr_args("gpu_defaults.up_to_date", &[("vendor", vendor)])
+    } else {
+        tr_count("gpu_defaults.applied", event.applied.len()).replace("{vendor}", vendor)
+    };
+    if !event.skipped.is_empty() {
+        message = format!(
+            "{message}. {} {}",
+            tr_count("gpu_defaults.kept", event.skipped.len()),
+            event.skipped.join(", ")
+        );
+    }
+
+    Some(message)
+}
+
+fn device_preset_message(event: &DevicePresetEvent) -> String {
+    let mut message = if event.applied.is_empty() {
+        tr_args("device_presets.up_to_date", &[("preset", &event.preset)])
+    } else {
+        tr_count("device_presets.applied", event.applied.len())
+            .replace("{preset}", &event.preset)
+            .replace("{hostname}", &event.hostname)
+    };
+    if !event.skipped.is_empty() {
+        message = format!(
+            "{message}. {} {}",
+            tr_count("gpu_defaults.kept", event.skipped.len()),
+            event.skipped.join(", ")
+        );
+    }
+
+    message
+}
+
 fn get_id() -> usize {
     static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
 
@@ -51,36 +183,78 @@ enum Tab {
     #[cfg(not(target_arch = "wasm32"))]
     Installation,
     Logs,
+    Input,
     Debug,
     About,
 }
 
+impl Tab {
+    const ALL: &'static [Tab] = &[
+        Tab::Connections,
+        Tab::Statistics,
+        Tab::Settings,
+        #[cfg(not(target_arch = "wasm32"))]
+        Tab::Installation,
+        Tab::Logs,
+        Tab::Input,
+        Tab::Debug,
+        Tab::About,
+    ];
+
+    fn label(self) -> String {
+        let (icon, key) = match self {
+            Tab::Connections => ("🔌", "tab.connections"),
+            Tab::Statistics => ("📈", "tab.statistics"),
+            Tab::Settings => ("⚙", "tab.settings"),
+            #[cfg(not(target_arch = "wasm32"))]
+            Tab::Installation => ("💾", "tab.installation"),
+            Tab::Logs => ("📝", "tab.logs"),
+            Tab::Input => ("🎮", "tab.input"),
+            Tab::Debug => ("🐞", "tab.debug"),
+            Tab::About => ("ℹ", "tab.about"),
+        };
+
+        format!("{icon}  {}", tr(key))
+    }
+}
+
 pub struct Dashboard {
     data_sources: DataSources,
     just_opened: bool,
     server_restarting: Arc<Mutex<bool>>,
     server_restarting_condvar: Arc<Condvar>,
     selected_tab: Tab,
-    tab_labels: BTreeMap<Tab, &'static str>,
     connections_tab: ConnectionsTab,
     statistics_tab: StatisticsTab,
     settings_tab: SettingsTab,
     #[cfg(not(target_arch = "wasm32"))]
     installation_tab: components::InstallationTab,
     logs_tab: LogsTab,
+    input_tab: InputTab,
+    about_tab: AboutTab,
     notification_bar: NotificationBar,
+    toasts: Toasts,
     setup_wizard: SetupWizard,
     setup_wizard_open: bool,
+    restart_prompt: Option<SettingChange>,
+    steamvr_restart: SteamvrRestart,
     session: Option<SessionConfig>,
+    dashboard_config: Option<DashboardConfig>,
+    // The system theme is part of the key because eframe may reset the visuals when it changes
+    applied_theme: (Palette, Option<eframe::Theme>),
+    applied_language: String,
 }
 
 impl Dashboard {
     pub fn new(creation_context: &eframe::CreationContext<'_>, data_sources: DataSources) -> Self {
-        alvr_gui_common::theme::set_theme(&creation_context.egui_ctx);
+        let system_theme = creation_context.integration_info.system_theme;
+        let palette = theme_palette(None, system_theme);
+        theme::set_theme(&creation_context.egui_ctx, palette);
 
         // Audio devices need to be queried early to mitigate buggy/slow hardware queries on Linux.
         data_sources.request(ServerRequest::GetSession);
         data_sources.request(ServerRequest::GetAudioDevices);
+        data_sources.request(ServerRequest::CheckFirewall);
 
         Self {
             data_sources,
@@ -88,34 +262,31 @@ impl Dashboard {
             server_restarting: Arc::new(Mutex::new(false)),
             server_restarting_condvar: Arc::new(Condvar::new()),
             selected_tab: Tab::Connections,
-            tab_labels: [
-                (Tab::Connections, "🔌  Connections"),
-                (Tab::Statistics, "📈  Statistics"),
-                (Tab::Settings, "⚙  Settings"),
-                #[cfg(not(target_arch = "wasm32"))]
-                (Tab::Installation, "💾  Installation"),
-                (Tab::Logs, "📝  Logs"),
-                (Tab::Debug, "🐞  Debug"),
-                (Tab::About, "ℹ  About"),
-            ]
-            .into_iter()
-            .collect(),
             connections_tab: ConnectionsTab::new(),
             statistics_tab: StatisticsTab::new(),
             settings_tab: SettingsTab::new(),
             #[cfg(not(target_arch = "wasm32"))]
             installation_tab: components::InstallationTab::new(),
             logs_tab: LogsTab::new(),
+            input_tab: InputTab::new(),
+            about_tab: AboutTab::new(),
             notification_bar: NotificationBar::new(),
+            toasts: Toasts::new(),
             setup_wizard: SetupWizard::new(),
             setup_wizard_open: false,
+            restart_prompt: None,
+            steamvr_restart: SteamvrRestart::new(),
             session: None,
+            dashboard_config: None,
+            applied_theme: (palette, system_theme),
+            applied_language: localization::ENGLISH_CODE.into(),
         }
     }
 
     // This call may block
-    fn restart_steamvr(&self, requests: &mut Vec<ServerRequest>) {
+    fn restart_steamvr(&mut self, requests: &mut Vec<ServerRequest>) {
         requests.push(ServerRequest::RestartSteamvr);
+        self.steamvr_restart.set_restarting();
 
         let mut server_restarting_lock = self.server_restarting.lock();
 
@@ -138,10 +309,109 @@ impl Dashboard {
             }
         });
     }
+
+    // Disconnecting the streaming clients lets them reconnect with the new settings
+    fn restart_stream(&self, requests: &mut Vec<ServerRequest>) {
+        if let Some(session) = &self.session {
+            for (hostname, info) in &session.client_connections {
+                if info.connection_state == ConnectionState::Streaming {
+                    requests.push(ServerRequest::UpdateClientList {
+                        hostname: hostname.clone(),
+                        action: ClientListAction::SetConnectionState(
+                            ConnectionState::Disconnecting,
+                        ),
+                    });
+                }
+            }
+        }
+    }
+
+    // Shown for as long as the option is enabled, so that it is not forgotten after a debugging
+    // session
+    fn incompatible_clients_banner_ui(
+        &mut self,
+        context: &egui::Context,
+        requests: &mut Vec<ServerRequest>,
+    ) {
+        let enabled = self
+            .session
+            .as_ref()
+            .map(|session| {
+                session
+                    .session_settings
+                    .connection
+                    .accept_incompatible_clients
+            })
+            .unwrap_or(false);
+        if !enabled {
+            return;
+        }
+
+        TopBottomPanel::top("incompatible_clients_banner")
+            .frame(
+                Frame::default()
+                    .inner_margin(egui::vec2(10.0, 5.0))
+                    .fill(theme::palette().log.error)
+                    .stroke(Stroke::new(1.0, theme::palette().separator_bg)),
+            )
+            .show(context, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(tr("incompatible_clients_banner.message"))
+                            .color(theme::palette().bg)
+                            .size(12.0),
+                    );
+
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui.small_button(tr("button.disable")).clicked() {
+                            requests.push(ServerRequest::SetValues(vec![PathValuePair {
+                                path: alvr_packets::parse_path(
+                                    "session_settings.connection.accept_incompatible_clients",
+                                ),
+                                value: false.into(),
+                            }]));
+                        }
+                    });
+                });
+            });
+    }
+
+    // Changes that need a SteamVR restart are listed by the pending restart banner instead
+    fn restart_prompt_ui(&mut self, context: &egui::Context, requests: &mut Vec<ServerRequest>) {
+        let Some(change) = self.restart_prompt.clone() else {
+            return;
+        };
+
+        TopBottomPanel::top("restart_prompt")
+            .frame(
+                Frame::default()
+                    .inner_margin(egui::vec2(10.0, 5.0))
+                    .fill(theme::palette().log.warning)
+                    .stroke(Stroke::new(1.0, theme::palette().separator_bg)),
+            )
+            .show(context, |ui| {
+                ui.horizontal(|ui| {
+                    let setting = alvr_packets::path_to_string(&change.path);
+                    let message = tr_args("restart_prompt.stream", &[("setting", &setting)]);
+                    ui.label(RichText::new(message).color(theme::palette().bg).size(12.0));
+
+                    ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                        if ui.small_button(tr("button.dismiss")).clicked() {
+                            self.restart_prompt = None;
+                        }
+                        if ui.small_button(tr("button.restart_stream")).clicked() {
+                            self.restart_stream(requests);
+
+                            self.restart_prompt = None;
+                        }
+                    });
+                });
+            });
+    }
 }
 
 impl eframe::App for Dashboard {
-    fn update(&mut self, context: &egui::Context, _: &mut eframe::Frame) {
+    fn update(&mut self, context: &egui::Context, frame: &mut eframe::Frame) {
         let mut requests = vec![];
 
         let connected_to_server = self.data_sources.server_connected();
@@ -149,14 +419,21 @@ impl eframe::App for Dashboard {
         while let Some(event) = self.data_sources.poll_event() {
             self.logs_tab.push_event(event.inner.clone());
 
+            // Replayed events update the state but do not notify again
+            let replayed = event.inner.replayed;
+            let severity = event.inner.severity;
             match event.inner.event_type {
-                EventType::Log(log_event) => {
+                EventType::Log(log_event) if !replayed => {
+                    self.toasts.push_log(&log_event);
                     self.notification_bar
                         .push_notification(log_event, event.from_dashboard);
                 }
-                EventType::GraphStatistics(graph_statistics) => self
-                    .statistics_tab
-                    .update_graph_statistics(graph_statistics),
+                EventType::GraphStatistics(graph_statistics) => {
+                    self.connections_tab
+                        .update_graph_statistics(&graph_statistics);
+                    self.statistics_tab
+                        .update_graph_statistics(graph_statistics)
+                }
                 EventType::StatisticsSummary(statistics) => {
                     self.statistics_tab.update_statistics(statistics)
                 }
@@ -164,9 +441,16 @@ impl eframe::App for Dashboard {
                     let settings = session.to_settings();
 
                     self.connections_tab.update_client_list(&session);
+                    self.connections_tab.update_settings(&settings);
                     self.settings_tab.update_session(&session.session_settings);
                     self.logs_tab.update_settings(&settings);
+                    self.input_tab.update_settings(&settings);
+                    self.statistics_tab.update_session(&session, &settings);
+                    self.about_tab.update_client_list(&session);
+                    self.about_tab.update_settings(&settings);
+                    self.dashboard_config = Some(settings.dashboard.clone());
                     self.notification_bar.update_settings(&settings);
+                    self.toasts.update_settings(&settings.dashboard.toasts);
                     if self.just_opened {
                         if settings.open_setup_wizard {
                             self.setup_wizard_open = true;
@@ -177,20 +461,203 @@ impl eframe::App for Dashboard {
 
                     self.session = Some(*session);
                 }
-                EventType::ServerRequestsSelfRestart => self.restart_steamvr(&mut requests),
+                EventType::RestartRequired(change) => {
+                    // Keep showing the most disruptive restart until it is handled
+                    if change.effect != SettingChangeEffect::DriverRestart
+                        && self
+                            .restart_prompt
+                            .as_ref()
+                            .map(|prompt| change.effect >= prompt.effect)
+                            .unwrap_or(true)
+                    {
+                        self.restart_prompt = Some(change);
+                    }
+                }
+                EventType::PendingRestart(changes) => {
+                    self.steamvr_restart.update_pending_changes(changes)
+                }
+                EventType::StreamSetup(stream_setup) => {
+                    self.connections_tab.update_stream_setup(stream_setup)
+                }
+                EventType::SessionMigrated(report) if !replayed => {
+                    self.notification_bar.push_notification(
+                        LogEntry {
+                            severity: alvr_events::session_migration_severity(&report),
+                            content: session_migration_message(&report),
+                        },
+                        event.from_dashboard,
+                    )
+                }
+                EventType::SettingsValidation(errors) => {
+                    if !replayed && !errors.is_empty() {
+                        self.notification_bar.push_notification(
+                            LogEntry {
+                                severity: alvr_events::settings_validation_severity(&errors),
+                                content: settings_validation_message(&errors),
+                            },
+                            event.from_dashboard,
+                        )
+                    }
+
+                    self.settings_tab.update_validation_errors(errors);
+                }
+                EventType::ClientDiscovered(event) if !replayed => {
+                    self.toasts.push_client_discovered(&event)
+                }
+                EventType::ClientConnected(event) if !replayed => {
+                    self.toasts.push_client_connected(&event)
+                }
+                EventType::StreamStarted(event) if !replayed => {
+                    self.toasts.push_stream_started(&event);
+                    self.connections_tab.update_stream_started(&event);
+                }
+                EventType::StreamPaused(paused) => {
+                    self.connections_tab.update_stream_paused(paused)
+                }
+                EventType::ClientDisconnected(event) => {
+                    self.connections_tab.update_client_disconnected(event)
+                }
+                EventType::ClientPairing(pairing) => {
+                    let message_key = match pairing.state {
+                        PairingState::Failed => Some("connections.pairing_failed"),
+                        PairingState::Expired => Some("connections.pairing_expired"),
+                        _ => None,
+                    };
+                    if let Some(key) = message_key {
+                        self.notification_bar.push_notification(
+                            LogEntry {
+                                severity,
+                                content: tr_args(key, &[("hostname", &pairing.hostname)]),
+                            },
+                            event.from_dashboard,
+                        )
+                    }
+
+                    self.connections_tab.update_client_pairing(pairing);
+                }
+                EventType::PortConflict(port_conflict) if !replayed => {
+                    self.notification_bar.push_notification(
+                        LogEntry {
+                            severity,
+                            content: port_conflict_message(&port_conflict),
+                        },
+                        event.from_dashboard,
+                    )
+                }
+                EventType::GpuDefaults(gpu_defaults) if !replayed => {
+                    if let Some(content) = gpu_defaults_message(&gpu_defaults) {
+                        self.notification_bar
+                            .push_notification(LogEntry { severity, content }, event.from_dashboard)
+                    }
+                }
+                EventType::DevicePreset(device_preset) if !replayed => {
+                    self.notification_bar.push_notification(
+                        LogEntry {
+                            severity,
+                            content: device_preset_message(&device_preset),
+                        },
+                        event.from_dashboard,
+                    )
+                }
+                EventType::IncompatibleClientAccepted(accepted) if !replayed => {
+                    self.notification_bar.push_notification(
+                        LogEntry {
+                            severity,
+                            content: tr_args(
+                                "incompatible_clients_banner.accepted",
+                                &[
+                                    ("hostname", &accepted.hostname),
+                                    ("expected", &accepted.expected_protocol_id.to_string()),
+                                    ("found", &accepted.client_protocol_id.to_string()),
+                                ],
+                            ),
+                        },
+                        event.from_dashboard,
+                    )
+                }
+                EventType::LowBattery(low_battery) if !replayed => {
+                    self.notification_bar.push_notification(
+                        LogEntry {
+                            severity,
+                            content: tr_args(
+                                if low_battery.critical {
+                                    "low_battery.critical"
+                                } else {
+                                    "low_battery.low"
+                                },
+                                &[
+                                    ("device", &low_battery.device_name),
+                                    (
+                                        "percent",
+                                        &format!("{:.0}", low_battery.gauge_value * 100.0),
+                                    ),
+                                ],
+                            ),
+                        },
+                        event.from_dashboard,
+                    )
+                }
+                EventType::IncompatibleClient(hostname) if !replayed => {
+                    self.toasts.push_incompatible_client(&hostname)
+                }
+                EventType::ServerRequestsSelfRestart => {
+                    self.restart_prompt = None;
+
+                    self.restart_steamvr(&mut requests)
+                }
+                EventType::InputSnapshot(snapshot) => {
+                    self.input_tab.update_input_snapshot(*snapshot)
+                }
                 EventType::AudioDevices(list) => self.settings_tab.update_audio_devices(list),
+                EventType::ApplicationProfile(event) => {
+                    self.settings_tab.update_application_profile(event)
+                }
                 #[cfg(not(target_arch = "wasm32"))]
                 EventType::DriversList(list) => self.installation_tab.update_drivers(list),
+                #[cfg(not(target_arch = "wasm32"))]
+                EventType::FirewallStatus(status) => {
+                    self.installation_tab.update_firewall_status(status)
+                }
+                #[cfg(not(target_arch = "wasm32"))]
+                EventType::SteamvrRootDir(dir) => self.installation_tab.update_steamvr_dir(dir),
+                #[cfg(not(target_arch = "wasm32"))]
+                EventType::InstallationActionResult(result) => {
+                    self.installation_tab.update_action_result(result)
+                }
                 _ => (),
             }
         }
 
+        // The settings tab is rebuilt because it stores the translated schema strings
+        if let Some(config) = &self.dashboard_config {
+            if config.language != self.applied_language {
+                localization::set_language(&config.language);
+                self.settings_tab.update_language();
+                self.applied_language = config.language.clone();
+            }
+        }
+
+        // Theme changes are applied immediately, for all tabs
+        let system_theme = frame.info().system_theme;
+        let palette = theme_palette(self.dashboard_config.as_ref(), system_theme);
+        if self.applied_theme != (palette, system_theme) {
+            theme::set_theme(context, palette);
+            self.applied_theme = (palette, system_theme);
+        }
+
+        if let Some(request) = self
+            .steamvr_restart
+            .update_server_connected(context, connected_to_server)
+        {
+            self.data_sources.request(request);
+        }
+
         if *self.server_restarting.lock() {
             CentralPanel::default().show(context, |ui| {
                 // todo: find a way to center both vertically and horizontally
                 ui.vertical_centered(|ui| {
                     ui.add_space(100.0);
-                    ui.heading(RichText::new("SteamVR is restarting").size(30.0));
+                    ui.heading(RichText::new(tr("steamvr_restarting")).size(30.0));
                 });
             });
 
@@ -198,6 +665,17 @@ impl eframe::App for Dashboard {
         }
 
         self.notification_bar.ui(context);
+        for request in self.toasts.ui(context) {
+            match request {
+                ToastRequest::OpenTab(tab) => self.selected_tab = tab,
+                ToastRequest::ServerRequest(request) => requests.push(request),
+            }
+        }
+        if self.steamvr_restart.ui(context, connected_to_server) {
+            self.restart_steamvr(&mut requests);
+        }
+        self.restart_prompt_ui(context, &mut requests);
+        self.incompatible_clients_banner_ui(context, &mut requests);
 
         if self.setup_wizard_open {
             CentralPanel::default().show(context, |ui| {
@@ -226,9 +704,9 @@ impl eframe::App for Dashboard {
                 .resizable(false)
                 .frame(
                     Frame::none()
-                        .fill(theme::LIGHTER_BG)
+                        .fill(theme::palette().lighter_bg)
                         .inner_margin(Margin::same(7.0))
-                        .stroke(Stroke::new(1.0, theme::SEPARATOR_BG)),
+                        .stroke(Stroke::new(1.0, theme::palette().separator_bg)),
                 )
                 .exact_width(150.0)
                 .show(context, |ui| {
@@ -239,8 +717,8 @@ impl eframe::App for Dashboard {
                     });
 
                     ui.with_layout(Layout::top_down_justified(Align::Min), |ui| {
-                        for (tab, label) in &self.tab_labels {
-                            ui.selectable_value(&mut self.selected_tab, *tab, *label);
+                        for tab in Tab::ALL {
+                            ui.selectable_value(&mut self.selected_tab, *tab, tab.label());
                         }
                     });
 
@@ -251,27 +729,27 @@ impl eframe::App for Dashboard {
                             ui.add_space(5.0);
 
                             if connected_to_server {
-                                if ui.button("Restart SteamVR").clicked() {
-                                    self.restart_steamvr(&mut requests);
+                                if ui.button(tr("button.restart_steamvr")).clicked() {
+                                    self.steamvr_restart.request(connected_to_server);
                                 }
-                            } else if ui.button("Launch SteamVR").clicked() {
+                            } else if ui.button(tr("button.launch_steamvr")).clicked() {
                                 crate::steamvr_launcher::LAUNCHER.lock().launch_steamvr();
                             }
 
                             ui.horizontal(|ui| {
                                 ui.add_space(5.0);
-                                ui.label(RichText::new("SteamVR:").size(13.0));
+                                ui.label(RichText::new(tr("sidebar.steamvr")).size(13.0));
                                 ui.add_space(-10.0);
                                 if connected_to_server {
                                     ui.label(
-                                        RichText::new("Connected")
-                                            .color(theme::OK_GREEN)
+                                        RichText::new(tr("sidebar.connected"))
+                                            .color(theme::palette().ok_green)
                                             .size(13.0),
                                     );
                                 } else {
                                     ui.label(
-                                        RichText::new("Disconnected")
-                                            .color(theme::KO_RED)
+                                        RichText::new(tr("sidebar.disconnected"))
+                                            .color(theme::palette().ko_red)
                                             .size(13.0),
                                     );
                                 }
@@ -284,14 +762,11 @@ impl eframe::App for Dashboard {
                 .frame(
                     Frame::none()
                         .inner_margin(Margin::same(20.0))
-                        .fill(theme::BG),
+                        .fill(theme::palette().bg),
                 )
                 .show(context, |ui| {
                     ui.with_layout(Layout::top_down_justified(Align::LEFT), |ui| {
-                        ui.heading(
-                            RichText::new(*self.tab_labels.get(&self.selected_tab).unwrap())
-                                .size(25.0),
-                        );
+                        ui.heading(RichText::new(self.selected_tab.label()).size(25.0));
                         match self.selected_tab {
                             Tab::Connections => {
                                 requests.extend(self.connections_tab.ui(ui, connected_to_server));
@@ -320,12 +795,17 @@ impl eframe::App for Dashboard {
                                 }
                             }
                             Tab::Logs => self.logs_tab.ui(ui),
+                            Tab::Input => {
+                                if let Some(request) = self.input_tab.ui(ui) {
+                                    requests.push(request);
+                                }
+                            }
                             Tab::Debug => {
                                 if let Some(request) = components::debug_tab_ui(ui) {
                                     requests.push(request);
                                 }
                             }
-                            Tab::About => components::about_tab_ui(ui),
+                            Tab::About => self.about_tab.ui(ui),
                         }
                     })
                 });
@@ -1,4 +1,6 @@
-use alvr_common::{debug, error, info, parking_lot::Mutex, warn, RelaxedAtomic};
+use alvr_common::{
+    debug, error, info, parking_lot::Mutex, warn, GpuVendor, LogSeverity, RelaxedAtomic,
+};
 use alvr_events::{Event, EventType};
 use alvr_packets::ServerRequest;
 use alvr_server_io::ServerDataManager;
@@ -29,15 +31,61 @@ pub fn get_local_data_source() -> ServerDataManager {
     ServerDataManager::new(&session_file_path)
 }
 
+// The local session is loaded before the UI starts, the events are polled with the first frames
+pub fn report_startup_events(
+    sender: &mpsc::Sender<PolledEvent>,
+    data_manager: &mut ServerDataManager,
+) {
+    let send = |severity, event_type| {
+        sender
+            .send(PolledEvent {
+                inner: Event {
+                    timestamp: "".into(),
+                    severity,
+                    replayed: false,
+                    event_type,
+                },
+                from_dashboard: true,
+            })
+            .ok();
+    };
+
+    // Applied before the validation, so that the reported errors are the ones of the final session
+    if data_manager.is_new_session() {
+        match data_manager.apply_gpu_defaults(crate::gpu_vendors::GPU_VENDORS.clone()) {
+            Ok((event, _)) => send(LogSeverity::Info, EventType::GpuDefaults(event)),
+            Err(e) => error!("Failed to apply the GPU defaults: {e}"),
+        }
+    }
+
+    if let Some(report) = data_manager.take_migration_report() {
+        send(
+            alvr_events::session_migration_severity(&report),
+            EventType::SessionMigrated(report),
+        );
+    }
+
+    let errors = data_manager.validation_errors().to_vec();
+    if !errors.is_empty() {
+        send(
+            alvr_events::settings_validation_severity(&errors),
+            EventType::SettingsValidation(errors),
+        );
+    }
+}
+
 fn report_event_local(
     context: &egui::Context,
     sender: &mpsc::Sender<PolledEvent>,
+    severity: LogSeverity,
     event_type: EventType,
 ) {
     sender
         .send(PolledEvent {
             inner: Event {
                 timestamp: "".into(),
+                severity,
+                replayed: false,
                 event_type,
             },
             from_dashboard: false,
@@ -54,10 +102,47 @@ fn report_session_local(
     report_event_local(
         context,
         sender,
+        LogSeverity::Info,
         EventType::Session(Box::new(data_manager.session().clone())),
     )
 }
 
+fn apply_gpu_defaults_local(
+    context: &egui::Context,
+    sender: &mpsc::Sender<PolledEvent>,
+    data_manager: &mut ServerDataManager,
+    detected_vendors: Vec<GpuVendor>,
+) {
+    match data_manager.apply_gpu_defaults(detected_vendors) {
+        Ok((event, _)) => report_event_local(
+            context,
+            sender,
+            LogSeverity::Info,
+            EventType::GpuDefaults(event),
+        ),
+        Err(e) => error!("Failed to apply the GPU defaults: {e}"),
+    }
+    report_session_local(context, sender, data_manager);
+}
+
+fn apply_device_preset_local(
+    context: &egui::Context,
+    sender: &mpsc::Sender<PolledEvent>,
+    data_manager: &mut ServerDataManager,
+    hostname: String,
+) {
+    match data_manager.reapply_device_preset(hostname) {
+        Ok((event, _)) => report_event_local(
+            context,
+            sender,
+            LogSeverity::Info,
+            EventType::DevicePreset(event),
+        ),
+        Err(e) => error!("Failed to apply the device defaults: {e}"),
+    }
+    report_session_local(context, sender, data_manager);
+}
+
 pub struct PolledEvent {
     pub inner: Event,
     pub from_dashboard: bool,
@@ -114,12 +199,40 @@ impl DataSources {
                                     report_session_local(&context, &events_sender, data_manager);
                                 }
                                 ServerRequest::SetValues(descs) => {
-                                    if let Err(e) = data_manager.set_values(descs) {
-                                        error!("Failed to set session value: {e}")
+                                    let res = data_manager.set_values(descs);
+
+                                    // Rejected changes are reported by the validation event
+                                    let errors = data_manager.validation_errors().to_vec();
+                                    if let Err(e) = res {
+                                        if errors.is_empty() {
+                                            error!("Failed to set session value: {e}")
+                                        }
                                     }
 
+                                    report_event_local(
+                                        &context,
+                                        &events_sender,
+                                        alvr_events::settings_validation_severity(&errors),
+                                        EventType::SettingsValidation(errors),
+                                    );
                                     report_session_local(&context, &events_sender, data_manager);
                                 }
+                                ServerRequest::ApplyGpuDefaults(detected_vendors) => {
+                                    apply_gpu_defaults_local(
+                                        &context,
+                                        &events_sender,
+                                        data_manager,
+                                        detected_vendors,
+                                    );
+                                }
+                                ServerRequest::ApplyDevicePreset(hostname) => {
+                                    apply_device_preset_local(
+                                        &context,
+                                        &events_sender,
+                                        data_manager,
+                                        hostname,
+                                    );
+                                }
                                 ServerRequest::UpdateClientList { hostname, action } => {
                                     data_manager.update_client_list(hostname, action);
 
@@ -130,16 +243,40 @@ impl DataSources {
                                         report_event_local(
                                             &context,
                                             &events_sender,
+                                            LogSeverity::Info,
                                             EventType::AudioDevices(list),
                                         )
                                     }
                                 }
                                 ServerRequest::FirewallRules(action) => {
-                                    if alvr_server_io::firewall_rules(action).is_ok() {
-                                        info!("Setting firewall rules succeeded!");
-                                    } else {
-                                        error!("Setting firewall rules failed!");
-                                    }
+                                    let result = alvr_server_io::firewall_rules_with_result(action);
+                                    report_event_local(
+                                        &context,
+                                        &events_sender,
+                                        alvr_events::installation_result_severity(&result),
+                                        EventType::InstallationActionResult(result),
+                                    );
+
+                                    let status = alvr_server_io::check_firewall_rules(
+                                        &data_manager.settings().connection,
+                                    );
+                                    report_event_local(
+                                        &context,
+                                        &events_sender,
+                                        alvr_events::firewall_status_severity(&status),
+                                        EventType::FirewallStatus(status),
+                                    )
+                                }
+                                ServerRequest::CheckFirewall => {
+                                    let status = alvr_server_io::check_firewall_rules(
+                                        &data_manager.settings().connection,
+                                    );
+                                    report_event_local(
+                                        &context,
+                                        &events_sender,
+                                        alvr_events::firewall_status_severity(&status),
+                                        EventType::FirewallStatus(status),
+                                    )
                                 }
                                 ServerRequest::RegisterAlvrDriver => {
                                     let alvr_driver_dir =
@@ -148,24 +285,43 @@ impl DataSources {
                                         )
                                         .openvr_driver_root_dir;
 
-                                    alvr_server_io::driver_registration(&[alvr_driver_dir], true)
-                                        .ok();
+                                    let result = alvr_server_io::driver_registration_with_result(
+                                        &[alvr_driver_dir],
+                                        true,
+                                    );
+                                    report_event_local(
+                                        &context,
+                                        &events_sender,
+                                        alvr_events::installation_result_severity(&result),
+                                        EventType::InstallationActionResult(result),
+                                    );
 
                                     if let Ok(list) = alvr_server_io::get_registered_drivers() {
                                         report_event_local(
                                             &context,
                                             &events_sender,
+                                            LogSeverity::Info,
                                             EventType::DriversList(list),
                                         )
                                     }
                                 }
                                 ServerRequest::UnregisterDriver(path) => {
-                                    alvr_server_io::driver_registration(&[path], false).ok();
+                                    let result = alvr_server_io::driver_registration_with_result(
+                                        &[path],
+                                        false,
+                                    );
+                                    report_event_local(
+                                        &context,
+                                        &events_sender,
+                                        alvr_events::installation_result_severity(&result),
+                                        EventType::InstallationActionResult(result),
+                                    );
 
                                     if let Ok(list) = alvr_server_io::get_registered_drivers() {
                                         report_event_local(
                                             &context,
                                             &events_sender,
+                                            LogSeverity::Info,
                                             EventType::DriversList(list),
                                         )
                                     }
@@ -175,14 +331,28 @@ impl DataSources {
                                         report_event_local(
                                             &context,
                                             &events_sender,
+                                            LogSeverity::Info,
                                             EventType::DriversList(list),
                                         )
                                     }
                                 }
+                                ServerRequest::CheckSteamvr => report_event_local(
+                                    &context,
+                                    &events_sender,
+                                    LogSeverity::Info,
+                                    EventType::SteamvrRootDir(
+                                        alvr_server_io::steamvr_root_dir().ok(),
+                                    ),
+                                ),
                                 ServerRequest::CaptureFrame
                                 | ServerRequest::InsertIdr
+                                | ServerRequest::Recenter
+                                | ServerRequest::SetStreamPaused(_)
                                 | ServerRequest::StartRecording
-                                | ServerRequest::StopRecording => {
+                                | ServerRequest::StopRecording
+                                | ServerRequest::StartTimingCapture
+                                | ServerRequest::StopTimingCapture
+                                | ServerRequest::SetTemporaryValues(_) => {
                                     warn!("Cannot perform action, streamer (SteamVR) is not connected.")
                                 }
                                 ServerRequest::RestartSteamvr | ServerRequest::ShutdownSteamvr => {
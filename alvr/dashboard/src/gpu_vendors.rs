@@ -0,0 +1,24 @@
+use alvr_common::{once_cell::sync::Lazy, GpuVendor};
+
+// Creating the wgpu instance is slow, the adapters are enumerated only once. Software renderers
+// like llvmpipe are not listed
+pub static GPU_VENDORS: Lazy<Vec<GpuVendor>> = Lazy::new(|| {
+    let adapters = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::PRIMARY,
+        flags: wgpu::InstanceFlags::empty(),
+        dx12_shader_compiler: Default::default(),
+        gles_minor_version: Default::default(),
+    })
+    .enumerate_adapters(wgpu::Backends::PRIMARY);
+
+    // The same adapter can be listed once per backend
+    let mut vendors = vec![];
+    for info in adapters.iter().map(|adapter| adapter.get_info()) {
+        let vendor = GpuVendor::from_pci_vendor_id(info.vendor);
+        if info.device_type != wgpu::DeviceType::Cpu && !vendors.contains(&vendor) {
+            vendors.push(vendor);
+        }
+    }
+
+    vendors
+});
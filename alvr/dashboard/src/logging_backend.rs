@@ -22,14 +22,17 @@ pub fn init_logging(event_sender: mpsc::Sender<PolledEvent>) {
         })
         .format(move |f, record| {
             let timestamp = chrono::Local::now().format("%H:%M:%S.%f").to_string();
+            let severity = LogSeverity::from_log_level(record.level());
 
             event_sender
                 .lock()
                 .send(PolledEvent {
                     inner: Event {
                         timestamp: timestamp.clone(),
+                        severity,
+                        replayed: false,
                         event_type: EventType::Log(LogEntry {
-                            severity: LogSeverity::from_log_level(record.level()),
+                            severity,
                             content: format!("{}", record.args()),
                         }),
                     },
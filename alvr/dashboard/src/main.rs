@@ -8,6 +8,8 @@ mod data_sources;
 #[cfg(target_arch = "wasm32")]
 mod data_sources_wasm;
 #[cfg(not(target_arch = "wasm32"))]
+mod gpu_vendors;
+#[cfg(not(target_arch = "wasm32"))]
 mod logging_backend;
 #[cfg(not(target_arch = "wasm32"))]
 mod steamvr_launcher;
@@ -35,34 +37,17 @@ fn main() {
 
     {
         let mut data_manager = data_sources::get_local_data_source();
+        data_sources::report_startup_events(&server_events_sender, &mut data_manager);
 
         data_manager.clean_client_list();
 
-        #[cfg(target_os = "linux")]
-        {
-            let has_nvidia = wgpu::Instance::new(wgpu::InstanceDescriptor {
-                backends: wgpu::Backends::VULKAN,
-                flags: wgpu::InstanceFlags::empty(),
-                dx12_shader_compiler: Default::default(),
-                gles_minor_version: Default::default(),
-            })
-            .enumerate_adapters(wgpu::Backends::VULKAN)
-            .iter()
-            .any(|adapter| adapter.get_info().vendor == 0x10de);
-
-            if has_nvidia {
-                data_manager
-                    .session_mut()
-                    .session_settings
-                    .patches
-                    .linux_async_reprojection = false;
-            }
-        }
-
         if data_manager.session().server_version != *ALVR_VERSION {
             let mut session_ref = data_manager.session_mut();
             session_ref.server_version = ALVR_VERSION.clone();
-            session_ref.client_connections.clear();
+            // Trusted clients survive version updates, untrusted ones are discarded
+            session_ref
+                .client_connections
+                .retain(|_, connection| connection.trusted);
             session_ref.session_settings.open_setup_wizard = true;
         }
 
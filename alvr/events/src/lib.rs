@@ -1,8 +1,11 @@
-use alvr_common::{info, DeviceMotion, LogEntry, Pose};
-use alvr_packets::{AudioDevicesList, ButtonValue};
-use alvr_session::SessionConfig;
+use alvr_common::{glam::UVec2, info, DeviceMotion, GpuVendor, LogEntry, LogSeverity, Pose};
+use alvr_packets::{
+    AudioDevicesList, ButtonValue, FirewallStatus, InstallationActionResult, SettingChange,
+    SettingValidationError, ThermalStatus,
+};
+use alvr_session::{CodecType, MigrationReport, SessionConfig, SocketProtocol};
 use serde::{Deserialize, Serialize};
-use std::{path::PathBuf, time::Duration};
+use std::{net::IpAddr, path::PathBuf, time::Duration};
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct StatisticsSummary {
@@ -20,6 +23,17 @@ pub struct StatisticsSummary {
     pub server_fps: u32,
     pub battery_hmd: u32,
     pub hmd_plugged: bool,
+    // Round trip time of the control connection, 0 until it is measured
+    #[serde(default)]
+    pub control_rtt_ms: f32,
+    #[serde(default)]
+    pub encoder_bitrate_mbps: f32,
+    // Encoded frames the client never reported as displayed
+    #[serde(default)]
+    pub frames_dropped_total: usize,
+    // Received packets discarded because the server did not keep up
+    #[serde(default)]
+    pub stream_packets_dropped_total: usize,
 }
 
 // Bitrate statistics minus the empirical output value
@@ -34,21 +48,190 @@ pub struct NominalBitrateStats {
     pub requested_bps: f32,
 }
 
+// Sent at the rate set by statistics_event_rate while streaming. Third party tools read these
+// fields through the events websocket, so they can be added but not renamed. Latencies are in
+// milliseconds and the timestamp in milliseconds since the UNIX epoch
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct GraphStatistics {
-    pub total_pipeline_latency_s: f32,
-    pub game_time_s: f32,
-    pub server_compositor_s: f32,
-    pub encoder_s: f32,
-    pub network_s: f32,
-    pub decoder_s: f32,
-    pub decoder_queue_s: f32,
-    pub client_compositor_s: f32,
-    pub vsync_queue_s: f32,
+    pub timestamp_ms: u64,
+    pub total_pipeline_latency_ms: f32,
+    pub game_time_ms: f32,
+    pub server_compositor_ms: f32,
+    pub encoder_ms: f32,
+    #[serde(default)]
+    pub pacing_ms: f32,
+    pub network_ms: f32,
+    pub jitter_buffer_ms: f32,
+    pub decoder_ms: f32,
+    pub decoder_queue_ms: f32,
+    pub client_compositor_ms: f32,
+    pub vsync_queue_ms: f32,
     pub client_fps: f32,
     pub server_fps: f32,
     pub nominal_bitrate: NominalBitrateStats,
     pub actual_bitrate_bps: f32,
+    pub packets_lost_total: usize,
+    // Since the previous event
+    pub packets_lost: usize,
+}
+
+// Sent when the client thermal status changes. bitrate_multiplier is 1 when there is no
+// mitigation in place
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ThermalMitigationEvent {
+    pub status: ThermalStatus,
+    pub bitrate_multiplier: f32,
+    // Used from the next connection
+    pub resolution_scale: Option<f32>,
+}
+
+// Sent when the battery of a device that is not charging drops below a warning threshold
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LowBatteryEvent {
+    pub device_id: u64,
+    pub device_name: String,
+    pub gauge_value: f32, // range [0, 1]
+    pub critical: bool,
+}
+
+// Sent when the running SteamVR application or the active application profile changes
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ApplicationProfileEvent {
+    // None while no application is running or not streaming
+    pub app_key: Option<String>,
+    pub profile_name: Option<String>,
+    // The profile is the manual_profile setting instead of the one of the application
+    pub manual: bool,
+}
+
+// Negotiated with the client before the stream starts. Sent again when the codec is switched while
+// streaming
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StreamSetupEvent {
+    pub hostname: String,
+    pub codec: CodecType,
+    pub view_resolution: UVec2,
+    pub refresh_rate: f32,
+    // SteamVR is restarted to apply the new configuration before the stream can start
+    pub driver_restart: bool,
+}
+
+// Sent the first time an untrusted client announces itself
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ClientDiscoveredEvent {
+    pub hostname: String,
+    pub ip: IpAddr,
+}
+
+// The control socket is established and the client is compatible. A ClientDisconnected event
+// always follows, also when the stream never starts
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ClientConnectedEvent {
+    pub hostname: String,
+    pub display_name: String,
+    pub ip: IpAddr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum PairingState {
+    // The code must be entered in the headset before the expiration
+    WaitingForCode { code: String, valid_for: Duration },
+    Verified,
+    // Too many wrong codes were entered
+    Failed,
+    Expired,
+    Cancelled,
+}
+
+// Sent when the verification of a client trusted from the dashboard starts and ends
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ClientPairingEvent {
+    pub hostname: String,
+    pub state: PairingState,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IncompatibleClientAcceptedEvent {
+    pub hostname: String,
+    pub expected_protocol_id: u64,
+    pub client_protocol_id: u64,
+}
+
+// Sent when a connection starts with the network simulator enabled. The config is the
+// network_simulator setting, as in the session
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NetworkSimulatorEvent {
+    pub hostname: String,
+    pub config: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StreamStartedEvent {
+    pub hostname: String,
+    pub display_name: String,
+    pub view_resolution: UVec2,
+    pub refresh_rate: f32,
+    pub codec: CodecType,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum DisconnectionReason {
+    KeepaliveTimeout,
+    // The client announced itself again while streaming, after closing the connection on its side
+    ClientRequested,
+    // From the dashboard, by disconnecting or removing the client
+    ServerRequested,
+    ServerRestarting,
+    SocketError(String),
+    // The connection was dropped before the stream started
+    HandshakeFailed(String),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ClientDisconnectedEvent {
+    pub hostname: String,
+    pub reason: DisconnectionReason,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PortUsage {
+    Discovery,
+    Stream,
+    WebServer,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PortConflictEvent {
+    pub port: u16,
+    pub protocol: SocketProtocol,
+    pub usage: PortUsage,
+    // Program that holds the port, if it could be found
+    pub owner: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GpuDefaultsEvent {
+    // Vendors of the graphics adapters, without duplicates
+    pub detected_vendors: Vec<GpuVendor>,
+    // The vendor of the overlay, None if no overlay exists for the detected GPUs
+    pub selected_vendor: Option<GpuVendor>,
+    // False for the "reset to recommended" action
+    pub fresh_session: bool,
+    // Paths relative to session_settings
+    pub applied: Vec<String>,
+    // Settings changed by the user, that the overlay left alone
+    pub skipped: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DevicePresetEvent {
+    pub hostname: String,
+    pub preset: String,
+    // False for the "re-apply recommended defaults" action
+    pub first_connection: bool,
+    // Paths relative to session_settings
+    pub applied: Vec<String>,
+    pub skipped: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -75,27 +258,168 @@ pub struct HapticsEvent {
     pub amplitude: f32,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InputDeviceSnapshot {
+    pub path: String,
+    pub client_motion: DeviceMotion,
+    // After the offsets and the recentering. None if the device is not sent to SteamVR
+    pub processed_motion: Option<DeviceMotion>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InputButtonSnapshot {
+    pub path: String,
+    // None for the inputs that are only produced by the processing
+    pub client_value: Option<ButtonValue>,
+    // After the thumbstick deadzones
+    pub processed_value: Option<ButtonValue>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InputHapticsSnapshot {
+    pub steamvr: HapticsEvent,
+    // After the haptics settings. None if haptics are disabled
+    pub sent: Option<HapticsEvent>,
+}
+
+// Sent ten times per second while streaming, if logging.input_debug_events is enabled. Devices and
+// buttons have the last value received from the client
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InputSnapshotEvent {
+    pub devices: Vec<InputDeviceSnapshot>,
+    pub buttons: Vec<InputButtonSnapshot>,
+    // Values set on the inputs of the SteamVR controllers by the button mapping
+    pub steamvr_buttons: Vec<ButtonEvent>,
+    // Requested by SteamVR since the previous snapshot
+    pub haptics: Vec<InputHapticsSnapshot>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(tag = "id", content = "data")]
 pub enum EventType {
     Log(LogEntry),
     Session(Box<SessionConfig>),
+    // session.json was saved by another version or could not be read
+    SessionMigrated(MigrationReport),
+    // Result of the validation of the last change, or of the session loaded at startup
+    SettingsValidation(Vec<SettingValidationError>),
     StatisticsSummary(StatisticsSummary),
     GraphStatistics(GraphStatistics),
     Tracking(Box<TrackingEvent>),
     Buttons(Vec<ButtonEvent>),
     Haptics(HapticsEvent),
+    InputSnapshot(Box<InputSnapshotEvent>),
     AudioDevices(AudioDevicesList),
     DriversList(Vec<PathBuf>),
+    FirewallStatus(FirewallStatus),
+    // None if SteamVR is not installed
+    SteamvrRootDir(Option<PathBuf>),
+    InstallationActionResult(InstallationActionResult),
+    RestartRequired(SettingChange),
+    // Changes made since the driver started that are waiting for a SteamVR restart
+    PendingRestart(Vec<SettingChange>),
+    // Hostname of a client that was rejected because of a protocol mismatch
+    IncompatibleClient(String),
+    // The protocol IDs differ, but the client was let through by accept_incompatible_clients
+    IncompatibleClientAccepted(IncompatibleClientAcceptedEvent),
+    ThermalMitigation(ThermalMitigationEvent),
+    LowBattery(LowBatteryEvent),
+    ApplicationProfile(ApplicationProfileEvent),
+    StreamSetup(StreamSetupEvent),
+    ClientDiscovered(ClientDiscoveredEvent),
+    ClientPairing(ClientPairingEvent),
+    ClientConnected(ClientConnectedEvent),
+    StreamStarted(StreamStartedEvent),
+    // The video and the game audio were paused or resumed, from the dashboard or from the client
+    StreamPaused(bool),
+    // The streams of the connection are degraded on purpose
+    NetworkSimulator(NetworkSimulatorEvent),
+    ClientDisconnected(ClientDisconnectedEvent),
+    // A socket could not be bound because the port is used by another program
+    PortConflict(PortConflictEvent),
+    // The vendor specific defaults were applied to the session
+    GpuDefaults(GpuDefaultsEvent),
+    // The recommended defaults for the headset model of a client were applied
+    DevicePreset(DevicePresetEvent),
+    // Location of the event log file, sent at startup
+    EventLogFile(PathBuf),
     ServerRequestsSelfRestart,
+    // Sent to a new events consumer after the replayed events, the following ones are live
+    ReplayFinished,
+}
+
+// Payloads from older streamers have no severity and are treated as info
+fn default_severity() -> LogSeverity {
+    LogSeverity::Info
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Event {
     pub timestamp: String,
+    #[serde(default = "default_severity")]
+    pub severity: LogSeverity,
+    // Sent again from the history of the streamer, to a consumer that connected after it
+    #[serde(default)]
+    pub replayed: bool,
     pub event_type: EventType,
 }
 
-pub fn send_event(event_type: EventType) {
-    info!("{}", serde_json::to_string(&event_type).unwrap());
+impl Event {
+    // Used by every consumer that subscribes at a minimum level, so that the log file, the
+    // dashboard forwarder and the logs tab agree on what each level includes
+    pub fn passes_filter(&self, min_severity: LogSeverity) -> bool {
+        self.severity >= min_severity
+    }
+}
+
+// What send_event() hands to the logging backend, which adds the timestamp
+#[derive(Serialize, Deserialize)]
+pub struct LoggedEvent {
+    pub severity: LogSeverity,
+    pub event_type: EventType,
+}
+
+// Events always go through the logger at info level, whatever their severity, so that the
+// filtering is done on the severity alone
+pub fn send_event(severity: LogSeverity, event_type: EventType) {
+    info!(
+        "{}",
+        serde_json::to_string(&LoggedEvent {
+            severity,
+            event_type
+        })
+        .unwrap()
+    );
+}
+
+pub fn firewall_status_severity(status: &FirewallStatus) -> LogSeverity {
+    if status.blocked_ports().next().is_some() {
+        LogSeverity::Warning
+    } else {
+        LogSeverity::Info
+    }
+}
+
+pub fn session_migration_severity(report: &MigrationReport) -> LogSeverity {
+    if report.error.is_some() || !report.dropped.is_empty() {
+        LogSeverity::Warning
+    } else {
+        LogSeverity::Info
+    }
+}
+
+pub fn settings_validation_severity(errors: &[SettingValidationError]) -> LogSeverity {
+    if errors.is_empty() {
+        LogSeverity::Debug
+    } else {
+        LogSeverity::Warning
+    }
+}
+
+pub fn installation_result_severity(result: &InstallationActionResult) -> LogSeverity {
+    if result.error.is_some() {
+        LogSeverity::Error
+    } else {
+        LogSeverity::Info
+    }
 }
@@ -211,10 +211,18 @@ impl Layout {
         self.static_resources_dir.join("presets")
     }
 
+    pub fn languages_dir(&self) -> PathBuf {
+        self.static_resources_dir.join("languages")
+    }
+
     pub fn session(&self) -> PathBuf {
         self.config_dir.join("session.json")
     }
 
+    pub fn settings_presets(&self) -> PathBuf {
+        self.config_dir.join("settings_presets.json")
+    }
+
     pub fn session_log(&self) -> PathBuf {
         if cfg!(target_os = "linux") {
             self.log_dir.join("alvr_session_log.txt")
@@ -223,6 +231,15 @@ impl Layout {
         }
     }
 
+    // Rotated files are named after this one, with the index before the extension
+    pub fn event_log(&self) -> PathBuf {
+        if cfg!(target_os = "linux") {
+            self.log_dir.join("alvr_events.jsonl")
+        } else {
+            self.log_dir.join("events.jsonl")
+        }
+    }
+
     pub fn crash_log(&self) -> PathBuf {
         self.log_dir.join("crash_log.txt")
     }
@@ -1,24 +1,79 @@
+use alvr_common::parking_lot::RwLock;
 use egui::{self, Color32, Context, Rounding, Stroke, TextStyle, Visuals};
 
-pub const ACCENT: Color32 = Color32::from_rgb(0, 76, 176);
-pub const BG: Color32 = Color32::from_rgb(30, 30, 30);
-pub const LIGHTER_BG: Color32 = Color32::from_rgb(36, 36, 36);
-pub const SECTION_BG: Color32 = Color32::from_rgb(36, 36, 36);
-pub const DARKER_BG: Color32 = Color32::from_rgb(26, 26, 26);
-pub const SEPARATOR_BG: Color32 = Color32::from_rgb(69, 69, 69);
-pub const FG: Color32 = Color32::from_rgb(250, 250, 250);
-
-pub const OK_GREEN: Color32 = Color32::GREEN;
-pub const KO_RED: Color32 = Color32::RED;
-
-pub mod log_colors {
-    use egui::epaint::Color32;
-
-    pub const ERROR_LIGHT: Color32 = Color32::from_rgb(255, 50, 50);
-    pub const WARNING_LIGHT: Color32 = Color32::from_rgb(205, 147, 9);
-    pub const INFO_LIGHT: Color32 = Color32::from_rgb(134, 171, 241);
-    pub const DEBUG_LIGHT: Color32 = Color32::LIGHT_GRAY;
-    pub const EVENT_LIGHT: Color32 = Color32::GRAY;
+pub const DEFAULT_ACCENT: Color32 = Color32::from_rgb(0, 76, 176);
+
+// Colors used to tell log severities apart. Each theme has its own set so that all of them stay
+// readable on the background
+#[derive(Clone, Copy, PartialEq)]
+pub struct LogColors {
+    pub error: Color32,
+    pub warning: Color32,
+    pub info: Color32,
+    pub debug: Color32,
+    pub event: Color32,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Palette {
+    pub dark_mode: bool,
+    pub accent: Color32,
+    pub bg: Color32,
+    pub lighter_bg: Color32,
+    pub section_bg: Color32,
+    pub darker_bg: Color32,
+    pub separator_bg: Color32,
+    pub fg: Color32,
+    pub ok_green: Color32,
+    pub ko_red: Color32,
+    pub log: LogColors,
+}
+
+pub const DARK_PALETTE: Palette = Palette {
+    dark_mode: true,
+    accent: DEFAULT_ACCENT,
+    bg: Color32::from_rgb(30, 30, 30),
+    lighter_bg: Color32::from_rgb(36, 36, 36),
+    section_bg: Color32::from_rgb(36, 36, 36),
+    darker_bg: Color32::from_rgb(26, 26, 26),
+    separator_bg: Color32::from_rgb(69, 69, 69),
+    fg: Color32::from_rgb(250, 250, 250),
+    ok_green: Color32::GREEN,
+    ko_red: Color32::RED,
+    log: LogColors {
+        error: Color32::from_rgb(255, 50, 50),
+        warning: Color32::from_rgb(205, 147, 9),
+        info: Color32::from_rgb(134, 171, 241),
+        debug: Color32::LIGHT_GRAY,
+        event: Color32::GRAY,
+    },
+};
+
+pub const LIGHT_PALETTE: Palette = Palette {
+    dark_mode: false,
+    accent: DEFAULT_ACCENT,
+    bg: Color32::from_rgb(245, 245, 245),
+    lighter_bg: Color32::from_rgb(235, 235, 235),
+    section_bg: Color32::from_rgb(232, 232, 232),
+    darker_bg: Color32::from_rgb(222, 222, 222),
+    separator_bg: Color32::from_rgb(190, 190, 190),
+    fg: Color32::from_rgb(20, 20, 20),
+    ok_green: Color32::from_rgb(0, 140, 0),
+    ko_red: Color32::from_rgb(200, 0, 0),
+    log: LogColors {
+        error: Color32::from_rgb(190, 0, 0),
+        warning: Color32::from_rgb(150, 95, 0),
+        info: Color32::from_rgb(20, 80, 180),
+        debug: Color32::from_rgb(90, 90, 90),
+        event: Color32::from_rgb(120, 120, 120),
+    },
+};
+
+static PALETTE: RwLock<Palette> = RwLock::new(DARK_PALETTE);
+
+// Palette of the theme last applied with set_theme()
+pub fn palette() -> Palette {
+    *PALETTE.read()
 }
 
 // Graph colors
@@ -34,7 +89,10 @@ pub mod graph_colors {
     pub const CLIENT_FPS: Color32 = Color32::KHAKI;
 }
 
-pub fn set_theme(ctx: &Context) {
+// Can be called again at any time to switch theme
+pub fn set_theme(ctx: &Context, palette: Palette) {
+    *PALETTE.write() = palette;
+
     let mut style = (*ctx.style()).clone();
     style.spacing.slider_width = 200_f32; // slider width can only be set globally
     style.spacing.item_spacing = egui::vec2(15.0, 15.0);
@@ -43,30 +101,37 @@ pub fn set_theme(ctx: &Context) {
 
     ctx.set_style(style);
 
-    let mut visuals = Visuals::dark();
+    let mut visuals = if palette.dark_mode {
+        Visuals::dark()
+    } else {
+        Visuals::light()
+    };
 
     let rounding = Rounding::same(10.0);
 
-    visuals.widgets.active.bg_fill = ACCENT;
-    visuals.widgets.active.fg_stroke = Stroke::new(1.0, FG);
+    visuals.widgets.active.bg_fill = palette.accent;
+    visuals.widgets.active.fg_stroke = Stroke::new(1.0, palette.fg);
     visuals.widgets.active.rounding = rounding;
 
-    visuals.widgets.inactive.fg_stroke = Stroke::new(1.0, FG);
+    visuals.widgets.inactive.fg_stroke = Stroke::new(1.0, palette.fg);
     visuals.widgets.inactive.rounding = rounding;
 
     visuals.widgets.hovered.rounding = rounding;
 
-    visuals.widgets.open.bg_fill = SEPARATOR_BG;
+    visuals.widgets.open.bg_fill = palette.separator_bg;
     visuals.widgets.open.rounding = rounding;
 
-    visuals.selection.bg_fill = ACCENT;
-    visuals.selection.stroke = Stroke::new(1.0, FG);
+    visuals.selection.bg_fill = palette.accent;
+    visuals.selection.stroke = Stroke::new(1.0, palette.fg);
 
-    visuals.widgets.noninteractive.bg_fill = BG;
-    visuals.faint_bg_color = DARKER_BG;
-    visuals.widgets.noninteractive.fg_stroke = Stroke::new(1.0, FG);
-    visuals.widgets.noninteractive.bg_stroke = Stroke::new(0.5, SEPARATOR_BG);
+    visuals.widgets.noninteractive.bg_fill = palette.bg;
+    visuals.faint_bg_color = palette.darker_bg;
+    visuals.widgets.noninteractive.fg_stroke = Stroke::new(1.0, palette.fg);
+    visuals.widgets.noninteractive.bg_stroke = Stroke::new(0.5, palette.separator_bg);
     visuals.widgets.noninteractive.rounding = rounding;
 
+    visuals.warn_fg_color = palette.log.warning;
+    visuals.error_fg_color = palette.log.error;
+
     ctx.set_visuals(visuals);
 }
@@ -58,7 +58,7 @@ impl Launcher {
         worker_message_receiver: Receiver<WorkerMessage>,
         ui_message_sender: Sender<UiMessage>,
     ) -> Self {
-        alvr_gui_common::theme::set_theme(&cc.egui_ctx);
+        alvr_gui_common::theme::set_theme(&cc.egui_ctx, alvr_gui_common::theme::DARK_PALETTE);
 
         Self {
             worker_message_receiver,
@@ -283,7 +283,7 @@ impl eframe::App for Launcher {
                         let path = actions::installations_dir().join(&installation.version);
 
                         Frame::group(ui.style())
-                            .fill(alvr_gui_common::theme::SECTION_BG)
+                            .fill(alvr_gui_common::theme::palette().section_bg)
                             .inner_margin(egui::vec2(10.0, 5.0))
                             .show(ui, |ui| {
                                 Grid::new(&installation.version)
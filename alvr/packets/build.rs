@@ -0,0 +1,102 @@
+use std::{env, fs, path::PathBuf};
+
+// Sources of the types that are serialized for the network. The poses and motions embedded in the
+// tracking packets are defined in alvr_common, the session and the settings sent with the stream
+// configuration in alvr_session
+const TYPE_SOURCES: [&str; 5] = [
+    "src/lib.rs",
+    "src/tracking_delta.rs",
+    "../common/src/primitives.rs",
+    "../session/src/lib.rs",
+    "../session/src/settings.rs",
+];
+// The fixed tracking layout is written as code, all of it is part of the schema
+const CODE_SOURCES: [&str; 1] = ["src/tracking_codec.rs"];
+
+fn strip_code(line: &str) -> String {
+    line.split("//")
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect()
+}
+
+// The public structs and enums, as written in the source with comments, derives and whitespace
+// removed. Relies on the items being formatted by rustfmt, with the closing brace on column 0
+fn type_definitions(source: &str) -> Vec<String> {
+    let mut definitions = vec![];
+    let mut attributes = String::new();
+    let mut current = None::<String>;
+
+    for line in source.lines() {
+        let code = strip_code(line);
+
+        if let Some(definition) = &mut current {
+            definition.push_str(&code);
+
+            if line.starts_with('}') {
+                definitions.push(current.take().unwrap());
+            }
+        } else if line.starts_with("pub struct ") || line.starts_with("pub enum ") {
+            let definition = format!("{attributes}{code}");
+            attributes.clear();
+
+            if code.ends_with(';') || code.ends_with('}') {
+                definitions.push(definition);
+            } else {
+                current = Some(definition);
+            }
+        } else if line.starts_with("#[") {
+            // Derives do not change the encoding, but serde and repr attributes might
+            if !line.starts_with("#[derive") {
+                attributes.push_str(&code);
+            }
+        } else {
+            attributes.clear();
+        }
+    }
+
+    definitions
+}
+
+// The code before the tests, with comments and whitespace removed
+fn code(source: &str) -> String {
+    source
+        .lines()
+        .take_while(|line| !line.starts_with("#[cfg(test)]"))
+        .map(strip_code)
+        .collect()
+}
+
+// FNV-1a, which unlike the std hasher is stable across compiler versions, so that the hash
+// acknowledged by the tests stays valid
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn main() {
+    let mut definitions = vec![];
+    for path in TYPE_SOURCES {
+        println!("cargo:rerun-if-changed={path}");
+
+        definitions.extend(type_definitions(&fs::read_to_string(path).unwrap()));
+    }
+    for path in CODE_SOURCES {
+        println!("cargo:rerun-if-changed={path}");
+
+        definitions.push(code(&fs::read_to_string(path).unwrap()));
+    }
+    let schema = definitions.join("\n");
+
+    fs::write(
+        PathBuf::from(env::var("OUT_DIR").unwrap()).join("wire_schema.rs"),
+        format!(
+            "pub const WIRE_SCHEMA_HASH: u64 = {:#018x};\npub const WIRE_SCHEMA: &str = {schema:?};\n",
+            fnv1a_64(schema.as_bytes())
+        ),
+    )
+    .unwrap();
+}
@@ -1,11 +1,19 @@
+mod tracking_codec;
+mod tracking_delta;
+
+pub use tracking_codec::*;
+pub use tracking_delta::*;
+
 use alvr_common::{
     anyhow::Result,
     glam::{UVec2, Vec2},
-    ConnectionState, DeviceMotion, Fov, LogEntry, LogSeverity, Pose, ToAny,
+    semver::Version,
+    ConnectionState, DeviceMotion, Fov, GpuVendor, LogEntry, LogSeverity, Pose, ToAny,
 };
-use alvr_session::{CodecType, SessionConfig, Settings};
+use alvr_session::{CodecType, HeadsetInfo, SessionConfig, Settings, SocketProtocol};
 use serde::{Deserialize, Serialize};
 use serde_json as json;
+use sha2::{Digest, Sha256};
 use std::{
     collections::HashSet,
     fmt::{self, Debug},
@@ -14,11 +22,41 @@ use std::{
     time::Duration,
 };
 
+// WIRE_SCHEMA_HASH and WIRE_SCHEMA, see build.rs
+include!(concat!(env!("OUT_DIR"), "/wire_schema.rs"));
+
+// Semver compatible versions with the same packet definitions will produce the same protocol ID.
+// Protocol IDs are not ordered
+// As a convention, encode/decode the protocol ID bytes as little endian.
+// Forks that change the packets while staying compatible through other means can build with the
+// "protocol-id-from-version" feature, which uses only the major and prerelease version
+pub fn protocol_id() -> String {
+    if cfg!(feature = "protocol-id-from-version") {
+        alvr_common::version_protocol_id()
+    } else {
+        format!(
+            "{}+{WIRE_SCHEMA_HASH:016x}",
+            alvr_common::version_protocol_id()
+        )
+    }
+}
+
+pub fn protocol_id_u64() -> u64 {
+    alvr_common::hash_string(&protocol_id())
+}
+
 pub const TRACKING: u16 = 0;
 pub const HAPTICS: u16 = 1;
 pub const AUDIO: u16 = 2;
 pub const VIDEO: u16 = 3;
 pub const STATISTICS: u16 = 4;
+// Used instead of TRACKING if tracking_delta_encoding was negotiated
+pub const TRACKING_DELTA: u16 = 5;
+// Used instead of TRACKING if fixed_layout_tracking was negotiated, see tracking_codec.rs
+pub const TRACKING_FIXED: u16 = 6;
+
+// Number of digits of the code shown in the dashboard to pair a new client
+pub const PAIRING_CODE_LENGTH: usize = 6;
 
 // todo: use simple string
 #[derive(Serialize, Deserialize, Clone)]
@@ -38,6 +76,20 @@ pub struct VideoStreamingCapabilities {
     pub encoder_high_profile: bool,
     pub encoder_10_bits: bool,
     pub encoder_av1: bool,
+    pub multi_slice_decoding: bool,
+    pub haptics_envelopes: bool,
+    // The client can receive the video as datagrams while the stream socket uses TCP
+    pub udp_video: bool,
+    // The client can send the tracking in the layout of tracking_codec.rs
+    pub fixed_layout_tracking: bool,
+    // The client can send the tracking as TRACKING_DELTA packets
+    pub tracking_delta_encoding: bool,
+    // Platform name, like "Quest 2", used to pick the recommended defaults
+    pub device_model: Option<String>,
+    pub device_manufacturer: Option<String>,
+    // Identifies the client connected via USB, the streamer connects to it through localhost
+    // without a discovery announcement
+    pub hostname: Option<String>,
 }
 
 // Nasty workaround to make the packet extensible, pushing the limits of protocol compatibility
@@ -66,20 +118,31 @@ pub fn encode_video_streaming_capabilities(
     })
 }
 
+// The full json of the capabilities, including fields unknown to this version
+pub fn video_streaming_capabilities_extension(
+    legacy: &VideoStreamingCapabilitiesLegacy,
+) -> Result<json::Value> {
+    let json_bytes = legacy
+        .supported_refresh_rates_plus_extra_data
+        .iter()
+        .filter(|rate| **rate < 0.0)
+        .map(|rate| (-*rate) as u8)
+        .collect::<Vec<_>>();
+
+    Ok(json::from_str(&String::from_utf8(json_bytes)?)?)
+}
+
 pub fn decode_video_streaming_capabilities(
     legacy: &VideoStreamingCapabilitiesLegacy,
 ) -> Result<VideoStreamingCapabilities> {
-    let mut json_bytes = vec![];
-    let mut supported_refresh_rates = vec![];
-    for rate in &legacy.supported_refresh_rates_plus_extra_data {
-        if *rate < 0.0 {
-            json_bytes.push((-*rate) as u8)
-        } else {
-            supported_refresh_rates.push(*rate);
-        }
-    }
+    let supported_refresh_rates = legacy
+        .supported_refresh_rates_plus_extra_data
+        .iter()
+        .copied()
+        .filter(|rate| *rate >= 0.0)
+        .collect();
 
-    let caps_json = json::from_str::<json::Value>(&String::from_utf8(json_bytes)?)?;
+    let caps_json = video_streaming_capabilities_extension(legacy)?;
 
     Ok(VideoStreamingCapabilities {
         default_view_resolution: legacy.default_view_resolution,
@@ -91,6 +154,21 @@ pub fn decode_video_streaming_capabilities(
         encoder_high_profile: caps_json["encoder_high_profile"].as_bool().unwrap_or(true),
         encoder_10_bits: caps_json["encoder_10_bits"].as_bool().unwrap_or(true),
         encoder_av1: caps_json["encoder_av1"].as_bool().unwrap_or(true),
+        // Older clients cannot reassemble sliced frames
+        multi_slice_decoding: caps_json["multi_slice_decoding"]
+            .as_bool()
+            .unwrap_or(false),
+        haptics_envelopes: caps_json["haptics_envelopes"].as_bool().unwrap_or(false),
+        udp_video: caps_json["udp_video"].as_bool().unwrap_or(false),
+        fixed_layout_tracking: caps_json["fixed_layout_tracking"]
+            .as_bool()
+            .unwrap_or(false),
+        tracking_delta_encoding: caps_json["tracking_delta_encoding"]
+            .as_bool()
+            .unwrap_or(false),
+        device_model: caps_json["device_model"].as_str().map(String::from),
+        device_manufacturer: caps_json["device_manufacturer"].as_str().map(String::from),
+        hostname: caps_json["hostname"].as_str().map(String::from),
     })
 }
 
@@ -112,6 +190,20 @@ pub struct NegotiatedStreamingConfig {
     pub refresh_rate_hint: f32,
     pub game_audio_sample_rate: u32,
     pub enable_foveated_encoding: bool,
+    pub slice_count: u32,
+    pub tracking_delta_encoding: bool,
+    // None: the client sends three samples per display refresh
+    pub tracking_send_rate_hz: Option<f32>,
+    pub vector2_button_values: bool,
+    // Haptics are sent as HapticsWithEnvelope instead of Haptics
+    pub haptics_envelopes: bool,
+    // The client can send ReservedClientControlPacket::PlayspaceBoundary instead of PlayspaceSync
+    pub playspace_boundary: bool,
+    // The video is sent with a DatagramSender instead of the VIDEO stream, see the udp_video
+    // setting
+    pub udp_video: bool,
+    // The tracking is sent on TRACKING_FIXED instead of TRACKING
+    pub fixed_layout_tracking: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -146,6 +238,20 @@ pub fn decode_stream_config(
     let enable_foveated_encoding =
         json::from_value(negotiated_json["enable_foveated_encoding"].clone())
             .unwrap_or_else(|_| settings.video.foveated_encoding.enabled());
+    let slice_count = json::from_value(negotiated_json["slice_count"].clone()).unwrap_or(1);
+    let tracking_delta_encoding =
+        json::from_value(negotiated_json["tracking_delta_encoding"].clone()).unwrap_or(false);
+    let tracking_send_rate_hz =
+        json::from_value(negotiated_json["tracking_send_rate_hz"].clone()).unwrap_or(None);
+    let vector2_button_values =
+        json::from_value(negotiated_json["vector2_button_values"].clone()).unwrap_or(false);
+    let haptics_envelopes =
+        json::from_value(negotiated_json["haptics_envelopes"].clone()).unwrap_or(false);
+    let playspace_boundary =
+        json::from_value(negotiated_json["playspace_boundary"].clone()).unwrap_or(false);
+    let udp_video = json::from_value(negotiated_json["udp_video"].clone()).unwrap_or(false);
+    let fixed_layout_tracking =
+        json::from_value(negotiated_json["fixed_layout_tracking"].clone()).unwrap_or(false);
 
     Ok((
         settings,
@@ -154,6 +260,14 @@ pub fn decode_stream_config(
             refresh_rate_hint,
             game_audio_sample_rate,
             enable_foveated_encoding,
+            slice_count,
+            tracking_delta_encoding,
+            tracking_send_rate_hz,
+            vector2_button_values,
+            haptics_envelopes,
+            playspace_boundary,
+            udp_video,
+            fixed_layout_tracking,
         },
     ))
 }
@@ -164,6 +278,56 @@ pub struct DecoderInitializationConfig {
     pub config_buffer: Vec<u8>, // e.g. SPS + PPS NALs
 }
 
+// Statistics that can be measured only by the server, shown in the client statistics overlay
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct ServerStatistics {
+    pub network_latency: Duration,
+    pub encode_latency: Duration,
+    pub video_mbits_per_sec: f32,
+    pub packets_lost_per_sec: f32,
+    pub battery_hmd: f32,
+    pub hmd_plugged: bool,
+}
+
+// to be de/serialized with ServerControlPacket::Reserved()
+#[derive(Serialize, Deserialize)]
+pub enum ReservedServerControlPacket {
+    // The current decoder must be destroyed. A new DecoderConfig packet will follow
+    CodecChanged(CodecType),
+    // Sent periodically only if the statistics overlay is enabled
+    Statistics(ServerStatistics),
+    // Send time of the streamer since the UNIX epoch. Echoed back right away with KeepAlivePong to
+    // measure the round trip time of the control connection
+    KeepAlivePing(Duration),
+    // A tracking delta referenced a keyframe that was not received
+    RequestTrackingKeyframe,
+    // Sent before the connection is closed because the streamer is quitting, not restarting
+    ShuttingDown,
+    // Short message to be shown in the headset while streaming
+    Notification(String),
+    // Answers ReservedClientControlPacket::Request with the same ID. The response is an encoded
+    // ServerResponse
+    Response { id: u64, response: String },
+    // Sent before StartStream to a client that is being paired. Answered with PairingResponse, and
+    // sent again with a new nonce after a wrong code
+    PairingChallenge { nonce: u64, attempts_left: u32 },
+    // The client re-creates its reference space and sends PlayspaceSync again, which recenters the
+    // stream
+    Recenter,
+    // The video and the game audio are paused or resumed. Sent also when the state did not change,
+    // in answer to SetStreamPaused
+    StreamPaused(bool),
+    // Sent after a change of a setting that is applied without restarting the stream. Contains
+    // also the temporary values
+    SettingsChanged(Box<Settings>),
+}
+
+pub fn encode_reserved_server_control_packet(
+    packet: &ReservedServerControlPacket,
+) -> ServerControlPacket {
+    ServerControlPacket::Reserved(json::to_string(packet).unwrap())
+}
+
 #[derive(Serialize, Deserialize)]
 pub enum ServerControlPacket {
     StartStream,
@@ -189,18 +353,56 @@ pub struct BatteryPacket {
     pub is_plugged: bool,
 }
 
+// Same levels as the Android PowerManager thermal status
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum ThermalStatus {
+    None,
+    Light,
+    Moderate,
+    Severe,
+    Critical,
+    Emergency,
+    Shutdown,
+}
+
+// Touch (capacitive) states are sent as separate Binary entries, with the ".../touch" path of the
+// same input (see alvr_common::inputs). The server maps them to the touch components of the
+// emulated controllers, and synthesizes them from hand gestures when using hand tracking
 #[derive(Serialize, Deserialize, Clone, Copy, Debug)]
 pub enum ButtonValue {
     Binary(bool),
     Scalar(f32),
+    // Only sent if vector2_button_values was negotiated, old servers cannot decode it. Uses the
+    // path of the whole input, without "/x" or "/y"
+    Vector2(Vec2),
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct ButtonEntry {
     pub path_id: u64,
     pub value: ButtonValue,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DeviceRole {
+    Hmd,
+    LeftController,
+    RightController,
+    GenericTracker,
+    Gamepad,
+}
+
+// The hmd, controller and gamepad roles must use the IDs of /user/head, /user/hand/left,
+// /user/hand/right and /user/gamepad
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DeviceDeclaration {
+    pub device_id: u64,
+    pub role: DeviceRole,
+    pub display_name: String,
+    // IDs of the paths of the ButtonEntry values sent for this device
+    pub input_ids: HashSet<u64>,
+}
+
 // to be de/serialized with ClientControlPacket::Reserved()
 #[derive(Serialize, Deserialize)]
 pub enum ReservedClientControlPacket {
@@ -208,6 +410,46 @@ pub enum ReservedClientControlPacket {
         device_id: u64,
         input_ids: HashSet<u64>,
     },
+    GameAudioBufferStatistics {
+        underruns_per_minute: f32,
+        overruns_per_minute: f32,
+    },
+    ProximityState {
+        headset_worn: bool,
+    },
+    ThermalStatus(ThermalStatus),
+    // The decoder keeps failing even after being recreated
+    DecoderFailing {
+        message: String,
+    },
+    // Sent once the stream is ready, shown in the dashboard
+    ClientVersion(Version),
+    KeepAlivePong(Duration),
+    // Boundary polygon on the floor of the stage space, with points as (x, z). Same as
+    // PlayspaceSync, with the area being the bounding box of the polygon
+    PlayspaceBoundary(Vec<Vec2>),
+    // No video datagram arrived shortly after the stream started. The streamer sends the video
+    // with the VIDEO stream from now on
+    UdpVideoUnavailable,
+    // The request is an encoded ClientRequest, so that a request type unknown to the streamer can
+    // still be answered with ServerResponse::Unsupported. IDs are increasing
+    Request {
+        id: u64,
+        request: String,
+    },
+    // Answers PairingChallenge with pairing_proof() of the code entered by the user
+    PairingResponse {
+        proof: String,
+    },
+    // Sent once after the stream setup, before any tracking or button. Lists all the devices that
+    // will be referenced in Tracking::device_motions and Buttons. Clients that do not send it get
+    // the legacy profile of one headset and two controllers
+    DeviceRegistration(Vec<DeviceDeclaration>),
+    // Requested by the user in the headset. The streamer recenters on the current head pose and
+    // sends the last playspace to SteamVR again
+    Recenter,
+    // Requested by the user in the headset. Answered with StreamPaused
+    SetStreamPaused(bool),
 }
 
 pub fn encode_reserved_client_control_packet(
@@ -232,7 +474,7 @@ pub enum ClientControlPacket {
     ReservedBuffer(Vec<u8>),
 }
 
-#[derive(Serialize, Deserialize, Default)]
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct FaceData {
     pub eye_gazes: [Option<Pose>; 2],
     pub fb_face_expression: Option<Vec<f32>>, // issue: Serialize does not support [f32; 63]
@@ -240,10 +482,14 @@ pub struct FaceData {
     pub htc_lip_expression: Option<Vec<f32>>, // issue: Serialize does not support [f32; 37]
 }
 
-#[derive(Serialize, Deserialize)]
+// When the frame is split into slices, each slice is sent as a separate packet with the same
+// timestamp. Unsliced frames use slice_index 0 and slice_count 1.
+#[derive(Serialize, Deserialize, Clone, Copy)]
 pub struct VideoPacketHeader {
     pub timestamp: Duration,
     pub is_idr: bool,
+    pub slice_index: u32,
+    pub slice_count: u32,
 }
 
 // Note: face_data does not respect target_timestamp.
@@ -255,7 +501,7 @@ pub struct Tracking {
     pub face_data: FaceData,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone, Copy)]
 pub struct Haptics {
     pub device_id: u64,
     pub duration: Duration,
@@ -263,13 +509,106 @@ pub struct Haptics {
     pub amplitude: f32,
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct HapticsEnvelopePoint {
+    pub time_offset: Duration, // from the start of the event
+    pub amplitude: f32,
+}
+
+// The amplitude is interpolated linearly between the points of the envelope, and is held after the
+// last point. Without points, Haptics::amplitude is used for the whole duration
+#[derive(Serialize, Deserialize)]
+pub struct HapticsWithEnvelope {
+    pub haptics: Haptics,
+    pub envelope: Vec<HapticsEnvelopePoint>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct AudioDeviceInfo {
+    // Stable across restarts. Uses the endpoint ID on Windows and the sysfs path of the sound card
+    // for ALSA devices. Other devices use the name reported by the audio backend, with a suffix if
+    // several devices share the name
+    pub id: String,
+    pub name: String,
+    pub is_default: bool,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct AudioDevicesList {
+    pub output: Vec<AudioDeviceInfo>,
+    pub input: Vec<AudioDeviceInfo>,
+}
+
+// Format used before devices had IDs
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LegacyAudioDevicesList {
     pub output: Vec<String>,
     pub input: Vec<String>,
 }
 
-#[derive(Serialize, Deserialize, Clone)]
+impl From<AudioDevicesList> for LegacyAudioDevicesList {
+    fn from(list: AudioDevicesList) -> Self {
+        Self {
+            output: list.output.into_iter().map(|device| device.name).collect(),
+            input: list.input.into_iter().map(|device| device.name).collect(),
+        }
+    }
+}
+
+// Statistics about the current stream, as measured by the streamer
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ClientStatisticsSnapshot {
+    pub video_packets_total: u64,
+    pub video_bytes_total: u64,
+    pub packets_lost_total: u64,
+    pub total_pipeline_latency_average: Duration,
+    pub network_latency: Duration,
+    pub encode_latency: Duration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub enum ClientRequest {
+    Session,
+    AudioDevices,
+    ClientStatisticsSnapshot,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub enum ServerResponse {
+    Session(String), // Encoded SessionConfig
+    AudioDevices(AudioDevicesList),
+    ClientStatisticsSnapshot(ClientStatisticsSnapshot),
+    Unsupported,
+    Failed(String),
+}
+
+// Sent instead of the pairing code, so that the code does not travel in clear. The nonce makes a
+// proof valid for one challenge only
+pub fn pairing_proof(nonce: u64, code: &str) -> String {
+    Sha256::new()
+        .chain_update(nonce.to_le_bytes())
+        .chain_update(code.as_bytes())
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+pub fn encode_client_request(id: u64, request: ClientRequest) -> ClientControlPacket {
+    encode_reserved_client_control_packet(&ReservedClientControlPacket::Request {
+        id,
+        request: json::to_string(&request).unwrap(),
+    })
+}
+
+pub fn encode_server_response(id: u64, response: &ServerResponse) -> ServerControlPacket {
+    encode_reserved_server_control_packet(&ReservedServerControlPacket::Response {
+        id,
+        response: json::to_string(response).unwrap(),
+    })
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub enum PathSegment {
     Name(String),
     Index(usize),
@@ -307,6 +646,50 @@ pub fn parse_path(path: &str) -> Vec<PathSegment> {
     path.split('.').map(|s| s.into()).collect()
 }
 
+pub fn path_to_string(path: &[PathSegment]) -> String {
+    let mut path_string = String::new();
+    for segment in path {
+        match segment {
+            PathSegment::Name(name) => {
+                if !path_string.is_empty() {
+                    path_string.push('.');
+                }
+                path_string.push_str(name);
+            }
+            PathSegment::Index(index) => path_string.push_str(&format!("[{index}]")),
+        }
+    }
+
+    path_string
+}
+
+// Ordered from least to most disruptive
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SettingChangeEffect {
+    Live,
+    StreamRestart,
+    DriverRestart,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SettingChange {
+    pub path: Vec<PathSegment>,
+    pub effect: SettingChangeEffect,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct InvalidSetting {
+    pub path: Vec<PathSegment>,
+    pub value: json::Value,
+}
+
+// Constraints between several settings list all the settings involved
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SettingValidationError {
+    pub entries: Vec<InvalidSetting>,
+    pub message: String,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum ClientListAction {
     AddIfMissing {
@@ -314,17 +697,21 @@ pub enum ClientListAction {
         manual_ips: Vec<IpAddr>,
     },
     SetDisplayName(String),
-    Trust,
+    TrustAndMaybeAddIp(Option<IpAddr>),
     SetManualIps(Vec<IpAddr>),
     RemoveEntry,
     UpdateCurrentIp(Option<IpAddr>),
     SetConnectionState(ConnectionState),
+    SetVersion(Version),
+    SetHeadsetInfo(HeadsetInfo),
+    SetDevicePreset(String),
 }
 
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub struct ClientStatistics {
     pub target_timestamp: Duration, // identifies the frame
     pub frame_interval: Duration,
+    pub video_jitter_buffer: Duration,
     pub video_decode: Duration,
     pub video_decoder_queue: Duration,
     pub rendering: Duration,
@@ -338,18 +725,67 @@ pub struct PathValuePair {
     pub value: json::Value,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 pub enum FirewallRulesAction {
     Add,
     Remove,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FirewallRuleState {
+    Allowed,
+    Blocked,
+    // The firewall could not be queried, usually because of missing permissions
+    Unknown,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FirewallPortStatus {
+    pub port: u16,
+    pub protocol: SocketProtocol,
+    pub state: FirewallRuleState,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FirewallStatus {
+    pub backend: String,
+    pub ports: Vec<FirewallPortStatus>,
+}
+
+impl FirewallStatus {
+    pub fn blocked_ports(&self) -> impl Iterator<Item = &FirewallPortStatus> {
+        self.ports
+            .iter()
+            .filter(|p| p.state == FirewallRuleState::Blocked)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InstallationAction {
+    RegisterDriver,
+    UnregisterDriver,
+    AddFirewallRules,
+    RemoveFirewallRules,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InstallationActionResult {
+    pub action: InstallationAction,
+    pub error: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub enum ServerRequest {
     Log(LogEntry),
     GetSession,
     UpdateSession(Box<SessionConfig>),
     SetValues(Vec<PathValuePair>),
+    // Applied until the stream ends, without saving them in the session
+    SetTemporaryValues(Vec<PathValuePair>),
+    // The GPUs are detected by the dashboard, the streamer has no graphics context to query them
+    ApplyGpuDefaults(Vec<GpuVendor>),
+    // Applies the recommended defaults for the headset model of the client again
+    ApplyDevicePreset(String),
     UpdateClientList {
         hostname: String,
         action: ClientListAction,
@@ -357,12 +793,20 @@ pub enum ServerRequest {
     GetAudioDevices,
     CaptureFrame,
     InsertIdr,
+    // Recenters the stream of the connected client
+    Recenter,
+    // Pauses or resumes the video and the game audio of the connected client
+    SetStreamPaused(bool),
     StartRecording,
     StopRecording,
+    StartTimingCapture,
+    StopTimingCapture,
     FirewallRules(FirewallRulesAction),
+    CheckFirewall,
     RegisterAlvrDriver,
     UnregisterDriver(PathBuf),
     GetDriverList,
+    CheckSteamvr,
     RestartSteamvr,
     ShutdownSteamvr,
 }
@@ -374,3 +818,34 @@ pub struct ViewParams {
     pub pose: Pose,
     pub fov: Fov,
 }
+
+#[cfg(test)]
+mod tests {
+    // The packet, session and settings definitions are part of the protocol ID, so that builds with
+    // different definitions refuse to connect instead of failing to decode packets mid-session.
+    // Changing them breaks compatibility with the released clients and streamers: update this hash
+    // only once that is intended
+    const ACKNOWLEDGED_WIRE_SCHEMA_HASH: u64 = 0x0d418471a3638ef0;
+
+    #[test]
+    fn test_wire_schema_acknowledged() {
+        assert_eq!(
+            super::WIRE_SCHEMA_HASH,
+            ACKNOWLEDGED_WIRE_SCHEMA_HASH,
+            "The packet definitions changed, which changes the protocol ID. Set \
+            ACKNOWLEDGED_WIRE_SCHEMA_HASH to {:#018x} to acknowledge it. Current schema:\n{}",
+            super::WIRE_SCHEMA_HASH,
+            super::WIRE_SCHEMA
+        );
+    }
+
+    #[test]
+    fn test_pairing_proof() {
+        let proof = super::pairing_proof(1, "123456");
+
+        assert_eq!(proof.len(), 64);
+        assert_eq!(proof, super::pairing_proof(1, "123456"));
+        assert_ne!(proof, super::pairing_proof(2, "123456"));
+        assert_ne!(proof, super::pairing_proof(1, "123457"));
+    }
+}
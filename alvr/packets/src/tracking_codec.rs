@@ -0,0 +1,393 @@
+// Fixed layout of the tracking packets, used instead of bincode on the TRACKING_FIXED stream. The
+// stream is sent hundreds of times per second, this is encoded in place into the recycled buffers
+// of the stream sender without any allocation.
+//
+// Layout, little endian:
+// - target timestamp in nanoseconds: u64
+// - flags of the optional sections: u8
+// - device motions count: varint, then for each motion the device ID (u64), the orientation
+//   (4 f32), the position, the linear and the angular velocity (3 f32 each)
+// - the present hand skeletons: 26 poses each
+// - the present eye gazes: one pose each
+// - the present face expressions: varint count, then the f32 values
+
+use crate::{FaceData, Tracking};
+use alvr_common::{
+    anyhow::{bail, Result},
+    glam::{Quat, Vec3},
+    DeviceMotion, Pose,
+};
+use std::{mem, time::Duration};
+
+const LEFT_HAND_SKELETON: u8 = 1 << 0;
+const RIGHT_HAND_SKELETON: u8 = 1 << 1;
+const LEFT_EYE_GAZE: u8 = 1 << 2;
+const RIGHT_EYE_GAZE: u8 = 1 << 3;
+const FB_FACE_EXPRESSION: u8 = 1 << 4;
+const HTC_EYE_EXPRESSION: u8 = 1 << 5;
+const HTC_LIP_EXPRESSION: u8 = 1 << 6;
+
+const POSE_SIZE: usize = 7 * mem::size_of::<f32>();
+const DEVICE_MOTION_SIZE: usize = mem::size_of::<u64>() + POSE_SIZE + 6 * mem::size_of::<f32>();
+
+fn varint_size(mut value: usize) -> usize {
+    let mut size = 1;
+    while value >= 0x80 {
+        value >>= 7;
+        size += 1;
+    }
+
+    size
+}
+
+fn expressions(face_data: &FaceData) -> [(u8, &Option<Vec<f32>>); 3] {
+    [
+        (FB_FACE_EXPRESSION, &face_data.fb_face_expression),
+        (HTC_EYE_EXPRESSION, &face_data.htc_eye_expression),
+        (HTC_LIP_EXPRESSION, &face_data.htc_lip_expression),
+    ]
+}
+
+pub fn encoded_tracking_size(tracking: &Tracking) -> usize {
+    let skeletons_size = tracking.hand_skeletons.iter().flatten().count() * 26 * POSE_SIZE;
+    let gazes_size = tracking.face_data.eye_gazes.iter().flatten().count() * POSE_SIZE;
+    let expressions_size = expressions(&tracking.face_data)
+        .iter()
+        .filter_map(|(_, expression)| expression.as_ref())
+        .map(|values| varint_size(values.len()) + values.len() * mem::size_of::<f32>())
+        .sum::<usize>();
+
+    mem::size_of::<u64>()
+        + mem::size_of::<u8>()
+        + varint_size(tracking.device_motions.len())
+        + tracking.device_motions.len() * DEVICE_MOTION_SIZE
+        + skeletons_size
+        + gazes_size
+        + expressions_size
+}
+
+struct Writer<'a> {
+    buffer: &'a mut [u8],
+    cursor: usize,
+}
+
+impl Writer<'_> {
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.buffer[self.cursor..][..bytes.len()].copy_from_slice(bytes);
+        self.cursor += bytes.len();
+    }
+
+    fn varint(&mut self, mut value: usize) {
+        while value >= 0x80 {
+            self.bytes(&[(value as u8 & 0x7f) | 0x80]);
+            value >>= 7;
+        }
+        self.bytes(&[value as u8]);
+    }
+
+    fn f32s(&mut self, values: &[f32]) {
+        for value in values {
+            self.bytes(&value.to_le_bytes());
+        }
+    }
+
+    fn pose(&mut self, pose: &Pose) {
+        self.f32s(&pose.orientation.to_array());
+        self.f32s(&pose.position.to_array());
+    }
+}
+
+// The buffer must be encoded_tracking_size() bytes long
+pub fn encode_tracking(tracking: &Tracking, buffer: &mut [u8]) {
+    let face_data = &tracking.face_data;
+
+    let mut flags = 0;
+    for (flag, present) in [
+        (LEFT_HAND_SKELETON, tracking.hand_skeletons[0].is_some()),
+        (RIGHT_HAND_SKELETON, tracking.hand_skeletons[1].is_some()),
+        (LEFT_EYE_GAZE, face_data.eye_gazes[0].is_some()),
+        (RIGHT_EYE_GAZE, face_data.eye_gazes[1].is_some()),
+    ] {
+        if present {
+            flags |= flag;
+        }
+    }
+    for (flag, expression) in expressions(face_data) {
+        if expression.is_some() {
+            flags |= flag;
+        }
+    }
+
+    let mut writer = Writer { buffer, cursor: 0 };
+    writer.bytes(&(tracking.target_timestamp.as_nanos() as u64).to_le_bytes());
+    writer.bytes(&[flags]);
+
+    writer.varint(tracking.device_motions.len());
+    for (id, motion) in &tracking.device_motions {
+        writer.bytes(&id.to_le_bytes());
+        writer.pose(&motion.pose);
+        writer.f32s(&motion.linear_velocity.to_array());
+        writer.f32s(&motion.angular_velocity.to_array());
+    }
+
+    for skeleton in tracking.hand_skeletons.iter().flatten() {
+        for pose in skeleton {
+            writer.pose(pose);
+        }
+    }
+    for gaze in face_data.eye_gazes.iter().flatten() {
+        writer.pose(gaze);
+    }
+    for (_, expression) in expressions(face_data) {
+        if let Some(values) = expression {
+            writer.varint(values.len());
+            writer.f32s(values);
+        }
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N]> {
+        if self.data.len() < N {
+            bail!("Truncated tracking packet");
+        }
+        let (bytes, rest) = self.data.split_at(N);
+        self.data = rest;
+
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn varint(&mut self) -> Result<usize> {
+        let mut value = 0;
+        for shift in (0..usize::BITS).step_by(7) {
+            let [byte] = self.bytes()?;
+            value |= ((byte & 0x7f) as usize) << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        bail!("Invalid varint in tracking packet")
+    }
+
+    // Checks the count against the remaining data before anything is allocated for it
+    fn count(&mut self, item_size: usize) -> Result<usize> {
+        let count = self.varint()?;
+        if count > self.data.len() / item_size {
+            bail!("Invalid count in tracking packet");
+        }
+
+        Ok(count)
+    }
+
+    fn f32(&mut self) -> Result<f32> {
+        Ok(f32::from_le_bytes(self.bytes()?))
+    }
+
+    fn vec3(&mut self) -> Result<Vec3> {
+        Ok(Vec3::new(self.f32()?, self.f32()?, self.f32()?))
+    }
+
+    fn pose(&mut self) -> Result<Pose> {
+        let orientation = Quat::from_xyzw(self.f32()?, self.f32()?, self.f32()?, self.f32()?);
+
+        Ok(Pose {
+            orientation,
+            position: self.vec3()?,
+        })
+    }
+}
+
+pub fn decode_tracking(data: &[u8]) -> Result<Tracking> {
+    let mut reader = Reader { data };
+
+    let target_timestamp = Duration::from_nanos(u64::from_le_bytes(reader.bytes()?));
+    let [flags] = reader.bytes()?;
+
+    let motions_count = reader.count(DEVICE_MOTION_SIZE)?;
+    let mut device_motions = Vec::with_capacity(motions_count);
+    for _ in 0..motions_count {
+        let id = u64::from_le_bytes(reader.bytes()?);
+        device_motions.push((
+            id,
+            DeviceMotion {
+                pose: reader.pose()?,
+                linear_velocity: reader.vec3()?,
+                angular_velocity: reader.vec3()?,
+            },
+        ));
+    }
+
+    let mut hand_skeletons = [None, None];
+    for (skeleton, flag) in hand_skeletons
+        .iter_mut()
+        .zip([LEFT_HAND_SKELETON, RIGHT_HAND_SKELETON])
+    {
+        if flags & flag != 0 {
+            let mut poses = [Pose::default(); 26];
+            for pose in &mut poses {
+                *pose = reader.pose()?;
+            }
+            *skeleton = Some(poses);
+        }
+    }
+
+    let mut face_data = FaceData::default();
+    for (gaze, flag) in face_data
+        .eye_gazes
+        .iter_mut()
+        .zip([LEFT_EYE_GAZE, RIGHT_EYE_GAZE])
+    {
+        if flags & flag != 0 {
+            *gaze = Some(reader.pose()?);
+        }
+    }
+    for (expression, flag) in [
+        (&mut face_data.fb_face_expression, FB_FACE_EXPRESSION),
+        (&mut face_data.htc_eye_expression, HTC_EYE_EXPRESSION),
+        (&mut face_data.htc_lip_expression, HTC_LIP_EXPRESSION),
+    ] {
+        if flags & flag != 0 {
+            let count = reader.count(mem::size_of::<f32>())?;
+            *expression = Some((0..count).map(|_| reader.f32()).collect::<Result<_>>()?);
+        }
+    }
+
+    if !reader.data.is_empty() {
+        bail!("Unexpected data at the end of the tracking packet");
+    }
+
+    Ok(Tracking {
+        target_timestamp,
+        device_motions,
+        hand_skeletons,
+        face_data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // xorshift64, the tests must not depend on the seed of a global generator
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn bool(&mut self) -> bool {
+            self.next() % 2 == 0
+        }
+
+        fn f32(&mut self) -> f32 {
+            (self.next() % 2_000_000) as f32 / 1000.0 - 1000.0
+        }
+
+        fn pose(&mut self) -> Pose {
+            Pose {
+                orientation: Quat::from_xyzw(self.f32(), self.f32(), self.f32(), self.f32()),
+                position: Vec3::new(self.f32(), self.f32(), self.f32()),
+            }
+        }
+
+        fn expression(&mut self) -> Option<Vec<f32>> {
+            self.bool()
+                .then(|| (0..self.next() % 200).map(|_| self.f32()).collect())
+        }
+
+        fn tracking(&mut self) -> Tracking {
+            let device_motions = (0..self.next() % 20)
+                .map(|_| {
+                    (
+                        self.next(),
+                        DeviceMotion {
+                            pose: self.pose(),
+                            linear_velocity: Vec3::new(self.f32(), self.f32(), self.f32()),
+                            angular_velocity: Vec3::new(self.f32(), self.f32(), self.f32()),
+                        },
+                    )
+                })
+                .collect();
+            let mut skeleton = || self.bool().then(|| [(); 26].map(|_| self.pose()));
+            let hand_skeletons = [skeleton(), skeleton()];
+
+            Tracking {
+                target_timestamp: Duration::from_nanos(self.next() >> 1),
+                device_motions,
+                hand_skeletons,
+                face_data: FaceData {
+                    eye_gazes: [
+                        self.bool().then(|| self.pose()),
+                        self.bool().then(|| self.pose()),
+                    ],
+                    fb_face_expression: self.expression(),
+                    htc_eye_expression: self.expression(),
+                    htc_lip_expression: self.expression(),
+                },
+            }
+        }
+    }
+
+    fn encode(tracking: &Tracking) -> Vec<u8> {
+        let mut buffer = vec![0; encoded_tracking_size(tracking)];
+        encode_tracking(tracking, &mut buffer);
+
+        buffer
+    }
+
+    #[test]
+    fn test_round_trip_matches_serde() {
+        let mut random = Random(0x5eed);
+
+        for _ in 0..1000 {
+            let tracking = random.tracking();
+            let decoded = decode_tracking(&encode(&tracking)).unwrap();
+
+            // Compares the same logical data through the serde encoding
+            assert_eq!(
+                bincode::serialize(&decoded).unwrap(),
+                bincode::serialize(&tracking).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_default_tracking() {
+        let tracking = Tracking::default();
+        let decoded = decode_tracking(&encode(&tracking)).unwrap();
+
+        assert_eq!(
+            bincode::serialize(&decoded).unwrap(),
+            bincode::serialize(&tracking).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_packets_are_rejected() {
+        let mut random = Random(0xbad);
+        let encoded = encode(&random.tracking());
+
+        for size in 0..encoded.len() {
+            assert!(decode_tracking(&encoded[..size]).is_err());
+        }
+
+        let mut extended = encoded.clone();
+        extended.push(0);
+        assert!(decode_tracking(&extended).is_err());
+
+        // A huge motions count must not be allocated
+        let mut huge_count = encoded[..9].to_vec();
+        huge_count.extend_from_slice(&[0xff, 0xff, 0xff, 0xff, 0x0f]);
+        assert!(decode_tracking(&huge_count).is_err());
+    }
+}
@@ -0,0 +1,383 @@
+// Delta mode of the tracking stream. Each delta only depends on the last keyframe, so a lost delta
+// does not affect the following ones. Devices that did not move since the keyframe are omitted.
+
+use crate::{FaceData, Tracking};
+use alvr_common::{
+    glam::{Quat, Vec3, Vec4},
+    DeviceMotion, Pose,
+};
+use serde::{Deserialize, Serialize};
+use std::{mem, time::Duration};
+
+// A receiver that lost a keyframe also requests a new one, this only bounds the wait if the
+// request is lost too
+const KEYFRAME_INTERVAL: u32 = 60;
+// Positions within ±3.2 m of the keyframe can be encoded. The error is at most half a step
+const POSITION_STEP_M: f32 = 0.0001;
+// Maximum difference of the quaternion components, about 0.01°. The angle computed with acos is
+// too imprecise for this
+const ORIENTATION_EPSILON: f32 = 0.0001;
+const VELOCITY_EPSILON: f32 = 0.001;
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct DeviceMotionDelta {
+    // Offset from the position in the keyframe, in steps of POSITION_STEP_M
+    pub position_offset: [i16; 3],
+    pub orientation: Quat,
+    pub linear_velocity: Vec3,
+    pub angular_velocity: Vec3,
+}
+
+impl DeviceMotionDelta {
+    fn apply(&self, reference: &DeviceMotion) -> DeviceMotion {
+        let [x, y, z] = self.position_offset;
+
+        DeviceMotion {
+            pose: Pose {
+                orientation: self.orientation,
+                position: reference.pose.position
+                    + Vec3::new(x as f32, y as f32, z as f32) * POSITION_STEP_M,
+            },
+            linear_velocity: self.linear_velocity,
+            angular_velocity: self.angular_velocity,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum TrackingDeltaPacket {
+    Keyframe {
+        index: u32,
+        tracking: Tracking,
+    },
+    Delta {
+        keyframe_index: u32,
+        target_timestamp: Duration,
+        // Devices missing from this list have the same motion as in the keyframe
+        device_motions: Vec<(u64, DeviceMotionDelta)>,
+        hand_skeletons: [Option<[Pose; 26]>; 2],
+        face_data: FaceData,
+    },
+}
+
+fn orientation_distance(a: Quat, b: Quat) -> f32 {
+    // Both signs of a quaternion represent the same rotation
+    let b = if a.dot(b) < 0.0 { -b } else { b };
+
+    (Vec4::from(a) - Vec4::from(b)).abs().max_element()
+}
+
+fn has_moved(reference: &DeviceMotion, motion: &DeviceMotion) -> bool {
+    (motion.pose.position - reference.pose.position)
+        .abs()
+        .max_element()
+        > POSITION_STEP_M / 2.0
+        || orientation_distance(motion.pose.orientation, reference.pose.orientation)
+            > ORIENTATION_EPSILON
+        || motion.linear_velocity.distance(reference.linear_velocity) > VELOCITY_EPSILON
+        || motion.angular_velocity.distance(reference.angular_velocity) > VELOCITY_EPSILON
+}
+
+// None if the tracked devices changed since the keyframe or if a device moved out of the range of
+// the offsets
+fn encode_motions(
+    keyframe_motions: &[(u64, DeviceMotion)],
+    motions: &[(u64, DeviceMotion)],
+) -> Option<Vec<(u64, DeviceMotionDelta)>> {
+    if motions.len() != keyframe_motions.len() {
+        return None;
+    }
+
+    let mut deltas = vec![];
+    for (id, motion) in motions {
+        let (_, reference) = keyframe_motions
+            .iter()
+            .find(|(keyframe_id, _)| keyframe_id == id)?;
+
+        if !has_moved(reference, motion) {
+            continue;
+        }
+
+        let offset = ((motion.pose.position - reference.pose.position) / POSITION_STEP_M).round();
+        if offset.abs().max_element() > i16::MAX as f32 {
+            return None;
+        }
+
+        deltas.push((
+            *id,
+            DeviceMotionDelta {
+                position_offset: [offset.x as i16, offset.y as i16, offset.z as i16],
+                orientation: motion.pose.orientation,
+                linear_velocity: motion.linear_velocity,
+                angular_velocity: motion.angular_velocity,
+            },
+        ));
+    }
+
+    Some(deltas)
+}
+
+#[derive(Default)]
+pub struct TrackingDeltaEncoder {
+    keyframe_index: u32,
+    keyframe_motions: Option<Vec<(u64, DeviceMotion)>>,
+    deltas_since_keyframe: u32,
+}
+
+impl TrackingDeltaEncoder {
+    pub fn encode(&mut self, tracking: Tracking) -> TrackingDeltaPacket {
+        if let Some(keyframe_motions) = &self.keyframe_motions {
+            if self.deltas_since_keyframe < KEYFRAME_INTERVAL {
+                if let Some(device_motions) =
+                    encode_motions(keyframe_motions, &tracking.device_motions)
+                {
+                    self.deltas_since_keyframe += 1;
+
+                    return TrackingDeltaPacket::Delta {
+                        keyframe_index: self.keyframe_index,
+                        target_timestamp: tracking.target_timestamp,
+                        device_motions,
+                        hand_skeletons: tracking.hand_skeletons,
+                        face_data: tracking.face_data,
+                    };
+                }
+            }
+        }
+
+        self.keyframe_index = self.keyframe_index.wrapping_add(1);
+        self.keyframe_motions = Some(tracking.device_motions.clone());
+        self.deltas_since_keyframe = 0;
+
+        TrackingDeltaPacket::Keyframe {
+            index: self.keyframe_index,
+            tracking,
+        }
+    }
+
+    // The next packet is a keyframe
+    pub fn request_keyframe(&mut self) {
+        self.keyframe_motions = None;
+    }
+}
+
+#[derive(Default)]
+pub struct TrackingDeltaDecoder {
+    keyframe: Option<(u32, Vec<(u64, DeviceMotion)>)>,
+    requested_keyframe_index: Option<u32>,
+    keyframe_request: bool,
+}
+
+impl TrackingDeltaDecoder {
+    // Returns None for the deltas of a keyframe that was not received
+    pub fn decode(&mut self, packet: TrackingDeltaPacket) -> Option<Tracking> {
+        match packet {
+            TrackingDeltaPacket::Keyframe { index, tracking } => {
+                self.keyframe = Some((index, tracking.device_motions.clone()));
+
+                Some(tracking)
+            }
+            TrackingDeltaPacket::Delta {
+                keyframe_index,
+                target_timestamp,
+                device_motions,
+                hand_skeletons,
+                face_data,
+            } => {
+                let keyframe_motions = match &self.keyframe {
+                    Some((index, motions)) if *index == keyframe_index => motions,
+                    _ => {
+                        // Requested once per missing keyframe
+                        if self.requested_keyframe_index != Some(keyframe_index) {
+                            self.requested_keyframe_index = Some(keyframe_index);
+                            self.keyframe_request = true;
+                        }
+
+                        return None;
+                    }
+                };
+
+                let device_motions = keyframe_motions
+                    .iter()
+                    .map(|(id, reference)| {
+                        let motion = device_motions
+                            .iter()
+                            .find(|(delta_id, _)| delta_id == id)
+                            .map(|(_, delta)| delta.apply(reference))
+                            .unwrap_or(*reference);
+
+                        (*id, motion)
+                    })
+                    .collect();
+
+                Some(Tracking {
+                    target_timestamp,
+                    device_motions,
+                    hand_skeletons,
+                    face_data,
+                })
+            }
+        }
+    }
+
+    // True once after a delta referenced a keyframe that was not received
+    pub fn take_keyframe_request(&mut self) -> bool {
+        mem::take(&mut self.keyframe_request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEAD_ID: u64 = 1;
+    const LEFT_HAND_ID: u64 = 2;
+    const RIGHT_HAND_ID: u64 = 3;
+
+    fn motion(position: Vec3, yaw: f32) -> DeviceMotion {
+        DeviceMotion {
+            pose: Pose {
+                orientation: Quat::from_rotation_y(yaw),
+                position,
+            },
+            linear_velocity: Vec3::ZERO,
+            angular_velocity: Vec3::ZERO,
+        }
+    }
+
+    // The head moves on every sample, the controllers only move on the samples in moving_hands
+    fn tracking(sample: u32, moving_hands: bool) -> Tracking {
+        let t = sample as f32 / 500.0;
+        let hand_offset = if moving_hands { t.sin() * 0.3 } else { 0.0 };
+
+        Tracking {
+            target_timestamp: Duration::from_micros(sample as u64 * 2000),
+            device_motions: vec![
+                (HEAD_ID, motion(Vec3::new(t.sin(), 1.6, t.cos()), t)),
+                (
+                    LEFT_HAND_ID,
+                    motion(Vec3::new(-0.2 + hand_offset, 1.0, -0.3), 0.5),
+                ),
+                (
+                    RIGHT_HAND_ID,
+                    motion(Vec3::new(0.2, 1.0 + hand_offset, -0.3), -0.5),
+                ),
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn assert_close(expected: &Tracking, decoded: &Tracking) {
+        assert_eq!(expected.target_timestamp, decoded.target_timestamp);
+        assert_eq!(expected.device_motions.len(), decoded.device_motions.len());
+
+        for ((expected_id, expected), (id, motion)) in
+            expected.device_motions.iter().zip(&decoded.device_motions)
+        {
+            assert_eq!(expected_id, id);
+            assert!(
+                (expected.pose.position - motion.pose.position)
+                    .abs()
+                    .max_element()
+                    <= POSITION_STEP_M / 2.0 + 1e-5
+            );
+            assert!(
+                orientation_distance(expected.pose.orientation, motion.pose.orientation)
+                    <= ORIENTATION_EPSILON
+            );
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut encoder = TrackingDeltaEncoder::default();
+        let mut decoder = TrackingDeltaDecoder::default();
+
+        let mut deltas_count = 0;
+        for sample in 0..500 {
+            let packet = encoder.encode(tracking(sample, sample % 3 == 0));
+            if let TrackingDeltaPacket::Delta { .. } = &packet {
+                deltas_count += 1;
+            }
+
+            let decoded = decoder.decode(packet).unwrap();
+            assert_close(&tracking(sample, sample % 3 == 0), &decoded);
+        }
+
+        assert!(deltas_count > 400);
+        assert!(!decoder.take_keyframe_request());
+    }
+
+    #[test]
+    fn test_device_changes_send_keyframe() {
+        let mut encoder = TrackingDeltaEncoder::default();
+        encoder.encode(tracking(0, false));
+
+        let mut lost_controller = tracking(1, false);
+        lost_controller.device_motions.pop();
+        assert!(matches!(
+            encoder.encode(lost_controller),
+            TrackingDeltaPacket::Keyframe { .. }
+        ));
+
+        // Too far from the keyframe to be encoded as an offset
+        let mut far = tracking(2, false);
+        far.device_motions.pop();
+        far.device_motions[0].1.pose.position += Vec3::X * 10.0;
+        assert!(matches!(
+            encoder.encode(far),
+            TrackingDeltaPacket::Keyframe { .. }
+        ));
+    }
+
+    #[test]
+    fn test_resynchronization() {
+        let mut encoder = TrackingDeltaEncoder::default();
+        let mut decoder = TrackingDeltaDecoder::default();
+
+        decoder.decode(encoder.encode(tracking(0, false))).unwrap();
+        decoder.decode(encoder.encode(tracking(1, false))).unwrap();
+
+        // The keyframe is lost
+        encoder.request_keyframe();
+        assert!(matches!(
+            encoder.encode(tracking(2, false)),
+            TrackingDeltaPacket::Keyframe { .. }
+        ));
+
+        assert!(decoder.decode(encoder.encode(tracking(3, false))).is_none());
+        assert!(decoder.take_keyframe_request());
+        assert!(decoder.decode(encoder.encode(tracking(4, false))).is_none());
+        assert!(!decoder.take_keyframe_request());
+
+        encoder.request_keyframe();
+        let decoded = decoder.decode(encoder.encode(tracking(5, false))).unwrap();
+        assert_close(&tracking(5, false), &decoded);
+        let decoded = decoder.decode(encoder.encode(tracking(6, false))).unwrap();
+        assert_close(&tracking(6, false), &decoded);
+    }
+
+    #[test]
+    fn test_bandwidth_savings() {
+        const SAMPLES: u32 = 5000;
+        const MAX_SIZE_RATIO: f64 = 0.75;
+
+        let mut encoder = TrackingDeltaEncoder::default();
+        let mut full_size = 0;
+        let mut delta_size = 0;
+        for sample in 0..SAMPLES {
+            // Controllers resting on a table most of the time
+            let moving_hands = sample % 10 == 0;
+
+            full_size += bincode::serialized_size(&tracking(sample, moving_hands)).unwrap();
+            delta_size +=
+                bincode::serialized_size(&encoder.encode(tracking(sample, moving_hands))).unwrap();
+        }
+
+        let size_ratio = delta_size as f64 / full_size as f64;
+        assert!(
+            size_ratio < MAX_SIZE_RATIO,
+            "Delta packets are {:.0}% of the full size",
+            size_ratio * 100.0
+        );
+    }
+}
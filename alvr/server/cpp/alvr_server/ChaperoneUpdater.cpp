@@ -1,7 +1,9 @@
 #include "ALVR-common/packet_types.h"
 #include "Logger.h"
 #include "bindings.h"
+#include <cstring>
 #include <mutex>
+#include <vector>
 
 #ifndef __APPLE__
 // Workaround symbol clash in openvr.h / openvr_driver.h
@@ -52,26 +54,58 @@ bool IsOpenvrClientReady() {
     return isOpenvrInit;
 }
 
-void _SetChaperoneArea(float areaWidth, float areaHeight) {
+unsigned int GetSceneApplicationKey(char *outKey, unsigned int bufferSize) {
 #ifndef __APPLE__
     std::unique_lock<std::mutex> lock(chaperone_mutex);
 
+    if (!isOpenvrInit) {
+        return 0;
+    }
+
+    auto processId = vr::VRApplications()->GetCurrentSceneProcessId();
+    if (processId == 0) {
+        return 0;
+    }
+
+    auto error = vr::VRApplications()->GetApplicationKeyByProcessId(processId, outKey, bufferSize);
+    if (error != vr::VRApplicationError_None) {
+        return 0;
+    }
+
+    return strnlen(outKey, bufferSize);
+#else
+    return 0;
+#endif
+}
+
+#ifndef __APPLE__
+// Height of the walls of the collision bounds
+const float BOUNDS_HEIGHT = 2.5f;
+
+// Must be called with chaperone_mutex locked. The whole working copy is replaced, so that a new
+// boundary does not keep walls of the previous one
+void CommitChaperone(const std::vector<vr::HmdVector2_t> &perimeter,
+                     float areaWidth,
+                     float areaHeight) {
     const vr::HmdMatrix34_t MATRIX_IDENTITY = {
         {{1.0, 0.0, 0.0, 0.0}, {0.0, 1.0, 0.0, 0.0}, {0.0, 0.0, 1.0, 0.0}}};
 
-    float perimeterPoints[4][2];
-
-    perimeterPoints[0][0] = -1.0f * areaWidth;
-    perimeterPoints[0][1] = -1.0f * areaHeight;
-    perimeterPoints[1][0] = -1.0f * areaWidth;
-    perimeterPoints[1][1] = 1.0f * areaHeight;
-    perimeterPoints[2][0] = 1.0f * areaWidth;
-    perimeterPoints[2][1] = 1.0f * areaHeight;
-    perimeterPoints[3][0] = 1.0f * areaWidth;
-    perimeterPoints[3][1] = -1.0f * areaHeight;
+    std::vector<vr::HmdQuad_t> walls;
+    for (size_t i = 0; i < perimeter.size(); i++) {
+        auto &start = perimeter[i];
+        auto &end = perimeter[(i + 1) % perimeter.size()];
+
+        vr::HmdQuad_t wall;
+        wall.vCorners[0] = {start.v[0], 0.0f, start.v[1]};
+        wall.vCorners[1] = {start.v[0], BOUNDS_HEIGHT, start.v[1]};
+        wall.vCorners[2] = {end.v[0], BOUNDS_HEIGHT, end.v[1]};
+        wall.vCorners[3] = {end.v[0], 0.0f, end.v[1]};
+        walls.push_back(wall);
+    }
 
     vr::VRChaperoneSetup()->SetWorkingPerimeter(
-        reinterpret_cast<vr::HmdVector2_t *>(perimeterPoints), 4);
+        const_cast<vr::HmdVector2_t *>(perimeter.data()), perimeter.size());
+    vr::VRChaperoneSetup()->SetWorkingCollisionBoundsInfo(walls.data(), walls.size());
     vr::VRChaperoneSetup()->SetWorkingStandingZeroPoseToRawTrackingPose(&MATRIX_IDENTITY);
     vr::VRChaperoneSetup()->SetWorkingSeatedZeroPoseToRawTrackingPose(&MATRIX_IDENTITY);
     vr::VRChaperoneSetup()->SetWorkingPlayAreaSize(areaWidth, areaHeight);
@@ -80,6 +114,35 @@ void _SetChaperoneArea(float areaWidth, float areaHeight) {
     // Hide SteamVR Chaperone
     vr::VRSettings()->SetFloat(
         vr::k_pch_CollisionBounds_Section, vr::k_pch_CollisionBounds_FadeDistance_Float, 0.0f);
+}
+#endif
+
+void _SetChaperoneArea(float areaWidth, float areaHeight) {
+#ifndef __APPLE__
+    std::unique_lock<std::mutex> lock(chaperone_mutex);
+
+    std::vector<vr::HmdVector2_t> perimeter = {
+        {-1.0f * areaWidth, -1.0f * areaHeight},
+        {-1.0f * areaWidth, 1.0f * areaHeight},
+        {1.0f * areaWidth, 1.0f * areaHeight},
+        {1.0f * areaWidth, -1.0f * areaHeight},
+    };
+
+    CommitChaperone(perimeter, areaWidth, areaHeight);
+#endif
+}
+
+void _SetChaperoneBoundary(
+    const FfiPlayspacePoint *points, int pointsCount, float areaWidth, float areaHeight) {
+#ifndef __APPLE__
+    std::unique_lock<std::mutex> lock(chaperone_mutex);
+
+    std::vector<vr::HmdVector2_t> perimeter;
+    for (int i = 0; i < pointsCount; i++) {
+        perimeter.push_back({points[i].x, points[i].z});
+    }
+
+    CommitChaperone(perimeter, areaWidth, areaHeight);
 #endif
 }
 
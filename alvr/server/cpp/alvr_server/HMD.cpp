@@ -223,7 +223,8 @@ void Hmd::OnPoseUpdated(uint64_t targetTimestampNs, FfiDeviceMotion motion) {
 #if !defined(_WIN32) && !defined(__APPLE__)
     // This has to be set after initialization is done, because something in vrcompositor is
     // setting it to 90Hz in the meantime
-    if (!m_refreshRateSet && m_encoder && m_encoder->IsConnected()) {
+    auto encoder = GetEncoder();
+    if (!m_refreshRateSet && encoder && encoder->IsConnected()) {
         m_refreshRateSet = true;
         vr::VRProperties()->SetFloatProperty(
             this->prop_container,
@@ -243,9 +244,9 @@ void Hmd::StartStreaming() {
     // Spin up a separate thread to handle the overlapped encoding/transmit step.
     if (IsHMD()) {
 #ifdef _WIN32
-        m_encoder = std::make_shared<CEncoder>();
+        auto encoder = std::make_shared<CEncoder>();
         try {
-            m_encoder->Initialize(m_D3DRender);
+            encoder->Initialize(m_D3DRender);
         } catch (Exception e) {
             Error("Your GPU does not meet the requirements for video encoding. %s %s\n%s %s\n",
                   "If you get this error after changing some settings, you can revert them by",
@@ -253,17 +254,20 @@ void Hmd::StartStreaming() {
                   "Failed to initialize CEncoder:",
                   e.what());
         }
-        m_encoder->Start();
+        encoder->Start();
 
-        m_directModeComponent->SetEncoder(m_encoder);
+        m_directModeComponent->SetEncoder(encoder);
 
 #elif __APPLE__
-        m_encoder = std::make_shared<CEncoder>();
+        auto encoder = std::make_shared<CEncoder>();
 #else
-        m_encoder = std::make_shared<CEncoder>(m_poseHistory);
-        m_encoder->Start();
+        auto encoder = std::make_shared<CEncoder>(m_poseHistory);
+        encoder->Start();
 #endif
-        m_encoder->OnStreamStart();
+        encoder->OnStreamStart();
+
+        std::lock_guard<std::mutex> lock(m_encoderMutex);
+        m_encoder = encoder;
     }
 
     m_streamComponentsInitialized = true;
@@ -271,6 +275,62 @@ void Hmd::StartStreaming() {
 
 void Hmd::StopStreaming() { vr::VRDriverInput()->UpdateBooleanComponent(m_proximity, false, 0.0); }
 
+// Callers keep the encoder alive while they use it, even if it is recreated in the meantime
+std::shared_ptr<CEncoder> Hmd::GetEncoder() {
+    std::lock_guard<std::mutex> lock(m_encoderMutex);
+    return m_encoder;
+}
+
+// Used to apply encoder settings that cannot be changed on the fly, like the codec. The settings
+// are reloaded only once the old encoder is detached from the compositor and stopped, so that they
+// are not read while they change
+void Hmd::RecreateEncoder() {
+    if (!m_streamComponentsInitialized) {
+        Settings::Instance().Load();
+        return;
+    }
+
+#ifdef _WIN32
+    // Waits for the frame being presented
+    m_directModeComponent->SetEncoder(nullptr);
+#endif
+
+    std::shared_ptr<CEncoder> encoder;
+    {
+        std::lock_guard<std::mutex> lock(m_encoderMutex);
+        encoder.swap(m_encoder);
+    }
+    if (encoder) {
+        Debug("Hmd::RecreateEncoder(): Stopping encoder...\n");
+        encoder->Stop();
+        encoder.reset();
+    }
+
+    Settings::Instance().Load();
+
+    m_streamComponentsInitialized = false;
+    StartStreaming();
+}
+
+// Used while the headset is not worn. The encoder stops producing frames and SteamVR sees the HMD
+// as idle, so applications can pause.
+void Hmd::SetStandby(bool standby) {
+    vr::VRDriverInput()->UpdateBooleanComponent(m_proximity, !standby, 0.0);
+    vr::VRServerDriverHost()->VendorSpecificEvent(
+        object_id,
+        standby ? vr::VREvent_TrackedDeviceUserInteractionEnded
+                : vr::VREvent_TrackedDeviceUserInteractionStarted,
+        {},
+        0);
+
+    if (auto encoder = GetEncoder()) {
+        encoder->SetPaused(standby);
+        if (!standby) {
+            encoder->InsertIDR();
+        }
+    }
+}
+
 void Hmd::SetViewsConfig(FfiViewsConfig config) {
     this->views_config = config;
 
@@ -4,6 +4,7 @@
 #include "TrackedDevice.h"
 #include "openvr_driver.h"
 #include <memory>
+#include <mutex>
 #ifdef _WIN32
 #include "platform/win32/OvrDirectModeComponent.h"
 #endif
@@ -37,6 +38,12 @@ class Hmd : public TrackedDevice, public vr::ITrackedDeviceServerDriver, vr::IVR
 
     void StopStreaming();
 
+    std::shared_ptr<CEncoder> GetEncoder();
+
+    void RecreateEncoder();
+
+    void SetStandby(bool standby);
+
     void SetViewsConfig(FfiViewsConfig config);
 
     vr::ETrackedDeviceClass GetDeviceClass() const { return m_deviceClass; }
@@ -57,10 +64,12 @@ class Hmd : public TrackedDevice, public vr::ITrackedDeviceServerDriver, vr::IVR
 
     vr::VRInputComponentHandle_t m_proximity;
 
-    std::shared_ptr<CEncoder> m_encoder;
     std::shared_ptr<PoseHistory> m_poseHistory;
 
   private:
+    // Replaced by RecreateEncoder() while other threads use it, accessed with GetEncoder()
+    std::shared_ptr<CEncoder> m_encoder;
+    std::mutex m_encoderMutex;
     FfiViewsConfig views_config;
 
     bool m_baseComponentsInitialized;
@@ -58,17 +58,14 @@ void Info(const char *format, ...)
 	va_end(args);
 }
 
+// Debug messages are always forwarded, the streamer decides whether to keep them depending on the
+// configured log level
 void Debug(const char *format, ...)
 {
-// Use our define instead of _DEBUG - see build.rs for details.
-#ifdef ALVR_DEBUG_LOG
 	va_list args;
 	va_start(args, format);
 	_log(format, args, LogDebug);
 	va_end(args);
-#else
-	(void)format;
-#endif
 }
 
 void LogPeriod(const char *tag, const char *format, ...)
@@ -5,6 +5,7 @@
 #include "bindings.h"
 #include <mutex>
 #include <string.h>
+#include <vector>
 
 static const char NAL_PREFIX_3B[] = {0x00, 0x00, 0x01};
 static const char NAL_PREFIX_4B[] = {0x00, 0x00, 0x00, 0x01};
@@ -15,6 +16,10 @@ static const unsigned char HEVC_NAL_TYPE_VPS = 32;
 static const unsigned char H264_NAL_TYPE_AUD = 9;
 static const unsigned char HEVC_NAL_TYPE_AUD = 35;
 
+// Video Coding Layer NALs contain the slice data, the other NALs contain parameters or metadata
+static const unsigned char H264_NAL_TYPE_VCL_MAX = 5;
+static const unsigned char HEVC_NAL_TYPE_VCL_MAX = 31;
+
 int8_t getNalPrefixSize(unsigned char *buf) {
     if (memcmp(buf, NAL_PREFIX_3B, sizeof(NAL_PREFIX_3B)) == 0) {
         return sizeof(NAL_PREFIX_3B);
@@ -96,14 +101,10 @@ void processHevcNals(unsigned char *&buf, int &len) {
     }
 }
 
-void ParseFrameNals(
-    int codec, unsigned char *buf, int len, unsigned long long targetTimestampNs, bool isIdr) {
+// Sends the configuration NALs found at the start of the frame and skips them
+void processConfigNals(int codec, unsigned char *&buf, int &len) {
     static bool av1GotFrame = false;
 
-    if ((unsigned)len < sizeof(NAL_PREFIX_4B)) {
-        return;
-    }
-
     if (codec == ALVR_CODEC_H264) {
         processH264Nals(buf, len);
     } else if (codec == ALVR_CODEC_HEVC) {
@@ -112,6 +113,88 @@ void ParseFrameNals(
         av1GotFrame = true;
         SetVideoConfigNals(0, 0, codec);
     }
+}
+
+bool isVclNal(int codec, unsigned char *nal) {
+    if (codec == ALVR_CODEC_H264) {
+        unsigned char nalType = nal[0] & 0x1F;
+        return nalType >= 1 && nalType <= H264_NAL_TYPE_VCL_MAX;
+    } else {
+        unsigned char nalType = (nal[0] >> 1) & 0x3F;
+        return nalType <= HEVC_NAL_TYPE_VCL_MAX;
+    }
+}
+
+/*
+Sends each slice of the frame as a separate video packet. Every packet contains one VCL NAL together
+with the non-VCL NALs that precede it, so that the client can concatenate the packets in slice order
+to obtain the original frame. Used for the encoders that only output whole frames, the others call
+ParseSliceNals() as soon as each slice is encoded.
+*/
+void sendSlices(int codec, unsigned char *buf, int len, unsigned long long targetTimestampNs, bool isIdr) {
+    std::vector<int> sliceStarts = {0};
+    bool foundVcl = false;
+    int nonVclStart = -1;
+
+    int offset = 0;
+    while (offset + (int)sizeof(NAL_PREFIX_4B) < len) {
+        int8_t prefixSize = getNalPrefixSize(buf + offset);
+        if (prefixSize == -1) {
+            offset++;
+            continue;
+        }
+
+        if (isVclNal(codec, buf + offset + prefixSize)) {
+            if (foundVcl) {
+                sliceStarts.push_back(nonVclStart != -1 ? nonVclStart : offset);
+            }
+            foundVcl = true;
+            nonVclStart = -1;
+        } else if (foundVcl && nonVclStart == -1) {
+            nonVclStart = offset;
+        }
+
+        offset += prefixSize + 1;
+    }
+
+    int sliceCount = sliceStarts.size();
+    for (int i = 0; i < sliceCount; i++) {
+        int sliceEnd = i + 1 < sliceCount ? sliceStarts[i + 1] : len;
+        VideoSend(
+            targetTimestampNs, buf + sliceStarts[i], sliceEnd - sliceStarts[i], isIdr, i, sliceCount);
+    }
+}
+
+void ParseFrameNals(
+    int codec, unsigned char *buf, int len, unsigned long long targetTimestampNs, bool isIdr) {
+    if ((unsigned)len < sizeof(NAL_PREFIX_4B)) {
+        return;
+    }
+
+    processConfigNals(codec, buf, len);
+
+    if (Settings::Instance().m_sliceCount > 1 && codec != ALVR_CODEC_AV1) {
+        sendSlices(codec, buf, len, targetTimestampNs, isIdr);
+    } else {
+        VideoSend(targetTimestampNs, buf, len, isIdr, 0, 1);
+    }
+}
+
+void ParseSliceNals(int codec,
+                    unsigned char *buf,
+                    int len,
+                    unsigned long long targetTimestampNs,
+                    bool isIdr,
+                    int sliceIndex,
+                    int sliceCount) {
+    if ((unsigned)len < sizeof(NAL_PREFIX_4B)) {
+        return;
+    }
+
+    // Only the first slice is preceded by the configuration NALs
+    if (sliceIndex == 0) {
+        processConfigNals(codec, buf, len);
+    }
 
-    VideoSend(targetTimestampNs, buf, len, isIdr);
+    VideoSend(targetTimestampNs, buf, len, isIdr, sliceIndex, sliceCount);
 }
@@ -32,8 +32,8 @@ void PoseHistory::OnPoseUpdated(uint64_t targetTimestampNs, FfiDeviceMotion moti
 			m_poseBuffer.push_back(history);
 		}
 	}
-        // The value should match with the client's MAXIMUM_TRACKING_FRAMES in ovr_context.cpp
-	if (m_poseBuffer.size() > 120 * 3) {
+	// About one second of poses at the maximum tracking send rate (MAX_TRACKING_SEND_RATE_HZ)
+	if (m_poseBuffer.size() > 500) {
 		m_poseBuffer.pop_front();
 	}
 }
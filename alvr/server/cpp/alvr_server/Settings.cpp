@@ -64,6 +64,7 @@ void Settings::Load() {
         m_rateControlMode = (uint32_t)config.get("rate_control_mode").get<int64_t>();
         m_fillerData = config.get("filler_data").get<bool>();
         m_entropyCoding = (uint32_t)config.get("entropy_coding").get<int64_t>();
+        m_sliceCount = (uint32_t)config.get("slice_count").get<int64_t>();
         m_use10bitEncoder = config.get("use_10bit_encoder").get<bool>();
         m_useFullRangeEncoding = config.get("use_full_range_encoding").get<bool>();
         m_encodingGamma = config.get("encoding_gamma").get<double>();
@@ -59,6 +59,7 @@ class Settings {
     uint32_t m_rateControlMode;
     bool m_fillerData;
     uint32_t m_entropyCoding;
+    uint32_t m_sliceCount;
     bool m_force_sw_encoding;
     uint32_t m_swThreadCount;
 
@@ -21,6 +21,7 @@
 #include <cmath>
 #include <cstring>
 #include <map>
+#include <mutex>
 #include <optional>
 
 #ifdef __linux__
@@ -34,6 +35,8 @@ vr::HmdMatrix34_t GetRawZeroPose() {
 bool IsOpenvrClientReady();
 #endif
 void _SetChaperoneArea(float areaWidth, float areaHeight);
+void _SetChaperoneBoundary(
+    const FfiPlayspacePoint *points, int pointsCount, float areaWidth, float areaHeight);
 
 vr::EVREventType VendorEvent_ALVRDriverResync = (vr::EVREventType) (vr::VREvent_VendorSpecific_Reserved_Start + ((vr::EVREventType) 0xC0));
 
@@ -77,6 +80,10 @@ class DriverProvider : public vr::IServerTrackedDeviceProvider {
     std::unique_ptr<Hmd> hmd;
     std::unique_ptr<Controller> left_controller, right_controller;
     std::vector<std::unique_ptr<FakeViveTracker>> generic_trackers;
+    // Trackers declared by the client, by device ID. Registered from the control thread while the
+    // tracking thread looks them up
+    std::map<uint64_t, std::unique_ptr<FakeViveTracker>> registered_trackers;
+    std::mutex registered_trackers_mutex;
     bool shutdown_called = false;
 
     std::map<uint64_t, TrackedDevice *> tracked_devices;
@@ -277,7 +284,12 @@ void (*LogDebug)(const char *stringPtr);
 void (*LogPeriodically)(const char *tag, const char *stringPtr);
 void (*DriverReadyIdle)(bool setDefaultChaprone);
 void (*SetVideoConfigNals)(const unsigned char *configBuffer, int len, int codec);
-void (*VideoSend)(unsigned long long targetTimestampNs, unsigned char *buf, int len, bool isIdr);
+void (*VideoSend)(unsigned long long targetTimestampNs,
+                  unsigned char *buf,
+                  int len,
+                  bool isIdr,
+                  int sliceIndex,
+                  int sliceCount);
 void (*HapticsSend)(unsigned long long path, float duration_s, float frequency, float amplitude);
 void (*ShutdownRuntime)();
 unsigned long long (*PathStringToHash)(const char *path);
@@ -322,11 +334,27 @@ void DeinitializeStreaming() {
     }
 }
 
+void RecreateEncoder() {
+    if (g_driver_provider.hmd) {
+        g_driver_provider.hmd->RecreateEncoder();
+    } else {
+        Settings::Instance().Load();
+    }
+}
+
+void SetStandby(bool standby) {
+    if (g_driver_provider.hmd) {
+        g_driver_provider.hmd->SetStandby(standby);
+    }
+}
+
 void SendVSync() { vr::VRServerDriverHost()->VsyncEvent(0.0); }
 
 void RequestIDR() {
-    if (g_driver_provider.hmd && g_driver_provider.hmd->m_encoder) {
-        g_driver_provider.hmd->m_encoder->InsertIDR();
+    if (g_driver_provider.hmd) {
+        if (auto encoder = g_driver_provider.hmd->GetEncoder()) {
+            encoder->InsertIDR();
+        }
     }
 }
 
@@ -345,11 +373,25 @@ void SetTracking(unsigned long long targetTimestampNs,
         } else {
             if (g_driver_provider.left_controller && deviceMotions[i].deviceID == HAND_LEFT_ID) {
                 g_driver_provider.left_controller->onPoseUpdate(
-                    controllerPoseTimeOffsetS, deviceMotions[i], leftHand, controllersTracked);
+                    controllerPoseTimeOffsetS, deviceMotions[i], leftHand, controllersTracked & 1);
             } else if (g_driver_provider.right_controller &&
                        deviceMotions[i].deviceID == HAND_RIGHT_ID) {
-                g_driver_provider.right_controller->onPoseUpdate(
-                    controllerPoseTimeOffsetS, deviceMotions[i], rightHand, controllersTracked);
+                g_driver_provider.right_controller->onPoseUpdate(controllerPoseTimeOffsetS,
+                                                                 deviceMotions[i],
+                                                                 rightHand,
+                                                                 (controllersTracked >> 1) & 1);
+            } else {
+                std::unique_lock<std::mutex> lock(g_driver_provider.registered_trackers_mutex);
+                auto tracker_it = g_driver_provider.registered_trackers.find(deviceMotions[i].deviceID);
+                if (tracker_it != g_driver_provider.registered_trackers.end()) {
+                    auto motion = deviceMotions[i];
+                    tracker_it->second->OnPoseUpdated(
+                        targetTimestampNs,
+                        FfiBodyTracker{0,
+                                       motion.orientation,
+                                       {motion.position[0], motion.position[1], motion.position[2]},
+                                       1});
+                }
             }
         }
     }
@@ -362,7 +404,9 @@ void SetTracking(unsigned long long targetTimestampNs,
 
 void VideoErrorReportReceive() {
     if (g_driver_provider.hmd) {
-        g_driver_provider.hmd->m_encoder->OnPacketLoss();
+        if (auto encoder = g_driver_provider.hmd->GetEncoder()) {
+            encoder->OnPacketLoss();
+        }
     }
 }
 
@@ -427,14 +471,42 @@ void SetButton(unsigned long long buttonID, FfiButtonValue value) {
     }
 }
 
+void RegisterGenericTracker(unsigned long long deviceID, const char *name) {
+    {
+        std::unique_lock<std::mutex> lock(g_driver_provider.registered_trackers_mutex);
+        if (g_driver_provider.registered_trackers.find(deviceID) !=
+            g_driver_provider.registered_trackers.end()) {
+            return;
+        }
+    }
+
+    auto tracker = std::make_unique<FakeViveTracker>(name);
+    if (!vr::VRServerDriverHost()->TrackedDeviceAdded(
+            tracker->GetSerialNumber(), vr::TrackedDeviceClass_GenericTracker, tracker.get())) {
+        Warn("Failed to register Vive tracker (%s)", name);
+        return;
+    }
+
+    // The tracking thread is not blocked while the device is added
+    std::unique_lock<std::mutex> lock(g_driver_provider.registered_trackers_mutex);
+    g_driver_provider.registered_trackers.insert({deviceID, std::move(tracker)});
+}
+
 void SetChaperoneArea(float areaWidth, float areaHeight) {
     _SetChaperoneArea(areaWidth, areaHeight);
 }
 
+void SetChaperoneBoundary(
+    const FfiPlayspacePoint *points, int pointsCount, float areaWidth, float areaHeight) {
+    _SetChaperoneBoundary(points, pointsCount, areaWidth, areaHeight);
+}
+
 void CaptureFrame() {
 #ifndef __APPLE__
-    if (g_driver_provider.hmd && g_driver_provider.hmd->m_encoder) {
-        g_driver_provider.hmd->m_encoder->CaptureFrame();
+    if (g_driver_provider.hmd) {
+        if (auto encoder = g_driver_provider.hmd->GetEncoder()) {
+            encoder->CaptureFrame();
+        }
     }
 #endif
 }
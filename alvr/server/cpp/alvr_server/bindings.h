@@ -27,6 +27,12 @@ struct FfiDeviceMotion {
     float angularVelocity[3];
 };
 
+// Point on the floor plane
+struct FfiPlayspacePoint {
+    float x;
+    float z;
+};
+
 struct FfiBodyTracker {
     unsigned int trackerID;
     FfiQuat orientation;
@@ -120,7 +126,9 @@ extern "C" void (*SetVideoConfigNals)(const unsigned char *configBuffer, int len
 extern "C" void (*VideoSend)(unsigned long long targetTimestampNs,
                              unsigned char *buf,
                              int len,
-                             bool isIdr);
+                             bool isIdr,
+                             int sliceIndex,
+                             int sliceCount);
 extern "C" void (*HapticsSend)(unsigned long long path,
                                float duration_s,
                                float frequency,
@@ -140,12 +148,15 @@ extern "C" void InitializeStreaming();
 extern "C" void DeinitializeStreaming();
 extern "C" void SendVSync();
 extern "C" void RequestIDR();
+extern "C" void RecreateEncoder();
+extern "C" void SetStandby(bool standby);
 extern "C" void SetTracking(unsigned long long targetTimestampNs,
                             float controllerPoseTimeOffsetS,
                             const FfiDeviceMotion *deviceMotions,
                             int motionsCount,
                             const FfiHandSkeleton *leftHand,
                             const FfiHandSkeleton *rightHand,
+                            // Bit 0 for the left controller, bit 1 for the right one
                             unsigned int controllersTracked,
                             const FfiBodyTracker *bodyTrackers,
                             int bodyTrackersCount);
@@ -158,16 +169,31 @@ extern "C" void RegisterButton(unsigned long long buttonID);
 extern "C" void SetViewsConfig(FfiViewsConfig config);
 extern "C" void SetBattery(unsigned long long deviceID, float gauge_value, bool is_plugged);
 extern "C" void SetButton(unsigned long long buttonID, FfiButtonValue value);
+extern "C" void RegisterGenericTracker(unsigned long long deviceID, const char *name);
 
 extern "C" void InitOpenvrClient();
 extern "C" void ShutdownOpenvrClient();
 extern "C" void SetChaperoneArea(float areaWidth, float areaHeight);
+// Returns the length of the key, 0 if no application is running
+extern "C" unsigned int GetSceneApplicationKey(char *outKey, unsigned int bufferSize);
+extern "C" void SetChaperoneBoundary(const FfiPlayspacePoint *points,
+                                     int pointsCount,
+                                     float areaWidth,
+                                     float areaHeight);
 
 extern "C" void CaptureFrame();
 
 // NalParsing.cpp
 void ParseFrameNals(
     int codec, unsigned char *buf, int len, unsigned long long targetTimestampNs, bool isIdr);
+// For encoders that output each slice as soon as it is encoded
+void ParseSliceNals(int codec,
+                    unsigned char *buf,
+                    int len,
+                    unsigned long long targetTimestampNs,
+                    bool isIdr,
+                    int sliceIndex,
+                    int sliceCount);
 
 // CrashHandler.cpp
 void HookCrashHandler();
@@ -199,7 +199,7 @@ void CEncoder::Run() {
 
         m_connected = true;
 
-      fprintf(stderr, "\n\nWe are initalizing Vulkan in CEncoder thread\n\n\n");
+      Info("Initializing Vulkan in the CEncoder thread");
 
       av_log_set_callback(av_logfn);
 
@@ -235,6 +235,11 @@ void CEncoder::Run() {
 
         render.Render(frame_info.image, frame_info.semaphore_value);
 
+        // The frame is still consumed to keep the compositor in sync
+        if (m_paused) {
+          continue;
+        }
+
         if (!valid_timestamps) {
           ReportPresent(pose->targetTimestampNs, 0);
           ReportComposed(pose->targetTimestampNs, 0);
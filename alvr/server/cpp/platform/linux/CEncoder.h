@@ -22,6 +22,7 @@ class CEncoder : public CThread {
     void InsertIDR();
     bool IsConnected() { return m_connected; }
     void CaptureFrame();
+    void SetPaused(bool paused) { m_paused = paused; }
 
   private:
     void GetFds(int client, int (*fds)[6]);
@@ -33,4 +34,5 @@ class CEncoder : public CThread {
     int m_fds[6];
     bool m_connected = false;
     std::atomic_bool m_captureFrame = false;
+    std::atomic_bool m_paused = false;
 };
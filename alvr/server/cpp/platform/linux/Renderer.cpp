@@ -1,4 +1,5 @@
 #include "Renderer.h"
+#include "alvr_server/Logger.h"
 
 #include <array>
 #include <fstream>
@@ -35,7 +36,7 @@ static uint32_t to_drm_format(VkFormat format)
     case VK_FORMAT_R8G8B8A8_UNORM:
         return DRM_FORMAT_ABGR8888;
     default:
-        std::cerr << "Unsupported format " << format << std::endl;
+        Error("Unsupported format %d", format);
         return DRM_FORMAT_INVALID;
     }
 }
@@ -301,11 +302,11 @@ void Renderer::CreateOutput(uint32_t width, uint32_t height, ExternalHandle hand
         vkGetPhysicalDeviceFormatProperties2(m_physDev, m_output.imageInfo.format, &formatProps);
 
         std::vector<uint64_t> imageModifiers;
-        std::cout << "Available modifiers:" << std::endl;
+        Debug("Available modifiers:");
         for (const VkDrmFormatModifierPropertiesEXT &prop : modifierProps) {
-            std::cout << "modifier: " << prop.drmFormatModifier << " planes: " << prop.drmFormatModifierPlaneCount << std::endl;
+            Debug("modifier: %llu planes: %u", (unsigned long long)prop.drmFormatModifier, prop.drmFormatModifierPlaneCount);
             if (!filter_modifier(prop.drmFormatModifier)) {
-                std::cout << " filtered" << std::endl;
+                Debug(" filtered");
                 continue;
             }
 
@@ -404,14 +405,14 @@ void Renderer::CreateOutput(uint32_t width, uint32_t height, ExternalHandle hand
         memoryGetFdInfo.handleType = VK_EXTERNAL_MEMORY_HANDLE_TYPE_DMA_BUF_BIT_EXT;
         VkResult res = d.vkGetMemoryFdKHR(m_dev, &memoryGetFdInfo, &m_output.drm.fd);
         if (res != VK_SUCCESS) {
-            std::cout << "vkGetMemoryFdKHR " << result_to_str(res) << std::endl;
+            Error("vkGetMemoryFdKHR %s", result_to_str(res).c_str());
         } else {
             if (d.haveDrmModifiers) {
                 VkImageDrmFormatModifierPropertiesEXT imageDrmProps = {};
                 imageDrmProps.sType = VK_STRUCTURE_TYPE_IMAGE_DRM_FORMAT_MODIFIER_PROPERTIES_EXT;
                 d.vkGetImageDrmFormatModifierPropertiesEXT(m_dev, m_output.image, &imageDrmProps);
                 if (res != VK_SUCCESS) {
-                    std::cout << "vkGetImageDrmFormatModifierPropertiesEXT " << result_to_str(res) << std::endl;
+                    Error("vkGetImageDrmFormatModifierPropertiesEXT %s", result_to_str(res).c_str());
                 } else {
                     m_output.drm.modifier = imageDrmProps.drmFormatModifier;
                     for (VkDrmFormatModifierPropertiesEXT prop : modifierProps) {
@@ -998,7 +999,7 @@ void Renderer::dumpImage(VkImage image, VkImageView imageView, VkImageLayout ima
     }
     file.close();
 
-    std::cout << "Image saved to \"" << filename << "\"" << std::endl;
+    Info("Image saved to \"%s\"", filename.c_str());
 
     vkUnmapMemory(m_dev, dstMemory);
     vkFreeMemory(m_dev, dstMemory, nullptr);
@@ -1038,7 +1039,7 @@ void RenderPipeline::SetShader(const char *filename)
 {
     std::ifstream is(filename, std::ios::binary | std::ios::in | std::ios::ate);
     if (!is.is_open()) {
-        std::cerr << "Failed to open shader file: " << filename << std::endl;
+        Error("Failed to open shader file: %s", filename);
         return;
     }
     size_t size = is.tellg();
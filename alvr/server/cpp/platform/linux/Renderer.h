@@ -6,11 +6,13 @@
 #include <iostream>
 #include <vulkan/vulkan.h>
 
+#include "alvr_server/Logger.h"
+
 #define VK_CHECK(f) \
 { \
     VkResult res = (f); \
     if (res != VK_SUCCESS) { \
-        std::cerr << Renderer::result_to_str(res) << "at" << __FILE__ << ":" << __LINE__ << std::endl; \
+        Error("Vulkan: %s at %s:%d", Renderer::result_to_str(res).c_str(), __FILE__, __LINE__); \
         throw std::runtime_error("Vulkan: " + Renderer::result_to_str(res) + "at " __FILE__ ":" + std::to_string(__LINE__)); \
     } \
 }
@@ -97,7 +97,7 @@
 				if (m_bExiting)
 					break;
 
-				if (m_FrameRender->GetTexture())
+				if (m_FrameRender->GetTexture() && !m_paused)
 				{
 					m_videoEncoder->Transmit(m_FrameRender->GetTexture().Get(), m_presentationTime, m_targetTimestampNs, m_scheduler.CheckIDRInsertion());
 				}
@@ -139,3 +139,7 @@
 
 		void CEncoder::CaptureFrame() {
 		}
+
+		void CEncoder::SetPaused(bool paused) {
+			m_paused = paused;
+		}
@@ -6,6 +6,7 @@
 #include <d3d11.h>
 #include <wrl.h>
 #include <map>
+#include <atomic>
 #include <d3d11_1.h>
 #include <wincodec.h>
 #include <wincodecsdk.h>
@@ -54,10 +55,13 @@
 
 		void CaptureFrame();
 
+		void SetPaused(bool paused);
+
 	private:
 		CThreadEvent m_newFrameReady, m_encodeFinished;
 		std::shared_ptr<VideoEncoder> m_videoEncoder;
 		bool m_bExiting;
+		std::atomic_bool m_paused = false;
 		uint64_t m_presentationTime;
 		uint64_t m_targetTimestampNs;
 
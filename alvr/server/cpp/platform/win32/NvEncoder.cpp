@@ -10,6 +10,7 @@
 */
 
 #include "NvEncoder.h"
+#include <thread>
 
 #ifndef _WIN32
 #include <cstring>
@@ -493,6 +494,74 @@ void NvEncoder::EncodeFrame(std::vector<std::vector<uint8_t>> &vPacket, NV_ENC_P
     }
 }
 
+void NvEncoder::EncodeFrameSlices(NV_ENC_PIC_PARAMS *pPicParams,
+    const std::function<void(uint8_t *data, uint32_t size, uint32_t sliceIndex)> &onSlice)
+{
+    if (!IsHWEncoderInitialized())
+    {
+        NVENC_THROW_ERROR("Encoder device not found", NV_ENC_ERR_NO_ENCODE_DEVICE);
+    }
+
+    int bfrIdx = m_iToSend % m_nEncoderBuffer;
+
+    MapResources(bfrIdx);
+
+    NVENCSTATUS nvStatus = DoEncode(m_vMappedInputBuffers[bfrIdx], m_vBitstreamOutputBuffer[bfrIdx], pPicParams);
+    if (nvStatus != NV_ENC_SUCCESS)
+    {
+        NVENC_THROW_ERROR("nvEncEncodePicture API failed", nvStatus);
+    }
+    m_iToSend++;
+
+    // There cannot be more slices than macroblocks
+    std::vector<uint32_t> sliceOffsets(((m_nWidth + 15) / 16) * ((m_nHeight + 15) / 16));
+    uint32_t sentSlices = 0;
+    bool completed = false;
+    while (!completed)
+    {
+        NV_ENC_LOCK_BITSTREAM lockBitstreamData = { NV_ENC_LOCK_BITSTREAM_VER };
+        lockBitstreamData.outputBitstream = m_vBitstreamOutputBuffer[bfrIdx];
+        lockBitstreamData.doNotWait = true;
+        lockBitstreamData.sliceOffsets = sliceOffsets.data();
+        nvStatus = m_nvenc.nvEncLockBitstream(m_hEncoder, &lockBitstreamData);
+        if (nvStatus == NV_ENC_ERR_LOCK_BUSY)
+        {
+            std::this_thread::yield();
+            continue;
+        }
+        if (nvStatus != NV_ENC_SUCCESS)
+        {
+            NVENC_THROW_ERROR("nvEncLockBitstream API failed", nvStatus);
+        }
+
+        // While the picture is being encoded, numSlices and bitstreamSizeInBytes only count the
+        // slices already written. hwEncodeStatus is 2 once the whole picture is encoded
+        completed = lockBitstreamData.hwEncodeStatus == 2;
+        uint8_t *pData = (uint8_t *)lockBitstreamData.bitstreamBufferPtr;
+        for (; sentSlices < lockBitstreamData.numSlices; sentSlices++)
+        {
+            uint32_t sliceEnd = sentSlices + 1 < lockBitstreamData.numSlices
+                ? sliceOffsets[sentSlices + 1]
+                : lockBitstreamData.bitstreamSizeInBytes;
+            onSlice(&pData[sliceOffsets[sentSlices]], sliceEnd - sliceOffsets[sentSlices], sentSlices);
+        }
+
+        NVENC_API_CALL(m_nvenc.nvEncUnlockBitstream(m_hEncoder, lockBitstreamData.outputBitstream));
+
+        if (!completed)
+        {
+            std::this_thread::yield();
+        }
+    }
+
+    if (m_vMappedInputBuffers[bfrIdx])
+    {
+        NVENC_API_CALL(m_nvenc.nvEncUnmapInputResource(m_hEncoder, m_vMappedInputBuffers[bfrIdx]));
+        m_vMappedInputBuffers[bfrIdx] = nullptr;
+    }
+    m_iGot++;
+}
+
 void NvEncoder::RunMotionEstimation(std::vector<uint8_t> &mvData)
 {
     if (!m_hEncoder)
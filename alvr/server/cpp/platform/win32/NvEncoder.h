@@ -12,6 +12,7 @@
 #pragma once
 
 #include <vector>
+#include <functional>
 #include "alvr_server/nvEncodeAPI.h"
 #include <stdint.h>
 #include <mutex>
@@ -126,6 +127,15 @@ public:
     */
     virtual void EncodeFrame(std::vector<std::vector<uint8_t>> &vPacket, NV_ENC_PIC_PARAMS *pPicParams = nullptr);
 
+    /**
+    *  @brief  This function is used to encode a frame with subframe readback.
+    *  onSlice is called with each slice as soon as it is encoded. The encoder must be
+    *  created with enableSubFrameWrite and reportSliceOffsets, in synchronous mode,
+    *  without output delay.
+    */
+    void EncodeFrameSlices(NV_ENC_PIC_PARAMS *pPicParams,
+        const std::function<void(uint8_t *data, uint32_t size, uint32_t sliceIndex)> &onSlice);
+
     /**
     *  @brief  This function to flush the encoder queue.
     *  The encoder might be queuing frames for B picture encoding or lookahead;
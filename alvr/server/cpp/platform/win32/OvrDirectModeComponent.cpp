@@ -7,7 +7,9 @@ OvrDirectModeComponent::OvrDirectModeComponent(std::shared_ptr<CD3DRender> pD3DR
 {
 }
 
+// Present() holds the lock while it uses the encoder, the old one is not used after this returns
 void OvrDirectModeComponent::SetEncoder(std::shared_ptr<CEncoder> pEncoder) {
+	std::lock_guard<std::mutex> lock(m_presentMutex);
 	m_pEncoder = pEncoder;
 }
 
@@ -264,6 +264,8 @@ amf::AMFComponentPtr VideoEncoderAMF::MakeEncoder(
 
 		amfEncoder->SetProperty(AMF_VIDEO_ENCODER_MAX_NUM_REFRAMES, 0);
 
+		amfEncoder->SetProperty(AMF_VIDEO_ENCODER_SLICES_PER_FRAME, Settings::Instance().m_sliceCount);
+
 		if (m_hasQueryTimeout) {
 			amfEncoder->SetProperty(AMF_VIDEO_ENCODER_QUERY_TIMEOUT, 1000); // 1s timeout
 		}
@@ -363,6 +365,8 @@ amf::AMFComponentPtr VideoEncoderAMF::MakeEncoder(
 
 		amfEncoder->SetProperty(AMF_VIDEO_ENCODER_HEVC_MAX_NUM_REFRAMES, 0);
 
+		amfEncoder->SetProperty(AMF_VIDEO_ENCODER_HEVC_SLICES_PER_FRAME, Settings::Instance().m_sliceCount);
+
 		if (m_hasQueryTimeout) {
 			amfEncoder->SetProperty(AMF_VIDEO_ENCODER_HEVC_QUERY_TIMEOUT, 1000); // 1s timeout
 		}
@@ -109,6 +109,19 @@ void VideoEncoderNVENC::Transmit(ID3D11Texture2D *pTexture, uint64_t presentatio
 		Debug("Inserting IDR frame.\n");
 		picParams.encodePicFlags = NV_ENC_PIC_FLAG_FORCEIDR;
 	}
+	if (m_subFrameReadback) {
+		int sliceCount = Settings::Instance().m_sliceCount;
+		m_NvNecoder->EncodeFrameSlices(&picParams, [&](uint8_t *data, uint32_t size, uint32_t sliceIndex) {
+			if (fpOut) {
+				fpOut.write(reinterpret_cast<char*>(data), size);
+			}
+
+			ParseSliceNals(m_codec, data, (int)size, targetTimestampNs, insertIDR, (int)sliceIndex, sliceCount);
+		});
+
+		return;
+	}
+
 	m_NvNecoder->EncodeFrame(vPacket, &picParams);
 
 	for (std::vector<uint8_t> &packet : vPacket)
@@ -171,6 +184,15 @@ void VideoEncoderNVENC::FillEncodeConfig(NV_ENC_INITIALIZE_PARAMS &initializePar
 
 	m_NvNecoder->CreateDefaultEncoderParams(&initializeParams, encoderGUID, qualityPreset, tuningPreset);
 
+	// The slice offsets are only reported in synchronous mode
+	m_subFrameReadback = Settings::Instance().m_sliceCount > 1 && m_codec != ALVR_CODEC_AV1
+		&& m_NvNecoder->GetCapabilityValue(encoderGUID, NV_ENC_CAPS_SUPPORT_SUBFRAME_READBACK);
+	if (m_subFrameReadback) {
+		initializeParams.enableEncodeAsync = 0;
+		initializeParams.reportSliceOffsets = 1;
+		initializeParams.enableSubFrameWrite = 1;
+	}
+
 	initializeParams.encodeWidth = initializeParams.darWidth = renderWidth;
 	initializeParams.encodeHeight = initializeParams.darHeight = renderHeight;
 	initializeParams.frameRateNum = refreshRate;
@@ -220,6 +242,11 @@ void VideoEncoderNVENC::FillEncodeConfig(NV_ENC_INITIALIZE_PARAMS &initializePar
 		config.maxNumRefFrames = maxNumRefFrames;
 		config.idrPeriod = gopLength;
 
+		if (Settings::Instance().m_sliceCount > 1) {
+			config.sliceMode = 3; // fixed number of slices per frame
+			config.sliceModeData = Settings::Instance().m_sliceCount;
+		}
+
 		if (Settings::Instance().m_fillerData) {
 			config.enableFillerDataInsertion = Settings::Instance().m_rateControlMode == ALVR_CBR;
 		}
@@ -255,6 +282,11 @@ void VideoEncoderNVENC::FillEncodeConfig(NV_ENC_INITIALIZE_PARAMS &initializePar
 		config.maxNumRefFramesInDPB = maxNumRefFrames;
 		config.idrPeriod = gopLength;
 
+		if (Settings::Instance().m_sliceCount > 1) {
+			config.sliceMode = 3; // fixed number of slices per frame
+			config.sliceModeData = Settings::Instance().m_sliceCount;
+		}
+
 		if (Settings::Instance().m_use10bitEncoder) {
 			encodeConfig.encodeCodecConfig.hevcConfig.pixelBitDepthMinus8 = 2;
 		}
@@ -29,6 +29,9 @@ private:
 	std::ofstream fpOut;
 	std::shared_ptr<NvEncoder> m_NvNecoder;
 
+	// Each slice is sent as soon as it is encoded
+	bool m_subFrameReadback = false;
+
 	std::shared_ptr<CD3DRender> m_pD3DRender;
 
 	int m_codec;
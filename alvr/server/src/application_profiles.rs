@@ -0,0 +1,141 @@
+use crate::SERVER_DATA_MANAGER;
+use alvr_common::{info, settings_schema::Switch, LogSeverity};
+use alvr_events::{ApplicationProfileEvent, EventType};
+use alvr_packets::SettingChange;
+use alvr_server_io::ServerDataManager;
+use alvr_session::ApplicationProfile;
+
+// vr::k_unMaxApplicationKeyLength
+const APP_KEY_BUFFER_SIZE: usize = 128;
+
+// Requires the OpenVR client
+fn scene_application_key() -> Option<String> {
+    let mut buffer = [0_u8; APP_KEY_BUFFER_SIZE];
+    let len = unsafe { crate::GetSceneApplicationKey(buffer.as_mut_ptr() as _, buffer.len() as _) };
+
+    (len > 0).then(|| String::from_utf8_lossy(&buffer[..len as usize]).into_owned())
+}
+
+// Applies the profile of the running SteamVR application, or the manual profile, on top of the
+// session. Edits to the active profile are applied at the next update
+#[derive(Default)]
+pub struct ApplicationProfileManager {
+    active_profile: Option<ApplicationProfile>,
+    last_event: Option<ApplicationProfileEvent>,
+}
+
+impl ApplicationProfileManager {
+    // Returns the settings that changed value
+    fn activate(
+        &mut self,
+        data_manager: &mut ServerDataManager,
+        profile: Option<ApplicationProfile>,
+    ) -> Vec<SettingChange> {
+        if profile == self.active_profile {
+            return vec![];
+        }
+
+        match (&profile, &self.active_profile) {
+            (Some(profile), _) => info!("Application profile \"{}\" activated", profile.name),
+            (None, Some(previous)) => {
+                info!("Application profile \"{}\" deactivated", previous.name)
+            }
+            (None, None) => (),
+        }
+
+        let changes = data_manager.set_profile_overrides(
+            profile
+                .as_ref()
+                .map(alvr_server_io::profile_overrides)
+                .unwrap_or_default(),
+        );
+
+        self.active_profile = profile;
+
+        changes
+    }
+
+    // The changes are reported after the lock is released
+    fn activate_and_report(&mut self, profile: Option<ApplicationProfile>) {
+        let changes = self.activate(&mut SERVER_DATA_MANAGER.write(), profile);
+        crate::report_setting_changes(&changes);
+    }
+
+    fn report(&mut self, event: ApplicationProfileEvent) {
+        if self.last_event.as_ref() != Some(&event) {
+            alvr_events::send_event(
+                LogSeverity::Info,
+                EventType::ApplicationProfile(event.clone()),
+            );
+            self.last_event = Some(event);
+        }
+    }
+
+    // To be called periodically while streaming
+    pub fn update(&mut self) {
+        let app_key = scene_application_key();
+
+        let selection = match &SERVER_DATA_MANAGER.read().settings().application_profiles {
+            Switch::Enabled(config) => alvr_server_io::select_profile(config, app_key.as_deref())
+                .map(|(profile, manual)| (profile.clone(), manual)),
+            Switch::Disabled => None,
+        };
+        let manual = selection.as_ref().is_some_and(|(_, manual)| *manual);
+
+        self.activate_and_report(selection.map(|(profile, _)| profile));
+        self.report(ApplicationProfileEvent {
+            app_key,
+            profile_name: self.active_profile.as_ref().map(|p| p.name.clone()),
+            manual,
+        });
+    }
+
+    // Reverts to the session values, when the stream ends
+    pub fn deactivate(&mut self) {
+        self.activate_and_report(None);
+        self.report(ApplicationProfileEvent {
+            app_key: None,
+            profile_name: None,
+            manual: false,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alvr_session::SettingOverride;
+
+    #[test]
+    fn test_activate() {
+        let dir = std::env::temp_dir().join(format!("alvr_profiles_{}", std::process::id()));
+        let mut data_manager = ServerDataManager::new(&dir.join("session.json"));
+        assert!(data_manager.settings().video.optimize_game_render_latency);
+
+        let profile = ApplicationProfile {
+            name: "Test".into(),
+            app_key: "steam.app.1".into(),
+            overrides: vec![SettingOverride {
+                path: "video.optimize_game_render_latency".into(),
+                value: "false".into(),
+            }],
+        };
+        let path = alvr_packets::parse_path("session_settings.video.optimize_game_render_latency");
+
+        let mut manager = ApplicationProfileManager::default();
+        let changes = manager.activate(&mut data_manager, Some(profile.clone()));
+        assert!(changes.len() == 1 && changes[0].path == path);
+        assert!(!data_manager.settings().video.optimize_game_render_latency);
+
+        // Nothing changes while the same profile stays active
+        assert!(manager
+            .activate(&mut data_manager, Some(profile))
+            .is_empty());
+
+        let changes = manager.activate(&mut data_manager, None);
+        assert!(changes.len() == 1 && changes[0].path == path);
+        assert!(data_manager.settings().video.optimize_game_render_latency);
+
+        std::fs::remove_dir_all(dir).ok();
+    }
+}
@@ -0,0 +1,128 @@
+use alvr_common::{DEVICE_ID_TO_PATH, HAND_LEFT_ID, HAND_RIGHT_ID, HEAD_ID};
+use alvr_session::LowBatteryWarningsConfig;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+pub enum BatteryLevel {
+    #[default]
+    Normal,
+    Low,
+    Critical,
+}
+
+fn level_for(config: &LowBatteryWarningsConfig, percent: f32, offset_percent: f32) -> BatteryLevel {
+    if percent <= config.critical_threshold_percent + offset_percent {
+        BatteryLevel::Critical
+    } else if percent <= config.low_threshold_percent + offset_percent {
+        BatteryLevel::Low
+    } else {
+        BatteryLevel::Normal
+    }
+}
+
+pub fn device_name(device_id: u64) -> String {
+    if device_id == *HEAD_ID {
+        "Headset".into()
+    } else if device_id == *HAND_LEFT_ID {
+        "Left controller".into()
+    } else if device_id == *HAND_RIGHT_ID {
+        "Right controller".into()
+    } else {
+        DEVICE_ID_TO_PATH
+            .get(&device_id)
+            .map(|path| (*path).to_owned())
+            .unwrap_or_else(|| format!("Device {device_id:#x}"))
+    }
+}
+
+// The level of a device gets more severe as soon as a threshold is crossed, but goes back only
+// once the battery charged past the threshold by the hysteresis, so that a gauge that jitters
+// around a threshold does not repeat the warning
+#[derive(Default)]
+pub struct BatteryWarnings {
+    levels: HashMap<u64, BatteryLevel>,
+}
+
+impl BatteryWarnings {
+    // Returns the new level if a warning should be shown
+    pub fn report(
+        &mut self,
+        config: &LowBatteryWarningsConfig,
+        device_id: u64,
+        gauge_value: f32,
+        is_plugged: bool,
+    ) -> Option<BatteryLevel> {
+        let percent = gauge_value * 100.0;
+        let level = self.levels.entry(device_id).or_default();
+
+        let recovered_level = level_for(config, percent, config.hysteresis_percent);
+        if recovered_level < *level {
+            *level = recovered_level;
+        }
+
+        // A charging device is not reported, but its level is kept, so unplugging it does not
+        // repeat a warning that was already shown
+        let new_level = level_for(config, percent, 0.0);
+        if !is_plugged && new_level > *level {
+            *level = new_level;
+
+            Some(new_level)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEVICE_ID: u64 = 1;
+
+    fn config() -> LowBatteryWarningsConfig {
+        LowBatteryWarningsConfig {
+            low_threshold_percent: 15.0,
+            critical_threshold_percent: 5.0,
+            hysteresis_percent: 3.0,
+            notify_in_headset: true,
+        }
+    }
+
+    #[test]
+    fn test_thresholds_with_hysteresis() {
+        let config = config();
+        let mut warnings = BatteryWarnings::default();
+        let mut report =
+            |gauge_value, is_plugged| warnings.report(&config, DEVICE_ID, gauge_value, is_plugged);
+
+        assert_eq!(report(0.5, false), None);
+        assert_eq!(report(0.14, false), Some(BatteryLevel::Low));
+        // Jitter around the threshold
+        assert_eq!(report(0.16, false), None);
+        assert_eq!(report(0.13, false), None);
+        assert_eq!(report(0.04, false), Some(BatteryLevel::Critical));
+        assert_eq!(report(0.03, false), None);
+
+        // Charging past the hysteresis rearms the warnings
+        assert_eq!(report(0.2, true), None);
+        assert_eq!(report(0.14, false), Some(BatteryLevel::Low));
+    }
+
+    #[test]
+    fn test_plugged_devices() {
+        let config = config();
+        let mut warnings = BatteryWarnings::default();
+
+        assert_eq!(warnings.report(&config, DEVICE_ID, 0.03, true), None);
+        assert_eq!(
+            warnings.report(&config, DEVICE_ID, 0.04, false),
+            Some(BatteryLevel::Critical)
+        );
+
+        // Devices are tracked separately
+        assert_eq!(
+            warnings.report(&config, DEVICE_ID + 1, 0.1, false),
+            Some(BatteryLevel::Low)
+        );
+    }
+}
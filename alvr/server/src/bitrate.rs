@@ -1,8 +1,10 @@
 use crate::FfiDynamicEncoderParams;
-use alvr_common::SlidingWindowAverage;
+use alvr_common::{once_cell::sync::Lazy, RelaxedAtomicF32, SlidingWindowAverage};
 use alvr_events::NominalBitrateStats;
+use alvr_packets::ThermalStatus;
 use alvr_session::{
     settings_schema::Switch, BitrateAdaptiveFramerateConfig, BitrateConfig, BitrateMode,
+    ThermalMitigationConfig,
 };
 use std::{
     collections::VecDeque,
@@ -11,6 +13,9 @@ use std::{
 
 const UPDATE_INTERVAL: Duration = Duration::from_secs(1);
 
+// Last bitrate requested to the encoder, read by the statistics without locking the manager
+pub static ENCODER_BITRATE_BPS: Lazy<RelaxedAtomicF32> = Lazy::new(|| RelaxedAtomicF32::new(0.0));
+
 pub struct BitrateManager {
     nominal_frame_interval: Duration,
     frame_interval_average: SlidingWindowAverage<Duration>,
@@ -24,6 +29,9 @@ pub struct BitrateManager {
     last_frame_instant: Instant,
     last_update_instant: Instant,
     dynamic_max_bitrate: f32,
+    // A new manager is created for every stream, so the ramp restarts after each reconnection
+    ramp_fraction: Option<f32>,
+    thermal_multiplier: f32,
     previous_config: Option<BitrateConfig>,
     update_needed: bool,
 }
@@ -50,6 +58,8 @@ impl BitrateManager {
             last_frame_instant: Instant::now(),
             last_update_instant: Instant::now(),
             dynamic_max_bitrate: f32::MAX,
+            ramp_fraction: None,
+            thermal_multiplier: 1.0,
             previous_config: None,
             update_needed: true,
         }
@@ -142,12 +152,37 @@ impl BitrateManager {
         }
     }
 
+    // Returns the new bitrate multiplier. Each thermal level above Light lowers the bitrate by one
+    // step, the multiplier goes back up as the device cools down
+    pub fn report_thermal_status(
+        &mut self,
+        config: &ThermalMitigationConfig,
+        status: ThermalStatus,
+    ) -> f32 {
+        let multiplier = if config.automatic {
+            let levels = (status as i32 - ThermalStatus::Light as i32).max(0);
+            (1.0 - config.bitrate_reduction_per_level).powi(levels)
+        } else {
+            1.0
+        };
+
+        if multiplier != self.thermal_multiplier {
+            self.thermal_multiplier = multiplier;
+            self.update_needed = true;
+        }
+
+        multiplier
+    }
+
     pub fn get_encoder_params(
         &mut self,
         config: &BitrateConfig,
     ) -> (FfiDynamicEncoderParams, Option<NominalBitrateStats>) {
         let now = Instant::now();
 
+        let ramp_step_due = now >= self.last_update_instant + UPDATE_INTERVAL;
+        let ramping = config.ramp_up.enabled() && self.ramp_fraction.unwrap_or(0.0) < 1.0;
+
         if self
             .previous_config
             .as_ref()
@@ -158,7 +193,7 @@ impl BitrateManager {
             // Continue method. Always update bitrate in this case
         } else if !self.update_needed
             && (now < self.last_update_instant + UPDATE_INTERVAL
-                || matches!(config.mode, BitrateMode::ConstantMbps(_)))
+                || (matches!(config.mode, BitrateMode::ConstantMbps(_)) && !ramping))
         {
             return (
                 FfiDynamicEncoderParams {
@@ -232,6 +267,26 @@ impl BitrateManager {
             }
         };
 
+        let bitrate_bps = if let Switch::Enabled(ramp) = &config.ramp_up {
+            let fraction = self.ramp_fraction.get_or_insert(ramp.initial_fraction);
+
+            // The bitrate is raised one step per update interval, only while the link keeps up
+            if ramp_step_due
+                && *fraction < 1.0
+                && self.network_latency_average.get_average()
+                    <= Duration::from_millis(ramp.max_network_latency_ms)
+            {
+                let step =
+                    (1.0 - ramp.initial_fraction) * UPDATE_INTERVAL.as_secs_f32() / ramp.duration_s;
+                *fraction = f32::min(*fraction + step, 1.0);
+            }
+
+            bitrate_bps * *fraction
+        } else {
+            bitrate_bps
+        };
+        let bitrate_bps = bitrate_bps * self.thermal_multiplier;
+
         stats.requested_bps = bitrate_bps;
 
         let frame_interval = if config.adapt_to_framerate.enabled() {
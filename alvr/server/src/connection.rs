@@ -1,71 +1,217 @@
 use crate::{
-    bitrate::BitrateManager,
+    application_profiles::ApplicationProfileManager,
+    battery::{self, BatteryLevel, BatteryWarnings},
+    bitrate::{self, BitrateManager},
     body_tracking::BodyTrackingSink,
+    device_registry::DeviceRegistry,
     face_tracking::FaceTrackingSink,
+    gamepad::VirtualGamepad,
     hand_gestures::{trigger_hand_gesture_actions, HandGestureManager, HAND_GESTURE_BUTTON_SET},
-    haptics,
+    haptics::{self, HapticsEnvelopeBuilder},
+    input_debug,
     input_mapping::ButtonMappingManager,
-    sockets::WelcomeSocket,
+    pairing, playspace,
+    pose_osc::PoseOscSender,
+    sockets::{self, WelcomeSocket},
     statistics::StatisticsManager,
+    thumbstick::ThumbstickProcessor,
+    timing_capture::TrackingRow,
     tracking::{self, TrackingManager},
-    FfiFov, FfiViewsConfig, VideoPacket, BITRATE_MANAGER, DECODER_CONFIG, LIFECYCLE_STATE,
-    SERVER_DATA_MANAGER, STATISTICS_MANAGER, VIDEO_MIRROR_SENDER, VIDEO_RECORDING_FILE,
+    wired::AdbForwarder,
+    FfiFov, FfiViewsConfig, VideoPacket, BITRATE_MANAGER, DECODER_CONFIG, IS_RESTARTING,
+    LIFECYCLE_STATE, SERVER_DATA_MANAGER, STATISTICS_MANAGER, TIMING_CAPTURE, VIDEO_MIRROR_SENDER,
+    VIDEO_RECORDING_FILE,
 };
 use alvr_audio::AudioDevice;
 use alvr_common::{
+    align32,
+    anyhow::Result,
     con_bail, debug, error,
     glam::{UVec2, Vec2},
     info,
     once_cell::sync::Lazy,
-    parking_lot::{Condvar, Mutex},
+    parking_lot::{Condvar, Mutex, RwLockWriteGuard},
     settings_schema::Switch,
-    warn, AnyhowToCon, ConResult, ConnectionError, ConnectionState, LifecycleState, OptLazy,
-    BUTTON_INFO, CONTROLLER_PROFILE_INFO, DEVICE_ID_TO_PATH, HAND_LEFT_ID, HAND_RIGHT_ID, HEAD_ID,
-    QUEST_CONTROLLER_PROFILE_PATH,
+    warn, AnyhowToCon, ConResult, ConnectionError, ConnectionState, DeviceMotion, Fov,
+    LifecycleState, LogEntry, LogSeverity, OptLazy, SlidingAverage, BUTTON_INFO,
+    CONTROLLER_PROFILE_INFO, DEVICE_ID_TO_PATH, GAMEPAD_BUTTON_INFO, HAND_LEFT_ID, HAND_RIGHT_ID,
+    HEAD_ID, QUEST_CONTROLLER_PROFILE_PATH,
+};
+use alvr_events::{
+    ButtonEvent, ClientConnectedEvent, ClientDisconnectedEvent, ClientDiscoveredEvent,
+    DisconnectionReason, EventType, IncompatibleClientAcceptedEvent, LowBatteryEvent,
+    NetworkSimulatorEvent, PortUsage, StreamSetupEvent, StreamStartedEvent, ThermalMitigationEvent,
+    TrackingEvent,
 };
-use alvr_events::{ButtonEvent, EventType, HapticsEvent, TrackingEvent};
 use alvr_packets::{
-    ClientConnectionResult, ClientControlPacket, ClientListAction, ClientStatistics, Haptics,
-    NegotiatedStreamingConfig, ReservedClientControlPacket, ServerControlPacket, Tracking,
-    VideoPacketHeader, AUDIO, HAPTICS, STATISTICS, TRACKING, VIDEO,
+    ClientConnectionResult, ClientControlPacket, ClientListAction, ClientRequest, ClientStatistics,
+    Haptics, HapticsWithEnvelope, NegotiatedStreamingConfig, ReservedClientControlPacket,
+    ReservedServerControlPacket, ServerControlPacket, ServerResponse, ThermalStatus, Tracking,
+    TrackingDeltaDecoder, TrackingDeltaPacket, VideoPacketHeader, VideoStreamingCapabilities,
+    ViewsConfig, AUDIO, HAPTICS, STATISTICS, TRACKING, TRACKING_DELTA, TRACKING_FIXED, VIDEO,
 };
 use alvr_session::{
-    BodyTrackingConfig, BodyTrackingSinkConfig, CodecType, ControllersEmulationMode, FrameSize,
-    H264Profile, OpenvrConfig, SessionConfig,
+    BodyTrackingConfig, BodyTrackingSinkConfig, CodecType, ControllersConfig,
+    ControllersEmulationMode, FovOverride, FrameSize, H264Profile, HapticsConfig, HeadsetInfo,
+    OpenvrConfig, SessionConfig, SocketProtocol, SocketProtocolDefaultVariant, VideoPacingConfig,
+    ViewsOverrideConfig, WiredConnectionConfig,
 };
 use alvr_sockets::{
-    PeerType, ProtoControlSocket, StreamSender, StreamSocketBuilder, KEEPALIVE_INTERVAL,
-    KEEPALIVE_TIMEOUT,
+    ControlSocketSender, DatagramSender, OverflowPolicy, PeerType, ProtoControlSocket,
+    StreamReceiver, StreamSender, StreamSocketBuilder, KEEPALIVE_INTERVAL, KEEPALIVE_TIMEOUT,
+    LOCAL_IP,
 };
 use std::{
     collections::{HashMap, HashSet},
+    f32::consts::FRAC_PI_2,
+    ffi::CString,
     io::Write,
-    net::IpAddr,
+    net::{IpAddr, Ipv4Addr},
     process::Command,
     ptr,
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicUsize, Ordering},
         mpsc::{RecvTimeoutError, SyncSender, TrySendError},
         Arc,
     },
     thread::{self, JoinHandle},
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 const RETRY_CONNECT_MIN_INTERVAL: Duration = Duration::from_secs(1);
 const HANDSHAKE_ACTION_TIMEOUT: Duration = Duration::from_secs(2);
 const STREAMING_RECV_TIMEOUT: Duration = Duration::from_millis(500);
 
-const MAX_UNREAD_PACKETS: usize = 10; // Applies per stream
+const OVERLAY_STATISTICS_INTERVAL: Duration = Duration::from_millis(250);
+// Applies per stream
+const MAX_UNREAD_PACKETS: usize = 10;
+// Tracking can be sent at several times the frame rate
+const MAX_UNREAD_TRACKING_PACKETS: usize = 50;
+// The client divides by the rate, it must stay positive
+const MIN_TRACKING_SEND_RATE_HZ: f32 = 1.0;
+const MAX_TRACKING_SEND_RATE_HZ: f32 = 500.0;
+// The streamer is quitting, a client that stopped reading is not waited for
+const SHUTDOWN_NOTIFICATION_TIMEOUT: Duration = Duration::from_millis(200);
+const APPLICATION_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+const AUDIO_BUFFER_ISSUES_PER_MINUTE_THRESHOLD: f32 = 2.0;
+// Recenters requested again within this time, from the dashboard or from the client, are ignored
+const RECENTER_DEBOUNCE: Duration = Duration::from_secs(1);
 
 static VIDEO_CHANNEL_SENDER: OptLazy<SyncSender<VideoPacket>> = alvr_common::lazy_mut_none();
-static HAPTICS_SENDER: OptLazy<StreamSender<Haptics>> = alvr_common::lazy_mut_none();
+static HAPTICS_SENDER: OptLazy<HapticsSender> = alvr_common::lazy_mut_none();
+static CONTROL_SENDER: OptLazy<Arc<Mutex<ControlSocketSender<ServerControlPacket>>>> =
+    alvr_common::lazy_mut_none();
+static STREAMING_CAPS: OptLazy<VideoStreamingCapabilities> = alvr_common::lazy_mut_none();
+static STREAM_SETUP: OptLazy<StreamSetupEvent> = alvr_common::lazy_mut_none();
+// Set while the headset is not worn, after the proximity standby timeout
+static HEADSET_REMOVED: AtomicBool = AtomicBool::new(false);
+// Set while the stream is paused from the dashboard or from the client
+static PAUSED: AtomicBool = AtomicBool::new(false);
+// Set while the video and the game audio are paused for either of the reasons above
+static STANDBY: AtomicBool = AtomicBool::new(false);
+// Cleared if the client reports that no video datagram reached it
+static UDP_VIDEO_ACTIVE: AtomicBool = AtomicBool::new(false);
+// Set while the headset overheats, applied to the resolution of the next streams
+static THERMAL_RESOLUTION_SCALE: OptLazy<f32> = alvr_common::lazy_mut_none();
 static CONNECTION_THREADS: Lazy<Mutex<Vec<JoinHandle<()>>>> = Lazy::new(|| Mutex::new(vec![]));
+// Set from the wired connection attempt until its thread ends, with or without a client
+static WIRED_PROBE_RUNNING: AtomicBool = AtomicBool::new(false);
 pub static CLIENTS_TO_BE_REMOVED: Lazy<Mutex<HashSet<String>>> =
     Lazy::new(|| Mutex::new(HashSet::new()));
+// Kept to apply the views override again when it is changed during the stream
+static CLIENT_VIEWS_CONFIG: OptLazy<ViewsConfig> = alvr_common::lazy_mut_none();
+static LAST_RECENTER: OptLazy<Instant> = alvr_common::lazy_mut_none();
+// Round trip time of the control connection, in milliseconds
+pub static KEEPALIVE_RTT_MS: SlidingAverage = SlidingAverage::new(8);
+// Reported in the ClientDisconnected event once the connection thread ends
+static DISCONNECTION_REASONS: Lazy<Mutex<HashMap<String, DisconnectionReason>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+// Several threads notice a disconnection at about the same time. The first cause is kept, the
+// others are usually a consequence of it
+pub fn set_disconnection_reason(hostname: &str, reason: DisconnectionReason) {
+    DISCONNECTION_REASONS
+        .lock()
+        .entry(hostname.to_owned())
+        .or_insert(reason);
+}
+
+fn unix_time() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+enum TrackingReceiver {
+    Full(StreamReceiver<Tracking>),
+    Delta(StreamReceiver<TrackingDeltaPacket>, TrackingDeltaDecoder),
+    Fixed(StreamReceiver<()>),
+}
+
+impl TrackingReceiver {
+    fn recv(&mut self, timeout: Duration) -> ConResult<Tracking> {
+        match self {
+            TrackingReceiver::Full(receiver) => receiver.recv(timeout)?.get_header().to_con(),
+            TrackingReceiver::Delta(receiver, decoder) => {
+                let packet = receiver.recv(timeout)?.get_header().to_con()?;
+
+                if let Some(tracking) = decoder.decode(packet) {
+                    Ok(tracking)
+                } else {
+                    if decoder.take_keyframe_request() {
+                        if let Some(control_sender) = CONTROL_SENDER.lock().clone() {
+                            control_sender
+                                .lock()
+                                .send(&alvr_packets::encode_reserved_server_control_packet(
+                                    &ReservedServerControlPacket::RequestTrackingKeyframe,
+                                ))
+                                .ok();
+                        }
+                    }
+
+                    alvr_common::try_again()
+                }
+            }
+            TrackingReceiver::Fixed(receiver) => {
+                let data = receiver.recv(timeout)?;
+                let (_, payload) = data.get().to_con()?;
 
-fn align32(value: f32) -> u32 {
-    ((value / 32.).floor() * 32.) as u32
+                alvr_packets::decode_tracking(payload).to_con()
+            }
+        }
+    }
+
+    fn dropped_packets(&self) -> usize {
+        match self {
+            TrackingReceiver::Full(receiver) => receiver.dropped_packets(),
+            TrackingReceiver::Delta(receiver, _) => receiver.dropped_packets(),
+            TrackingReceiver::Fixed(receiver) => receiver.dropped_packets(),
+        }
+    }
+}
+
+enum HapticsSender {
+    Plain(StreamSender<Haptics>),
+    Envelope(StreamSender<HapticsWithEnvelope>, HapticsEnvelopeBuilder),
+}
+
+impl HapticsSender {
+    // Haptics come in bursts of small packets, they are queued to be written together
+    fn send(&mut self, config: &HapticsConfig, haptics: Haptics) -> Result<()> {
+        match self {
+            HapticsSender::Plain(sender) => {
+                sender.queue_header(&haptics::map_haptics(config, haptics))
+            }
+            HapticsSender::Envelope(sender, builder) => {
+                let envelope = builder.push(&haptics, Instant::now());
+
+                sender.queue_header(&HapticsWithEnvelope {
+                    haptics: haptics::map_haptics(config, haptics),
+                    envelope: haptics::map_envelope(config, envelope),
+                })
+            }
+        }
+    }
 }
 
 fn is_streaming(client_hostname: &str) -> bool {
@@ -169,6 +315,7 @@ pub fn contruct_openvr_config(session: &SessionConfig) -> OpenvrConfig {
         rate_control_mode: settings.video.encoder_config.rate_control_mode as u32,
         filler_data: settings.video.encoder_config.filler_data,
         entropy_coding: settings.video.encoder_config.entropy_coding as u32,
+        slice_count: settings.video.encoder_config.slice_count,
         use_10bit_encoder: settings.video.encoder_config.use_10bit,
         use_full_range_encoding: settings.video.encoder_config.use_full_range,
         encoding_gamma: settings.video.encoder_config.encoding_gamma,
@@ -232,17 +379,279 @@ pub fn contruct_openvr_config(session: &SessionConfig) -> OpenvrConfig {
     }
 }
 
+// Switch the codec of the running stream by recreating the encoder and the client decoder, without
+// restarting SteamVR. Returns false if the stream must be restarted for the codec to be applied.
+pub fn switch_codec(preferred_codec: CodecType) -> bool {
+    let Some(caps) = STREAMING_CAPS.lock().clone() else {
+        return false;
+    };
+
+    // On Linux the encoder is connected to the Vulkan layer once per SteamVR session
+    if !cfg!(windows) {
+        return false;
+    }
+
+    if preferred_codec == CodecType::AV1 && !caps.encoder_av1 {
+        warn!("AV1 decoding is not supported by the client. Keeping the current codec.");
+
+        // The codec in use differs from the setting, the restart prompt stays
+        return false;
+    }
+
+    {
+        let mut data_manager_lock = SERVER_DATA_MANAGER.write();
+        let mut session_lock = data_manager_lock.session_mut();
+        if session_lock.openvr_config.codec == preferred_codec as u8 {
+            return true;
+        }
+        session_lock.openvr_config.codec = preferred_codec as u8;
+    }
+
+    let Some(control_sender) = CONTROL_SENDER.lock().clone() else {
+        return false;
+    };
+
+    info!("Switching codec to {preferred_codec:?}");
+
+    // The client will request the new decoder config when the first frame of the new encoder
+    // arrives, the old config must not be sent in the meantime
+    *DECODER_CONFIG.lock() = None;
+
+    control_sender
+        .lock()
+        .send(&alvr_packets::encode_reserved_server_control_packet(
+            &ReservedServerControlPacket::CodecChanged(preferred_codec),
+        ))
+        .ok();
+
+    unsafe { crate::RecreateEncoder() };
+
+    if let Some(stream_setup) = &mut *STREAM_SETUP.lock() {
+        stream_setup.codec = preferred_codec;
+        alvr_events::send_event(
+            LogSeverity::Info,
+            EventType::StreamSetup(stream_setup.clone()),
+        );
+    }
+
+    true
+}
+
+fn handle_client_request(request: ClientRequest) -> ServerResponse {
+    match request {
+        ClientRequest::Session => match serde_json::to_string(SERVER_DATA_MANAGER.read().session())
+        {
+            Ok(session) => ServerResponse::Session(session),
+            Err(e) => ServerResponse::Failed(e.to_string()),
+        },
+        ClientRequest::AudioDevices => match SERVER_DATA_MANAGER.read().get_audio_devices_list() {
+            Ok(list) => ServerResponse::AudioDevices(list),
+            Err(e) => ServerResponse::Failed(e.to_string()),
+        },
+        ClientRequest::ClientStatisticsSnapshot => {
+            if let Some(stats) = &*STATISTICS_MANAGER.lock() {
+                ServerResponse::ClientStatisticsSnapshot(stats.snapshot())
+            } else {
+                ServerResponse::Failed("The stream is not running".into())
+            }
+        }
+    }
+}
+
+// Projections get degenerate at 90°
+const MAX_FOV_ANGLE: f32 = FRAC_PI_2 - 0.01;
+
+fn recenter_playspace(tracking_manager: &Mutex<TrackingManager>) {
+    let data_manager_lock = SERVER_DATA_MANAGER.read();
+    let config = &data_manager_lock.settings().headset;
+    tracking_manager.lock().recenter(
+        config.position_recentering_mode,
+        config.rotation_recentering_mode,
+    );
+}
+
+fn should_recenter() -> bool {
+    let mut last_recenter = LAST_RECENTER.lock();
+    let now = Instant::now();
+    if last_recenter.is_some_and(|last| now < last + RECENTER_DEBOUNCE) {
+        return false;
+    }
+    *last_recenter = Some(now);
+
+    true
+}
+
+// The client answers with PlayspaceSync once its reference space is created again. Returns false
+// if no client is connected
+pub fn request_recenter() -> bool {
+    let Some(control_sender) = CONTROL_SENDER.lock().clone() else {
+        return false;
+    };
+
+    if should_recenter() {
+        info!("Requesting the client to recenter");
+
+        control_sender
+            .lock()
+            .send(&alvr_packets::encode_reserved_server_control_packet(
+                &ReservedServerControlPacket::Recenter,
+            ))
+            .ok();
+    }
+
+    true
+}
+
+// The control connection, the keepalives and the tracking continue. The encoder is resumed with an
+// IDR, nothing is negotiated again. Returns false if no client is connected
+pub fn set_stream_paused(paused: bool) -> bool {
+    let Some(control_sender) = CONTROL_SENDER.lock().clone() else {
+        return false;
+    };
+
+    if PAUSED.swap(paused, Ordering::SeqCst) != paused {
+        update_standby();
+
+        alvr_events::send_event(LogSeverity::Info, EventType::StreamPaused(paused));
+    }
+
+    control_sender
+        .lock()
+        .send(&alvr_packets::encode_reserved_server_control_packet(
+            &ReservedServerControlPacket::StreamPaused(paused),
+        ))
+        .ok();
+
+    true
+}
+
+// The client applies the real-time settings it reads while streaming, like the haptics logging
+pub fn update_client_settings() {
+    let Some(control_sender) = CONTROL_SENDER.lock().clone() else {
+        return;
+    };
+
+    let settings = SERVER_DATA_MANAGER.read().settings().clone();
+    control_sender
+        .lock()
+        .send(&alvr_packets::encode_reserved_server_control_packet(
+            &ReservedServerControlPacket::SettingsChanged(Box::new(settings)),
+        ))
+        .ok();
+}
+
+fn apply_views_override(config: &ViewsConfig, views_override: &ViewsOverrideConfig) -> ViewsConfig {
+    let ipd_m = views_override
+        .ipd_mm
+        .map(|ipd_mm| ipd_mm / 1000.0)
+        .unwrap_or(config.ipd_m);
+
+    let fov = match &views_override.fov {
+        None => config.fov,
+        Some(FovOverride::Scale(scale)) => config.fov.map(|fov| {
+            let scaled = |angle: f32| (angle * scale).clamp(-MAX_FOV_ANGLE, MAX_FOV_ANGLE);
+
+            Fov {
+                left: scaled(fov.left),
+                right: scaled(fov.right),
+                up: scaled(fov.up),
+                down: scaled(fov.down),
+            }
+        }),
+        // Left and down angles are negative
+        Some(FovOverride::Absolute {
+            left_eye,
+            right_eye,
+        }) => [left_eye, right_eye].map(|eye| Fov {
+            left: -eye.left_deg.to_radians(),
+            right: eye.right_deg.to_radians(),
+            up: eye.up_deg.to_radians(),
+            down: -eye.down_deg.to_radians(),
+        }),
+    };
+
+    ViewsConfig { ipd_m, fov }
+}
+
+fn set_views_config(client_config: &ViewsConfig) {
+    let views_override = SERVER_DATA_MANAGER
+        .read()
+        .settings()
+        .headset
+        .views_override
+        .clone();
+    let config = apply_views_override(client_config, &views_override);
+
+    let ffi_fov = |fov: Fov| FfiFov {
+        left: fov.left,
+        right: fov.right,
+        up: fov.up,
+        down: fov.down,
+    };
+    unsafe {
+        crate::SetViewsConfig(FfiViewsConfig {
+            fov: config.fov.map(ffi_fov),
+            ipd_m: config.ipd_m,
+        })
+    };
+}
+
+// Clearing an override restores the values reported by the client
+pub fn update_views_override() {
+    if let Some(config) = &*CLIENT_VIEWS_CONFIG.lock() {
+        set_views_config(config);
+    }
+}
+
 // Alternate connection trials with manual IPs and clients discovered on the local network
 pub fn handshake_loop() {
-    let mut welcome_socket = match WelcomeSocket::new() {
-        Ok(socket) => socket,
+    // Changing the ports requires restarting SteamVR
+    let (control_port, discovery_port) = {
+        let data_manager = SERVER_DATA_MANAGER.read();
+        let connection = &data_manager.settings().connection;
+
+        (connection.control_port, connection.discovery_port)
+    };
+
+    // Manual IPs and the wired connection still work without discovery
+    let mut welcome_socket = match WelcomeSocket::new(discovery_port) {
+        Ok(socket) => Some(socket),
         Err(e) => {
-            error!("Failed to create discovery socket: {e:?}");
-            return;
+            sockets::report_port_conflict(
+                &e,
+                discovery_port,
+                SocketProtocol::Udp,
+                PortUsage::Discovery,
+            );
+            error!("Failed to create discovery socket, clients will not be discovered: {e:?}");
+
+            None
         }
     };
 
+    let mut adb_forwarder = AdbForwarder::default();
+
     while *LIFECYCLE_STATE.write() != LifecycleState::ShuttingDown {
+        let (wired_config, stream_port) = {
+            let data_manager = SERVER_DATA_MANAGER.read();
+            let connection = &data_manager.settings().connection;
+
+            (
+                connection.wired_connection.clone().into_option(),
+                connection.stream_port,
+            )
+        };
+        // Tried first, so that the cable is preferred over the network
+        if let Some(config) = wired_config {
+            if adb_forwarder.update(&[control_port, stream_port])
+                && !is_wired_client_connected()
+                && try_connect_wired(control_port, &config).is_ok()
+            {
+                thread::sleep(RETRY_CONNECT_MIN_INTERVAL);
+                continue;
+            }
+        }
+
         let available_manual_client_ips = {
             let mut manual_client_ips = HashMap::new();
             for (hostname, connection_info) in SERVER_DATA_MANAGER
@@ -259,7 +668,7 @@ pub fn handshake_loop() {
         };
 
         if !available_manual_client_ips.is_empty()
-            && try_connect(available_manual_client_ips).is_ok()
+            && try_connect(available_manual_client_ips, control_port).is_ok()
         {
             thread::sleep(RETRY_CONNECT_MIN_INTERVAL);
             continue;
@@ -271,7 +680,9 @@ pub fn handshake_loop() {
             .connection
             .client_discovery
             .clone();
-        if let Switch::Enabled(config) = discovery_config {
+        if let (Switch::Enabled(config), Some(welcome_socket)) =
+            (discovery_config, &mut welcome_socket)
+        {
             let clients = match welcome_socket.recv_all() {
                 Ok(clients) => clients,
                 Err(e) => {
@@ -287,10 +698,13 @@ pub fn handshake_loop() {
                 continue;
             }
 
-            for (client_hostname, client_ip) in clients {
-                let trusted = {
+            for (client_hostname, client_address) in clients {
+                let client_ip = client_address.ip();
+
+                let (discovered, trusted) = {
                     let mut data_manager = SERVER_DATA_MANAGER.write();
 
+                    let discovered = !data_manager.client_list().contains_key(&client_hostname);
                     data_manager.update_client_list(
                         client_hostname.clone(),
                         ClientListAction::AddIfMissing {
@@ -299,20 +713,63 @@ pub fn handshake_loop() {
                         },
                     );
 
-                    if config.auto_trust_clients {
-                        data_manager
-                            .update_client_list(client_hostname.clone(), ClientListAction::Trust);
+                    let already_trusted = data_manager
+                        .client_list()
+                        .get(&client_hostname)
+                        .map(|c| c.trusted)
+                        .unwrap_or(false);
+
+                    if config.auto_trust_clients && !already_trusted {
+                        data_manager.update_client_list(
+                            client_hostname.clone(),
+                            ClientListAction::TrustAndMaybeAddIp(None),
+                        );
+
+                        warn!("Automatically trusted new client {client_hostname} ({client_ip})");
                     }
 
-                    data_manager
+                    let trusted = data_manager
                         .client_list()
                         .get(&client_hostname)
                         .map(|c| c.trusted)
-                        .unwrap_or(false)
+                        .unwrap_or(false);
+
+                    (discovered, trusted)
                 };
 
+                if discovered && !trusted {
+                    alvr_events::send_event(
+                        LogSeverity::Info,
+                        EventType::ClientDiscovered(ClientDiscoveredEvent {
+                            hostname: client_hostname.clone(),
+                            ip: client_ip,
+                        }),
+                    );
+                }
+
+                let connection_state = SERVER_DATA_MANAGER
+                    .read()
+                    .client_list()
+                    .get(&client_hostname)
+                    .map(|c| c.connection_state.clone());
+
+                // A streaming client announces itself only if it lost the connection and is trying
+                // to reconnect. Drop the old half-open connection, so the next announce goes through
+                if trusted && connection_state == Some(ConnectionState::Streaming) {
+                    info!("Client {client_hostname} is reconnecting, closing the old connection");
+
+                    set_disconnection_reason(
+                        &client_hostname,
+                        DisconnectionReason::ClientRequested,
+                    );
+                    SERVER_DATA_MANAGER.write().update_client_list(
+                        client_hostname.clone(),
+                        ClientListAction::SetConnectionState(ConnectionState::Disconnecting),
+                    );
+                }
+
                 // do not attempt connection if the client is already connected
-                if trusted
+                if (trusted || pairing::is_pending(&client_hostname))
                     && SERVER_DATA_MANAGER
                         .read()
                         .client_list()
@@ -320,9 +777,10 @@ pub fn handshake_loop() {
                         .map(|c| c.connection_state == ConnectionState::Disconnected)
                         .unwrap_or(false)
                 {
-                    if let Err(e) =
-                        try_connect([(client_ip, client_hostname.clone())].into_iter().collect())
-                    {
+                    if let Err(e) = try_connect(
+                        [(client_ip, client_hostname.clone())].into_iter().collect(),
+                        client_address.port(),
+                    ) {
                         error!("Could not initiate connection for {client_hostname}: {e}");
                     }
                 }
@@ -340,42 +798,252 @@ pub fn handshake_loop() {
     }
 }
 
-fn try_connect(mut client_ips: HashMap<IpAddr, String>) -> ConResult {
+// Returns the IP of the configured network interface, or the unspecified address if the interface
+// is set to automatic or currently has no address.
+fn get_bind_ip() -> IpAddr {
+    static INTERFACE_UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+
+    let bind_interface = SERVER_DATA_MANAGER
+        .read()
+        .settings()
+        .connection
+        .bind_interface
+        .clone();
+
+    if let Switch::Enabled(interface) = bind_interface {
+        if let Some(ip) = alvr_sockets::find_interface_ip(&interface) {
+            if INTERFACE_UNAVAILABLE.swap(false, Ordering::Relaxed) {
+                info!("Network interface \"{interface}\" is available again ({ip})");
+            }
+
+            return ip;
+        }
+
+        // Warn only once, this is called on every connection attempt
+        if !INTERFACE_UNAVAILABLE.swap(true, Ordering::Relaxed) {
+            warn!(
+                "Network interface \"{interface}\" has no address. Falling back to automatic interface selection"
+            );
+        }
+    }
+
+    LOCAL_IP
+}
+
+// The port is the control port of the clients
+fn try_connect(mut client_ips: HashMap<IpAddr, String>, port: u16) -> ConResult {
+    let local_ip = get_bind_ip();
+
     let (proto_socket, client_ip) = ProtoControlSocket::connect_to(
         Duration::from_secs(1),
-        PeerType::AnyClient(client_ips.keys().cloned().collect()),
+        PeerType::AnyClient {
+            local_ip,
+            client_ips: client_ips.keys().cloned().collect(),
+            port,
+        },
     )?;
 
     let Some(client_hostname) = client_ips.remove(&client_ip) else {
         con_bail!("unreachable");
     };
 
-    CONNECTION_THREADS.lock().push(thread::spawn(move || {
-        if let Err(e) = connection_pipeline(proto_socket, client_hostname.clone(), client_ip) {
-            error!("Handshake error for {client_hostname}: {e}");
+    spawn_connection_thread(move || {
+        run_connection(proto_socket, local_ip, client_hostname, client_ip, None)
+    });
+
+    Ok(())
+}
+
+// The handles of the threads that ended are dropped, so that the list does not grow with the
+// connection attempts
+fn spawn_connection_thread(f: impl FnOnce() + Send + 'static) {
+    let mut threads = CONNECTION_THREADS.lock();
+    threads.retain(|thread| !thread.is_finished());
+    threads.push(thread::spawn(f));
+}
+
+fn is_wired_client_connected() -> bool {
+    SERVER_DATA_MANAGER
+        .read()
+        .client_list()
+        .values()
+        .any(|client| {
+            client.current_ip.is_some_and(|ip| ip.is_loopback())
+                && client.connection_state != ConnectionState::Disconnected
+        })
+}
+
+// The client connected via USB has no discovery announcement, it is identified by the hostname in
+// its capabilities. The port is the control port of the client, forwarded by adb
+fn try_connect_wired(port: u16, config: &WiredConnectionConfig) -> ConResult {
+    // adb accepts the connection even if the client app is not running, the probe then waits for
+    // the whole handshake timeout. Only one probe runs at a time
+    if WIRED_PROBE_RUNNING.swap(true, Ordering::Relaxed) {
+        return alvr_common::try_again();
+    }
+
+    let (proto_socket, client_ip) = match ProtoControlSocket::connect_to(
+        Duration::from_secs(1),
+        PeerType::AnyClient {
+            local_ip: LOCAL_IP,
+            client_ips: vec![Ipv4Addr::LOCALHOST.into()],
+            port,
+        },
+    ) {
+        Ok(pair) => pair,
+        Err(e) => {
+            WIRED_PROBE_RUNNING.store(false, Ordering::Relaxed);
+            return Err(e);
         }
+    };
 
-        let mut clients_to_be_removed = CLIENTS_TO_BE_REMOVED.lock();
+    let trust_clients = config.trust_clients;
+    spawn_connection_thread(move || {
+        wired_connection(proto_socket, client_ip, trust_clients);
+        WIRED_PROBE_RUNNING.store(false, Ordering::Relaxed);
+    });
 
-        let action = if clients_to_be_removed.contains(&client_hostname) {
-            clients_to_be_removed.remove(&client_hostname);
+    Ok(())
+}
 
-            ClientListAction::RemoveEntry
-        } else {
-            ClientListAction::SetConnectionState(ConnectionState::Disconnected)
+fn wired_connection(mut proto_socket: ProtoControlSocket, client_ip: IpAddr, trust_clients: bool) {
+    // adb accepts the connection even if the client is not listening
+    let Ok(connection_result) = proto_socket.recv(HANDSHAKE_ACTION_TIMEOUT) else {
+        return;
+    };
+    let hostname = match &connection_result {
+        ClientConnectionResult::ConnectionAccepted {
+            streaming_capabilities: Some(caps),
+            ..
+        } => alvr_packets::decode_video_streaming_capabilities(caps)
+            .ok()
+            .and_then(|caps| caps.hostname),
+        _ => None,
+    };
+    let Some(client_hostname) = hostname else {
+        debug!("The client connected via USB did not send its hostname, it is too old");
+        return;
+    };
+
+    let (discovered, trusted, disconnected) = {
+        let mut data_manager = SERVER_DATA_MANAGER.write();
+
+        let discovered = !data_manager.client_list().contains_key(&client_hostname);
+        data_manager.update_client_list(
+            client_hostname.clone(),
+            ClientListAction::AddIfMissing {
+                trusted: false,
+                manual_ips: vec![],
+            },
+        );
+        if trust_clients {
+            data_manager.update_client_list(
+                client_hostname.clone(),
+                ClientListAction::TrustAndMaybeAddIp(None),
+            );
+        }
+
+        let entry = data_manager.client_list().get(&client_hostname);
+        (
+            discovered,
+            entry.is_some_and(|c| c.trusted),
+            entry.is_some_and(|c| c.connection_state == ConnectionState::Disconnected),
+        )
+    };
+
+    if discovered && !trusted {
+        alvr_events::send_event(
+            LogSeverity::Info,
+            EventType::ClientDiscovered(ClientDiscoveredEvent {
+                hostname: client_hostname.clone(),
+                ip: client_ip,
+            }),
+        );
+    }
+
+    if disconnected && (trusted || pairing::is_pending(&client_hostname)) {
+        run_connection(
+            proto_socket,
+            LOCAL_IP,
+            client_hostname,
+            client_ip,
+            Some(connection_result),
+        );
+    }
+}
+
+fn run_connection(
+    proto_socket: ProtoControlSocket,
+    local_ip: IpAddr,
+    client_hostname: String,
+    client_ip: IpAddr,
+    connection_result: Option<ClientConnectionResult>,
+) {
+    // Drop causes recorded while the client was not connected
+    DISCONNECTION_REASONS.lock().remove(&client_hostname);
+
+    let mut client_connected = false;
+    let result = connection_pipeline(
+        proto_socket,
+        local_ip,
+        client_hostname.clone(),
+        client_ip,
+        connection_result,
+        &mut client_connected,
+    );
+    if let Err(e) = &result {
+        error!("Handshake error for {client_hostname}: {e}");
+    }
+
+    let reason = DISCONNECTION_REASONS.lock().remove(&client_hostname);
+    if client_connected {
+        // Without a recorded cause, the state was changed from outside the connection threads
+        let reason = match (reason, result) {
+            (Some(reason), _) => reason,
+            (None, Err(e)) => DisconnectionReason::HandshakeFailed(e.to_string()),
+            (None, Ok(())) => DisconnectionReason::ServerRequested,
         };
-        SERVER_DATA_MANAGER
-            .write()
-            .update_client_list(client_hostname, action);
-    }));
 
-    Ok(())
+        alvr_events::send_event(
+            if matches!(
+                reason,
+                DisconnectionReason::KeepaliveTimeout
+                    | DisconnectionReason::SocketError(_)
+                    | DisconnectionReason::HandshakeFailed(_)
+            ) {
+                LogSeverity::Warning
+            } else {
+                LogSeverity::Info
+            },
+            EventType::ClientDisconnected(ClientDisconnectedEvent {
+                hostname: client_hostname.clone(),
+                reason,
+            }),
+        );
+    }
+
+    let mut clients_to_be_removed = CLIENTS_TO_BE_REMOVED.lock();
+
+    let action = if clients_to_be_removed.contains(&client_hostname) {
+        clients_to_be_removed.remove(&client_hostname);
+
+        ClientListAction::RemoveEntry
+    } else {
+        ClientListAction::SetConnectionState(ConnectionState::Disconnected)
+    };
+    SERVER_DATA_MANAGER
+        .write()
+        .update_client_list(client_hostname, action);
 }
 
 fn connection_pipeline(
     mut proto_socket: ProtoControlSocket,
+    local_ip: IpAddr,
     client_hostname: String,
     client_ip: IpAddr,
+    // Already received for the clients connected via USB, to find their hostname
+    connection_result: Option<ClientConnectionResult>,
+    client_connected: &mut bool,
 ) -> ConResult {
     // This session lock will make sure settings cannot be changed while connecting and no other
     // client can connect (until handshake is finished)
@@ -392,51 +1060,122 @@ fn connection_pipeline(
 
     let disconnect_notif = Arc::new(Condvar::new());
 
-    let connection_result = match proto_socket.recv(HANDSHAKE_ACTION_TIMEOUT) {
-        Ok(r) => r,
-        Err(ConnectionError::TryAgain(e)) => {
-            debug!("Failed to recive client connection packet. This is normal for USB connection.\n{e}");
+    let connection_result = match connection_result {
+        Some(r) => r,
+        None => match proto_socket.recv(HANDSHAKE_ACTION_TIMEOUT) {
+            Ok(r) => r,
+            Err(ConnectionError::TryAgain(e)) => {
+                debug!("Failed to recive client connection packet. This is normal for USB connection.\n{e}");
 
-            return Ok(());
-        }
-        Err(e) => return Err(e),
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        },
     };
 
-    let maybe_streaming_caps = if let ClientConnectionResult::ConnectionAccepted {
-        client_protocol_id,
-        display_name,
-        streaming_capabilities,
-        ..
-    } = connection_result
-    {
-        server_data_lock.update_client_list(
-            client_hostname.clone(),
-            ClientListAction::SetDisplayName(display_name),
-        );
+    let (client_display_name, maybe_streaming_caps) =
+        if let ClientConnectionResult::ConnectionAccepted {
+            client_protocol_id,
+            display_name,
+            streaming_capabilities,
+            ..
+        } = connection_result
+        {
+            server_data_lock.update_client_list(
+                client_hostname.clone(),
+                ClientListAction::SetDisplayName(display_name.clone()),
+            );
 
-        if client_protocol_id != alvr_common::protocol_id_u64() {
-            warn!(
-                "Trusted client is incompatible! Expected protocol ID: {}, found: {}",
-                alvr_common::protocol_id_u64(),
-                client_protocol_id,
+            let expected_protocol_id = alvr_packets::protocol_id_u64();
+            if client_protocol_id != expected_protocol_id {
+                if !server_data_lock
+                    .settings()
+                    .connection
+                    .accept_incompatible_clients
+                {
+                    warn!(
+                        "Trusted client is incompatible! Expected protocol ID: {}, found: {}",
+                        expected_protocol_id, client_protocol_id,
+                    );
+                    alvr_events::send_event(
+                        LogSeverity::Warning,
+                        EventType::IncompatibleClient(client_hostname),
+                    );
+
+                    return Ok(());
+                }
+
+                warn!(
+                    "ACCEPTING INCOMPATIBLE CLIENT {client_hostname}! Expected protocol ID: {}, found: {}. The connection may fail or misbehave",
+                    expected_protocol_id, client_protocol_id,
+                );
+                alvr_events::send_event(
+                    LogSeverity::Warning,
+                    EventType::IncompatibleClientAccepted(IncompatibleClientAcceptedEvent {
+                        hostname: client_hostname.clone(),
+                        expected_protocol_id,
+                        client_protocol_id,
+                    }),
+                );
+
+                proto_socket.set_protocol_mismatch();
+            }
+
+            alvr_events::send_event(
+                LogSeverity::Info,
+                EventType::ClientConnected(ClientConnectedEvent {
+                    hostname: client_hostname.clone(),
+                    display_name: display_name.clone(),
+                    ip: client_ip,
+                }),
             );
+            *client_connected = true;
 
+            (display_name, streaming_capabilities)
+        } else {
+            debug!("Found client in standby. Retrying");
             return Ok(());
-        }
-
-        streaming_capabilities
-    } else {
-        debug!("Found client in standby. Retrying");
-        return Ok(());
-    };
+        };
 
-    let streaming_caps = if let Some(streaming_caps) = maybe_streaming_caps {
-        alvr_packets::decode_video_streaming_capabilities(&streaming_caps).to_con()?
+    let (streaming_caps, caps_json) = if let Some(streaming_caps) = maybe_streaming_caps {
+        (
+            alvr_packets::decode_video_streaming_capabilities(&streaming_caps).to_con()?,
+            alvr_packets::video_streaming_capabilities_extension(&streaming_caps).to_con()?,
+        )
     } else {
         con_bail!("Only streaming clients are supported for now");
     };
 
-    let settings = server_data_lock.settings().clone();
+    // Applied before the negotiation, so that they are used by this stream already
+    if server_data_lock
+        .client_list()
+        .get(&client_hostname)
+        .is_some_and(|entry| entry.device_preset.is_none())
+    {
+        match server_data_lock.apply_device_preset(
+            client_hostname.clone(),
+            streaming_caps.device_model.as_deref(),
+            streaming_caps.default_view_resolution,
+            true,
+        ) {
+            Ok((event, _)) => {
+                info!(
+                    "Applied the recommended defaults for {} to {client_hostname}",
+                    event.preset
+                );
+                alvr_events::send_event(LogSeverity::Info, EventType::DevicePreset(event));
+            }
+            Err(e) => warn!("Failed to apply the recommended defaults for {client_hostname}: {e}"),
+        }
+    }
+
+    let mut settings = server_data_lock.settings().clone();
+
+    // adb cannot forward UDP
+    let wired = client_ip.is_loopback();
+    if wired {
+        settings.connection.stream_protocol = SocketProtocol::Tcp;
+    }
 
     fn get_view_res(config: FrameSize, default_res: UVec2) -> UVec2 {
         let res = match config {
@@ -456,10 +1195,15 @@ fn connection_pipeline(
         UVec2::new(align32(res.x), align32(res.y))
     }
 
-    let stream_view_resolution = get_view_res(
-        settings.video.transcoding_view_resolution,
-        streaming_caps.default_view_resolution,
-    );
+    let stream_view_resolution = {
+        let default_res = get_view_res(
+            settings.video.transcoding_view_resolution,
+            streaming_caps.default_view_resolution,
+        );
+        let res = default_res.as_vec2() * THERMAL_RESOLUTION_SCALE.lock().unwrap_or(1.0);
+
+        UVec2::new(align32(res.x), align32(res.y))
+    };
 
     let target_view_resolution = get_view_res(
         settings.video.emulated_headset_view_resolution,
@@ -543,6 +1287,18 @@ fn connection_pipeline(
         settings.video.preferred_codec
     };
 
+    let slice_count = if settings.video.encoder_config.slice_count > 1 && codec != CodecType::AV1 {
+        if streaming_caps.multi_slice_decoding {
+            settings.video.encoder_config.slice_count
+        } else {
+            warn!("Multi-slice encoding is not supported by the client.");
+
+            1
+        }
+    } else {
+        1
+    };
+
     let game_audio_sample_rate =
         if let Switch::Enabled(game_audio_config) = &settings.audio.game_audio {
             let game_audio_device = AudioDevice::new_output(
@@ -570,13 +1326,43 @@ fn connection_pipeline(
             0
         };
 
+    let udp_video = streaming_caps.udp_video
+        && !wired
+        && settings.connection.stream_protocol == SocketProtocol::Tcp
+        && settings.connection.udp_video.enabled();
+    let tracking_delta_encoding =
+        streaming_caps.tracking_delta_encoding && settings.connection.tracking_delta_encoding;
+    // Delta packets have their own encoding
+    let fixed_layout_tracking = streaming_caps.fixed_layout_tracking && !tracking_delta_encoding;
+
+    let mut client_session = server_data_lock.session().clone();
+    if wired {
+        client_session
+            .session_settings
+            .connection
+            .stream_protocol
+            .variant = SocketProtocolDefaultVariant::Tcp;
+    }
+
     let stream_config_packet = alvr_packets::encode_stream_config(
-        server_data_lock.session(),
+        &client_session,
         &NegotiatedStreamingConfig {
             view_resolution: stream_view_resolution,
             refresh_rate_hint: fps,
             game_audio_sample_rate,
             enable_foveated_encoding,
+            slice_count,
+            tracking_delta_encoding,
+            tracking_send_rate_hz: settings
+                .headset
+                .tracking_send_rate_hz
+                .as_option()
+                .map(|rate| rate.clamp(MIN_TRACKING_SEND_RATE_HZ, MAX_TRACKING_SEND_RATE_HZ)),
+            vector2_button_values: true,
+            haptics_envelopes: streaming_caps.haptics_envelopes,
+            playspace_boundary: true,
+            udp_video,
+            fixed_layout_tracking,
         },
     )
     .to_con()?;
@@ -585,6 +1371,24 @@ fn connection_pipeline(
     let (mut control_sender, mut control_receiver) =
         proto_socket.split(STREAMING_RECV_TIMEOUT).to_con()?;
 
+    if pairing::is_pending(&client_hostname) {
+        // The user can take a while to enter the code, the dashboard must not be blocked meanwhile
+        RwLockWriteGuard::unlocked(&mut server_data_lock, || {
+            pairing::verify_client(&client_hostname, &mut control_sender, &mut control_receiver)
+        })?;
+
+        server_data_lock.update_client_list(
+            client_hostname.clone(),
+            ClientListAction::TrustAndMaybeAddIp(None),
+        );
+    } else if !server_data_lock
+        .client_list()
+        .get(&client_hostname)
+        .is_some_and(|entry| entry.trusted)
+    {
+        con_bail!("The client is not trusted");
+    }
+
     let mut new_openvr_config = contruct_openvr_config(server_data_lock.session());
     new_openvr_config.eye_resolution_width = stream_view_resolution.x;
     new_openvr_config.eye_resolution_height = stream_view_resolution.y;
@@ -595,8 +1399,54 @@ fn connection_pipeline(
     new_openvr_config.h264_profile = encoder_profile as _;
     new_openvr_config.use_10bit_encoder = enable_10_bits_encoding;
     new_openvr_config.codec = codec as _;
+    new_openvr_config.slice_count = slice_count;
+
+    let driver_restart = server_data_lock.session().openvr_config != new_openvr_config;
+
+    // The fields of VideoStreamingCapabilitiesLegacy are shown separately
+    let mut extra_capabilities = caps_json.as_object().cloned().unwrap_or_default();
+    for key in [
+        "default_view_resolution",
+        "supported_refresh_rates",
+        "preferred_refresh_rate",
+        "microphone_sample_rate",
+    ] {
+        extra_capabilities.remove(key);
+    }
+    server_data_lock.update_client_list(
+        client_hostname.clone(),
+        ClientListAction::SetHeadsetInfo(HeadsetInfo {
+            captured_at: 0,
+            default_view_resolution: streaming_caps.default_view_resolution,
+            supported_refresh_rates: streaming_caps.supported_refresh_rates.clone(),
+            preferred_refresh_rate: caps_json["preferred_refresh_rate"]
+                .as_f64()
+                .map(|rate| rate as f32),
+            microphone_sample_rate: streaming_caps.microphone_sample_rate,
+            extra_capabilities: extra_capabilities.into_iter().collect(),
+            negotiated_view_resolution: stream_view_resolution,
+            negotiated_refresh_rate: fps,
+            negotiated_codec: codec,
+            negotiated_foveated_encoding: enable_foveated_encoding,
+            negotiated_10_bits_encoding: enable_10_bits_encoding,
+            negotiated_slice_count: slice_count,
+        }),
+    );
 
-    if server_data_lock.session().openvr_config != new_openvr_config {
+    let stream_setup = StreamSetupEvent {
+        hostname: client_hostname.clone(),
+        codec,
+        view_resolution: stream_view_resolution,
+        refresh_rate: fps,
+        driver_restart,
+    };
+    alvr_events::send_event(
+        LogSeverity::Info,
+        EventType::StreamSetup(stream_setup.clone()),
+    );
+    *STREAM_SETUP.lock() = Some(stream_setup);
+
+    if driver_restart {
         server_data_lock.session_mut().openvr_config = new_openvr_config;
 
         control_sender.send(&ServerControlPacket::Restarting).ok();
@@ -620,12 +1470,16 @@ fn connection_pipeline(
         } else {
             0.0
         },
+        Duration::from_secs_f32(1.0 / f32::max(settings.connection.statistics_event_rate, 1.0)),
     ));
 
     *BITRATE_MANAGER.lock() = BitrateManager::new(settings.video.bitrate.history_size, fps);
+    bitrate::ENCODER_BITRATE_BPS.set(0.0);
+    KEEPALIVE_RTT_MS.reset();
 
     let mut stream_socket = StreamSocketBuilder::connect_to_client(
         HANDSHAKE_ACTION_TIMEOUT,
+        local_ip,
         client_ip,
         settings.connection.stream_port,
         settings.connection.stream_protocol,
@@ -633,16 +1487,113 @@ fn connection_pipeline(
         settings.connection.server_send_buffer_bytes,
         settings.connection.server_recv_buffer_bytes,
         settings.connection.packet_size as _,
-    )?;
+    )
+    .map_err(|e| {
+        if let ConnectionError::Other(e) = &e {
+            sockets::report_port_conflict(
+                e,
+                settings.connection.stream_port,
+                settings.connection.stream_protocol,
+                PortUsage::Stream,
+            );
+        }
+
+        e
+    })?;
+
+    if let Switch::Enabled(config) = &settings.connection.network_simulator {
+        let streams = [
+            (VIDEO, &config.video),
+            (AUDIO, &config.game_audio),
+            (HAPTICS, &config.haptics),
+        ]
+        .into_iter()
+        .filter_map(|(stream_id, impairment)| Some((stream_id, impairment.as_option()?.clone())))
+        .collect::<HashMap<_, _>>();
+
+        warn!("Network simulator enabled for {client_hostname}");
+        alvr_events::send_event(
+            LogSeverity::Warning,
+            EventType::NetworkSimulator(NetworkSimulatorEvent {
+                hostname: client_hostname.clone(),
+                config: serde_json::to_value(config).unwrap_or_default(),
+            }),
+        );
+
+        stream_socket.simulate_network(config.seed, streams);
+    }
 
     let mut video_sender = stream_socket.request_stream(VIDEO);
+    let mut video_datagram_sender = match &settings.connection.udp_video {
+        Switch::Enabled(config) if udp_video => match DatagramSender::connect(
+            local_ip,
+            client_ip,
+            config.port,
+            settings.connection.dscp,
+            settings.connection.server_send_buffer_bytes,
+            config.max_datagram_size as _,
+        ) {
+            Ok(mut sender) => {
+                if let Switch::Enabled(simulator) = &settings.connection.network_simulator {
+                    if let Some(video) = simulator.video.as_option() {
+                        if let Err(e) =
+                            sender.simulate_network(simulator.seed, VIDEO, video.clone())
+                        {
+                            warn!("Network simulator not applied to the video datagrams: {e}");
+                        }
+                    }
+                }
+                sender.send_probe().ok();
+                Some(sender)
+            }
+            // The client falls back to the VIDEO stream when no datagram arrives
+            Err(e) => {
+                warn!("Failed to create the video UDP socket: {e}");
+                None
+            }
+        },
+        _ => None,
+    };
+    UDP_VIDEO_ACTIVE.store(video_datagram_sender.is_some(), Ordering::Relaxed);
     let game_audio_sender = stream_socket.request_stream(AUDIO);
-    let mut microphone_receiver = stream_socket.subscribe_to_stream(AUDIO, MAX_UNREAD_PACKETS);
-    let mut tracking_receiver =
-        stream_socket.subscribe_to_stream::<Tracking>(TRACKING, MAX_UNREAD_PACKETS);
-    let haptics_sender = stream_socket.request_stream(HAPTICS);
-    let mut statics_receiver =
-        stream_socket.subscribe_to_stream::<ClientStatistics>(STATISTICS, MAX_UNREAD_PACKETS);
+    let mut microphone_receiver =
+        stream_socket.subscribe_to_stream(AUDIO, MAX_UNREAD_PACKETS, OverflowPolicy::DropNewest);
+    // Old poses are useless once newer ones arrived
+    let mut tracking_receiver = if tracking_delta_encoding {
+        TrackingReceiver::Delta(
+            stream_socket.subscribe_to_stream(
+                TRACKING_DELTA,
+                MAX_UNREAD_TRACKING_PACKETS,
+                OverflowPolicy::DropOldest,
+            ),
+            TrackingDeltaDecoder::default(),
+        )
+    } else if fixed_layout_tracking {
+        TrackingReceiver::Fixed(stream_socket.subscribe_to_stream(
+            TRACKING_FIXED,
+            MAX_UNREAD_TRACKING_PACKETS,
+            OverflowPolicy::DropOldest,
+        ))
+    } else {
+        TrackingReceiver::Full(stream_socket.subscribe_to_stream(
+            TRACKING,
+            MAX_UNREAD_TRACKING_PACKETS,
+            OverflowPolicy::DropOldest,
+        ))
+    };
+    let haptics_sender = if streaming_caps.haptics_envelopes {
+        HapticsSender::Envelope(
+            stream_socket.request_stream(HAPTICS),
+            HapticsEnvelopeBuilder::default(),
+        )
+    } else {
+        HapticsSender::Plain(stream_socket.request_stream(HAPTICS))
+    };
+    let mut statics_receiver = stream_socket.subscribe_to_stream::<ClientStatistics>(
+        STATISTICS,
+        MAX_UNREAD_PACKETS,
+        OverflowPolicy::DropNewest,
+    );
 
     let (video_channel_sender, video_channel_receiver) =
         std::sync::mpsc::sync_channel(settings.connection.max_queued_server_video_frames);
@@ -651,21 +1602,71 @@ fn connection_pipeline(
 
     let video_send_thread = thread::spawn({
         let client_hostname = client_hostname.clone();
+        let frame_interval = Duration::from_secs_f32(1.0 / fps);
         move || {
             while is_streaming(&client_hostname) {
+                let udp_video_active = UDP_VIDEO_ACTIVE.load(Ordering::Relaxed);
+
                 let VideoPacket { header, payload } =
                     match video_channel_receiver.recv_timeout(STREAMING_RECV_TIMEOUT) {
                         Ok(packet) => packet,
-                        Err(RecvTimeoutError::Timeout) => continue,
+                        Err(RecvTimeoutError::Timeout) => {
+                            // Lets the client detect that datagrams reach it while there is no
+                            // video to send
+                            if let Some(sender) = &mut video_datagram_sender {
+                                if udp_video_active {
+                                    sender.send_probe().ok();
+                                }
+                            }
+
+                            continue;
+                        }
                         Err(RecvTimeoutError::Disconnected) => return,
                     };
 
-                let mut buffer = video_sender.get_buffer(&header).unwrap();
-                // todo: make encoder write to socket buffers directly to avoid copy
-                buffer
-                    .get_range_mut(0, payload.len())
-                    .copy_from_slice(&payload);
-                video_sender.send(buffer).ok();
+                let pacing_duration = if let Switch::Enabled(config) = &SERVER_DATA_MANAGER
+                    .read()
+                    .settings()
+                    .connection
+                    .video_pacing
+                {
+                    Some(video_pacing_duration(
+                        config,
+                        &header,
+                        payload.len(),
+                        frame_interval,
+                    ))
+                } else {
+                    None
+                };
+
+                let pacing = match &mut video_datagram_sender {
+                    Some(sender) if udp_video_active => sender
+                        .send_paced(
+                            &header,
+                            &payload,
+                            header.is_idr,
+                            pacing_duration.unwrap_or_default(),
+                        )
+                        .ok(),
+                    _ => {
+                        let mut buffer = video_sender.get_buffer(&header).unwrap();
+                        // todo: make encoder write to socket buffers directly to avoid copy
+                        buffer
+                            .get_range_mut(0, payload.len())
+                            .copy_from_slice(&payload);
+
+                        video_sender
+                            .send_paced(buffer, pacing_duration.unwrap_or_default())
+                            .ok()
+                    }
+                };
+
+                if let Some(pacing) = pacing {
+                    if let Some(stats) = &mut *STATISTICS_MANAGER.lock() {
+                        stats.report_video_sent(header.timestamp, pacing);
+                    }
+                }
             }
         }
     });
@@ -674,6 +1675,11 @@ fn connection_pipeline(
         let client_hostname = client_hostname.clone();
         thread::spawn(move || {
             while is_streaming(&client_hostname) {
+                if STANDBY.load(Ordering::SeqCst) {
+                    thread::sleep(STREAMING_RECV_TIMEOUT);
+                    continue;
+                }
+
                 let device = match AudioDevice::new_output(
                     Some(settings.audio.linux_backend),
                     config.device.as_ref(),
@@ -703,11 +1709,12 @@ fn connection_pipeline(
                 if let Err(e) = alvr_audio::record_audio_blocking(
                     Arc::new({
                         let client_hostname = client_hostname.clone();
-                        move || is_streaming(&client_hostname)
+                        move || is_streaming(&client_hostname) && !STANDBY.load(Ordering::SeqCst)
                     }),
                     game_audio_sender.clone(),
                     &device,
                     2,
+                    1.0,
                     config.mute_when_streaming,
                 ) {
                     error!("Audio record error: {e:?}");
@@ -763,7 +1770,9 @@ fn connection_pipeline(
                 1,
                 streaming_caps.microphone_sample_rate,
                 config.buffering,
+                Arc::new(Mutex::new(1.0)),
                 &mut microphone_receiver,
+                |_| (),
             ));
         })
     } else {
@@ -772,10 +1781,19 @@ fn connection_pipeline(
 
     let tracking_manager = Arc::new(Mutex::new(TrackingManager::new()));
     let hand_gesture_manager = Arc::new(Mutex::new(HandGestureManager::new()));
+    let device_registry = Arc::new(Mutex::new(DeviceRegistry::default()));
+    let pose_osc_sender = settings
+        .headset
+        .pose_osc
+        .clone()
+        .into_option()
+        .map(PoseOscSender::new);
 
     let tracking_receive_thread = thread::spawn({
         let tracking_manager = Arc::clone(&tracking_manager);
         let hand_gesture_manager = Arc::clone(&hand_gesture_manager);
+        let device_registry = Arc::clone(&device_registry);
+        let mut pose_osc_sender = pose_osc_sender.clone();
 
         let mut gestures_button_mapping_manager =
             settings.headset.controllers.as_option().map(|config| {
@@ -805,16 +1823,50 @@ fn connection_pipeline(
                         BodyTrackingSink::new(config.sink, settings.connection.osc_local_port).ok()
                     });
 
+            let mut dropped_packets = 0;
             while is_streaming(&client_hostname) {
-                let data = match tracking_receiver.recv(STREAMING_RECV_TIMEOUT) {
+                let mut tracking = match tracking_receiver.recv(STREAMING_RECV_TIMEOUT) {
                     Ok(tracking) => tracking,
                     Err(ConnectionError::TryAgain(_)) => continue,
                     Err(ConnectionError::Other(_)) => return,
                 };
-                let Ok(tracking) = data.get_header() else {
-                    return;
+
+                let new_dropped_packets = tracking_receiver.dropped_packets();
+                if new_dropped_packets != dropped_packets {
+                    if let Some(stats) = &mut *STATISTICS_MANAGER.lock() {
+                        stats.report_stream_packets_dropped(new_dropped_packets - dropped_packets);
+                    }
+                    dropped_packets = new_dropped_packets;
+                }
+
+                let (tracker_ids, undeclared_controller_ids) = {
+                    let mut device_registry_lock = device_registry.lock();
+                    device_registry_lock.filter_motions(&mut tracking.device_motions);
+
+                    (
+                        device_registry_lock.tracker_ids(),
+                        device_registry_lock.undeclared_controller_ids(),
+                    )
                 };
 
+                if let Some(capture) = &*TIMING_CAPTURE.lock() {
+                    let motion = |device_id: u64| {
+                        tracking
+                            .device_motions
+                            .iter()
+                            .find(|(id, _)| *id == device_id)
+                            .map(|(_, motion)| *motion)
+                    };
+
+                    capture.report_tracking(TrackingRow {
+                        arrival: Instant::now(),
+                        target_timestamp: tracking.target_timestamp,
+                        head: motion(*HEAD_ID),
+                        left_hand: motion(*HAND_LEFT_ID).map(|motion| motion.pose),
+                        right_hand: motion(*HAND_RIGHT_ID).map(|motion| motion.pose),
+                    });
+                }
+
                 let controllers_config = {
                     let data_lock = SERVER_DATA_MANAGER.read();
                     data_lock
@@ -845,8 +1897,13 @@ fn connection_pipeline(
                             tracking.hand_skeletons[0].is_some(),
                             tracking.hand_skeletons[1].is_some(),
                         ],
+                        &tracker_ids,
                     );
 
+                    if let Some(sender) = &mut pose_osc_sender {
+                        sender.report_poses(&motions);
+                    }
+
                     left_hand_skeleton = tracking.hand_skeletons[0].map(|s| {
                         tracking::to_openvr_hand_skeleton(headset_config, *HAND_LEFT_ID, s)
                     });
@@ -865,23 +1922,33 @@ fn connection_pipeline(
 
                 {
                     let data_manager_lock = SERVER_DATA_MANAGER.read();
+                    input_debug::set_enabled(
+                        data_manager_lock.settings().logging.input_debug_events,
+                    );
+
                     if data_manager_lock.settings().logging.log_tracking {
-                        alvr_events::send_event(EventType::Tracking(Box::new(TrackingEvent {
-                            device_motions: motions
-                                .iter()
-                                .filter_map(|(id, motion)| {
-                                    Some(((*DEVICE_ID_TO_PATH.get(id)?).into(), *motion))
-                                })
-                                .collect(),
-                            hand_skeletons: [left_hand_skeleton, right_hand_skeleton],
-                            eye_gazes: local_eye_gazes,
-                            fb_face_expression: tracking.face_data.fb_face_expression.clone(),
-                            htc_eye_expression: tracking.face_data.htc_eye_expression.clone(),
-                            htc_lip_expression: tracking.face_data.htc_lip_expression.clone(),
-                        })))
+                        alvr_events::send_event(
+                            LogSeverity::Debug,
+                            EventType::Tracking(Box::new(TrackingEvent {
+                                device_motions: motions
+                                    .iter()
+                                    .filter_map(|(id, motion)| {
+                                        Some(((*DEVICE_ID_TO_PATH.get(id)?).into(), *motion))
+                                    })
+                                    .collect(),
+                                hand_skeletons: [left_hand_skeleton, right_hand_skeleton],
+                                eye_gazes: local_eye_gazes,
+                                fb_face_expression: tracking.face_data.fb_face_expression.clone(),
+                                htc_eye_expression: tracking.face_data.htc_eye_expression.clone(),
+                                htc_lip_expression: tracking.face_data.htc_lip_expression.clone(),
+                            })),
+                        )
                     }
                 }
 
+                input_debug::report_motions(&tracking.device_motions, &motions);
+                input_debug::send_snapshot_if_due();
+
                 if let Some(sink) = &mut face_tracking_sink {
                     let mut face_data = tracking.face_data;
                     face_data.eye_gazes = local_eye_gazes;
@@ -904,10 +1971,43 @@ fn connection_pipeline(
                     }
                 }
 
-                let ffi_motions = motions
+                let extrapolation_s = SERVER_DATA_MANAGER
+                    .read()
+                    .settings()
+                    .headset
+                    .pose_extrapolation
+                    .as_option()
+                    .and_then(|config| {
+                        let max_s = config.max_prediction_ms / 1000.0;
+                        STATISTICS_MANAGER
+                            .lock()
+                            .as_ref()
+                            .map(|stats| stats.pose_extrapolation_offset_s().clamp(-max_s, max_s))
+                    });
+
+                let mut ffi_motions = motions
                     .into_iter()
-                    .map(|(id, motion)| tracking::to_ffi_motion(id, motion))
+                    .map(|(id, motion)| {
+                        let motion = if let Some(delta_s) = extrapolation_s {
+                            tracking::extrapolate_motion(motion, delta_s)
+                        } else {
+                            motion
+                        };
+
+                        tracking::to_ffi_motion(id, motion)
+                    })
                     .collect::<Vec<_>>();
+                // Without a pose update the undeclared controllers would not be disconnected
+                ffi_motions.extend(
+                    undeclared_controller_ids
+                        .iter()
+                        .map(|id| tracking::to_ffi_motion(*id, DeviceMotion::default())),
+                );
+                let controllers_tracked = [*HAND_LEFT_ID, *HAND_RIGHT_ID]
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, id)| track_controllers && !undeclared_controller_ids.contains(*id))
+                    .fold(0, |mask, (index, _)| mask | 1 << index);
 
                 let ffi_body_trackers: Option<Vec<crate::FfiBodyTracker>> = {
                     let tracking_manager_lock = tracking_manager.lock();
@@ -985,7 +2085,7 @@ fn connection_pipeline(
                             } else {
                                 ptr::null()
                             },
-                            track_controllers.into(),
+                            controllers_tracked,
                             if let Some(body_trackers) = &ffi_body_trackers {
                                 body_trackers.as_ptr()
                             } else {
@@ -1005,8 +2105,11 @@ fn connection_pipeline(
 
     let statistics_thread = thread::spawn({
         let client_hostname = client_hostname.clone();
+        let statistics_overlay_enabled = settings.logging.statistics_overlay.as_option().is_some();
         move || {
             let mut _last_resync = Instant::now();
+            let mut last_overlay_report = Instant::now();
+            let mut dropped_packets = 0;
             while is_streaming(&client_hostname) {
                 let data = match statics_receiver.recv(STREAMING_RECV_TIMEOUT) {
                     Ok(stats) => stats,
@@ -1018,6 +2121,10 @@ fn connection_pipeline(
                 };
 
                 if let Some(stats) = &mut *STATISTICS_MANAGER.lock() {
+                    let new_dropped_packets = statics_receiver.dropped_packets();
+                    stats.report_stream_packets_dropped(new_dropped_packets - dropped_packets);
+                    dropped_packets = new_dropped_packets;
+
                     let timestamp = client_stats.target_timestamp;
                     let decoder_latency = client_stats.video_decode;
                     let (network_latency, _game_latency) = stats.report_statistics(client_stats);
@@ -1032,12 +2139,31 @@ fn connection_pipeline(
                         network_latency,
                         decoder_latency,
                     );
+
+                    if statistics_overlay_enabled
+                        && last_overlay_report + OVERLAY_STATISTICS_INTERVAL < Instant::now()
+                    {
+                        last_overlay_report = Instant::now();
+
+                        if let Some(control_sender) = CONTROL_SENDER.lock().clone() {
+                            control_sender
+                                .lock()
+                                .send(&alvr_packets::encode_reserved_server_control_packet(
+                                    &ReservedServerControlPacket::Statistics(
+                                        stats.overlay_statistics(),
+                                    ),
+                                ))
+                                .ok();
+                        }
+                    }
                 }
             }
         }
     });
 
     let control_sender = Arc::new(Mutex::new(control_sender));
+    *CONTROL_SENDER.lock() = Some(Arc::clone(&control_sender));
+    *STREAMING_CAPS.lock() = Some(streaming_caps.clone());
 
     let keepalive_thread = thread::spawn({
         let control_sender = Arc::clone(&control_sender);
@@ -1045,9 +2171,22 @@ fn connection_pipeline(
         let client_hostname = client_hostname.clone();
         move || {
             while is_streaming(&client_hostname) {
-                if let Err(e) = control_sender.lock().send(&ServerControlPacket::KeepAlive) {
+                let ping = alvr_packets::encode_reserved_server_control_packet(
+                    &ReservedServerControlPacket::KeepAlivePing(unix_time()),
+                );
+                let res = {
+                    let mut control_sender = control_sender.lock();
+                    control_sender
+                        .send(&ServerControlPacket::KeepAlive)
+                        .and_then(|_| control_sender.send(&ping))
+                };
+                if let Err(e) = res {
                     info!("Client disconnected. Cause: {e:?}");
 
+                    set_disconnection_reason(
+                        &client_hostname,
+                        DisconnectionReason::SocketError(e.to_string()),
+                    );
                     disconnect_notif.notify_one();
 
                     return;
@@ -1080,6 +2219,8 @@ fn connection_pipeline(
 
         let disconnect_notif = Arc::clone(&disconnect_notif);
         let control_sender = Arc::clone(&control_sender);
+        let device_registry = Arc::clone(&device_registry);
+        let pose_osc_sender = pose_osc_sender.clone();
         let client_hostname = client_hostname.clone();
         move || {
             unsafe {
@@ -1088,12 +2229,44 @@ fn connection_pipeline(
             }
 
             let mut disconnection_deadline = Instant::now() + KEEPALIVE_TIMEOUT;
+            let mut headset_removed_instant = None::<Instant>;
+            let mut last_thermal_status = ThermalStatus::None;
+            let mut battery_warnings = BatteryWarnings::default();
+            let mut thumbstick_processor = ThumbstickProcessor::default();
+            let mut virtual_gamepad = VirtualGamepad::default();
+            let mut application_profiles = ApplicationProfileManager::default();
+            let mut next_application_check = Instant::now();
             while is_streaming(&client_hostname) {
+                if Instant::now() >= next_application_check {
+                    application_profiles.update();
+                    next_application_check = Instant::now() + APPLICATION_CHECK_INTERVAL;
+                }
+
+                let standby_timeout = SERVER_DATA_MANAGER
+                    .read()
+                    .settings()
+                    .connection
+                    .proximity_standby
+                    .as_option()
+                    .map(|config| Duration::from_secs_f32(config.timeout_s));
+                match (standby_timeout, headset_removed_instant) {
+                    (Some(timeout), Some(instant)) if instant.elapsed() >= timeout => {
+                        set_headset_removed(true)
+                    }
+                    (None, _) => set_headset_removed(false),
+                    _ => (),
+                }
+
                 let packet = match control_receiver.recv(STREAMING_RECV_TIMEOUT) {
                     Ok(packet) => packet,
                     Err(ConnectionError::TryAgain(_)) => {
                         if Instant::now() > disconnection_deadline {
                             info!("Client disconnected. Timeout");
+
+                            set_disconnection_reason(
+                                &client_hostname,
+                                DisconnectionReason::KeepaliveTimeout,
+                            );
                             break;
                         } else {
                             continue;
@@ -1101,29 +2274,20 @@ fn connection_pipeline(
                     }
                     Err(e) => {
                         info!("Client disconnected. Cause: {e}");
+
+                        set_disconnection_reason(
+                            &client_hostname,
+                            DisconnectionReason::SocketError(e.to_string()),
+                        );
                         break;
                     }
                 };
 
                 match packet {
-                    ClientControlPacket::PlayspaceSync(packet) => {
+                    ClientControlPacket::PlayspaceSync(area) => {
                         if !settings.headset.tracking_ref_only {
-                            let data_manager_lock = SERVER_DATA_MANAGER.read();
-                            let config = &data_manager_lock.settings().headset;
-                            tracking_manager.lock().recenter(
-                                config.position_recentering_mode,
-                                config.rotation_recentering_mode,
-                            );
-
-                            let area = packet.unwrap_or(Vec2::new(2.0, 2.0));
-                            let wh = area.x * area.y;
-                            if wh.is_finite() && wh > 0.0 {
-                                info!("Received new playspace with size: {}", area);
-                                unsafe { crate::SetChaperoneArea(area.x, area.y) };
-                            } else {
-                                warn!("Received invalid playspace size: {}", area);
-                                unsafe { crate::SetChaperoneArea(2.0, 2.0) };
-                            }
+                            recenter_playspace(&tracking_manager);
+                            playspace::set_area(area);
                         }
                     }
                     ClientControlPacket::RequestIdr => {
@@ -1142,27 +2306,18 @@ fn connection_pipeline(
                         }
                         unsafe { crate::VideoErrorReportReceive() };
                     }
-                    ClientControlPacket::ViewsConfig(config) => unsafe {
-                        crate::SetViewsConfig(FfiViewsConfig {
-                            fov: [
-                                FfiFov {
-                                    left: config.fov[0].left,
-                                    right: config.fov[0].right,
-                                    up: config.fov[0].up,
-                                    down: config.fov[0].down,
-                                },
-                                FfiFov {
-                                    left: config.fov[1].left,
-                                    right: config.fov[1].right,
-                                    up: config.fov[1].up,
-                                    down: config.fov[1].down,
-                                },
-                            ],
-                            ipd_m: config.ipd_m,
-                        });
-                    },
-                    ClientControlPacket::Battery(packet) => unsafe {
-                        crate::SetBattery(packet.device_id, packet.gauge_value, packet.is_plugged);
+                    ClientControlPacket::ViewsConfig(config) => {
+                        set_views_config(&config);
+                        *CLIENT_VIEWS_CONFIG.lock() = Some(config);
+                    }
+                    ClientControlPacket::Battery(packet) => {
+                        unsafe {
+                            crate::SetBattery(
+                                packet.device_id,
+                                packet.gauge_value,
+                                packet.is_plugged,
+                            )
+                        };
 
                         if let Some(stats) = &mut *STATISTICS_MANAGER.lock() {
                             stats.report_battery(
@@ -1171,28 +2326,111 @@ fn connection_pipeline(
                                 packet.is_plugged,
                             );
                         }
-                    },
+
+                        if let Some(sender) = &pose_osc_sender {
+                            sender.report_battery(
+                                packet.device_id,
+                                packet.gauge_value,
+                                packet.is_plugged,
+                            );
+                        }
+
+                        let warnings_config = SERVER_DATA_MANAGER
+                            .read()
+                            .settings()
+                            .headset
+                            .low_battery_warnings
+                            .clone();
+                        if let Switch::Enabled(config) = warnings_config {
+                            if let Some(level) = battery_warnings.report(
+                                &config,
+                                packet.device_id,
+                                packet.gauge_value,
+                                packet.is_plugged,
+                            ) {
+                                let device_name = device_registry
+                                    .lock()
+                                    .display_name(packet.device_id)
+                                    .map(String::from)
+                                    .unwrap_or_else(|| battery::device_name(packet.device_id));
+                                let critical = level == BatteryLevel::Critical;
+
+                                alvr_events::send_event(
+                                    LogSeverity::Warning,
+                                    EventType::LowBattery(LowBatteryEvent {
+                                        device_id: packet.device_id,
+                                        device_name: device_name.clone(),
+                                        gauge_value: packet.gauge_value,
+                                        critical,
+                                    }),
+                                );
+
+                                if config.notify_in_headset {
+                                    let message = format!(
+                                        "{device_name} battery {}: {:.0}%",
+                                        if critical { "critically low" } else { "low" },
+                                        packet.gauge_value * 100.0
+                                    );
+                                    control_sender
+                                        .lock()
+                                        .send(&alvr_packets::encode_reserved_server_control_packet(
+                                            &ReservedServerControlPacket::Notification(message),
+                                        ))
+                                        .ok();
+                                }
+                            }
+                        }
+                    }
                     ClientControlPacket::Buttons(entries) => {
                         {
                             let data_manager_lock = SERVER_DATA_MANAGER.read();
                             if data_manager_lock.settings().logging.log_button_presses {
-                                alvr_events::send_event(EventType::Buttons(
-                                    entries
-                                        .iter()
-                                        .map(|e| ButtonEvent {
-                                            path: BUTTON_INFO
-                                                .get(&e.path_id)
-                                                .map(|info| info.path.to_owned())
-                                                .unwrap_or_else(|| {
-                                                    format!("Unknown (ID: {:#16x})", e.path_id)
-                                                }),
-                                            value: e.value,
-                                        })
-                                        .collect(),
-                                ));
+                                alvr_events::send_event(
+                                    LogSeverity::Debug,
+                                    EventType::Buttons(
+                                        entries
+                                            .iter()
+                                            .map(|e| ButtonEvent {
+                                                path: BUTTON_INFO
+                                                    .get(&e.path_id)
+                                                    .or_else(|| GAMEPAD_BUTTON_INFO.get(&e.path_id))
+                                                    .map(|info| info.path.to_owned())
+                                                    .unwrap_or_else(|| {
+                                                        format!("Unknown (ID: {:#16x})", e.path_id)
+                                                    }),
+                                                value: e.value,
+                                            })
+                                            .collect(),
+                                    ),
+                                );
                             }
                         }
 
+                        let entries = device_registry.lock().filter_buttons(entries);
+                        input_debug::report_client_buttons(&entries);
+
+                        // The gamepad inputs are not mapped to the controllers
+                        let (gamepad_entries, entries): (Vec<_>, Vec<_>) = entries
+                            .into_iter()
+                            .partition(|entry| GAMEPAD_BUTTON_INFO.contains_key(&entry.path_id));
+                        if !gamepad_entries.is_empty()
+                            && SERVER_DATA_MANAGER.read().settings().headset.gamepad
+                        {
+                            virtual_gamepad.report(&gamepad_entries);
+                        }
+
+                        let entries = if let Switch::Enabled(ControllersConfig {
+                            thumbsticks: Switch::Enabled(config),
+                            ..
+                        }) =
+                            &SERVER_DATA_MANAGER.read().settings().headset.controllers
+                        {
+                            thumbstick_processor.process(config, entries)
+                        } else {
+                            entries
+                        };
+                        input_debug::report_processed_buttons(&entries);
+
                         if let Some(manager) = &mut controller_button_mapping_manager {
                             for entry in entries {
                                 manager.report_button(entry.path_id, entry.value);
@@ -1220,9 +2458,13 @@ fn connection_pipeline(
                                 None
                             };
                     }
-                    ClientControlPacket::Log { level, message } => {
-                        info!("Client {client_hostname}: [{level:?}] {message}")
-                    }
+                    ClientControlPacket::Log { level, message } => alvr_events::send_event(
+                        level,
+                        EventType::Log(LogEntry {
+                            severity: level,
+                            content: format!("Client {client_hostname}: {message}"),
+                        }),
+                    ),
                     ClientControlPacket::Reserved(json_string) => {
                         let reserved: ReservedClientControlPacket =
                             match serde_json::from_str(&json_string) {
@@ -1255,6 +2497,175 @@ fn connection_pipeline(
                                     None
                                 };
                             }
+                            ReservedClientControlPacket::GameAudioBufferStatistics {
+                                underruns_per_minute,
+                                overruns_per_minute,
+                            } => {
+                                if underruns_per_minute > AUDIO_BUFFER_ISSUES_PER_MINUTE_THRESHOLD {
+                                    warn!(
+                                        "Client game audio: {underruns_per_minute:.1} underruns per minute. Consider increasing the game audio buffering."
+                                    );
+                                } else if overruns_per_minute
+                                    > AUDIO_BUFFER_ISSUES_PER_MINUTE_THRESHOLD
+                                {
+                                    info!(
+                                        "Client game audio: {overruns_per_minute:.1} overruns per minute. Consider decreasing the game audio buffering."
+                                    );
+                                }
+                            }
+                            ReservedClientControlPacket::ThermalStatus(status)
+                                if status != last_thermal_status =>
+                            {
+                                let config = SERVER_DATA_MANAGER
+                                    .read()
+                                    .settings()
+                                    .video
+                                    .bitrate
+                                    .thermal_mitigation
+                                    .clone();
+                                let bitrate_multiplier = BITRATE_MANAGER
+                                    .lock()
+                                    .report_thermal_status(&config, status);
+
+                                // The resolution changes when the client connects again, which
+                                // restarts SteamVR. It stays lowered until the headset is back to
+                                // Light so that it does not flip back and forth
+                                let resolution_scale = {
+                                    let mut scale_lock = THERMAL_RESOLUTION_SCALE.lock();
+                                    let step_down = config
+                                        .resolution_step_down
+                                        .as_option()
+                                        .filter(|_| config.automatic);
+                                    if status >= ThermalStatus::Severe && step_down.is_some() {
+                                        if scale_lock.is_none() {
+                                            warn!("Headset thermal status: {status:?}. The resolution will be reduced from the next connection");
+                                        }
+                                        *scale_lock = step_down.copied();
+                                    } else if status <= ThermalStatus::Light || step_down.is_none()
+                                    {
+                                        if scale_lock.take().is_some() {
+                                            info!("The resolution will be restored from the next connection");
+                                        }
+                                    }
+
+                                    *scale_lock
+                                };
+
+                                if status > ThermalStatus::Light {
+                                    if config.automatic {
+                                        warn!(
+                                            "Headset thermal status: {status:?}. Bitrate reduced to {:.0}%",
+                                            bitrate_multiplier * 100.0
+                                        );
+                                    } else {
+                                        warn!("Headset thermal status: {status:?}. Consider lowering the bitrate or resolution");
+                                    }
+                                } else if last_thermal_status > ThermalStatus::Light {
+                                    info!("Headset thermal status: {status:?}. The headset cooled down");
+                                }
+
+                                alvr_events::send_event(
+                                    if status > ThermalStatus::Light {
+                                        LogSeverity::Warning
+                                    } else {
+                                        LogSeverity::Info
+                                    },
+                                    EventType::ThermalMitigation(ThermalMitigationEvent {
+                                        status,
+                                        bitrate_multiplier,
+                                        resolution_scale,
+                                    }),
+                                );
+
+                                last_thermal_status = status;
+                            }
+                            ReservedClientControlPacket::ThermalStatus(_) => (),
+                            ReservedClientControlPacket::PlayspaceBoundary(points) => {
+                                if !settings.headset.tracking_ref_only {
+                                    recenter_playspace(&tracking_manager);
+                                    playspace::set_boundary(&points);
+                                }
+                            }
+                            ReservedClientControlPacket::ProximityState { headset_worn } => {
+                                if headset_worn {
+                                    headset_removed_instant = None;
+                                    set_headset_removed(false);
+                                } else if headset_removed_instant.is_none() {
+                                    headset_removed_instant = Some(Instant::now());
+                                }
+                            }
+                            ReservedClientControlPacket::UdpVideoUnavailable => {
+                                if UDP_VIDEO_ACTIVE.swap(false, Ordering::Relaxed) {
+                                    warn!("Video datagrams are not reaching the client, using TCP");
+                                    // The frames sent as datagrams in the meantime were lost
+                                    unsafe { crate::RequestIDR() };
+                                }
+                            }
+                            ReservedClientControlPacket::DecoderFailing { message } => {
+                                error!("Client decoder failing: {message}");
+                            }
+                            ReservedClientControlPacket::KeepAlivePong(send_time) => {
+                                let rtt = unix_time().saturating_sub(send_time);
+                                KEEPALIVE_RTT_MS.submit_sample(rtt.as_secs_f32() * 1000.0);
+                            }
+                            ReservedClientControlPacket::ClientVersion(version) => {
+                                SERVER_DATA_MANAGER.write().update_client_list(
+                                    client_hostname.clone(),
+                                    ClientListAction::SetVersion(version),
+                                );
+                            }
+                            ReservedClientControlPacket::Request { id, request } => {
+                                let response = match serde_json::from_str(&request) {
+                                    Ok(request) => handle_client_request(request),
+                                    Err(_) => {
+                                        debug!("Unsupported client request: {request}");
+                                        ServerResponse::Unsupported
+                                    }
+                                };
+
+                                control_sender
+                                    .lock()
+                                    .send(&alvr_packets::encode_server_response(id, &response))
+                                    .ok();
+                            }
+                            // Expected only before the stream starts
+                            ReservedClientControlPacket::PairingResponse { .. } => (),
+                            ReservedClientControlPacket::Recenter => {
+                                if !settings.headset.tracking_ref_only && should_recenter() {
+                                    info!("{client_hostname} requested a recenter");
+                                    recenter_playspace(&tracking_manager);
+                                    playspace::resync();
+                                }
+                            }
+                            ReservedClientControlPacket::SetStreamPaused(paused) => {
+                                let action = if paused { "pause" } else { "resume" };
+                                info!("{client_hostname} requested to {action} the stream");
+                                set_stream_paused(paused);
+                            }
+                            ReservedClientControlPacket::DeviceRegistration(declarations) => {
+                                info!(
+                                    "{client_hostname} registered the devices: {}",
+                                    declarations
+                                        .iter()
+                                        .map(|device| device.display_name.as_str())
+                                        .collect::<Vec<_>>()
+                                        .join(", ")
+                                );
+
+                                let trackers = device_registry.lock().register(declarations);
+                                for tracker in trackers {
+                                    // Devices cannot be removed from SteamVR. A tracker registered
+                                    // again later reuses the same device
+                                    let name =
+                                        CString::new(tracker.display_name).unwrap_or_default();
+                                    unsafe {
+                                        crate::RegisterGenericTracker(
+                                            tracker.device_id,
+                                            name.as_ptr(),
+                                        )
+                                    };
+                                }
+                            }
                         }
                     }
                     _ => (),
@@ -1262,31 +2673,33 @@ fn connection_pipeline(
 
                 disconnection_deadline = Instant::now() + KEEPALIVE_TIMEOUT;
             }
+            application_profiles.deactivate();
+            SERVER_DATA_MANAGER.write().clear_temporary_values();
             unsafe { crate::ShutdownOpenvrClient() };
 
             disconnect_notif.notify_one()
         }
     });
 
-    let stream_receive_thread = thread::spawn({
-        let disconnect_notif = Arc::clone(&disconnect_notif);
-        let client_hostname = client_hostname.clone();
-        move || {
-            while is_streaming(&client_hostname) {
-                match stream_socket.recv() {
-                    Ok(()) => (),
-                    Err(ConnectionError::TryAgain(_)) => continue,
-                    Err(e) => {
-                        info!("Client disconnected. Cause: {e}");
-
-                        disconnect_notif.notify_one();
-
-                        return;
-                    }
-                }
+    let stream_receive_thread = stream_socket.spawn_receive_thread(
+        {
+            let client_hostname = client_hostname.clone();
+            move || is_streaming(&client_hostname)
+        },
+        {
+            let disconnect_notif = Arc::clone(&disconnect_notif);
+            let client_hostname = client_hostname.clone();
+            move |e| {
+                info!("Client disconnected. Cause: {e}");
+
+                set_disconnection_reason(
+                    &client_hostname,
+                    DisconnectionReason::SocketError(e.to_string()),
+                );
+                disconnect_notif.notify_one();
             }
-        }
-    });
+        },
+    );
 
     let lifecycle_check_thread = thread::spawn({
         let disconnect_notif = Arc::clone(&disconnect_notif);
@@ -1303,6 +2716,9 @@ fn connection_pipeline(
                 thread::sleep(STREAMING_RECV_TIMEOUT);
             }
 
+            if *LIFECYCLE_STATE.read() != LifecycleState::Resumed {
+                set_disconnection_reason(&client_hostname, DisconnectionReason::ServerRestarting);
+            }
             disconnect_notif.notify_one()
         }
     });
@@ -1325,20 +2741,60 @@ fn connection_pipeline(
         crate::create_recording_file(server_data_lock.settings());
     }
 
+    if settings.capture.startup_timing_capture {
+        crate::start_timing_capture(server_data_lock.settings());
+    }
+
     unsafe { crate::InitializeStreaming() };
 
     server_data_lock.update_client_list(
         client_hostname.clone(),
         ClientListAction::SetConnectionState(ConnectionState::Streaming),
     );
+    alvr_events::send_event(
+        LogSeverity::Info,
+        EventType::StreamStarted(StreamStartedEvent {
+            hostname: client_hostname.clone(),
+            display_name: client_display_name,
+            view_resolution: stream_view_resolution,
+            refresh_rate: fps,
+            codec,
+        }),
+    );
 
     alvr_common::wait_rwlock(&disconnect_notif, &mut server_data_lock);
 
     // This requests shutdown from threads
     *VIDEO_CHANNEL_SENDER.lock() = None;
     *HAPTICS_SENDER.lock() = None;
+    let control_sender = CONTROL_SENDER.lock().take();
+    if let Some(control_sender) = control_sender {
+        // Lets the client skip the fast reconnection. If the packet is lost, the client sees an
+        // abrupt disconnection instead
+        if *LIFECYCLE_STATE.read() == LifecycleState::ShuttingDown && !IS_RESTARTING.value() {
+            control_sender
+                .lock()
+                .send_with_timeout(
+                    &alvr_packets::encode_reserved_server_control_packet(
+                        &ReservedServerControlPacket::ShuttingDown,
+                    ),
+                    SHUTDOWN_NOTIFICATION_TIMEOUT,
+                )
+                .ok();
+        }
+    }
+    *STREAMING_CAPS.lock() = None;
+    *STREAM_SETUP.lock() = None;
+    *CLIENT_VIEWS_CONFIG.lock() = None;
 
     *VIDEO_RECORDING_FILE.lock() = None;
+    *TIMING_CAPTURE.lock() = None;
+
+    HEADSET_REMOVED.store(false, Ordering::SeqCst);
+    if PAUSED.swap(false, Ordering::SeqCst) {
+        alvr_events::send_event(LogSeverity::Info, EventType::StreamPaused(false));
+    }
+    update_standby();
 
     unsafe { crate::DeinitializeStreaming() };
 
@@ -1379,15 +2835,82 @@ fn connection_pipeline(
     Ok(())
 }
 
-pub extern "C" fn send_video(timestamp_ns: u64, buffer_ptr: *mut u8, len: i32, is_idr: bool) {
+fn set_headset_removed(removed: bool) {
+    if HEADSET_REMOVED.swap(removed, Ordering::SeqCst) != removed {
+        if removed {
+            info!("Headset removed");
+        } else {
+            info!("Headset put back on");
+        }
+
+        update_standby();
+    }
+}
+
+fn update_standby() {
+    let standby = HEADSET_REMOVED.load(Ordering::SeqCst) || PAUSED.load(Ordering::SeqCst);
+    if STANDBY.swap(standby, Ordering::SeqCst) != standby {
+        if standby {
+            info!("Pausing stream");
+        } else {
+            info!("Resuming stream");
+        }
+
+        unsafe { crate::SetStandby(standby) };
+    }
+}
+
+// The packet is sent at a multiple of the encoder bitrate, but it can take at most a fraction of the
+// frame interval, shared by the slices of the frame
+fn video_pacing_duration(
+    config: &VideoPacingConfig,
+    header: &VideoPacketHeader,
+    bytes_count: usize,
+    frame_interval: Duration,
+) -> Duration {
+    let multiplier = if header.is_idr {
+        config.keyframe_bitrate_multiplier
+    } else {
+        config.bitrate_multiplier
+    };
+    let rate_bps = bitrate::ENCODER_BITRATE_BPS.get() * multiplier;
+    if rate_bps <= 0.0 {
+        return Duration::ZERO;
+    }
+
+    let max_duration_s = frame_interval.as_secs_f32() * config.max_frame_interval_fraction.max(0.0)
+        / header.slice_count.max(1) as f32;
+
+    Duration::from_secs_f32(f32::min(
+        bytes_count as f32 * 8.0 / rate_bps,
+        max_duration_s,
+    ))
+}
+
+pub extern "C" fn send_video(
+    timestamp_ns: u64,
+    buffer_ptr: *mut u8,
+    len: i32,
+    is_idr: bool,
+    slice_index: i32,
+    slice_count: i32,
+) {
     // start in the corrupts state, the client didn't receive the initial IDR yet.
     static STREAM_CORRUPTED: AtomicBool = AtomicBool::new(true);
     static LAST_IDR_INSTANT: Lazy<Mutex<Instant>> = Lazy::new(|| Mutex::new(Instant::now()));
+    static FRAME_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+    // Frames that were already in the encoder when the standby started
+    if STANDBY.load(Ordering::SeqCst) {
+        return;
+    }
 
     if let Some(sender) = &*VIDEO_CHANNEL_SENDER.lock() {
         let buffer_size = len as usize;
 
-        if is_idr {
+        // The corrupted state is reset only at the start of the IDR frame, otherwise the client
+        // would receive a partial IDR
+        if is_idr && slice_index == 0 {
             STREAM_CORRUPTED.store(false, Ordering::SeqCst);
         }
 
@@ -1400,7 +2923,7 @@ pub extern "C" fn send_video(timestamp_ns: u64, buffer_ptr: *mut u8, len: i32, i
             if Instant::now() > *LAST_IDR_INSTANT.lock() + Duration::from_secs(config.duration_s) {
                 unsafe { crate::RequestIDR() };
 
-                if is_idr {
+                if is_idr && slice_index == 0 {
                     crate::create_recording_file(SERVER_DATA_MANAGER.read().settings());
                     *LAST_IDR_INSTANT.lock() = Instant::now();
                 }
@@ -1433,7 +2956,12 @@ pub extern "C" fn send_video(timestamp_ns: u64, buffer_ptr: *mut u8, len: i32, i
 
             if matches!(
                 sender.try_send(VideoPacket {
-                    header: VideoPacketHeader { timestamp, is_idr },
+                    header: VideoPacketHeader {
+                        timestamp,
+                        is_idr,
+                        slice_index: slice_index as u32,
+                        slice_count: slice_count as u32,
+                    },
                     payload,
                 }),
                 Err(TrySendError::Full(_))
@@ -1446,13 +2974,20 @@ pub extern "C" fn send_video(timestamp_ns: u64, buffer_ptr: *mut u8, len: i32, i
             warn!("Dropping video packet. Reason: Waiting for IDR frame");
         }
 
+        // Statistics are collected per frame, after the last slice
+        let frame_size = FRAME_SIZE.fetch_add(buffer_size, Ordering::Relaxed) + buffer_size;
+        if slice_index + 1 < slice_count {
+            return;
+        }
+        FRAME_SIZE.store(0, Ordering::Relaxed);
+
         if let Some(stats) = &mut *STATISTICS_MANAGER.lock() {
             let encoder_latency =
-                stats.report_frame_encoded(Duration::from_nanos(timestamp_ns), buffer_size);
+                stats.report_frame_encoded(Duration::from_nanos(timestamp_ns), frame_size);
 
             BITRATE_MANAGER
                 .lock()
-                .report_frame_encoded(timestamp, encoder_latency, buffer_size);
+                .report_frame_encoded(timestamp, encoder_latency, frame_size);
         }
     }
 }
@@ -1469,15 +3004,10 @@ pub extern "C" fn send_haptics(device_id: u64, duration_s: f32, frequency: f32,
         let data_manager_lock = SERVER_DATA_MANAGER.read();
 
         if data_manager_lock.settings().logging.log_haptics {
-            alvr_events::send_event(EventType::Haptics(HapticsEvent {
-                path: DEVICE_ID_TO_PATH
-                    .get(&haptics.device_id)
-                    .map(|p| (*p).to_owned())
-                    .unwrap_or_else(|| format!("Unknown (ID: {:#16x})", haptics.device_id)),
-                duration: haptics.duration,
-                frequency: haptics.frequency,
-                amplitude: haptics.amplitude,
-            }))
+            alvr_events::send_event(
+                LogSeverity::Debug,
+                EventType::Haptics(input_debug::haptics_event(&haptics)),
+            )
         }
 
         data_manager_lock
@@ -1488,10 +3018,10 @@ pub extern "C" fn send_haptics(device_id: u64, duration_s: f32, frequency: f32,
             .and_then(|c| c.haptics.as_option().cloned())
     };
 
+    input_debug::report_haptics(&haptics, haptics_config.as_ref());
+
     if let (Some(config), Some(sender)) = (haptics_config, &mut *HAPTICS_SENDER.lock()) {
-        sender
-            .send_header(&haptics::map_haptics(&config, haptics))
-            .ok();
+        sender.send(&config, haptics).ok();
     }
 }
 
@@ -0,0 +1,244 @@
+use alvr_common::{
+    warn, DeviceMotion, BODY_CHEST_ID, BODY_HIPS_ID, BODY_LEFT_ELBOW_ID, BODY_LEFT_FOOT_ID,
+    BODY_LEFT_KNEE_ID, BODY_RIGHT_ELBOW_ID, BODY_RIGHT_FOOT_ID, BODY_RIGHT_KNEE_ID, GAMEPAD_ID,
+    HAND_LEFT_ID, HAND_RIGHT_ID, HEAD_ID,
+};
+use alvr_packets::{ButtonEntry, DeviceDeclaration, DeviceRole};
+use std::collections::{HashMap, HashSet};
+
+// Driven by the emulated Vive trackers of the body tracking, no device is created for them
+fn is_body_tracker(device_id: u64) -> bool {
+    [
+        *BODY_CHEST_ID,
+        *BODY_HIPS_ID,
+        *BODY_LEFT_ELBOW_ID,
+        *BODY_RIGHT_ELBOW_ID,
+        *BODY_LEFT_KNEE_ID,
+        *BODY_LEFT_FOOT_ID,
+        *BODY_RIGHT_KNEE_ID,
+        *BODY_RIGHT_FOOT_ID,
+    ]
+    .contains(&device_id)
+}
+
+// A generic tracker that needs an OpenVR device
+pub struct TrackerDevice {
+    pub device_id: u64,
+    pub display_name: String,
+}
+
+// Devices declared by the client with ReservedClientControlPacket::DeviceRegistration. Until the
+// declaration arrives the legacy profile is used, where all devices and inputs are accepted like
+// before. The headset is always accepted, the stream cannot work without it
+#[derive(Default)]
+pub struct DeviceRegistry {
+    // None for the legacy profile
+    devices: Option<HashMap<u64, DeviceDeclaration>>,
+    input_ids: HashSet<u64>,
+    // The IDs already reported as unregistered, to warn only once
+    rejected_ids: HashSet<u64>,
+}
+
+impl DeviceRegistry {
+    // Replaces the previous declaration. Returns the generic trackers to be created. Hmd,
+    // controller and gamepad roles with an ID different from the one of the fixed device are
+    // skipped
+    pub fn register(&mut self, declarations: Vec<DeviceDeclaration>) -> Vec<TrackerDevice> {
+        let mut devices = HashMap::new();
+        let mut trackers = vec![];
+        for declaration in declarations {
+            let expected_id = match declaration.role {
+                DeviceRole::Hmd => Some(*HEAD_ID),
+                DeviceRole::LeftController => Some(*HAND_LEFT_ID),
+                DeviceRole::RightController => Some(*HAND_RIGHT_ID),
+                DeviceRole::Gamepad => Some(*GAMEPAD_ID),
+                DeviceRole::GenericTracker => None,
+            };
+            let is_fixed_device = [*HEAD_ID, *HAND_LEFT_ID, *HAND_RIGHT_ID, *GAMEPAD_ID]
+                .contains(&declaration.device_id);
+
+            if expected_id.map_or(is_fixed_device, |id| id != declaration.device_id) {
+                warn!(
+                    "The device \"{}\" cannot be registered with the role {:?}",
+                    declaration.display_name, declaration.role
+                );
+                continue;
+            }
+
+            if declaration.role == DeviceRole::GenericTracker
+                && !is_body_tracker(declaration.device_id)
+            {
+                trackers.push(TrackerDevice {
+                    device_id: declaration.device_id,
+                    display_name: declaration.display_name.clone(),
+                });
+            }
+
+            devices.insert(declaration.device_id, declaration);
+        }
+
+        if !devices.contains_key(&*HEAD_ID) {
+            warn!("The client did not declare the headset, it is used anyway");
+        }
+
+        self.input_ids = devices
+            .values()
+            .flat_map(|device| device.input_ids.iter().copied())
+            .collect();
+        self.devices = Some(devices);
+        self.rejected_ids.clear();
+
+        trackers
+    }
+
+    pub fn display_name(&self, device_id: u64) -> Option<&str> {
+        Some(&self.devices.as_ref()?.get(&device_id)?.display_name)
+    }
+
+    // The generic trackers are passed to the driver together with the head and the controllers
+    pub fn tracker_ids(&self) -> Vec<u64> {
+        self.devices
+            .iter()
+            .flat_map(|devices| devices.values())
+            .filter(|device| {
+                device.role == DeviceRole::GenericTracker && !is_body_tracker(device.device_id)
+            })
+            .map(|device| device.device_id)
+            .collect()
+    }
+
+    // Reported to SteamVR as disconnected, instead of keeping their last pose
+    pub fn undeclared_controller_ids(&self) -> Vec<u64> {
+        let Some(devices) = &self.devices else {
+            return vec![];
+        };
+
+        [*HAND_LEFT_ID, *HAND_RIGHT_ID]
+            .into_iter()
+            .filter(|id| !devices.contains_key(id))
+            .collect()
+    }
+
+    pub fn accepts_device(&mut self, device_id: u64) -> bool {
+        let Some(devices) = &self.devices else {
+            return true;
+        };
+
+        let accepted = device_id == *HEAD_ID || devices.contains_key(&device_id);
+        if !accepted && self.rejected_ids.insert(device_id) {
+            warn!("Ignoring the unregistered device {device_id:#x}");
+        }
+
+        accepted
+    }
+
+    pub fn filter_motions(&mut self, device_motions: &mut Vec<(u64, DeviceMotion)>) {
+        device_motions.retain(|(id, _)| self.accepts_device(*id));
+    }
+
+    pub fn filter_buttons(&mut self, entries: Vec<ButtonEntry>) -> Vec<ButtonEntry> {
+        if self.devices.is_none() {
+            return entries;
+        }
+
+        entries
+            .into_iter()
+            .filter(|entry| {
+                let accepted = self.input_ids.contains(&entry.path_id);
+                if !accepted && self.rejected_ids.insert(entry.path_id) {
+                    warn!("Ignoring the unregistered input {:#x}", entry.path_id);
+                }
+
+                accepted
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alvr_common::{GAMEPAD_A_CLICK_ID, LEFT_A_CLICK_ID, RIGHT_A_CLICK_ID};
+    use alvr_packets::ButtonValue;
+
+    fn declaration(device_id: u64, role: DeviceRole, input_ids: &[u64]) -> DeviceDeclaration {
+        DeviceDeclaration {
+            device_id,
+            role,
+            display_name: "Device".into(),
+            input_ids: input_ids.iter().copied().collect(),
+        }
+    }
+
+    fn button(path_id: u64) -> ButtonEntry {
+        ButtonEntry {
+            path_id,
+            value: ButtonValue::Binary(true),
+        }
+    }
+
+    #[test]
+    fn test_legacy_profile() {
+        let mut registry = DeviceRegistry::default();
+
+        let mut motions = vec![
+            (*HAND_RIGHT_ID, DeviceMotion::default()),
+            (1, DeviceMotion::default()),
+        ];
+        registry.filter_motions(&mut motions);
+        assert_eq!(motions.len(), 2);
+        assert_eq!(registry.filter_buttons(vec![button(2)]).len(), 1);
+        assert!(registry.tracker_ids().is_empty());
+        assert!(registry.undeclared_controller_ids().is_empty());
+    }
+
+    #[test]
+    fn test_registration() {
+        let mut registry = DeviceRegistry::default();
+
+        let trackers = registry.register(vec![
+            declaration(
+                *HAND_LEFT_ID,
+                DeviceRole::LeftController,
+                &[*LEFT_A_CLICK_ID],
+            ),
+            declaration(1, DeviceRole::GenericTracker, &[]),
+            declaration(*BODY_CHEST_ID, DeviceRole::GenericTracker, &[]),
+            declaration(*GAMEPAD_ID, DeviceRole::Gamepad, &[*GAMEPAD_A_CLICK_ID]),
+            // Wrong IDs for the role
+            declaration(2, DeviceRole::RightController, &[*RIGHT_A_CLICK_ID]),
+            declaration(*HAND_RIGHT_ID, DeviceRole::GenericTracker, &[]),
+            declaration(3, DeviceRole::Gamepad, &[]),
+        ]);
+        assert_eq!(trackers.len(), 1);
+        assert_eq!(trackers[0].device_id, 1);
+        assert_eq!(registry.tracker_ids(), vec![1]);
+        assert_eq!(registry.undeclared_controller_ids(), vec![*HAND_RIGHT_ID]);
+
+        let mut motions = [
+            *HEAD_ID,
+            *HAND_LEFT_ID,
+            *HAND_RIGHT_ID,
+            1,
+            2,
+            *BODY_CHEST_ID,
+        ]
+        .into_iter()
+        .map(|id| (id, DeviceMotion::default()))
+        .collect();
+        registry.filter_motions(&mut motions);
+        let ids = motions.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        assert_eq!(ids, vec![*HEAD_ID, *HAND_LEFT_ID, 1, *BODY_CHEST_ID]);
+
+        let entries = registry.filter_buttons(vec![
+            button(*LEFT_A_CLICK_ID),
+            button(*RIGHT_A_CLICK_ID),
+            button(*GAMEPAD_A_CLICK_ID),
+        ]);
+        let ids = entries
+            .iter()
+            .map(|entry| entry.path_id)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![*LEFT_A_CLICK_ID, *GAMEPAD_A_CLICK_ID]);
+    }
+}
@@ -0,0 +1,253 @@
+use alvr_common::{anyhow::Result, LogSeverity};
+use alvr_events::Event;
+use alvr_session::EventLogConfig;
+use chrono::Local;
+use serde_json as json;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+const MB: u64 = 1024 * 1024;
+
+// events.jsonl is rotated to events.1.jsonl, events.1.jsonl to events.2.jsonl and so on
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+
+    path.with_file_name(format!("{stem}.{index}.jsonl"))
+}
+
+// The process can die in the middle of a write, leaving a partial line that would break the
+// readers that parse the file line by line
+fn truncate_incomplete_line(file: &mut File) -> Result<u64> {
+    let mut content = vec![];
+    file.seek(SeekFrom::Start(0))?;
+    file.read_to_end(&mut content)?;
+
+    let size = content
+        .iter()
+        .rposition(|byte| *byte == b'\n')
+        .map(|index| index as u64 + 1)
+        .unwrap_or(0);
+    file.set_len(size)?;
+    file.seek(SeekFrom::Start(size))?;
+
+    Ok(size)
+}
+
+pub struct EventLog {
+    path: PathBuf,
+    config: EventLogConfig,
+    // None after a write error
+    file: Option<File>,
+    file_size: u64,
+}
+
+impl EventLog {
+    pub fn open(path: PathBuf, config: EventLogConfig) -> Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        let file_size = truncate_incomplete_line(&mut file)?;
+
+        Ok(Self {
+            path,
+            config,
+            file: Some(file),
+            file_size,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn set_min_severity(&mut self, severity: LogSeverity) {
+        self.config.min_severity = severity;
+    }
+
+    // Renames are atomic, so at worst a crash during the rotation loses the oldest file
+    fn rotate(&mut self) -> Result<()> {
+        self.file = None;
+
+        // Files left over from a higher kept_files setting are removed too
+        let mut index = self.config.kept_files + 1;
+        while rotated_path(&self.path, index).exists() {
+            fs::remove_file(rotated_path(&self.path, index))?;
+            index += 1;
+        }
+
+        if self.config.kept_files > 0 {
+            for index in (1..self.config.kept_files).rev() {
+                let path = rotated_path(&self.path, index);
+                if path.exists() {
+                    fs::rename(path, rotated_path(&self.path, index + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        }
+
+        self.remove_files_over_cap()?;
+
+        self.file = Some(File::create(&self.path)?);
+        self.file_size = 0;
+
+        Ok(())
+    }
+
+    // The space for a full current file is reserved, the rotated files share the rest starting
+    // from the newest
+    fn remove_files_over_cap(&self) -> Result<()> {
+        let cap = self.config.max_total_size_mb * MB;
+        let mut total_size = self.config.max_file_size_mb * MB;
+
+        for index in 1..=self.config.kept_files {
+            let path = rotated_path(&self.path, index);
+            let Ok(metadata) = fs::metadata(&path) else {
+                break;
+            };
+
+            total_size += metadata.len();
+            if total_size > cap {
+                fs::remove_file(path)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn try_write(&mut self, event: &Event) -> Result<()> {
+        let event_json = json::to_value(&event.event_type)?;
+        let line = format!(
+            "{}\n",
+            json::json!({
+                "timestamp": Local::now().to_rfc3339(),
+                "severity": event.severity,
+                "type": event_json["id"],
+                "payload": event_json["data"],
+            })
+        );
+
+        if self.file_size > 0
+            && self.file_size + line.len() as u64 > self.config.max_file_size_mb * MB
+        {
+            self.rotate()?;
+        }
+
+        if let Some(file) = &mut self.file {
+            file.write_all(line.as_bytes())?;
+            self.file_size += line.len() as u64;
+        }
+
+        Ok(())
+    }
+
+    // This is called from inside the logger, where errors cannot be logged. The sink stops
+    // instead of failing again on every event
+    pub fn write(&mut self, event: &Event) {
+        if self.file.is_none() || !event.passes_filter(self.config.min_severity) {
+            return;
+        }
+
+        if self.try_write(event).is_err() {
+            self.file = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alvr_common::LogEntry;
+    use alvr_events::EventType;
+
+    // Each of these takes a bit more than half of a 1 MB file
+    fn large_event() -> Event {
+        Event {
+            timestamp: String::new(),
+            severity: LogSeverity::Info,
+            replayed: false,
+            event_type: EventType::Log(LogEntry {
+                severity: LogSeverity::Info,
+                content: "a".repeat(MB as usize / 2 + 1000),
+            }),
+        }
+    }
+
+    fn config(kept_files: usize, max_total_size_mb: u64) -> EventLogConfig {
+        EventLogConfig {
+            min_severity: LogSeverity::Debug,
+            max_file_size_mb: 1,
+            kept_files,
+            max_total_size_mb,
+        }
+    }
+
+    fn line_count(path: &Path) -> usize {
+        fs::read_to_string(path).unwrap().lines().count()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("alvr_{name}_{}", std::process::id()));
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+
+        dir
+    }
+
+    #[test]
+    fn test_rotation() {
+        let dir = temp_dir("event_log_rotation");
+        let path = dir.join("events.jsonl");
+        // A file left over from a higher kept_files setting
+        fs::write(rotated_path(&path, 3), "{}\n").unwrap();
+
+        let mut event_log = EventLog::open(path.clone(), config(2, 100)).unwrap();
+        for _ in 0..4 {
+            event_log.write(&large_event());
+        }
+
+        assert_eq!(line_count(&path), 1);
+        assert_eq!(line_count(&rotated_path(&path, 1)), 1);
+        assert_eq!(line_count(&rotated_path(&path, 2)), 1);
+        assert!(!rotated_path(&path, 3).exists());
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_total_size_cap() {
+        let dir = temp_dir("event_log_cap");
+        let path = dir.join("events.jsonl");
+
+        // Only one rotated file fits next to the space reserved for the current one
+        let mut event_log = EventLog::open(path.clone(), config(5, 2)).unwrap();
+        for _ in 0..4 {
+            event_log.write(&large_event());
+        }
+
+        assert!(rotated_path(&path, 1).exists());
+        assert!(!rotated_path(&path, 2).exists());
+
+        fs::remove_dir_all(dir).ok();
+    }
+
+    #[test]
+    fn test_incomplete_line() {
+        let dir = temp_dir("event_log_incomplete");
+        let path = dir.join("events.jsonl");
+        let mut file = File::create(&path).unwrap();
+        file.write_all(b"{\"type\":\"Log\"}\n{\"type\":").unwrap();
+        drop(file);
+
+        drop(EventLog::open(path.clone(), config(1, 100)).unwrap());
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"type\":\"Log\"}\n");
+
+        fs::remove_dir_all(dir).ok();
+    }
+}
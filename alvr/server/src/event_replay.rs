@@ -0,0 +1,117 @@
+use alvr_common::{once_cell::sync::Lazy, parking_lot::Mutex, LogSeverity};
+use alvr_events::{Event, EventType};
+use chrono::Local;
+use std::{collections::VecDeque, mem};
+use tokio::sync::broadcast::{Receiver, Sender};
+
+const MAX_REPLAYED_EVENTS: usize = 500;
+
+// Also locked while the events are broadcast, so that a new consumer receives each event either
+// in the replay or live, never both
+pub static EVENT_REPLAY: Lazy<Mutex<EventReplayBuffer>> =
+    Lazy::new(|| Mutex::new(EventReplayBuffer::new()));
+
+enum ReplayKind {
+    // Only the latest event of this type matters
+    Snapshot,
+    History,
+    // Replaying these would trigger actions again, or they are too frequent to be useful
+    Skipped,
+}
+
+fn replay_kind(event_type: &EventType) -> ReplayKind {
+    match event_type {
+        EventType::Session(_)
+        | EventType::StatisticsSummary(_)
+        | EventType::GraphStatistics(_)
+        | EventType::AudioDevices(_)
+        | EventType::DriversList(_)
+        | EventType::FirewallStatus(_)
+        | EventType::SteamvrRootDir(_)
+        | EventType::PendingRestart(_)
+        | EventType::SettingsValidation(_)
+        | EventType::ThermalMitigation(_)
+        | EventType::ApplicationProfile(_)
+        | EventType::StreamSetup(_)
+        | EventType::StreamPaused(_)
+        | EventType::GpuDefaults(_)
+        | EventType::EventLogFile(_) => ReplayKind::Snapshot,
+        EventType::Log(_)
+        | EventType::SessionMigrated(_)
+        | EventType::IncompatibleClient(_)
+        | EventType::IncompatibleClientAccepted(_)
+        | EventType::ClientDiscovered(_)
+        | EventType::ClientConnected(_)
+        | EventType::StreamStarted(_)
+        | EventType::NetworkSimulator(_)
+        | EventType::ClientDisconnected(_)
+        | EventType::PortConflict(_)
+        | EventType::LowBattery(_)
+        | EventType::DevicePreset(_) => ReplayKind::History,
+        EventType::Tracking(_)
+        | EventType::Buttons(_)
+        | EventType::Haptics(_)
+        | EventType::InputSnapshot(_)
+        | EventType::InstallationActionResult(_)
+        | EventType::ClientPairing(_)
+        | EventType::RestartRequired(_)
+        | EventType::ServerRequestsSelfRestart
+        | EventType::ReplayFinished => ReplayKind::Skipped,
+    }
+}
+
+pub struct EventReplayBuffer {
+    history: VecDeque<Event>,
+    // Ordered by the time of the last update
+    snapshots: Vec<Event>,
+}
+
+impl EventReplayBuffer {
+    fn new() -> Self {
+        Self {
+            history: VecDeque::new(),
+            snapshots: vec![],
+        }
+    }
+
+    pub fn push(&mut self, event: &Event) {
+        match replay_kind(&event.event_type) {
+            ReplayKind::Snapshot => {
+                let discriminant = mem::discriminant(&event.event_type);
+                self.snapshots
+                    .retain(|snapshot| mem::discriminant(&snapshot.event_type) != discriminant);
+                self.snapshots.push(event.clone());
+            }
+            ReplayKind::History => {
+                if self.history.len() == MAX_REPLAYED_EVENTS {
+                    self.history.pop_front();
+                }
+                self.history.push_back(event.clone());
+            }
+            ReplayKind::Skipped => (),
+        }
+    }
+
+    // The history comes first, so that the snapshots describe the current state. The last event
+    // marks the start of the live events
+    pub fn subscribe(&self, sender: &Sender<Event>) -> (Vec<Event>, Receiver<Event>) {
+        let mut events = self
+            .history
+            .iter()
+            .chain(&self.snapshots)
+            .cloned()
+            .map(|event| Event {
+                replayed: true,
+                ..event
+            })
+            .collect::<Vec<_>>();
+        events.push(Event {
+            timestamp: Local::now().format("%H:%M:%S.%f").to_string(),
+            severity: LogSeverity::Debug,
+            replayed: false,
+            event_type: EventType::ReplayFinished,
+        });
+
+        (events, sender.subscribe())
+    }
+}
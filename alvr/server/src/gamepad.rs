@@ -0,0 +1,496 @@
+// Virtual Xbox controller driven by the gamepad paired with the headset, see the canonical layout
+// in alvr_common::inputs. The device is created with the ViGEmBus driver on Windows and with uinput
+// on Linux, when the first gamepad input is received, and removed at the end of the stream. The
+// gamepad is not exposed to SteamVR as an OpenVR input device, games read it through XInput or evdev
+use alvr_common::{
+    anyhow::Result, glam::Vec2, info, once_cell::sync::Lazy, warn, GAMEPAD_A_CLICK_ID,
+    GAMEPAD_B_CLICK_ID, GAMEPAD_DPAD_DOWN_CLICK_ID, GAMEPAD_DPAD_LEFT_CLICK_ID,
+    GAMEPAD_DPAD_RIGHT_CLICK_ID, GAMEPAD_DPAD_UP_CLICK_ID, GAMEPAD_MENU_CLICK_ID,
+    GAMEPAD_SHOULDER_LEFT_CLICK_ID, GAMEPAD_SHOULDER_RIGHT_CLICK_ID,
+    GAMEPAD_THUMBSTICK_LEFT_CLICK_ID, GAMEPAD_THUMBSTICK_LEFT_ID, GAMEPAD_THUMBSTICK_LEFT_X_ID,
+    GAMEPAD_THUMBSTICK_LEFT_Y_ID, GAMEPAD_THUMBSTICK_RIGHT_CLICK_ID, GAMEPAD_THUMBSTICK_RIGHT_ID,
+    GAMEPAD_THUMBSTICK_RIGHT_X_ID, GAMEPAD_THUMBSTICK_RIGHT_Y_ID, GAMEPAD_TRIGGER_LEFT_VALUE_ID,
+    GAMEPAD_TRIGGER_RIGHT_VALUE_ID, GAMEPAD_VIEW_CLICK_ID, GAMEPAD_X_CLICK_ID, GAMEPAD_Y_CLICK_ID,
+};
+use alvr_packets::{ButtonEntry, ButtonValue};
+use std::collections::HashMap;
+
+// Bits of XINPUT_GAMEPAD::wButtons
+const DPAD_UP: u16 = 0x0001;
+const DPAD_DOWN: u16 = 0x0002;
+const DPAD_LEFT: u16 = 0x0004;
+const DPAD_RIGHT: u16 = 0x0008;
+const START: u16 = 0x0010;
+const BACK: u16 = 0x0020;
+const LEFT_THUMB: u16 = 0x0040;
+const RIGHT_THUMB: u16 = 0x0080;
+const LEFT_SHOULDER: u16 = 0x0100;
+const RIGHT_SHOULDER: u16 = 0x0200;
+const A: u16 = 0x1000;
+const B: u16 = 0x2000;
+const X: u16 = 0x4000;
+const Y: u16 = 0x8000;
+
+static BUTTON_BITS: Lazy<HashMap<u64, u16>> = Lazy::new(|| {
+    [
+        (*GAMEPAD_DPAD_UP_CLICK_ID, DPAD_UP),
+        (*GAMEPAD_DPAD_DOWN_CLICK_ID, DPAD_DOWN),
+        (*GAMEPAD_DPAD_LEFT_CLICK_ID, DPAD_LEFT),
+        (*GAMEPAD_DPAD_RIGHT_CLICK_ID, DPAD_RIGHT),
+        (*GAMEPAD_MENU_CLICK_ID, START),
+        (*GAMEPAD_VIEW_CLICK_ID, BACK),
+        (*GAMEPAD_THUMBSTICK_LEFT_CLICK_ID, LEFT_THUMB),
+        (*GAMEPAD_THUMBSTICK_RIGHT_CLICK_ID, RIGHT_THUMB),
+        (*GAMEPAD_SHOULDER_LEFT_CLICK_ID, LEFT_SHOULDER),
+        (*GAMEPAD_SHOULDER_RIGHT_CLICK_ID, RIGHT_SHOULDER),
+        (*GAMEPAD_A_CLICK_ID, A),
+        (*GAMEPAD_B_CLICK_ID, B),
+        (*GAMEPAD_X_CLICK_ID, X),
+        (*GAMEPAD_Y_CLICK_ID, Y),
+    ]
+    .into_iter()
+    .collect()
+});
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct GamepadState {
+    pub buttons: u16,
+    pub left_trigger: f32,
+    pub right_trigger: f32,
+    pub left_thumbstick: Vec2,
+    pub right_thumbstick: Vec2,
+}
+
+impl GamepadState {
+    // Returns false for inputs that are not part of the gamepad layout
+    pub fn apply(&mut self, entry: &ButtonEntry) -> bool {
+        let id = entry.path_id;
+        match entry.value {
+            ButtonValue::Binary(pressed) => {
+                let Some(bit) = BUTTON_BITS.get(&id) else {
+                    return false;
+                };
+                if pressed {
+                    self.buttons |= bit;
+                } else {
+                    self.buttons &= !bit;
+                }
+            }
+            ButtonValue::Scalar(value) => {
+                let trigger = value.clamp(0.0, 1.0);
+                let axis = value.clamp(-1.0, 1.0);
+                if id == *GAMEPAD_TRIGGER_LEFT_VALUE_ID {
+                    self.left_trigger = trigger;
+                } else if id == *GAMEPAD_TRIGGER_RIGHT_VALUE_ID {
+                    self.right_trigger = trigger;
+                } else if id == *GAMEPAD_THUMBSTICK_LEFT_X_ID {
+                    self.left_thumbstick.x = axis;
+                } else if id == *GAMEPAD_THUMBSTICK_LEFT_Y_ID {
+                    self.left_thumbstick.y = axis;
+                } else if id == *GAMEPAD_THUMBSTICK_RIGHT_X_ID {
+                    self.right_thumbstick.x = axis;
+                } else if id == *GAMEPAD_THUMBSTICK_RIGHT_Y_ID {
+                    self.right_thumbstick.y = axis;
+                } else {
+                    return false;
+                }
+            }
+            ButtonValue::Vector2(value) => {
+                let value = value.clamp(Vec2::NEG_ONE, Vec2::ONE);
+                if id == *GAMEPAD_THUMBSTICK_LEFT_ID {
+                    self.left_thumbstick = value;
+                } else if id == *GAMEPAD_THUMBSTICK_RIGHT_ID {
+                    self.right_thumbstick = value;
+                } else {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+// Xbox controllers report the triggers from 0 to 255 and the axes from -32767 to 32767, y up
+fn trigger_value(value: f32) -> u8 {
+    (value * u8::MAX as f32).round() as u8
+}
+
+fn axis_value(value: f32) -> i16 {
+    (value * i16::MAX as f32).round() as i16
+}
+
+#[cfg(windows)]
+mod backend {
+    use super::*;
+    use vigem_client::{Client, TargetId, XButtons, XGamepad, Xbox360Wired};
+
+    pub struct Device(Xbox360Wired<Client>);
+
+    impl Device {
+        pub fn new() -> Result<Self> {
+            let mut target = Xbox360Wired::new(Client::connect()?, TargetId::XBOX360_WIRED);
+            target.plugin()?;
+            target.wait_ready()?;
+
+            Ok(Self(target))
+        }
+
+        pub fn update(&mut self, state: &GamepadState) -> Result<()> {
+            self.0.update(&XGamepad {
+                buttons: XButtons { raw: state.buttons },
+                left_trigger: trigger_value(state.left_trigger),
+                right_trigger: trigger_value(state.right_trigger),
+                thumb_lx: axis_value(state.left_thumbstick.x),
+                thumb_ly: axis_value(state.left_thumbstick.y),
+                thumb_rx: axis_value(state.right_thumbstick.x),
+                thumb_ry: axis_value(state.right_thumbstick.y),
+            })?;
+
+            Ok(())
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod backend {
+    use super::*;
+    use alvr_common::anyhow::{anyhow, bail};
+    use std::{
+        fs::{File, OpenOptions},
+        io::{self, Write},
+        mem,
+        os::{fd::AsRawFd, unix::fs::OpenOptionsExt},
+        slice,
+    };
+
+    // Constants of linux/input-event-codes.h and linux/uinput.h
+    const EV_SYN: u16 = 0x00;
+    const EV_KEY: u16 = 0x01;
+    const EV_ABS: u16 = 0x03;
+    const SYN_REPORT: u16 = 0x00;
+    const ABS_X: u16 = 0x00;
+    const ABS_Y: u16 = 0x01;
+    const ABS_Z: u16 = 0x02;
+    const ABS_RX: u16 = 0x03;
+    const ABS_RY: u16 = 0x04;
+    const ABS_RZ: u16 = 0x05;
+    const ABS_HAT0X: u16 = 0x10;
+    const ABS_HAT0Y: u16 = 0x11;
+    const BUS_USB: u16 = 0x03;
+    const UI_DEV_CREATE: u64 = 0x5501;
+    const UI_DEV_DESTROY: u64 = 0x5502;
+    const UI_DEV_SETUP: u64 = 0x405c_5503;
+    const UI_ABS_SETUP: u64 = 0x401c_5504;
+    const UI_SET_EVBIT: u64 = 0x4004_5564;
+    const UI_SET_KEYBIT: u64 = 0x4004_5565;
+    const UI_SET_ABSBIT: u64 = 0x4004_5567;
+
+    // Same key codes as the xpad driver. The dpad is reported as a hat
+    const KEY_CODES: [(u16, u16); 10] = [
+        (A, 0x130),
+        (B, 0x131),
+        (X, 0x133),
+        (Y, 0x134),
+        (LEFT_SHOULDER, 0x136),
+        (RIGHT_SHOULDER, 0x137),
+        (BACK, 0x13a),
+        (START, 0x13b),
+        (LEFT_THUMB, 0x13d),
+        (RIGHT_THUMB, 0x13e),
+    ];
+    const AXES: [(u16, i32, i32); 8] = [
+        (ABS_X, -32767, 32767),
+        (ABS_Y, -32767, 32767),
+        (ABS_RX, -32767, 32767),
+        (ABS_RY, -32767, 32767),
+        (ABS_Z, 0, 255),
+        (ABS_RZ, 0, 255),
+        (ABS_HAT0X, -1, 1),
+        (ABS_HAT0Y, -1, 1),
+    ];
+
+    #[repr(C)]
+    struct InputId {
+        bustype: u16,
+        vendor: u16,
+        product: u16,
+        version: u16,
+    }
+
+    #[repr(C)]
+    struct UinputSetup {
+        id: InputId,
+        name: [u8; 80],
+        ff_effects_max: u32,
+    }
+
+    #[repr(C)]
+    struct InputAbsinfo {
+        value: i32,
+        minimum: i32,
+        maximum: i32,
+        fuzz: i32,
+        flat: i32,
+        resolution: i32,
+    }
+
+    #[repr(C)]
+    struct UinputAbsSetup {
+        code: u16,
+        absinfo: InputAbsinfo,
+    }
+
+    #[repr(C)]
+    struct InputEvent {
+        time: libc::timeval,
+        ty: u16,
+        code: u16,
+        value: i32,
+    }
+
+    fn check(result: libc::c_int) -> Result<()> {
+        if result < 0 {
+            bail!("{}", io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+
+    pub struct Device(File);
+
+    impl Device {
+        pub fn new() -> Result<Self> {
+            let file = OpenOptions::new()
+                .write(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open("/dev/uinput")
+                .map_err(|e| anyhow!("Cannot open /dev/uinput: {e}"))?;
+            let fd = file.as_raw_fd();
+
+            let mut name = [0; 80];
+            let device_name = b"ALVR virtual Xbox controller";
+            name[..device_name.len()].copy_from_slice(device_name);
+            let setup = UinputSetup {
+                // Recognized as an Xbox 360 controller
+                id: InputId {
+                    bustype: BUS_USB,
+                    vendor: 0x045e,
+                    product: 0x028e,
+                    version: 1,
+                },
+                name,
+                ff_effects_max: 0,
+            };
+
+            unsafe {
+                check(libc::ioctl(
+                    fd,
+                    UI_SET_EVBIT as _,
+                    libc::c_int::from(EV_KEY),
+                ))?;
+                for (_, code) in KEY_CODES {
+                    check(libc::ioctl(fd, UI_SET_KEYBIT as _, libc::c_int::from(code)))?;
+                }
+
+                check(libc::ioctl(
+                    fd,
+                    UI_SET_EVBIT as _,
+                    libc::c_int::from(EV_ABS),
+                ))?;
+                for (code, minimum, maximum) in AXES {
+                    check(libc::ioctl(fd, UI_SET_ABSBIT as _, libc::c_int::from(code)))?;
+
+                    let abs_setup = UinputAbsSetup {
+                        code,
+                        absinfo: InputAbsinfo {
+                            value: 0,
+                            minimum,
+                            maximum,
+                            fuzz: 0,
+                            flat: 0,
+                            resolution: 0,
+                        },
+                    };
+                    check(libc::ioctl(
+                        fd,
+                        UI_ABS_SETUP as _,
+                        &abs_setup as *const UinputAbsSetup,
+                    ))?;
+                }
+
+                check(libc::ioctl(
+                    fd,
+                    UI_DEV_SETUP as _,
+                    &setup as *const UinputSetup,
+                ))?;
+                check(libc::ioctl(fd, UI_DEV_CREATE as _))?;
+            }
+
+            Ok(Self(file))
+        }
+
+        // All values are written, the kernel drops the ones that did not change
+        pub fn update(&mut self, state: &GamepadState) -> Result<()> {
+            let event = |ty, code, value| InputEvent {
+                time: libc::timeval {
+                    tv_sec: 0,
+                    tv_usec: 0,
+                },
+                ty,
+                code,
+                value,
+            };
+            let pressed = |bit| i32::from(state.buttons & bit != 0);
+
+            let mut events = KEY_CODES
+                .iter()
+                .map(|(bit, code)| event(EV_KEY, *code, pressed(*bit)))
+                .collect::<Vec<_>>();
+            // The y axes point down
+            events.extend([
+                event(EV_ABS, ABS_X, axis_value(state.left_thumbstick.x).into()),
+                event(EV_ABS, ABS_Y, (-axis_value(state.left_thumbstick.y)).into()),
+                event(EV_ABS, ABS_RX, axis_value(state.right_thumbstick.x).into()),
+                event(
+                    EV_ABS,
+                    ABS_RY,
+                    (-axis_value(state.right_thumbstick.y)).into(),
+                ),
+                event(EV_ABS, ABS_Z, trigger_value(state.left_trigger).into()),
+                event(EV_ABS, ABS_RZ, trigger_value(state.right_trigger).into()),
+                event(EV_ABS, ABS_HAT0X, pressed(DPAD_RIGHT) - pressed(DPAD_LEFT)),
+                event(EV_ABS, ABS_HAT0Y, pressed(DPAD_DOWN) - pressed(DPAD_UP)),
+                event(EV_SYN, SYN_REPORT, 0),
+            ]);
+
+            let bytes = unsafe {
+                slice::from_raw_parts(
+                    events.as_ptr().cast::<u8>(),
+                    events.len() * mem::size_of::<InputEvent>(),
+                )
+            };
+            self.0.write_all(bytes)?;
+
+            Ok(())
+        }
+    }
+
+    impl Drop for Device {
+        fn drop(&mut self) {
+            unsafe { libc::ioctl(self.0.as_raw_fd(), UI_DEV_DESTROY as _) };
+        }
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+mod backend {
+    use super::*;
+    use alvr_common::anyhow::bail;
+
+    pub struct Device;
+
+    impl Device {
+        pub fn new() -> Result<Self> {
+            bail!("Virtual gamepads are not supported on this platform");
+        }
+
+        pub fn update(&mut self, _: &GamepadState) -> Result<()> {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct VirtualGamepad {
+    state: GamepadState,
+    device: Option<backend::Device>,
+    // The device is not created again for the rest of the stream after a failure
+    failed: bool,
+}
+
+impl VirtualGamepad {
+    pub fn report(&mut self, entries: &[ButtonEntry]) {
+        let mut state = self.state;
+        for entry in entries {
+            state.apply(entry);
+        }
+        if state == self.state && self.device.is_some() {
+            return;
+        }
+        self.state = state;
+
+        if self.device.is_none() && !self.failed {
+            match backend::Device::new() {
+                Ok(device) => {
+                    info!("Virtual gamepad created");
+                    self.device = Some(device);
+                }
+                Err(e) => {
+                    warn!("Failed to create the virtual gamepad: {e}");
+                    self.failed = true;
+                }
+            }
+        }
+
+        if let Some(device) = &mut self.device {
+            if let Err(e) = device.update(&state) {
+                warn!("Failed to update the virtual gamepad: {e}");
+                self.device = None;
+                self.failed = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alvr_common::LEFT_A_CLICK_ID;
+
+    fn entry(path_id: u64, value: ButtonValue) -> ButtonEntry {
+        ButtonEntry { path_id, value }
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut state = GamepadState::default();
+
+        assert!(state.apply(&entry(*GAMEPAD_A_CLICK_ID, ButtonValue::Binary(true))));
+        assert!(state.apply(&entry(*GAMEPAD_DPAD_UP_CLICK_ID, ButtonValue::Binary(true))));
+        assert!(state.apply(&entry(
+            *GAMEPAD_DPAD_UP_CLICK_ID,
+            ButtonValue::Binary(false)
+        )));
+        assert_eq!(state.buttons, A);
+
+        assert!(state.apply(&entry(
+            *GAMEPAD_TRIGGER_LEFT_VALUE_ID,
+            ButtonValue::Scalar(1.5)
+        )));
+        assert!(state.apply(&entry(
+            *GAMEPAD_THUMBSTICK_RIGHT_Y_ID,
+            ButtonValue::Scalar(-0.5)
+        )));
+        assert!(state.apply(&entry(
+            *GAMEPAD_THUMBSTICK_LEFT_ID,
+            ButtonValue::Vector2(Vec2::new(0.25, -2.0))
+        )));
+        assert_eq!(state.left_trigger, 1.0);
+        assert_eq!(state.right_thumbstick, Vec2::new(0.0, -0.5));
+        assert_eq!(state.left_thumbstick, Vec2::new(0.25, -1.0));
+
+        // Controller inputs and mismatched value types are ignored
+        assert!(!state.apply(&entry(*LEFT_A_CLICK_ID, ButtonValue::Binary(true))));
+        assert!(!state.apply(&entry(*GAMEPAD_A_CLICK_ID, ButtonValue::Scalar(1.0))));
+        assert_eq!(state.buttons, A);
+    }
+
+    #[test]
+    fn test_value_ranges() {
+        assert_eq!(trigger_value(0.0), 0);
+        assert_eq!(trigger_value(1.0), 255);
+        assert_eq!(axis_value(-1.0), -32767);
+        assert_eq!(axis_value(1.0), 32767);
+    }
+}
@@ -1,6 +1,16 @@
-use alvr_packets::Haptics;
+use alvr_packets::{Haptics, HapticsEnvelopePoint};
 use alvr_session::HapticsConfig;
-use std::time::Duration;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+// Events further apart are not considered part of the same effect
+const MAX_SEQUENCE_INTERVAL: Duration = Duration::from_millis(50);
+
+fn map_amplitude(config: &HapticsConfig, amplitude: f32) -> f32 {
+    config.intensity_multiplier * f32::powf(amplitude, config.amplitude_curve)
+}
 
 pub fn map_haptics(config: &HapticsConfig, haptics: Haptics) -> Haptics {
     Haptics {
@@ -8,8 +18,130 @@ pub fn map_haptics(config: &HapticsConfig, haptics: Haptics) -> Haptics {
             haptics.duration,
             Duration::from_secs_f32(config.min_duration_s),
         ),
-        amplitude: config.intensity_multiplier
-            * f32::powf(haptics.amplitude, config.amplitude_curve),
+        amplitude: map_amplitude(config, haptics.amplitude),
         ..haptics
     }
 }
+
+pub fn map_envelope(
+    config: &HapticsConfig,
+    envelope: Vec<HapticsEnvelopePoint>,
+) -> Vec<HapticsEnvelopePoint> {
+    envelope
+        .into_iter()
+        .map(|point| HapticsEnvelopePoint {
+            amplitude: map_amplitude(config, point.amplitude),
+            ..point
+        })
+        .collect()
+}
+
+struct LastEvent {
+    time: Instant,
+    amplitude: f32,
+    slope: f32, // amplitude change per second since the previous event
+}
+
+// Games render shaped effects by sending a new event every few milliseconds, each one replacing the
+// previous. When the amplitude keeps moving in the same direction, the ramp is extrapolated over the
+// duration of the latest event, so that the client does not play it as a staircase. The next event
+// corrects the extrapolation.
+#[derive(Default)]
+pub struct HapticsEnvelopeBuilder {
+    last_events: HashMap<u64, LastEvent>,
+}
+
+impl HapticsEnvelopeBuilder {
+    // Returns an empty envelope if no ramp was detected
+    pub fn push(&mut self, haptics: &Haptics, now: Instant) -> Vec<HapticsEnvelopePoint> {
+        let previous = self.last_events.insert(
+            haptics.device_id,
+            LastEvent {
+                time: now,
+                amplitude: haptics.amplitude,
+                slope: 0.0,
+            },
+        );
+
+        let Some(previous) = previous else {
+            return vec![];
+        };
+        let interval = now.saturating_duration_since(previous.time);
+        if interval.is_zero() || interval > MAX_SEQUENCE_INTERVAL || haptics.amplitude <= 0.0 {
+            return vec![];
+        }
+
+        let slope = (haptics.amplitude - previous.amplitude) / interval.as_secs_f32();
+        if let Some(last_event) = self.last_events.get_mut(&haptics.device_id) {
+            last_event.slope = slope;
+        }
+
+        // A single step could be a new effect, a ramp needs two steps in the same direction
+        if slope == 0.0 || slope.signum() != previous.slope.signum() || previous.slope == 0.0 {
+            return vec![];
+        }
+
+        let end_amplitude =
+            (haptics.amplitude + slope * haptics.duration.as_secs_f32()).clamp(0.0, 1.0);
+
+        vec![
+            HapticsEnvelopePoint {
+                time_offset: Duration::ZERO,
+                amplitude: haptics.amplitude,
+            },
+            HapticsEnvelopePoint {
+                time_offset: haptics.duration,
+                amplitude: end_amplitude,
+            },
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(amplitude: f32) -> Haptics {
+        Haptics {
+            device_id: 1,
+            duration: Duration::from_millis(100),
+            frequency: 0.0,
+            amplitude,
+        }
+    }
+
+    #[test]
+    fn test_ramp_detection() {
+        const STEP: Duration = Duration::from_millis(10);
+
+        let mut builder = HapticsEnvelopeBuilder::default();
+        let start = Instant::now();
+
+        assert!(builder.push(&event(0.1), start).is_empty());
+        // One step is not a ramp yet
+        assert!(builder.push(&event(0.2), start + STEP).is_empty());
+
+        let envelope = builder.push(&event(0.3), start + STEP * 2);
+        assert_eq!(envelope.len(), 2);
+        assert_eq!(envelope[0].amplitude, 0.3);
+        assert_eq!(envelope[1].time_offset, Duration::from_millis(100));
+        // Extrapolated at 0.1 per step, clamped to the maximum amplitude
+        assert_eq!(envelope[1].amplitude, 1.0);
+
+        // Changing direction ends the ramp
+        assert!(builder.push(&event(0.25), start + STEP * 3).is_empty());
+
+        // So does a pause between events
+        builder.push(&event(0.2), start + STEP * 4);
+        assert!(builder
+            .push(&event(0.15), start + STEP * 4 + MAX_SEQUENCE_INTERVAL * 2)
+            .is_empty());
+
+        // Different hands are tracked separately
+        let other_hand = Haptics {
+            device_id: 2,
+            ..event(0.5)
+        };
+        assert!(builder.push(&other_hand, start + STEP * 5).is_empty());
+    }
+}
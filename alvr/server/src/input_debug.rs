@@ -0,0 +1,177 @@
+// Collects the input of the client, as received and after the processing of the streamer, for the
+// input tab of the dashboard. Only the last value of each device and button is kept, and a snapshot
+// is sent at a low rate so that the events do not follow the tracking rate
+use crate::haptics;
+use alvr_common::{
+    once_cell::sync::Lazy, parking_lot::Mutex, DeviceMotion, LogSeverity, BUTTON_INFO,
+    DEVICE_ID_TO_PATH, GAMEPAD_BUTTON_INFO,
+};
+use alvr_events::{
+    ButtonEvent, EventType, HapticsEvent, InputButtonSnapshot, InputDeviceSnapshot,
+    InputHapticsSnapshot, InputSnapshotEvent,
+};
+use alvr_packets::{ButtonEntry, ButtonValue, Haptics};
+use alvr_session::HapticsConfig;
+use std::{
+    collections::BTreeMap,
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+const SNAPSHOT_INTERVAL: Duration = Duration::from_millis(100);
+// SteamVR can request haptics much faster than the snapshot rate
+const MAX_HAPTICS_PER_SNAPSHOT: usize = 32;
+
+#[derive(Default)]
+struct InputDebugState {
+    devices: BTreeMap<u64, (DeviceMotion, Option<DeviceMotion>)>,
+    buttons: BTreeMap<u64, (Option<ButtonValue>, Option<ButtonValue>)>,
+    steamvr_buttons: BTreeMap<u64, ButtonValue>,
+    haptics: Vec<InputHapticsSnapshot>,
+    last_snapshot_instant: Option<Instant>,
+}
+
+// Mirrors logging.input_debug_events, so that the button mapping does not need the session lock
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static STATE: Lazy<Mutex<InputDebugState>> = Lazy::new(|| Mutex::new(InputDebugState::default()));
+
+fn device_path(id: u64) -> String {
+    DEVICE_ID_TO_PATH
+        .get(&id)
+        .map(|path| (*path).to_owned())
+        .unwrap_or_else(|| format!("Unknown (ID: {id:#16x})"))
+}
+
+fn button_path(id: u64) -> String {
+    BUTTON_INFO
+        .get(&id)
+        .or_else(|| GAMEPAD_BUTTON_INFO.get(&id))
+        .map(|info| info.path.to_owned())
+        .unwrap_or_else(|| format!("Unknown (ID: {id:#16x})"))
+}
+
+pub fn haptics_event(haptics: &Haptics) -> HapticsEvent {
+    HapticsEvent {
+        path: device_path(haptics.device_id),
+        duration: haptics.duration,
+        frequency: haptics.frequency,
+        amplitude: haptics.amplitude,
+    }
+}
+
+pub fn set_enabled(enabled: bool) {
+    if !ENABLED.swap(enabled, Ordering::Relaxed) || enabled {
+        return;
+    }
+
+    // Values of a previous session would look live when enabling again
+    *STATE.lock() = InputDebugState::default();
+}
+
+pub fn report_motions(client: &[(u64, DeviceMotion)], processed: &[(u64, DeviceMotion)]) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let mut state = STATE.lock();
+    for (id, motion) in client {
+        let processed_motion = processed
+            .iter()
+            .find(|(processed_id, _)| processed_id == id)
+            .map(|(_, motion)| *motion);
+        state.devices.insert(*id, (*motion, processed_motion));
+    }
+}
+
+pub fn report_client_buttons(entries: &[ButtonEntry]) {
+    if ENABLED.load(Ordering::Relaxed) {
+        let mut state = STATE.lock();
+        for entry in entries {
+            state.buttons.entry(entry.path_id).or_default().0 = Some(entry.value);
+        }
+    }
+}
+
+pub fn report_processed_buttons(entries: &[ButtonEntry]) {
+    if ENABLED.load(Ordering::Relaxed) {
+        let mut state = STATE.lock();
+        for entry in entries {
+            state.buttons.entry(entry.path_id).or_default().1 = Some(entry.value);
+        }
+    }
+}
+
+pub fn report_steamvr_button(id: u64, value: ButtonValue) {
+    if ENABLED.load(Ordering::Relaxed) {
+        STATE.lock().steamvr_buttons.insert(id, value);
+    }
+}
+
+// Without a config the haptics are not sent to the client
+pub fn report_haptics(steamvr_haptics: &Haptics, config: Option<&HapticsConfig>) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let mut state = STATE.lock();
+    if state.haptics.len() < MAX_HAPTICS_PER_SNAPSHOT {
+        state.haptics.push(InputHapticsSnapshot {
+            steamvr: haptics_event(steamvr_haptics),
+            sent: config
+                .map(|config| haptics_event(&haptics::map_haptics(config, *steamvr_haptics))),
+        });
+    }
+}
+
+// Called for every tracking packet
+pub fn send_snapshot_if_due() {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let now = Instant::now();
+    let mut state = STATE.lock();
+    if state
+        .last_snapshot_instant
+        .is_some_and(|instant| now < instant + SNAPSHOT_INTERVAL)
+    {
+        return;
+    }
+    state.last_snapshot_instant = Some(now);
+
+    let snapshot = InputSnapshotEvent {
+        devices: state
+            .devices
+            .iter()
+            .map(|(id, (client, processed))| InputDeviceSnapshot {
+                path: device_path(*id),
+                client_motion: *client,
+                processed_motion: *processed,
+            })
+            .collect(),
+        buttons: state
+            .buttons
+            .iter()
+            .map(|(id, (client, processed))| InputButtonSnapshot {
+                path: button_path(*id),
+                client_value: *client,
+                processed_value: *processed,
+            })
+            .collect(),
+        steamvr_buttons: state
+            .steamvr_buttons
+            .iter()
+            .map(|(id, value)| ButtonEvent {
+                path: button_path(*id),
+                value: *value,
+            })
+            .collect(),
+        haptics: std::mem::take(&mut state.haptics),
+    };
+    drop(state);
+
+    alvr_events::send_event(
+        LogSeverity::Debug,
+        EventType::InputSnapshot(Box::new(snapshot)),
+    );
+}
@@ -7,6 +7,30 @@ use alvr_session::{
 };
 use std::collections::{HashMap, HashSet};
 
+// Inputs registered in SteamVR for the emulated controller
+pub fn emulated_button_set(emulation_mode: &ControllersEmulationMode) -> HashSet<u64> {
+    let profile_id = match emulation_mode {
+        ControllersEmulationMode::RiftSTouch
+        | ControllersEmulationMode::Quest2Touch
+        | ControllersEmulationMode::Quest3Plus => *QUEST_CONTROLLER_PROFILE_ID,
+        ControllersEmulationMode::ValveIndex => *INDEX_CONTROLLER_PROFILE_ID,
+        ControllersEmulationMode::ViveWand => *VIVE_CONTROLLER_PROFILE_ID,
+        ControllersEmulationMode::ViveTracker => return HashSet::new(),
+        ControllersEmulationMode::Custom { button_set, .. } => {
+            return button_set
+                .iter()
+                .map(|b| alvr_common::hash_string(b))
+                .collect()
+        }
+    };
+
+    CONTROLLER_PROFILE_INFO
+        .get(&profile_id)
+        .unwrap()
+        .button_set
+        .clone()
+}
+
 pub static REGISTERED_BUTTON_SET: Lazy<HashSet<u64>> = Lazy::new(|| {
     let data_manager_lock = SERVER_DATA_MANAGER.read();
     let Switch::Enabled(controllers_config) = &data_manager_lock.settings().headset.controllers
@@ -14,32 +38,28 @@ pub static REGISTERED_BUTTON_SET: Lazy<HashSet<u64>> = Lazy::new(|| {
         return HashSet::new();
     };
 
-    match &controllers_config.emulation_mode {
-        ControllersEmulationMode::RiftSTouch
-        | ControllersEmulationMode::Quest2Touch
-        | ControllersEmulationMode::Quest3Plus => CONTROLLER_PROFILE_INFO
-            .get(&QUEST_CONTROLLER_PROFILE_ID)
-            .unwrap()
-            .button_set
-            .clone(),
-        ControllersEmulationMode::ValveIndex => CONTROLLER_PROFILE_INFO
-            .get(&INDEX_CONTROLLER_PROFILE_ID)
-            .unwrap()
-            .button_set
-            .clone(),
-        ControllersEmulationMode::ViveWand => CONTROLLER_PROFILE_INFO
-            .get(&VIVE_CONTROLLER_PROFILE_ID)
-            .unwrap()
-            .button_set
-            .clone(),
-        ControllersEmulationMode::ViveTracker => HashSet::new(),
-        ControllersEmulationMode::Custom { button_set, .. } => button_set
-            .iter()
-            .map(|b| alvr_common::hash_string(b))
-            .collect(),
-    }
+    emulated_button_set(&controllers_config.emulation_mode)
 });
 
+// Each button of the client is bound to the first of the candidate buttons that the emulated
+// controller has on the same hand. Buttons are named like the segment of the input paths, as in
+// "/user/hand/left/input/<button>/click"
+const BUTTON_CANDIDATES: &[(&str, &[&str])] = &[
+    ("system", &["system", "menu"]),
+    ("menu", &["menu", "system"]),
+    ("a", &["a", "trackpad"]),
+    ("x", &["x", "a", "trackpad"]),
+    // The Vive wand has no second face button
+    ("b", &["b", "menu"]),
+    ("y", &["y", "b", "menu"]),
+    ("squeeze", &["squeeze"]),
+    ("trigger", &["trigger"]),
+    ("thumbstick", &["thumbstick", "trackpad"]),
+    ("thumbrest", &["thumbrest", "trackpad"]),
+];
+
+const HANDS: [&str; 2] = ["left", "right"];
+
 pub struct BindingTarget {
     destination: u64,
     mapping_type: ButtonMappingType,
@@ -55,24 +75,36 @@ pub struct ButtonInputs {
     force: Option<u64>,
 }
 
-fn click(click: u64) -> ButtonInputs {
-    ButtonInputs {
-        click: Some(click),
-        touch: None,
-        value: None,
-        force: None,
+impl ButtonInputs {
+    // Only the inputs contained in the set are kept
+    fn new(set: &HashSet<u64>, hand: &str, button: &str) -> Self {
+        let input = |name| Some(input_id(hand, button, name)).filter(|id| set.contains(id));
+
+        Self {
+            click: input("click"),
+            touch: input("touch"),
+            value: input("value"),
+            force: input("force"),
+        }
     }
-}
 
-fn ct(set: &HashSet<u64>, click: u64, touch: u64) -> ButtonInputs {
-    ButtonInputs {
-        click: Some(click),
-        touch: set.contains(&touch).then_some(touch),
-        value: None,
-        force: None,
+    fn is_empty(&self) -> bool {
+        self.click.is_none() && self.touch.is_none() && self.value.is_none() && self.force.is_none()
     }
 }
 
+fn input_id(hand: &str, button: &str, input: &str) -> u64 {
+    alvr_common::hash_string(&format!("/user/hand/{hand}/input/{button}/{input}"))
+}
+
+// IDs of the x and y inputs, for thumbsticks and trackpads
+fn axes(set: &HashSet<u64>, hand: &str, button: &str) -> Option<(u64, u64)> {
+    let x = input_id(hand, button, "x");
+    let y = input_id(hand, button, "y");
+
+    (set.contains(&x) && set.contains(&y)).then_some((x, y))
+}
+
 fn value(value: u64) -> ButtonInputs {
     ButtonInputs {
         click: None,
@@ -82,24 +114,6 @@ fn value(value: u64) -> ButtonInputs {
     }
 }
 
-fn ctv(set: &HashSet<u64>, click: u64, touch: u64, value: u64) -> ButtonInputs {
-    ButtonInputs {
-        click: set.contains(&click).then_some(click),
-        touch: set.contains(&touch).then_some(touch),
-        value: set.contains(&value).then_some(value),
-        force: None,
-    }
-}
-
-fn ctvf(set: &HashSet<u64>, click: u64, touch: u64, value: u64, force: u64) -> ButtonInputs {
-    ButtonInputs {
-        click: set.contains(&click).then_some(click),
-        touch: set.contains(&touch).then_some(touch),
-        value: set.contains(&value).then_some(value),
-        force: set.contains(&force).then_some(force),
-    }
-}
-
 fn passthrough(target: u64) -> BindingTarget {
     BindingTarget {
         destination: target,
@@ -236,289 +250,42 @@ pub fn automatic_bindings(
     destination_set: &HashSet<u64>,
     config: &AutomaticButtonMappingConfig,
 ) -> HashMap<u64, Vec<BindingTarget>> {
-    let s_set = source_set;
-    let d_set = destination_set;
-
     let mut bindings = HashMap::new();
 
-    // Menu buttons
-    if s_set.contains(&*LEFT_MENU_CLICK_ID) {
-        let click = click(*LEFT_MENU_CLICK_ID);
-        if d_set.contains(&*LEFT_MENU_CLICK_ID) {
-            bindings.extend(map_button_pair_automatic(click, click, config));
-        } else if d_set.contains(&*LEFT_SYSTEM_CLICK_ID) {
-            bindings.extend(map_button_pair_automatic(
-                click,
-                ct(s_set, *LEFT_SYSTEM_CLICK_ID, *LEFT_SYSTEM_TOUCH_ID),
-                config,
-            ));
-        }
-    }
-    if s_set.contains(&*RIGHT_MENU_CLICK_ID) {
-        let click = click(*RIGHT_MENU_CLICK_ID);
-        if d_set.contains(&*RIGHT_MENU_CLICK_ID) {
-            bindings.extend(map_button_pair_automatic(click, click, config));
-        } else if d_set.contains(&*RIGHT_SYSTEM_CLICK_ID) {
-            bindings.extend(map_button_pair_automatic(
-                click,
-                ct(s_set, *RIGHT_SYSTEM_CLICK_ID, *RIGHT_SYSTEM_TOUCH_ID),
-                config,
-            ));
-        }
-    }
-
-    // A/X buttons
-    if s_set.contains(&*LEFT_X_CLICK_ID) {
-        let source = ct(s_set, *LEFT_X_CLICK_ID, *LEFT_X_TOUCH_ID);
-        if d_set.contains(&*LEFT_X_CLICK_ID) {
-            bindings.extend(map_button_pair_automatic(
-                source,
-                ct(d_set, *LEFT_X_CLICK_ID, *LEFT_X_TOUCH_ID),
-                config,
-            ));
-        } else if d_set.contains(&*LEFT_A_CLICK_ID) {
-            bindings.extend(map_button_pair_automatic(
-                source,
-                ct(d_set, *LEFT_A_CLICK_ID, *LEFT_A_TOUCH_ID),
-                config,
-            ));
-        } else if d_set.contains(&*LEFT_TRACKPAD_CLICK_ID) {
-            bindings.extend(map_button_pair_automatic(
-                source,
-                ct(d_set, *LEFT_TRACKPAD_CLICK_ID, *LEFT_TRACKPAD_TOUCH_ID),
-                config,
-            ));
-        }
-    }
-    if s_set.contains(&*RIGHT_A_CLICK_ID) {
-        let source = ct(s_set, *RIGHT_A_CLICK_ID, *RIGHT_A_TOUCH_ID);
-        if d_set.contains(&*RIGHT_A_CLICK_ID) {
-            bindings.extend(map_button_pair_automatic(
-                source,
-                ct(d_set, *RIGHT_A_CLICK_ID, *RIGHT_A_TOUCH_ID),
-                config,
-            ));
-        } else if d_set.contains(&*RIGHT_TRACKPAD_CLICK_ID) {
-            bindings.extend(map_button_pair_automatic(
-                source,
-                ct(d_set, *RIGHT_TRACKPAD_CLICK_ID, *RIGHT_TRACKPAD_TOUCH_ID),
-                config,
-            ));
-        }
-    }
-
-    // B/Y buttons
-    if s_set.contains(&*LEFT_Y_CLICK_ID) {
-        let source = ct(s_set, *LEFT_Y_CLICK_ID, *LEFT_Y_TOUCH_ID);
-        if d_set.contains(&*LEFT_Y_CLICK_ID) {
-            bindings.extend(map_button_pair_automatic(
-                source,
-                ct(d_set, *LEFT_Y_CLICK_ID, *LEFT_Y_TOUCH_ID),
-                config,
-            ));
-        } else if d_set.contains(&*LEFT_B_CLICK_ID) {
-            bindings.extend(map_button_pair_automatic(
-                source,
-                ct(d_set, *LEFT_B_CLICK_ID, *LEFT_B_TOUCH_ID),
-                config,
-            ));
-        }
-    }
-    if s_set.contains(&*RIGHT_B_CLICK_ID) && d_set.contains(&*RIGHT_B_CLICK_ID) {
-        bindings.extend(map_button_pair_automatic(
-            ct(s_set, *RIGHT_B_CLICK_ID, *RIGHT_B_TOUCH_ID),
-            ct(d_set, *RIGHT_B_CLICK_ID, *RIGHT_B_TOUCH_ID),
-            config,
-        ));
-    }
-
-    // Squeeze buttons
-    if (s_set.contains(&*LEFT_SQUEEZE_CLICK_ID) || s_set.contains(&*LEFT_SQUEEZE_VALUE_ID))
-        && (d_set.contains(&*LEFT_SQUEEZE_CLICK_ID) || d_set.contains(&*LEFT_SQUEEZE_VALUE_ID))
-    {
-        bindings.extend(map_button_pair_automatic(
-            ctvf(
-                s_set,
-                *LEFT_SQUEEZE_CLICK_ID,
-                *LEFT_SQUEEZE_TOUCH_ID,
-                *LEFT_SQUEEZE_VALUE_ID,
-                *LEFT_SQUEEZE_FORCE_ID,
-            ),
-            ctvf(
-                d_set,
-                *LEFT_SQUEEZE_CLICK_ID,
-                *LEFT_SQUEEZE_TOUCH_ID,
-                *LEFT_SQUEEZE_VALUE_ID,
-                *LEFT_SQUEEZE_FORCE_ID,
-            ),
-            config,
-        ));
-    }
-    if (s_set.contains(&*RIGHT_SQUEEZE_CLICK_ID) || s_set.contains(&*RIGHT_SQUEEZE_VALUE_ID))
-        && (d_set.contains(&*RIGHT_SQUEEZE_CLICK_ID) || d_set.contains(&*RIGHT_SQUEEZE_VALUE_ID))
-    {
-        bindings.extend(map_button_pair_automatic(
-            ctvf(
-                s_set,
-                *RIGHT_SQUEEZE_CLICK_ID,
-                *RIGHT_SQUEEZE_TOUCH_ID,
-                *RIGHT_SQUEEZE_VALUE_ID,
-                *RIGHT_SQUEEZE_FORCE_ID,
-            ),
-            ctvf(
-                d_set,
-                *RIGHT_SQUEEZE_CLICK_ID,
-                *RIGHT_SQUEEZE_TOUCH_ID,
-                *RIGHT_SQUEEZE_VALUE_ID,
-                *RIGHT_SQUEEZE_FORCE_ID,
-            ),
-            config,
-        ));
-    }
-
-    // Trigger buttons
-    if (s_set.contains(&*LEFT_TRIGGER_CLICK_ID) || s_set.contains(&*LEFT_TRIGGER_VALUE_ID))
-        && (d_set.contains(&*LEFT_TRIGGER_CLICK_ID) || d_set.contains(&*LEFT_TRIGGER_VALUE_ID))
-    {
-        bindings.extend(map_button_pair_automatic(
-            ctv(
-                s_set,
-                *LEFT_TRIGGER_CLICK_ID,
-                *LEFT_TRIGGER_TOUCH_ID,
-                *LEFT_TRIGGER_VALUE_ID,
-            ),
-            ctv(
-                d_set,
-                *LEFT_TRIGGER_CLICK_ID,
-                *LEFT_TRIGGER_TOUCH_ID,
-                *LEFT_TRIGGER_VALUE_ID,
-            ),
-            config,
-        ));
-    }
-    if (s_set.contains(&*RIGHT_TRIGGER_CLICK_ID) || s_set.contains(&*RIGHT_TRIGGER_VALUE_ID))
-        && (d_set.contains(&*RIGHT_TRIGGER_CLICK_ID) || d_set.contains(&*RIGHT_TRIGGER_VALUE_ID))
-    {
-        bindings.extend(map_button_pair_automatic(
-            ctv(
-                s_set,
-                *RIGHT_TRIGGER_CLICK_ID,
-                *RIGHT_TRIGGER_TOUCH_ID,
-                *RIGHT_TRIGGER_VALUE_ID,
-            ),
-            ctv(
-                d_set,
-                *RIGHT_TRIGGER_CLICK_ID,
-                *RIGHT_TRIGGER_TOUCH_ID,
-                *RIGHT_TRIGGER_VALUE_ID,
-            ),
-            config,
-        ));
-    }
+    for hand in HANDS {
+        for (button, candidates) in BUTTON_CANDIDATES {
+            let source = ButtonInputs::new(source_set, hand, button);
+            let source_axes = axes(source_set, hand, button);
+            if source.is_empty() && source_axes.is_none() {
+                continue;
+            }
 
-    // Thumbsticks
-    if s_set.contains(&*LEFT_THUMBSTICK_X_ID) {
-        let x = value(*LEFT_THUMBSTICK_X_ID);
-        let y = value(*LEFT_THUMBSTICK_Y_ID);
-        if d_set.contains(&*LEFT_THUMBSTICK_X_ID) {
-            bindings.extend(map_button_pair_automatic(x, x, config));
-            bindings.extend(map_button_pair_automatic(y, y, config));
-        } else if d_set.contains(&*LEFT_TRACKPAD_X_ID) {
-            bindings.extend(map_button_pair_automatic(
-                x,
-                value(*LEFT_TRACKPAD_X_ID),
-                config,
-            ));
-            bindings.extend(map_button_pair_automatic(
-                y,
-                value(*LEFT_TRACKPAD_Y_ID),
-                config,
-            ));
-        }
-    }
-    if s_set.contains(&*LEFT_THUMBSTICK_CLICK_ID) {
-        let source = ct(s_set, *LEFT_THUMBSTICK_CLICK_ID, *LEFT_THUMBSTICK_TOUCH_ID);
-        if d_set.contains(&*LEFT_THUMBSTICK_CLICK_ID) {
-            bindings.extend(map_button_pair_automatic(
-                source,
-                ct(d_set, *LEFT_THUMBSTICK_CLICK_ID, *LEFT_THUMBSTICK_TOUCH_ID),
-                config,
-            ));
-        } else if d_set.contains(&*LEFT_TRACKPAD_CLICK_ID) {
-            bindings.extend(map_button_pair_automatic(
-                source,
-                ct(d_set, *LEFT_TRACKPAD_CLICK_ID, *LEFT_TRACKPAD_TOUCH_ID),
-                config,
-            ));
-        }
-    }
-    if s_set.contains(&*RIGHT_THUMBSTICK_X_ID) {
-        let x = value(*RIGHT_THUMBSTICK_X_ID);
-        let y = value(*RIGHT_THUMBSTICK_Y_ID);
-        if d_set.contains(&*RIGHT_THUMBSTICK_X_ID) {
-            bindings.extend(map_button_pair_automatic(x, x, config));
-            bindings.extend(map_button_pair_automatic(y, y, config));
-        } else if d_set.contains(&*RIGHT_TRACKPAD_X_ID) {
-            bindings.extend(map_button_pair_automatic(
-                x,
-                value(*RIGHT_TRACKPAD_X_ID),
-                config,
-            ));
-            bindings.extend(map_button_pair_automatic(
-                y,
-                value(*RIGHT_TRACKPAD_Y_ID),
-                config,
-            ));
-        }
-    }
-    if s_set.contains(&*RIGHT_THUMBSTICK_CLICK_ID) {
-        let source = ct(
-            s_set,
-            *RIGHT_THUMBSTICK_CLICK_ID,
-            *RIGHT_THUMBSTICK_TOUCH_ID,
-        );
-        if d_set.contains(&*RIGHT_THUMBSTICK_CLICK_ID) {
-            bindings.extend(map_button_pair_automatic(
-                source,
-                ct(
-                    d_set,
-                    *RIGHT_THUMBSTICK_CLICK_ID,
-                    *RIGHT_THUMBSTICK_TOUCH_ID,
-                ),
-                config,
-            ));
-        } else if d_set.contains(&*RIGHT_TRACKPAD_CLICK_ID) {
-            bindings.extend(map_button_pair_automatic(
-                source,
-                ct(d_set, *RIGHT_TRACKPAD_CLICK_ID, *RIGHT_TRACKPAD_TOUCH_ID),
-                config,
-            ));
-        }
-    }
+            let Some(destination_button) = candidates.iter().find(|candidate| {
+                !ButtonInputs::new(destination_set, hand, candidate).is_empty()
+                    || axes(destination_set, hand, candidate).is_some()
+            }) else {
+                continue;
+            };
 
-    // Thumbrests
-    if s_set.contains(&*LEFT_THUMBREST_TOUCH_ID) {
-        let source = value(*LEFT_THUMBREST_TOUCH_ID);
-        if d_set.contains(&*LEFT_THUMBREST_TOUCH_ID) {
-            bindings.extend(map_button_pair_automatic(source, source, config));
-        } else if d_set.contains(&*LEFT_TRACKPAD_TOUCH_ID) {
             bindings.extend(map_button_pair_automatic(
                 source,
-                value(*LEFT_TRACKPAD_TOUCH_ID),
-                config,
-            ));
-        }
-    }
-    if s_set.contains(&*RIGHT_THUMBREST_TOUCH_ID) {
-        let source = value(*RIGHT_THUMBREST_TOUCH_ID);
-        if d_set.contains(&*RIGHT_THUMBREST_TOUCH_ID) {
-            bindings.extend(map_button_pair_automatic(source, source, config));
-        } else if d_set.contains(&*RIGHT_TRACKPAD_TOUCH_ID) {
-            bindings.extend(map_button_pair_automatic(
-                source,
-                value(*RIGHT_TRACKPAD_TOUCH_ID),
+                ButtonInputs::new(destination_set, hand, destination_button),
                 config,
             ));
+            if let (Some((source_x, source_y)), Some((destination_x, destination_y))) =
+                (source_axes, axes(destination_set, hand, destination_button))
+            {
+                bindings.extend(map_button_pair_automatic(
+                    value(source_x),
+                    value(destination_x),
+                    config,
+                ));
+                bindings.extend(map_button_pair_automatic(
+                    value(source_y),
+                    value(destination_y),
+                    config,
+                ));
+            }
         }
     }
 
@@ -583,6 +350,18 @@ impl ButtonMappingManager {
 
     // Apply any button changes that are mapped to this specific button
     pub fn report_button(&mut self, source_id: u64, source_value: ButtonValue) {
+        // The mappings are defined on the x and y components
+        if let ButtonValue::Vector2(value) = source_value {
+            if let Some([x_id, y_id]) = VECTOR2_COMPONENTS.get(&source_id) {
+                self.report_button(*x_id, ButtonValue::Scalar(value.x));
+                self.report_button(*y_id, ButtonValue::Scalar(value.y));
+            } else {
+                error!("Received Vector2 value for a button without components");
+            }
+
+            return;
+        }
+
         if let ButtonValue::Binary(value) = source_value {
             let val_ref = self.binary_source_states.entry(source_id).or_default();
 
@@ -647,6 +426,8 @@ impl ButtonMappingManager {
                     }
                 }
 
+                crate::input_debug::report_steamvr_button(mapping.destination, destination_value);
+
                 let destination_value = match destination_value {
                     ButtonValue::Binary(value) => FfiButtonValue {
                         type_: crate::FfiButtonType_BUTTON_TYPE_BINARY,
@@ -659,6 +440,8 @@ impl ButtonMappingManager {
                         type_: crate::FfiButtonType_BUTTON_TYPE_SCALAR,
                         __bindgen_anon_1: crate::FfiButtonValue__bindgen_ty_1 { scalar: value },
                     },
+                    // Expanded at the top of this function
+                    ButtonValue::Vector2(_) => unreachable!(),
                 };
                 unsafe { crate::SetButton(mapping.destination, destination_value) };
             }
@@ -671,3 +454,137 @@ impl ButtonMappingManager {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hand_gestures::HAND_GESTURE_BUTTON_SET;
+
+    const EMULATION_MODES: [(&str, ControllersEmulationMode); 3] = [
+        ("Touch", ControllersEmulationMode::Quest2Touch),
+        ("Index", ControllersEmulationMode::ValveIndex),
+        ("Vive wand", ControllersEmulationMode::ViveWand),
+    ];
+
+    fn config() -> AutomaticButtonMappingConfig {
+        AutomaticButtonMappingConfig {
+            click_threshold: HysteresisThreshold {
+                value: 0.5,
+                deviation: 0.05,
+            },
+            touch_threshold: HysteresisThreshold {
+                value: 0.1,
+                deviation: 0.05,
+            },
+            force_threshold: 0.8,
+        }
+    }
+
+    fn destinations(bindings: &HashMap<u64, Vec<BindingTarget>>, source: u64) -> Vec<u64> {
+        bindings
+            .get(&source)
+            .map(|targets| targets.iter().map(|t| t.destination).collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_every_client_button_is_bound() {
+        let source_sets = CONTROLLER_PROFILE_INFO
+            .values()
+            .map(|info| (info.path, &info.button_set))
+            .chain([("hand gestures", &*HAND_GESTURE_BUTTON_SET)]);
+
+        for (source_name, source_set) in source_sets {
+            for (mode_name, mode) in &EMULATION_MODES {
+                let destination_set = emulated_button_set(mode);
+                let bindings = automatic_bindings(source_set, &destination_set, &config());
+
+                for source in source_set {
+                    let source_info = BUTTON_INFO.get(source).unwrap();
+                    let targets = destinations(&bindings, *source);
+
+                    // A touch is dropped if the emulated button cannot report it, the other
+                    // inputs of the button still reach it
+                    assert!(
+                        !targets.is_empty()
+                            || (source_info.path.ends_with("/touch")
+                                && bindings.contains_key(source)),
+                        "{} of {source_name} is not bound for the {mode_name} emulation",
+                        source_info.path,
+                    );
+
+                    for target in targets {
+                        assert!(destination_set.contains(&target));
+                        assert_eq!(
+                            BUTTON_INFO.get(&target).unwrap().device_id,
+                            source_info.device_id
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_button_fallbacks() {
+        let quest_set = &CONTROLLER_PROFILE_INFO
+            .get(&QUEST_CONTROLLER_PROFILE_ID)
+            .unwrap()
+            .button_set;
+        let pico_set = &CONTROLLER_PROFILE_INFO
+            .get(&PICO4_CONTROLLER_PROFILE_ID)
+            .unwrap()
+            .button_set;
+
+        let vive_bindings = automatic_bindings(
+            quest_set,
+            &emulated_button_set(&ControllersEmulationMode::ViveWand),
+            &config(),
+        );
+        assert_eq!(
+            destinations(&vive_bindings, *RIGHT_THUMBSTICK_X_ID),
+            [*RIGHT_TRACKPAD_X_ID]
+        );
+        assert_eq!(
+            destinations(&vive_bindings, *RIGHT_THUMBSTICK_CLICK_ID),
+            [*RIGHT_TRACKPAD_CLICK_ID]
+        );
+        assert_eq!(
+            destinations(&vive_bindings, *RIGHT_B_CLICK_ID),
+            [*RIGHT_MENU_CLICK_ID]
+        );
+        assert_eq!(
+            destinations(&vive_bindings, *LEFT_Y_CLICK_ID),
+            [*LEFT_MENU_CLICK_ID]
+        );
+
+        let index_bindings = automatic_bindings(
+            quest_set,
+            &emulated_button_set(&ControllersEmulationMode::ValveIndex),
+            &config(),
+        );
+        assert_eq!(
+            destinations(&index_bindings, *LEFT_MENU_CLICK_ID),
+            [*LEFT_SYSTEM_CLICK_ID, *LEFT_SYSTEM_TOUCH_ID]
+        );
+        assert_eq!(
+            destinations(&index_bindings, *LEFT_X_CLICK_ID),
+            [*LEFT_A_CLICK_ID]
+        );
+        assert_eq!(
+            destinations(&index_bindings, *LEFT_THUMBREST_TOUCH_ID),
+            [*LEFT_TRACKPAD_TOUCH_ID]
+        );
+
+        // The Touch controllers have no system button on the left hand
+        let quest_bindings = automatic_bindings(
+            pico_set,
+            &emulated_button_set(&ControllersEmulationMode::Quest2Touch),
+            &config(),
+        );
+        assert_eq!(
+            destinations(&quest_bindings, *LEFT_SYSTEM_CLICK_ID),
+            [*LEFT_MENU_CLICK_ID]
+        );
+    }
+}
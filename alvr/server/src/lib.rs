@@ -1,17 +1,30 @@
+mod application_profiles;
+mod battery;
 mod bitrate;
 mod body_tracking;
 mod c_api;
 mod connection;
+mod device_registry;
+mod event_log;
+mod event_replay;
 mod face_tracking;
+mod gamepad;
 mod hand_gestures;
 mod haptics;
+mod input_debug;
 mod input_mapping;
 mod logging_backend;
 mod openvr_props;
+mod pairing;
+mod playspace;
+mod pose_osc;
 mod sockets;
 mod statistics;
+mod thumbstick;
+mod timing_capture;
 mod tracking;
 mod web_server;
+mod wired;
 
 #[allow(
     non_camel_case_types,
@@ -28,17 +41,21 @@ use bindings::*;
 use alvr_common::{
     error,
     glam::Quat,
-    log,
+    info, log,
     once_cell::sync::Lazy,
     parking_lot::{Mutex, RwLock},
-    ConnectionState, LifecycleState, OptLazy, RelaxedAtomic,
+    warn, ConnectionState, LifecycleState, LogSeverity, OptLazy, RelaxedAtomic,
 };
 use alvr_events::EventType;
 use alvr_filesystem::{self as afs, Layout};
-use alvr_packets::{ClientListAction, DecoderInitializationConfig, VideoPacketHeader};
+use alvr_packets::{
+    ClientListAction, DecoderInitializationConfig, SettingChange, SettingChangeEffect,
+    VideoPacketHeader,
+};
 use alvr_server_io::ServerDataManager;
 use alvr_session::{CodecType, Settings};
 use bitrate::BitrateManager;
+use serde_json as json;
 use statistics::StatisticsManager;
 use std::{
     collections::HashMap,
@@ -52,8 +69,11 @@ use std::{
     time::{Duration, Instant},
 };
 use sysinfo::{ProcessRefreshKind, RefreshKind};
+use timing_capture::TimingCapture;
 use tokio::{runtime::Runtime, sync::broadcast};
 
+const SESSION_FILE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 pub static LIFECYCLE_STATE: RwLock<LifecycleState> = RwLock::new(LifecycleState::StartingUp);
 pub static IS_RESTARTING: RelaxedAtomic = RelaxedAtomic::new(false);
 static CONNECTION_THREAD: RwLock<Option<JoinHandle<()>>> = RwLock::new(None);
@@ -65,6 +85,9 @@ static FILESYSTEM_LAYOUT: Lazy<Layout> = Lazy::new(|| {
 });
 static SERVER_DATA_MANAGER: Lazy<RwLock<ServerDataManager>> =
     Lazy::new(|| RwLock::new(ServerDataManager::new(&FILESYSTEM_LAYOUT.session())));
+// Forced in init(), before any setting can change
+static DRIVER_START_SETTINGS: Lazy<json::Value> =
+    Lazy::new(|| json::to_value(&SERVER_DATA_MANAGER.read().session().session_settings).unwrap());
 static WEBSERVER_RUNTIME: OptLazy<Runtime> = Lazy::new(|| Mutex::new(Runtime::new().ok()));
 
 static STATISTICS_MANAGER: OptLazy<StatisticsManager> = alvr_common::lazy_mut_none();
@@ -78,6 +101,7 @@ pub struct VideoPacket {
 
 static VIDEO_MIRROR_SENDER: OptLazy<broadcast::Sender<Vec<u8>>> = alvr_common::lazy_mut_none();
 static VIDEO_RECORDING_FILE: OptLazy<File> = alvr_common::lazy_mut_none();
+static TIMING_CAPTURE: OptLazy<TimingCapture> = alvr_common::lazy_mut_none();
 
 static FRAME_RENDER_VS_CSO: &[u8] = include_bytes!("../cpp/platform/win32/FrameRenderVS.cso");
 static FRAME_RENDER_PS_CSO: &[u8] = include_bytes!("../cpp/platform/win32/FrameRenderPS.cso");
@@ -134,6 +158,114 @@ pub fn create_recording_file(settings: &Settings) {
     }
 }
 
+pub fn start_timing_capture(settings: &Settings) {
+    let dir = FILESYSTEM_LAYOUT.log_dir.join(format!(
+        "timing_capture.{}",
+        chrono::Local::now().format("%F.%H-%M-%S")
+    ));
+
+    match TimingCapture::new(
+        &dir,
+        settings.capture.timing_capture_max_size_mb * 1_000_000,
+    ) {
+        Ok(capture) => {
+            info!("Timing capture started in {}", dir.display());
+            *TIMING_CAPTURE.lock() = Some(capture);
+        }
+        Err(e) => error!("Failed to start the timing capture: {e}"),
+    }
+}
+
+pub fn check_firewall() {
+    let status =
+        alvr_server_io::check_firewall_rules(&SERVER_DATA_MANAGER.read().settings().connection);
+
+    for port in status.blocked_ports() {
+        warn!(
+            "{} is blocking inbound {:?} traffic on port {}. Clients may not be able to connect.",
+            status.backend, port.protocol, port.port
+        );
+    }
+
+    alvr_events::send_event(
+        alvr_events::firewall_status_severity(&status),
+        EventType::FirewallStatus(status),
+    );
+}
+
+// Live settings are read again by the server threads on every use, so only changes that need a
+// restart are reported, and only while the stream they would affect is running.
+pub fn report_setting_changes(changes: &[SettingChange]) {
+    if changes.iter().any(|change| {
+        alvr_packets::path_to_string(&change.path)
+            .starts_with("session_settings.headset.views_override")
+    }) {
+        connection::update_views_override();
+    }
+
+    if changes
+        .iter()
+        .any(|change| change.effect == SettingChangeEffect::Live)
+    {
+        connection::update_client_settings();
+    }
+
+    let is_codec_change = |change: &&SettingChange| {
+        alvr_packets::path_to_string(&change.path)
+            == "session_settings.video.preferred_codec.variant"
+    };
+
+    // The codec can be switched on the running stream where supported
+    let preferred_codec = SERVER_DATA_MANAGER.read().settings().video.preferred_codec;
+    let codec_switched = changes.iter().any(|change| is_codec_change(&change))
+        && connection::switch_codec(preferred_codec);
+
+    let Some(change) = changes
+        .iter()
+        .filter(|change| !(codec_switched && is_codec_change(change)))
+        .max_by_key(|change| change.effect)
+    else {
+        return;
+    };
+
+    let streaming = SERVER_DATA_MANAGER
+        .read()
+        .client_list()
+        .values()
+        .any(|info| info.connection_state == ConnectionState::Streaming);
+
+    if streaming && change.effect != SettingChangeEffect::Live {
+        info!(
+            "Setting {} changed, {:?} required",
+            alvr_packets::path_to_string(&change.path),
+            change.effect
+        );
+
+        alvr_events::send_event(
+            LogSeverity::Info,
+            EventType::RestartRequired(change.clone()),
+        );
+    }
+}
+
+// Unlike RestartRequired, this is sent whether or not a client is streaming, so the dashboard can
+// tell which changes are still waiting for a SteamVR restart
+pub fn report_pending_restart() {
+    let session_settings_json =
+        json::to_value(&SERVER_DATA_MANAGER.read().session().session_settings).unwrap();
+
+    let changes = alvr_server_io::get_setting_changes(
+        &DRIVER_START_SETTINGS,
+        &session_settings_json,
+        &Settings::schema(alvr_session::session_settings_default()),
+    )
+    .into_iter()
+    .filter(|change| change.effect == SettingChangeEffect::DriverRestart)
+    .collect();
+
+    alvr_events::send_event(LogSeverity::Info, EventType::PendingRestart(changes));
+}
+
 // This call is blocking
 pub extern "C" fn shutdown_driver() {
     // Invoke connection runtimes shutdown
@@ -209,7 +341,7 @@ pub fn notify_restart_driver() {
         .next()
         .is_some()
     {
-        alvr_events::send_event(EventType::ServerRequestsSelfRestart);
+        alvr_events::send_event(LogSeverity::Info, EventType::ServerRequestsSelfRestart);
     } else {
         error!("Cannot restart SteamVR. No dashboard process found on local device.");
     }
@@ -222,10 +354,44 @@ pub fn restart_driver() {
     shutdown_driver();
 }
 
+// session.json can be edited by hand or by other programs while the streamer runs
+fn watch_session_file() {
+    while *LIFECYCLE_STATE.read() != LifecycleState::ShuttingDown {
+        thread::sleep(SESSION_FILE_POLL_INTERVAL);
+
+        let Some(res) = SERVER_DATA_MANAGER.write().reload_if_modified() else {
+            continue;
+        };
+
+        match res {
+            Ok(changes) => {
+                logging_backend::update_log_levels(&SERVER_DATA_MANAGER.read().settings().logging);
+                report_setting_changes(&changes);
+                report_pending_restart();
+            }
+            Err(e) => warn!("Failed to reload session.json, the current session is kept: {e}"),
+        }
+    }
+}
+
 fn init() {
     let (events_sender, _) = broadcast::channel(web_server::WS_BROADCAST_CAPACITY);
     logging_backend::init_logging(events_sender.clone());
 
+    if let Some(report) = SERVER_DATA_MANAGER.write().take_migration_report() {
+        alvr_events::send_event(
+            alvr_events::session_migration_severity(&report),
+            EventType::SessionMigrated(report),
+        );
+    }
+    let validation_errors = SERVER_DATA_MANAGER.read().validation_errors().to_vec();
+    if !validation_errors.is_empty() {
+        alvr_events::send_event(
+            alvr_events::settings_validation_severity(&validation_errors),
+            EventType::SettingsValidation(validation_errors),
+        );
+    }
+
     if SERVER_DATA_MANAGER
         .read()
         .settings()
@@ -236,6 +402,10 @@ fn init() {
     }
 
     SERVER_DATA_MANAGER.write().clean_client_list();
+    Lazy::force(&DRIVER_START_SETTINGS);
+
+    thread::spawn(check_firewall);
+    thread::spawn(watch_session_file);
 
     if let Some(runtime) = WEBSERVER_RUNTIME.lock().as_mut() {
         runtime.spawn(async { alvr_common::show_err(web_server::web_server(events_sender).await) });
@@ -407,6 +577,10 @@ pub unsafe extern "C" fn HmdDriverFactory(
                 .get_encoder_params(&server_data_lock.settings().video.bitrate)
         };
 
+        if params.updated != 0 {
+            bitrate::ENCODER_BITRATE_BPS.set(params.bitrate_bps as f32);
+        }
+
         if let Some(stats) = stats {
             if let Some(stats_manager) = &mut *STATISTICS_MANAGER.lock() {
                 stats_manager.report_nominal_bitrate_stats(stats);
@@ -1,11 +1,71 @@
-use crate::{FILESYSTEM_LAYOUT, SERVER_DATA_MANAGER};
-use alvr_common::{log::LevelFilter, LogEntry, LogSeverity};
-use alvr_events::{Event, EventType};
+use crate::{
+    event_log::EventLog, event_replay::EVENT_REPLAY, FILESYSTEM_LAYOUT, SERVER_DATA_MANAGER,
+};
+use alvr_common::{
+    error,
+    log::{self, LevelFilter},
+    parking_lot::{Mutex, RwLock},
+    settings_schema::Switch,
+    LogEntry, LogSeverity,
+};
+use alvr_events::{Event, EventType, LoggedEvent};
+use alvr_session::{LogLevel, LoggingConfig};
 use chrono::Local;
 use fern::Dispatch;
 use std::fs;
 use tokio::sync::broadcast::Sender;
 
+#[derive(Clone, Copy)]
+struct LogLevels {
+    file: LevelFilter,
+    events: LogSeverity,
+}
+
+// The levels can change at any time, so they are checked by the dispatch filters instead of being
+// set once on the dispatchers
+static LOG_LEVELS: RwLock<LogLevels> = RwLock::new(LogLevels {
+    file: LevelFilter::Info,
+    events: LogSeverity::Info,
+});
+
+static EVENT_LOG: Mutex<Option<EventLog>> = Mutex::new(None);
+
+fn level_filter(level: LogLevel) -> LevelFilter {
+    match level {
+        LogLevel::Error => LevelFilter::Error,
+        LogLevel::Warning => LevelFilter::Warn,
+        LogLevel::Info => LevelFilter::Info,
+        LogLevel::Debug => LevelFilter::Debug,
+        LogLevel::Trace => LevelFilter::Trace,
+    }
+}
+
+// Applies to the messages logged after this call
+pub fn update_log_levels(config: &LoggingConfig) {
+    let levels = LogLevels {
+        file: level_filter(config.server_log_level),
+        events: config.dashboard_log_level.min_severity(),
+    };
+    *LOG_LEVELS.write() = levels;
+
+    let mut max_level = levels.file.max(level_filter(config.dashboard_log_level));
+    if let (Some(event_log), Switch::Enabled(event_log_config)) =
+        (&mut *EVENT_LOG.lock(), &config.event_log)
+    {
+        event_log.set_min_severity(event_log_config.min_severity);
+        max_level = max_level.max(
+            event_log_config
+                .min_severity
+                .into_log_level()
+                .to_level_filter(),
+        );
+    }
+
+    // Events are logged with info level and must always reach the dashboard. The macros skip
+    // anything above the max level before the message is formatted
+    log::set_max_level(max_level.max(LevelFilter::Info));
+}
+
 // todo: don't stringify events immediately, use Sender<Event>
 pub fn init_logging(events_sender: Sender<Event>) {
     let mut log_dispatch = Dispatch::new()
@@ -13,31 +73,55 @@ pub fn init_logging(events_sender: Sender<Event>) {
         .filter(|meta| !meta.target().starts_with("mdns_sd"))
         .format(move |out, message, record| {
             let maybe_event = format!("{message}");
-            let event_type = if maybe_event.starts_with('{') && maybe_event.ends_with('}') {
+            let LoggedEvent {
+                severity,
+                event_type,
+            } = if maybe_event.starts_with('{') && maybe_event.ends_with('}') {
                 serde_json::from_str(&maybe_event).unwrap()
             } else {
-                EventType::Log(LogEntry {
-                    severity: LogSeverity::from_log_level(record.level()),
-                    content: message.to_string(),
-                })
+                let severity = LogSeverity::from_log_level(record.level());
+                LoggedEvent {
+                    severity,
+                    event_type: EventType::Log(LogEntry {
+                        severity,
+                        content: message.to_string(),
+                    }),
+                }
             };
+            if let EventType::Log(_) = &event_type {
+                alvr_common::push_recent_log(severity, maybe_event);
+            }
+
             let event = Event {
                 timestamp: Local::now().format("%H:%M:%S.%f").to_string(),
+                severity,
+                replayed: false,
                 event_type,
             };
+
+            // The other events carry state that the dashboard needs whatever the level, the
+            // dashboard filters them by severity on its side
+            let forward_event = !matches!(event.event_type, EventType::Log(_))
+                || event.passes_filter(LOG_LEVELS.read().events);
             out.finish(format_args!("{}", serde_json::to_string(&event).unwrap()));
 
-            events_sender.send(event).ok();
-        });
+            if let Some(event_log) = &mut *EVENT_LOG.lock() {
+                event_log.write(&event);
+            }
 
-    if cfg!(debug_assertions) {
-        log_dispatch = log_dispatch.level(LevelFilter::Debug)
-    } else {
-        log_dispatch = log_dispatch.level(LevelFilter::Info);
-    }
+            if forward_event {
+                let mut event_replay = EVENT_REPLAY.lock();
+                event_replay.push(&event);
+                events_sender.send(event).ok();
+            }
+        })
+        // Filtering is done by the max level set in update_log_levels()
+        .level(LevelFilter::Trace);
+
+    let mut file_dispatch = Dispatch::new().filter(|meta| meta.level() <= LOG_LEVELS.read().file);
 
     if SERVER_DATA_MANAGER.read().settings().logging.log_to_disk {
-        log_dispatch = log_dispatch.chain(
+        file_dispatch = file_dispatch.chain(
             fs::OpenOptions::new()
                 .write(true)
                 .create(true)
@@ -48,18 +132,19 @@ pub fn init_logging(events_sender: Sender<Event>) {
     } else {
         // this sink is required to make sure all log gets processed and forwarded to the websocket
         if cfg!(target_os = "linux") {
-            log_dispatch = log_dispatch.chain(
+            file_dispatch = file_dispatch.chain(
                 fs::OpenOptions::new()
                     .write(true)
                     .open("/dev/null")
                     .unwrap(),
             );
         } else {
-            log_dispatch = log_dispatch.chain(std::io::stdout());
+            file_dispatch = file_dispatch.chain(std::io::stdout());
         }
     }
 
     log_dispatch
+        .chain(file_dispatch)
         .chain(
             Dispatch::new()
                 .level(LevelFilter::Error)
@@ -68,5 +153,26 @@ pub fn init_logging(events_sender: Sender<Event>) {
         .apply()
         .unwrap();
 
+    // Opened after the logger is set up so that the failure can be reported
+    let event_log_config = SERVER_DATA_MANAGER
+        .read()
+        .settings()
+        .logging
+        .event_log
+        .clone();
+    if let Switch::Enabled(config) = event_log_config {
+        match EventLog::open(FILESYSTEM_LAYOUT.event_log(), config) {
+            Ok(event_log) => {
+                let path = event_log.path().to_owned();
+                *EVENT_LOG.lock() = Some(event_log);
+
+                alvr_events::send_event(LogSeverity::Info, EventType::EventLogFile(path));
+            }
+            Err(e) => error!("Failed to open the event log: {e}"),
+        }
+    }
+
+    update_log_levels(&SERVER_DATA_MANAGER.read().settings().logging);
+
     alvr_common::set_panic_hook();
 }
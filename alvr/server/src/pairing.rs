@@ -0,0 +1,168 @@
+// Verification of the discovered clients trusted from the dashboard, when pin_verification is
+// enabled. The client stays untrusted until the user enters in the headset the code shown in the
+// dashboard. The check is a challenge-response over the control socket, before the stream starts
+use crate::LIFECYCLE_STATE;
+use alvr_common::{
+    con_bail, info, once_cell::sync::Lazy, parking_lot::Mutex, warn, AnyhowToCon, ConResult,
+    ConnectionError, LifecycleState, LogSeverity,
+};
+use alvr_events::{ClientPairingEvent, EventType, PairingState};
+use alvr_packets::{
+    ClientControlPacket, ReservedClientControlPacket, ReservedServerControlPacket,
+    ServerControlPacket, PAIRING_CODE_LENGTH,
+};
+use alvr_sockets::{ControlSocketReceiver, ControlSocketSender};
+use rand::Rng;
+use serde_json as json;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+const CODE_VALIDITY: Duration = Duration::from_secs(60);
+const MAX_ATTEMPTS: u32 = 3;
+const RESPONSE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+struct PendingPairing {
+    code: String,
+    expiration: Instant,
+    attempts_left: u32,
+}
+
+static PENDING_PAIRINGS: Lazy<Mutex<HashMap<String, PendingPairing>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn send_pairing_event(hostname: &str, state: PairingState) {
+    let severity = if matches!(state, PairingState::Failed | PairingState::Expired) {
+        LogSeverity::Warning
+    } else {
+        LogSeverity::Info
+    };
+
+    alvr_events::send_event(
+        severity,
+        EventType::ClientPairing(ClientPairingEvent {
+            hostname: hostname.to_owned(),
+            state,
+        }),
+    );
+}
+
+fn finish(hostname: &str, state: PairingState) {
+    if PENDING_PAIRINGS.lock().remove(hostname).is_some() {
+        send_pairing_event(hostname, state);
+    }
+}
+
+// Starting again replaces the code and resets the attempts
+pub fn start(hostname: String) {
+    let code = (0..PAIRING_CODE_LENGTH)
+        .map(|_| char::from(b'0' + rand::thread_rng().gen_range(0..10)))
+        .collect::<String>();
+
+    info!("Waiting for the pairing code to be entered on {hostname}");
+
+    PENDING_PAIRINGS.lock().insert(
+        hostname.clone(),
+        PendingPairing {
+            code: code.clone(),
+            expiration: Instant::now() + CODE_VALIDITY,
+            attempts_left: MAX_ATTEMPTS,
+        },
+    );
+    send_pairing_event(
+        &hostname,
+        PairingState::WaitingForCode {
+            code,
+            valid_for: CODE_VALIDITY,
+        },
+    );
+}
+
+pub fn cancel(hostname: &str) {
+    finish(hostname, PairingState::Cancelled);
+}
+
+// Clients with a pending pairing are connected like trusted ones. An expired pairing is removed
+pub fn is_pending(hostname: &str) -> bool {
+    let expired = match PENDING_PAIRINGS.lock().get(hostname) {
+        Some(pairing) => Instant::now() > pairing.expiration,
+        None => return false,
+    };
+    if expired {
+        finish(hostname, PairingState::Expired);
+    }
+
+    !expired
+}
+
+// Returns once the client proved it knows the code. With a wrong code the challenge is sent again
+// until no attempts are left. A disconnection keeps the pairing, the client can retry until the code
+// expires
+pub fn verify_client(
+    hostname: &str,
+    control_sender: &mut ControlSocketSender<ServerControlPacket>,
+    control_receiver: &mut ControlSocketReceiver<ClientControlPacket>,
+) -> ConResult {
+    loop {
+        let nonce = rand::random::<u64>();
+        let (attempts_left, expiration) = match PENDING_PAIRINGS.lock().get(hostname) {
+            Some(pairing) => (pairing.attempts_left, pairing.expiration),
+            None => con_bail!("The pairing was cancelled"),
+        };
+
+        control_sender
+            .send(&alvr_packets::encode_reserved_server_control_packet(
+                &ReservedServerControlPacket::PairingChallenge {
+                    nonce,
+                    attempts_left,
+                },
+            ))
+            .to_con()?;
+
+        let proof = loop {
+            let now = Instant::now();
+            if now > expiration {
+                finish(hostname, PairingState::Expired);
+                con_bail!("The pairing code expired");
+            }
+            if *LIFECYCLE_STATE.read() == LifecycleState::ShuttingDown {
+                con_bail!("The streamer is shutting down");
+            }
+
+            match control_receiver.recv(RESPONSE_POLL_INTERVAL.min(expiration - now)) {
+                Ok(ClientControlPacket::Reserved(json_string)) => {
+                    if let Ok(ReservedClientControlPacket::PairingResponse { proof }) =
+                        json::from_str(&json_string)
+                    {
+                        break proof;
+                    }
+                }
+                Ok(_) | Err(ConnectionError::TryAgain(_)) => (),
+                Err(e) => return Err(e),
+            }
+        };
+
+        let mut pairings = PENDING_PAIRINGS.lock();
+        let Some(pairing) = pairings.get_mut(hostname) else {
+            con_bail!("The pairing was cancelled");
+        };
+
+        if proof == alvr_packets::pairing_proof(nonce, &pairing.code) {
+            drop(pairings);
+            info!("{hostname} entered the right pairing code");
+            finish(hostname, PairingState::Verified);
+
+            return Ok(());
+        }
+
+        pairing.attempts_left -= 1;
+        if pairing.attempts_left == 0 {
+            drop(pairings);
+            finish(hostname, PairingState::Failed);
+            con_bail!("Too many wrong pairing codes");
+        }
+
+        warn!("{hostname} entered a wrong pairing code");
+    }
+}
@@ -0,0 +1,183 @@
+use crate::FfiPlayspacePoint;
+use alvr_common::{
+    anyhow::{bail, Result},
+    glam::Vec2,
+    info, warn, OptLazy,
+};
+
+const DEFAULT_AREA: Vec2 = Vec2::new(2.0, 2.0);
+const MIN_BOUNDARY_AREA_M2: f32 = 0.01;
+
+#[derive(Clone)]
+enum Playspace {
+    Area(Option<Vec2>),
+    Boundary(Vec<Vec2>),
+}
+
+// Sent again to SteamVR after a recenter requested by the client
+static LAST_PLAYSPACE: OptLazy<Playspace> = alvr_common::lazy_mut_none();
+
+// Positive if b is on the left of the line from o to a
+fn cross(o: Vec2, a: Vec2, b: Vec2) -> f32 {
+    (a - o).perp_dot(b - o)
+}
+
+// Collinear overlaps count as intersections
+fn segments_intersect(a1: Vec2, a2: Vec2, b1: Vec2, b2: Vec2) -> bool {
+    let d1 = cross(b1, b2, a1);
+    let d2 = cross(b1, b2, a2);
+    let d3 = cross(a1, a2, b1);
+    let d4 = cross(a1, a2, b2);
+
+    let on_segment =
+        |p: Vec2, q1: Vec2, q2: Vec2| p.cmpge(q1.min(q2)).all() && p.cmple(q1.max(q2)).all();
+
+    (d1 * d2 < 0.0 && d3 * d4 < 0.0)
+        || (d1 == 0.0 && on_segment(a1, b1, b2))
+        || (d2 == 0.0 && on_segment(a2, b1, b2))
+        || (d3 == 0.0 && on_segment(b1, a1, a2))
+        || (d4 == 0.0 && on_segment(b2, a1, a2))
+}
+
+// Some runtimes repeat the first point at the end to close the loop
+fn open_polygon(points: &[Vec2]) -> &[Vec2] {
+    match points {
+        [first, .., last] if points.len() > 3 && first == last => &points[..points.len() - 1],
+        _ => points,
+    }
+}
+
+fn validate_boundary(points: &[Vec2]) -> Result<()> {
+    if points.len() < 3 {
+        bail!("{} points", points.len());
+    }
+    if !points.iter().all(|p| p.is_finite()) {
+        bail!("non finite points");
+    }
+
+    let area = (0..points.len())
+        .map(|i| points[i].perp_dot(points[(i + 1) % points.len()]))
+        .sum::<f32>()
+        .abs()
+        / 2.0;
+    if area < MIN_BOUNDARY_AREA_M2 {
+        bail!("area of {area} m^2");
+    }
+
+    let edge = |i: usize| (points[i], points[(i + 1) % points.len()]);
+    for i in 0..points.len() {
+        // Adjacent edges share a point, so they are skipped. The last edge is adjacent to the first
+        for j in i + 2..points.len() - usize::from(i == 0) {
+            let (a1, a2) = edge(i);
+            let (b1, b2) = edge(j);
+            if segments_intersect(a1, a2, b1, b2) {
+                bail!("edges {i} and {j} intersect");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn bounding_box_size(points: &[Vec2]) -> Vec2 {
+    let min = points.iter().copied().reduce(Vec2::min).unwrap_or_default();
+    let max = points.iter().copied().reduce(Vec2::max).unwrap_or_default();
+
+    max - min
+}
+
+// Width and depth of a rectangle centered in the stage space origin
+pub fn set_area(area: Option<Vec2>) {
+    *LAST_PLAYSPACE.lock() = Some(Playspace::Area(area));
+
+    let area = area.unwrap_or(DEFAULT_AREA);
+    let wh = area.x * area.y;
+    if wh.is_finite() && wh > 0.0 {
+        info!("Received new playspace with size: {}", area);
+        unsafe { crate::SetChaperoneArea(area.x, area.y) };
+    } else {
+        warn!("Received invalid playspace size: {}", area);
+        unsafe { crate::SetChaperoneArea(DEFAULT_AREA.x, DEFAULT_AREA.y) };
+    }
+}
+
+// Points on the floor of the stage space. The play area is the bounding box of the boundary
+pub fn set_boundary(points: &[Vec2]) {
+    *LAST_PLAYSPACE.lock() = Some(Playspace::Boundary(points.to_vec()));
+
+    let points = open_polygon(points);
+
+    if let Err(e) = validate_boundary(points) {
+        warn!("Received invalid playspace boundary ({e}). Using its bounding box instead");
+        set_area(Some(bounding_box_size(points)));
+
+        // The bounding box is derived again from the boundary on resync
+        *LAST_PLAYSPACE.lock() = Some(Playspace::Boundary(points.to_vec()));
+
+        return;
+    }
+
+    let size = bounding_box_size(points);
+    info!(
+        "Received new playspace boundary with {} points and size: {size}",
+        points.len()
+    );
+
+    let ffi_points = points
+        .iter()
+        .map(|p| FfiPlayspacePoint { x: p.x, z: p.y })
+        .collect::<Vec<_>>();
+    unsafe {
+        crate::SetChaperoneBoundary(ffi_points.as_ptr(), ffi_points.len() as _, size.x, size.y)
+    };
+}
+
+// The recentering origin changed, the chaperone must be set again
+pub fn resync() {
+    let playspace = LAST_PLAYSPACE.lock().clone();
+    match playspace {
+        Some(Playspace::Area(area)) => set_area(area),
+        Some(Playspace::Boundary(points)) => set_boundary(&points),
+        None => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boundary_validation() {
+        let square = [
+            Vec2::new(-1.0, -1.0),
+            Vec2::new(1.0, -1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(-1.0, 1.0),
+        ];
+        assert!(validate_boundary(&square).is_ok());
+        assert_eq!(bounding_box_size(&square), Vec2::new(2.0, 2.0));
+
+        // Concave shapes are fine
+        let l_shape = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(2.0, 1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(1.0, 2.0),
+            Vec2::new(0.0, 2.0),
+        ];
+        assert!(validate_boundary(&l_shape).is_ok());
+
+        assert!(validate_boundary(&square[..2]).is_err());
+
+        let bowtie = [square[0], square[1], square[3], square[2]];
+        assert!(validate_boundary(&bowtie).is_err());
+
+        let flat = [Vec2::ZERO, Vec2::X, Vec2::X * 2.0];
+        assert!(validate_boundary(&flat).is_err());
+
+        let mut closed = square.to_vec();
+        closed.push(square[0]);
+        assert_eq!(open_polygon(&closed), &square);
+    }
+}
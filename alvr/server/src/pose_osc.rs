@@ -0,0 +1,206 @@
+// Sends the poses of the headset and of the controllers, after recentering, as OSC messages over
+// UDP. Addresses and arguments:
+// * /alvr/head/pose, /alvr/controller/left/pose, /alvr/controller/right/pose: position x, y, z in
+//   meters, then either the orientation quaternion x, y, z, w or pitch, yaw and roll in degrees
+// * the same addresses ending with /battery: the charge from 0 to 1 and whether it is plugged
+// Messages are encoded and sent by a separate thread. Updates that do not fit in the queue are
+// dropped, so that a slow receiver never delays the tracking
+use alvr_common::{
+    anyhow::{bail, Result},
+    glam::EulerRot,
+    once_cell::sync::Lazy,
+    warn, DeviceMotion, Pose, HAND_LEFT_ID, HAND_RIGHT_ID, HEAD_ID,
+};
+use alvr_session::{PoseOscConfig, PoseOscFormat};
+use rosc::{OscMessage, OscPacket, OscType};
+use std::{
+    collections::HashMap,
+    net::{Ipv4Addr, Ipv6Addr, ToSocketAddrs, UdpSocket},
+    sync::mpsc::{self, Receiver, SyncSender},
+    thread,
+    time::{Duration, Instant},
+};
+
+const CHANNEL_CAPACITY: usize = 64;
+
+static DEVICE_OSC_PATH_MAP: Lazy<HashMap<u64, &'static str>> = Lazy::new(|| {
+    HashMap::from([
+        (*HEAD_ID, "/alvr/head"),
+        (*HAND_LEFT_ID, "/alvr/controller/left"),
+        (*HAND_RIGHT_ID, "/alvr/controller/right"),
+    ])
+});
+
+enum OscUpdate {
+    Poses(Vec<(u64, Pose)>),
+    Battery {
+        device_id: u64,
+        gauge_value: f32,
+        is_plugged: bool,
+    },
+}
+
+fn pose_message(device_id: u64, pose: Pose, format: PoseOscFormat) -> Option<OscMessage> {
+    let path = DEVICE_OSC_PATH_MAP.get(&device_id)?;
+
+    let p = pose.position;
+    let mut args = vec![p.x, p.y, p.z];
+    match format {
+        PoseOscFormat::Quaternion => {
+            let q = pose.orientation;
+            args.extend([q.x, q.y, q.z, q.w]);
+        }
+        PoseOscFormat::EulerAngles => {
+            let (yaw, pitch, roll) = pose.orientation.to_euler(EulerRot::YXZ);
+            args.extend([pitch.to_degrees(), yaw.to_degrees(), roll.to_degrees()]);
+        }
+    }
+
+    Some(OscMessage {
+        addr: format!("{path}/pose"),
+        args: args.into_iter().map(OscType::Float).collect(),
+    })
+}
+
+fn battery_message(device_id: u64, gauge_value: f32, is_plugged: bool) -> Option<OscMessage> {
+    let path = DEVICE_OSC_PATH_MAP.get(&device_id)?;
+
+    Some(OscMessage {
+        addr: format!("{path}/battery"),
+        args: vec![OscType::Float(gauge_value), OscType::Bool(is_plugged)],
+    })
+}
+
+// Returns when all the senders are dropped
+fn run(config: PoseOscConfig, receiver: Receiver<OscUpdate>) -> Result<()> {
+    let Some(target) = (config.host.as_str(), config.port)
+        .to_socket_addrs()?
+        .next()
+    else {
+        bail!("{} could not be resolved", config.host);
+    };
+
+    let socket = if target.is_ipv4() {
+        UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))?
+    } else {
+        UdpSocket::bind((Ipv6Addr::UNSPECIFIED, 0))?
+    };
+    socket.connect(target)?;
+
+    while let Ok(update) = receiver.recv() {
+        let messages = match update {
+            OscUpdate::Poses(poses) => poses
+                .into_iter()
+                .filter_map(|(id, pose)| pose_message(id, pose, config.format))
+                .collect(),
+            OscUpdate::Battery {
+                device_id,
+                gauge_value,
+                is_plugged,
+            } => battery_message(device_id, gauge_value, is_plugged)
+                .into_iter()
+                .collect::<Vec<_>>(),
+        };
+
+        for message in messages {
+            // The receiver may not be running yet
+            socket
+                .send(&rosc::encoder::encode(&OscPacket::Message(message))?)
+                .ok();
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct PoseOscSender {
+    sender: SyncSender<OscUpdate>,
+    pose_interval: Duration,
+    next_pose_send: Instant,
+    battery: bool,
+}
+
+impl PoseOscSender {
+    pub fn new(config: PoseOscConfig) -> Self {
+        let pose_interval = Duration::from_secs_f32(1.0 / config.send_rate_hz.max(1.0));
+        let battery = config.battery;
+
+        let (sender, receiver) = mpsc::sync_channel(CHANNEL_CAPACITY);
+        thread::spawn(move || {
+            if let Err(e) = run(config, receiver) {
+                warn!("OSC pose output stopped: {e}");
+            }
+        });
+
+        Self {
+            sender,
+            pose_interval,
+            next_pose_send: Instant::now(),
+            battery,
+        }
+    }
+
+    // Updates more frequent than the send rate are skipped
+    pub fn report_poses(&mut self, motions: &[(u64, DeviceMotion)]) {
+        let now = Instant::now();
+        if now < self.next_pose_send {
+            return;
+        }
+        self.next_pose_send = now + self.pose_interval;
+
+        let poses = motions
+            .iter()
+            .filter(|(id, _)| DEVICE_OSC_PATH_MAP.contains_key(id))
+            .map(|(id, motion)| (*id, motion.pose))
+            .collect();
+        self.sender.try_send(OscUpdate::Poses(poses)).ok();
+    }
+
+    pub fn report_battery(&self, device_id: u64, gauge_value: f32, is_plugged: bool) {
+        if self.battery {
+            self.sender
+                .try_send(OscUpdate::Battery {
+                    device_id,
+                    gauge_value,
+                    is_plugged,
+                })
+                .ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alvr_common::glam::{Quat, Vec3};
+
+    #[test]
+    fn test_messages() {
+        let pose = Pose {
+            orientation: Quat::from_rotation_y(std::f32::consts::FRAC_PI_2),
+            position: Vec3::new(1.0, 2.0, 3.0),
+        };
+
+        let message = pose_message(*HAND_LEFT_ID, pose, PoseOscFormat::Quaternion).unwrap();
+        assert_eq!(message.addr, "/alvr/controller/left/pose");
+        assert_eq!(message.args.len(), 7);
+        assert_eq!(message.args[0], OscType::Float(1.0));
+        assert_eq!(message.args[6], OscType::Float(pose.orientation.w));
+
+        let message = pose_message(*HEAD_ID, pose, PoseOscFormat::EulerAngles).unwrap();
+        assert_eq!(message.addr, "/alvr/head/pose");
+        assert_eq!(message.args.len(), 6);
+        let OscType::Float(yaw) = message.args[4] else {
+            panic!("The yaw is not a float");
+        };
+        assert!((yaw - 90.0).abs() < 1e-3);
+
+        let message = battery_message(*HAND_RIGHT_ID, 0.5, true).unwrap();
+        assert_eq!(message.addr, "/alvr/controller/right/battery");
+        assert_eq!(message.args, vec![OscType::Float(0.5), OscType::Bool(true)]);
+
+        // Only the head and the controllers have an address
+        assert!(pose_message(1, pose, PoseOscFormat::Quaternion).is_none());
+    }
+}
@@ -1,15 +1,108 @@
 use alvr_common::{
-    anyhow::{bail, Result},
-    warn, ConnectionError, HandleTryAgain, ToAny, ALVR_NAME,
+    anyhow::{self, bail, Result},
+    warn, ConnectionError, HandleTryAgain, LogSeverity, ToAny, ALVR_NAME,
 };
-use alvr_sockets::{CONTROL_PORT, HANDSHAKE_PACKET_SIZE_BYTES, LOCAL_IP};
+use alvr_events::{EventType, PortConflictEvent, PortUsage};
+use alvr_session::SocketProtocol;
+use alvr_sockets::{HANDSHAKE_PACKET_SIZE_BYTES, LEGACY_HANDSHAKE_PACKET_SIZE_BYTES, LOCAL_IP};
 use flume::TryRecvError;
 use mdns_sd::{Receiver, ServiceDaemon, ServiceEvent};
 use std::{
     collections::HashMap,
-    net::{IpAddr, UdpSocket},
+    io,
+    net::{SocketAddr, UdpSocket},
+    process::Command,
 };
 
+#[cfg(windows)]
+pub const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let mut command = Command::new(program);
+    command.args(args);
+    #[cfg(windows)]
+    std::os::windows::process::CommandExt::creation_flags(&mut command, CREATE_NO_WINDOW);
+
+    let output = command.output().ok()?;
+
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// Best effort: the tools may be missing, and on Linux only the processes of the same user are
+// listed
+fn find_port_owner(port: u16, protocol: SocketProtocol) -> Option<String> {
+    if cfg!(windows) {
+        let protocol = match protocol {
+            SocketProtocol::Udp => "UDP",
+            SocketProtocol::Tcp => "TCP",
+        };
+        let local_address_suffix = format!(":{port}");
+
+        // Columns: protocol, local address, remote address, [state], PID
+        let pid = command_output("netstat", &["-ano", "-p", protocol])?
+            .lines()
+            .find_map(|line| {
+                let columns = line.split_whitespace().collect::<Vec<_>>();
+
+                columns
+                    .get(1)
+                    .filter(|address| address.ends_with(&local_address_suffix))
+                    .and_then(|_| columns.last())
+                    .map(|pid| pid.to_string())
+            })?;
+
+        let tasklist = command_output(
+            "tasklist",
+            &["/FI", &format!("PID eq {pid}"), "/FO", "CSV", "/NH"],
+        )?;
+        let name = tasklist.split(',').next()?.trim().trim_matches('"');
+
+        Some(format!("{name} (PID {pid})"))
+    } else {
+        let protocol = match protocol {
+            SocketProtocol::Udp => "-u",
+            SocketProtocol::Tcp => "-t",
+        };
+
+        // The process is listed as users:(("<name>",pid=<pid>,fd=<fd>))
+        let sockets = command_output("ss", &["-Hlnp", protocol, &format!("sport = :{port}")])?;
+        let (name, process) = sockets.split("users:((\"").nth(1)?.split_once('"')?;
+        let pid = process.split("pid=").nth(1)?.split(',').next()?;
+
+        Some(format!("{name} (PID {pid})"))
+    }
+}
+
+// Returns false if the error is not caused by the port being in use
+pub fn report_port_conflict(
+    error: &anyhow::Error,
+    port: u16,
+    protocol: SocketProtocol,
+    usage: PortUsage,
+) -> bool {
+    let addr_in_use = error.chain().any(|cause| {
+        cause
+            .downcast_ref::<io::Error>()
+            .map(|e| e.kind() == io::ErrorKind::AddrInUse)
+            .unwrap_or(false)
+    });
+    if !addr_in_use {
+        return false;
+    }
+
+    alvr_events::send_event(
+        LogSeverity::Error,
+        EventType::PortConflict(PortConflictEvent {
+            port,
+            protocol,
+            usage,
+            owner: find_port_owner(port, protocol),
+        }),
+    );
+
+    true
+}
+
 pub struct WelcomeSocket {
     buffer: [u8; HANDSHAKE_PACKET_SIZE_BYTES],
     broadcast_receiver: UdpSocket,
@@ -17,8 +110,10 @@ pub struct WelcomeSocket {
 }
 
 impl WelcomeSocket {
-    pub fn new() -> Result<Self> {
-        let socket = UdpSocket::bind((LOCAL_IP, CONTROL_PORT))?;
+    // Bound to the discovery port whatever the control port setting, since clients that never
+    // connected to this streamer announce only there
+    pub fn new(port: u16) -> Result<Self> {
+        let socket = UdpSocket::bind((LOCAL_IP, port))?;
         // socket.set_read_timeout(Some(read_timeout))?;
         socket.set_nonblocking(true)?;
 
@@ -31,8 +126,8 @@ impl WelcomeSocket {
         })
     }
 
-    // Returns: client IP, client hostname
-    pub fn recv_all(&mut self) -> Result<HashMap<String, IpAddr>> {
+    // Returns: client hostname, client IP and control port
+    pub fn recv_all(&mut self) -> Result<HashMap<String, SocketAddr>> {
         let mut clients = HashMap::new();
 
         loop {
@@ -42,7 +137,8 @@ impl WelcomeSocket {
                 .handle_try_again()
             {
                 Ok((size, address)) => {
-                    if size == HANDSHAKE_PACKET_SIZE_BYTES
+                    if (size == HANDSHAKE_PACKET_SIZE_BYTES
+                        || size == LEGACY_HANDSHAKE_PACKET_SIZE_BYTES)
                         && &self.buffer[..ALVR_NAME.len()] == ALVR_NAME.as_bytes()
                         && self.buffer[ALVR_NAME.len()..16].iter().all(|b| *b == 0)
                     {
@@ -50,11 +146,11 @@ impl WelcomeSocket {
                         protocol_id_bytes.copy_from_slice(&self.buffer[16..24]);
                         let received_protocol_id = u64::from_le_bytes(protocol_id_bytes);
 
-                        if received_protocol_id != alvr_common::protocol_id_u64() {
+                        if received_protocol_id != alvr_packets::protocol_id_u64() {
                             warn!(
                                 "Found incompatible client! Upgrade or downgrade\n{} {}, {} {}",
                                 "Expected protocol ID",
-                                alvr_common::protocol_id_u64(),
+                                alvr_packets::protocol_id_u64(),
                                 "Found",
                                 received_protocol_id
                             );
@@ -66,7 +162,13 @@ impl WelcomeSocket {
                             .trim_end_matches('\x00')
                             .to_owned();
 
-                        clients.insert(hostname, address.ip());
+                        let control_port = if size == HANDSHAKE_PACKET_SIZE_BYTES {
+                            u16::from_le_bytes([self.buffer[56], self.buffer[57]])
+                        } else {
+                            address.port()
+                        };
+
+                        clients.insert(hostname, SocketAddr::new(address.ip(), control_port));
                     } else if &self.buffer[..16]
                         == b"\x00\x00\x00\x00\x04\x00\x00\x00\x00\x00\x00\x00ALVR"
                         || &self.buffer[..5] == b"\x01ALVR"
@@ -94,10 +196,10 @@ impl WelcomeSocket {
                             .get_property_val_str(alvr_sockets::MDNS_PROTOCOL_KEY)
                             .to_any()?;
 
-                        if protocol != alvr_common::protocol_id() {
+                        if protocol != alvr_packets::protocol_id() {
                             let msg = format!(
                                 r#"Expected protocol ID "{}", found "{}""#,
-                                alvr_common::protocol_id(),
+                                alvr_packets::protocol_id(),
                                 protocol
                             );
                             warn!(
@@ -105,7 +207,7 @@ impl WelcomeSocket {
                             );
                         }
 
-                        clients.insert(hostname.into(), address);
+                        clients.insert(hostname.into(), SocketAddr::new(address, info.get_port()));
                     }
                 }
                 Err(TryRecvError::Empty) => break,
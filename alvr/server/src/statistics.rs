@@ -1,38 +1,73 @@
-use alvr_common::{SlidingWindowAverage, HEAD_ID};
+use crate::{bitrate, connection, timing_capture::FrameRow, TIMING_CAPTURE};
+use alvr_common::{LogSeverity, SlidingWindowAverage, HEAD_ID};
 use alvr_events::{EventType, GraphStatistics, NominalBitrateStats, StatisticsSummary};
-use alvr_packets::ClientStatistics;
+use alvr_packets::{ClientStatistics, ClientStatisticsSnapshot, ServerStatistics};
 use std::{
     collections::{HashMap, VecDeque},
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 const FULL_REPORT_INTERVAL: Duration = Duration::from_millis(500);
+// Frames without client statistics after this time are considered dropped
+const FRAME_COMPLETION_TIMEOUT: Duration = Duration::from_secs(1);
 
+// Record of the journey of one frame, keyed by the tracking frame index assigned when its pose is
+// received. Stages that were not reached are None
 pub struct HistoryFrame {
+    tracking_frame_index: u64,
     target_timestamp: Duration,
     tracking_received: Instant,
-    frame_present: Instant,
-    frame_composed: Instant,
-    frame_encoded: Instant,
+    frame_present: Option<Instant>,
+    frame_composed: Option<Instant>,
+    // While the client is idle, tracking is received at a reduced rate and SteamVR renders several
+    // frames with the same pose. They are still valid frames, but only the first one is timed
+    frame_encoded: Option<Instant>,
+    // When the last slice was written to the socket
+    video_sent: Option<Instant>,
     video_packet_bytes: usize,
+    // Time the video packets were held back by the pacer, summed over the slices
+    video_pacing: Duration,
     total_pipeline_latency: Duration,
+    // Set when the client statistics arrive or when the frame is finalized as dropped
+    completed: bool,
 }
 
 impl Default for HistoryFrame {
     fn default() -> Self {
-        let now = Instant::now();
         Self {
+            tracking_frame_index: 0,
             target_timestamp: Duration::ZERO,
-            tracking_received: now,
-            frame_present: now,
-            frame_composed: now,
-            frame_encoded: now,
+            tracking_received: Instant::now(),
+            frame_present: None,
+            frame_composed: None,
+            frame_encoded: None,
+            video_sent: None,
             video_packet_bytes: 0,
+            video_pacing: Duration::ZERO,
             total_pipeline_latency: Duration::ZERO,
+            completed: false,
         }
     }
 }
 
+// Completed frames are written to the timing capture, if running
+fn record_frame(frame: &HistoryFrame, client_stats: Option<&ClientStatistics>) {
+    if let Some(capture) = &*TIMING_CAPTURE.lock() {
+        capture.report_frame(FrameRow {
+            tracking_frame_index: frame.tracking_frame_index,
+            target_timestamp: frame.target_timestamp,
+            tracking_received: frame.tracking_received,
+            frame_present: frame.frame_present,
+            frame_composed: frame.frame_composed,
+            frame_encoded: frame.frame_encoded,
+            video_sent: frame.video_sent,
+            video_bytes: frame.video_packet_bytes,
+            video_pacing: frame.video_pacing,
+            client_stats: client_stats.cloned(),
+        });
+    }
+}
+
 #[derive(Default, Clone)]
 struct BatteryData {
     gauge_value: f32,
@@ -42,6 +77,7 @@ struct BatteryData {
 pub struct StatisticsManager {
     history_buffer: VecDeque<HistoryFrame>,
     max_history_size: usize,
+    next_tracking_frame_index: u64,
     last_full_report_instant: Instant,
     last_frame_present_instant: Instant,
     last_frame_present_interval: Duration,
@@ -51,12 +87,28 @@ pub struct StatisticsManager {
     video_bytes_partial_sum: usize,
     packets_lost_total: usize,
     packets_lost_partial_sum: usize,
+    // Encoded frames that the client never reported as displayed
+    frames_dropped_total: usize,
+    // Packets discarded by the overflow policy of the receive streams
+    stream_packets_dropped_total: usize,
     battery_gauges: HashMap<u64, BatteryData>,
     steamvr_pipeline_latency: Duration,
     total_pipeline_latency_average: SlidingWindowAverage<Duration>,
+    // Used for pose extrapolation. Approximates the latency the client predicted poses for
+    reported_pipeline_latency_average: SlidingWindowAverage<Duration>,
+    last_total_pipeline_latency: Duration,
     last_vsync_time: Instant,
     frame_interval: Duration,
     last_nominal_bitrate_stats: NominalBitrateStats,
+    // Accumulated since the last statistics overlay report
+    overlay_video_bytes: usize,
+    overlay_packets_lost: usize,
+    last_overlay_report_instant: Instant,
+    last_network_latency: Duration,
+    last_encoder_latency: Duration,
+    graph_statistics_interval: Duration,
+    last_graph_statistics_instant: Instant,
+    graph_packets_lost: usize,
 }
 
 impl StatisticsManager {
@@ -65,10 +117,12 @@ impl StatisticsManager {
         max_history_size: usize,
         nominal_server_frame_interval: Duration,
         steamvr_pipeline_frames: f32,
+        graph_statistics_interval: Duration,
     ) -> Self {
         Self {
             history_buffer: VecDeque::new(),
             max_history_size,
+            next_tracking_frame_index: 0,
             last_full_report_instant: Instant::now(),
             last_frame_present_instant: Instant::now(),
             last_frame_present_interval: Duration::ZERO,
@@ -78,6 +132,8 @@ impl StatisticsManager {
             video_bytes_partial_sum: 0,
             packets_lost_total: 0,
             packets_lost_partial_sum: 0,
+            frames_dropped_total: 0,
+            stream_packets_dropped_total: 0,
             battery_gauges: HashMap::new(),
             steamvr_pipeline_latency: Duration::from_secs_f32(
                 steamvr_pipeline_frames * nominal_server_frame_interval.as_secs_f32(),
@@ -86,53 +142,108 @@ impl StatisticsManager {
                 Duration::ZERO,
                 max_history_size,
             ),
+            reported_pipeline_latency_average: SlidingWindowAverage::new(
+                Duration::ZERO,
+                max_history_size,
+            ),
+            last_total_pipeline_latency: Duration::ZERO,
             last_vsync_time: Instant::now(),
             frame_interval: nominal_server_frame_interval,
             last_nominal_bitrate_stats: NominalBitrateStats::default(),
+            overlay_video_bytes: 0,
+            overlay_packets_lost: 0,
+            last_overlay_report_instant: Instant::now(),
+            last_network_latency: Duration::ZERO,
+            last_encoder_latency: Duration::ZERO,
+            graph_statistics_interval,
+            last_graph_statistics_instant: Instant::now(),
+            graph_packets_lost: 0,
         }
     }
 
-    pub fn report_tracking_received(&mut self, target_timestamp: Duration) {
-        if !self
-            .history_buffer
+    // The driver and the client only know the pose of a frame, which is resolved to the index of
+    // the tracking it was received with
+    fn tracking_frame_index(&self, target_timestamp: Duration) -> Option<u64> {
+        self.history_buffer
             .iter()
-            .any(|frame| frame.target_timestamp == target_timestamp)
-        {
+            .find(|frame| frame.target_timestamp == target_timestamp)
+            .map(|frame| frame.tracking_frame_index)
+    }
+
+    fn frame_mut(&mut self, target_timestamp: Duration) -> Option<&mut HistoryFrame> {
+        let tracking_frame_index = self.tracking_frame_index(target_timestamp)?;
+
+        self.history_buffer
+            .iter_mut()
+            .find(|frame| frame.tracking_frame_index == tracking_frame_index)
+    }
+
+    fn pending_frame_mut(&mut self, target_timestamp: Duration) -> Option<&mut HistoryFrame> {
+        self.frame_mut(target_timestamp)
+            .filter(|frame| frame.frame_encoded.is_none())
+    }
+
+    fn finalize_dropped_frame(frame: &mut HistoryFrame, frames_dropped_total: &mut usize) {
+        frame.completed = true;
+        if frame.frame_encoded.is_some() {
+            *frames_dropped_total += 1;
+        }
+
+        record_frame(frame, None);
+    }
+
+    pub fn report_tracking_received(&mut self, target_timestamp: Duration) {
+        if self.tracking_frame_index(target_timestamp).is_none() {
             self.history_buffer.push_front(HistoryFrame {
+                tracking_frame_index: self.next_tracking_frame_index,
                 target_timestamp,
                 tracking_received: Instant::now(),
                 ..Default::default()
             });
+            self.next_tracking_frame_index += 1;
         }
 
+        // SteamVR does not render every pose, the ones that were never rendered are not frames
+        // and are discarded
+        let now = Instant::now();
+        let frames_dropped_total = &mut self.frames_dropped_total;
+        self.history_buffer.retain_mut(|frame| {
+            if frame.completed || now <= frame.tracking_received + FRAME_COMPLETION_TIMEOUT {
+                return true;
+            }
+            if frame.frame_present.is_none() {
+                return false;
+            }
+
+            Self::finalize_dropped_frame(frame, frames_dropped_total);
+
+            true
+        });
+
         if self.history_buffer.len() > self.max_history_size {
-            self.history_buffer.pop_back();
+            if let Some(mut frame) = self.history_buffer.pop_back() {
+                if !frame.completed && frame.frame_present.is_some() {
+                    Self::finalize_dropped_frame(&mut frame, &mut self.frames_dropped_total);
+                }
+            }
         }
     }
 
     pub fn report_frame_present(&mut self, target_timestamp: Duration, offset: Duration) {
-        if let Some(frame) = self
-            .history_buffer
-            .iter_mut()
-            .find(|frame| frame.target_timestamp == target_timestamp)
-        {
-            let now = Instant::now() - offset;
+        let now = Instant::now() - offset;
 
-            self.last_frame_present_interval =
-                now.saturating_duration_since(self.last_frame_present_instant);
-            self.last_frame_present_instant = now;
+        self.last_frame_present_interval =
+            now.saturating_duration_since(self.last_frame_present_instant);
+        self.last_frame_present_instant = now;
 
-            frame.frame_present = now;
+        if let Some(frame) = self.pending_frame_mut(target_timestamp) {
+            frame.frame_present = Some(now);
         }
     }
 
     pub fn report_frame_composed(&mut self, target_timestamp: Duration, offset: Duration) {
-        if let Some(frame) = self
-            .history_buffer
-            .iter_mut()
-            .find(|frame| frame.target_timestamp == target_timestamp)
-        {
-            frame.frame_composed = Instant::now() - offset;
+        if let Some(frame) = self.pending_frame_mut(target_timestamp) {
+            frame.frame_composed = Some(Instant::now() - offset);
         }
     }
 
@@ -146,27 +257,38 @@ impl StatisticsManager {
         self.video_packets_partial_sum += 1;
         self.video_bytes_total += bytes_count;
         self.video_bytes_partial_sum += bytes_count;
+        self.overlay_video_bytes += bytes_count;
 
-        if let Some(frame) = self
-            .history_buffer
-            .iter_mut()
-            .find(|frame| frame.target_timestamp == target_timestamp)
-        {
-            frame.frame_encoded = Instant::now();
+        if let Some(frame) = self.pending_frame_mut(target_timestamp) {
+            let now = Instant::now();
+            frame.frame_encoded = Some(now);
 
             frame.video_packet_bytes = bytes_count;
 
-            frame
-                .frame_encoded
-                .saturating_duration_since(frame.frame_composed)
+            now.saturating_duration_since(frame.frame_composed.unwrap_or(frame.tracking_received))
         } else {
             Duration::ZERO
         }
     }
 
+    // Called for each slice written to the socket. The frame can be sent before or after it is
+    // reported as encoded
+    pub fn report_video_sent(&mut self, target_timestamp: Duration, pacing: Duration) {
+        if let Some(frame) = self.frame_mut(target_timestamp) {
+            frame.video_sent = Some(Instant::now());
+            frame.video_pacing += pacing;
+        }
+    }
+
     pub fn report_packet_loss(&mut self) {
         self.packets_lost_total += 1;
         self.packets_lost_partial_sum += 1;
+        self.overlay_packets_lost += 1;
+        self.graph_packets_lost += 1;
+    }
+
+    pub fn report_stream_packets_dropped(&mut self, count: usize) {
+        self.stream_packets_dropped_total += count;
     }
 
     pub fn report_battery(&mut self, device_id: u64, gauge_value: f32, is_plugged: bool) {
@@ -183,41 +305,53 @@ impl StatisticsManager {
     // Called every frame. Some statistics are reported once every frame
     // Returns (network latency, game time latency)
     pub fn report_statistics(&mut self, client_stats: ClientStatistics) -> (Duration, Duration) {
-        if let Some(frame) = self
-            .history_buffer
-            .iter_mut()
-            .find(|frame| frame.target_timestamp == client_stats.target_timestamp)
-        {
+        // Statistics arriving after the frame was finalized as dropped are ignored
+        let tracking_frame_index = self.tracking_frame_index(client_stats.target_timestamp);
+        if let Some(frame) = self.history_buffer.iter_mut().find(|frame| {
+            Some(frame.tracking_frame_index) == tracking_frame_index && !frame.completed
+        }) {
             frame.total_pipeline_latency = client_stats.total_pipeline_latency;
+            frame.completed = true;
+            record_frame(frame, Some(&client_stats));
+
+            // Stages that were not reached take no time
+            let frame_present = frame.frame_present.unwrap_or(frame.tracking_received);
+            let frame_composed = frame.frame_composed.unwrap_or(frame_present);
+            let frame_encoded = frame.frame_encoded.unwrap_or(frame_composed);
 
-            let game_time_latency = frame
-                .frame_present
-                .saturating_duration_since(frame.tracking_received);
+            self.last_total_pipeline_latency = client_stats.total_pipeline_latency;
+            self.reported_pipeline_latency_average
+                .submit_sample(client_stats.total_pipeline_latency);
 
-            let server_compositor_latency = frame
-                .frame_composed
-                .saturating_duration_since(frame.frame_present);
+            let game_time_latency =
+                frame_present.saturating_duration_since(frame.tracking_received);
 
-            let encoder_latency = frame
-                .frame_encoded
-                .saturating_duration_since(frame.frame_composed);
+            let server_compositor_latency = frame_composed.saturating_duration_since(frame_present);
+
+            let encoder_latency = frame_encoded.saturating_duration_since(frame_composed);
 
             // The network latency cannot be estiamed directly. It is what's left of the total
             // latency after subtracting all other latency intervals. In particular it contains the
             // transport latency of the tracking packet and the interval between the first video
-            // packet is sent and the last video packet is received for a specific frame.
+            // packet is sent and the last video packet is received for a specific frame, minus the
+            // pacing delay which is reported separately.
             // For safety, use saturating_sub to avoid a crash if for some reason the network
             // latency is miscalculated as negative.
             let network_latency = frame.total_pipeline_latency.saturating_sub(
                 game_time_latency
                     + server_compositor_latency
                     + encoder_latency
+                    + frame.video_pacing
+                    + client_stats.video_jitter_buffer
                     + client_stats.video_decode
                     + client_stats.video_decoder_queue
                     + client_stats.rendering
                     + client_stats.vsync_queue,
             );
 
+            self.last_network_latency = network_latency;
+            self.last_encoder_latency = encoder_latency;
+
             let client_fps = 1.0
                 / client_stats
                     .frame_interval
@@ -234,68 +368,94 @@ impl StatisticsManager {
 
                 let interval_secs = FULL_REPORT_INTERVAL.as_secs_f32();
 
-                alvr_events::send_event(EventType::StatisticsSummary(StatisticsSummary {
-                    video_packets_total: self.video_packets_total,
-                    video_packets_per_sec: (self.video_packets_partial_sum as f32 / interval_secs)
-                        as _,
-                    video_mbytes_total: (self.video_bytes_total as f32 / 1e6) as usize,
-                    video_mbits_per_sec: self.video_bytes_partial_sum as f32 * 8.
-                        / 1e6
-                        / interval_secs,
-                    total_latency_ms: client_stats.total_pipeline_latency.as_secs_f32() * 1000.,
-                    network_latency_ms: network_latency.as_secs_f32() * 1000.,
-                    encode_latency_ms: encoder_latency.as_secs_f32() * 1000.,
-                    decode_latency_ms: client_stats.video_decode.as_secs_f32() * 1000.,
-                    packets_lost_total: self.packets_lost_total,
-                    packets_lost_per_sec: (self.packets_lost_partial_sum as f32 / interval_secs)
-                        as _,
-                    client_fps: client_fps as _,
-                    server_fps: server_fps as _,
-                    battery_hmd: (self
-                        .battery_gauges
-                        .get(&HEAD_ID)
-                        .cloned()
-                        .unwrap_or_default()
-                        .gauge_value
-                        * 100.) as u32,
-                    hmd_plugged: self
-                        .battery_gauges
-                        .get(&HEAD_ID)
-                        .cloned()
-                        .unwrap_or_default()
-                        .is_plugged,
-                }));
+                alvr_events::send_event(
+                    LogSeverity::Debug,
+                    EventType::StatisticsSummary(StatisticsSummary {
+                        video_packets_total: self.video_packets_total,
+                        video_packets_per_sec: (self.video_packets_partial_sum as f32
+                            / interval_secs) as _,
+                        video_mbytes_total: (self.video_bytes_total as f32 / 1e6) as usize,
+                        video_mbits_per_sec: self.video_bytes_partial_sum as f32 * 8.
+                            / 1e6
+                            / interval_secs,
+                        total_latency_ms: client_stats.total_pipeline_latency.as_secs_f32() * 1000.,
+                        network_latency_ms: network_latency.as_secs_f32() * 1000.,
+                        encode_latency_ms: encoder_latency.as_secs_f32() * 1000.,
+                        decode_latency_ms: client_stats.video_decode.as_secs_f32() * 1000.,
+                        packets_lost_total: self.packets_lost_total,
+                        packets_lost_per_sec: (self.packets_lost_partial_sum as f32 / interval_secs)
+                            as _,
+                        frames_dropped_total: self.frames_dropped_total,
+                        stream_packets_dropped_total: self.stream_packets_dropped_total,
+                        client_fps: client_fps as _,
+                        server_fps: server_fps as _,
+                        battery_hmd: (self
+                            .battery_gauges
+                            .get(&HEAD_ID)
+                            .cloned()
+                            .unwrap_or_default()
+                            .gauge_value
+                            * 100.) as u32,
+                        hmd_plugged: self
+                            .battery_gauges
+                            .get(&HEAD_ID)
+                            .cloned()
+                            .unwrap_or_default()
+                            .is_plugged,
+                        control_rtt_ms: connection::KEEPALIVE_RTT_MS.get_average().unwrap_or(0.0),
+                        encoder_bitrate_mbps: bitrate::ENCODER_BITRATE_BPS.get() / 1e6,
+                    }),
+                );
 
                 self.video_packets_partial_sum = 0;
                 self.video_bytes_partial_sum = 0;
                 self.packets_lost_partial_sum = 0;
             }
 
-            // While not accurate, this prevents NaNs and zeros that would cause a crash or pollute
-            // the graph
-            let bitrate_bps = if network_latency != Duration::ZERO {
-                frame.video_packet_bytes as f32 * 8.0 / network_latency.as_secs_f32()
-            } else {
-                0.0
-            };
-
-            // todo: use target timestamp in nanoseconds. the dashboard needs to use the first
-            // timestamp as the graph time origin.
-            alvr_events::send_event(EventType::GraphStatistics(GraphStatistics {
-                total_pipeline_latency_s: client_stats.total_pipeline_latency.as_secs_f32(),
-                game_time_s: game_time_latency.as_secs_f32(),
-                server_compositor_s: server_compositor_latency.as_secs_f32(),
-                encoder_s: encoder_latency.as_secs_f32(),
-                network_s: network_latency.as_secs_f32(),
-                decoder_s: client_stats.video_decode.as_secs_f32(),
-                decoder_queue_s: client_stats.video_decoder_queue.as_secs_f32(),
-                client_compositor_s: client_stats.rendering.as_secs_f32(),
-                vsync_queue_s: client_stats.vsync_queue.as_secs_f32(),
-                client_fps,
-                server_fps,
-                nominal_bitrate: self.last_nominal_bitrate_stats.clone(),
-                actual_bitrate_bps: bitrate_bps,
-            }));
+            // The statistics of the other frames are skipped, this is cheap enough to be always on
+            let now = Instant::now();
+            if now >= self.last_graph_statistics_instant + self.graph_statistics_interval {
+                self.last_graph_statistics_instant = now;
+
+                // While not accurate, this prevents NaNs and zeros that would cause a crash or
+                // pollute the graph
+                let bitrate_bps = if network_latency != Duration::ZERO {
+                    frame.video_packet_bytes as f32 * 8.0 / network_latency.as_secs_f32()
+                } else {
+                    0.0
+                };
+
+                let ms = |duration: Duration| duration.as_secs_f32() * 1000.0;
+
+                alvr_events::send_event(
+                    LogSeverity::Debug,
+                    EventType::GraphStatistics(GraphStatistics {
+                        timestamp_ms: SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .map(|time| time.as_millis() as u64)
+                            .unwrap_or(0),
+                        total_pipeline_latency_ms: ms(client_stats.total_pipeline_latency),
+                        game_time_ms: ms(game_time_latency),
+                        server_compositor_ms: ms(server_compositor_latency),
+                        encoder_ms: ms(encoder_latency),
+                        pacing_ms: ms(frame.video_pacing),
+                        network_ms: ms(network_latency),
+                        jitter_buffer_ms: ms(client_stats.video_jitter_buffer),
+                        decoder_ms: ms(client_stats.video_decode),
+                        decoder_queue_ms: ms(client_stats.video_decoder_queue),
+                        client_compositor_ms: ms(client_stats.rendering),
+                        vsync_queue_ms: ms(client_stats.vsync_queue),
+                        client_fps,
+                        server_fps,
+                        nominal_bitrate: self.last_nominal_bitrate_stats.clone(),
+                        actual_bitrate_bps: bitrate_bps,
+                        packets_lost_total: self.packets_lost_total,
+                        packets_lost: self.graph_packets_lost,
+                    }),
+                );
+
+                self.graph_packets_lost = 0;
+            }
 
             (network_latency, game_time_latency)
         } else {
@@ -303,6 +463,48 @@ impl StatisticsManager {
         }
     }
 
+    // Bitrate and packet loss are averaged since the previous call
+    pub fn overlay_statistics(&mut self) -> ServerStatistics {
+        let now = Instant::now();
+        let interval_secs = now
+            .saturating_duration_since(self.last_overlay_report_instant)
+            .as_secs_f32()
+            .max(0.001);
+        self.last_overlay_report_instant = now;
+
+        let battery = self
+            .battery_gauges
+            .get(&HEAD_ID)
+            .cloned()
+            .unwrap_or_default();
+
+        let statistics = ServerStatistics {
+            network_latency: self.last_network_latency,
+            encode_latency: self.last_encoder_latency,
+            video_mbits_per_sec: self.overlay_video_bytes as f32 * 8. / 1e6 / interval_secs,
+            packets_lost_per_sec: self.overlay_packets_lost as f32 / interval_secs,
+            battery_hmd: battery.gauge_value,
+            hmd_plugged: battery.is_plugged,
+        };
+
+        self.overlay_video_bytes = 0;
+        self.overlay_packets_lost = 0;
+
+        statistics
+    }
+
+    // Unlike overlay_statistics(), does not reset anything
+    pub fn snapshot(&self) -> ClientStatisticsSnapshot {
+        ClientStatisticsSnapshot {
+            video_packets_total: self.video_packets_total as u64,
+            video_bytes_total: self.video_bytes_total as u64,
+            packets_lost_total: self.packets_lost_total as u64,
+            total_pipeline_latency_average: self.total_pipeline_latency_average.get_average(),
+            network_latency: self.last_network_latency,
+            encode_latency: self.last_encoder_latency,
+        }
+    }
+
     pub fn video_pipeline_latency_average(&self) -> Duration {
         self.total_pipeline_latency_average.get_average()
     }
@@ -313,6 +515,16 @@ impl StatisticsManager {
             .saturating_sub(self.total_pipeline_latency_average.get_average())
     }
 
+    // Time by which poses should be advanced to compensate for the latest latency deviation from
+    // the average. Positive if the pipeline is currently slower than what the client predicted for.
+    pub fn pose_extrapolation_offset_s(&self) -> f32 {
+        self.last_total_pipeline_latency.as_secs_f32()
+            - self
+                .reported_pipeline_latency_average
+                .get_average()
+                .as_secs_f32()
+    }
+
     // NB: this call is non-blocking, waiting should be done externally
     pub fn duration_until_next_vsync(&mut self) -> Duration {
         let now = Instant::now();
@@ -0,0 +1,215 @@
+use alvr_common::{
+    glam::Vec2, LEFT_THUMBSTICK_ID, LEFT_THUMBSTICK_X_ID, LEFT_THUMBSTICK_Y_ID,
+    RIGHT_THUMBSTICK_ID, RIGHT_THUMBSTICK_X_ID, RIGHT_THUMBSTICK_Y_ID,
+};
+use alvr_packets::{ButtonEntry, ButtonValue};
+use alvr_session::{ThumbstickConfig, ThumbsticksConfig};
+
+// The remap acts on the deflection length only, so that the direction is preserved. The range
+// between the deadzone and the threshold is stretched to 0..1, so full deflection still reaches 1
+pub fn remap(value: Vec2, config: &ThumbstickConfig) -> Vec2 {
+    let deflection = value.length();
+    if !deflection.is_finite() || deflection <= config.inner_deadzone {
+        return Vec2::ZERO;
+    }
+
+    let range = config.outer_threshold - config.inner_deadzone;
+    let normalized = if range > f32::EPSILON {
+        ((f32::min(deflection, config.outer_threshold) - config.inner_deadzone) / range)
+            .clamp(0.0, 1.0)
+    } else {
+        1.0
+    };
+
+    value * normalized.powf(config.response_exponent) / deflection
+}
+
+// Clients that do not send Vector2 values report the axes as separate scalars, possibly in
+// different packets. The last value of each axis is kept, so a stick can be remapped as a whole
+#[derive(Default)]
+pub struct ThumbstickProcessor {
+    left: Vec2,
+    right: Vec2,
+}
+
+impl ThumbstickProcessor {
+    pub fn process(
+        &mut self,
+        config: &ThumbsticksConfig,
+        entries: Vec<ButtonEntry>,
+    ) -> Vec<ButtonEntry> {
+        let mut dirty_left = false;
+        let mut dirty_right = false;
+
+        let mut processed = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let id = entry.path_id;
+            match entry.value {
+                ButtonValue::Vector2(value) if id == *LEFT_THUMBSTICK_ID => {
+                    self.left = value;
+                    processed.push(ButtonEntry {
+                        path_id: id,
+                        value: ButtonValue::Vector2(remap(value, &config.left)),
+                    });
+                }
+                ButtonValue::Vector2(value) if id == *RIGHT_THUMBSTICK_ID => {
+                    self.right = value;
+                    processed.push(ButtonEntry {
+                        path_id: id,
+                        value: ButtonValue::Vector2(remap(value, &config.right)),
+                    });
+                }
+                ButtonValue::Scalar(value) if id == *LEFT_THUMBSTICK_X_ID => {
+                    self.left.x = value;
+                    dirty_left = true;
+                }
+                ButtonValue::Scalar(value) if id == *LEFT_THUMBSTICK_Y_ID => {
+                    self.left.y = value;
+                    dirty_left = true;
+                }
+                ButtonValue::Scalar(value) if id == *RIGHT_THUMBSTICK_X_ID => {
+                    self.right.x = value;
+                    dirty_right = true;
+                }
+                ButtonValue::Scalar(value) if id == *RIGHT_THUMBSTICK_Y_ID => {
+                    self.right.y = value;
+                    dirty_right = true;
+                }
+                _ => processed.push(entry),
+            }
+        }
+
+        // Moving one axis can change both remapped axes
+        for (dirty, value, config, x_id, y_id) in [
+            (
+                dirty_left,
+                self.left,
+                &config.left,
+                *LEFT_THUMBSTICK_X_ID,
+                *LEFT_THUMBSTICK_Y_ID,
+            ),
+            (
+                dirty_right,
+                self.right,
+                &config.right,
+                *RIGHT_THUMBSTICK_X_ID,
+                *RIGHT_THUMBSTICK_Y_ID,
+            ),
+        ] {
+            if !dirty {
+                continue;
+            }
+
+            let remapped = remap(value, config);
+
+            processed.push(ButtonEntry {
+                path_id: x_id,
+                value: ButtonValue::Scalar(remapped.x),
+            });
+            processed.push(ButtonEntry {
+                path_id: y_id,
+                value: ButtonValue::Scalar(remapped.y),
+            });
+        }
+
+        processed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-5;
+
+    fn stick_config(
+        inner_deadzone: f32,
+        outer_threshold: f32,
+        response_exponent: f32,
+    ) -> ThumbstickConfig {
+        ThumbstickConfig {
+            inner_deadzone,
+            outer_threshold,
+            response_exponent,
+        }
+    }
+
+    #[test]
+    fn test_deadzone_boundary() {
+        let config = stick_config(0.2, 1.0, 1.0);
+
+        assert_eq!(remap(Vec2::new(0.2, 0.0), &config), Vec2::ZERO);
+        assert_eq!(remap(Vec2::new(0.1, -0.1), &config), Vec2::ZERO);
+
+        // Just outside the deadzone the output starts from 0, without a jump
+        let just_outside = remap(Vec2::new(0.0, 0.201), &config);
+        assert!(just_outside.x == 0.0 && just_outside.y > 0.0 && just_outside.y < 0.01);
+
+        // Halfway between the deadzone and full deflection
+        let half = remap(Vec2::new(-0.6, 0.0), &config);
+        assert!((half - Vec2::new(-0.5, 0.0)).length() < EPSILON);
+    }
+
+    #[test]
+    fn test_corners() {
+        let config = stick_config(0.1, 0.9, 2.0);
+
+        // Square gates let the stick report lengths above 1 at the corners
+        for corner in [
+            Vec2::new(1.0, 1.0),
+            Vec2::new(-1.0, 1.0),
+            Vec2::new(1.0, -1.0),
+            Vec2::new(-1.0, -1.0),
+        ] {
+            let remapped = remap(corner, &config);
+            assert!((remapped.length() - 1.0).abs() < EPSILON);
+            assert!((remapped - corner.normalize()).length() < EPSILON);
+        }
+
+        // Full deflection is reached at the outer threshold
+        let diagonal = Vec2::new(1.0, 1.0).normalize();
+        let remapped = remap(diagonal * 0.9, &config);
+        assert!((remapped - diagonal).length() < EPSILON);
+
+        // The curve applies to the deflection, not to each axis
+        let remapped = remap(diagonal * 0.5, &config);
+        assert!((remapped - diagonal * 0.25).length() < EPSILON);
+    }
+
+    #[test]
+    fn test_separate_axes() {
+        let config = ThumbsticksConfig {
+            left: stick_config(0.5, 1.0, 1.0),
+            right: stick_config(0.0, 1.0, 1.0),
+        };
+        let mut processor = ThumbstickProcessor::default();
+
+        let scalar = |path_id, value| ButtonEntry {
+            path_id,
+            value: ButtonValue::Scalar(value),
+        };
+        let values = |entries: Vec<ButtonEntry>| {
+            entries
+                .iter()
+                .map(|e| match e.value {
+                    ButtonValue::Scalar(value) => (e.path_id, value),
+                    _ => panic!(),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // The other axis of the stick is reported too
+        let entries = processor.process(&config, vec![scalar(*LEFT_THUMBSTICK_X_ID, 0.75)]);
+        assert_eq!(
+            values(entries),
+            [(*LEFT_THUMBSTICK_X_ID, 0.5), (*LEFT_THUMBSTICK_Y_ID, 0.0)]
+        );
+
+        // Remapped with the stored value of the other axis
+        let entries = processor.process(&config, vec![scalar(*LEFT_THUMBSTICK_X_ID, 0.25)]);
+        assert_eq!(
+            values(entries),
+            [(*LEFT_THUMBSTICK_X_ID, 0.0), (*LEFT_THUMBSTICK_Y_ID, 0.0)]
+        );
+    }
+}
@@ -0,0 +1,320 @@
+// Writes the received tracking and the timings of each frame to CSV files, to analyze a session
+// offline. Rows are written by a separate thread. If the disk does not keep up, rows are dropped
+// and counted instead of blocking the caller, so that the capture does not change the timings it
+// measures. Times are in microseconds since the start of the capture
+use alvr_common::{anyhow::Result, error, info, warn, DeviceMotion, Pose};
+use alvr_packets::ClientStatistics;
+use std::{
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+const CHANNEL_CAPACITY: usize = 1024;
+
+const TRACKING_HEADER: &str = "arrival_us,target_timestamp_us,dropped_rows,\
+head_x,head_y,head_z,head_qx,head_qy,head_qz,head_qw,\
+head_vx,head_vy,head_vz,head_wx,head_wy,head_wz,\
+left_x,left_y,left_z,left_qx,left_qy,left_qz,left_qw,\
+right_x,right_y,right_z,right_qx,right_qy,right_qz,right_qw";
+const FRAMES_HEADER: &str = "tracking_frame_index,target_timestamp_us,dropped_rows,frame_dropped,\
+tracking_received_us,frame_present_us,frame_composed_us,frame_encoded_us,video_sent_us,video_bytes,\
+video_pacing_us,client_frame_interval_us,client_jitter_buffer_us,client_decode_us,client_decoder_queue_us,\
+client_rendering_us,client_vsync_queue_us,total_pipeline_latency_us";
+
+// Uses the raw values sent by the client
+pub struct TrackingRow {
+    pub arrival: Instant,
+    pub target_timestamp: Duration,
+    pub head: Option<DeviceMotion>,
+    pub left_hand: Option<Pose>,
+    pub right_hand: Option<Pose>,
+}
+
+// Written when the client statistics of the frame are received, or without them when the frame
+// is considered dropped. Stages that were not reached leave the columns empty
+pub struct FrameRow {
+    pub tracking_frame_index: u64,
+    pub target_timestamp: Duration,
+    pub tracking_received: Instant,
+    pub frame_present: Option<Instant>,
+    pub frame_composed: Option<Instant>,
+    pub frame_encoded: Option<Instant>,
+    pub video_sent: Option<Instant>,
+    pub video_bytes: usize,
+    pub video_pacing: Duration,
+    pub client_stats: Option<ClientStatistics>,
+}
+
+enum Row {
+    Tracking(TrackingRow),
+    Frame(FrameRow),
+}
+
+// Missing values leave the columns empty
+fn pose_columns(pose: Option<Pose>) -> String {
+    if let Some(Pose {
+        orientation: q,
+        position: p,
+    }) = pose
+    {
+        format!("{},{},{},{},{},{},{}", p.x, p.y, p.z, q.x, q.y, q.z, q.w)
+    } else {
+        ",,,,,,".into()
+    }
+}
+
+fn tracking_line(row: &TrackingRow, start: Instant, dropped_rows: usize) -> String {
+    let velocities = if let Some(motion) = &row.head {
+        let (v, w) = (motion.linear_velocity, motion.angular_velocity);
+        format!("{},{},{},{},{},{}", v.x, v.y, v.z, w.x, w.y, w.z)
+    } else {
+        ",,,,,".into()
+    };
+
+    format!(
+        "{},{},{dropped_rows},{},{velocities},{},{}\n",
+        row.arrival.saturating_duration_since(start).as_micros(),
+        row.target_timestamp.as_micros(),
+        pose_columns(row.head.map(|motion| motion.pose)),
+        pose_columns(row.left_hand),
+        pose_columns(row.right_hand),
+    )
+}
+
+fn frame_line(row: &FrameRow, start: Instant, dropped_rows: usize) -> String {
+    let since_start = |instant: Option<Instant>| {
+        instant
+            .map(|instant| {
+                instant
+                    .saturating_duration_since(start)
+                    .as_micros()
+                    .to_string()
+            })
+            .unwrap_or_default()
+    };
+    let client_columns = if let Some(client) = &row.client_stats {
+        format!(
+            "{},{},{},{},{},{},{}",
+            client.frame_interval.as_micros(),
+            client.video_jitter_buffer.as_micros(),
+            client.video_decode.as_micros(),
+            client.video_decoder_queue.as_micros(),
+            client.rendering.as_micros(),
+            client.vsync_queue.as_micros(),
+            client.total_pipeline_latency.as_micros(),
+        )
+    } else {
+        ",,,,,,".into()
+    };
+
+    format!(
+        "{},{},{dropped_rows},{},{},{},{},{},{},{},{},{client_columns}\n",
+        row.tracking_frame_index,
+        row.target_timestamp.as_micros(),
+        u8::from(row.client_stats.is_none()),
+        since_start(Some(row.tracking_received)),
+        since_start(row.frame_present),
+        since_start(row.frame_composed),
+        since_start(row.frame_encoded),
+        since_start(row.video_sent),
+        row.video_bytes,
+        row.video_pacing.as_micros(),
+    )
+}
+
+struct CaptureWriter {
+    tracking_file: BufWriter<File>,
+    frames_file: BufWriter<File>,
+    start: Instant,
+    remaining_bytes: u64,
+    dropped_rows: Arc<AtomicUsize>,
+}
+
+impl CaptureWriter {
+    // Returns false once the size limit is reached
+    fn write(&mut self, row: Row) -> Result<bool> {
+        let dropped_rows = self.dropped_rows.load(Ordering::Relaxed);
+        let (file, line) = match &row {
+            Row::Tracking(row) => (
+                &mut self.tracking_file,
+                tracking_line(row, self.start, dropped_rows),
+            ),
+            Row::Frame(row) => (
+                &mut self.frames_file,
+                frame_line(row, self.start, dropped_rows),
+            ),
+        };
+
+        if line.len() as u64 > self.remaining_bytes {
+            return Ok(false);
+        }
+        self.remaining_bytes -= line.len() as u64;
+        file.write_all(line.as_bytes())?;
+
+        Ok(true)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.tracking_file.flush()?;
+        self.frames_file.flush()?;
+
+        Ok(())
+    }
+
+    // Flushes whenever the queue is empty, so that a crash loses at most the queued rows
+    fn run(mut self, receiver: Receiver<Row>) -> Result<()> {
+        while let Ok(row) = receiver.recv() {
+            let mut row = Some(row);
+            while let Some(next_row) = row.take().or_else(|| receiver.try_recv().ok()) {
+                if !self.write(next_row)? {
+                    self.flush()?;
+                    warn!("Timing capture size limit reached, the capture is stopped");
+
+                    return Ok(());
+                }
+            }
+
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct TimingCapture {
+    sender: SyncSender<Row>,
+    dropped_rows: Arc<AtomicUsize>,
+}
+
+impl TimingCapture {
+    // Creates tracking.csv and frames.csv in the directory. The capture ends when this is dropped
+    pub fn new(dir: &Path, max_size_bytes: u64) -> Result<Self> {
+        fs::create_dir_all(dir)?;
+
+        let mut tracking_file = BufWriter::new(File::create(dir.join("tracking.csv"))?);
+        let mut frames_file = BufWriter::new(File::create(dir.join("frames.csv"))?);
+        writeln!(tracking_file, "{TRACKING_HEADER}")?;
+        writeln!(frames_file, "{FRAMES_HEADER}")?;
+
+        let dropped_rows = Arc::new(AtomicUsize::new(0));
+        let writer = CaptureWriter {
+            tracking_file,
+            frames_file,
+            start: Instant::now(),
+            remaining_bytes: max_size_bytes,
+            dropped_rows: Arc::clone(&dropped_rows),
+        };
+
+        let (sender, receiver) = mpsc::sync_channel(CHANNEL_CAPACITY);
+        thread::spawn(move || {
+            if let Err(e) = writer.run(receiver) {
+                error!("Timing capture failed: {e}");
+            }
+        });
+
+        Ok(Self {
+            sender,
+            dropped_rows,
+        })
+    }
+
+    fn send(&self, row: Row) {
+        if let Err(TrySendError::Full(_)) = self.sender.try_send(row) {
+            self.dropped_rows.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn report_tracking(&self, row: TrackingRow) {
+        self.send(Row::Tracking(row));
+    }
+
+    pub fn report_frame(&self, row: FrameRow) {
+        self.send(Row::Frame(row));
+    }
+}
+
+impl Drop for TimingCapture {
+    fn drop(&mut self) {
+        let dropped_rows = self.dropped_rows.load(Ordering::Relaxed);
+        if dropped_rows > 0 {
+            warn!("Timing capture stopped, {dropped_rows} rows were dropped");
+        } else {
+            info!("Timing capture stopped");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracking_row(arrival: Instant, target_timestamp: Duration) -> TrackingRow {
+        TrackingRow {
+            arrival,
+            target_timestamp,
+            head: Some(DeviceMotion::default()),
+            left_hand: None,
+            right_hand: Some(Pose::default()),
+        }
+    }
+
+    #[test]
+    fn test_rows_match_the_headers() {
+        let start = Instant::now();
+        let columns = |header: &str| header.split(',').count();
+
+        let line = tracking_line(&tracking_row(start, Duration::from_millis(5)), start, 3);
+        assert_eq!(line.trim_end().split(',').count(), columns(TRACKING_HEADER));
+        assert!(line.starts_with("0,5000,3,"));
+
+        let mut row = FrameRow {
+            tracking_frame_index: 7,
+            target_timestamp: Duration::from_millis(5),
+            tracking_received: start,
+            frame_present: Some(start + Duration::from_millis(1)),
+            frame_composed: Some(start),
+            frame_encoded: Some(start),
+            video_sent: Some(start),
+            video_bytes: 1000,
+            video_pacing: Duration::ZERO,
+            client_stats: Some(ClientStatistics::default()),
+        };
+        let line = frame_line(&row, start, 0);
+        assert_eq!(line.trim_end().split(',').count(), columns(FRAMES_HEADER));
+        assert!(line.starts_with("7,5000,0,0,0,1000,"));
+
+        // A dropped frame keeps the stages it reached
+        row.video_sent = None;
+        row.client_stats = None;
+        let line = frame_line(&row, start, 0);
+        assert_eq!(line.trim_end().split(',').count(), columns(FRAMES_HEADER));
+        assert!(line.starts_with("7,5000,0,1,0,1000,0,0,,1000,0,"));
+    }
+
+    #[test]
+    fn test_size_limit() {
+        let dir = std::env::temp_dir().join(format!("alvr_timing_capture_{}", std::process::id()));
+        let capture = TimingCapture::new(&dir, 1000).unwrap();
+        for index in 0..100 {
+            capture.report_tracking(tracking_row(Instant::now(), Duration::from_millis(index)));
+        }
+        // The writer thread exits after writing the queued rows
+        drop(capture);
+        thread::sleep(Duration::from_millis(200));
+
+        let tracking = fs::read_to_string(dir.join("tracking.csv")).unwrap();
+        let rows = tracking.lines().count() - 1;
+        assert!(rows > 0 && rows < 100);
+        assert!(tracking.len() <= TRACKING_HEADER.len() + 1 + 1000);
+
+        fs::remove_dir_all(dir).ok();
+    }
+}
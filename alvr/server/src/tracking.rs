@@ -129,15 +129,20 @@ impl TrackingManager {
         }
     }
 
-    // Performs all kinds of tracking transformations, driven by settings.
+    // Performs all kinds of tracking transformations, driven by settings. The generic trackers are
+    // only recentered
     pub fn transform_motions(
         &mut self,
         config: &HeadsetConfig,
         device_motions: &[(u64, DeviceMotion)],
         hand_skeletons_enabled: [bool; 2],
+        tracker_ids: &[u64],
     ) -> Vec<(u64, DeviceMotion)> {
         let mut device_motion_configs = HashMap::new();
         device_motion_configs.insert(*HEAD_ID, MotionConfig::default());
+        for id in tracker_ids {
+            device_motion_configs.insert(*id, MotionConfig::default());
+        }
 
         if let Switch::Enabled(controllers) = &config.controllers {
             let t = controllers.left_controller_position_offset;
@@ -241,6 +246,17 @@ impl TrackingManager {
     }
 }
 
+// Advance a motion transformed by TrackingManager::transform_motions() in time. Angular velocity is
+// expected in the local frame of the device.
+pub fn extrapolate_motion(mut motion: DeviceMotion, delta_s: f32) -> DeviceMotion {
+    motion.pose.position += motion.linear_velocity * delta_s;
+    motion.pose.orientation = (motion.pose.orientation
+        * Quat::from_scaled_axis(motion.angular_velocity * delta_s))
+    .normalize();
+
+    motion
+}
+
 pub fn to_openvr_hand_skeleton(
     config: &HeadsetConfig,
     device_id: u64,
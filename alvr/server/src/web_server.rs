@@ -1,13 +1,17 @@
 use crate::{
-    bindings::FfiButtonValue, connection::CLIENTS_TO_BE_REMOVED, DECODER_CONFIG, FILESYSTEM_LAYOUT,
-    SERVER_DATA_MANAGER, STATISTICS_MANAGER, VIDEO_MIRROR_SENDER, VIDEO_RECORDING_FILE,
+    bindings::FfiButtonValue,
+    connection::{self, CLIENTS_TO_BE_REMOVED},
+    event_replay::EVENT_REPLAY,
+    logging_backend, sockets, DECODER_CONFIG, FILESYSTEM_LAYOUT, SERVER_DATA_MANAGER,
+    STATISTICS_MANAGER, TIMING_CAPTURE, VIDEO_MIRROR_SENDER, VIDEO_RECORDING_FILE,
 };
 use alvr_common::{
     anyhow::{self, Result},
-    error, info, log, warn, ConnectionState,
+    error, info, log, warn, ConnectionState, LogSeverity,
 };
-use alvr_events::{ButtonEvent, Event, EventType};
+use alvr_events::{ButtonEvent, DisconnectionReason, Event, EventType, PortUsage};
 use alvr_packets::{ButtonValue, ClientListAction, ServerRequest};
+use alvr_session::SocketProtocol;
 use bytes::Buf;
 use futures::SinkExt;
 use headers::HeaderMapExt;
@@ -34,21 +38,29 @@ async fn from_request_body<T: DeserializeOwned>(request: Request<Body>) -> Resul
     )?)
 }
 
+// The initial data is sent before anything received from data_receiver
 async fn websocket<T: Clone + Send + 'static>(
     request: Request<Body>,
-    sender: broadcast::Sender<T>,
+    initial_data: Vec<T>,
+    mut data_receiver: broadcast::Receiver<T>,
     message_builder: impl Fn(T) -> protocol::Message + Send + Sync + 'static,
 ) -> Result<Response<Body>> {
     if let Some(key) = request.headers().typed_get::<headers::SecWebsocketKey>() {
         tokio::spawn(async move {
             match hyper::upgrade::on(request).await {
                 Ok(upgraded) => {
-                    let mut data_receiver = sender.subscribe();
-
                     let mut ws =
                         WebSocketStream::from_raw_socket(upgraded, protocol::Role::Server, None)
                             .await;
 
+                    for data in initial_data {
+                        if let Err(e) = ws.feed(message_builder(data)).await {
+                            info!("Failed to send log with websocket: {e}");
+                            return;
+                        }
+                    }
+                    ws.flush().await.ok();
+
                     loop {
                         match data_receiver.recv().await {
                             Ok(data) => {
@@ -101,15 +113,72 @@ async fn http_api(
                         log::log!(level, "{}", event.content);
                     }
                     ServerRequest::GetSession => {
-                        alvr_events::send_event(EventType::Session(Box::new(
-                            SERVER_DATA_MANAGER.read().session().clone(),
-                        )));
+                        alvr_events::send_event(
+                            LogSeverity::Info,
+                            EventType::Session(Box::new(
+                                SERVER_DATA_MANAGER.read().session().clone(),
+                            )),
+                        );
+                        crate::report_pending_restart();
                     }
                     ServerRequest::UpdateSession(session) => {
-                        *SERVER_DATA_MANAGER.write().session_mut() = *session
+                        *SERVER_DATA_MANAGER.write().session_mut() = *session;
+
+                        logging_backend::update_log_levels(
+                            &SERVER_DATA_MANAGER.read().settings().logging,
+                        );
+                        crate::report_pending_restart();
                     }
                     ServerRequest::SetValues(descs) => {
-                        SERVER_DATA_MANAGER.write().set_values(descs).ok();
+                        let res = SERVER_DATA_MANAGER.write().set_values(descs);
+
+                        logging_backend::update_log_levels(
+                            &SERVER_DATA_MANAGER.read().settings().logging,
+                        );
+
+                        if let Ok(changes) = res {
+                            crate::report_setting_changes(&changes);
+                        }
+                        crate::report_pending_restart();
+                    }
+                    ServerRequest::SetTemporaryValues(descs) => {
+                        let res = SERVER_DATA_MANAGER.write().set_temporary_values(descs);
+                        match res {
+                            Ok(changes) => crate::report_setting_changes(&changes),
+                            Err(e) => warn!("Failed to apply the temporary settings: {e}"),
+                        }
+                    }
+                    ServerRequest::ApplyGpuDefaults(detected_vendors) => {
+                        let res = SERVER_DATA_MANAGER
+                            .write()
+                            .apply_gpu_defaults(detected_vendors);
+
+                        match res {
+                            Ok((event, changes)) => {
+                                alvr_events::send_event(
+                                    LogSeverity::Info,
+                                    EventType::GpuDefaults(event),
+                                );
+                                crate::report_setting_changes(&changes);
+                            }
+                            Err(e) => error!("Failed to apply the GPU defaults: {e}"),
+                        }
+                        crate::report_pending_restart();
+                    }
+                    ServerRequest::ApplyDevicePreset(hostname) => {
+                        let res = SERVER_DATA_MANAGER.write().reapply_device_preset(hostname);
+
+                        match res {
+                            Ok((event, changes)) => {
+                                alvr_events::send_event(
+                                    LogSeverity::Info,
+                                    EventType::DevicePreset(event),
+                                );
+                                crate::report_setting_changes(&changes);
+                            }
+                            Err(e) => error!("Failed to apply the device defaults: {e}"),
+                        }
+                        crate::report_pending_restart();
                     }
                     ServerRequest::UpdateClientList {
                         hostname,
@@ -117,6 +186,8 @@ async fn http_api(
                     } => {
                         let mut data_manager = SERVER_DATA_MANAGER.write();
                         if matches!(action, ClientListAction::RemoveEntry) {
+                            crate::pairing::cancel(&hostname);
+
                             if let Some(entry) = data_manager.client_list().get(&hostname) {
                                 if entry.connection_state != ConnectionState::Disconnected {
                                     CLIENTS_TO_BE_REMOVED.lock().insert(hostname.clone());
@@ -128,49 +199,121 @@ async fn http_api(
                             }
                         }
 
-                        data_manager.update_client_list(hostname, action);
+                        if matches!(
+                            action,
+                            ClientListAction::SetConnectionState(ConnectionState::Disconnecting)
+                        ) {
+                            connection::set_disconnection_reason(
+                                &hostname,
+                                DisconnectionReason::ServerRequested,
+                            );
+                        }
+
+                        // The client is trusted once it entered the code
+                        let verify_client =
+                            matches!(action, ClientListAction::TrustAndMaybeAddIp(None))
+                                && data_manager
+                                    .settings()
+                                    .connection
+                                    .client_discovery
+                                    .as_option()
+                                    .is_some_and(|config| config.pin_verification)
+                                && data_manager
+                                    .client_list()
+                                    .get(&hostname)
+                                    .is_some_and(|entry| !entry.trusted);
+                        if verify_client {
+                            crate::pairing::start(hostname);
+                        } else {
+                            data_manager.update_client_list(hostname, action);
+                        }
                     }
                     ServerRequest::GetAudioDevices => {
                         if let Ok(list) = SERVER_DATA_MANAGER.read().get_audio_devices_list() {
-                            alvr_events::send_event(EventType::AudioDevices(list));
+                            alvr_events::send_event(
+                                LogSeverity::Info,
+                                EventType::AudioDevices(list),
+                            );
                         }
                     }
                     ServerRequest::CaptureFrame => unsafe { crate::CaptureFrame() },
                     ServerRequest::InsertIdr => unsafe { crate::RequestIDR() },
+                    ServerRequest::Recenter => {
+                        if !connection::request_recenter() {
+                            warn!("Cannot recenter, no client is connected");
+                        }
+                    }
+                    ServerRequest::SetStreamPaused(paused) => {
+                        if !connection::set_stream_paused(paused) {
+                            warn!("Cannot pause or resume the stream, no client is connected");
+                        }
+                    }
                     ServerRequest::StartRecording => {
                         crate::create_recording_file(SERVER_DATA_MANAGER.read().settings())
                     }
                     ServerRequest::StopRecording => *VIDEO_RECORDING_FILE.lock() = None,
+                    ServerRequest::StartTimingCapture => {
+                        crate::start_timing_capture(SERVER_DATA_MANAGER.read().settings())
+                    }
+                    ServerRequest::StopTimingCapture => *TIMING_CAPTURE.lock() = None,
                     ServerRequest::FirewallRules(action) => {
-                        if alvr_server_io::firewall_rules(action).is_ok() {
-                            info!("Setting firewall rules succeeded!");
-                        } else {
-                            error!("Setting firewall rules failed!");
-                        }
+                        let result = alvr_server_io::firewall_rules_with_result(action);
+                        alvr_events::send_event(
+                            alvr_events::installation_result_severity(&result),
+                            EventType::InstallationActionResult(result),
+                        );
+
+                        thread::spawn(crate::check_firewall);
+                    }
+                    ServerRequest::CheckFirewall => {
+                        thread::spawn(crate::check_firewall);
                     }
                     ServerRequest::RegisterAlvrDriver => {
-                        alvr_server_io::driver_registration(
+                        let result = alvr_server_io::driver_registration_with_result(
                             &[FILESYSTEM_LAYOUT.openvr_driver_root_dir.clone()],
                             true,
-                        )
-                        .ok();
+                        );
+                        alvr_events::send_event(
+                            alvr_events::installation_result_severity(&result),
+                            EventType::InstallationActionResult(result),
+                        );
 
                         if let Ok(list) = alvr_server_io::get_registered_drivers() {
-                            alvr_events::send_event(EventType::DriversList(list));
+                            alvr_events::send_event(
+                                LogSeverity::Info,
+                                EventType::DriversList(list),
+                            );
                         }
                     }
                     ServerRequest::UnregisterDriver(path) => {
-                        alvr_server_io::driver_registration(&[path], false).ok();
+                        let result =
+                            alvr_server_io::driver_registration_with_result(&[path], false);
+                        alvr_events::send_event(
+                            alvr_events::installation_result_severity(&result),
+                            EventType::InstallationActionResult(result),
+                        );
 
                         if let Ok(list) = alvr_server_io::get_registered_drivers() {
-                            alvr_events::send_event(EventType::DriversList(list));
+                            alvr_events::send_event(
+                                LogSeverity::Info,
+                                EventType::DriversList(list),
+                            );
                         }
                     }
                     ServerRequest::GetDriverList => {
                         if let Ok(list) = alvr_server_io::get_registered_drivers() {
-                            alvr_events::send_event(EventType::DriversList(list));
+                            alvr_events::send_event(
+                                LogSeverity::Info,
+                                EventType::DriversList(list),
+                            );
                         }
                     }
+                    ServerRequest::CheckSteamvr => {
+                        alvr_events::send_event(
+                            LogSeverity::Info,
+                            EventType::SteamvrRootDir(alvr_server_io::steamvr_root_dir().ok()),
+                        );
+                    }
                     ServerRequest::RestartSteamvr => {
                         thread::spawn(crate::restart_driver);
                     }
@@ -187,7 +330,9 @@ async fn http_api(
             }
         }
         "/api/events" => {
-            websocket(request, events_sender, |e| {
+            let (replayed_events, receiver) = EVENT_REPLAY.lock().subscribe(&events_sender);
+
+            websocket(request, replayed_events, receiver, |e| {
                 protocol::Message::Text(json::to_string(&e).unwrap())
             })
             .await?
@@ -209,7 +354,13 @@ async fn http_api(
                 sender.send(config.config_buffer.clone()).ok();
             }
 
-            let res = websocket(request, sender, protocol::Message::Binary).await?;
+            let res = websocket(
+                request,
+                vec![],
+                sender.subscribe(),
+                protocol::Message::Binary,
+            )
+            .await?;
 
             unsafe { crate::RequestIDR() };
 
@@ -219,6 +370,20 @@ async fn http_api(
             let buttons = from_request_body::<Vec<ButtonEvent>>(request).await?;
 
             for button in buttons {
+                if let ButtonValue::Vector2(value) = button.value {
+                    for (component, value) in [("x", value.x), ("y", value.y)] {
+                        let value = FfiButtonValue {
+                            type_: crate::FfiButtonType_BUTTON_TYPE_SCALAR,
+                            __bindgen_anon_1: crate::FfiButtonValue__bindgen_ty_1 { scalar: value },
+                        };
+                        let id = alvr_common::hash_string(&format!("{}/{component}", button.path));
+
+                        unsafe { crate::SetButton(id, value) };
+                    }
+
+                    continue;
+                }
+
                 let value = match button.value {
                     ButtonValue::Binary(value) => FfiButtonValue {
                         type_: crate::FfiButtonType_BUTTON_TYPE_BINARY,
@@ -231,6 +396,7 @@ async fn http_api(
                         type_: crate::FfiButtonType_BUTTON_TYPE_SCALAR,
                         __bindgen_anon_1: crate::FfiButtonValue__bindgen_ty_1 { scalar: value },
                     },
+                    ButtonValue::Vector2(_) => unreachable!(),
                 };
 
                 unsafe { crate::SetButton(alvr_common::hash_string(&button.path), value) };
@@ -249,6 +415,9 @@ async fn http_api(
                 .header(header::CONTENT_TYPE, "application/json")
                 .body(latency.to_string().into())?
         }
+        "/api/recent-logs" => Response::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(json::to_string(&alvr_common::get_recent_logs())?.into())?,
         "/api/ping" => reply(StatusCode::OK)?,
         other_uri => {
             if other_uri.contains("..") {
@@ -318,10 +487,21 @@ pub async fn web_server(events_sender: broadcast::Sender<Event>) -> Result<()> {
         }
     });
 
-    Ok(hyper::Server::bind(&SocketAddr::new(
+    let server = hyper::Server::try_bind(&SocketAddr::new(
         "0.0.0.0".parse().unwrap(),
         web_server_port,
     ))
-    .serve(service)
-    .await?)
+    .map_err(|e| {
+        let e = anyhow::Error::from(e);
+        sockets::report_port_conflict(
+            &e,
+            web_server_port,
+            SocketProtocol::Tcp,
+            PortUsage::WebServer,
+        );
+
+        e
+    })?;
+
+    Ok(server.serve(service).await?)
 }
@@ -0,0 +1,128 @@
+// Connection via USB. adb forwards the control and stream ports of this PC to the headset, then
+// the streamer connects to the client through localhost, like to a client with a manual IP. Only
+// TCP can be forwarded
+use alvr_common::{
+    anyhow::{bail, Result},
+    info, warn,
+};
+use std::{
+    process::Command,
+    time::{Duration, Instant},
+};
+
+const ADB_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+#[cfg(not(windows))]
+const ADB_EXECUTABLE: &str = "adb";
+#[cfg(windows)]
+const ADB_EXECUTABLE: &str = "adb.exe";
+
+fn adb(args: &[&str]) -> Result<String> {
+    let mut command = Command::new(ADB_EXECUTABLE);
+    command.args(args);
+    #[cfg(windows)]
+    std::os::windows::process::CommandExt::creation_flags(
+        &mut command,
+        crate::sockets::CREATE_NO_WINDOW,
+    );
+
+    let output = command.output()?;
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// Serials of the devices that allowed USB debugging. Unauthorized and offline devices are skipped
+fn parse_devices(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+            let serial = columns.next()?;
+
+            (columns.next() == Some("device")).then(|| serial.to_owned())
+        })
+        .collect()
+}
+
+#[derive(Default)]
+pub struct AdbForwarder {
+    // The ports of this PC can be forwarded to only one device
+    forwarded_serial: Option<String>,
+    next_check: Option<Instant>,
+    adb_unavailable: bool,
+}
+
+impl AdbForwarder {
+    // Returns whether a headset is connected via USB with the ports forwarded. adb is queried at
+    // most every few seconds. An unplugged headset loses its forwards
+    pub fn update(&mut self, ports: &[u16]) -> bool {
+        if self.next_check.is_some_and(|time| Instant::now() < time) {
+            return self.forwarded_serial.is_some();
+        }
+        self.next_check = Some(Instant::now() + ADB_CHECK_INTERVAL);
+
+        let serials = match adb(&["devices"]) {
+            Ok(output) => {
+                self.adb_unavailable = false;
+                parse_devices(&output)
+            }
+            Err(e) => {
+                if !self.adb_unavailable {
+                    warn!("Cannot run adb for the wired connection: {e}");
+                    self.adb_unavailable = true;
+                }
+                self.forwarded_serial = None;
+
+                return false;
+            }
+        };
+
+        if let Some(serial) = &self.forwarded_serial {
+            if serials.contains(serial) {
+                return true;
+            }
+
+            info!("Headset {serial} disconnected from USB");
+            self.forwarded_serial = None;
+        }
+
+        let Some(serial) = serials.into_iter().next() else {
+            return false;
+        };
+
+        for port in ports {
+            let port = format!("tcp:{port}");
+            if let Err(e) = adb(&["-s", &serial, "forward", &port, &port]) {
+                warn!("Failed to forward the port {port} to the headset {serial}: {e}");
+
+                return false;
+            }
+        }
+
+        info!("Headset {serial} connected via USB, ports forwarded");
+        self.forwarded_serial = Some(serial);
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_devices() {
+        let output = "List of devices attached\n\
+            1WMHH000000000\tdevice\n\
+            2G0YC000000000\tunauthorized\n\
+            emulator-5554\toffline\n\
+            \n";
+
+        assert_eq!(parse_devices(output), vec!["1WMHH000000000".to_owned()]);
+        assert!(parse_devices("List of devices attached\n\n").is_empty());
+    }
+}
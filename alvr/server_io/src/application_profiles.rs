@@ -0,0 +1,102 @@
+use crate::setting_changes;
+use alvr_common::{
+    anyhow::{bail, Result},
+    warn,
+};
+use alvr_packets::{PathSegment, PathValuePair, SettingChangeEffect};
+use alvr_session::{ApplicationProfile, ApplicationProfilesConfig, SessionConfig, Settings};
+use serde_json as json;
+
+fn parse_override_path(path: &str) -> Vec<PathSegment> {
+    alvr_packets::parse_path(&format!("session_settings.{}", path.trim()))
+}
+
+// Fails for settings that cannot be changed while streaming
+pub fn check_override(path: &str, value: &str) -> Result<PathValuePair> {
+    let path_segments = parse_override_path(path);
+
+    // A profile must not change which profile is active
+    if path_segments.get(1) == Some(&"application_profiles".into()) {
+        bail!("The application profiles cannot be overridden");
+    }
+
+    let schema = Settings::schema(alvr_session::session_settings_default());
+    match setting_changes::setting_path_effect(&path_segments, &schema) {
+        Some(SettingChangeEffect::Live) => (),
+        Some(_) => bail!("{path} needs a restart to take effect and cannot be overridden"),
+        None => bail!("{path} is not a setting"),
+    }
+
+    let value = match json::from_str(value) {
+        Ok(value) => value,
+        Err(e) => bail!("The value of {path} is not valid JSON: {e}"),
+    };
+
+    Ok(PathValuePair {
+        path: path_segments,
+        value,
+    })
+}
+
+// Invalid overrides are skipped
+pub fn profile_overrides(profile: &ApplicationProfile) -> Vec<PathValuePair> {
+    profile
+        .overrides
+        .iter()
+        .filter_map(|entry| match check_override(&entry.path, &entry.value) {
+            Ok(pair) => Some(pair),
+            Err(e) => {
+                warn!("Profile \"{}\": {e}", profile.name);
+                None
+            }
+        })
+        .collect()
+}
+
+// The manual profile takes precedence over the one of the running application
+pub fn select_profile<'a>(
+    config: &'a ApplicationProfilesConfig,
+    app_key: Option<&str>,
+) -> Option<(&'a ApplicationProfile, bool)> {
+    if let Some(name) = &config.manual_profile {
+        return config
+            .profiles
+            .iter()
+            .find(|profile| &profile.name == name)
+            .map(|profile| (profile, true));
+    }
+
+    let app_key = app_key?;
+    config
+        .profiles
+        .iter()
+        .find(|profile| profile.app_key == app_key)
+        .map(|profile| (profile, false))
+}
+
+// The session itself is not modified, so the overrides are reverted by converting it again
+pub fn settings_with_overrides(
+    session: &SessionConfig,
+    overrides: &[PathValuePair],
+) -> Result<Settings> {
+    let mut session_json = json::to_value(session)?;
+    for pair in overrides {
+        *crate::json_entry_mut(&mut session_json, &pair.path)? = pair.value.clone();
+    }
+
+    Ok(json::from_value::<SessionConfig>(session_json)?.to_settings())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_override_checks() {
+        assert!(check_override("video.bitrate.mode.ConstantMbps", "100").is_ok());
+        assert!(check_override("headset.controllers.enabled", "false").is_err());
+        assert!(check_override("video.not_a_setting", "1").is_err());
+        assert!(check_override("video.bitrate.mode.ConstantMbps", "fast").is_err());
+        assert!(check_override("application_profiles.enabled", "false").is_err());
+    }
+}
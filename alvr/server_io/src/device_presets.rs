@@ -0,0 +1,175 @@
+use crate::overlay::{self, Overlay};
+use alvr_common::glam::UVec2;
+use alvr_packets::PathValuePair;
+use serde_json as json;
+
+// Headsets that advertise a larger view use the high resolution generic preset
+const HIGH_RESOLUTION_VIEW_WIDTH: u32 = 2000;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DevicePreset {
+    Quest1,
+    Quest2,
+    Quest3,
+    QuestPro,
+    PicoNeo3,
+    Pico4,
+    GenericHighResolution,
+    Generic,
+}
+
+const PRESETS: [DevicePreset; 8] = [
+    DevicePreset::Quest1,
+    DevicePreset::Quest2,
+    DevicePreset::Quest3,
+    DevicePreset::QuestPro,
+    DevicePreset::PicoNeo3,
+    DevicePreset::Pico4,
+    DevicePreset::GenericHighResolution,
+    DevicePreset::Generic,
+];
+
+impl DevicePreset {
+    pub fn name(self) -> &'static str {
+        match self {
+            DevicePreset::Quest1 => "Quest 1",
+            DevicePreset::Quest2 => "Quest 2",
+            DevicePreset::Quest3 => "Quest 3",
+            DevicePreset::QuestPro => "Quest Pro",
+            DevicePreset::PicoNeo3 => "Pico Neo 3",
+            DevicePreset::Pico4 => "Pico 4",
+            DevicePreset::GenericHighResolution => "Generic (high resolution)",
+            DevicePreset::Generic => "Generic",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        PRESETS.into_iter().find(|preset| preset.name() == name)
+    }
+}
+
+// The model is the platform name reported by the client, like "Quest 2"
+pub fn select_device_preset(model: Option<&str>, default_view_resolution: UVec2) -> DevicePreset {
+    match model {
+        Some("Quest 1") => DevicePreset::Quest1,
+        Some("Quest 2") => DevicePreset::Quest2,
+        Some("Quest 3") => DevicePreset::Quest3,
+        Some("Quest Pro") => DevicePreset::QuestPro,
+        Some("Pico Neo 3") => DevicePreset::PicoNeo3,
+        Some("Pico 4") => DevicePreset::Pico4,
+        _ if default_view_resolution.x >= HIGH_RESOLUTION_VIEW_WIDTH => {
+            DevicePreset::GenericHighResolution
+        }
+        _ => DevicePreset::Generic,
+    }
+}
+
+// Paths are relative to session_settings. Every preset sets the same paths, so that applying the
+// preset of another headset replaces all values of the previous one
+fn preset_overlay(preset: DevicePreset) -> Overlay {
+    // Resolution scale, refresh rate, foveation center size. Converted from f32 like the session
+    // values, so that they compare equal
+    let (scale, fps, center_size): (f32, f32, [f32; 2]) = match preset {
+        DevicePreset::Quest1 => (1.0, 72.0, [0.45, 0.4]),
+        DevicePreset::Quest2 => (1.0, 90.0, [0.45, 0.4]),
+        // Pancake lenses are sharp further from the center
+        DevicePreset::Quest3 | DevicePreset::QuestPro => (0.9, 90.0, [0.5, 0.45]),
+        DevicePreset::PicoNeo3 => (1.0, 90.0, [0.45, 0.4]),
+        DevicePreset::Pico4 => (0.85, 90.0, [0.5, 0.45]),
+        DevicePreset::GenericHighResolution => (0.8, 72.0, [0.45, 0.4]),
+        DevicePreset::Generic => (1.0, 72.0, [0.45, 0.4]),
+    };
+
+    vec![
+        ("video.transcoding_view_resolution.variant", "Scale".into()),
+        ("video.transcoding_view_resolution.Scale", scale.into()),
+        (
+            "video.emulated_headset_view_resolution.variant",
+            "Scale".into(),
+        ),
+        ("video.emulated_headset_view_resolution.Scale", scale.into()),
+        ("video.preferred_fps", fps.into()),
+        ("video.foveated_encoding.enabled", true.into()),
+        (
+            "video.foveated_encoding.content.center_size_x",
+            center_size[0].into(),
+        ),
+        (
+            "video.foveated_encoding.content.center_size_y",
+            center_size[1].into(),
+        ),
+    ]
+}
+
+// Returns the values to set, the paths that were applied and the ones kept because the user
+// changed them. previous_preset is the one last applied for the same client
+pub fn device_preset_overlay(
+    session_settings: &json::Value,
+    preset: DevicePreset,
+    previous_preset: Option<DevicePreset>,
+) -> (Vec<PathValuePair>, Vec<String>, Vec<String>) {
+    overlay::overlay_values(
+        session_settings,
+        preset_overlay(preset),
+        &previous_preset.map(preset_overlay).unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alvr_session::SessionConfig;
+
+    #[test]
+    fn test_device_presets() {
+        let quest_3_resolution = UVec2::new(2064, 2208);
+        assert_eq!(
+            select_device_preset(Some("Quest 3"), quest_3_resolution),
+            DevicePreset::Quest3
+        );
+        assert_eq!(
+            select_device_preset(Some("Lynx Headset"), quest_3_resolution),
+            DevicePreset::GenericHighResolution
+        );
+        assert_eq!(
+            select_device_preset(None, UVec2::new(1440, 1600)),
+            DevicePreset::Generic
+        );
+
+        let mut session_settings =
+            json::to_value(SessionConfig::default()).unwrap()["session_settings"].clone();
+        session_settings["video"]["preferred_fps"] = 120.0.into();
+
+        let (descs, applied, skipped) =
+            device_preset_overlay(&session_settings, DevicePreset::Quest2, None);
+        assert_eq!(descs.len(), applied.len());
+        assert!(applied.contains(&"video.transcoding_view_resolution.variant".to_owned()));
+        assert_eq!(skipped, ["video.preferred_fps"]);
+
+        // Only the values of the previous preset are replaced, the ones that match another preset
+        // are user choices
+        session_settings["video"]["preferred_fps"] = 90.0_f32.into();
+        session_settings["video"]["transcoding_view_resolution"]["Scale"] = 0.85_f32.into();
+        let (_, applied, skipped) = device_preset_overlay(
+            &session_settings,
+            DevicePreset::Quest3,
+            Some(DevicePreset::Quest2),
+        );
+        assert!(!applied.contains(&"video.preferred_fps".to_owned()));
+        assert_eq!(skipped, ["video.transcoding_view_resolution.Scale"]);
+
+        session_settings["video"]["transcoding_view_resolution"]["Scale"] = 1.0_f32.into();
+        let (_, applied, skipped) = device_preset_overlay(
+            &session_settings,
+            DevicePreset::Quest3,
+            Some(DevicePreset::Quest2),
+        );
+        assert!(applied.contains(&"video.transcoding_view_resolution.Scale".to_owned()));
+        assert!(skipped.is_empty());
+
+        assert_eq!(
+            DevicePreset::from_name(DevicePreset::Pico4.name()),
+            Some(DevicePreset::Pico4)
+        );
+    }
+}
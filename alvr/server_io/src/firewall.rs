@@ -1,11 +1,22 @@
 use crate::openvrpaths;
-use alvr_packets::FirewallRulesAction;
+use alvr_common::{error, info};
+use alvr_packets::{
+    FirewallPortStatus, FirewallRuleState, FirewallRulesAction, FirewallStatus, InstallationAction,
+    InstallationActionResult,
+};
+use alvr_session::{ConnectionConfig, SocketProtocol};
 use std::{
     env, fs,
+    ops::RangeInclusive,
     path::{Path, PathBuf},
     process::Command,
 };
 
+const WINDOWS_RULE_NAME: &str = "SteamVR ALVR vrserver";
+
+// Port range opened by the shipped firewalld service and ufw application files
+const SHIPPED_RULES_PORTS: RangeInclusive<u16> = 9943..=9944;
+
 fn netsh_add_rule_command_string(rule_name: &str, program_path: &Path) -> String {
     format!(
         "netsh advfirewall firewall add rule name=\"{}\" dir=in program=\"{}\" action=allow",
@@ -18,8 +29,11 @@ fn netsh_delete_rule_command_string(rule_name: &str) -> String {
     format!("netsh advfirewall firewall delete rule name=\"{rule_name}\"")
 }
 
-// Errors:
-// 1: firewall rule is already set
+// Errors, as defined by alvr_fw_config.sh on Linux:
+// 1: invalid command
+// 2: invalid action, or the rules are already added or removed
+// 3: the ufw application file could not be copied
+// 99: no supported firewall is running
 // 126: pkexec request dismissed
 // other: command failed
 pub fn firewall_rules(action: FirewallRulesAction) -> Result<(), i32> {
@@ -49,7 +63,7 @@ pub fn firewall_rules(action: FirewallRulesAction) -> Result<(), i32> {
             format!(
                 "{}\n{}",
                 netsh_add_rule_command_string(
-                    "SteamVR ALVR vrserver",
+                    WINDOWS_RULE_NAME,
                     &openvrpaths::steamvr_root_dir()
                         .map_err(|_| -1)?
                         .join("bin")
@@ -57,7 +71,7 @@ pub fn firewall_rules(action: FirewallRulesAction) -> Result<(), i32> {
                         .join("vrserver.exe")
                 ),
                 netsh_add_rule_command_string(
-                    "SteamVR ALVR vrserver",
+                    WINDOWS_RULE_NAME,
                     &openvrpaths::steamvr_root_dir()
                         .map_err(|_| -1)?
                         .join("bin")
@@ -66,7 +80,7 @@ pub fn firewall_rules(action: FirewallRulesAction) -> Result<(), i32> {
                 ),
             )
         } else {
-            netsh_delete_rule_command_string("SteamVR ALVR vrserver")
+            netsh_delete_rule_command_string(WINDOWS_RULE_NAME)
         };
         fs::write(&script_path, firewall_rules_script_content).map_err(|_| -1)?;
 
@@ -83,3 +97,178 @@ pub fn firewall_rules(action: FirewallRulesAction) -> Result<(), i32> {
         Err(exit_status.code().unwrap())
     }
 }
+
+fn firewall_rules_error_message(action: FirewallRulesAction, code: i32) -> String {
+    match (code, action) {
+        (1, _) => "The firewall script was called with an invalid command".into(),
+        (2, FirewallRulesAction::Add) => "The firewall rules are already set".into(),
+        (2, FirewallRulesAction::Remove) => "The firewall rules are not set".into(),
+        (3, _) => "The ufw application file for ALVR could not be installed".into(),
+        (99, _) => "No running firewall was found, firewalld or ufw is required".into(),
+        (126, _) => "The request for administrator privileges was dismissed".into(),
+        (code, _) => format!("The firewall script failed with code {code}"),
+    }
+}
+
+// Same as firewall_rules(), with the outcome logged and reported in a form the dashboard can show
+pub fn firewall_rules_with_result(action: FirewallRulesAction) -> InstallationActionResult {
+    let installation_action = match action {
+        FirewallRulesAction::Add => InstallationAction::AddFirewallRules,
+        FirewallRulesAction::Remove => InstallationAction::RemoveFirewallRules,
+    };
+
+    let error = firewall_rules(action)
+        .err()
+        .map(|code| firewall_rules_error_message(action, code));
+    if let Some(e) = &error {
+        error!("Setting firewall rules failed: {e}");
+    } else {
+        info!("Setting firewall rules succeeded!");
+    }
+
+    InstallationActionResult {
+        action: installation_action,
+        error,
+    }
+}
+
+fn protocol_str(protocol: SocketProtocol) -> &'static str {
+    match protocol {
+        SocketProtocol::Udp => "udp",
+        SocketProtocol::Tcp => "tcp",
+    }
+}
+
+// Returns None if the command could not be run or failed for other reasons than a negative answer
+fn query_command(program: &str, args: &[&str]) -> Option<(bool, String)> {
+    let output = Command::new(program).args(args).output().ok()?;
+
+    match output.status.code() {
+        Some(0) => Some((true, String::from_utf8_lossy(&output.stdout).into())),
+        Some(1) => Some((false, String::from_utf8_lossy(&output.stdout).into())),
+        _ => None,
+    }
+}
+
+fn firewalld_port_state(port: u16, protocol: SocketProtocol) -> FirewallRuleState {
+    if SHIPPED_RULES_PORTS.contains(&port) {
+        match query_command("firewall-cmd", &["--query-service=alvr"]) {
+            Some((true, _)) => return FirewallRuleState::Allowed,
+            Some((false, _)) => (),
+            None => return FirewallRuleState::Unknown,
+        }
+    }
+
+    let port_arg = format!("--query-port={port}/{}", protocol_str(protocol));
+    match query_command("firewall-cmd", &[&port_arg]) {
+        Some((true, _)) => FirewallRuleState::Allowed,
+        Some((false, _)) => FirewallRuleState::Blocked,
+        None => FirewallRuleState::Unknown,
+    }
+}
+
+fn ufw_port_state(ufw_status: &str, port: u16, protocol: SocketProtocol) -> FirewallRuleState {
+    let port_rule = format!("{port}/{}", protocol_str(protocol));
+    let allowed = ufw_status.lines().any(|line| {
+        line.contains("ALLOW")
+            && (line.starts_with(&port_rule)
+                || (line.starts_with("alvr") && SHIPPED_RULES_PORTS.contains(&port)))
+    });
+
+    if allowed {
+        FirewallRuleState::Allowed
+    } else {
+        FirewallRuleState::Blocked
+    }
+}
+
+// Checks if inbound traffic is allowed on the discovery and stream ports. This never requests
+// elevated privileges, any port that cannot be verified is reported as unknown.
+pub fn check_firewall_rules(config: &ConnectionConfig) -> FirewallStatus {
+    let ports = &[
+        (config.discovery_port, SocketProtocol::Udp),
+        (config.stream_port, config.stream_protocol),
+    ];
+
+    let all_ports = |state: FirewallRuleState| -> Vec<FirewallPortStatus> {
+        ports
+            .iter()
+            .map(|&(port, protocol)| FirewallPortStatus {
+                port,
+                protocol,
+                state,
+            })
+            .collect()
+    };
+
+    if cfg!(target_os = "linux") {
+        // firewall-cmd --state returns a non zero exit code if firewalld is not running
+        if Command::new("firewall-cmd")
+            .arg("--state")
+            .output()
+            .map(|out| out.status.success())
+            .unwrap_or(false)
+        {
+            return FirewallStatus {
+                backend: "firewalld".into(),
+                ports: ports
+                    .iter()
+                    .map(|&(port, protocol)| FirewallPortStatus {
+                        port,
+                        protocol,
+                        state: firewalld_port_state(port, protocol),
+                    })
+                    .collect(),
+            };
+        }
+
+        if Command::new("ufw").arg("version").output().is_ok() {
+            // "ufw status" requires root
+            let ports = match query_command("ufw", &["status"]) {
+                Some((true, status)) if status.contains("Status: inactive") => {
+                    all_ports(FirewallRuleState::Allowed)
+                }
+                Some((true, status)) => ports
+                    .iter()
+                    .map(|&(port, protocol)| FirewallPortStatus {
+                        port,
+                        protocol,
+                        state: ufw_port_state(&status, port, protocol),
+                    })
+                    .collect(),
+                _ => all_ports(FirewallRuleState::Unknown),
+            };
+
+            return FirewallStatus {
+                backend: "ufw".into(),
+                ports,
+            };
+        }
+
+        FirewallStatus {
+            backend: "none".into(),
+            ports: all_ports(FirewallRuleState::Allowed),
+        }
+    } else if cfg!(windows) {
+        // Rules are set per program, so either all ports are allowed or none are
+        let name_arg = format!("name={WINDOWS_RULE_NAME}");
+        let state = match Command::new("netsh")
+            .args(["advfirewall", "firewall", "show", "rule", &name_arg])
+            .output()
+        {
+            Ok(output) if output.status.success() => FirewallRuleState::Allowed,
+            Ok(output) if output.status.code() == Some(1) => FirewallRuleState::Blocked,
+            _ => FirewallRuleState::Unknown,
+        };
+
+        FirewallStatus {
+            backend: "Windows Firewall".into(),
+            ports: all_ports(state),
+        }
+    } else {
+        FirewallStatus {
+            backend: "unknown".into(),
+            ports: all_ports(FirewallRuleState::Unknown),
+        }
+    }
+}
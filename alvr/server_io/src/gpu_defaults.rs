@@ -0,0 +1,115 @@
+use crate::overlay::{self, Overlay};
+use alvr_common::GpuVendor;
+use alvr_events::GpuDefaultsEvent;
+use alvr_packets::PathValuePair;
+use serde_json as json;
+
+const VENDORS: [GpuVendor; 3] = [GpuVendor::Nvidia, GpuVendor::Amd, GpuVendor::Intel];
+
+// Paths are relative to session_settings, choices are set through their variant
+fn vendor_overlay(vendor: GpuVendor) -> Overlay {
+    let mut entries = vec![];
+
+    match vendor {
+        GpuVendor::Nvidia => {
+            entries.extend([
+                ("video.preferred_codec.variant", "H264".into()),
+                (
+                    "video.encoder_config.rate_control_mode.variant",
+                    "Cbr".into(),
+                ),
+                (
+                    "video.encoder_config.nvenc.quality_preset.variant",
+                    "P1".into(),
+                ),
+                (
+                    "video.encoder_config.nvenc.tuning_preset.variant",
+                    "LowLatency".into(),
+                ),
+                (
+                    "video.encoder_config.nvenc.multi_pass.variant",
+                    "QuarterResolution".into(),
+                ),
+            ]);
+
+            // 10 bit encoding is not supported by the Linux encoder, and async reprojection stutters
+            // with the Nvidia driver
+            if cfg!(target_os = "linux") {
+                entries.extend([
+                    ("video.encoder_config.use_10bit", false.into()),
+                    ("patches.linux_async_reprojection", false.into()),
+                ]);
+            }
+        }
+        GpuVendor::Amd => {
+            // AMF produces visible artifacts with h264 at the usual streaming bitrates
+            entries.extend([
+                ("video.preferred_codec.variant", "Hevc".into()),
+                (
+                    "video.encoder_config.rate_control_mode.variant",
+                    "Cbr".into(),
+                ),
+                (
+                    "video.encoder_config.amf.quality_preset.variant",
+                    "Speed".into(),
+                ),
+                ("video.encoder_config.amf.enable_pre_analysis", false.into()),
+            ]);
+        }
+        GpuVendor::Intel => {
+            entries.extend([
+                ("video.preferred_codec.variant", "H264".into()),
+                (
+                    "video.encoder_config.rate_control_mode.variant",
+                    "Cbr".into(),
+                ),
+            ]);
+
+            // There is no Quick Sync encoder on Windows, the hardware encoders would only be probed
+            // to fail
+            if cfg!(windows) {
+                entries.push((
+                    "video.encoder_config.software.force_software_encoding",
+                    true.into(),
+                ));
+            }
+        }
+        GpuVendor::Unknown => (),
+    }
+
+    entries
+}
+
+// A discrete GPU is preferred over the integrated one of the CPU
+pub fn select_gpu_vendor(detected_vendors: &[GpuVendor]) -> Option<GpuVendor> {
+    VENDORS
+        .into_iter()
+        .find(|vendor| detected_vendors.contains(vendor))
+}
+
+// Returns the values to set, together with the event that describes them. previous_vendor is the
+// one of the overlay applied last, its values are replaced when the GPU changes
+pub fn gpu_defaults_overlay(
+    session_settings: &json::Value,
+    detected_vendors: Vec<GpuVendor>,
+    fresh_session: bool,
+    previous_vendor: Option<GpuVendor>,
+) -> (Vec<PathValuePair>, GpuDefaultsEvent) {
+    let selected_vendor = select_gpu_vendor(&detected_vendors);
+
+    let (descs, applied, skipped) = overlay::overlay_values(
+        session_settings,
+        selected_vendor.map(vendor_overlay).unwrap_or_default(),
+        &previous_vendor.map(vendor_overlay).unwrap_or_default(),
+    );
+
+    let event = GpuDefaultsEvent {
+        detected_vendors,
+        selected_vendor,
+        fresh_session,
+        applied,
+        skipped,
+    };
+
+    (descs, event)
+}
@@ -1,25 +1,46 @@
+mod application_profiles;
+mod device_presets;
 mod firewall;
+mod gpu_defaults;
 mod openvr_drivers;
 mod openvrpaths;
+mod overlay;
+mod session_file;
+mod setting_changes;
+mod settings_validation;
 
+pub use application_profiles::*;
+pub use device_presets::*;
 pub use firewall::*;
+pub use gpu_defaults::*;
 pub use openvr_drivers::*;
 pub use openvrpaths::*;
+pub use setting_changes::*;
+pub use settings_validation::*;
 
 use alvr_common::{
     anyhow::{bail, Result},
-    error, info, ConnectionState,
+    error,
+    glam::UVec2,
+    info, warn, ConnectionState, GpuVendor, LogSeverity,
+};
+use alvr_events::{DevicePresetEvent, EventType, GpuDefaultsEvent};
+use alvr_packets::{
+    AudioDevicesList, ClientListAction, PathSegment, PathValuePair, SettingChange,
+    SettingChangeEffect, SettingValidationError,
+};
+use alvr_session::{
+    ClientConnectionConfig, InvalidSettingsAction, MigrationReport, SessionConfig, Settings,
+    SESSION_SCHEMA_VERSION,
 };
-use alvr_events::EventType;
-use alvr_packets::{AudioDevicesList, ClientListAction, PathSegment, PathValuePair};
-use alvr_session::{ClientConnectionConfig, SessionConfig, Settings};
-use cpal::traits::{DeviceTrait, HostTrait};
 use serde_json as json;
+use session_file::SessionFile;
 use std::{
     collections::{hash_map::Entry, HashMap},
     fs,
     ops::{Deref, DerefMut},
-    path::{Path, PathBuf},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 fn save_session(session: &SessionConfig, path: &Path) -> Result<()> {
@@ -28,11 +49,69 @@ fn save_session(session: &SessionConfig, path: &Path) -> Result<()> {
     Ok(())
 }
 
+fn json_entry_mut<'a>(
+    json: &'a mut json::Value,
+    path: &[PathSegment],
+) -> Result<&'a mut json::Value> {
+    let mut json_ref = json;
+    for segment in path {
+        json_ref = match segment {
+            PathSegment::Name(name) => {
+                if let Some(name) = json_ref.get_mut(name) {
+                    name
+                } else {
+                    bail!("From path {:?}: segment \"{name}\" not found", path);
+                }
+            }
+            PathSegment::Index(index) => {
+                if let Some(index) = json_ref.get_mut(index) {
+                    index
+                } else {
+                    bail!("From path {:?}: segment [{index}] not found", path);
+                }
+            }
+        };
+    }
+
+    Ok(json_ref)
+}
+
+// Every entry of the errors takes its value from source_json, the other values are kept
+fn revert_invalid_entries(
+    session_json: &mut json::Value,
+    source_json: &json::Value,
+    errors: &[SettingValidationError],
+) {
+    for entry in errors.iter().flat_map(|error| &error.entries) {
+        let source_value = entry
+            .path
+            .iter()
+            .try_fold(source_json, |json, segment| match segment {
+                PathSegment::Name(name) => json.get(name),
+                PathSegment::Index(index) => json.get(index),
+            });
+
+        if let (Some(value), Ok(target)) = (source_value, json_entry_mut(session_json, &entry.path))
+        {
+            *target = value.clone();
+        }
+    }
+}
+
+fn unix_time_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
 // SessionConfig wrapper that saves session.json on destruction.
 pub struct SessionLock<'a> {
     session_desc: &'a mut SessionConfig,
-    session_path: &'a Path,
+    session_file: &'a mut SessionFile,
     settings: &'a mut Settings,
+    profile_overrides: &'a [PathValuePair],
+    temporary_overrides: &'a [PathValuePair],
 }
 
 impl Deref for SessionLock<'_> {
@@ -50,10 +129,59 @@ impl DerefMut for SessionLock<'_> {
 
 impl Drop for SessionLock<'_> {
     fn drop(&mut self) {
-        save_session(self.session_desc, self.session_path).unwrap();
-        *self.settings = self.session_desc.to_settings();
-        alvr_events::send_event(EventType::Session(Box::new(self.session_desc.clone())));
+        self.session_file.save(self.session_desc).unwrap();
+        *self.settings = effective_settings(
+            self.session_desc,
+            self.profile_overrides,
+            self.temporary_overrides,
+        );
+        alvr_events::send_event(
+            LogSeverity::Info,
+            EventType::Session(Box::new(self.session_desc.clone())),
+        );
+    }
+}
+
+// The temporary overrides are applied last
+fn effective_settings(
+    session: &SessionConfig,
+    profile_overrides: &[PathValuePair],
+    temporary_overrides: &[PathValuePair],
+) -> Settings {
+    if profile_overrides.is_empty() && temporary_overrides.is_empty() {
+        return session.to_settings();
+    }
+
+    settings_with_overrides(session, &[profile_overrides, temporary_overrides].concat())
+        .unwrap_or_else(|e| {
+            warn!("Failed to apply the setting overrides: {e}");
+            session.to_settings()
+        })
+}
+
+// The paths set by only one of the lists or to different values. Only settings applied in real
+// time can be overridden
+fn override_changes(previous: &[PathValuePair], overrides: &[PathValuePair]) -> Vec<SettingChange> {
+    let is_kept = |pair: &PathValuePair| {
+        previous
+            .iter()
+            .chain(overrides)
+            .filter(|other| other.path == pair.path && other.value == pair.value)
+            .count()
+            == 2
+    };
+
+    let mut changes = Vec::<SettingChange>::new();
+    for pair in previous.iter().chain(overrides) {
+        if !is_kept(pair) && !changes.iter().any(|change| change.path == pair.path) {
+            changes.push(SettingChange {
+                path: pair.path.clone(),
+                effect: SettingChangeEffect::Live,
+            });
+        }
     }
+
+    changes
 }
 
 // Correct usage:
@@ -64,67 +192,117 @@ impl Drop for SessionLock<'_> {
 pub struct ServerDataManager {
     session: SessionConfig,
     settings: Settings,
-    session_path: PathBuf,
+    session_file: SessionFile,
+    migration_report: Option<MigrationReport>,
+    // session.json did not exist, until the GPU defaults are applied
+    new_session: bool,
+    // Of the session loaded at startup, then of the last change
+    validation_errors: Vec<SettingValidationError>,
+    // Of the active application profile, applied on top of the session
+    profile_overrides: Vec<PathValuePair>,
+    // Set while streaming without changing the session, for example by the live tuning panel
+    temporary_overrides: Vec<PathValuePair>,
 }
 
 impl ServerDataManager {
     pub fn new(session_path: &Path) -> Self {
         let config_dir = session_path.parent().unwrap();
         fs::create_dir_all(config_dir).ok();
-        let session_desc = Self::load_session(session_path, config_dir);
+        let new_session = !session_path.exists();
+        let (mut session_desc, migration_report) = Self::load_session(session_path);
+
+        // There is no previous value to go back to, the invalid values are reset to the defaults.
+        // Otherwise they would be reported again by every later change
+        let mut validation_errors = validate_settings(&session_desc.to_settings());
+        if !validation_errors.is_empty() {
+            let mut session_json = json::to_value(&session_desc).unwrap();
+            revert_invalid_entries(
+                &mut session_json,
+                &json::to_value(SessionConfig::default()).unwrap(),
+                &validation_errors,
+            );
+
+            if let Ok(session) = json::from_value::<SessionConfig>(session_json) {
+                for error in &validation_errors {
+                    warn!("Reset invalid settings to their default: {}", error.message);
+                }
+
+                session_desc = session;
+                save_session(&session_desc, session_path).ok();
+                validation_errors = validate_settings(&session_desc.to_settings());
+            }
+        }
 
         Self {
             session: session_desc.clone(),
             settings: session_desc.to_settings(),
-            session_path: session_path.to_owned(),
+            session_file: SessionFile::new(session_path),
+            migration_report,
+            new_session,
+            validation_errors,
+            profile_overrides: vec![],
+            temporary_overrides: vec![],
         }
     }
 
-    fn load_session(session_path: &Path, config_dir: &Path) -> SessionConfig {
+    // The original file is backed up before it is reset or rewritten in the new schema
+    fn load_session(session_path: &Path) -> (SessionConfig, Option<MigrationReport>) {
         let session_string = fs::read_to_string(session_path).unwrap_or_default();
 
         if session_string.is_empty() {
-            return SessionConfig::default();
+            return (SessionConfig::default(), None);
         }
 
-        let session_json = json::from_str::<json::Value>(&session_string)
-            .unwrap_or_else(|e| {
+        let backup_path = session_path.with_extension("json.bak");
+
+        let session_json = match json::from_str::<json::Value>(&session_string) {
+            Ok(session_json) => session_json,
+            Err(e) => {
                 error!(
                     "{} {} {}\n{}",
                     "Failed to load session.json.",
-                    "Its contents will be reset and the original file content stored as session_invalid.json.",
+                    "Its contents will be reset and the original file content stored as session.json.bak.",
                     "See error message below for details:",
                     e
                 );
-                json::Value::Null
-            });
+                fs::write(&backup_path, &session_string).ok();
 
-        if session_json.is_null() {
-            fs::write(config_dir.join("session_invalid.json"), &session_string).ok();
-            return SessionConfig::default();
-        }
+                let report = MigrationReport {
+                    from_version: SESSION_SCHEMA_VERSION,
+                    to_version: SESSION_SCHEMA_VERSION,
+                    error: Some(format!("session.json is not valid JSON: {e}")),
+                    ..Default::default()
+                };
 
-        json::from_value(session_json.clone()).unwrap_or_else(|_| {
-            fs::write(config_dir.join("session_old.json"), &session_string).ok();
-            let mut session_desc = SessionConfig::default();
-            match session_desc.merge_from_json(&session_json) {
-                Ok(_) => info!(
-                    "{} {}",
-                    "Session extrapolated successfully.",
-                    "Old session.json is stored as session_old.json"
-                ),
-                Err(e) => error!(
-                    "{} {} {}",
-                    "Error while extrapolating session.",
-                    "Old session.json is stored as session_old.json.",
-                    e
-                ),
+                return (SessionConfig::default(), Some(report));
             }
-            // not essential, but useful to avoid duplicated errors
-            save_session(&session_desc, session_path).ok();
+        };
 
-            session_desc
-        })
+        let (session_desc, report) = alvr_session::migrate_session_json(session_json);
+        if report.is_empty() {
+            return (session_desc, None);
+        }
+
+        fs::write(&backup_path, &session_string).ok();
+        info!(
+            "Session migrated from schema version {} to {}. Old session.json is stored as session.json.bak",
+            report.from_version, report.to_version
+        );
+
+        // not essential, but useful to avoid migrating again
+        save_session(&session_desc, session_path).ok();
+
+        (session_desc, Some(report))
+    }
+
+    // The session is loaded before the logger is set up, the report is sent as an event once
+    // events can be delivered
+    pub fn take_migration_report(&mut self) -> Option<MigrationReport> {
+        self.migration_report.take()
+    }
+
+    pub fn validation_errors(&self) -> &[SettingValidationError] {
+        &self.validation_errors
     }
 
     // prefer settings()
@@ -135,8 +313,10 @@ impl ServerDataManager {
     pub fn session_mut(&mut self) -> SessionLock {
         SessionLock {
             session_desc: &mut self.session,
-            session_path: &self.session_path,
+            session_file: &mut self.session_file,
             settings: &mut self.settings,
+            profile_overrides: &self.profile_overrides,
+            temporary_overrides: &self.temporary_overrides,
         }
     }
 
@@ -145,40 +325,285 @@ impl ServerDataManager {
     }
 
     // Note: "value" can be any session subtree, in json format.
-    pub fn set_values(&mut self, descs: Vec<PathValuePair>) -> Result<()> {
+    // Returns the list of settings that changed value. The validation errors are sent as an event
+    // every time, so that an empty list clears the errors shown by the dashboard.
+    pub fn set_values(&mut self, descs: Vec<PathValuePair>) -> Result<Vec<SettingChange>> {
         let mut session_json = serde_json::to_value(self.session.clone()).unwrap();
+        let old_session_json = session_json.clone();
+        let old_session_settings_json = session_json["session_settings"].clone();
 
         for desc in descs {
-            let mut session_ref = &mut session_json;
-            for segment in &desc.path {
-                session_ref = match segment {
-                    PathSegment::Name(name) => {
-                        if let Some(name) = session_ref.get_mut(name) {
-                            name
-                        } else {
-                            bail!("From path {:?}: segment \"{name}\" not found", desc.path);
-                        }
-                    }
-                    PathSegment::Index(index) => {
-                        if let Some(index) = session_ref.get_mut(index) {
-                            index
-                        } else {
-                            bail!("From path {:?}: segment [{index}] not found", desc.path);
-                        }
-                    }
-                };
-            }
-            *session_ref = desc.value.clone();
+            *json_entry_mut(&mut session_json, &desc.path)? = desc.value;
         }
 
         // session_json has been updated
-        self.session = serde_json::from_value(session_json)?;
-        self.settings = self.session.to_settings();
+        let mut session = serde_json::from_value::<SessionConfig>(session_json.clone())?;
+        let previous_errors = validate_settings(&self.session.to_settings());
+        self.validation_errors = validate_settings(&session.to_settings());
+        alvr_events::send_event(
+            alvr_events::settings_validation_severity(&self.validation_errors),
+            EventType::SettingsValidation(self.validation_errors.clone()),
+        );
+
+        let new_errors = new_validation_errors(&self.validation_errors, &previous_errors);
+        if !new_errors.is_empty() {
+            match self.settings.invalid_settings_action {
+                InvalidSettingsAction::RejectChange => {
+                    // The dashboard already shows the rejected values, they are replaced by the
+                    // current ones
+                    alvr_events::send_event(
+                        LogSeverity::Info,
+                        EventType::Session(Box::new(self.session.clone())),
+                    );
+
+                    let messages = new_errors
+                        .iter()
+                        .map(|error| error.message.as_str())
+                        .collect::<Vec<_>>();
+                    bail!("Invalid settings: {}", messages.join(", "));
+                }
+                InvalidSettingsAction::RevertInvalidValues => {
+                    revert_invalid_entries(&mut session_json, &old_session_json, &new_errors);
+                    session = serde_json::from_value(session_json)?;
+                }
+            }
+        }
+
+        self.session = session;
+
+        let changes = get_setting_changes(
+            &old_session_settings_json,
+            &json::to_value(&self.session.session_settings).unwrap(),
+            &Settings::schema(alvr_session::session_settings_default()),
+        );
+
+        // Manual changes win over the active profile until it is deactivated, and over the
+        // temporary values
+        let is_changed = |pair: &PathValuePair| {
+            changes.iter().any(|change| {
+                change.path.starts_with(&pair.path) || pair.path.starts_with(&change.path)
+            })
+        };
+        self.profile_overrides.retain(|pair| !is_changed(pair));
+        self.temporary_overrides.retain(|pair| !is_changed(pair));
+        self.settings = effective_settings(
+            &self.session,
+            &self.profile_overrides,
+            &self.temporary_overrides,
+        );
+
+        self.session_file.save(&self.session).unwrap();
+        alvr_events::send_event(
+            LogSeverity::Info,
+            EventType::Session(Box::new(self.session.clone())),
+        );
+
+        Ok(changes)
+    }
+
+    // Applies a change of session.json made by hand or by another program. Returns None if the file
+    // was not modified. If the new content is rejected, the current session is kept and replaces
+    // the file at the next save, after the rejected file is backed up
+    pub fn reload_if_modified(&mut self) -> Option<Result<Vec<SettingChange>>> {
+        let content = self.session_file.read_external_change()?;
+        info!("session.json was modified by another program, reloading it");
+
+        let parse = || -> Result<SessionConfig> {
+            let (session, report) = alvr_session::migrate_session_json(json::from_str(&content)?);
+            if let Some(e) = report.error {
+                bail!("{e}");
+            }
+
+            Ok(session)
+        };
+        let session = match parse() {
+            Ok(session) => session,
+            Err(e) => return Some(Err(e)),
+        };
+
+        // Accepted first, so that the save of the reloaded session does not back it up
+        let previous_content = self.session_file.accept_external_change(content);
+
+        // An empty path replaces the whole session
+        let res = self.set_values(vec![PathValuePair {
+            path: vec![],
+            value: json::to_value(session).unwrap(),
+        }]);
+        if res.is_err() {
+            self.session_file.accept_external_change(previous_content);
+        }
+
+        Some(res)
+    }
+
+    // Replaces the overrides of the previous profile. An empty list reverts to the session values.
+    // Returns the overridden settings of both profiles
+    pub fn set_profile_overrides(&mut self, overrides: Vec<PathValuePair>) -> Vec<SettingChange> {
+        let changes = override_changes(&self.profile_overrides, &overrides);
+
+        self.profile_overrides = overrides;
+        self.settings = effective_settings(
+            &self.session,
+            &self.profile_overrides,
+            &self.temporary_overrides,
+        );
+
+        changes
+    }
+
+    // Applies the values on top of the session without saving them. They replace the previous
+    // temporary values of the same paths. Only the settings that can change while streaming are
+    // accepted. Returns the settings that changed value
+    pub fn set_temporary_values(
+        &mut self,
+        descs: Vec<PathValuePair>,
+    ) -> Result<Vec<SettingChange>> {
+        let schema = Settings::schema(alvr_session::session_settings_default());
+        for desc in &descs {
+            if setting_path_effect(&desc.path, &schema) != Some(SettingChangeEffect::Live) {
+                bail!(
+                    "{} cannot be changed while streaming",
+                    alvr_packets::path_to_string(&desc.path)
+                );
+            }
+        }
+
+        let mut overrides = self.temporary_overrides.clone();
+        overrides.retain(|pair| !descs.iter().any(|desc| desc.path == pair.path));
+        overrides.extend(descs);
+
+        let settings = settings_with_overrides(
+            &self.session,
+            &[self.profile_overrides.as_slice(), &overrides].concat(),
+        )?;
+        let errors = new_validation_errors(
+            &validate_settings(&settings),
+            &validate_settings(&self.settings),
+        );
+        if !errors.is_empty() {
+            let messages = errors
+                .iter()
+                .map(|error| error.message.as_str())
+                .collect::<Vec<_>>();
+            bail!("Invalid settings: {}", messages.join(", "));
+        }
+
+        let changes = override_changes(&self.temporary_overrides, &overrides);
+        self.temporary_overrides = overrides;
+        self.settings = settings;
+
+        alvr_events::send_event(
+            LogSeverity::Info,
+            EventType::Session(Box::new(self.session.clone())),
+        );
+
+        Ok(changes)
+    }
 
-        save_session(&self.session, &self.session_path).unwrap();
-        alvr_events::send_event(EventType::Session(Box::new(self.session.clone())));
+    // Reverts to the session values, when the stream ends
+    pub fn clear_temporary_values(&mut self) {
+        if !self.temporary_overrides.is_empty() {
+            self.temporary_overrides.clear();
+            self.settings = effective_settings(&self.session, &self.profile_overrides, &[]);
+        }
+    }
 
-        Ok(())
+    pub fn is_new_session(&self) -> bool {
+        self.new_session
+    }
+
+    // Used for new sessions and for the "reset to recommended" action. The settings changed by the
+    // user are kept and listed in the event
+    pub fn apply_gpu_defaults(
+        &mut self,
+        detected_vendors: Vec<GpuVendor>,
+    ) -> Result<(GpuDefaultsEvent, Vec<SettingChange>)> {
+        let (descs, event) = gpu_defaults_overlay(
+            &json::to_value(&self.session.session_settings)?,
+            detected_vendors,
+            self.new_session,
+            self.session.gpu_defaults_vendor,
+        );
+        self.new_session = false;
+
+        let changes = if descs.is_empty() {
+            vec![]
+        } else {
+            self.set_values(descs)?
+        };
+
+        if event.selected_vendor.is_some()
+            && self.session.gpu_defaults_vendor != event.selected_vendor
+        {
+            self.session.gpu_defaults_vendor = event.selected_vendor;
+            self.session_file.save(&self.session).unwrap();
+        }
+
+        Ok((event, changes))
+    }
+
+    // Used when a client connects for the first time, and for the "re-apply recommended defaults"
+    // action. Like for the GPU defaults, the settings changed by the user are kept
+    pub fn apply_device_preset(
+        &mut self,
+        hostname: String,
+        device_model: Option<&str>,
+        default_view_resolution: UVec2,
+        first_connection: bool,
+    ) -> Result<(DevicePresetEvent, Vec<SettingChange>)> {
+        let preset = select_device_preset(device_model, default_view_resolution);
+        let previous_preset = self
+            .client_list()
+            .get(&hostname)
+            .and_then(|entry| entry.device_preset.as_deref())
+            .and_then(DevicePreset::from_name);
+        let (descs, applied, skipped) = device_preset_overlay(
+            &json::to_value(&self.session.session_settings)?,
+            preset,
+            previous_preset,
+        );
+
+        let changes = if descs.is_empty() {
+            vec![]
+        } else {
+            self.set_values(descs)?
+        };
+
+        self.update_client_list(
+            hostname.clone(),
+            ClientListAction::SetDevicePreset(preset.name().into()),
+        );
+
+        let event = DevicePresetEvent {
+            hostname,
+            preset: preset.name().into(),
+            first_connection,
+            applied,
+            skipped,
+        };
+
+        Ok((event, changes))
+    }
+
+    // Uses what the client advertised when it last connected
+    pub fn reapply_device_preset(
+        &mut self,
+        hostname: String,
+    ) -> Result<(DevicePresetEvent, Vec<SettingChange>)> {
+        let Some(info) = self
+            .client_list()
+            .get(&hostname)
+            .and_then(|entry| entry.headset_info.clone())
+        else {
+            bail!("{hostname} has not connected yet");
+        };
+
+        let device_model = info
+            .extra_capabilities
+            .get("device_model")
+            .and_then(|value| value.as_str());
+
+        self.apply_device_preset(hostname, device_model, info.default_view_resolution, false)
     }
 
     pub fn client_list(&self) -> &HashMap<String, ClientConnectionConfig> {
@@ -204,6 +629,11 @@ impl ServerDataManager {
                         trusted,
                         connection_state: ConnectionState::Disconnected,
                         cabled: false,
+                        first_seen: unix_time_secs(),
+                        last_seen: unix_time_secs(),
+                        version: None,
+                        headset_info: None,
+                        device_preset: None,
                     };
                     new_entry.insert(client_connection_desc);
 
@@ -217,9 +647,13 @@ impl ServerDataManager {
                     updated = true;
                 }
             }
-            ClientListAction::Trust => {
+            ClientListAction::TrustAndMaybeAddIp(maybe_ip) => {
                 if let Entry::Occupied(mut entry) = maybe_client_entry {
-                    entry.get_mut().trusted = true;
+                    let client_connection_ref = entry.get_mut();
+                    client_connection_ref.trusted = true;
+                    if let Some(ip) = maybe_ip {
+                        client_connection_ref.manual_ips.insert(ip);
+                    }
 
                     updated = true;
                 }
@@ -241,6 +675,9 @@ impl ServerDataManager {
             ClientListAction::UpdateCurrentIp(current_ip) => {
                 if let Entry::Occupied(mut entry) = maybe_client_entry {
                     if entry.get().current_ip != current_ip {
+                        if current_ip.is_some() {
+                            entry.get_mut().last_seen = unix_time_secs();
+                        }
                         entry.get_mut().current_ip = current_ip;
 
                         updated = true;
@@ -256,13 +693,41 @@ impl ServerDataManager {
                     }
                 }
             }
+            ClientListAction::SetVersion(version) => {
+                if let Entry::Occupied(mut entry) = maybe_client_entry {
+                    if entry.get().version.as_ref() != Some(&version) {
+                        entry.get_mut().version = Some(version);
+
+                        updated = true;
+                    }
+                }
+            }
+            // The capture time is set here, like the other timestamps of the entry
+            ClientListAction::SetHeadsetInfo(mut info) => {
+                if let Entry::Occupied(mut entry) = maybe_client_entry {
+                    info.captured_at = unix_time_secs();
+                    entry.get_mut().headset_info = Some(info);
+
+                    updated = true;
+                }
+            }
+            ClientListAction::SetDevicePreset(preset) => {
+                if let Entry::Occupied(mut entry) = maybe_client_entry {
+                    entry.get_mut().device_preset = Some(preset);
+
+                    updated = true;
+                }
+            }
         }
 
         if updated {
             self.session.client_connections = client_connections;
 
-            save_session(&self.session, &self.session_path).unwrap();
-            alvr_events::send_event(EventType::Session(Box::new(self.session.clone())));
+            self.session_file.save(&self.session).unwrap();
+            alvr_events::send_event(
+                LogSeverity::Info,
+                EventType::Session(Box::new(self.session.clone())),
+            );
         }
     }
 
@@ -289,26 +754,8 @@ impl ServerDataManager {
         }
     }
 
-    #[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
     pub fn get_audio_devices_list(&self) -> Result<AudioDevicesList> {
-        #[cfg(target_os = "linux")]
-        let host = match self.session.to_settings().audio.linux_backend {
-            alvr_session::LinuxAudioBackend::Alsa => cpal::host_from_id(cpal::HostId::Alsa)?,
-            alvr_session::LinuxAudioBackend::Jack => cpal::host_from_id(cpal::HostId::Jack)?,
-        };
-        #[cfg(not(target_os = "linux"))]
-        let host = cpal::default_host();
-
-        let output = host
-            .output_devices()?
-            .filter_map(|d| d.name().ok())
-            .collect::<Vec<_>>();
-        let input = host
-            .input_devices()?
-            .filter_map(|d| d.name().ok())
-            .collect::<Vec<_>>();
-
-        Ok(AudioDevicesList { output, input })
+        alvr_audio::list_devices(Some(self.session.to_settings().audio.linux_backend))
     }
 }
 
@@ -1,8 +1,9 @@
 use crate::openvrpaths;
 use alvr_common::{
     anyhow::{bail, Result},
-    ToAny,
+    error, ToAny,
 };
+use alvr_packets::{InstallationAction, InstallationActionResult};
 use serde_json as json;
 use std::{
     collections::{HashMap, HashSet},
@@ -41,6 +42,27 @@ pub fn driver_registration(driver_paths: &[PathBuf], register: bool) -> Result<(
     openvrpaths::save_openvr_paths_json(&openvr_paths_json)
 }
 
+pub fn driver_registration_with_result(
+    driver_paths: &[PathBuf],
+    register: bool,
+) -> InstallationActionResult {
+    let error = driver_registration(driver_paths, register)
+        .err()
+        .map(|e| e.to_string());
+    if let Some(e) = &error {
+        error!("Driver registration failed: {e}");
+    }
+
+    InstallationActionResult {
+        action: if register {
+            InstallationAction::RegisterDriver
+        } else {
+            InstallationAction::UnregisterDriver
+        },
+        error,
+    }
+}
+
 pub fn get_driver_dir_from_registered() -> Result<PathBuf> {
     for dir in get_registered_drivers()? {
         let maybe_driver_name = || -> Result<_> {
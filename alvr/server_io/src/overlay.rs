@@ -0,0 +1,62 @@
+use alvr_packets::PathValuePair;
+use alvr_session::SessionConfig;
+use serde_json as json;
+
+// Recommended values, like the GPU defaults and the device presets. Paths are relative to
+// session_settings
+pub(crate) type Overlay = Vec<(&'static str, json::Value)>;
+
+fn json_pointer(path: &str) -> String {
+    format!("/{}", path.replace('.', "/"))
+}
+
+// A value is considered changed by the user unless it is the generic default or the value set by
+// the previous overlay. The values of the other overlays do not count, they are common choices
+fn changed_by_user(
+    path: &str,
+    value: &json::Value,
+    default_settings: &json::Value,
+    previous_overlay: &Overlay,
+) -> bool {
+    default_settings.pointer(&json_pointer(path)) != Some(value)
+        && previous_overlay
+            .iter()
+            .all(|(previous_path, previous)| *previous_path != path || previous != value)
+}
+
+// Returns the values to set, the paths that were applied and the ones kept because the user
+// changed them
+pub(crate) fn overlay_values(
+    session_settings: &json::Value,
+    overlay: Overlay,
+    previous_overlay: &Overlay,
+) -> (Vec<PathValuePair>, Vec<String>, Vec<String>) {
+    let default_settings = json::to_value(SessionConfig::default())
+        .map(|session| session["session_settings"].clone())
+        .unwrap_or_default();
+
+    let mut descs = vec![];
+    let mut applied = vec![];
+    let mut skipped = vec![];
+    for (path, recommended) in overlay {
+        let Some(value) = session_settings.pointer(&json_pointer(path)) else {
+            continue;
+        };
+
+        if *value == recommended {
+            continue;
+        }
+
+        if changed_by_user(path, value, &default_settings, previous_overlay) {
+            skipped.push(path.to_owned());
+        } else {
+            descs.push(PathValuePair {
+                path: alvr_packets::parse_path(&format!("session_settings.{path}")),
+                value: recommended,
+            });
+            applied.push(path.to_owned());
+        }
+    }
+
+    (descs, applied, skipped)
+}
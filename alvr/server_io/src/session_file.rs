@@ -0,0 +1,79 @@
+use alvr_common::{anyhow::Result, warn};
+use alvr_session::SessionConfig;
+use serde_json as json;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+// Keeps the content of session.json as last written or read, to tell apart the changes made by
+// other programs
+pub struct SessionFile {
+    path: PathBuf,
+    content: String,
+    modified: Option<SystemTime>,
+}
+
+impl SessionFile {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_owned(),
+            content: fs::read_to_string(path).unwrap_or_default(),
+            modified: Self::modified_time(path),
+        }
+    }
+
+    fn modified_time(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    // Returns the new content if the file was modified since it was last written or read. The
+    // content is not considered read until it is accepted, so that the next save backs it up
+    pub fn read_external_change(&mut self) -> Option<String> {
+        let modified = Self::modified_time(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+
+        let content = fs::read_to_string(&self.path).ok()?;
+        if content == self.content {
+            return None;
+        }
+
+        Some(content)
+    }
+
+    // Returns the previous content, to go back to it if the change is rejected after all
+    pub fn accept_external_change(&mut self, content: String) -> String {
+        std::mem::replace(&mut self.content, content)
+    }
+
+    // The last write wins. A change made by another program that was not reloaded yet is backed up
+    // instead of being lost
+    pub fn save(&mut self, session: &SessionConfig) -> Result<()> {
+        let content = json::to_string_pretty(session)?;
+
+        let disk_content = fs::read_to_string(&self.path).unwrap_or_default();
+        if !disk_content.is_empty() && disk_content != self.content {
+            let backup_path = self
+                .path
+                .with_extension(format!("json.{}.overwritten", super::unix_time_secs()));
+            fs::write(&backup_path, disk_content)?;
+
+            warn!(
+                "session.json was modified by another program while the streamer saved it. The overwritten file is stored as {}",
+                backup_path.display()
+            );
+        }
+
+        fs::write(&self.path, &content)?;
+        self.content = content;
+        self.modified = Self::modified_time(&self.path);
+
+        Ok(())
+    }
+}
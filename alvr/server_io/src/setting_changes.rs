@@ -0,0 +1,234 @@
+use alvr_packets::{PathSegment, SettingChange, SettingChangeEffect};
+use alvr_session::settings_schema::{SchemaEntry, SchemaNode};
+use serde_json as json;
+
+fn entry_effect<T>(
+    entry: &SchemaEntry<T>,
+    parent_effect: SettingChangeEffect,
+) -> SettingChangeEffect {
+    if entry.flags.contains("steamvr-restart") {
+        SettingChangeEffect::DriverRestart
+    } else if entry.flags.contains("real-time") {
+        SettingChangeEffect::Live
+    } else {
+        parent_effect
+    }
+}
+
+fn push_change(
+    path: &[PathSegment],
+    leaf: PathSegment,
+    effect: SettingChangeEffect,
+    changes: &mut Vec<SettingChange>,
+) {
+    let mut path = path.to_vec();
+    path.push(leaf);
+
+    changes.push(SettingChange { path, effect });
+}
+
+// Walks the schema together with the old and new values. Only the active choice variant and the
+// content of enabled switches are inspected, changes to hidden data cannot affect the stream.
+fn collect_changes(
+    old: &json::Value,
+    new: &json::Value,
+    schema: &SchemaNode,
+    effect: SettingChangeEffect,
+    path: &mut Vec<PathSegment>,
+    changes: &mut Vec<SettingChange>,
+) {
+    if old == new {
+        return;
+    }
+
+    match schema {
+        SchemaNode::Section { entries, .. } => {
+            for entry in entries {
+                path.push(entry.name.clone().into());
+                collect_changes(
+                    &old[&entry.name],
+                    &new[&entry.name],
+                    &entry.content,
+                    entry_effect(entry, effect),
+                    path,
+                    changes,
+                );
+                path.pop();
+            }
+        }
+        SchemaNode::Choice { variants, .. } => {
+            if old["variant"] != new["variant"] {
+                push_change(path, "variant".into(), effect, changes);
+            } else if let Some(entry) = variants
+                .iter()
+                .find(|entry| new["variant"].as_str() == Some(&entry.name))
+            {
+                if let Some(content) = &entry.content {
+                    path.push(entry.name.clone().into());
+                    collect_changes(
+                        &old[&entry.name],
+                        &new[&entry.name],
+                        content,
+                        entry_effect(entry, effect),
+                        path,
+                        changes,
+                    );
+                    path.pop();
+                }
+            }
+        }
+        SchemaNode::Optional { content, .. } | SchemaNode::Switch { content, .. } => {
+            let state_key = if matches!(schema, SchemaNode::Optional { .. }) {
+                "set"
+            } else {
+                "enabled"
+            };
+
+            if old[state_key] != new[state_key] {
+                push_change(path, state_key.into(), effect, changes);
+            } else if new[state_key].as_bool().unwrap_or(false) {
+                path.push("content".into());
+                collect_changes(
+                    &old["content"],
+                    &new["content"],
+                    content,
+                    effect,
+                    path,
+                    changes,
+                );
+                path.pop();
+            }
+        }
+        SchemaNode::Array(array_schema) => {
+            path.push("content".into());
+            for (idx, schema) in array_schema.iter().enumerate() {
+                path.push(idx.into());
+                collect_changes(
+                    &old["content"][idx],
+                    &new["content"][idx],
+                    schema,
+                    effect,
+                    path,
+                    changes,
+                );
+                path.pop();
+            }
+            path.pop();
+        }
+        SchemaNode::Vector {
+            default_element, ..
+        } => {
+            match (old["content"].as_array(), new["content"].as_array()) {
+                (Some(old_vec), Some(new_vec)) if old_vec.len() == new_vec.len() => {
+                    path.push("content".into());
+                    for (idx, (old, new)) in old_vec.iter().zip(new_vec).enumerate() {
+                        path.push(idx.into());
+                        collect_changes(old, new, default_element, effect, path, changes);
+                        path.pop();
+                    }
+                    path.pop();
+                }
+                // "element" only holds the value of the next element to be added
+                _ if old["content"] != new["content"] => {
+                    push_change(path, "content".into(), effect, changes)
+                }
+                _ => (),
+            }
+        }
+        SchemaNode::Dictionary { .. } => {
+            if old["content"] != new["content"] {
+                push_change(path, "content".into(), effect, changes);
+            }
+        }
+        _ => changes.push(SettingChange {
+            path: path.clone(),
+            effect,
+        }),
+    }
+}
+
+/// Lists the settings that differ between two session_settings json trees, together with what is
+/// needed for the new values to take effect. Settings without a flag are read when the stream
+/// starts, so they inherit the effect of their parent or default to a stream restart.
+pub fn get_setting_changes(
+    old_session_settings: &json::Value,
+    new_session_settings: &json::Value,
+    schema: &SchemaNode,
+) -> Vec<SettingChange> {
+    let mut changes = vec![];
+    collect_changes(
+        old_session_settings,
+        new_session_settings,
+        schema,
+        SettingChangeEffect::StreamRestart,
+        &mut vec!["session_settings".into()],
+        &mut changes,
+    );
+
+    changes
+}
+
+fn node_effect(
+    schema: &SchemaNode,
+    path: &[PathSegment],
+    effect: SettingChangeEffect,
+) -> Option<SettingChangeEffect> {
+    let Some((segment, rest)) = path.split_first() else {
+        return Some(effect);
+    };
+    let PathSegment::Name(name) = segment else {
+        return None;
+    };
+
+    match schema {
+        SchemaNode::Section { entries, .. } => {
+            let entry = entries.iter().find(|entry| &entry.name == name)?;
+            node_effect(&entry.content, rest, entry_effect(entry, effect))
+        }
+        SchemaNode::Choice { variants, .. } => {
+            if name == "variant" {
+                return rest.is_empty().then_some(effect);
+            }
+
+            let entry = variants.iter().find(|entry| &entry.name == name)?;
+            node_effect(entry.content.as_ref()?, rest, entry_effect(entry, effect))
+        }
+        SchemaNode::Optional { content, .. } | SchemaNode::Switch { content, .. } => {
+            match name.as_str() {
+                "set" | "enabled" => rest.is_empty().then_some(effect),
+                "content" => node_effect(content, rest, effect),
+                _ => None,
+            }
+        }
+        SchemaNode::Array(array_schema) if name == "content" => match rest.split_first() {
+            Some((PathSegment::Index(idx), rest)) => {
+                node_effect(array_schema.get(*idx)?, rest, effect)
+            }
+            None => Some(effect),
+            _ => None,
+        },
+        SchemaNode::Vector {
+            default_element, ..
+        } if name == "content" => match rest.split_first() {
+            Some((PathSegment::Index(_), rest)) => node_effect(default_element, rest, effect),
+            None => Some(effect),
+            _ => None,
+        },
+        SchemaNode::Dictionary { .. } if name == "content" => Some(effect),
+        _ => None,
+    }
+}
+
+/// What is needed for a change of the setting at this path to take effect, with the same rules as
+/// get_setting_changes(). None if the path does not lead to a setting
+pub fn setting_path_effect(
+    path: &[PathSegment],
+    schema: &SchemaNode,
+) -> Option<SettingChangeEffect> {
+    match path.split_first() {
+        Some((PathSegment::Name(root), rest)) if root == "session_settings" => {
+            node_effect(schema, rest, SettingChangeEffect::StreamRestart)
+        }
+        _ => None,
+    }
+}
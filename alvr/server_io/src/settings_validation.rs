@@ -0,0 +1,413 @@
+use alvr_packets::{InvalidSetting, SettingValidationError};
+use alvr_session::{
+    settings_schema::Switch, BitrateMode, EyeFovConfig, FovOverride, FrameSize, Settings,
+    SocketProtocol,
+};
+use serde_json as json;
+use std::net::IpAddr;
+
+// Well beyond the range of human IPDs, only typos are caught
+const MAX_IPD_MM: f32 = 100.0;
+const MAX_FOV_SCALE: f32 = 2.0;
+
+// The encoder works on blocks of 32 pixels
+const RESOLUTION_ALIGNMENT: u32 = 32;
+
+// The path is relative to session_settings
+fn invalid(path: &str, value: impl Into<json::Value>) -> InvalidSetting {
+    InvalidSetting {
+        path: alvr_packets::parse_path(&format!("session_settings.{path}")),
+        value: value.into(),
+    }
+}
+
+fn push_error(
+    errors: &mut Vec<SettingValidationError>,
+    entries: Vec<InvalidSetting>,
+    message: impl Into<String>,
+) {
+    errors.push(SettingValidationError {
+        entries,
+        message: message.into(),
+    });
+}
+
+fn validate_resolution(
+    errors: &mut Vec<SettingValidationError>,
+    name: &str,
+    frame_size: &FrameSize,
+) {
+    match frame_size {
+        FrameSize::Scale(scale) => {
+            if *scale <= 0.0 {
+                push_error(
+                    errors,
+                    vec![invalid(&format!("video.{name}.Scale"), *scale)],
+                    "The resolution scale must be greater than 0",
+                );
+            }
+        }
+        FrameSize::Absolute { width, height } => {
+            let sizes = [("width", Some(*width)), ("height.content", *height)];
+            for (field, size) in sizes {
+                if let Some(size) =
+                    size.filter(|size| *size == 0 || size % RESOLUTION_ALIGNMENT != 0)
+                {
+                    push_error(
+                        errors,
+                        vec![invalid(&format!("video.{name}.Absolute.{field}"), size)],
+                        format!(
+                            "The resolution must be a non-zero multiple of {RESOLUTION_ALIGNMENT}"
+                        ),
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn validate_eye_fov(errors: &mut Vec<SettingValidationError>, name: &str, fov: &EyeFovConfig) {
+    let angles = [
+        ("left_deg", fov.left_deg),
+        ("right_deg", fov.right_deg),
+        ("up_deg", fov.up_deg),
+        ("down_deg", fov.down_deg),
+    ];
+    for (field, angle) in angles {
+        if angle <= 0.0 || angle >= 90.0 {
+            push_error(
+                errors,
+                vec![invalid(
+                    &format!("headset.views_override.fov.content.Absolute.{name}.{field}"),
+                    angle,
+                )],
+                "The FOV angles must be between 0° and 90°",
+            );
+        }
+    }
+}
+
+// Hostnames are checked against the DNS label rules, they are resolved only when used
+fn is_valid_host(host: &str) -> bool {
+    host.parse::<IpAddr>().is_ok()
+        || (!host.is_empty()
+            && host.len() <= 253
+            && host.split('.').all(|label| {
+                !label.is_empty()
+                    && label.len() <= 63
+                    && !label.starts_with('-')
+                    && !label.ends_with('-')
+                    && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            }))
+}
+
+// The errors that are not in previous_errors, or that involve different values. The other ones
+// were not caused by the change
+pub fn new_validation_errors(
+    errors: &[SettingValidationError],
+    previous_errors: &[SettingValidationError],
+) -> Vec<SettingValidationError> {
+    errors
+        .iter()
+        .filter(|error| !previous_errors.contains(error))
+        .cloned()
+        .collect()
+}
+
+// Only the active variants and the enabled switches are checked, the other values are not used
+pub fn validate_settings(settings: &Settings) -> Vec<SettingValidationError> {
+    let mut errors = vec![];
+
+    let video = &settings.video;
+    validate_resolution(
+        &mut errors,
+        "transcoding_view_resolution",
+        &video.transcoding_view_resolution,
+    );
+    validate_resolution(
+        &mut errors,
+        "emulated_headset_view_resolution",
+        &video.emulated_headset_view_resolution,
+    );
+
+    if video.preferred_fps <= 0.0 {
+        push_error(
+            &mut errors,
+            vec![invalid("video.preferred_fps", video.preferred_fps)],
+            "The framerate must be greater than 0",
+        );
+    }
+
+    match &video.bitrate.mode {
+        BitrateMode::ConstantMbps(mbps) => {
+            if *mbps == 0 {
+                push_error(
+                    &mut errors,
+                    vec![invalid("video.bitrate.mode.ConstantMbps", *mbps)],
+                    "The bitrate must be greater than 0",
+                );
+            }
+        }
+        BitrateMode::Adaptive {
+            saturation_multiplier,
+            max_bitrate_mbps,
+            min_bitrate_mbps,
+            ..
+        } => {
+            const ADAPTIVE_PATH: &str = "video.bitrate.mode.Adaptive";
+
+            if *saturation_multiplier <= 0.0 {
+                push_error(
+                    &mut errors,
+                    vec![invalid(
+                        &format!("{ADAPTIVE_PATH}.saturation_multiplier"),
+                        *saturation_multiplier,
+                    )],
+                    "The saturation multiplier must be greater than 0",
+                );
+            }
+
+            let max_entry = max_bitrate_mbps.as_option().map(|max| {
+                (
+                    *max,
+                    invalid(&format!("{ADAPTIVE_PATH}.max_bitrate_mbps.content"), *max),
+                )
+            });
+            let min_entry = min_bitrate_mbps.as_option().map(|min| {
+                (
+                    *min,
+                    invalid(&format!("{ADAPTIVE_PATH}.min_bitrate_mbps.content"), *min),
+                )
+            });
+
+            for (mbps, entry) in max_entry.iter().chain(&min_entry) {
+                if *mbps == 0 {
+                    push_error(
+                        &mut errors,
+                        vec![entry.clone()],
+                        "The bitrate must be greater than 0",
+                    );
+                }
+            }
+            if let (Some((min, min_entry)), Some((max, max_entry))) = (min_entry, max_entry) {
+                if min > max {
+                    push_error(
+                        &mut errors,
+                        vec![min_entry, max_entry],
+                        "The minimum bitrate is greater than the maximum bitrate",
+                    );
+                }
+            }
+        }
+    }
+
+    let views_override = &settings.headset.views_override;
+    if let Some(ipd_mm) = views_override.ipd_mm {
+        if ipd_mm <= 0.0 || ipd_mm > MAX_IPD_MM {
+            push_error(
+                &mut errors,
+                vec![invalid("headset.views_override.ipd_mm.content", ipd_mm)],
+                format!("The IPD override must be between 0 and {MAX_IPD_MM} mm"),
+            );
+        }
+    }
+    match &views_override.fov {
+        Some(FovOverride::Scale(scale)) => {
+            if *scale <= 0.0 || *scale > MAX_FOV_SCALE {
+                push_error(
+                    &mut errors,
+                    vec![invalid("headset.views_override.fov.content.Scale", *scale)],
+                    format!("The FOV scale must be between 0 and {MAX_FOV_SCALE}"),
+                );
+            }
+        }
+        Some(FovOverride::Absolute {
+            left_eye,
+            right_eye,
+        }) => {
+            validate_eye_fov(&mut errors, "left_eye", left_eye);
+            validate_eye_fov(&mut errors, "right_eye", right_eye);
+        }
+        None => (),
+    }
+
+    if let Switch::Enabled(rate) = settings.headset.tracking_send_rate_hz {
+        if rate <= 0.0 {
+            push_error(
+                &mut errors,
+                vec![invalid("headset.tracking_send_rate_hz.content", rate)],
+                "The tracking send rate must be greater than 0",
+            );
+        }
+    }
+
+    if let Switch::Enabled(config) = &settings.headset.pose_osc {
+        if !is_valid_host(&config.host) {
+            push_error(
+                &mut errors,
+                vec![invalid(
+                    "headset.pose_osc.content.host",
+                    config.host.as_str(),
+                )],
+                "The OSC host must be an IP address or a hostname",
+            );
+        }
+        if config.port == 0 {
+            push_error(
+                &mut errors,
+                vec![invalid("headset.pose_osc.content.port", config.port)],
+                "The port must be greater than 0",
+            );
+        }
+        if config.send_rate_hz <= 0.0 {
+            push_error(
+                &mut errors,
+                vec![invalid(
+                    "headset.pose_osc.content.send_rate_hz",
+                    config.send_rate_hz,
+                )],
+                "The send rate must be greater than 0",
+            );
+        }
+    }
+
+    let connection = &settings.connection;
+    let ports = [
+        ("control_port", "control", connection.control_port),
+        ("stream_port", "stream", connection.stream_port),
+        ("web_server_port", "web server", connection.web_server_port),
+    ];
+    for (name, _, port) in ports {
+        if port == 0 {
+            push_error(
+                &mut errors,
+                vec![invalid(&format!("connection.{name}"), port)],
+                "The port must be greater than 0",
+            );
+        }
+    }
+    for (i, (name_a, display_a, port_a)) in ports.iter().enumerate() {
+        for (name_b, display_b, port_b) in &ports[i + 1..] {
+            if port_a == port_b {
+                push_error(
+                    &mut errors,
+                    vec![
+                        invalid(&format!("connection.{name_a}"), *port_a),
+                        invalid(&format!("connection.{name_b}"), *port_b),
+                    ],
+                    format!("The {display_a} and {display_b} ports must be different"),
+                );
+            }
+        }
+    }
+
+    if connection.discovery_port == 0 {
+        push_error(
+            &mut errors,
+            vec![invalid(
+                "connection.discovery_port",
+                connection.discovery_port,
+            )],
+            "The port must be greater than 0",
+        );
+    }
+    if connection.stream_protocol == SocketProtocol::Udp
+        && connection.stream_port == connection.discovery_port
+    {
+        push_error(
+            &mut errors,
+            vec![
+                invalid("connection.stream_port", connection.stream_port),
+                invalid("connection.discovery_port", connection.discovery_port),
+            ],
+            "The UDP stream port must be different from the discovery port",
+        );
+    }
+
+    if let Switch::Enabled(config) = &settings.logging.event_log {
+        if config.max_file_size_mb == 0 {
+            push_error(
+                &mut errors,
+                vec![invalid(
+                    "logging.event_log.content.max_file_size_mb",
+                    config.max_file_size_mb,
+                )],
+                "The maximum file size must be greater than 0",
+            );
+        }
+        if config.max_total_size_mb < config.max_file_size_mb {
+            push_error(
+                &mut errors,
+                vec![
+                    invalid(
+                        "logging.event_log.content.max_total_size_mb",
+                        config.max_total_size_mb,
+                    ),
+                    invalid(
+                        "logging.event_log.content.max_file_size_mb",
+                        config.max_file_size_mb,
+                    ),
+                ],
+                "The maximum total size is smaller than the maximum size of a single file",
+            );
+        }
+    }
+
+    if let Switch::Enabled(config) = &settings.application_profiles {
+        for (profile_idx, profile) in config.profiles.iter().enumerate() {
+            for (override_idx, entry) in profile.overrides.iter().enumerate() {
+                if let Err(e) = crate::check_override(&entry.path, &entry.value) {
+                    push_error(
+                        &mut errors,
+                        vec![invalid(
+                            &format!(
+                                "application_profiles.content.profiles.content.{profile_idx}.overrides.content.{override_idx}.path"
+                            ),
+                            entry.path.as_str(),
+                        )],
+                        format!("Profile \"{}\": {e}", profile.name),
+                    );
+                }
+            }
+        }
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolution_errors(width: u32, height: Option<u32>) -> Vec<SettingValidationError> {
+        let mut errors = vec![];
+        validate_resolution(
+            &mut errors,
+            "transcoding_view_resolution",
+            &FrameSize::Absolute { width, height },
+        );
+
+        errors
+    }
+
+    #[test]
+    fn test_validate_resolution() {
+        assert!(resolution_errors(1920, Some(1088)).is_empty());
+        assert!(resolution_errors(1920, None).is_empty());
+
+        let errors = resolution_errors(1900, Some(1088));
+        assert_eq!(errors.len(), 1);
+        assert!(
+            errors[0].entries[0].path
+                == alvr_packets::parse_path(
+                    "session_settings.video.transcoding_view_resolution.Absolute.width"
+                )
+        );
+
+        let errors = resolution_errors(1920, Some(0));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].entries[0].value, json::Value::from(0));
+
+        assert_eq!(resolution_errors(0, Some(20)).len(), 2);
+    }
+}
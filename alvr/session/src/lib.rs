@@ -1,18 +1,21 @@
+mod migrations;
 mod settings;
 
+pub use migrations::*;
 pub use settings::*;
 pub use settings_schema;
 
 use alvr_common::{
     anyhow::{bail, Result},
+    glam::UVec2,
     semver::Version,
-    ConnectionState, ToAny, ALVR_VERSION,
+    ConnectionState, GpuVendor, ToAny, ALVR_VERSION,
 };
 use serde::{Deserialize, Serialize};
 use serde_json as json;
 use settings_schema::{NumberType, SchemaNode};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     net::IpAddr,
     path::PathBuf,
 };
@@ -64,6 +67,7 @@ pub struct OpenvrConfig {
     pub rate_control_mode: u32,
     pub filler_data: bool,
     pub entropy_coding: u32,
+    pub slice_count: u32,
     pub force_sw_encoding: bool,
     pub sw_thread_count: u32,
     pub controller_is_tracker: bool,
@@ -111,6 +115,26 @@ pub struct OpenvrConfig {
     pub _controller_profile: i32,
 }
 
+// What the headset advertised when it last connected, next to what the server chose from it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct HeadsetInfo {
+    // Unix timestamp in seconds
+    pub captured_at: u64,
+    pub default_view_resolution: UVec2,
+    pub supported_refresh_rates: Vec<f32>,
+    // Not sent by current clients
+    pub preferred_refresh_rate: Option<f32>,
+    pub microphone_sample_rate: u32,
+    // Remaining entries of the extensible part of the capabilities, like encoder support
+    pub extra_capabilities: BTreeMap<String, json::Value>,
+    pub negotiated_view_resolution: UVec2,
+    pub negotiated_refresh_rate: f32,
+    pub negotiated_codec: CodecType,
+    pub negotiated_foveated_encoding: bool,
+    pub negotiated_10_bits_encoding: bool,
+    pub negotiated_slice_count: u32,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct ClientConnectionConfig {
     pub display_name: String,
@@ -119,15 +143,35 @@ pub struct ClientConnectionConfig {
     pub trusted: bool,
     pub connection_state: ConnectionState,
     pub cabled: bool,
+    // Unix timestamps in seconds. Missing on entries saved by older versions.
+    #[serde(default)]
+    pub first_seen: u64,
+    #[serde(default)]
+    pub last_seen: u64,
+    // Reported by clients since v21, None until the client streams once
+    #[serde(default)]
+    pub version: Option<Version>,
+    // Kept after the client disconnects
+    #[serde(default)]
+    pub headset_info: Option<HeadsetInfo>,
+    // Name of the recommended defaults applied for the headset model. Set at the first connection,
+    // so that they are not applied again
+    #[serde(default)]
+    pub device_preset: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct SessionConfig {
     pub server_version: Version,
+    // Bumped by each migration, see migrations.rs
+    pub schema_version: u32,
     pub drivers_backup: Option<DriversBackup>,
     pub openvr_config: OpenvrConfig,
     // The hashmap key is the hostname
     pub client_connections: HashMap<String, ClientConnectionConfig>,
+    // Vendor of the GPU defaults applied last, so that only their values are replaced later
+    #[serde(default)]
+    pub gpu_defaults_vendor: Option<GpuVendor>,
     pub session_settings: SessionSettings,
 }
 
@@ -135,6 +179,7 @@ impl Default for SessionConfig {
     fn default() -> Self {
         Self {
             server_version: ALVR_VERSION.clone(),
+            schema_version: SESSION_SCHEMA_VERSION,
             drivers_backup: None,
             openvr_config: OpenvrConfig {
                 // avoid realistic resolutions, as on first start, on Linux, it
@@ -154,6 +199,7 @@ impl Default for SessionConfig {
                 ..<_>::default()
             },
             client_connections: HashMap::new(),
+            gpu_defaults_vendor: None,
             session_settings: settings::session_settings_default(),
         }
     }
@@ -190,17 +236,30 @@ impl SessionConfig {
                     )
                 });
 
-        let new_fields = old_session_fields
-            .iter()
-            .map(|(name, json_field_value)| {
-                let new_json_field_value = if name == SESSION_SETTINGS_STR {
-                    json::to_value(settings::session_settings_default()).unwrap()
-                } else {
-                    json_value.get(name).unwrap_or(json_field_value).clone()
-                };
-                (name.clone(), new_json_field_value)
-            })
-            .collect();
+        let mut new_fields = old_session_fields.clone();
+        new_fields.insert(
+            SESSION_SETTINGS_STR.into(),
+            json::to_value(settings::session_settings_default()).unwrap(),
+        );
+        // Each field is taken separately, so that a field that does not deserialize anymore does
+        // not reset the others (like the trusted clients)
+        for name in old_session_fields.keys() {
+            if let Some(new_json_field_value) = json_value
+                .get(name)
+                .filter(|_| name != SESSION_SETTINGS_STR)
+            {
+                let old_json_field_value =
+                    new_fields.insert(name.clone(), new_json_field_value.clone());
+
+                if json::from_value::<SessionConfig>(json::Value::Object(new_fields.clone()))
+                    .is_err()
+                {
+                    if let Some(value) = old_json_field_value {
+                        new_fields.insert(name.clone(), value);
+                    }
+                }
+            }
+        }
         // Failure to extrapolate other session_desc fields is not notified.
         let mut session_desc_mut =
             json::from_value::<SessionConfig>(json::Value::Object(new_fields)).unwrap_or_default();
@@ -235,6 +294,21 @@ impl SessionConfig {
     }
 }
 
+// Used to import settings exported by another ALVR version. Entries that cannot be matched with
+// the current schema keep the value they have in `current_settings`
+pub fn extrapolate_session_settings(
+    current_settings: &SessionSettings,
+    imported_settings_json: &json::Value,
+) -> Result<SessionSettings> {
+    let extrapolated_json = extrapolate_session_settings_from_session_settings(
+        &json::to_value(current_settings)?,
+        imported_settings_json,
+        &Settings::schema(settings::session_settings_default()),
+    );
+
+    Ok(json::from_value(extrapolated_json)?)
+}
+
 // Current data extrapolation strategy: match both field name and value type exactly.
 // Integer bounds are not validated, if they do not match the schema, deserialization will fail and
 // all data is lost.
@@ -587,6 +661,7 @@ fn json_session_settings_to_settings(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alvr_common::LogSeverity;
 
     #[test]
     fn test_manual_session_to_settings() {
@@ -640,4 +715,113 @@ mod tests {
         assert_eq!(settings.video.preferred_fps, 60.0);
         assert!(settings.headset.controllers.as_option().is_none());
     }
+
+    #[test]
+    fn test_settings_import_extrapolation() {
+        let imported_json = json::json!({
+            "unknown_section": {},
+            "video": {
+                "preferred_fps": 90.0,
+                "adapter_index": "invalid"
+            }
+        });
+
+        let current = session_settings_default();
+        let imported = extrapolate_session_settings(&current, &imported_json).unwrap();
+
+        assert_eq!(imported.video.preferred_fps, 90.0);
+        assert_eq!(imported.video.adapter_index, current.video.adapter_index);
+    }
+
+    #[test]
+    fn test_session_migration_current() {
+        let session_json = json::to_value(SessionConfig::default()).unwrap();
+
+        let (_, report) = migrate_session_json(session_json);
+
+        assert!(report.is_empty(), "{report:?}");
+    }
+
+    // Files saved by v21 before the schema version was stored
+    #[test]
+    fn test_session_migration_unversioned() {
+        let mut session_json = json::to_value(SessionConfig::default()).unwrap();
+        session_json
+            .as_object_mut()
+            .unwrap()
+            .remove("schema_version");
+
+        let (session, report) = migrate_session_json(session_json);
+
+        assert_eq!(session.schema_version, SESSION_SCHEMA_VERSION);
+        assert_eq!(report.from_version, 0);
+        assert!(report.migrated.is_empty(), "{report:?}");
+        assert!(report.dropped.is_empty(), "{report:?}");
+    }
+
+    #[test]
+    fn test_settings_file_migration() {
+        let (settings_json, report) = migrate_session_settings_json(
+            json::json!({ "extra": { "logging": { "log_to_disk": true } } }),
+            0,
+        );
+
+        assert_eq!(settings_json["logging"]["log_to_disk"], true);
+        assert!(settings_json.get("extra").is_none());
+        assert!(report
+            .migrated
+            .contains(&"session_settings.extra.logging -> session_settings.logging".into()));
+
+        // Leaves the entries missing from the file to the extrapolation
+        assert!(settings_json.get("video").is_none());
+    }
+
+    // session_schema_v0.json is not a file saved by a release. No v20 session file could be
+    // obtained, so it reconstructs by hand the v20.6.1 sections that the migration touches. It
+    // should be replaced with a session.json saved by v20.6.1
+    #[test]
+    fn test_session_migration_v0() {
+        let session_json =
+            json::from_str(include_str!("../resources/session_schema_v0.json")).unwrap();
+
+        let (session, report) = migrate_session_json(session_json);
+        let settings = session.to_settings();
+
+        assert_eq!(report.from_version, 0);
+        assert_eq!(report.to_version, SESSION_SCHEMA_VERSION);
+        assert!(report.error.is_none());
+        assert!(report
+            .migrated
+            .contains(&"session_settings.extra.logging -> session_settings.logging".into()));
+
+        assert_eq!(settings.video.preferred_fps, 72.0);
+        assert!(matches!(
+            settings.connection.stream_protocol,
+            SocketProtocol::Udp
+        ));
+        assert!(settings.logging.log_to_disk);
+        assert!(!settings.logging.show_notification_tip);
+        assert_eq!(settings.logging.notification_level, LogSeverity::Warning);
+        assert!(settings.steamvr_launcher.open_close_steamvr_with_dashboard);
+        assert_eq!(settings.capture.capture_frame_dir, "/home/alvr/frames");
+        assert!(settings.patches.linux_async_reprojection);
+        assert!(!settings.open_setup_wizard);
+
+        // The old openvr_config does not deserialize anymore, the trusted client must survive it
+        assert!(session.client_connections["3100.client.alvr"].trusted);
+        assert!(report
+            .dropped
+            .contains(&"openvr_config.eye_resolution_width".into()));
+
+        for path in [
+            "session_settings.extra.revert_confirm_dialog",
+            "session_settings.connection.stream_protocol.ThrottledUdp",
+        ] {
+            assert!(report.dropped.contains(&path.into()), "{path}");
+        }
+        assert!(!report
+            .dropped
+            .iter()
+            .any(|path| path.starts_with("session_settings.extra.logging")));
+    }
 }
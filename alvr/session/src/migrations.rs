@@ -0,0 +1,257 @@
+use crate::SessionConfig;
+use serde::{Deserialize, Serialize};
+use serde_json as json;
+
+// Changes of the session shape that the extrapolation cannot follow (renames, moves, enum changes)
+// need a migration. MIGRATIONS[n] upgrades a session from version n to version n + 1. Released
+// migrations must not be edited, a new one is appended instead.
+const MIGRATIONS: &[fn(&mut json::Value, &mut MigrationReport)] = &[migrate_v0_to_v1];
+
+// Sessions saved before the version was stored are version 0
+pub const SESSION_SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    // One line per applied change, like "session_settings.extra.logging -> session_settings.logging"
+    pub migrated: Vec<String>,
+    // Entries of the file that were reset to their default value
+    pub dropped: Vec<String>,
+    // Set when the file could not be parsed or its settings could not be extrapolated at all
+    pub error: Option<String>,
+}
+
+impl MigrationReport {
+    pub fn is_empty(&self) -> bool {
+        self.from_version == self.to_version
+            && self.migrated.is_empty()
+            && self.dropped.is_empty()
+            && self.error.is_none()
+    }
+}
+
+fn path_to_string(path: &[&str]) -> String {
+    path.join(".")
+}
+
+fn get_entry_mut<'a>(session: &'a mut json::Value, path: &[&str]) -> Option<&'a mut json::Value> {
+    path.iter()
+        .try_fold(session, |value, segment| value.get_mut(*segment))
+}
+
+fn take_entry(session: &mut json::Value, path: &[&str]) -> Option<json::Value> {
+    let (name, parent_path) = path.split_last()?;
+
+    get_entry_mut(session, parent_path)?
+        .as_object_mut()?
+        .remove(*name)
+}
+
+// Missing parent sections are created
+fn insert_entry(session: &mut json::Value, path: &[&str], value: json::Value) {
+    let Some((name, parent_path)) = path.split_last() else {
+        return;
+    };
+
+    let mut parent = session;
+    for segment in parent_path {
+        let Some(object) = parent.as_object_mut() else {
+            return;
+        };
+        parent = object
+            .entry(*segment)
+            .or_insert_with(|| json::Value::Object(json::Map::new()));
+    }
+
+    if let Some(object) = parent.as_object_mut() {
+        object.insert((*name).to_owned(), value);
+    }
+}
+
+// Used both for renames and for moves to another section
+fn move_entry(
+    session: &mut json::Value,
+    from_path: &[&str],
+    to_path: &[&str],
+    report: &mut MigrationReport,
+) {
+    if let Some(value) = take_entry(session, from_path) {
+        insert_entry(session, to_path, value);
+
+        report.migrated.push(format!(
+            "{} -> {}",
+            path_to_string(from_path),
+            path_to_string(to_path)
+        ));
+    }
+}
+
+// Choices are stored as {"variant": "<name>", "<name>": <content of the variant>, ...}. The
+// content of the old variant cannot be mapped and is dropped
+fn replace_variant(
+    session: &mut json::Value,
+    path: &[&str],
+    old_variant: &str,
+    new_variant: &str,
+    report: &mut MigrationReport,
+) {
+    let Some(choice) = get_entry_mut(session, path) else {
+        return;
+    };
+
+    if choice.get("variant").and_then(|v| v.as_str()) == Some(old_variant) {
+        choice["variant"] = new_variant.into();
+
+        report.migrated.push(format!(
+            "{}: {old_variant} -> {new_variant}",
+            path_to_string(path)
+        ));
+    }
+
+    if let Some(object) = choice.as_object_mut() {
+        if object.remove(old_variant).is_some() {
+            report
+                .dropped
+                .push(format!("{}.{old_variant}", path_to_string(path)));
+        }
+    }
+}
+
+// v20 kept these sections inside "extra", and still had the throttled UDP protocol
+fn migrate_v0_to_v1(session: &mut json::Value, report: &mut MigrationReport) {
+    for name in [
+        "logging",
+        "steamvr_launcher",
+        "capture",
+        "patches",
+        "open_setup_wizard",
+    ] {
+        move_entry(
+            session,
+            &["session_settings", "extra", name],
+            &["session_settings", name],
+            report,
+        );
+    }
+    // Whatever is left in "extra" is reported as dropped
+    if get_entry_mut(session, &["session_settings", "extra"])
+        .and_then(|extra| extra.as_object())
+        .map(|extra| extra.is_empty())
+        .unwrap_or(false)
+    {
+        take_entry(session, &["session_settings", "extra"]);
+    }
+
+    replace_variant(
+        session,
+        &["session_settings", "connection", "stream_protocol"],
+        "ThrottledUdp",
+        "Udp",
+        report,
+    );
+}
+
+// Values of float settings go through f32, and the order of sets is not preserved
+fn same_value(a: &json::Value, b: &json::Value) -> bool {
+    match (a, b) {
+        (json::Value::Number(a), json::Value::Number(b)) => {
+            a.as_f64().map(|a| a as f32) == b.as_f64().map(|b| b as f32)
+        }
+        (json::Value::Object(a), json::Value::Object(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(name, a)| b.get(name).map(|b| same_value(a, b)).unwrap_or(false))
+        }
+        (json::Value::Array(a), json::Value::Array(b)) => {
+            a.len() == b.len() && a.iter().all(|a| b.iter().any(|b| same_value(a, b)))
+        }
+        _ => a == b,
+    }
+}
+
+// Entries of the migrated file that did not survive the extrapolation
+fn collect_dropped(
+    migrated: &json::Value,
+    loaded: &json::Value,
+    path: &str,
+    dropped: &mut Vec<String>,
+) {
+    match (migrated, loaded) {
+        (json::Value::Object(migrated), json::Value::Object(loaded)) => {
+            for (name, migrated_value) in migrated {
+                let entry_path = if path.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{path}.{name}")
+                };
+
+                if let Some(loaded_value) = loaded.get(name) {
+                    collect_dropped(migrated_value, loaded_value, &entry_path, dropped);
+                } else {
+                    dropped.push(entry_path);
+                }
+            }
+        }
+        _ => {
+            if !same_value(migrated, loaded) {
+                dropped.push(path.to_owned());
+            }
+        }
+    }
+}
+
+fn apply_migrations(session_json: &mut json::Value, from_version: u32) -> MigrationReport {
+    let mut report = MigrationReport {
+        from_version,
+        to_version: SESSION_SCHEMA_VERSION,
+        ..Default::default()
+    };
+
+    // Sessions from a newer version are only extrapolated
+    for migration in MIGRATIONS.iter().skip(from_version as usize) {
+        migration(session_json, &mut report);
+    }
+
+    report
+}
+
+// Loads a session saved by any version. Entries that no migration can map keep their default
+// value and are listed in the report
+pub fn migrate_session_json(mut session_json: json::Value) -> (SessionConfig, MigrationReport) {
+    let from_version = session_json
+        .get("schema_version")
+        .and_then(|v| v.as_u64())
+        .and_then(|v| u32::try_from(v).ok())
+        .unwrap_or(0);
+
+    let mut report = apply_migrations(&mut session_json, from_version);
+
+    let mut session = SessionConfig::default();
+    if let Err(e) = session.merge_from_json(&session_json) {
+        report.error = Some(e.to_string());
+    }
+    session.schema_version = SESSION_SCHEMA_VERSION;
+
+    // Keys that are rewritten whatever the file contains
+    if let Some(object) = session_json.as_object_mut() {
+        object.remove("schema_version");
+        object.remove("server_version");
+    }
+    let loaded_json = json::to_value(&session).unwrap();
+    collect_dropped(&session_json, &loaded_json, "", &mut report.dropped);
+
+    (session, report)
+}
+
+// Migrates settings exported by another version. Unlike migrate_session_json(), the entries
+// missing from the file are not filled in, they are left to the extrapolation
+pub fn migrate_session_settings_json(
+    session_settings_json: json::Value,
+    from_version: u32,
+) -> (json::Value, MigrationReport) {
+    let mut session_json = json::json!({ "session_settings": session_settings_json });
+    let report = apply_migrations(&mut session_json, from_version);
+
+    (session_json["session_settings"].take(), report)
+}
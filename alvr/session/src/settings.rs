@@ -220,6 +220,13 @@ CABAC produces better compression but it's significantly slower and may lead to
     #[schema(flag = "steamvr-restart")]
     pub entropy_coding: EntropyCoding,
 
+    #[schema(strings(
+        help = "Number of slices each frame is split into. Slices are encoded in parallel and sent to the client individually. Has no effect with AV1 or if the client does not support it"
+    ))]
+    #[schema(flag = "steamvr-restart")]
+    #[schema(gui(slider(min = 1, max = 8)))]
+    pub slice_count: u32,
+
     #[schema(strings(
         display_name = "10 bit encoding",
         help = "Sets the encoder to use 10 bits per channel instead of 8. Does not work on Linux with Nvidia"
@@ -370,6 +377,50 @@ pub struct BitrateAdaptiveFramerateConfig {
     pub framerate_reset_threshold_multiplier: f32,
 }
 
+#[derive(SettingsSchema, Serialize, Deserialize, Clone, PartialEq)]
+#[schema(collapsible)]
+pub struct BitrateRampUpConfig {
+    #[schema(strings(
+        display_name = "Initial bitrate fraction",
+        help = "Fraction of the target bitrate used when the stream starts"
+    ))]
+    #[schema(flag = "real-time")]
+    #[schema(gui(slider(min = 0.05, max = 1.0, step = 0.05)))]
+    pub initial_fraction: f32,
+
+    #[schema(strings(help = "Time needed to reach the target bitrate on a healthy link"))]
+    #[schema(flag = "real-time")]
+    #[schema(gui(slider(min = 1.0, max = 30.0, step = 1.0)), suffix = "s")]
+    pub duration_s: f32,
+
+    #[schema(strings(
+        display_name = "Maximum network latency",
+        help = "The bitrate is not increased while the network latency is above this threshold"
+    ))]
+    #[schema(flag = "real-time")]
+    #[schema(gui(slider(min = 1, max = 50)), suffix = "ms")]
+    pub max_network_latency_ms: u64,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone, PartialEq)]
+#[schema(collapsible)]
+pub struct ThermalMitigationConfig {
+    #[schema(strings(
+        help = "Lower the bitrate when the headset reports that it is overheating, and restore it when it cools down. If disabled, only warnings are shown"
+    ))]
+    pub automatic: bool,
+
+    #[schema(strings(help = "Bitrate reduction for each thermal level above \"light\""))]
+    #[schema(gui(slider(min = 0.05, max = 0.5, step = 0.05)))]
+    pub bitrate_reduction_per_level: f32,
+
+    #[schema(strings(
+        help = "Scale of the stream resolution while the headset stays at the \"severe\" thermal level or above in spite of the lower bitrate. The resolution is restored once the headset cools down to \"light\". Both changes apply the next time the client connects, which restarts SteamVR"
+    ))]
+    #[schema(gui(slider(min = 0.5, max = 0.95, step = 0.05)))]
+    pub resolution_step_down: Switch<f32>,
+}
+
 #[derive(SettingsSchema, Serialize, Deserialize, Clone, PartialEq)]
 #[schema(collapsible)]
 pub struct BitrateConfig {
@@ -382,7 +433,17 @@ pub struct BitrateConfig {
     #[schema(flag = "real-time")]
     pub adapt_to_framerate: Switch<BitrateAdaptiveFramerateConfig>,
 
+    #[schema(strings(
+        help = "Start each stream at a lower bitrate and increase it gradually while the network latency is low"
+    ))]
+    #[schema(flag = "real-time")]
+    pub ramp_up: Switch<BitrateRampUpConfig>,
+
+    #[schema(flag = "real-time")]
+    pub thermal_mitigation: ThermalMitigationConfig,
+
     #[schema(strings(help = "Controls the smoothness during calculations"))]
+    #[schema(flag = "advanced")]
     pub history_size: usize,
 
     #[schema(strings(
@@ -509,6 +570,7 @@ pub enum H264Profile {
 pub struct VideoConfig {
     #[schema(strings(help = "You probably don't want to change this"))]
     #[schema(flag = "steamvr-restart")]
+    #[schema(flag = "advanced")]
     pub adapter_index: u32,
 
     #[schema(strings(
@@ -536,33 +598,48 @@ pub struct VideoConfig {
         gui(slider(min = 1.0, max = 10.0, step = 0.1, logarithmic)),
         suffix = " frames"
     )]
+    #[schema(flag = "advanced")]
     pub max_buffering_frames: f32,
 
     #[schema(gui(slider(min = 0.50, max = 0.99, step = 0.01)))]
+    #[schema(flag = "advanced")]
     pub buffering_history_weight: f32,
 
     #[schema(strings(help = "This works only on Windows"))]
     #[schema(flag = "real-time")]
+    #[schema(flag = "advanced")]
     pub optimize_game_render_latency: bool,
 
     pub bitrate: BitrateConfig,
 
     #[schema(strings(
-        help = "HEVC may provide better visual fidelity at the cost of increased encoder latency"
+        help = "HEVC may provide better visual fidelity at the cost of increased encoder latency.
+On Windows the codec can be switched while streaming."
     ))]
-    #[schema(flag = "steamvr-restart")]
     pub preferred_codec: CodecType,
 
     #[schema(flag = "steamvr-restart")]
+    #[schema(flag = "advanced")]
     pub encoder_config: EncoderConfig,
 
     #[schema(strings(
         help = "Attempts to use a software decoder on the device. Slow, but may work around broken codecs."
     ))]
+    #[schema(flag = "advanced")]
     pub force_software_decoder: bool,
 
+    #[schema(flag = "advanced")]
     pub mediacodec_extra_options: Vec<(String, MediacodecDataType)>,
 
+    #[schema(flag = "advanced")]
+    pub decoder_recovery: DecoderRecoveryConfig,
+
+    #[schema(strings(
+        help = "Holds received frames on the client to smooth out irregular arrival times, at the cost of up to the configured amount of added latency"
+    ))]
+    #[schema(flag = "advanced")]
+    pub jitter_buffer: Switch<JitterBufferDepth>,
+
     #[schema(flag = "steamvr-restart")]
     pub foveated_encoding: Switch<FoveatedEncodingConfig>,
 
@@ -572,6 +649,38 @@ pub struct VideoConfig {
     pub color_correction: Switch<ColorCorrectionConfig>,
 }
 
+#[derive(SettingsSchema, Serialize, Deserialize, Clone)]
+#[schema(collapsible)]
+pub struct DecoderRecoveryConfig {
+    #[schema(strings(
+        help = "After a decoder error, a new IDR frame is requested only if none arrived within this time"
+    ))]
+    #[schema(gui(slider(min = 50, max = 2000)), suffix = "ms")]
+    pub idr_request_timeout_ms: u64,
+
+    #[schema(strings(help = "Decoder errors are counted within this time window"))]
+    #[schema(gui(slider(min = 1.0, max = 30.0)), suffix = "s")]
+    pub error_window_s: f32,
+
+    #[schema(strings(
+        help = "The decoder is recreated when this many errors happen within the error window"
+    ))]
+    #[schema(gui(slider(min = 1, max = 10)))]
+    pub errors_before_recreation: u32,
+
+    #[schema(strings(
+        help = "The streamer is notified that the client decoder is failing after this many consecutive recreations"
+    ))]
+    #[schema(gui(slider(min = 1, max = 10)))]
+    pub recreations_before_report: u32,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone, Copy)]
+pub enum JitterBufferDepth {
+    Frames(#[schema(gui(slider(min = 1, max = 10)), suffix = " frames")] u32),
+    Milliseconds(#[schema(gui(slider(min = 1, max = 100)), suffix = "ms")] u64),
+}
+
 #[derive(SettingsSchema, Serialize, Deserialize, Clone, Copy)]
 #[schema(gui = "button_group")]
 pub enum LinuxAudioBackend {
@@ -588,12 +697,19 @@ pub enum CustomAudioDeviceConfig {
     NameSubstring(String),
     #[schema(strings(display_name = "By index"))]
     Index(usize),
+    // Set by the device list of the dashboard. If no device has the ID anymore, the first device
+    // with the name is used
+    #[schema(strings(display_name = "By ID"))]
+    Id { id: String, name: String },
 }
 
 #[derive(SettingsSchema, Serialize, Deserialize, Clone)]
 #[schema(collapsible)]
 pub struct AudioBufferingConfig {
-    #[schema(strings(display_name = "Average buffering"))]
+    #[schema(strings(
+        display_name = "Average buffering",
+        help = "Target latency of the audio buffer. The buffer is slowly resized towards this value. Increase it if audio crackles."
+    ))]
     #[schema(gui(slider(min = 0, max = 200)), suffix = "ms")]
     pub average_buffering_ms: u64,
 
@@ -610,6 +726,7 @@ pub struct GameAudioConfig {
     #[schema(strings(display_name = "Mute desktop audio when streaming"))]
     pub mute_when_streaming: bool,
 
+    #[schema(flag = "advanced")]
     pub buffering: AudioBufferingConfig,
 }
 
@@ -638,6 +755,14 @@ pub enum MicrophoneDevicesConfig {
 #[schema(collapsible)]
 pub struct MicrophoneConfig {
     pub devices: MicrophoneDevicesConfig,
+
+    #[schema(strings(
+        help = "Applied on the headset before the audio is sent. 0 mutes the microphone"
+    ))]
+    #[schema(gui(slider(min = 0.0, max = 4.0, step = 0.05)))]
+    pub gain: f32,
+
+    #[schema(flag = "advanced")]
     pub buffering: AudioBufferingConfig,
 }
 
@@ -710,6 +835,37 @@ pub enum BodyTrackingSinkConfig {
     VrchatBodyOsc { port: u16 },
 }
 
+#[derive(SettingsSchema, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum PoseOscFormat {
+    #[schema(strings(
+        display_name = "Quaternion",
+        help = "Position x, y, z in meters, then orientation quaternion x, y, z, w"
+    ))]
+    Quaternion,
+    #[schema(strings(
+        display_name = "Euler angles",
+        help = "Position x, y, z in meters, then pitch, yaw and roll in degrees"
+    ))]
+    EulerAngles,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone)]
+#[schema(collapsible)]
+pub struct PoseOscConfig {
+    #[schema(strings(help = "IP address or hostname of the program receiving the messages"))]
+    pub host: String,
+
+    pub port: u16,
+
+    #[schema(gui(slider(min = 1.0, max = 120.0, step = 1.0)), suffix = "Hz")]
+    pub send_rate_hz: f32,
+
+    pub format: PoseOscFormat,
+
+    #[schema(strings(help = "Also send the battery levels, when the client reports them"))]
+    pub battery: bool,
+}
+
 #[derive(SettingsSchema, Serialize, Deserialize, Clone)]
 #[schema(collapsible)]
 pub struct BodyTrackingConfig {
@@ -878,6 +1034,36 @@ pub struct HapticsConfig {
     pub min_duration_s: f32,
 }
 
+#[derive(SettingsSchema, Serialize, Deserialize, Clone, Copy)]
+#[schema(collapsible)]
+pub struct ThumbstickConfig {
+    #[schema(strings(help = "Deflections below this fraction are reported as centered"))]
+    #[schema(flag = "real-time")]
+    #[schema(gui(slider(min = 0.0, max = 0.5, step = 0.01)))]
+    pub inner_deadzone: f32,
+
+    #[schema(strings(help = "Deflections above this fraction are reported as fully deflected"))]
+    #[schema(flag = "real-time")]
+    #[schema(gui(slider(min = 0.5, max = 1.0, step = 0.01)))]
+    pub outer_threshold: f32,
+
+    #[schema(strings(
+        help = "Values above 1 give finer control near the center, values below 1 make the stick more sensitive"
+    ))]
+    #[schema(flag = "real-time")]
+    #[schema(gui(slider(min = 0.2, max = 3.0, step = 0.05)))]
+    pub response_exponent: f32,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone, Copy)]
+pub struct ThumbsticksConfig {
+    #[schema(flag = "real-time")]
+    pub left: ThumbstickConfig,
+
+    #[schema(flag = "real-time")]
+    pub right: ThumbstickConfig,
+}
+
 #[derive(SettingsSchema, Serialize, Deserialize, Clone)]
 #[schema(collapsible)]
 pub struct ControllersConfig {
@@ -895,6 +1081,7 @@ pub struct ControllersConfig {
     pub emulation_mode: ControllersEmulationMode,
 
     #[schema(flag = "steamvr-restart")]
+    #[schema(flag = "advanced")]
     pub extra_openvr_props: Vec<OpenvrProperty>,
 
     #[schema(strings(help = "List of OpenXR-syle paths"))]
@@ -915,16 +1102,19 @@ Technically, this is the time (counted in frames) between pose submitted to Stea
 Currently this cannot be reliably estimated automatically. The correct value should be 2 but 3 is default for smoother tracking at the cost of slight lag."
     ))]
     #[schema(gui(slider(min = 1.0, max = 10.0, logarithmic)), suffix = "frames")]
+    #[schema(flag = "advanced")]
     pub steamvr_pipeline_frames: f32,
 
     #[schema(flag = "real-time")]
     // note: logarithmic scale seems to be glitchy for this control
     #[schema(gui(slider(min = 0.0, max = 1.0, step = 0.01)), suffix = "m/s")]
+    #[schema(flag = "advanced")]
     pub linear_velocity_cutoff: f32,
 
     #[schema(flag = "real-time")]
     // note: logarithmic scale seems to be glitchy for this control
     #[schema(gui(slider(min = 0.0, max = 100.0, step = 1.0)), suffix = "°/s")]
+    #[schema(flag = "advanced")]
     pub angular_velocity_cutoff: f32,
 
     #[schema(flag = "real-time")]
@@ -947,6 +1137,12 @@ Currently this cannot be reliably estimated automatically. The correct value sho
 
     #[schema(flag = "real-time")]
     pub haptics: Switch<HapticsConfig>,
+
+    #[schema(strings(
+        help = "Radial deadzone and response curve applied to the thumbsticks before they reach SteamVR"
+    ))]
+    #[schema(flag = "real-time")]
+    pub thumbsticks: Switch<ThumbsticksConfig>,
 }
 
 #[derive(SettingsSchema, Serialize, Deserialize, Clone, Copy)]
@@ -966,6 +1162,98 @@ pub enum RotationRecenteringMode {
     Tilted,
 }
 
+#[derive(SettingsSchema, Serialize, Deserialize, Clone)]
+pub struct PoseExtrapolationConfig {
+    #[schema(strings(
+        help = "Maximum time by which poses can be advanced. This avoids overshooting during latency spikes."
+    ))]
+    #[schema(gui(slider(min = 0.0, max = 50.0, step = 1.0)), suffix = "ms")]
+    pub max_prediction_ms: f32,
+}
+
+// Half-angles from the view direction, all positive
+#[derive(SettingsSchema, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct EyeFovConfig {
+    #[schema(suffix = "°")]
+    pub left_deg: f32,
+    #[schema(suffix = "°")]
+    pub right_deg: f32,
+    #[schema(suffix = "°")]
+    pub up_deg: f32,
+    #[schema(suffix = "°")]
+    pub down_deg: f32,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum FovOverride {
+    #[schema(strings(help = "Multiplies all the angles reported by the headset"))]
+    Scale(#[schema(gui(slider(min = 0.5, max = 1.5, step = 0.01)), suffix = "x")] f32),
+    Absolute {
+        left_eye: EyeFovConfig,
+        right_eye: EyeFovConfig,
+    },
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone, PartialEq)]
+#[schema(collapsible)]
+pub struct ViewsOverrideConfig {
+    #[schema(strings(display_name = "IPD"))]
+    #[schema(suffix = "mm")]
+    pub ipd_mm: Option<f32>,
+
+    #[schema(strings(display_name = "FOV"))]
+    pub fov: Option<FovOverride>,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ClientPosePredictionConfig {
+    #[schema(strings(
+        help = "Maximum time by which poses are extrapolated on the client. This avoids overshooting during latency spikes."
+    ))]
+    #[schema(gui(slider(min = 0.0, max = 100.0, step = 1.0)), suffix = "ms")]
+    pub max_prediction_ms: f32,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone, PartialEq)]
+pub struct IdleTrackingConfig {
+    #[schema(strings(
+        help = "Movements of the headset and controllers smaller than this are ignored when detecting idleness"
+    ))]
+    #[schema(gui(slider(min = 0.1, max = 10.0, step = 0.1)), suffix = "mm")]
+    pub position_threshold_mm: f32,
+
+    #[schema(gui(slider(min = 0.1, max = 5.0, step = 0.1)), suffix = "°")]
+    pub rotation_threshold_deg: f32,
+
+    #[schema(strings(
+        help = "Time without movement or button activity before the rate is reduced"
+    ))]
+    #[schema(gui(slider(min = 1.0, max = 60.0, step = 1.0)), suffix = "s")]
+    pub idle_timeout_s: f32,
+
+    #[schema(gui(slider(min = 1.0, max = 30.0, step = 1.0)), suffix = "Hz")]
+    pub idle_send_rate_hz: f32,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LowBatteryWarningsConfig {
+    #[schema(gui(slider(min = 1.0, max = 50.0, step = 1.0)), suffix = "%")]
+    pub low_threshold_percent: f32,
+
+    #[schema(gui(slider(min = 1.0, max = 50.0, step = 1.0)), suffix = "%")]
+    pub critical_threshold_percent: f32,
+
+    #[schema(strings(
+        help = "After a warning, the battery must charge this much above the threshold before the warning can be shown again"
+    ))]
+    #[schema(flag = "advanced")]
+    #[schema(gui(slider(min = 0.0, max = 10.0, step = 0.5)), suffix = "%")]
+    pub hysteresis_percent: f32,
+
+    #[schema(strings(help = "Also show the warnings in the headset while streaming"))]
+    pub notify_in_headset: bool,
+}
+
 #[derive(SettingsSchema, Serialize, Deserialize, Clone)]
 #[schema(collapsible)]
 pub struct HeadsetConfig {
@@ -973,12 +1261,15 @@ pub struct HeadsetConfig {
     pub emulation_mode: HeadsetEmulationMode,
 
     #[schema(flag = "steamvr-restart")]
+    #[schema(flag = "advanced")]
     pub extra_openvr_props: Vec<OpenvrProperty>,
 
     #[schema(flag = "steamvr-restart")]
+    #[schema(flag = "advanced")]
     pub tracking_ref_only: bool,
 
     #[schema(flag = "steamvr-restart")]
+    #[schema(flag = "advanced")]
     pub enable_vive_tracker_proxy: bool,
 
     pub face_tracking: Switch<FaceTrackingConfig>,
@@ -989,6 +1280,18 @@ pub struct HeadsetConfig {
     #[schema(flag = "steamvr-restart")]
     pub controllers: Switch<ControllersConfig>,
 
+    #[schema(strings(
+        display_name = "Forward gamepad",
+        help = "Forward a gamepad paired with the headset to the PC as a virtual Xbox controller. Games read it like a physical controller, it is not added to SteamVR as an OpenVR input device. On Windows this requires the ViGEmBus driver, on Linux write access to /dev/uinput. Applied on the next connection"
+    ))]
+    pub gamepad: bool,
+
+    #[schema(strings(
+        display_name = "OSC pose output",
+        help = "Send the poses of the headset and of the controllers as OSC messages over UDP, for VTubing software and other tools. The addresses are /alvr/head/pose, /alvr/controller/left/pose and /alvr/controller/right/pose, and the same with /battery for the battery levels (charge from 0 to 1, then whether the device is plugged). Applied on the next connection"
+    ))]
+    pub pose_osc: Switch<PoseOscConfig>,
+
     #[schema(strings(
         help = r#"Disabled: the playspace origin is determined by the room-scale guardian setup.
 Local floor: the origin is on the floor and resets when long pressing the oculus button.
@@ -1004,9 +1307,60 @@ Tilted: the world gets tilted when long pressing the oculus button. This is usef
     ))]
     #[schema(flag = "real-time")]
     pub rotation_recentering_mode: RotationRecenteringMode,
+
+    #[schema(strings(
+        display_name = "Recenter gesture",
+        help = "Recenter by holding both thumbsticks clicked for one second. The buttons are still sent to SteamVR"
+    ))]
+    pub recenter_gesture: bool,
+
+    #[schema(strings(
+        display_name = "Pause gesture",
+        help = "Pause or resume the stream by holding the left menu button for two seconds. While paused the lobby is shown and the tracking is still sent"
+    ))]
+    pub pause_gesture: bool,
+
+    #[schema(strings(
+        help = "Extrapolate device poses on the streamer using their velocities and the measured latency, to compensate for network jitter."
+    ))]
+    #[schema(flag = "real-time")]
+    #[schema(flag = "advanced")]
+    pub pose_extrapolation: Switch<PoseExtrapolationConfig>,
+
+    #[schema(strings(
+        help = "Sample the headset and controller poses on the client at the current time and extrapolate them to the expected display time using their velocities. When disabled, the poses predicted by the runtime are used."
+    ))]
+    #[schema(flag = "advanced")]
+    pub client_pose_prediction: Switch<ClientPosePredictionConfig>,
+
+    #[schema(strings(
+        help = "Send tracking at a reduced rate while the headset and controllers are still and no button is used. Full rate is restored on any movement or input. Disable this if a tool needs tracking at the full rate at all times."
+    ))]
+    #[schema(flag = "advanced")]
+    pub idle_tracking: Switch<IdleTrackingConfig>,
+
+    #[schema(strings(
+        help = "Send tracking on a separate timer at this rate. A higher rate gives the server more samples to predict and match the poses of each frame. When disabled, three samples are sent per display refresh, which uses less battery"
+    ))]
+    #[schema(flag = "advanced")]
+    #[schema(gui(slider(min = 72.0, max = 500.0, step = 1.0)), suffix = "Hz")]
+    pub tracking_send_rate_hz: Switch<f32>,
+
+    #[schema(strings(
+        help = "Warn when the battery of the headset or of a controller drops below these levels. Devices that are charging are not reported"
+    ))]
+    #[schema(flag = "real-time")]
+    pub low_battery_warnings: Switch<LowBatteryWarningsConfig>,
+
+    #[schema(strings(
+        help = "Replace the IPD and FOV reported by the headset, to correct scale or distortion issues. Unset values are taken from the headset."
+    ))]
+    #[schema(flag = "real-time")]
+    #[schema(flag = "advanced")]
+    pub views_override: ViewsOverrideConfig,
 }
 
-#[derive(SettingsSchema, Serialize, Deserialize, Clone)]
+#[derive(SettingsSchema, Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
 #[schema(gui = "button_group")]
 pub enum SocketProtocol {
     #[schema(strings(display_name = "UDP"))]
@@ -1018,9 +1372,26 @@ pub enum SocketProtocol {
 #[derive(SettingsSchema, Serialize, Deserialize, Clone)]
 pub struct DiscoveryConfig {
     #[schema(strings(
-        help = "Allow untrusted clients to connect without confirmation. This is not recommended for security reasons."
+        display_name = "Automatically trust new clients",
+        help = "Trust and connect to any client discovered on the network without confirmation. A notification is shown for every client trusted this way. This is not recommended for security reasons."
     ))]
     pub auto_trust_clients: bool,
+
+    #[schema(strings(
+        display_name = "Verify new clients with a code",
+        help = r#"When trusting a discovered client from the dashboard, a code is shown next to it and must be entered in the headset before the client is trusted. This prevents other devices on the network from posing as the headset.
+The code expires after a minute, and the pairing is cancelled after 3 wrong codes. Clients older than this streamer cannot enter the code and are never trusted while this is enabled."#
+    ))]
+    pub pin_verification: bool,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone)]
+pub struct WiredConnectionConfig {
+    #[schema(strings(
+        display_name = "Trust clients connected via USB",
+        help = "A headset can be reached through adb only after allowing USB debugging for this PC in the headset, so it is trusted without confirmation"
+    ))]
+    pub trust_clients: bool,
 }
 
 #[derive(SettingsSchema, Serialize, Deserialize, Clone)]
@@ -1030,6 +1401,105 @@ pub enum SocketBufferSize {
     Custom(#[schema(suffix = "B")] u32),
 }
 
+#[derive(SettingsSchema, Serialize, Deserialize, Clone)]
+pub struct ProximityStandbyConfig {
+    #[schema(strings(help = "Time the headset must be off before the stream is paused"))]
+    #[schema(gui(slider(min = 0.0, max = 60.0)), suffix = "s")]
+    pub timeout_s: f32,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone, Copy)]
+#[schema(gui = "button_group")]
+pub enum JitterDistribution {
+    // Between 0 and the jitter
+    Uniform,
+    // Half normal, with the jitter as standard deviation
+    Normal,
+    // With the jitter as mean. Long tail, like the retransmissions of a busy Wi-Fi network
+    Exponential,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone)]
+pub struct BurstLossConfig {
+    #[schema(strings(help = "Average number of shards sent between two loss episodes"))]
+    #[schema(gui(slider(min = 100, max = 100000, logarithmic)))]
+    pub mean_shards_between_episodes: u32,
+
+    #[schema(strings(help = "Average number of consecutive shards lost in an episode"))]
+    #[schema(gui(slider(min = 1, max = 2000, logarithmic)))]
+    pub mean_episode_shards: u32,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone)]
+#[schema(collapsible)]
+pub struct NetworkImpairmentConfig {
+    #[schema(gui(slider(min = 0.0, max = 200.0)), suffix = "ms")]
+    pub latency_ms: f32,
+
+    #[schema(gui(slider(min = 0.0, max = 100.0)), suffix = "ms")]
+    pub jitter_ms: f32,
+
+    pub jitter_distribution: JitterDistribution,
+
+    #[schema(gui(slider(min = 0.0, max = 20.0, step = 0.1)), suffix = "%")]
+    pub loss_percent: f32,
+
+    #[schema(strings(
+        help = "Shards are delayed to fit the bandwidth. Like with a full socket buffer, sending blocks once the link is more than 500ms behind"
+    ))]
+    #[schema(gui(slider(min = 1.0, max = 1000.0, logarithmic)), suffix = "Mbps")]
+    pub bandwidth_mbps: Switch<f32>,
+
+    pub burst_loss: Switch<BurstLossConfig>,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone)]
+pub struct NetworkSimulatorConfig {
+    #[schema(strings(
+        help = "The same seed reproduces the same sequence of delays and losses for the same traffic"
+    ))]
+    pub seed: u64,
+
+    pub video: Switch<NetworkImpairmentConfig>,
+    pub game_audio: Switch<NetworkImpairmentConfig>,
+    pub haptics: Switch<NetworkImpairmentConfig>,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone)]
+pub struct UdpVideoConfig {
+    #[schema(strings(help = "UDP port used by the client to receive the video"))]
+    pub port: u16,
+
+    #[schema(strings(
+        help = r#"Size of the UDP payload of each datagram. There is no path MTU discovery: with a 1500 bytes MTU the maximum is 1472, lower this on VPNs and other links with a smaller MTU.
+Must be the same on the client and the streamer, changes apply on reconnection."#
+    ))]
+    #[schema(gui(slider(min = 512, max = 8972, logarithmic)), suffix = "B")]
+    pub max_datagram_size: u32,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone)]
+#[schema(collapsible)]
+pub struct VideoPacingConfig {
+    #[schema(strings(
+        help = "Send rate relative to the encoder bitrate. Lower values spread the frames more, adding latency"
+    ))]
+    #[schema(gui(slider(min = 1.0, max = 4.0, step = 0.1)), suffix = "x")]
+    pub bitrate_multiplier: f32,
+
+    #[schema(strings(
+        help = "Keyframes are much larger than the other frames, a higher rate avoids delaying them by several frames"
+    ))]
+    #[schema(gui(slider(min = 1.0, max = 10.0, step = 0.5)), suffix = "x")]
+    pub keyframe_bitrate_multiplier: f32,
+
+    #[schema(strings(
+        help = "Maximum time spent sending a frame, as a fraction of the frame interval. Larger frames are sent faster"
+    ))]
+    #[schema(gui(slider(min = 0.1, max = 1.0, step = 0.05)))]
+    pub max_frame_interval_fraction: f32,
+}
+
 #[derive(SettingsSchema, Serialize, Deserialize, Clone)]
 #[schema(collapsible)]
 pub struct ConnectionConfig {
@@ -1041,42 +1511,117 @@ TCP: Slower than UDP, but more stable. Pick this if you experience video or audi
 
     pub client_discovery: Switch<DiscoveryConfig>,
 
+    #[schema(strings(
+        display_name = "Wired connection (USB)",
+        help = r#"Forwards the control and stream ports to the headset connected via USB with adb, which must be installed and in the PATH. The streamer then connects to the headset through the cable before looking for clients on the network.
+The wired connection always uses the TCP stream protocol, adb cannot forward UDP."#
+    ))]
+    pub wired_connection: Switch<WiredConnectionConfig>,
+
+    #[schema(strings(
+        help = r#"TCP port used by the client for the control connection. The discovery uses the discovery port.
+Clients found by discovery announce the port they listen on. Manual IPs and the wired connection use this port, which the client adopts after it is found by discovery once."#
+    ))]
+    #[schema(flag = "steamvr-restart")]
+    #[schema(flag = "advanced")]
+    pub control_port: u16,
+
+    #[schema(strings(
+        help = r#"UDP port the streamer listens on for the clients announcing themselves on the local network. Clients always announce on the port 9943, change it only if another application uses that port. Clients are then found only through mDNS, by manual IP or with the wired connection."#
+    ))]
+    #[schema(flag = "steamvr-restart")]
+    #[schema(flag = "advanced")]
+    pub discovery_port: u16,
+
     pub stream_port: u16,
     pub web_server_port: u16,
+    #[schema(flag = "advanced")]
     pub osc_local_port: u16,
 
+    #[schema(strings(
+        display_name = "Bind to network interface",
+        help = r#"Name or local IP address of the network interface used to connect to the client and stream.
+If the interface has no address, the interface is selected automatically."#
+    ))]
+    pub bind_interface: Switch<String>,
+
+    #[schema(flag = "advanced")]
     pub dscp: Option<DscpTos>,
 
     #[schema(strings(display_name = "Streamer send buffer size"))]
+    #[schema(flag = "advanced")]
     pub server_send_buffer_bytes: SocketBufferSize,
 
     #[schema(strings(display_name = "Streamer receive buffer size"))]
+    #[schema(flag = "advanced")]
     pub server_recv_buffer_bytes: SocketBufferSize,
 
     #[schema(strings(display_name = "Client send buffer size"))]
+    #[schema(flag = "advanced")]
     pub client_send_buffer_bytes: SocketBufferSize,
 
     #[schema(strings(display_name = "Client receive buffer size"))]
+    #[schema(flag = "advanced")]
     pub client_recv_buffer_bytes: SocketBufferSize,
 
     #[schema(strings(
         help = r#"The server discards video packets if it can't push them to the network.
 This could happen on TCP. A IDR frame is requested in this case."#
     ))]
+    #[schema(flag = "advanced")]
     pub max_queued_server_video_frames: usize,
 
+    #[schema(strings(
+        help = r#"Spread the video packets of each frame over time instead of sending them in a single burst, which can overflow the buffers of some routers and access points.
+The added latency is shown as "Pacing" in the latency graph."#
+    ))]
+    #[schema(flag = "real-time")]
+    #[schema(flag = "advanced")]
+    pub video_pacing: Switch<VideoPacingConfig>,
+
+    #[schema(strings(
+        display_name = "Send video over UDP",
+        help = r#"Used only if the stream protocol is TCP. The video is sent as datagrams, frames with lost datagrams are dropped instead of being retransmitted late.
+If no datagram reaches the client shortly after the stream starts, the video is sent over TCP again."#
+    ))]
+    #[schema(flag = "advanced")]
+    pub udp_video: Switch<UdpVideoConfig>,
+
+    #[schema(strings(
+        display_name = "Network simulator (developer)",
+        help = r#"Degrades the streams sent by the streamer with added latency, jitter, loss and a bandwidth cap. Meant to reproduce network issues, keep disabled otherwise.
+The video settings also apply to the video sent over UDP datagrams. Applied at the next connection."#
+    ))]
+    #[schema(flag = "advanced")]
+    pub network_simulator: Switch<NetworkSimulatorConfig>,
+
     #[schema(strings(
         help = r#"If the client, server or the network discarded one packet, discard packets until a IDR packet is found.
 For now works only on Windows+Nvidia"#
     ))]
+    #[schema(flag = "advanced")]
     pub avoid_video_glitching: bool,
 
     #[schema(strings(
         help = "Reduce minimum delay between IDR keyframes from 100ms to 5ms. Use on networks with high packet loss."
     ))]
     #[schema(flag = "steamvr-restart")]
+    #[schema(flag = "advanced")]
     pub aggressive_keyframe_resend: bool,
 
+    #[schema(strings(
+        help = "Pause video and audio while the headset is not being worn. SteamVR is notified so games can pause too"
+    ))]
+    #[schema(flag = "real-time")]
+    pub proximity_standby: Switch<ProximityStandbyConfig>,
+
+    #[schema(strings(
+        help = "After an unexpected disconnection, the client keeps the stream open and tries to reconnect to the same streamer for this long before going back to the lobby"
+    ))]
+    #[schema(gui(slider(min = 1.0, max = 60.0)), suffix = "s")]
+    #[schema(flag = "advanced")]
+    pub fast_reconnection_timeout_s: Switch<f32>,
+
     #[schema(strings(
         help = "This script will be ran when the headset connects. Env var ACTION will be set to `connect`."
     ))]
@@ -1089,10 +1634,34 @@ For now works only on Windows+Nvidia"#
     pub on_disconnect_script: String,
 
     #[schema(gui(slider(min = 1024, max = 65507, logarithmic)), suffix = "B")]
+    #[schema(flag = "advanced")]
     pub packet_size: i32,
 
     #[schema(suffix = " frames")]
+    #[schema(flag = "advanced")]
     pub statistics_history_size: usize,
+
+    #[schema(strings(
+        help = "Rate of the statistics sent to the dashboard graphs and to the events API"
+    ))]
+    #[schema(flag = "advanced")]
+    #[schema(gui(slider(min = 1.0, max = 60.0, step = 1.0)), suffix = "Hz")]
+    pub statistics_event_rate: f32,
+
+    #[schema(strings(
+        help = r#"Send the tracking data as changes from periodic full packets. Devices that are not moving are left out, which reduces the upload bandwidth.
+Positions are rounded to 0.1 mm. Older clients keep sending full packets."#
+    ))]
+    #[schema(flag = "advanced")]
+    pub tracking_delta_encoding: bool,
+
+    #[schema(strings(
+        display_name = "Accept incompatible client versions (dangerous)",
+        help = r#"For development only. Clients with a different protocol ID are allowed to connect.
+Packets that changed between the two versions fail to decode or are silently misread, which can cause crashes or erratic tracking."#
+    ))]
+    #[schema(flag = "advanced")]
+    pub accept_incompatible_clients: bool,
 }
 
 #[derive(SettingsSchema, Serialize, Deserialize, Clone)]
@@ -1119,37 +1688,142 @@ pub enum DscpTos {
     ExpeditedForwarding,
 }
 
+// Unlike LogSeverity, this includes the trace level used by some dependencies
+#[derive(SettingsSchema, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogLevel {
+    Error,
+    Warning,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    // Events and log entries carry a LogSeverity, trace messages are reported as debug
+    pub fn min_severity(self) -> LogSeverity {
+        match self {
+            LogLevel::Error => LogSeverity::Error,
+            LogLevel::Warning => LogSeverity::Warning,
+            LogLevel::Info => LogSeverity::Info,
+            LogLevel::Debug | LogLevel::Trace => LogSeverity::Debug,
+        }
+    }
+}
+
 #[derive(SettingsSchema, Serialize, Deserialize, Clone)]
 pub struct RawEventsConfig {
     #[schema(flag = "real-time")]
     pub hide_spammy_events: bool,
 }
 
+#[derive(SettingsSchema, Serialize, Deserialize, Clone)]
+pub struct EventLogConfig {
+    #[schema(flag = "real-time")]
+    pub min_severity: LogSeverity,
+
+    #[schema(strings(help = "The file is rotated when it grows past this size"))]
+    #[schema(gui(slider(min = 1, max = 100)), suffix = "MB")]
+    pub max_file_size_mb: u64,
+
+    #[schema(strings(help = "Number of rotated files kept besides the current one"))]
+    #[schema(gui(slider(min = 0, max = 20)), suffix = " files")]
+    pub kept_files: usize,
+
+    #[schema(strings(
+        help = "The oldest rotated files are deleted when all the event files together take more than this"
+    ))]
+    #[schema(gui(slider(min = 10, max = 1000, step = 10)), suffix = "MB")]
+    pub max_total_size_mb: u64,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum StatisticsOverlayAnchor {
+    LeftWrist,
+    RightWrist,
+    ViewCorner,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone)]
+pub struct StatisticsOverlayConfig {
+    pub anchor: StatisticsOverlayAnchor,
+
+    #[schema(strings(
+        help = "Press both thumbsticks at the same time to show or hide the overlay while streaming"
+    ))]
+    pub visible_at_start: bool,
+}
+
 #[derive(SettingsSchema, Serialize, Deserialize, Clone)]
 #[schema(collapsible)]
 pub struct LoggingConfig {
+    #[schema(strings(
+        help = "Minimum severity of the client log lines that are forwarded to the streamer and shown in the logs"
+    ))]
     pub client_log_report_level: Switch<LogSeverity>,
 
     #[schema(strings(help = "Write logs into the session_log.txt file."))]
     pub log_to_disk: bool,
 
+    #[schema(strings(
+        help = "Write the events as JSON lines into events.jsonl (alvr_events.jsonl on Linux) in the logs directory. Unlike the session log, older files are kept across restarts"
+    ))]
+    #[schema(flag = "steamvr-restart")]
+    pub event_log: Switch<EventLogConfig>,
+
+    #[schema(strings(
+        display_name = "Streamer log level",
+        help = "Minimum severity of the streamer messages written to the session log"
+    ))]
     #[schema(flag = "real-time")]
+    pub server_log_level: LogLevel,
+
+    #[schema(strings(
+        help = "Minimum severity of the streamer messages forwarded to the dashboard logs and notifications. Other events are always forwarded, the logs tab filters them by their own severity."
+    ))]
+    #[schema(flag = "real-time")]
+    pub dashboard_log_level: LogLevel,
+
+    #[schema(flag = "real-time")]
+    #[schema(flag = "advanced")]
     pub log_tracking: bool,
 
     #[schema(flag = "real-time")]
+    #[schema(flag = "advanced")]
     pub log_button_presses: bool,
 
     #[schema(flag = "real-time")]
+    #[schema(flag = "advanced")]
     pub log_haptics: bool,
 
+    #[schema(strings(
+        help = "Sends the input of the streaming client ten times per second to the input tab of the dashboard, before and after the processing of the streamer"
+    ))]
+    #[schema(flag = "real-time")]
+    #[schema(flag = "advanced")]
+    pub input_debug_events: bool,
+
     #[schema(flag = "real-time")]
     pub notification_level: LogSeverity,
 
     #[schema(flag = "real-time")]
+    #[schema(flag = "advanced")]
     pub show_raw_events: Switch<RawEventsConfig>,
 
+    #[schema(strings(
+        help = "Maximum number of entries kept in the dashboard logs tab. The oldest entries are discarded first."
+    ))]
+    #[schema(flag = "real-time")]
+    #[schema(suffix = " entries")]
+    pub dashboard_log_limit: usize,
+
+    #[schema(strings(
+        help = "Show latency, bitrate, packet loss and battery statistics inside the headset"
+    ))]
+    pub statistics_overlay: Switch<StatisticsOverlayConfig>,
+
     #[schema(strings(help = "This applies only to certain error or warning messages."))]
     #[schema(flag = "steamvr-restart")]
+    #[schema(flag = "advanced")]
     pub prefer_backtrace: bool,
 
     #[schema(strings(help = "Notification tips teach you how to use ALVR"))]
@@ -1194,6 +1868,22 @@ pub struct CaptureConfig {
 
     pub rolling_video_files: Switch<RollingVideoFilesConfig>,
 
+    #[schema(strings(
+        display_name = "Start timing capture at client connection",
+        help = "Writes the received tracking and the timings of each frame to CSV files in the log folder"
+    ))]
+    pub startup_timing_capture: bool,
+
+    #[schema(strings(help = "The timing capture stops when its files reach this size"))]
+    #[schema(gui(slider(min = 10, max = 2000, step = 10)), suffix = "MB")]
+    pub timing_capture_max_size_mb: u64,
+
+    #[schema(strings(
+        display_name = "Record the client tracking",
+        help = "Writes the tracking and buttons sent by the client to a file on the headset, in the recordings folder next to the client configuration. The file can be replayed with the mock client (--replay) to test the streamer without the headset"
+    ))]
+    pub client_tracking_recording: bool,
+
     #[schema(flag = "steamvr-restart")]
     pub capture_frame_dir: String,
 }
@@ -1213,6 +1903,99 @@ pub struct Patches {
     pub linux_async_reprojection: bool,
 }
 
+#[derive(SettingsSchema, Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[schema(gui = "button_group")]
+pub enum InvalidSettingsAction {
+    #[schema(strings(display_name = "Reject the change"))]
+    RejectChange,
+    #[schema(strings(display_name = "Revert the invalid values"))]
+    RevertInvalidValues,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[schema(gui = "button_group")]
+pub enum DashboardTheme {
+    System,
+    Light,
+    Dark,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone)]
+#[schema(collapsible)]
+pub struct ToastsConfig {
+    #[schema(strings(help = "A client that is not trusted yet was discovered"))]
+    pub client_found: bool,
+
+    pub client_connected: bool,
+    pub stream_started: bool,
+
+    #[schema(strings(help = "A client was rejected because of a different protocol version"))]
+    pub incompatible_client: bool,
+
+    #[schema(strings(help = "Error toasts stay visible until they are clicked"))]
+    pub errors: bool,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SettingOverride {
+    #[schema(strings(
+        help = "Path of the setting as shown in the validation messages, like video.bitrate.mode.ConstantMbps"
+    ))]
+    pub path: String,
+    #[schema(strings(help = "JSON value, like 100, true or \"Light\""))]
+    pub value: String,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ApplicationProfile {
+    pub name: String,
+    #[schema(strings(
+        help = "SteamVR application key, like steam.app.620980. The key of the running application is shown in the Settings tab while streaming"
+    ))]
+    pub app_key: String,
+    #[schema(strings(
+        help = "Only settings that are applied in real time can be overridden. A setting changed manually while the profile is active keeps the manual value until the profile is deactivated"
+    ))]
+    pub overrides: Vec<SettingOverride>,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone)]
+pub struct ApplicationProfilesConfig {
+    pub profiles: Vec<ApplicationProfile>,
+    #[schema(strings(help = "Name of the profile to use regardless of the running application"))]
+    pub manual_profile: Option<String>,
+}
+
+#[derive(SettingsSchema, Serialize, Deserialize, Clone)]
+#[schema(collapsible)]
+pub struct DashboardConfig {
+    #[schema(strings(help = "System follows the light or dark mode of the operating system"))]
+    #[schema(flag = "real-time")]
+    pub theme: DashboardTheme,
+
+    #[schema(strings(help = "Red, green and blue components of the color of selected items"))]
+    #[schema(flag = "real-time")]
+    pub accent_color: [u8; 3],
+
+    #[schema(strings(
+        help = "Language code of the dashboard, for example en. Translations are read from the languages folder, missing text is shown in English"
+    ))]
+    #[schema(flag = "real-time")]
+    pub language: String,
+
+    #[schema(strings(
+        help = "Kinds of events shown as popups. Each kind can also be muted from its popup"
+    ))]
+    #[schema(flag = "real-time")]
+    pub toasts: ToastsConfig,
+
+    #[schema(strings(
+        help = "Show the settings that are rarely changed. Search always looks through all settings"
+    ))]
+    #[schema(flag = "real-time")]
+    pub show_advanced_settings: bool,
+}
+
 #[derive(SettingsSchema, Serialize, Deserialize, Clone)]
 pub struct Settings {
     pub video: VideoConfig,
@@ -1222,8 +2005,25 @@ pub struct Settings {
     pub logging: LoggingConfig,
     pub steamvr_launcher: SteamvrLauncher,
     pub capture: CaptureConfig,
+    #[schema(strings(
+        help = "Overrides for some settings, applied while a SteamVR application is running"
+    ))]
+    #[schema(flag = "real-time")]
+    pub application_profiles: Switch<ApplicationProfilesConfig>,
+    #[schema(flag = "advanced")]
     pub patches: Patches,
+    pub dashboard: DashboardConfig,
     pub open_setup_wizard: bool,
+    #[schema(strings(
+        help = "Check once per launch if a newer release is published on GitHub. The result is shown in the About tab."
+    ))]
+    pub check_for_updates: bool,
+    #[schema(strings(
+        help = "What happens when a change makes the settings invalid, like a bitrate of 0. The other values of the same change are kept when only the invalid values are reverted. Only the errors caused by the change count. At startup, the invalid values are always reset to their default."
+    ))]
+    #[schema(flag = "real-time")]
+    #[schema(flag = "advanced")]
+    pub invalid_settings_action: InvalidSettingsAction,
 }
 
 pub fn session_settings_default() -> SettingsDefault {
@@ -1241,6 +2041,10 @@ pub fn session_settings_default() -> SettingsDefault {
     let default_custom_audio_device = CustomAudioDeviceConfigDefault {
         NameSubstring: "".into(),
         Index: 0,
+        Id: CustomAudioDeviceConfigIdDefault {
+            id: "".into(),
+            name: "".into(),
+        },
         variant: CustomAudioDeviceConfigDefaultVariant::NameSubstring,
     };
     let default_custom_openvr_props = VectorDefault {
@@ -1252,6 +2056,26 @@ pub fn session_settings_default() -> SettingsDefault {
         Custom: 100000,
         variant: SocketBufferSizeDefaultVariant::Maximum,
     };
+    let network_impairment = NetworkImpairmentConfigDefault {
+        gui_collapsed: false,
+        latency_ms: 20.0,
+        jitter_ms: 5.0,
+        jitter_distribution: JitterDistributionDefault {
+            variant: JitterDistributionDefaultVariant::Exponential,
+        },
+        loss_percent: 0.5,
+        bandwidth_mbps: SwitchDefault {
+            enabled: false,
+            content: 100.0,
+        },
+        burst_loss: SwitchDefault {
+            enabled: false,
+            content: BurstLossConfigDefault {
+                mean_shards_between_episodes: 20000,
+                mean_episode_shards: 200,
+            },
+        },
+    };
 
     SettingsDefault {
         video: VideoConfigDefault {
@@ -1306,6 +2130,24 @@ pub fn session_settings_default() -> SettingsDefault {
                         framerate_reset_threshold_multiplier: 2.0,
                     },
                 },
+                ramp_up: SwitchDefault {
+                    enabled: false,
+                    content: BitrateRampUpConfigDefault {
+                        gui_collapsed: true,
+                        initial_fraction: 0.3,
+                        duration_s: 5.0,
+                        max_network_latency_ms: 10,
+                    },
+                },
+                thermal_mitigation: ThermalMitigationConfigDefault {
+                    gui_collapsed: true,
+                    automatic: true,
+                    bitrate_reduction_per_level: 0.2,
+                    resolution_step_down: SwitchDefault {
+                        enabled: false,
+                        content: 0.75,
+                    },
+                },
                 history_size: 256,
                 image_corruption_fix: false,
             },
@@ -1324,6 +2166,7 @@ pub fn session_settings_default() -> SettingsDefault {
                 entropy_coding: EntropyCodingDefault {
                     variant: EntropyCodingDefaultVariant::Cavlc,
                 },
+                slice_count: 1,
                 use_10bit: false,
                 use_full_range: true,
                 encoding_gamma: 1.0,
@@ -1436,6 +2279,21 @@ pub fn session_settings_default() -> SettingsDefault {
                     vertical_offset_deg: 0.0,
                 },
             },
+            decoder_recovery: DecoderRecoveryConfigDefault {
+                gui_collapsed: true,
+                idr_request_timeout_ms: 500,
+                error_window_s: 5.0,
+                errors_before_recreation: 3,
+                recreations_before_report: 3,
+            },
+            jitter_buffer: SwitchDefault {
+                enabled: false,
+                content: JitterBufferDepthDefault {
+                    Frames: 2,
+                    Milliseconds: 20,
+                    variant: JitterBufferDepthDefaultVariant::Frames,
+                },
+            },
             force_software_decoder: false,
             color_correction: SwitchDefault {
                 enabled: true,
@@ -1481,6 +2339,7 @@ pub fn session_settings_default() -> SettingsDefault {
                         },
                         variant: MicrophoneDevicesConfigDefaultVariant::Automatic,
                     },
+                    gain: 1.0,
                     buffering: AudioBufferingConfigDefault {
                         gui_collapsed: true,
                         average_buffering_ms: 50,
@@ -1645,6 +2504,37 @@ pub fn session_settings_default() -> SettingsDefault {
                             min_duration_s: 0.01,
                         },
                     },
+                    thumbsticks: SwitchDefault {
+                        enabled: false,
+                        content: ThumbsticksConfigDefault {
+                            left: ThumbstickConfigDefault {
+                                gui_collapsed: false,
+                                inner_deadzone: 0.1,
+                                outer_threshold: 0.95,
+                                response_exponent: 1.0,
+                            },
+                            right: ThumbstickConfigDefault {
+                                gui_collapsed: false,
+                                inner_deadzone: 0.1,
+                                outer_threshold: 0.95,
+                                response_exponent: 1.0,
+                            },
+                        },
+                    },
+                },
+            },
+            gamepad: false,
+            pose_osc: SwitchDefault {
+                enabled: false,
+                content: PoseOscConfigDefault {
+                    gui_collapsed: true,
+                    host: "127.0.0.1".into(),
+                    port: 9010,
+                    send_rate_hz: 60.0,
+                    format: PoseOscFormatDefault {
+                        variant: PoseOscFormatDefaultVariant::Quaternion,
+                    },
+                    battery: true,
                 },
             },
             position_recentering_mode: PositionRecenteringModeDefault {
@@ -1654,6 +2544,70 @@ pub fn session_settings_default() -> SettingsDefault {
             rotation_recentering_mode: RotationRecenteringModeDefault {
                 variant: RotationRecenteringModeDefaultVariant::Yaw,
             },
+            recenter_gesture: false,
+            pause_gesture: false,
+            pose_extrapolation: SwitchDefault {
+                enabled: false,
+                content: PoseExtrapolationConfigDefault {
+                    max_prediction_ms: 20.0,
+                },
+            },
+            client_pose_prediction: SwitchDefault {
+                enabled: false,
+                content: ClientPosePredictionConfigDefault {
+                    max_prediction_ms: 50.0,
+                },
+            },
+            idle_tracking: SwitchDefault {
+                enabled: true,
+                content: IdleTrackingConfigDefault {
+                    position_threshold_mm: 1.0,
+                    rotation_threshold_deg: 0.5,
+                    idle_timeout_s: 5.0,
+                    idle_send_rate_hz: 4.0,
+                },
+            },
+            tracking_send_rate_hz: SwitchDefault {
+                enabled: true,
+                content: 250.0,
+            },
+            low_battery_warnings: SwitchDefault {
+                enabled: true,
+                content: LowBatteryWarningsConfigDefault {
+                    low_threshold_percent: 15.0,
+                    critical_threshold_percent: 5.0,
+                    hysteresis_percent: 3.0,
+                    notify_in_headset: true,
+                },
+            },
+            views_override: ViewsOverrideConfigDefault {
+                gui_collapsed: true,
+                ipd_mm: OptionalDefault {
+                    set: false,
+                    content: 63.0,
+                },
+                fov: OptionalDefault {
+                    set: false,
+                    content: FovOverrideDefault {
+                        variant: FovOverrideDefaultVariant::Scale,
+                        Scale: 1.0,
+                        Absolute: FovOverrideAbsoluteDefault {
+                            left_eye: EyeFovConfigDefault {
+                                left_deg: 52.0,
+                                right_deg: 45.0,
+                                up_deg: 50.0,
+                                down_deg: 55.0,
+                            },
+                            right_eye: EyeFovConfigDefault {
+                                left_deg: 45.0,
+                                right_deg: 52.0,
+                                up_deg: 50.0,
+                                down_deg: 55.0,
+                            },
+                        },
+                    },
+                },
+            },
         },
         connection: ConnectionConfigDefault {
             gui_collapsed: false,
@@ -1663,12 +2617,25 @@ pub fn session_settings_default() -> SettingsDefault {
             client_discovery: SwitchDefault {
                 enabled: true,
                 content: DiscoveryConfigDefault {
-                    auto_trust_clients: cfg!(debug_assertions),
+                    auto_trust_clients: false,
+                    pin_verification: false,
+                },
+            },
+            wired_connection: SwitchDefault {
+                enabled: false,
+                content: WiredConnectionConfigDefault {
+                    trust_clients: true,
                 },
             },
             web_server_port: 8082,
+            control_port: 9943,
+            discovery_port: 9943,
             stream_port: 9944,
             osc_local_port: 9942,
+            bind_interface: SwitchDefault {
+                enabled: false,
+                content: "".into(),
+            },
             dscp: OptionalDefault {
                 set: false,
                 content: DscpTosDefault {
@@ -1687,25 +2654,96 @@ pub fn session_settings_default() -> SettingsDefault {
             client_send_buffer_bytes: socket_buffer.clone(),
             client_recv_buffer_bytes: socket_buffer,
             max_queued_server_video_frames: 1024,
+            video_pacing: SwitchDefault {
+                enabled: false,
+                content: VideoPacingConfigDefault {
+                    gui_collapsed: true,
+                    bitrate_multiplier: 1.5,
+                    keyframe_bitrate_multiplier: 4.0,
+                    max_frame_interval_fraction: 0.5,
+                },
+            },
+            udp_video: SwitchDefault {
+                enabled: false,
+                content: UdpVideoConfigDefault {
+                    port: 9945,
+                    max_datagram_size: 1400,
+                },
+            },
+            network_simulator: SwitchDefault {
+                enabled: false,
+                content: NetworkSimulatorConfigDefault {
+                    seed: 0,
+                    video: SwitchDefault {
+                        enabled: true,
+                        content: network_impairment.clone(),
+                    },
+                    game_audio: SwitchDefault {
+                        enabled: false,
+                        content: network_impairment.clone(),
+                    },
+                    haptics: SwitchDefault {
+                        enabled: false,
+                        content: network_impairment,
+                    },
+                },
+            },
             avoid_video_glitching: false,
             aggressive_keyframe_resend: false,
+            proximity_standby: SwitchDefault {
+                enabled: false,
+                content: ProximityStandbyConfigDefault { timeout_s: 10.0 },
+            },
+            fast_reconnection_timeout_s: SwitchDefault {
+                enabled: true,
+                content: 15.0,
+            },
             on_connect_script: "".into(),
             on_disconnect_script: "".into(),
             packet_size: 1400,
             statistics_history_size: 256,
+            statistics_event_rate: 10.0,
+            tracking_delta_encoding: false,
+            accept_incompatible_clients: false,
         },
         logging: LoggingConfigDefault {
             gui_collapsed: false,
             client_log_report_level: SwitchDefault {
                 enabled: true,
                 content: LogSeverityDefault {
-                    variant: LogSeverityDefaultVariant::Error,
+                    variant: LogSeverityDefaultVariant::Warning,
                 },
             },
             log_to_disk: cfg!(debug_assertions),
+            event_log: SwitchDefault {
+                enabled: true,
+                content: EventLogConfigDefault {
+                    min_severity: LogSeverityDefault {
+                        variant: LogSeverityDefaultVariant::Info,
+                    },
+                    max_file_size_mb: 10,
+                    kept_files: 5,
+                    max_total_size_mb: 100,
+                },
+            },
+            server_log_level: LogLevelDefault {
+                variant: if cfg!(debug_assertions) {
+                    LogLevelDefaultVariant::Debug
+                } else {
+                    LogLevelDefaultVariant::Info
+                },
+            },
+            dashboard_log_level: LogLevelDefault {
+                variant: if cfg!(debug_assertions) {
+                    LogLevelDefaultVariant::Debug
+                } else {
+                    LogLevelDefaultVariant::Info
+                },
+            },
             log_button_presses: false,
             log_tracking: false,
             log_haptics: false,
+            input_debug_events: false,
             notification_level: LogSeverityDefault {
                 variant: if cfg!(debug_assertions) {
                     LogSeverityDefaultVariant::Info
@@ -1719,6 +2757,16 @@ pub fn session_settings_default() -> SettingsDefault {
                     hide_spammy_events: false,
                 },
             },
+            dashboard_log_limit: 5000,
+            statistics_overlay: SwitchDefault {
+                enabled: false,
+                content: StatisticsOverlayConfigDefault {
+                    anchor: StatisticsOverlayAnchorDefault {
+                        variant: StatisticsOverlayAnchorDefaultVariant::LeftWrist,
+                    },
+                    visible_at_start: true,
+                },
+            },
             prefer_backtrace: false,
             show_notification_tip: true,
         },
@@ -1736,17 +2784,69 @@ pub fn session_settings_default() -> SettingsDefault {
                 enabled: false,
                 content: RollingVideoFilesConfigDefault { duration_s: 5 },
             },
+            startup_timing_capture: false,
+            timing_capture_max_size_mb: 200,
+            client_tracking_recording: false,
             capture_frame_dir: if !cfg!(target_os = "linux") {
                 "/tmp".into()
             } else {
                 "".into()
             },
         },
+        application_profiles: SwitchDefault {
+            enabled: false,
+            content: ApplicationProfilesConfigDefault {
+                profiles: VectorDefault {
+                    gui_collapsed: false,
+                    element: ApplicationProfileDefault {
+                        name: "".into(),
+                        app_key: "".into(),
+                        overrides: VectorDefault {
+                            gui_collapsed: false,
+                            element: SettingOverrideDefault {
+                                path: "".into(),
+                                value: "".into(),
+                            },
+                            content: vec![],
+                        },
+                    },
+                    content: vec![],
+                },
+                manual_profile: OptionalDefault {
+                    set: false,
+                    content: "".into(),
+                },
+            },
+        },
         patches: PatchesDefault {
             gui_collapsed: false,
             linux_async_compute: false,
             linux_async_reprojection: false,
         },
+        dashboard: DashboardConfigDefault {
+            gui_collapsed: true,
+            theme: DashboardThemeDefault {
+                variant: DashboardThemeDefaultVariant::System,
+            },
+            accent_color: ArrayDefault {
+                gui_collapsed: true,
+                content: [0, 76, 176],
+            },
+            language: "en".into(),
+            toasts: ToastsConfigDefault {
+                gui_collapsed: true,
+                client_found: true,
+                client_connected: true,
+                stream_started: true,
+                incompatible_client: true,
+                errors: true,
+            },
+            show_advanced_settings: false,
+        },
         open_setup_wizard: alvr_common::is_stable() || alvr_common::is_nightly(),
+        check_for_updates: false,
+        invalid_settings_action: InvalidSettingsActionDefault {
+            variant: InvalidSettingsActionDefaultVariant::RejectChange,
+        },
     }
 }
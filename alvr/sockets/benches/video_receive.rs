@@ -0,0 +1,146 @@
+// Compares receiving video frames through the pooled buffers of a StreamReceiver, then copying the
+// payload into the destination buffer, with reading the payload directly into the destination
+// through a DirectStreamReceiver. Run with `cargo bench -p alvr_sockets`
+
+use alvr_session::{SocketBufferSize, SocketProtocol};
+use alvr_sockets::{OverflowPolicy, StreamSocket, StreamSocketBuilder};
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+const MAX_PACKET_SIZE: usize = 1400;
+const VIDEO_STREAM: u16 = 3;
+const FRAME_SIZE: usize = 300_000;
+const FRAMES_COUNT: u32 = 1000;
+
+fn connect(port: u16) -> (StreamSocket, StreamSocket) {
+    let localhost = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+    let builder = StreamSocketBuilder::listen_for_server(
+        TIMEOUT,
+        port,
+        SocketProtocol::Tcp,
+        None,
+        SocketBufferSize::Maximum,
+        SocketBufferSize::Maximum,
+    )
+    .unwrap();
+    let receiver_thread = thread::spawn(move || {
+        builder
+            .accept_from_server(localhost, port, MAX_PACKET_SIZE, TIMEOUT)
+            .unwrap()
+    });
+
+    let sender_socket = StreamSocketBuilder::connect_to_client(
+        TIMEOUT,
+        localhost,
+        localhost,
+        port,
+        SocketProtocol::Tcp,
+        None,
+        SocketBufferSize::Maximum,
+        SocketBufferSize::Maximum,
+        MAX_PACKET_SIZE,
+    )
+    .unwrap();
+
+    (sender_socket, receiver_thread.join().unwrap())
+}
+
+fn spawn_sender(socket: &StreamSocket) -> thread::JoinHandle<()> {
+    let mut sender = socket.request_stream::<u32>(VIDEO_STREAM);
+    let payload = vec![0xAB; FRAME_SIZE];
+
+    thread::spawn(move || {
+        for index in 0..FRAMES_COUNT {
+            let mut buffer = sender.get_buffer(&index).unwrap();
+            buffer
+                .get_range_mut(0, payload.len())
+                .copy_from_slice(&payload);
+            sender.send(buffer).unwrap();
+        }
+    })
+}
+
+fn spawn_receive_loop(
+    socket: StreamSocket,
+    is_running: &Arc<AtomicBool>,
+) -> thread::JoinHandle<()> {
+    let is_running = Arc::clone(is_running);
+    socket.spawn_receive_thread(move || is_running.load(Ordering::Relaxed), |_| ())
+}
+
+// Returns the bytes written into the destination
+fn receive_pooled(port: u16, destination: &mut [u8]) -> usize {
+    let (sender_socket, mut receiver_socket) = connect(port);
+    let mut receiver =
+        receiver_socket.subscribe_to_stream::<u32>(VIDEO_STREAM, 8, OverflowPolicy::Block);
+
+    let is_running = Arc::new(AtomicBool::new(true));
+    let receive_thread = spawn_receive_loop(receiver_socket, &is_running);
+    let sender_thread = spawn_sender(&sender_socket);
+
+    let mut written_bytes = 0;
+    for _ in 0..FRAMES_COUNT {
+        let data = receiver.recv(TIMEOUT).unwrap();
+        let (_, payload) = data.get().unwrap();
+        destination[..payload.len()].copy_from_slice(payload);
+        written_bytes += payload.len();
+    }
+
+    sender_thread.join().unwrap();
+    is_running.store(false, Ordering::Relaxed);
+    receive_thread.join().unwrap();
+
+    written_bytes
+}
+
+// Only the part of the payload in the first shard is copied by the reader, the receive loop writes
+// the rest directly into the destination
+fn receive_direct(port: u16, destination: &mut [u8]) -> usize {
+    let (sender_socket, mut receiver_socket) = connect(port);
+    let mut receiver = receiver_socket.subscribe_to_direct_stream::<u32>(VIDEO_STREAM);
+
+    let is_running = Arc::new(AtomicBool::new(true));
+    let receive_thread = spawn_receive_loop(receiver_socket, &is_running);
+    let sender_thread = spawn_sender(&sender_socket);
+
+    let mut written_bytes = 0;
+    for _ in 0..FRAMES_COUNT {
+        let packet = receiver.recv_header(TIMEOUT).unwrap();
+        written_bytes += receiver
+            .recv_payload_into(&mut destination[..packet.max_payload_size], TIMEOUT)
+            .unwrap();
+    }
+
+    sender_thread.join().unwrap();
+    is_running.store(false, Ordering::Relaxed);
+    receive_thread.join().unwrap();
+
+    written_bytes
+}
+
+fn main() {
+    let mut destination = vec![0; 2 * FRAME_SIZE];
+
+    let cases: [(&str, fn(u16, &mut [u8]) -> usize); 2] =
+        [("pooled", receive_pooled), ("direct", receive_direct)];
+    for (port, (name, receive)) in (9950..).zip(cases) {
+        let start = Instant::now();
+        let written_bytes = receive(port, &mut destination);
+        let elapsed = start.elapsed();
+
+        println!(
+            "{name}: {FRAMES_COUNT} frames of {FRAME_SIZE} B in {elapsed:?}, {:.0} MB/s, {} KB written per frame",
+            (FRAMES_COUNT as usize * FRAME_SIZE) as f64 / elapsed.as_secs_f64() / 1e6,
+            written_bytes / FRAMES_COUNT as usize / 1000,
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use crate::ControlTransport;
+use alvr_common::parking_lot::{Condvar, Mutex};
+use std::{
+    collections::VecDeque,
+    io::{self, Read, Write},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+#[derive(Default)]
+struct PipeState {
+    data: VecDeque<u8>,
+    writer_closed: bool,
+    reader_closed: bool,
+    // Set by MemoryTransport::interrupt_next_read()
+    interrupt: bool,
+}
+
+// One direction of the transport
+#[derive(Default)]
+struct Pipe {
+    state: Mutex<PipeState>,
+    condvar: Condvar,
+}
+
+// Shared by the clones of one end. Dropping the last clone closes the end, like closing a socket
+struct End {
+    incoming: Arc<Pipe>,
+    outgoing: Arc<Pipe>,
+    read_timeout: Mutex<Option<Duration>>,
+}
+
+impl Drop for End {
+    fn drop(&mut self) {
+        self.outgoing.state.lock().writer_closed = true;
+        self.outgoing.condvar.notify_all();
+
+        self.incoming.state.lock().reader_closed = true;
+        self.incoming.condvar.notify_all();
+    }
+}
+
+// In-memory byte stream with the behavior of a TCP stream: reads block until data is available or
+// the read timeout expires, and return 0 once the peer is closed. Writes never block. Used to test
+// the control socket without real sockets
+#[derive(Clone)]
+pub struct MemoryTransport(Arc<End>);
+
+impl MemoryTransport {
+    pub fn pair() -> (Self, Self) {
+        let a_to_b = Arc::new(Pipe::default());
+        let b_to_a = Arc::new(Pipe::default());
+
+        let end = |incoming: &Arc<Pipe>, outgoing: &Arc<Pipe>| {
+            Self(Arc::new(End {
+                incoming: Arc::clone(incoming),
+                outgoing: Arc::clone(outgoing),
+                read_timeout: Mutex::new(None),
+            }))
+        };
+
+        (end(&b_to_a, &a_to_b), end(&a_to_b, &b_to_a))
+    }
+
+    // The next read or peek fails with ErrorKind::Interrupted, like a call interrupted by a signal
+    pub fn interrupt_next_read(&self) {
+        self.0.incoming.state.lock().interrupt = true;
+    }
+
+    // Waits for incoming data, then copies it. Returns 0 if the peer is closed
+    fn read_or_peek(&self, buffer: &mut [u8], consume: bool) -> io::Result<usize> {
+        let deadline = self
+            .0
+            .read_timeout
+            .lock()
+            .map(|timeout| Instant::now() + timeout);
+
+        let pipe = &self.0.incoming;
+        let mut state = pipe.state.lock();
+        loop {
+            if state.interrupt {
+                state.interrupt = false;
+                return Err(io::ErrorKind::Interrupted.into());
+            }
+
+            if !state.data.is_empty() || state.writer_closed || buffer.is_empty() {
+                break;
+            }
+
+            if let Some(deadline) = deadline {
+                if pipe.condvar.wait_until(&mut state, deadline).timed_out() {
+                    return Err(io::ErrorKind::TimedOut.into());
+                }
+            } else {
+                pipe.condvar.wait(&mut state);
+            }
+        }
+
+        let count = usize::min(buffer.len(), state.data.len());
+        for (dst, src) in buffer.iter_mut().zip(state.data.iter()) {
+            *dst = *src;
+        }
+        if consume {
+            state.data.drain(..count);
+        }
+
+        Ok(count)
+    }
+}
+
+impl Read for MemoryTransport {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        self.read_or_peek(buffer, true)
+    }
+}
+
+impl Write for MemoryTransport {
+    fn write(&mut self, buffer: &[u8]) -> io::Result<usize> {
+        let pipe = &self.0.outgoing;
+        let mut state = pipe.state.lock();
+        if state.reader_closed {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+
+        state.data.extend(buffer);
+        pipe.condvar.notify_all();
+
+        Ok(buffer.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl ControlTransport for MemoryTransport {
+    fn set_read_timeout(&self, timeout: Duration) -> io::Result<()> {
+        *self.0.read_timeout.lock() = Some(timeout);
+
+        Ok(())
+    }
+
+    // Writes do not block
+    fn set_write_timeout(&self, _: Option<Duration>) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn peek(&self, buffer: &mut [u8]) -> io::Result<usize> {
+        self.read_or_peek(buffer, false)
+    }
+
+    fn try_clone(&self) -> io::Result<Self> {
+        Ok(self.clone())
+    }
+}
@@ -1,3 +1,4 @@
+pub mod memory;
 pub mod tcp;
 pub mod udp;
 
@@ -5,6 +6,12 @@ use alvr_common::{anyhow::Result, ConResult};
 
 pub trait SocketWriter: Send {
     fn send(&mut self, buffer: &[u8]) -> Result<()>;
+
+    // Whether several shards can be written with one send() call. Each datagram of a message based
+    // socket must contain exactly one shard
+    fn can_batch(&self) -> bool {
+        false
+    }
 }
 
 // Trait used to abstract different socket (or other input/output) implementations. The funtionality
@@ -1,8 +1,9 @@
-use crate::LOCAL_IP;
+use crate::{IoContext, SocketError, SocketResult, LOCAL_IP};
 
 use super::{SocketReader, SocketWriter};
-use alvr_common::{anyhow::Result, con_bail, ConResult, HandleTryAgain, ToCon};
+use alvr_common::{anyhow::Result, ConResult, HandleTryAgain};
 use alvr_session::{DscpTos, SocketBufferSize};
+use socket2::{Domain, Protocol, Socket, Type};
 use std::{
     io::Read,
     io::Write,
@@ -16,14 +17,18 @@ pub fn bind(
     dscp: Option<DscpTos>,
     send_buffer_bytes: SocketBufferSize,
     recv_buffer_bytes: SocketBufferSize,
-) -> Result<TcpListener> {
-    let socket = TcpListener::bind((LOCAL_IP, port))?.into();
+) -> SocketResult<TcpListener> {
+    let socket = TcpListener::bind((LOCAL_IP, port))
+        .io_context("Failed to bind the TCP socket")?
+        .into();
 
     crate::set_socket_buffers(&socket, send_buffer_bytes, recv_buffer_bytes).ok();
 
     crate::set_dscp(&socket, dscp);
 
-    socket.set_read_timeout(Some(timeout))?;
+    socket
+        .set_read_timeout(Some(timeout))
+        .io_context("Failed to set the read timeout")?;
 
     Ok(socket.into())
 }
@@ -32,53 +37,92 @@ pub fn accept_from_server(
     listener: &TcpListener,
     server_ip: Option<IpAddr>,
     timeout: Duration,
-) -> ConResult<(TcpStream, TcpStream)> {
+) -> SocketResult<(TcpStream, TcpStream)> {
     // Uses timeout set during bind()
-    let (socket, server_address) = listener.accept().handle_try_again()?;
+    let (socket, server_address) = listener
+        .accept()
+        .io_context("Failed to accept the connection")?;
 
     if let Some(ip) = server_ip {
         if server_address.ip() != ip {
-            con_bail!(
-                "Connected to wrong client: Expected: {ip}, Found {}",
-                server_address.ip()
-            );
+            return Err(SocketError::UnexpectedPeer {
+                expected: ip,
+                found: server_address.ip(),
+            });
         }
     }
 
-    socket.set_read_timeout(Some(timeout)).to_con()?;
-    socket.set_nodelay(true).to_con()?;
+    socket
+        .set_read_timeout(Some(timeout))
+        .io_context("Failed to set the read timeout")?;
+    socket
+        .set_nodelay(true)
+        .io_context("Failed to disable Nagle's algorithm")?;
 
-    Ok((socket.try_clone().to_con()?, socket))
+    let send_socket = socket
+        .try_clone()
+        .io_context("Failed to clone the TCP socket")?;
+
+    Ok((send_socket, socket))
+}
+
+fn connect_from(
+    local_ip: IpAddr,
+    address: SocketAddr,
+    timeout: Duration,
+) -> std::io::Result<Socket> {
+    let socket = Socket::new(
+        Domain::for_address(address),
+        Type::STREAM,
+        Some(Protocol::TCP),
+    )?;
+
+    if !local_ip.is_unspecified() {
+        socket.bind(&SocketAddr::new(local_ip, 0).into())?;
+    }
+
+    socket.connect_timeout(&address.into(), timeout)?;
+
+    Ok(socket)
 }
 
 pub fn connect_to_client(
     timeout: Duration,
+    local_ip: IpAddr,
     client_ips: &[IpAddr],
     port: u16,
     send_buffer_bytes: SocketBufferSize,
     recv_buffer_bytes: SocketBufferSize,
-) -> ConResult<(TcpStream, TcpStream)> {
+) -> SocketResult<(TcpStream, TcpStream)> {
     let split_timeout = timeout / client_ips.len() as u32;
 
-    let mut res = alvr_common::try_again();
+    let mut res = Err(SocketError::Timeout);
     for ip in client_ips {
-        res = TcpStream::connect_timeout(&SocketAddr::new(*ip, port), split_timeout)
-            .handle_try_again();
+        res = connect_from(local_ip, SocketAddr::new(*ip, port), split_timeout)
+            .io_context("Failed to connect to the client");
 
         if res.is_ok() {
             break;
         }
     }
-    let socket = res?.into();
+    let socket = res?;
 
     crate::set_socket_buffers(&socket, send_buffer_bytes, recv_buffer_bytes).ok();
-    socket.set_read_timeout(Some(timeout)).to_con()?;
+    socket
+        .set_read_timeout(Some(timeout))
+        .io_context("Failed to set the read timeout")?;
 
     let socket = TcpStream::from(socket);
 
-    socket.set_nodelay(true).to_con()?;
+    socket
+        .set_nodelay(true)
+        .io_context("Failed to disable Nagle's algorithm")?;
+
+    let send_socket = socket
+        .try_clone()
+        .io_context("Failed to clone the TCP socket")?;
 
-    Ok((socket.try_clone().to_con()?, socket))
+    Ok((send_socket, socket))
 }
 
 impl SocketWriter for TcpStream {
@@ -87,6 +131,10 @@ impl SocketWriter for TcpStream {
 
         Ok(())
     }
+
+    fn can_batch(&self) -> bool {
+        true
+    }
 }
 
 impl SocketReader for TcpStream {
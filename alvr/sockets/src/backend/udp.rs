@@ -1,6 +1,5 @@
-use crate::LOCAL_IP;
-
 use super::{SocketReader, SocketWriter};
+use crate::{IoContext, SocketResult};
 use alvr_common::{anyhow::Result, ConResult, HandleTryAgain};
 use alvr_session::{DscpTos, SocketBufferSize};
 use socket2::{MaybeUninitSlice, Socket};
@@ -14,12 +13,15 @@ use std::{
 // Create tokio socket, convert to socket2, apply settings, convert back to tokio. This is done to
 // let tokio set all the internal parameters it needs from the start.
 pub fn bind(
+    local_ip: IpAddr,
     port: u16,
     dscp: Option<DscpTos>,
     send_buffer_bytes: SocketBufferSize,
     recv_buffer_bytes: SocketBufferSize,
-) -> Result<UdpSocket> {
-    let socket = UdpSocket::bind((LOCAL_IP, port))?.into();
+) -> SocketResult<UdpSocket> {
+    let socket = UdpSocket::bind((local_ip, port))
+        .io_context("Failed to bind the UDP socket")?
+        .into();
 
     crate::set_socket_buffers(&socket, send_buffer_bytes, recv_buffer_bytes).ok();
 
@@ -33,11 +35,22 @@ pub fn connect(
     peer_ip: IpAddr,
     port: u16,
     timeout: Duration,
-) -> Result<(UdpSocket, Socket)> {
-    socket.connect((peer_ip, port))?;
-    socket.set_read_timeout(Some(timeout))?;
+) -> SocketResult<(UdpSocket, Socket)> {
+    socket
+        .connect((peer_ip, port))
+        .io_context("Failed to connect the UDP socket")?;
+    socket
+        .set_read_timeout(Some(timeout))
+        .io_context("Failed to set the read timeout")?;
+
+    let send_socket = socket
+        .try_clone()
+        .io_context("Failed to clone the UDP socket")?;
+    let receive_socket = socket
+        .try_clone()
+        .io_context("Failed to clone the UDP socket")?;
 
-    Ok((socket.try_clone()?, socket.try_clone()?.into()))
+    Ok((send_socket, receive_socket.into()))
 }
 
 impl SocketWriter for UdpSocket {
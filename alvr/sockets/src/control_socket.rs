@@ -1,10 +1,9 @@
-use crate::backend::{tcp, SocketReader, SocketWriter};
-
-use super::CONTROL_PORT;
-use alvr_common::{anyhow::Result, ConResult, HandleTryAgain, ToCon};
+use crate::{backend::tcp, IoContext, SocketError, SocketResult};
+use alvr_common::{anyhow::Result, ConResult, ToCon};
 use alvr_session::SocketBufferSize;
 use serde::{de::DeserializeOwned, Serialize};
 use std::{
+    io::{self, Read, Write},
     marker::PhantomData,
     mem,
     net::{IpAddr, TcpListener, TcpStream},
@@ -13,18 +12,100 @@ use std::{
 
 // This corresponds to the length of the payload
 const FRAMED_PREFIX_LENGTH: usize = mem::size_of::<u32>();
+// Far above the size of any control packet. A peer with a different protocol could otherwise make
+// the receiver allocate up to 4 GiB
+const MAX_PAYLOAD_LENGTH: usize = 16 * 1024 * 1024;
+// The OS rejects a zero read or write timeout
+const MIN_SOCKET_TIMEOUT: Duration = Duration::from_millis(1);
+
+// Byte stream the control packets are framed on. Clones made with try_clone() refer to the same
+// stream, so that one can be used for sending and the other for receiving
+pub trait ControlTransport: Read + Write + Send + Sized {
+    fn set_read_timeout(&self, timeout: Duration) -> io::Result<()>;
+
+    // None blocks until the data is written
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+
+    // Like read() but the data is not consumed. Returns 0 if the stream has ended
+    fn peek(&self, buffer: &mut [u8]) -> io::Result<usize>;
 
+    fn try_clone(&self) -> io::Result<Self>;
+}
+
+impl ControlTransport for TcpStream {
+    fn set_read_timeout(&self, timeout: Duration) -> io::Result<()> {
+        TcpStream::set_read_timeout(self, Some(timeout))
+    }
+
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        TcpStream::set_write_timeout(self, timeout)
+    }
+
+    fn peek(&self, buffer: &mut [u8]) -> io::Result<usize> {
+        TcpStream::peek(self, buffer)
+    }
+
+    fn try_clone(&self) -> io::Result<Self> {
+        TcpStream::try_clone(self)
+    }
+}
+
+// Calls interrupted by a signal are retried, like write_all() does
+fn retry_interrupted(mut f: impl FnMut() -> io::Result<usize>) -> io::Result<usize> {
+    loop {
+        match f() {
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            res => return res,
+        }
+    }
+}
+
+// Kept between calls, so that a timeout in the middle of a packet does not desynchronize the
+// stream: the next call continues reading the same packet
+#[derive(Default)]
 struct RecvState {
+    buffer: Vec<u8>,
+    partial_packet: Option<PartialPacket>,
+    // Last value set on the socket, to avoid a syscall per call
+    read_timeout: Option<Duration>,
+}
+
+struct PartialPacket {
     packet_length: usize, // contains length prefix
     packet_cursor: usize, // counts also the length prefix bytes
 }
 
-fn framed_send<S: Serialize>(
-    socket: &mut TcpStream,
+impl RecvState {
+    fn set_read_timeout<T: ControlTransport>(
+        &mut self,
+        socket: &T,
+        timeout: Duration,
+    ) -> SocketResult {
+        let timeout = Duration::max(timeout, MIN_SOCKET_TIMEOUT);
+        if self.read_timeout != Some(timeout) {
+            socket
+                .set_read_timeout(timeout)
+                .io_context("Failed to set the read timeout")?;
+            self.read_timeout = Some(timeout);
+        }
+
+        Ok(())
+    }
+}
+
+fn framed_send<S: Serialize, T: ControlTransport>(
+    socket: &mut T,
     buffer: &mut Vec<u8>,
     packet: &S,
-) -> Result<()> {
-    let serialized_size = bincode::serialized_size(&packet)? as usize;
+) -> SocketResult {
+    let serialized_size = bincode::serialized_size(&packet).map_err(SocketError::Encode)? as usize;
+    if serialized_size > MAX_PAYLOAD_LENGTH {
+        return Err(SocketError::OversizedFrame {
+            size: serialized_size,
+            max_size: MAX_PAYLOAD_LENGTH,
+        });
+    }
+
     let packet_size = serialized_size + FRAMED_PREFIX_LENGTH;
 
     if buffer.len() < packet_size {
@@ -32,103 +113,149 @@ fn framed_send<S: Serialize>(
     }
 
     buffer[0..FRAMED_PREFIX_LENGTH].copy_from_slice(&(serialized_size as u32).to_be_bytes());
-    bincode::serialize_into(&mut buffer[FRAMED_PREFIX_LENGTH..packet_size], &packet)?;
+    bincode::serialize_into(&mut buffer[FRAMED_PREFIX_LENGTH..packet_size], &packet)
+        .map_err(SocketError::Encode)?;
 
-    socket.send(&buffer[0..packet_size])?;
-
-    Ok(())
+    socket
+        .write_all(&buffer[0..packet_size])
+        .io_context("Failed to send a control packet")
 }
 
-fn framed_recv<R: DeserializeOwned>(
-    socket: &mut TcpStream,
-    buffer: &mut Vec<u8>,
-    maybe_recv_state: &mut Option<RecvState>,
+fn framed_recv<R: DeserializeOwned, T: ControlTransport>(
+    socket: &mut T,
+    recv_state: &mut RecvState,
     timeout: Duration,
-) -> ConResult<R> {
+    protocol_mismatch: bool,
+) -> SocketResult<R> {
     let deadline = Instant::now() + timeout;
+    recv_state.set_read_timeout(socket, timeout)?;
+
+    let RecvState {
+        buffer,
+        partial_packet,
+        ..
+    } = recv_state;
 
-    let recv_state_mut = if let Some(state) = maybe_recv_state {
-        state
+    let packet_mut = if let Some(packet) = partial_packet {
+        packet
     } else {
         let mut payload_length_bytes = [0; FRAMED_PREFIX_LENGTH];
 
         loop {
-            let count = socket.peek(&mut payload_length_bytes).handle_try_again()?;
+            // A timeout is reported as an error, reading nothing means the stream has ended
+            let count = retry_interrupted(|| socket.peek(&mut payload_length_bytes))
+                .io_context("Failed to receive a control packet")?;
             if count == FRAMED_PREFIX_LENGTH {
                 break;
+            } else if count == 0 {
+                return Err(SocketError::PeerClosed);
             } else if Instant::now() > deadline {
-                return alvr_common::try_again();
+                return Err(SocketError::Timeout);
             }
         }
 
-        let packet_length =
-            FRAMED_PREFIX_LENGTH + u32::from_be_bytes(payload_length_bytes) as usize;
+        let payload_length = u32::from_be_bytes(payload_length_bytes) as usize;
+        if payload_length > MAX_PAYLOAD_LENGTH {
+            return Err(SocketError::OversizedFrame {
+                size: payload_length,
+                max_size: MAX_PAYLOAD_LENGTH,
+            });
+        }
+
+        let packet_length = FRAMED_PREFIX_LENGTH + payload_length;
 
         if buffer.len() < packet_length {
             buffer.resize(packet_length, 0);
         }
 
-        maybe_recv_state.insert(RecvState {
+        partial_packet.insert(PartialPacket {
             packet_length,
             packet_cursor: 0,
         })
     };
 
     loop {
-        recv_state_mut.packet_cursor +=
-            socket.recv(&mut buffer[recv_state_mut.packet_cursor..recv_state_mut.packet_length])?;
+        let count = retry_interrupted(|| {
+            socket.read(&mut buffer[packet_mut.packet_cursor..packet_mut.packet_length])
+        })
+        .io_context("Failed to receive a control packet")?;
+        if count == 0 {
+            return Err(SocketError::PeerClosed);
+        }
+        packet_mut.packet_cursor += count;
 
-        if recv_state_mut.packet_cursor == recv_state_mut.packet_length {
+        if packet_mut.packet_cursor == packet_mut.packet_length {
             break;
         } else if Instant::now() > deadline {
-            return alvr_common::try_again();
+            return Err(SocketError::Timeout);
         } else {
             continue;
         }
     }
 
-    let packet = bincode::deserialize(&buffer[FRAMED_PREFIX_LENGTH..recv_state_mut.packet_length])
-        .to_con()?;
-
-    *maybe_recv_state = None;
+    let packet_length = packet_mut.packet_length;
+    *partial_packet = None;
 
-    Ok(packet)
+    bincode::deserialize(&buffer[FRAMED_PREFIX_LENGTH..packet_length]).map_err(|source| {
+        SocketError::Decode {
+            source,
+            protocol_mismatch,
+        }
+    })
 }
 
-pub struct ControlSocketSender<T> {
-    inner: TcpStream,
+pub struct FramedSender<P, T> {
+    inner: T,
     buffer: Vec<u8>,
-    _phantom: PhantomData<T>,
+    _phantom: PhantomData<P>,
 }
 
-impl<S: Serialize> ControlSocketSender<S> {
+pub type ControlSocketSender<P> = FramedSender<P, TcpStream>;
+
+impl<S: Serialize, T: ControlTransport> FramedSender<S, T> {
     pub fn send(&mut self, packet: &S) -> Result<()> {
-        framed_send(&mut self.inner, &mut self.buffer, packet)
+        Ok(framed_send(&mut self.inner, &mut self.buffer, packet)?)
+    }
+
+    // Used right before closing the connection, when a peer that stopped reading must not block
+    // the caller
+    pub fn send_with_timeout(&mut self, packet: &S, timeout: Duration) -> Result<()> {
+        self.inner
+            .set_write_timeout(Some(Duration::max(timeout, MIN_SOCKET_TIMEOUT)))
+            .io_context("Failed to set the write timeout")?;
+        let result = self.send(packet);
+        self.inner.set_write_timeout(None).ok();
+
+        result
     }
 }
 
-pub struct ControlSocketReceiver<T> {
-    inner: TcpStream,
-    buffer: Vec<u8>,
-    recv_state: Option<RecvState>,
-    _phantom: PhantomData<T>,
+pub struct FramedReceiver<P, T> {
+    inner: T,
+    recv_state: RecvState,
+    protocol_mismatch: bool,
+    _phantom: PhantomData<P>,
 }
 
-impl<R: DeserializeOwned> ControlSocketReceiver<R> {
+pub type ControlSocketReceiver<P> = FramedReceiver<P, TcpStream>;
+
+impl<R: DeserializeOwned, T: ControlTransport> FramedReceiver<R, T> {
+    // A timeout is returned as ConnectionError::TryAgain. A packet partially received when the
+    // timeout fired is completed by the next call
     pub fn recv(&mut self, timeout: Duration) -> ConResult<R> {
-        framed_recv(
+        Ok(framed_recv(
             &mut self.inner,
-            &mut self.buffer,
             &mut self.recv_state,
             timeout,
-        )
+            self.protocol_mismatch,
+        )?)
     }
 }
 
-pub fn get_server_listener(timeout: Duration) -> Result<TcpListener> {
+pub fn get_server_listener(timeout: Duration, port: u16) -> Result<TcpListener> {
     let listener = tcp::bind(
         timeout,
-        CONTROL_PORT,
+        port,
         None,
         SocketBufferSize::Default,
         SocketBufferSize::Default,
@@ -139,23 +266,36 @@ pub fn get_server_listener(timeout: Duration) -> Result<TcpListener> {
 
 // Proto-control-socket that can send and receive any packet. After the split, only the packets of
 // the specified types can be exchanged
-pub struct ProtoControlSocket {
-    inner: TcpStream,
+pub struct FramedSocket<T> {
+    inner: T,
+    recv_state: RecvState,
+    protocol_mismatch: bool,
 }
 
+pub type ProtoControlSocket = FramedSocket<TcpStream>;
+
 pub enum PeerType<'a> {
-    AnyClient(Vec<IpAddr>),
+    AnyClient {
+        local_ip: IpAddr,
+        client_ips: Vec<IpAddr>,
+        port: u16,
+    },
     Server(&'a TcpListener),
 }
 
 impl ProtoControlSocket {
     pub fn connect_to(timeout: Duration, peer: PeerType<'_>) -> ConResult<(Self, IpAddr)> {
         let socket = match peer {
-            PeerType::AnyClient(ips) => {
+            PeerType::AnyClient {
+                local_ip,
+                client_ips,
+                port,
+            } => {
                 tcp::connect_to_client(
                     timeout,
-                    &ips,
-                    CONTROL_PORT,
+                    local_ip,
+                    &client_ips,
+                    port,
                     SocketBufferSize::Default,
                     SocketBufferSize::Default,
                 )?
@@ -166,35 +306,222 @@ impl ProtoControlSocket {
 
         let peer_ip = socket.peer_addr().to_con()?.ip();
 
-        Ok((Self { inner: socket }, peer_ip))
+        Ok((Self::new(socket), peer_ip))
+    }
+}
+
+impl<T: ControlTransport> FramedSocket<T> {
+    // Used for transports other than TCP, which are connected by the caller
+    pub fn new(transport: T) -> Self {
+        Self {
+            inner: transport,
+            recv_state: RecvState::default(),
+            protocol_mismatch: false,
+        }
+    }
+
+    // Set when the peer was accepted despite a different protocol ID. The packets that fail to
+    // decode are then reported as a likely protocol mismatch
+    pub fn set_protocol_mismatch(&mut self) {
+        self.protocol_mismatch = true;
     }
 
     pub fn send<S: Serialize>(&mut self, packet: &S) -> Result<()> {
-        framed_send(&mut self.inner, &mut vec![], packet)
+        Ok(framed_send(&mut self.inner, &mut vec![], packet)?)
     }
 
     pub fn recv<R: DeserializeOwned>(&mut self, timeout: Duration) -> ConResult<R> {
-        framed_recv(&mut self.inner, &mut vec![], &mut None, timeout)
+        Ok(framed_recv(
+            &mut self.inner,
+            &mut self.recv_state,
+            timeout,
+            self.protocol_mismatch,
+        )?)
     }
 
     pub fn split<S: Serialize, R: DeserializeOwned>(
         self,
         timeout: Duration,
-    ) -> Result<(ControlSocketSender<S>, ControlSocketReceiver<R>)> {
-        self.inner.set_read_timeout(Some(timeout))?;
+    ) -> Result<(FramedSender<S, T>, FramedReceiver<R, T>)> {
+        let mut recv_state = self.recv_state;
+        recv_state.set_read_timeout(&self.inner, timeout)?;
 
         Ok((
-            ControlSocketSender {
+            FramedSender {
                 inner: self.inner.try_clone()?,
                 buffer: vec![],
                 _phantom: PhantomData,
             },
-            ControlSocketReceiver {
+            FramedReceiver {
                 inner: self.inner,
-                buffer: vec![],
-                recv_state: None,
+                recv_state,
+                protocol_mismatch: self.protocol_mismatch,
                 _phantom: PhantomData,
             },
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MemoryTransport;
+    use alvr_common::ConnectionError;
+    use std::net::Ipv4Addr;
+
+    const TIMEOUT: Duration = Duration::from_millis(50);
+
+    fn memory_pair() -> (MemoryTransport, FramedReceiver<String, MemoryTransport>) {
+        let (writer, reader) = MemoryTransport::pair();
+        let (_, receiver) = FramedSocket::new(reader)
+            .split::<(), String>(TIMEOUT)
+            .unwrap();
+
+        (writer, receiver)
+    }
+
+    fn framed_bytes(packet: &str) -> Vec<u8> {
+        let payload = bincode::serialize(packet).unwrap();
+
+        let mut bytes = (payload.len() as u32).to_be_bytes().to_vec();
+        bytes.extend(payload);
+
+        bytes
+    }
+
+    fn is_timeout(result: ConResult<String>) -> bool {
+        matches!(result, Err(ConnectionError::TryAgain(ref e)) if matches!(
+            e.downcast_ref::<SocketError>(),
+            Some(SocketError::Timeout)
+        ))
+    }
+
+    fn is_socket_error(result: ConResult<String>, check: impl Fn(&SocketError) -> bool) -> bool {
+        matches!(result, Err(ConnectionError::Other(ref e)) if e
+            .downcast_ref::<SocketError>()
+            .is_some_and(check))
+    }
+
+    #[test]
+    fn test_timeout_before_prefix() {
+        let (mut writer, mut receiver) = memory_pair();
+
+        assert!(is_timeout(receiver.recv(TIMEOUT)));
+
+        // Only part of the prefix: nothing is consumed yet
+        let bytes = framed_bytes("hello");
+        writer.write_all(&bytes[..2]).unwrap();
+        assert!(is_timeout(receiver.recv(TIMEOUT)));
+
+        writer.write_all(&bytes[2..]).unwrap();
+        assert_eq!(receiver.recv(TIMEOUT).unwrap(), "hello");
+    }
+
+    #[test]
+    fn test_timeout_mid_payload() {
+        let (mut writer, mut receiver) = memory_pair();
+
+        let bytes = framed_bytes("a packet split in two writes");
+        let split_index = FRAMED_PREFIX_LENGTH + 5;
+        writer.write_all(&bytes[..split_index]).unwrap();
+        assert!(is_timeout(receiver.recv(TIMEOUT)));
+
+        writer.write_all(&bytes[split_index..]).unwrap();
+        writer.write_all(&framed_bytes("next")).unwrap();
+        assert_eq!(
+            receiver.recv(TIMEOUT).unwrap(),
+            "a packet split in two writes"
+        );
+        assert_eq!(receiver.recv(TIMEOUT).unwrap(), "next");
+    }
+
+    #[test]
+    fn test_byte_by_byte() {
+        let (mut writer, mut receiver) = memory_pair();
+
+        let bytes = [framed_bytes("first"), framed_bytes("second")].concat();
+        for byte in &bytes[..bytes.len() - 1] {
+            writer.write_all(&[*byte]).unwrap();
+        }
+        assert_eq!(receiver.recv(TIMEOUT).unwrap(), "first");
+        assert!(is_timeout(receiver.recv(TIMEOUT)));
+
+        writer.write_all(&bytes[bytes.len() - 1..]).unwrap();
+        assert_eq!(receiver.recv(TIMEOUT).unwrap(), "second");
+    }
+
+    #[test]
+    fn test_interrupted_reads() {
+        let (mut writer, mut receiver) = memory_pair();
+        let bytes = framed_bytes("interrupted");
+
+        // While peeking the prefix
+        writer.write_all(&bytes).unwrap();
+        receiver.inner.interrupt_next_read();
+        assert_eq!(receiver.recv(TIMEOUT).unwrap(), "interrupted");
+
+        // While reading the payload
+        writer
+            .write_all(&bytes[..FRAMED_PREFIX_LENGTH + 2])
+            .unwrap();
+        assert!(is_timeout(receiver.recv(TIMEOUT)));
+        writer
+            .write_all(&bytes[FRAMED_PREFIX_LENGTH + 2..])
+            .unwrap();
+        receiver.inner.interrupt_next_read();
+        assert_eq!(receiver.recv(TIMEOUT).unwrap(), "interrupted");
+    }
+
+    #[test]
+    fn test_oversized_frame() {
+        let (mut writer, mut receiver) = memory_pair();
+
+        writer
+            .write_all(&(MAX_PAYLOAD_LENGTH as u32 + 1).to_be_bytes())
+            .unwrap();
+        assert!(is_socket_error(receiver.recv(TIMEOUT), |e| matches!(
+            e,
+            SocketError::OversizedFrame { size, .. } if *size == MAX_PAYLOAD_LENGTH + 1
+        )));
+    }
+
+    #[test]
+    fn test_complete_packets() {
+        let (writer, mut receiver) = memory_pair();
+        let mut sender = FramedSender::<String, _> {
+            inner: writer,
+            buffer: vec![],
+            _phantom: PhantomData,
+        };
+
+        for packet in ["first", "", "third"] {
+            sender.send(&packet.to_owned()).unwrap();
+        }
+        for packet in ["first", "", "third"] {
+            assert_eq!(receiver.recv(TIMEOUT).unwrap(), packet);
+        }
+
+        drop(sender);
+        assert!(is_socket_error(receiver.recv(TIMEOUT), |e| matches!(
+            e,
+            SocketError::PeerClosed
+        )));
+    }
+
+    #[test]
+    fn test_tcp_transport() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let writer = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (reader, _) = listener.accept().unwrap();
+
+        let (mut sender, _) = FramedSocket::new(writer)
+            .split::<String, ()>(TIMEOUT)
+            .unwrap();
+        let (_, mut receiver) = FramedSocket::new(reader)
+            .split::<(), String>(TIMEOUT)
+            .unwrap();
+
+        sender.send(&"over TCP".to_owned()).unwrap();
+        assert_eq!(receiver.recv(TIMEOUT).unwrap(), "over TCP");
+    }
+}
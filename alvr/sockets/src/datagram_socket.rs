@@ -0,0 +1,558 @@
+// Datagram sockets carry the video when the stream socket uses TCP. A late video frame is useless,
+// so lost datagrams are not retransmitted: the incomplete frame is discarded, and the next complete
+// frame is reported with had_packet_loss(), which makes the client request an IDR.
+//
+// Each datagram contains one fragment of a packet (the header and payload, like a stream socket
+// packet) after a fragment header. All fragments except the last one are filled up to the maximum
+// datagram size, which is set manually, there is no path MTU discovery.
+// Datagrams of exactly the fragment header size, with a fragment count of 0, are probes. They let
+// the client know that datagrams can reach it before any frame is encoded.
+
+use crate::{
+    backend::{udp, SocketWriter},
+    network_simulator::ImpairedWriter,
+    stream_socket::{self, ReceiverData, SHARD_PREFIX_SIZE},
+    IoContext, SocketError, SocketResult, LOCAL_IP,
+};
+use alvr_common::ConResult;
+use alvr_session::{DscpTos, NetworkImpairmentConfig, SocketBufferSize};
+use serde::Serialize;
+use std::{
+    cmp::Ordering,
+    io,
+    marker::PhantomData,
+    mem,
+    net::{IpAddr, UdpSocket},
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+const FRAGMENT_HEADER_SIZE: usize = mem::size_of::<u32>() // frame index
+    + mem::size_of::<u16>() // fragment index
+    + mem::size_of::<u16>() // fragment count
+    + mem::size_of::<u8>(); // flags
+
+const KEYFRAME_FLAG: u8 = 1;
+
+// Largest UDP payload over IPv4
+pub const MAX_DATAGRAM_SIZE: usize = 65507;
+
+// A zero read timeout is rejected by the OS
+const MIN_RECV_TIMEOUT: Duration = Duration::from_millis(1);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct FragmentHeader {
+    frame_index: u32,
+    fragment_index: u16,
+    fragment_count: u16,
+    keyframe: bool,
+}
+
+impl FragmentHeader {
+    fn write(&self, buffer: &mut [u8]) {
+        buffer[0..4].copy_from_slice(&self.frame_index.to_le_bytes());
+        buffer[4..6].copy_from_slice(&self.fragment_index.to_le_bytes());
+        buffer[6..8].copy_from_slice(&self.fragment_count.to_le_bytes());
+        buffer[8] = if self.keyframe { KEYFRAME_FLAG } else { 0 };
+    }
+
+    fn read(buffer: &[u8]) -> Option<Self> {
+        if buffer.len() < FRAGMENT_HEADER_SIZE {
+            return None;
+        }
+
+        Some(Self {
+            frame_index: u32::from_le_bytes(buffer[0..4].try_into().unwrap()),
+            fragment_index: u16::from_le_bytes(buffer[4..6].try_into().unwrap()),
+            fragment_count: u16::from_le_bytes(buffer[6..8].try_into().unwrap()),
+            keyframe: buffer[8] & KEYFRAME_FLAG != 0,
+        })
+    }
+}
+
+fn fragment_data_size(max_datagram_size: usize) -> usize {
+    max_datagram_size.clamp(FRAGMENT_HEADER_SIZE + 1, MAX_DATAGRAM_SIZE) - FRAGMENT_HEADER_SIZE
+}
+
+fn send_datagram(
+    socket: &UdpSocket,
+    simulator: Option<&mut ImpairedWriter>,
+    datagram: &[u8],
+) -> SocketResult {
+    if let Some(simulator) = simulator {
+        simulator.send(datagram).map_err(|e| SocketError::Io {
+            context: "Failed to send a video datagram",
+            source: io::Error::new(io::ErrorKind::Other, e.to_string()),
+        })
+    } else {
+        socket
+            .send(datagram)
+            .io_context("Failed to send a video datagram")?;
+
+        Ok(())
+    }
+}
+
+pub struct DatagramSender<H> {
+    socket: UdpSocket,
+    simulator: Option<ImpairedWriter>,
+    fragment_data_size: usize,
+    next_frame_index: u32,
+    frame: Vec<u8>,
+    datagram: Vec<u8>,
+    _phantom: PhantomData<H>,
+}
+
+impl<H: Serialize> DatagramSender<H> {
+    pub fn connect(
+        local_ip: IpAddr,
+        peer_ip: IpAddr,
+        port: u16,
+        dscp: Option<DscpTos>,
+        send_buffer_bytes: SocketBufferSize,
+        max_datagram_size: usize,
+    ) -> SocketResult<Self> {
+        // The source port is not checked by the receiver
+        let socket = udp::bind(
+            local_ip,
+            0,
+            dscp,
+            send_buffer_bytes,
+            SocketBufferSize::Default,
+        )?;
+        socket
+            .connect((peer_ip, port))
+            .io_context("Failed to connect the UDP socket")?;
+
+        Ok(Self {
+            socket,
+            simulator: None,
+            fragment_data_size: fragment_data_size(max_datagram_size),
+            next_frame_index: 0,
+            frame: vec![],
+            datagram: vec![],
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Developer tool: the datagrams are delayed and dropped like the shards of stream_id, see
+    /// network_simulator.rs
+    pub fn simulate_network(
+        &mut self,
+        seed: u64,
+        stream_id: u16,
+        config: NetworkImpairmentConfig,
+    ) -> SocketResult {
+        let socket = self
+            .socket
+            .try_clone()
+            .io_context("Failed to clone the UDP socket")?;
+        self.simulator = Some(ImpairedWriter::for_stream(
+            Box::new(socket),
+            seed,
+            stream_id,
+            config,
+        ));
+
+        Ok(())
+    }
+
+    pub fn send(&mut self, header: &H, payload: &[u8], keyframe: bool) -> SocketResult {
+        self.send_paced(header, payload, keyframe, Duration::ZERO)
+            .map(|_| ())
+    }
+
+    /// Like StreamSender::send_paced(), the fragments are spread evenly over the duration.
+    /// Returns the time spent waiting for the pacing
+    pub fn send_paced(
+        &mut self,
+        header: &H,
+        payload: &[u8],
+        keyframe: bool,
+        duration: Duration,
+    ) -> SocketResult<Duration> {
+        let start = Instant::now();
+        let mut pacing_wait = Duration::ZERO;
+
+        self.frame.clear();
+        bincode::serialize_into(&mut self.frame, header).map_err(SocketError::Encode)?;
+        self.frame.extend_from_slice(payload);
+
+        let fragment_count = usize::max(
+            (self.frame.len() + self.fragment_data_size - 1) / self.fragment_data_size,
+            1,
+        );
+        if fragment_count > u16::MAX as usize {
+            return Err(SocketError::OversizedFrame {
+                size: self.frame.len(),
+                max_size: u16::MAX as usize * self.fragment_data_size,
+            });
+        }
+
+        for index in 0..fragment_count {
+            let data_start = index * self.fragment_data_size;
+            let data = &self.frame
+                [data_start..usize::min(data_start + self.fragment_data_size, self.frame.len())];
+
+            self.datagram.resize(FRAGMENT_HEADER_SIZE + data.len(), 0);
+            FragmentHeader {
+                frame_index: self.next_frame_index,
+                fragment_index: index as u16,
+                fragment_count: fragment_count as u16,
+                keyframe,
+            }
+            .write(&mut self.datagram);
+            self.datagram[FRAGMENT_HEADER_SIZE..].copy_from_slice(data);
+
+            pacing_wait += stream_socket::wait_paced_shard(start, duration, index, fragment_count);
+
+            send_datagram(&self.socket, self.simulator.as_mut(), &self.datagram)?;
+        }
+
+        self.next_frame_index = self.next_frame_index.wrapping_add(1);
+
+        Ok(pacing_wait)
+    }
+
+    pub fn send_probe(&mut self) -> SocketResult {
+        let mut datagram = [0; FRAGMENT_HEADER_SIZE];
+        FragmentHeader {
+            frame_index: self.next_frame_index,
+            fragment_index: 0,
+            fragment_count: 0,
+            keyframe: false,
+        }
+        .write(&mut datagram);
+
+        send_datagram(&self.socket, self.simulator.as_mut(), &datagram)
+    }
+}
+
+struct InProgressFrame {
+    header: FragmentHeader,
+    buffer: Vec<u8>,
+    size: usize, // contains the prefix
+    received_fragments: Vec<bool>,
+    received_count: usize,
+}
+
+struct ReassembledFrame {
+    buffer: Vec<u8>,
+    size: usize,
+    had_packet_loss: bool,
+}
+
+// Only one frame is reassembled at a time. A fragment of a newer frame means that the fragments
+// still missing were lost, or will arrive too late to be useful
+struct FrameReassembler {
+    fragment_data_size: usize,
+    in_progress: Option<InProgressFrame>,
+    last_frame_index: Option<u32>,
+    used_buffer_sender: mpsc::Sender<Vec<u8>>,
+    used_buffer_receiver: mpsc::Receiver<Vec<u8>>,
+}
+
+impl FrameReassembler {
+    fn new(fragment_data_size: usize) -> Self {
+        let (used_buffer_sender, used_buffer_receiver) = mpsc::channel();
+
+        Self {
+            fragment_data_size,
+            in_progress: None,
+            last_frame_index: None,
+            used_buffer_sender,
+            used_buffer_receiver,
+        }
+    }
+
+    fn push(&mut self, datagram: &[u8]) -> Option<ReassembledFrame> {
+        let header = FragmentHeader::read(datagram)?;
+        let data = &datagram[FRAGMENT_HEADER_SIZE..];
+
+        let is_last_fragment = header.fragment_index + 1 == header.fragment_count;
+        if header.fragment_index >= header.fragment_count
+            || data.len() > self.fragment_data_size
+            || (!is_last_fragment && data.len() != self.fragment_data_size)
+        {
+            return None;
+        }
+
+        // Fragments of frames already completed or discarded
+        let is_stale = |index| {
+            self.last_frame_index
+                .is_some_and(|last| stream_socket::wrapping_cmp(index, last) != Ordering::Greater)
+        };
+        if is_stale(header.frame_index) {
+            return None;
+        }
+
+        let is_new_frame = match &self.in_progress {
+            Some(frame) => {
+                match stream_socket::wrapping_cmp(header.frame_index, frame.header.frame_index) {
+                    Ordering::Equal => false,
+                    Ordering::Greater => true,
+                    Ordering::Less => return None,
+                }
+            }
+            None => true,
+        };
+
+        if is_new_frame {
+            if let Some(frame) = self.in_progress.take() {
+                alvr_common::debug!(
+                    "Discarded incomplete video {} {}, received {} of {} fragments",
+                    if frame.header.keyframe {
+                        "keyframe"
+                    } else {
+                        "frame"
+                    },
+                    frame.header.frame_index,
+                    frame.received_count,
+                    frame.header.fragment_count
+                );
+                self.used_buffer_sender.send(frame.buffer).ok();
+            }
+
+            self.in_progress = Some(InProgressFrame {
+                header,
+                buffer: self.used_buffer_receiver.try_recv().unwrap_or_default(),
+                size: SHARD_PREFIX_SIZE,
+                received_fragments: vec![false; header.fragment_count as usize],
+                received_count: 0,
+            });
+        }
+
+        let frame = self.in_progress.as_mut().unwrap();
+        let fragment_index = header.fragment_index as usize;
+        if header.fragment_count != frame.header.fragment_count
+            || frame.received_fragments[fragment_index]
+        {
+            return None;
+        }
+
+        let start = SHARD_PREFIX_SIZE + fragment_index * self.fragment_data_size;
+        let end = start + data.len();
+        if frame.buffer.len() < end {
+            frame.buffer.resize(end, 0);
+        }
+        frame.buffer[start..end].copy_from_slice(data);
+        frame.size = usize::max(frame.size, end);
+
+        frame.received_fragments[fragment_index] = true;
+        frame.received_count += 1;
+
+        if frame.received_count < frame.received_fragments.len() {
+            return None;
+        }
+
+        let frame = self.in_progress.take().unwrap();
+        let had_packet_loss = self
+            .last_frame_index
+            .is_some_and(|last| frame.header.frame_index != last.wrapping_add(1));
+        self.last_frame_index = Some(frame.header.frame_index);
+
+        Some(ReassembledFrame {
+            buffer: frame.buffer,
+            size: frame.size,
+            had_packet_loss,
+        })
+    }
+}
+
+pub struct DatagramReceiver<H> {
+    socket: UdpSocket,
+    peer_ip: IpAddr,
+    datagram: Vec<u8>,
+    reassembler: FrameReassembler,
+    received_datagrams: bool,
+    _phantom: PhantomData<H>,
+}
+
+impl<H> DatagramReceiver<H> {
+    // Datagrams from other addresses than peer_ip are ignored
+    pub fn bind(
+        port: u16,
+        peer_ip: IpAddr,
+        recv_buffer_bytes: SocketBufferSize,
+        max_datagram_size: usize,
+    ) -> SocketResult<Self> {
+        let socket = udp::bind(
+            LOCAL_IP,
+            port,
+            None,
+            SocketBufferSize::Default,
+            recv_buffer_bytes,
+        )?;
+
+        Ok(Self {
+            socket,
+            peer_ip,
+            datagram: vec![0; MAX_DATAGRAM_SIZE],
+            reassembler: FrameReassembler::new(fragment_data_size(max_datagram_size)),
+            received_datagrams: false,
+            _phantom: PhantomData,
+        })
+    }
+
+    pub fn local_port(&self) -> SocketResult<u16> {
+        Ok(self
+            .socket
+            .local_addr()
+            .io_context("Failed to get the UDP socket address")?
+            .port())
+    }
+
+    // Any datagram counts, including probes and fragments of discarded frames
+    pub fn has_received_datagrams(&self) -> bool {
+        self.received_datagrams
+    }
+
+    pub fn recv(&mut self, timeout: Duration) -> ConResult<ReceiverData<H>> {
+        let deadline = Instant::now() + timeout;
+        self.socket
+            .set_read_timeout(Some(timeout.max(MIN_RECV_TIMEOUT)))
+            .io_context("Failed to set the read timeout")?;
+
+        loop {
+            let (size, address) = self
+                .socket
+                .recv_from(&mut self.datagram)
+                .io_context("Failed to receive a video datagram")?;
+
+            if address.ip() == self.peer_ip {
+                self.received_datagrams = true;
+
+                if let Some(frame) = self.reassembler.push(&self.datagram[..size]) {
+                    return Ok(ReceiverData::new(
+                        frame.buffer,
+                        frame.size,
+                        self.reassembler.used_buffer_sender.clone(),
+                        frame.had_packet_loss,
+                    ));
+                }
+            }
+
+            if Instant::now() >= deadline {
+                return alvr_common::try_again();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    const FRAGMENT_DATA_SIZE: usize = 4;
+
+    fn fragment(
+        frame_index: u32,
+        fragment_index: u16,
+        fragment_count: u16,
+        data: &[u8],
+    ) -> Vec<u8> {
+        let mut datagram = vec![0; FRAGMENT_HEADER_SIZE];
+        FragmentHeader {
+            frame_index,
+            fragment_index,
+            fragment_count,
+            keyframe: false,
+        }
+        .write(&mut datagram);
+        datagram.extend_from_slice(data);
+
+        datagram
+    }
+
+    fn frame_data(frame: &ReassembledFrame) -> &[u8] {
+        &frame.buffer[SHARD_PREFIX_SIZE..frame.size]
+    }
+
+    #[test]
+    fn test_out_of_order_fragments() {
+        let mut reassembler = FrameReassembler::new(FRAGMENT_DATA_SIZE);
+
+        assert!(reassembler.push(&fragment(0, 2, 3, b"ij")).is_none());
+        assert!(reassembler.push(&fragment(0, 0, 3, b"abcd")).is_none());
+        // Duplicates are ignored
+        assert!(reassembler.push(&fragment(0, 0, 3, b"abcd")).is_none());
+
+        let frame = reassembler.push(&fragment(0, 1, 3, b"efgh")).unwrap();
+        assert_eq!(frame_data(&frame), b"abcdefghij");
+        assert!(!frame.had_packet_loss);
+
+        // Fragments of a completed frame are stale
+        assert!(reassembler.push(&fragment(0, 1, 3, b"efgh")).is_none());
+    }
+
+    #[test]
+    fn test_incomplete_frame_is_discarded() {
+        let mut reassembler = FrameReassembler::new(FRAGMENT_DATA_SIZE);
+
+        let frame = reassembler.push(&fragment(10, 0, 1, b"a")).unwrap();
+        assert!(!frame.had_packet_loss);
+
+        // The second fragment of frame 11 is lost
+        assert!(reassembler.push(&fragment(11, 0, 2, b"bcde")).is_none());
+        let frame = reassembler.push(&fragment(12, 0, 1, b"f")).unwrap();
+        assert_eq!(frame_data(&frame), b"f");
+        assert!(frame.had_packet_loss);
+
+        // It arrives too late
+        assert!(reassembler.push(&fragment(11, 1, 2, b"g")).is_none());
+
+        let frame = reassembler.push(&fragment(13, 0, 1, b"h")).unwrap();
+        assert!(!frame.had_packet_loss);
+    }
+
+    #[test]
+    fn test_invalid_fragments() {
+        let mut reassembler = FrameReassembler::new(FRAGMENT_DATA_SIZE);
+
+        // Probe
+        assert!(reassembler.push(&fragment(0, 0, 0, &[])).is_none());
+        // Only the last fragment can be shorter
+        assert!(reassembler.push(&fragment(0, 0, 2, b"abc")).is_none());
+        assert!(reassembler.push(&fragment(0, 1, 2, b"defgh")).is_none());
+        assert!(reassembler.push(&[0; FRAGMENT_HEADER_SIZE - 1]).is_none());
+
+        let frame = reassembler.push(&fragment(0, 0, 1, b"abc")).unwrap();
+        assert_eq!(frame_data(&frame), b"abc");
+    }
+
+    #[test]
+    fn test_loopback_frame() {
+        const DATAGRAM_SIZE: usize = 100;
+
+        let localhost = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let mut receiver =
+            DatagramReceiver::<u32>::bind(0, localhost, SocketBufferSize::Default, DATAGRAM_SIZE)
+                .unwrap();
+        let mut sender = DatagramSender::<u32>::connect(
+            localhost,
+            localhost,
+            receiver.local_port().unwrap(),
+            None,
+            SocketBufferSize::Default,
+            DATAGRAM_SIZE,
+        )
+        .unwrap();
+
+        sender.send_probe().unwrap();
+        let payload = (0..10 * DATAGRAM_SIZE).map(|i| i as u8).collect::<Vec<_>>();
+        sender.send(&7, &payload, true).unwrap();
+
+        let data = loop {
+            if let Ok(data) = receiver.recv(Duration::from_millis(100)) {
+                break data;
+            }
+        };
+        assert!(receiver.has_received_datagrams());
+        assert!(!data.had_packet_loss());
+
+        let (header, received_payload) = data.get().unwrap();
+        assert_eq!(header, 7);
+        assert_eq!(received_payload, payload);
+    }
+}
@@ -0,0 +1,249 @@
+use alvr_common::{anyhow, ConnectionError};
+use std::{
+    error::Error,
+    fmt::{self, Display},
+    io,
+    net::IpAddr,
+    time::Duration,
+};
+
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+pub enum SocketError {
+    Io {
+        context: &'static str,
+        source: io::Error,
+    },
+    // The read timeout expired before a whole packet was received
+    Timeout,
+    PeerClosed,
+    OversizedFrame {
+        size: usize,
+        max_size: usize,
+    },
+    UnexpectedPeer {
+        expected: IpAddr,
+        found: IpAddr,
+    },
+    Encode(bincode::Error),
+    Decode {
+        source: bincode::Error,
+        // The peer was accepted despite a different protocol ID
+        protocol_mismatch: bool,
+    },
+}
+
+pub type SocketResult<T = ()> = Result<T, SocketError>;
+
+impl SocketError {
+    // Timeouts and connections closed by the peer get their own variants, so that the callers do
+    // not have to know which error kinds each platform uses for them
+    pub fn from_io(context: &'static str, source: io::Error) -> Self {
+        match source.kind() {
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => SocketError::Timeout,
+            io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::UnexpectedEof => SocketError::PeerClosed,
+            _ => SocketError::Io { context, source },
+        }
+    }
+
+    pub fn io_kind(&self) -> Option<io::ErrorKind> {
+        match self {
+            SocketError::Io { source, .. } => Some(source.kind()),
+            _ => None,
+        }
+    }
+
+    pub fn is_address_in_use(&self) -> bool {
+        self.io_kind() == Some(io::ErrorKind::AddrInUse)
+    }
+
+    // How long to wait before the next connection attempt, given the number of attempts that
+    // failed in a row before this one. A closed connection is retried right away once, the peer
+    // is likely restarting. A port held by another process is not going to be released soon
+    pub fn reconnect_delay(&self, base_delay: Duration, consecutive_failures: u32) -> Duration {
+        match self {
+            SocketError::PeerClosed if consecutive_failures == 0 => Duration::ZERO,
+            _ if self.is_address_in_use() => base_delay
+                .saturating_mul(1 << consecutive_failures.min(16))
+                .min(MAX_RECONNECT_DELAY),
+            _ => base_delay,
+        }
+    }
+
+    // Finds the socket error that caused a connection error, if any
+    pub fn find_in(error: &ConnectionError) -> Option<&SocketError> {
+        let (ConnectionError::TryAgain(e) | ConnectionError::Other(e)) = error;
+
+        e.chain().find_map(|cause| cause.downcast_ref())
+    }
+}
+
+impl Display for SocketError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SocketError::Io { context, source } => write!(f, "{context}: {source}"),
+            SocketError::Timeout => write!(f, "Socket timed out"),
+            SocketError::PeerClosed => write!(f, "The connection was closed by the peer"),
+            SocketError::OversizedFrame { size, max_size } => {
+                write!(
+                    f,
+                    "Packet of {size} bytes exceeds the maximum of {max_size} bytes"
+                )
+            }
+            SocketError::UnexpectedPeer { expected, found } => {
+                write!(
+                    f,
+                    "Connected to wrong client: Expected: {expected}, Found {found}"
+                )
+            }
+            SocketError::Encode(e) => write!(f, "Failed to encode a packet: {e}"),
+            SocketError::Decode {
+                source,
+                protocol_mismatch: true,
+            } => write!(
+                f,
+                "Failed to decode a packet, likely because of a protocol mismatch: {source}"
+            ),
+            SocketError::Decode { source, .. } => write!(f, "Failed to decode a packet: {source}"),
+        }
+    }
+}
+
+impl Error for SocketError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SocketError::Io { source, .. } => Some(source),
+            SocketError::Encode(source) | SocketError::Decode { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+pub trait IoContext<T> {
+    fn io_context(self, context: &'static str) -> SocketResult<T>;
+}
+
+impl<T> IoContext<T> for io::Result<T> {
+    fn io_context(self, context: &'static str) -> SocketResult<T> {
+        self.map_err(|e| SocketError::from_io(context, e))
+    }
+}
+
+// The rest of the codebase still uses ConResult. The socket error is kept as the cause, so it can
+// be recovered with SocketError::find_in()
+impl From<SocketError> for ConnectionError {
+    fn from(error: SocketError) -> Self {
+        match error {
+            SocketError::Timeout => ConnectionError::TryAgain(anyhow::Error::new(error)),
+            _ => ConnectionError::Other(anyhow::Error::new(error)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn io_error(kind: io::ErrorKind) -> SocketError {
+        SocketError::from_io("test", io::Error::from(kind))
+    }
+
+    #[test]
+    fn test_io_kind_mapping() {
+        for kind in [io::ErrorKind::TimedOut, io::ErrorKind::WouldBlock] {
+            assert!(matches!(io_error(kind), SocketError::Timeout));
+        }
+
+        for kind in [
+            io::ErrorKind::ConnectionReset,
+            io::ErrorKind::ConnectionAborted,
+            io::ErrorKind::BrokenPipe,
+            io::ErrorKind::UnexpectedEof,
+        ] {
+            assert!(matches!(io_error(kind), SocketError::PeerClosed));
+        }
+
+        let error = io_error(io::ErrorKind::AddrInUse);
+        assert!(matches!(
+            error,
+            SocketError::Io {
+                context: "test",
+                ..
+            }
+        ));
+        assert!(error.is_address_in_use());
+        assert!(!io_error(io::ErrorKind::ConnectionRefused).is_address_in_use());
+    }
+
+    #[test]
+    fn test_connection_error_conversion() {
+        assert!(matches!(
+            ConnectionError::from(SocketError::Timeout),
+            ConnectionError::TryAgain(_)
+        ));
+
+        for error in [
+            SocketError::PeerClosed,
+            io_error(io::ErrorKind::AddrInUse),
+            SocketError::OversizedFrame {
+                size: 2,
+                max_size: 1,
+            },
+        ] {
+            let message = error.to_string();
+            let connection_error = ConnectionError::from(error);
+
+            assert!(matches!(connection_error, ConnectionError::Other(_)));
+            assert_eq!(
+                SocketError::find_in(&connection_error).map(|e| e.to_string()),
+                Some(message)
+            );
+        }
+    }
+
+    #[test]
+    fn test_reconnect_delay() {
+        const BASE: Duration = Duration::from_secs(1);
+
+        assert_eq!(
+            SocketError::PeerClosed.reconnect_delay(BASE, 0),
+            Duration::ZERO
+        );
+        assert_eq!(SocketError::PeerClosed.reconnect_delay(BASE, 1), BASE);
+
+        let address_in_use = io_error(io::ErrorKind::AddrInUse);
+        assert_eq!(address_in_use.reconnect_delay(BASE, 0), BASE);
+        assert_eq!(
+            address_in_use.reconnect_delay(BASE, 3),
+            Duration::from_secs(8)
+        );
+        assert_eq!(
+            address_in_use.reconnect_delay(BASE, 100),
+            MAX_RECONNECT_DELAY
+        );
+
+        assert_eq!(SocketError::Timeout.reconnect_delay(BASE, 5), BASE);
+        assert_eq!(
+            io_error(io::ErrorKind::ConnectionRefused).reconnect_delay(BASE, 5),
+            BASE
+        );
+    }
+
+    #[test]
+    fn test_find_in_context_chain() {
+        let error = ConnectionError::Other(
+            anyhow::Error::new(SocketError::PeerClosed).context("Failed to receive a packet"),
+        );
+        assert!(matches!(
+            SocketError::find_in(&error),
+            Some(SocketError::PeerClosed)
+        ));
+
+        let error = ConnectionError::Other(anyhow::anyhow!("Unrelated"));
+        assert!(SocketError::find_in(&error).is_none());
+    }
+}
@@ -1,5 +1,9 @@
 mod backend;
 mod control_socket;
+mod datagram_socket;
+mod error;
+mod network_simulator;
+mod request;
 mod stream_socket;
 
 use alvr_common::{anyhow::Result, info};
@@ -10,18 +14,47 @@ use std::{
     time::Duration,
 };
 
+pub use backend::memory::MemoryTransport;
 pub use control_socket::*;
+pub use datagram_socket::*;
+pub use error::*;
+pub use request::*;
 pub use stream_socket::*;
 
 pub const LOCAL_IP: IpAddr = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
-pub const CONTROL_PORT: u16 = 9943;
-pub const HANDSHAKE_PACKET_SIZE_BYTES: usize = 56; // this may change in future protocols
+// The announced control port follows the hostname
+// this may change in future protocols
+pub const HANDSHAKE_PACKET_SIZE_BYTES: usize = 58;
+// Sent by older clients, which are reached on the port they announce from
+pub const LEGACY_HANDSHAKE_PACKET_SIZE_BYTES: usize = 56;
 pub const KEEPALIVE_INTERVAL: Duration = Duration::from_millis(500);
 pub const KEEPALIVE_TIMEOUT: Duration = Duration::from_secs(2);
 
 pub const MDNS_SERVICE_TYPE: &str = "_alvr._tcp.local.";
 pub const MDNS_PROTOCOL_KEY: &str = "protocol";
 
+// Find the address of a local network interface, specified either by name or by one of its IPs.
+// IPv4 addresses are preferred.
+pub fn find_interface_ip(interface: &str) -> Option<IpAddr> {
+    let interfaces = local_ip_address::list_afinet_netifas().ok()?;
+
+    if let Ok(ip) = interface.parse::<IpAddr>() {
+        return interfaces
+            .iter()
+            .any(|(_, local_ip)| *local_ip == ip)
+            .then_some(ip);
+    }
+
+    let mut ips = interfaces
+        .into_iter()
+        .filter(|(name, _)| name == interface)
+        .map(|(_, ip)| ip)
+        .collect::<Vec<_>>();
+    ips.sort_by_key(|ip| !ip.is_ipv4());
+
+    ips.first().copied()
+}
+
 fn set_socket_buffers(
     socket: &socket2::Socket,
     send_buffer_bytes: SocketBufferSize,
@@ -0,0 +1,353 @@
+// Developer tool that degrades the shards written by a stream socket, to reproduce the behavior of
+// bad networks. It wraps the SocketWriter, so it works the same with TCP and UDP. The impairment
+// is chosen per stream ID, the other streams are written right away. The video datagrams are
+// impaired as a whole like the shards of one stream.
+//
+// Which shards are lost depends only on the seed, the stream ID and the sequence of shards of the
+// stream, not on the timing or on the other streams. Delays never reorder the shards of a stream.
+// The bandwidth cap only delays: like with a full socket buffer, writes block while the simulated
+// link is too far behind.
+
+use crate::backend::SocketWriter;
+use alvr_common::{
+    anyhow::{bail, Result},
+    parking_lot::{Condvar, Mutex},
+    settings_schema::Switch,
+};
+use alvr_session::{JitterDistribution, NetworkImpairmentConfig};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    f32::consts::PI,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+// How far behind the simulated link can be before writes block
+const MAX_QUEUE_DELAY: Duration = Duration::from_millis(500);
+
+// SplitMix64. Implemented here so that a seed keeps producing the same sequence across versions
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64, stream_id: u16) -> Self {
+        Self(seed ^ (stream_id as u64).wrapping_mul(0xd6e8_feb8_6659_fd93))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+        z ^ (z >> 31)
+    }
+
+    // In [0, 1)
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1 << 24) as f32
+    }
+
+    // True with a probability of 1 / mean
+    fn one_in(&mut self, mean: u32) -> bool {
+        self.next_f32() * (mean.max(1) as f32) < 1.0
+    }
+}
+
+struct StreamImpairment {
+    config: NetworkImpairmentConfig,
+    rng: Rng,
+    in_loss_episode: bool,
+    // When the simulated link is done transmitting the previous shards
+    link_free_instant: Instant,
+    last_release_instant: Instant,
+}
+
+impl StreamImpairment {
+    fn new(config: NetworkImpairmentConfig, seed: u64, stream_id: u16) -> Self {
+        let now = Instant::now();
+
+        Self {
+            config,
+            rng: Rng::new(seed, stream_id),
+            in_loss_episode: false,
+            link_free_instant: now,
+            last_release_instant: now,
+        }
+    }
+
+    fn jitter(&mut self) -> Duration {
+        let jitter_s = self.config.jitter_ms.max(0.0) / 1000.0;
+        let sample = match self.config.jitter_distribution {
+            JitterDistribution::Uniform => self.rng.next_f32(),
+            JitterDistribution::Normal => {
+                // Box-Muller
+                let radius = (-2.0 * (1.0 - self.rng.next_f32()).ln()).sqrt();
+                (radius * (2.0 * PI * self.rng.next_f32()).cos()).abs()
+            }
+            JitterDistribution::Exponential => -(1.0 - self.rng.next_f32()).ln(),
+        };
+
+        Duration::from_secs_f32(jitter_s * sample)
+    }
+
+    // Returns when the shard should be written, or None if it is lost. The same number of random
+    // values is used for every shard, so that a decision does not shift the following ones
+    fn schedule(&mut self, now: Instant, size: usize) -> Option<Instant> {
+        let burst_lost = if let Switch::Enabled(config) = &self.config.burst_loss {
+            let transition = if self.in_loss_episode {
+                self.rng.one_in(config.mean_episode_shards)
+            } else {
+                self.rng.one_in(config.mean_shards_between_episodes)
+            };
+            self.in_loss_episode ^= transition;
+
+            self.in_loss_episode
+        } else {
+            false
+        };
+        let randomly_lost = self.rng.next_f32() * 100.0 < self.config.loss_percent;
+        let jitter = self.jitter();
+
+        if burst_lost || randomly_lost {
+            return None;
+        }
+
+        let sent_instant = if let Switch::Enabled(bandwidth_mbps) = self.config.bandwidth_mbps {
+            let start = Instant::max(self.link_free_instant, now);
+            self.link_free_instant = start
+                + Duration::from_secs_f32(size as f32 * 8.0 / (bandwidth_mbps.max(0.001) * 1e6));
+
+            self.link_free_instant
+        } else {
+            now
+        };
+
+        let latency = Duration::from_secs_f32(self.config.latency_ms.max(0.0) / 1000.0);
+        self.last_release_instant =
+            Instant::max(self.last_release_instant, sent_instant + latency + jitter);
+
+        Some(self.last_release_instant)
+    }
+
+    // How long the writer should block before the next shard
+    fn backpressure(&self, now: Instant) -> Duration {
+        self.link_free_instant
+            .saturating_duration_since(now)
+            .saturating_sub(MAX_QUEUE_DELAY)
+    }
+}
+
+struct QueueState {
+    // Ordered by release instant, then by write order
+    shards: BinaryHeap<Reverse<(Instant, u64, Vec<u8>)>>,
+    next_sequence: u64,
+    error: Option<String>,
+    closed: bool,
+}
+
+struct Queue {
+    state: Mutex<QueueState>,
+    condvar: Condvar,
+}
+
+pub struct ImpairedWriter {
+    streams: HashMap<u16, StreamImpairment>,
+    // Used for shards without the stream socket prefix
+    fixed_stream_id: Option<u16>,
+    queue: Arc<Queue>,
+}
+
+impl ImpairedWriter {
+    // The inner writer is moved to a thread that writes the shards when they are due
+    pub fn new(
+        mut inner: Box<dyn SocketWriter>,
+        seed: u64,
+        streams: HashMap<u16, NetworkImpairmentConfig>,
+    ) -> Self {
+        let queue = Arc::new(Queue {
+            state: Mutex::new(QueueState {
+                shards: BinaryHeap::new(),
+                next_sequence: 0,
+                error: None,
+                closed: false,
+            }),
+            condvar: Condvar::new(),
+        });
+
+        thread::spawn({
+            let queue = Arc::clone(&queue);
+            move || {
+                let mut state = queue.state.lock();
+                while !state.closed {
+                    let Some(Reverse((release_instant, ..))) = state.shards.peek() else {
+                        queue.condvar.wait(&mut state);
+                        continue;
+                    };
+
+                    if *release_instant > Instant::now() {
+                        let release_instant = *release_instant;
+                        queue.condvar.wait_until(&mut state, release_instant);
+                        continue;
+                    }
+
+                    let Reverse((_, _, shard)) = state.shards.pop().unwrap();
+                    drop(state);
+                    let result = inner.send(&shard);
+                    state = queue.state.lock();
+
+                    if let Err(e) = result {
+                        state.error = Some(e.to_string());
+                        return;
+                    }
+                }
+            }
+        });
+
+        Self {
+            streams: streams
+                .into_iter()
+                .map(|(id, config)| (id, StreamImpairment::new(config, seed, id)))
+                .collect(),
+            fixed_stream_id: None,
+            queue,
+        }
+    }
+
+    // All shards are impaired as part of stream_id
+    pub fn for_stream(
+        inner: Box<dyn SocketWriter>,
+        seed: u64,
+        stream_id: u16,
+        config: NetworkImpairmentConfig,
+    ) -> Self {
+        let mut writer = Self::new(inner, seed, HashMap::from([(stream_id, config)]));
+        writer.fixed_stream_id = Some(stream_id);
+
+        writer
+    }
+}
+
+impl SocketWriter for ImpairedWriter {
+    fn send(&mut self, shard: &[u8]) -> Result<()> {
+        let now = Instant::now();
+
+        // The stream ID follows the shard length in the prefix
+        let stream_id = self.fixed_stream_id.or_else(|| {
+            shard
+                .get(4..6)
+                .map(|bytes| u16::from_be_bytes(bytes.try_into().unwrap()))
+        });
+        let release_instant = match stream_id.and_then(|id| self.streams.get_mut(&id)) {
+            Some(stream) => {
+                let release_instant = stream.schedule(now, shard.len());
+                thread::sleep(stream.backpressure(now));
+
+                match release_instant {
+                    Some(instant) => instant,
+                    None => return Ok(()),
+                }
+            }
+            None => now,
+        };
+
+        let mut state = self.queue.state.lock();
+        if let Some(e) = &state.error {
+            bail!("Failed to write a delayed shard: {e}");
+        }
+
+        let sequence = state.next_sequence;
+        state.next_sequence += 1;
+        state
+            .shards
+            .push(Reverse((release_instant, sequence, shard.to_vec())));
+        self.queue.condvar.notify_one();
+
+        Ok(())
+    }
+}
+
+impl Drop for ImpairedWriter {
+    fn drop(&mut self) {
+        self.queue.state.lock().closed = true;
+        self.queue.condvar.notify_one();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn impairment_config(loss_percent: f32) -> NetworkImpairmentConfig {
+        NetworkImpairmentConfig {
+            latency_ms: 10.0,
+            jitter_ms: 5.0,
+            jitter_distribution: JitterDistribution::Exponential,
+            loss_percent,
+            bandwidth_mbps: Switch::Disabled,
+            burst_loss: Switch::Disabled,
+        }
+    }
+
+    fn decisions(
+        stream: &mut StreamImpairment,
+        now: Instant,
+        count: usize,
+    ) -> Vec<Option<Instant>> {
+        (0..count).map(|_| stream.schedule(now, 1000)).collect()
+    }
+
+    #[test]
+    fn test_same_seed_same_decisions() {
+        let now = Instant::now();
+        let mut first = StreamImpairment::new(impairment_config(10.0), 42, 3);
+        let mut second = StreamImpairment::new(impairment_config(10.0), 42, 3);
+        first.last_release_instant = now;
+        second.last_release_instant = now;
+
+        let first_decisions = decisions(&mut first, now, 1000);
+        assert_eq!(first_decisions, decisions(&mut second, now, 1000));
+
+        let lost = first_decisions.iter().filter(|d| d.is_none()).count();
+        assert!((50..150).contains(&lost));
+
+        // Delays never reorder the shards
+        let released = first_decisions.iter().flatten().collect::<Vec<_>>();
+        assert!(released.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(released
+            .iter()
+            .all(|instant| **instant >= now + Duration::from_millis(10)));
+
+        // Another stream gets another sequence
+        let mut other_stream = StreamImpairment::new(impairment_config(10.0), 42, 1);
+        other_stream.last_release_instant = now;
+        assert_ne!(first_decisions, decisions(&mut other_stream, now, 1000));
+    }
+
+    #[test]
+    fn test_bandwidth_cap() {
+        let now = Instant::now();
+        let mut config = impairment_config(0.0);
+        config.jitter_ms = 0.0;
+        config.latency_ms = 0.0;
+        // 1000 bytes take 1 ms
+        config.bandwidth_mbps = Switch::Enabled(8.0);
+        let mut stream = StreamImpairment::new(config, 0, 3);
+        stream.link_free_instant = now;
+        stream.last_release_instant = now;
+
+        let released = decisions(&mut stream, now, 600);
+        let tenth = released[9].unwrap().duration_since(now);
+        assert!(tenth > Duration::from_micros(9900) && tenth < Duration::from_micros(10100));
+
+        // Nothing is dropped, the writer is blocked instead
+        assert!(released.iter().all(Option::is_some));
+        let backpressure = stream.backpressure(now);
+        assert!(
+            backpressure > Duration::from_micros(99900)
+                && backpressure < Duration::from_micros(100100)
+        );
+        assert_eq!(stream.backpressure(now + backpressure), Duration::ZERO);
+    }
+}
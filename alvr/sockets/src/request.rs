@@ -0,0 +1,319 @@
+use alvr_common::{
+    parking_lot::{Condvar, Mutex},
+    RelaxedAtomicU64,
+};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display},
+    future::Future,
+    mem,
+    pin::Pin,
+    sync::{Arc, Weak},
+    task::{Context, Poll, Waker},
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestError {
+    Timeout,
+    // The connection was closed, or not established yet, before the response arrived
+    Disconnected,
+    // The peer does not know this request type
+    Unsupported,
+    // The peer knows the request but could not fulfill it
+    Failed(String),
+    // The response does not correspond to the request type
+    UnexpectedResponse,
+}
+
+impl Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestError::Timeout => write!(f, "The request timed out"),
+            RequestError::Disconnected => write!(f, "Not connected"),
+            RequestError::Unsupported => write!(f, "The request is not supported by the peer"),
+            RequestError::Failed(message) => write!(f, "The request failed: {message}"),
+            RequestError::UnexpectedResponse => write!(f, "Unexpected response"),
+        }
+    }
+}
+
+impl Error for RequestError {}
+
+struct ResponseSlot<R> {
+    result: Mutex<Option<Result<R, RequestError>>>,
+    waker: Mutex<Option<Waker>>,
+    notif: Condvar,
+}
+
+impl<R> ResponseSlot<R> {
+    // Only the first result is kept
+    fn resolve(&self, result: Result<R, RequestError>) {
+        {
+            let mut result_lock = self.result.lock();
+            if result_lock.is_none() {
+                *result_lock = Some(result);
+            }
+        }
+        self.notif.notify_all();
+
+        if let Some(waker) = self.waker.lock().take() {
+            waker.wake();
+        }
+    }
+}
+
+struct PendingRequest<R> {
+    slot: Arc<ResponseSlot<R>>,
+    deadline: Instant,
+}
+
+type PendingRequests<R> = Mutex<HashMap<u64, PendingRequest<R>>>;
+
+// Matches responses to requests with a correlation ID, which is sent with the request and echoed
+// back with the response. Any number of requests can be outstanding. Responses to requests that
+// already timed out are dropped
+pub struct RequestTracker<R> {
+    last_id: RelaxedAtomicU64,
+    pending: Arc<PendingRequests<R>>,
+}
+
+impl<R> Default for RequestTracker<R> {
+    fn default() -> Self {
+        Self {
+            last_id: RelaxedAtomicU64::new(0),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl<R> RequestTracker<R> {
+    // The returned ID must be sent with the request
+    pub fn begin(&self, timeout: Duration) -> (u64, PendingResponse<R>) {
+        let id = self.last_id.add(1);
+        let slot = Arc::new(ResponseSlot {
+            result: Mutex::new(None),
+            waker: Mutex::new(None),
+            notif: Condvar::new(),
+        });
+        let deadline = Instant::now() + timeout;
+
+        self.pending.lock().insert(
+            id,
+            PendingRequest {
+                slot: Arc::clone(&slot),
+                deadline,
+            },
+        );
+
+        (
+            id,
+            PendingResponse {
+                id,
+                slot,
+                deadline,
+                pending: Arc::downgrade(&self.pending),
+            },
+        )
+    }
+
+    // Returns false if there is no pending request with this ID
+    pub fn complete(&self, id: u64, result: Result<R, RequestError>) -> bool {
+        let request = self.pending.lock().remove(&id);
+        if let Some(request) = request {
+            request.slot.resolve(result);
+
+            true
+        } else {
+            false
+        }
+    }
+
+    // Blocking waits time out by themselves, but awaited responses are resolved only when this is
+    // called. The owner of the tracker should call it periodically
+    pub fn expire(&self, now: Instant) {
+        let expired = {
+            let mut pending = self.pending.lock();
+            let ids = pending
+                .iter()
+                .filter(|(_, request)| request.deadline <= now)
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>();
+
+            ids.into_iter()
+                .filter_map(|id| pending.remove(&id))
+                .collect::<Vec<_>>()
+        };
+
+        for request in expired {
+            request.slot.resolve(Err(RequestError::Timeout));
+        }
+    }
+
+    // To be called when the connection is closed
+    pub fn cancel_all(&self) {
+        let pending = mem::take(&mut *self.pending.lock());
+
+        for request in pending.into_values() {
+            request.slot.resolve(Err(RequestError::Disconnected));
+        }
+    }
+}
+
+// Can be waited on with wait(), or awaited. Dropping it abandons the request
+pub struct PendingResponse<R> {
+    id: u64,
+    slot: Arc<ResponseSlot<R>>,
+    deadline: Instant,
+    pending: Weak<PendingRequests<R>>,
+}
+
+impl<R> PendingResponse<R> {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    // Blocks until the response arrives or the timeout passed to begin() expires
+    pub fn wait(self) -> Result<R, RequestError> {
+        let mut result_lock = self.slot.result.lock();
+        while result_lock.is_none() {
+            if self
+                .slot
+                .notif
+                .wait_until(&mut result_lock, self.deadline)
+                .timed_out()
+            {
+                break;
+            }
+        }
+
+        result_lock.take().unwrap_or(Err(RequestError::Timeout))
+    }
+}
+
+impl<R> Future for PendingResponse<R> {
+    type Output = Result<R, RequestError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // The waker is registered before checking the result, so a result set in between is not
+        // missed
+        *self.slot.waker.lock() = Some(cx.waker().clone());
+
+        if let Some(result) = self.slot.result.lock().take() {
+            Poll::Ready(result)
+        } else if Instant::now() >= self.deadline {
+            Poll::Ready(Err(RequestError::Timeout))
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<R> Drop for PendingResponse<R> {
+    fn drop(&mut self) {
+        if let Some(pending) = self.pending.upgrade() {
+            pending.lock().remove(&self.id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        sync::atomic::{AtomicBool, Ordering},
+        task::Wake,
+        thread,
+    };
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    struct FlagWaker(AtomicBool);
+
+    impl Wake for FlagWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.store(true, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_out_of_order_responses() {
+        let tracker = RequestTracker::<&str>::default();
+
+        let (first_id, first) = tracker.begin(TIMEOUT);
+        let (second_id, second) = tracker.begin(TIMEOUT);
+        assert_ne!(first_id, second_id);
+
+        assert!(tracker.complete(second_id, Ok("second")));
+        assert!(tracker.complete(first_id, Ok("first")));
+        // Responses are delivered once
+        assert!(!tracker.complete(first_id, Ok("again")));
+
+        assert_eq!(first.wait(), Ok("first"));
+        assert_eq!(second.wait(), Ok("second"));
+    }
+
+    #[test]
+    fn test_blocking_wait() {
+        let tracker = Arc::new(RequestTracker::<u32>::default());
+
+        let (id, pending) = tracker.begin(TIMEOUT);
+        let responder = thread::spawn({
+            let tracker = Arc::clone(&tracker);
+            move || {
+                thread::sleep(Duration::from_millis(10));
+                tracker.complete(id, Ok(42));
+            }
+        });
+
+        assert_eq!(pending.wait(), Ok(42));
+        responder.join().unwrap();
+
+        let (id, pending) = tracker.begin(Duration::from_millis(10));
+        assert_eq!(pending.wait(), Err(RequestError::Timeout));
+        // A late response is dropped
+        assert!(!tracker.complete(id, Ok(0)));
+    }
+
+    #[test]
+    fn test_poll() {
+        let tracker = RequestTracker::<u32>::default();
+        let flag = Arc::new(FlagWaker(AtomicBool::new(false)));
+        let waker = Waker::from(Arc::clone(&flag));
+        let mut cx = Context::from_waker(&waker);
+
+        let (id, mut pending) = tracker.begin(TIMEOUT);
+        assert_eq!(Pin::new(&mut pending).poll(&mut cx), Poll::Pending);
+        tracker.complete(id, Ok(1));
+        assert!(flag.0.load(Ordering::Relaxed));
+        assert_eq!(Pin::new(&mut pending).poll(&mut cx), Poll::Ready(Ok(1)));
+
+        // Expired requests wake their waiters
+        flag.0.store(false, Ordering::Relaxed);
+        let (id, mut pending) = tracker.begin(TIMEOUT);
+        assert_eq!(Pin::new(&mut pending).poll(&mut cx), Poll::Pending);
+        tracker.expire(Instant::now() + TIMEOUT);
+        assert!(flag.0.load(Ordering::Relaxed));
+        assert_eq!(
+            Pin::new(&mut pending).poll(&mut cx),
+            Poll::Ready(Err(RequestError::Timeout))
+        );
+        assert!(!tracker.complete(id, Ok(0)));
+    }
+
+    #[test]
+    fn test_cancel_and_drop() {
+        let tracker = RequestTracker::<u32>::default();
+
+        let (_, first) = tracker.begin(TIMEOUT);
+        let (_, second) = tracker.begin(TIMEOUT);
+        tracker.cancel_all();
+        assert_eq!(first.wait(), Err(RequestError::Disconnected));
+        assert_eq!(second.wait(), Err(RequestError::Disconnected));
+
+        let (id, pending) = tracker.begin(TIMEOUT);
+        drop(pending);
+        assert!(!tracker.complete(id, Ok(0)));
+    }
+}
@@ -15,29 +15,106 @@
 // packet.
 // Note: We can't clone the underlying socket for each StreamSender and the mutex around the socket
 // cannot be removed. This is because we need to make sure at least shards are written whole.
-
-use crate::backend::{tcp, udp, SocketReader, SocketWriter};
+//
+// With TCP, Nagle's algorithm is disabled, so each small packet would be sent in its own
+// undersized TCP segment. Packets sent with StreamSender::queue() are instead collected and written
+// together. The shards are unchanged, the receiver cannot tell the difference.
+//
+// Large packets written in one go can overflow the buffers of routers and access points.
+// StreamSender::send_paced() spreads the shards of a packet over time instead.
+//
+// The receive loop of StreamSocket::spawn_receive_thread() reassembles the shards and hands each
+// packet to the StreamReceiver of its stream. A receiver that falls behind only affects its own
+// stream, according to its OverflowPolicy, unless the policy is Block.
+//
+// A DirectStreamReceiver instead lets the receive loop write the shards of a packet straight into
+// a buffer provided by the reader, for example the input buffer of a decoder, skipping the copy
+// out of the pooled buffers. The stream is read one packet at a time, like with Block.
+
+use crate::{
+    backend::{tcp, udp, SocketReader, SocketWriter},
+    network_simulator::ImpairedWriter,
+    LOCAL_IP,
+};
 use alvr_common::{
-    anyhow::Result, debug, parking_lot::Mutex, AnyhowToCon, ConResult, HandleTryAgain, ToCon,
+    anyhow::Result,
+    con_bail, debug,
+    parking_lot::{Condvar, Mutex},
+    ConResult, ConnectionError, ToCon,
 };
-use alvr_session::{DscpTos, SocketBufferSize, SocketProtocol};
+use alvr_session::{DscpTos, NetworkImpairmentConfig, SocketBufferSize, SocketProtocol};
 use serde::{de::DeserializeOwned, Serialize};
 use std::{
     cmp::Ordering,
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     marker::PhantomData,
     mem,
     net::{IpAddr, TcpListener, UdpSocket},
-    sync::{mpsc, Arc},
-    time::Duration,
+    slice,
+    sync::{
+        atomic::{self, AtomicUsize},
+        mpsc, Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
 
-const SHARD_PREFIX_SIZE: usize = mem::size_of::<u32>() // packet length - field itself (4 bytes)
+pub(crate) const SHARD_PREFIX_SIZE: usize = mem::size_of::<u32>() // packet length - field itself (4 bytes)
     + mem::size_of::<u16>() // stream ID
     + mem::size_of::<u32>() // packet index
     + mem::size_of::<u32>() // shards count
     + mem::size_of::<u32>(); // shards index
 
+// Queued shards are written once they reach this size, or once they waited this long
+const MAX_BATCH_SIZE: usize = 4096;
+const MAX_BATCH_AGE: Duration = Duration::from_micros(500);
+// The batch flusher checks whether the socket was dropped at least this often
+const BATCH_FLUSHER_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// Paced shards are written in small bursts when they are late by less than this, shorter sleeps are
+// not accurate on most platforms
+const MIN_PACING_SLEEP: Duration = Duration::from_micros(250);
+
+// The receive loop checks whether it should stop at least this often while a stream blocks it
+const BLOCKED_STREAM_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// What happens to a new packet of a stream when all the buffers of the stream are in use, because
+/// its receiver is not reading fast enough
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OverflowPolicy {
+    /// The new packet is discarded
+    DropNewest,
+    /// The oldest packet not read yet is discarded. For streams where only the latest packet
+    /// matters, like tracking
+    DropOldest,
+    /// The receive loop waits for the receiver. This holds back the packets of all the other
+    /// streams, meant only for streams where every packet matters, like video
+    Block,
+}
+
+// Waits until the shard is due. The shards of a packet are spread evenly over the pacing duration,
+// the first one is sent right away. Returns the time spent waiting
+pub(crate) fn wait_paced_shard(
+    start: Instant,
+    duration: Duration,
+    index: usize,
+    count: usize,
+) -> Duration {
+    if duration == Duration::ZERO {
+        return Duration::ZERO;
+    }
+
+    let shard_instant = start + duration.mul_f32(index as f32 / count as f32);
+    let wait = shard_instant.saturating_duration_since(Instant::now());
+    if wait >= MIN_PACING_SLEEP {
+        thread::sleep(wait);
+
+        wait
+    } else {
+        Duration::ZERO
+    }
+}
+
 /// Memory buffer that contains a hidden prefix
 #[derive(Default)]
 pub struct Buffer<H = ()> {
@@ -84,9 +161,99 @@ impl<H> Buffer<H> {
     }
 }
 
+// Stands in for the writer while it is being wrapped
+struct NoWriter;
+
+impl SocketWriter for NoWriter {
+    fn send(&mut self, _: &[u8]) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct SendSocket {
+    writer: Box<dyn SocketWriter>,
+    batch: Vec<u8>,
+    batch_start: Option<Instant>,
+    // Notified when a new batch starts, for the batch flusher
+    batch_started: Arc<Condvar>,
+}
+
+impl SendSocket {
+    fn new(writer: Box<dyn SocketWriter>) -> Self {
+        Self {
+            writer,
+            batch: vec![],
+            batch_start: None,
+            batch_started: Arc::new(Condvar::new()),
+        }
+    }
+
+    fn queue(&mut self, shard: &[u8]) -> Result<()> {
+        if !self.writer.can_batch() {
+            return self.writer.send(shard);
+        }
+
+        self.batch.extend_from_slice(shard);
+        let batch_start = *self.batch_start.get_or_insert_with(|| {
+            self.batch_started.notify_one();
+
+            Instant::now()
+        });
+
+        if self.batch.len() >= MAX_BATCH_SIZE || batch_start.elapsed() >= MAX_BATCH_AGE {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.batch_start = None;
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+
+        let res = self.writer.send(&self.batch);
+        self.batch.clear();
+
+        res
+    }
+
+    // Queued shards are written first, they should not wait for a whole video frame
+    fn send(&mut self, shard: &[u8]) -> Result<()> {
+        self.flush()?;
+        self.writer.send(shard)
+    }
+}
+
+// Writes the queued shards once they waited MAX_BATCH_AGE, even if nothing else is sent on the
+// socket. The thread ends once the socket is dropped
+fn spawn_batch_flusher(socket: &Arc<Mutex<SendSocket>>) {
+    let batch_started = Arc::clone(&socket.lock().batch_started);
+    let socket = Arc::downgrade(socket);
+
+    thread::spawn(move || {
+        while let Some(socket) = socket.upgrade() {
+            let mut socket_lock = socket.lock();
+            match socket_lock.batch_start {
+                Some(start) if start.elapsed() >= MAX_BATCH_AGE => {
+                    // A write error is reported again by the next send
+                    socket_lock.flush().ok();
+                }
+                Some(start) => {
+                    batch_started.wait_until(&mut socket_lock, start + MAX_BATCH_AGE);
+                }
+                None => {
+                    batch_started.wait_for(&mut socket_lock, BATCH_FLUSHER_POLL_INTERVAL);
+                }
+            }
+        }
+    });
+}
+
 #[derive(Clone)]
 pub struct StreamSender<H> {
-    inner: Arc<Mutex<Box<dyn SocketWriter>>>,
+    inner: Arc<Mutex<SendSocket>>,
     stream_id: u16,
     max_packet_size: usize,
     // if the packet index overflows the worst that happens is a false positive packet loss
@@ -98,7 +265,38 @@ pub struct StreamSender<H> {
 impl<H> StreamSender<H> {
     /// Shard and send a buffer with zero copies and zero allocations.
     /// The prefix of each shard is written over the previously sent shard to avoid reallocations.
-    pub fn send(&mut self, mut buffer: Buffer<H>) -> Result<()> {
+    pub fn send(&mut self, buffer: Buffer<H>) -> Result<()> {
+        self.send_shards(buffer, false, Duration::ZERO).map(|_| ())
+    }
+
+    /// Like send(), but the shards are spread evenly over the given duration instead of being
+    /// written in a single burst. Returns the time spent waiting for the pacing, without the time
+    /// spent writing to the socket
+    pub fn send_paced(&mut self, buffer: Buffer<H>, duration: Duration) -> Result<Duration> {
+        self.send_shards(buffer, false, duration)
+    }
+
+    /// Like send(), but the shards may be held back to be written together with other queued
+    /// packets of any stream. They are written by the next flush() or send() on the same socket,
+    /// or once the queued data grows too large or too old, at most MAX_BATCH_AGE later. Meant for
+    /// small packets sent in bursts.
+    pub fn queue(&mut self, buffer: Buffer<H>) -> Result<()> {
+        self.send_shards(buffer, true, Duration::ZERO).map(|_| ())
+    }
+
+    /// Write the packets queued on the socket by any stream
+    pub fn flush(&mut self) -> Result<()> {
+        self.inner.lock().flush()
+    }
+
+    fn send_shards(
+        &mut self,
+        mut buffer: Buffer<H>,
+        batched: bool,
+        pacing_duration: Duration,
+    ) -> Result<Duration> {
+        let start = Instant::now();
+        let mut pacing_wait = Duration::ZERO;
         let max_shard_data_size = self.max_packet_size - SHARD_PREFIX_SIZE;
         let actual_buffer_size = buffer.hidden_offset + buffer.length;
         let data_size = actual_buffer_size - SHARD_PREFIX_SIZE;
@@ -125,14 +323,22 @@ impl<H> StreamSender<H> {
             sub_buffer[10..14].copy_from_slice(&(shards_count as u32).to_be_bytes());
             sub_buffer[14..18].copy_from_slice(&(idx as u32).to_be_bytes());
 
-            self.inner.lock().send(&sub_buffer[..packet_length])?;
+            // The socket is not locked while sleeping, other streams can write in the meantime
+            pacing_wait += wait_paced_shard(start, pacing_duration, idx, shards_count);
+
+            let shard = &sub_buffer[..packet_length];
+            if batched {
+                self.inner.lock().queue(shard)?;
+            } else {
+                self.inner.lock().send(shard)?;
+            }
         }
 
         self.next_packet_index += 1;
 
         self.used_buffers.push(buffer.inner);
 
-        Ok(())
+        Ok(pacing_wait)
     }
 }
 
@@ -161,6 +367,11 @@ impl<H: Serialize> StreamSender<H> {
         let buffer = self.get_buffer(header)?;
         self.send(buffer)
     }
+
+    pub fn queue_header(&mut self, header: &H) -> Result<()> {
+        let buffer = self.get_buffer(header)?;
+        self.queue(buffer)
+    }
 }
 
 pub struct ReceiverData<H> {
@@ -172,6 +383,22 @@ pub struct ReceiverData<H> {
 }
 
 impl<H> ReceiverData<H> {
+    // The buffer contains the packet after SHARD_PREFIX_SIZE unused bytes
+    pub(crate) fn new(
+        buffer: Vec<u8>,
+        size: usize,
+        used_buffer_queue: mpsc::Sender<Vec<u8>>,
+        had_packet_loss: bool,
+    ) -> Self {
+        Self {
+            buffer: Some(buffer),
+            size,
+            used_buffer_queue,
+            had_packet_loss,
+            _phantom: PhantomData,
+        }
+    }
+
     pub fn had_packet_loss(&self) -> bool {
         self.had_packet_loss
     }
@@ -204,14 +431,49 @@ struct ReconstructedPacket {
     size: usize, // contains prefix
 }
 
+#[derive(Default)]
+struct PacketQueueState {
+    packets: VecDeque<ReconstructedPacket>,
+    // Set when the socket is dropped
+    closed: bool,
+}
+
+// Reconstructed packets of one stream. Unlike a channel, the receive loop can take back the oldest
+// packet for OverflowPolicy::DropOldest
+#[derive(Default)]
+struct PacketQueue {
+    state: Mutex<PacketQueueState>,
+    condvar: Condvar,
+}
+
+impl PacketQueue {
+    fn push(&self, packet: ReconstructedPacket) {
+        self.state.lock().packets.push_back(packet);
+        self.condvar.notify_one();
+    }
+
+    fn close(&self) {
+        self.state.lock().closed = true;
+        self.condvar.notify_all();
+    }
+}
+
 pub struct StreamReceiver<H> {
-    packet_receiver: mpsc::Receiver<ReconstructedPacket>,
+    packet_queue: Arc<PacketQueue>,
     used_buffer_queue: mpsc::Sender<Vec<u8>>,
     last_packet_index: Option<u32>,
+    dropped_packets: Arc<AtomicUsize>,
     _phantom: PhantomData<H>,
 }
 
-fn wrapping_cmp(lhs: u32, rhs: u32) -> Ordering {
+impl<H> StreamReceiver<H> {
+    /// Packets discarded by the receive loop because of the OverflowPolicy, since the subscription
+    pub fn dropped_packets(&self) -> usize {
+        self.dropped_packets.load(atomic::Ordering::Relaxed)
+    }
+}
+
+pub(crate) fn wrapping_cmp(lhs: u32, rhs: u32) -> Ordering {
     let diff = lhs.wrapping_sub(rhs);
     if diff == 0 {
         Ordering::Equal
@@ -227,10 +489,18 @@ fn wrapping_cmp(lhs: u32, rhs: u32) -> Ordering {
 /// Returns true if a packet has been recontructed and copied into the buffer.
 impl<H: DeserializeOwned + Serialize> StreamReceiver<H> {
     pub fn recv(&mut self, timeout: Duration) -> ConResult<ReceiverData<H>> {
-        let packet = self
-            .packet_receiver
-            .recv_timeout(timeout)
-            .handle_try_again()?;
+        let packet = {
+            let mut state = self.packet_queue.state.lock();
+            if state.packets.is_empty() && !state.closed {
+                self.packet_queue.condvar.wait_for(&mut state, timeout);
+            }
+
+            match state.packets.pop_front() {
+                Some(packet) => packet,
+                None if state.closed => con_bail!("The stream socket was closed"),
+                None => return alvr_common::try_again(),
+            }
+        };
 
         let mut had_packet_loss = false;
 
@@ -251,16 +521,223 @@ impl<H: DeserializeOwned + Serialize> StreamReceiver<H> {
         }
         self.last_packet_index = Some(packet.index);
 
-        Ok(ReceiverData {
-            buffer: Some(packet.buffer),
-            size: packet.size,
-            used_buffer_queue: self.used_buffer_queue.clone(),
+        Ok(ReceiverData::new(
+            packet.buffer,
+            packet.size,
+            self.used_buffer_queue.clone(),
             had_packet_loss,
-            _phantom: PhantomData,
+        ))
+    }
+}
+
+// Packet of a direct stream which is being received
+struct DirectPacket {
+    index: u32,
+    shards_count: usize,
+    // Contains the prefix, then the header and the start of the payload
+    first_shard: Vec<u8>,
+    // Set by recv_header()
+    header_size: Option<usize>,
+    payload_size: usize,
+    // Set by recv_payload_into() while it waits for the rest of the shards
+    destination: Option<(*mut u8, usize)>,
+    outcome: Option<ConResult<usize>>,
+}
+
+struct DirectStreamState {
+    packet: Option<DirectPacket>,
+    received_shards: Vec<bool>,
+    // Buffer for the next first shard
+    spare_buffer: Vec<u8>,
+    // Set when shards were lost since the last packet header
+    had_packet_loss: bool,
+    // Set when the socket is dropped
+    closed: bool,
+    // Set when the receiver is dropped or invalidated, its shards are discarded from then on
+    receiver_dropped: bool,
+}
+
+// Safety: the destination is written only while holding the lock and while it is set, it is set
+// only for the duration of recv_payload_into()
+unsafe impl Send for DirectStreamState {}
+
+struct DirectStream {
+    state: Mutex<DirectStreamState>,
+    condvar: Condvar,
+}
+
+pub struct PacketHeader<H> {
+    pub header: H,
+    pub had_packet_loss: bool,
+    /// The payload is at most this long. recv_payload_into() needs a buffer at least this big
+    pub max_payload_size: usize,
+}
+
+/// Receives the packets of a stream one at a time, the payload is written directly into a buffer
+/// provided by the caller. If reading a payload fails because of the socket or a timeout, the
+/// receiver is invalidated and all further calls fail.
+pub struct DirectStreamReceiver<H> {
+    stream: Arc<DirectStream>,
+    max_shard_data_size: usize,
+    last_packet_index: Option<u32>,
+    invalidated: bool,
+    _phantom: PhantomData<H>,
+}
+
+impl<H> DirectStreamReceiver<H> {
+    fn check_valid(&self) -> ConResult {
+        if self.invalidated {
+            con_bail!("The receiver failed to read a previous packet");
+        }
+
+        Ok(())
+    }
+
+    /// Receives the payload of the packet returned by the last recv_header() call. Returns the
+    /// size of the payload. If the buffer is too small, an error is returned and the payload can
+    /// be read again with a bigger buffer. If some shards of the packet were lost, try_again is
+    /// returned and the packet is discarded
+    pub fn recv_payload_into(&mut self, buffer: &mut [u8], timeout: Duration) -> ConResult<usize> {
+        self.check_valid()?;
+
+        let deadline = Instant::now() + timeout;
+        let mut state = self.stream.state.lock();
+
+        let Some(packet) = state.packet.as_mut().filter(|p| p.header_size.is_some()) else {
+            con_bail!("recv_header() must be called before recv_payload_into()");
+        };
+        let header_size = packet.header_size.unwrap();
+        let max_payload_size =
+            (packet.shards_count * self.max_shard_data_size).saturating_sub(header_size);
+        if buffer.len() < max_payload_size {
+            con_bail!(
+                "Payload buffer too small: {} bytes, {max_payload_size} needed",
+                buffer.len()
+            );
+        }
+
+        let first_payload = &packet.first_shard[SHARD_PREFIX_SIZE + header_size..];
+        buffer[..first_payload.len()].copy_from_slice(first_payload);
+        packet.payload_size = first_payload.len();
+
+        if packet.shards_count == 1 {
+            packet.outcome = Some(Ok(packet.payload_size));
+        } else {
+            packet.destination = Some((buffer.as_mut_ptr(), buffer.len()));
+            self.stream.condvar.notify_all();
+        }
+
+        let res = loop {
+            if let Some(outcome) = state.packet.as_mut().unwrap().outcome.take() {
+                break outcome;
+            }
+            if state.closed {
+                break Err(ConnectionError::Other(alvr_common::anyhow::anyhow!(
+                    "The stream socket was closed"
+                )));
+            }
+            if self
+                .stream
+                .condvar
+                .wait_until(&mut state, deadline)
+                .timed_out()
+            {
+                break Err(ConnectionError::Other(alvr_common::anyhow::anyhow!(
+                    "Timed out while receiving the payload"
+                )));
+            }
+        };
+
+        let packet = state.packet.take().unwrap();
+        state.spare_buffer = packet.first_shard;
+        if matches!(res, Err(ConnectionError::Other(_))) {
+            self.invalidated = true;
+            state.receiver_dropped = true;
+        }
+        self.stream.condvar.notify_all();
+
+        res
+    }
+}
+
+impl<H: DeserializeOwned> DirectStreamReceiver<H> {
+    /// Waits for the next packet. Its payload must be read with recv_payload_into() before the
+    /// next call
+    pub fn recv_header(&mut self, timeout: Duration) -> ConResult<PacketHeader<H>> {
+        self.check_valid()?;
+
+        let deadline = Instant::now() + timeout;
+        let mut state = self.stream.state.lock();
+
+        if state
+            .packet
+            .as_ref()
+            .is_some_and(|p| p.header_size.is_some())
+        {
+            con_bail!("The payload of the previous packet was not read");
+        }
+
+        while state.packet.is_none() && !state.closed {
+            if self
+                .stream
+                .condvar
+                .wait_until(&mut state, deadline)
+                .timed_out()
+            {
+                break;
+            }
+        }
+
+        let state = &mut *state;
+        let Some(packet) = &mut state.packet else {
+            if state.closed {
+                self.invalidated = true;
+                state.receiver_dropped = true;
+                con_bail!("The stream socket was closed");
+            }
+
+            return alvr_common::try_again();
+        };
+
+        // This will partially consume the slice, leaving only the payload
+        let mut data = &packet.first_shard[SHARD_PREFIX_SIZE..];
+        let header = match bincode::deserialize_from(&mut data) {
+            Ok(header) => header,
+            Err(e) => {
+                state.spare_buffer = state.packet.take().unwrap().first_shard;
+                self.stream.condvar.notify_all();
+
+                return Err(e).to_con();
+            }
+        };
+        let header_size = packet.first_shard.len() - SHARD_PREFIX_SIZE - data.len();
+        packet.header_size = Some(header_size);
+
+        let mut had_packet_loss = mem::take(&mut state.had_packet_loss);
+        if let Some(last_idx) = self.last_packet_index {
+            had_packet_loss |=
+                wrapping_cmp(packet.index, last_idx.wrapping_add(1)) != Ordering::Equal;
+        }
+        self.last_packet_index = Some(packet.index);
+
+        Ok(PacketHeader {
+            header,
+            had_packet_loss,
+            max_payload_size: (packet.shards_count * self.max_shard_data_size)
+                .saturating_sub(header_size),
         })
     }
 }
 
+impl<H> Drop for DirectStreamReceiver<H> {
+    fn drop(&mut self) {
+        let mut state = self.stream.state.lock();
+        state.receiver_dropped = true;
+        state.packet = None;
+        self.stream.condvar.notify_all();
+    }
+}
+
 pub enum StreamSocketBuilder {
     Tcp(TcpListener),
     Udp(UdpSocket),
@@ -277,6 +754,7 @@ impl StreamSocketBuilder {
     ) -> Result<Self> {
         Ok(match stream_socket_config {
             SocketProtocol::Udp => StreamSocketBuilder::Udp(udp::bind(
+                LOCAL_IP,
                 port,
                 stream_tos_config,
                 send_buffer_bytes,
@@ -303,7 +781,7 @@ impl StreamSocketBuilder {
             match self {
                 StreamSocketBuilder::Udp(socket) => {
                     let (send_socket, receive_socket) =
-                        udp::connect(&socket, server_ip, port, timeout).to_con()?;
+                        udp::connect(&socket, server_ip, port, timeout)?;
 
                     (Box::new(send_socket), Box::new(receive_socket))
                 }
@@ -315,20 +793,25 @@ impl StreamSocketBuilder {
                 }
             };
 
+        let send_socket = Arc::new(Mutex::new(SendSocket::new(send_socket)));
+        spawn_batch_flusher(&send_socket);
+
         Ok(StreamSocket {
             // +4 is a workaround to retain compatibilty with old protocol
             // todo: remove +4
             max_packet_size: max_packet_size + 4,
-            send_socket: Arc::new(Mutex::new(send_socket)),
+            send_socket,
             receive_socket,
             shard_recv_state: None,
             stream_recv_components: HashMap::new(),
+            direct_streams: HashMap::new(),
         })
     }
 
     #[allow(clippy::too_many_arguments)]
     pub fn connect_to_client(
         timeout: Duration,
+        local_ip: IpAddr,
         client_ip: IpAddr,
         port: u16,
         protocol: SocketProtocol,
@@ -341,15 +824,16 @@ impl StreamSocketBuilder {
             match protocol {
                 SocketProtocol::Udp => {
                     let socket =
-                        udp::bind(port, dscp, send_buffer_bytes, recv_buffer_bytes).to_con()?;
+                        udp::bind(local_ip, port, dscp, send_buffer_bytes, recv_buffer_bytes)?;
                     let (send_socket, receive_socket) =
-                        udp::connect(&socket, client_ip, port, timeout).to_con()?;
+                        udp::connect(&socket, client_ip, port, timeout)?;
 
                     (Box::new(send_socket), Box::new(receive_socket))
                 }
                 SocketProtocol::Tcp => {
                     let (send_socket, receive_socket) = tcp::connect_to_client(
                         timeout,
+                        local_ip,
                         &[client_ip],
                         port,
                         send_buffer_bytes,
@@ -360,18 +844,30 @@ impl StreamSocketBuilder {
                 }
             };
 
+        let send_socket = Arc::new(Mutex::new(SendSocket::new(send_socket)));
+        spawn_batch_flusher(&send_socket);
+
         Ok(StreamSocket {
             // +4 is a workaround to retain compatibilty with old protocol
             // todo: remove +4
             max_packet_size: max_packet_size + 4,
-            send_socket: Arc::new(Mutex::new(send_socket)),
+            send_socket,
             receive_socket,
             shard_recv_state: None,
             stream_recv_components: HashMap::new(),
+            direct_streams: HashMap::new(),
         })
     }
 }
 
+#[derive(Clone, Copy)]
+enum DirectShardTarget {
+    FirstShard,
+    // Offset of the shard data into the payload
+    Payload(usize),
+    Discard,
+}
+
 struct RecvState {
     shard_length: usize, // contains prefix length itself
     stream_id: u16,
@@ -381,6 +877,34 @@ struct RecvState {
     packet_cursor: usize, // counts also the prefix bytes
     overwritten_data_backup: Option<[u8; SHARD_PREFIX_SIZE]>,
     should_discard: bool,
+    // Only for direct streams, decided before reading the first byte of the shard
+    direct_target: Option<DirectShardTarget>,
+}
+
+// Reads the rest of the shard, starting from the beginning of the buffer. The bytes overwritten by
+// the prefix are restored once the shard is complete
+fn read_shard(
+    socket: &mut dyn SocketReader,
+    state: &mut RecvState,
+    sub_buffer: &mut [u8],
+) -> ConResult {
+    // Backup the small section of bytes that will be overwritten by reading from socket.
+    if state.overwritten_data_backup.is_none() {
+        state.overwritten_data_backup = Some(sub_buffer[..SHARD_PREFIX_SIZE].try_into().unwrap())
+    }
+
+    // This loop may bail out at any time if a timeout is reached. This is correctly handled by
+    // the previous code.
+    while state.packet_cursor < state.shard_length {
+        let size = socket.recv(&mut sub_buffer[state.packet_cursor..state.shard_length])?;
+        state.packet_cursor += size;
+    }
+
+    // Restore backed up bytes
+    // Safety: overwritten_data_backup is always set just before receiving the packet
+    sub_buffer[..SHARD_PREFIX_SIZE].copy_from_slice(&state.overwritten_data_backup.take().unwrap());
+
+    Ok(())
 }
 
 struct InProgressPacket {
@@ -392,22 +916,246 @@ struct InProgressPacket {
 struct StreamRecvComponents {
     used_buffer_sender: mpsc::Sender<Vec<u8>>,
     used_buffer_receiver: mpsc::Receiver<Vec<u8>>,
-    packet_queue: mpsc::Sender<ReconstructedPacket>,
+    packet_queue: Arc<PacketQueue>,
+    overflow_policy: OverflowPolicy,
     in_progress_packets: HashMap<u32, InProgressPacket>,
     discarded_shards_sink: InProgressPacket,
+    // To count each discarded packet once, not once per shard
+    last_discarded_index: Option<u32>,
+    dropped_packets: Arc<AtomicUsize>,
+}
+
+impl StreamRecvComponents {
+    // Returns None if no buffer can be freed according to the overflow policy
+    fn take_free_buffer(&mut self) -> Option<Vec<u8>> {
+        if let Ok(buffer) = self.used_buffer_receiver.try_recv() {
+            return Some(buffer);
+        }
+
+        if self.overflow_policy == OverflowPolicy::DropOldest {
+            if let Some(packet) = self.packet_queue.state.lock().packets.pop_front() {
+                self.dropped_packets.fetch_add(1, atomic::Ordering::Relaxed);
+
+                return Some(packet.buffer);
+            }
+        }
+
+        // Recycle one of the in progress packets, chances are these buffers are "dead" because one
+        // of their shards has been dropped by the network.
+        let idx = *self.in_progress_packets.keys().next()?;
+        Some(self.in_progress_packets.remove(&idx).unwrap().buffer)
+    }
+}
+
+impl Drop for StreamRecvComponents {
+    fn drop(&mut self) {
+        self.packet_queue.close();
+    }
+}
+
+struct DirectStreamComponents {
+    stream: Arc<DirectStream>,
+    discarded_shards_sink: Vec<u8>,
+}
+
+impl DirectStreamComponents {
+    // Returns None if the shard must wait in the socket until the receiver is ready
+    fn choose_target(
+        state: &mut DirectStreamState,
+        shard: &RecvState,
+        max_shard_data_size: usize,
+    ) -> Option<DirectShardTarget> {
+        if state.receiver_dropped {
+            return Some(DirectShardTarget::Discard);
+        }
+
+        let Some(packet) = &mut state.packet else {
+            if shard.shard_index == 0 {
+                return Some(DirectShardTarget::FirstShard);
+            }
+
+            // The first shard of this packet was lost
+            state.had_packet_loss = true;
+            return Some(DirectShardTarget::Discard);
+        };
+
+        match wrapping_cmp(shard.packet_index, packet.index) {
+            Ordering::Less => Some(DirectShardTarget::Discard),
+            Ordering::Equal => {
+                if shard.shard_index >= packet.shards_count
+                    || state.received_shards[shard.shard_index]
+                    || packet.outcome.is_some()
+                {
+                    // Duplicated or invalid shard
+                    return Some(DirectShardTarget::Discard);
+                }
+
+                let (header_size, (_, destination_size)) =
+                    packet.header_size.zip(packet.destination)?;
+                let offset = (shard.shard_index * max_shard_data_size).checked_sub(header_size);
+                match offset {
+                    Some(offset)
+                        if offset >= SHARD_PREFIX_SIZE
+                            && offset + shard.shard_length - SHARD_PREFIX_SIZE
+                                <= destination_size =>
+                    {
+                        Some(DirectShardTarget::Payload(offset))
+                    }
+                    _ => {
+                        debug!(
+                            "Invalid shard {} of stream {}",
+                            shard.shard_index, shard.stream_id
+                        );
+                        packet.outcome = Some(alvr_common::try_again());
+                        state.had_packet_loss = true;
+
+                        Some(DirectShardTarget::Discard)
+                    }
+                }
+            }
+            Ordering::Greater => {
+                // Some shards of the current packet were lost. If the receiver is not waiting for
+                // them yet, the new packet waits until it does
+                if packet.destination.is_some() && packet.outcome.is_none() {
+                    packet.outcome = Some(alvr_common::try_again());
+                    state.had_packet_loss = true;
+                }
+
+                None
+            }
+        }
+    }
+
+    fn recv_shard(
+        &mut self,
+        socket: &mut dyn SocketReader,
+        shard: &mut RecvState,
+        max_shard_data_size: usize,
+    ) -> ConResult {
+        let mut state = self.stream.state.lock();
+        let state_mut = &mut *state;
+
+        let target = match shard.direct_target {
+            Some(DirectShardTarget::Payload(_))
+                if state_mut
+                    .packet
+                    .as_ref()
+                    .and_then(|p| p.destination)
+                    .is_none() =>
+            {
+                // The receiver gave up on the packet while the shard was being read
+                DirectShardTarget::Discard
+            }
+            Some(target) => target,
+            None => {
+                let Some(target) = Self::choose_target(state_mut, shard, max_shard_data_size)
+                else {
+                    self.stream.condvar.notify_all();
+                    self.stream
+                        .condvar
+                        .wait_for(&mut state, BLOCKED_STREAM_POLL_INTERVAL);
+
+                    return alvr_common::try_again();
+                };
+
+                target
+            }
+        };
+        shard.direct_target = Some(target);
+
+        match target {
+            DirectShardTarget::FirstShard => {
+                let buffer = &mut state_mut.spare_buffer;
+                if buffer.len() < shard.shard_length {
+                    buffer.resize(shard.shard_length, 0);
+                }
+                read_shard(socket, shard, &mut buffer[..shard.shard_length])?;
+                buffer.truncate(shard.shard_length);
+
+                state_mut.received_shards.clear();
+                state_mut.received_shards.resize(shard.shards_count, false);
+                state_mut.received_shards[0] = true;
+                state_mut.packet = Some(DirectPacket {
+                    index: shard.packet_index,
+                    shards_count: shard.shards_count,
+                    first_shard: mem::take(&mut state_mut.spare_buffer),
+                    header_size: None,
+                    payload_size: 0,
+                    destination: None,
+                    outcome: None,
+                });
+            }
+            DirectShardTarget::Payload(offset) => {
+                let packet = state_mut.packet.as_mut().unwrap();
+                let (pointer, _) = packet.destination.unwrap();
+
+                // Safety: the destination is valid while it is set and the bounds were checked by
+                // choose_target(). The prefix overwrites the end of the previous shard, which is
+                // restored
+                let sub_buffer = unsafe {
+                    slice::from_raw_parts_mut(
+                        pointer.add(offset - SHARD_PREFIX_SIZE),
+                        shard.shard_length,
+                    )
+                };
+                read_shard(socket, shard, sub_buffer)?;
+
+                packet.payload_size = usize::max(
+                    packet.payload_size,
+                    offset + shard.shard_length - SHARD_PREFIX_SIZE,
+                );
+                state_mut.received_shards[shard.shard_index] = true;
+                if state_mut.received_shards.iter().all(|received| *received) {
+                    packet.outcome = Some(Ok(packet.payload_size));
+                }
+            }
+            DirectShardTarget::Discard => {
+                if self.discarded_shards_sink.len() < shard.shard_length {
+                    self.discarded_shards_sink.resize(shard.shard_length, 0);
+                }
+                read_shard(
+                    socket,
+                    shard,
+                    &mut self.discarded_shards_sink[..shard.shard_length],
+                )?;
+            }
+        }
+
+        self.stream.condvar.notify_all();
+
+        Ok(())
+    }
+}
+
+impl Drop for DirectStreamComponents {
+    fn drop(&mut self) {
+        self.stream.state.lock().closed = true;
+        self.stream.condvar.notify_all();
+    }
 }
 
 // Note: used buffers don't *have* to be split by stream ID, but doing so improves memory usage
 // todo: impose cap on number of created buffers to avoid OOM crashes
 pub struct StreamSocket {
     max_packet_size: usize,
-    send_socket: Arc<Mutex<Box<dyn SocketWriter>>>,
+    send_socket: Arc<Mutex<SendSocket>>,
     receive_socket: Box<dyn SocketReader>,
     shard_recv_state: Option<RecvState>,
     stream_recv_components: HashMap<u16, StreamRecvComponents>,
+    direct_streams: HashMap<u16, DirectStreamComponents>,
 }
 
 impl StreamSocket {
+    /// Developer tool: the shards of the given streams are delayed and dropped as configured, see
+    /// network_simulator.rs. Shards are not batched anymore
+    pub fn simulate_network(&self, seed: u64, streams: HashMap<u16, NetworkImpairmentConfig>) {
+        let mut send_socket = self.send_socket.lock();
+        send_socket.flush().ok();
+
+        let inner = mem::replace(&mut send_socket.writer, Box::new(NoWriter));
+        send_socket.writer = Box::new(ImpairedWriter::new(inner, seed, streams));
+    }
+
     pub fn request_stream<T>(&self, stream_id: u16) -> StreamSender<T> {
         StreamSender {
             inner: Arc::clone(&self.send_socket),
@@ -420,15 +1168,17 @@ impl StreamSocket {
     }
 
     // max_concurrent_buffers: number of buffers allocated by this call which will be reused to
-    // receive packets for this stream ID. If packets are not read fast enough, the shards received
-    // for this particular stream will be discarded
+    // receive packets for this stream ID. If packets are not read fast enough, overflow_policy
+    // decides which packets of this particular stream are discarded
     pub fn subscribe_to_stream<T>(
         &mut self,
         stream_id: u16,
         max_concurrent_buffers: usize,
+        overflow_policy: OverflowPolicy,
     ) -> StreamReceiver<T> {
-        let (packet_sender, packet_receiver) = mpsc::channel();
+        let packet_queue = Arc::new(PacketQueue::default());
         let (used_buffer_sender, used_buffer_receiver) = mpsc::channel();
+        let dropped_packets = Arc::new(AtomicUsize::new(0));
 
         for _ in 0..max_concurrent_buffers {
             used_buffer_sender.send(vec![]).ok();
@@ -439,25 +1189,83 @@ impl StreamSocket {
             StreamRecvComponents {
                 used_buffer_sender: used_buffer_sender.clone(),
                 used_buffer_receiver,
-                packet_queue: packet_sender,
+                packet_queue: Arc::clone(&packet_queue),
+                overflow_policy,
                 in_progress_packets: HashMap::new(),
                 discarded_shards_sink: InProgressPacket {
                     buffer: vec![],
                     buffer_length: 0,
                     received_shard_indices: HashSet::new(),
                 },
+                last_discarded_index: None,
+                dropped_packets: Arc::clone(&dropped_packets),
             },
         );
 
         StreamReceiver {
-            packet_receiver,
+            packet_queue,
             used_buffer_queue: used_buffer_sender,
             _phantom: PhantomData,
             last_packet_index: None,
+            dropped_packets,
+        }
+    }
+
+    /// Like subscribe_to_stream(), but the payload of each packet is read directly into a buffer
+    /// provided to the receiver. The receive loop waits for the receiver like with
+    /// OverflowPolicy::Block
+    pub fn subscribe_to_direct_stream<T>(&mut self, stream_id: u16) -> DirectStreamReceiver<T> {
+        let stream = Arc::new(DirectStream {
+            state: Mutex::new(DirectStreamState {
+                packet: None,
+                received_shards: vec![],
+                spare_buffer: vec![],
+                had_packet_loss: false,
+                closed: false,
+                receiver_dropped: false,
+            }),
+            condvar: Condvar::new(),
+        });
+
+        self.direct_streams.insert(
+            stream_id,
+            DirectStreamComponents {
+                stream: Arc::clone(&stream),
+                discarded_shards_sink: vec![],
+            },
+        );
+
+        DirectStreamReceiver {
+            stream,
+            max_shard_data_size: self.max_packet_size - SHARD_PREFIX_SIZE,
+            last_packet_index: None,
+            invalidated: false,
+            _phantom: PhantomData,
         }
     }
 
-    pub fn recv(&mut self) -> ConResult {
+    /// Runs the receive loop on its own thread until is_running() returns false or the socket
+    /// fails, in which case on_error() is called. All the streams must be subscribed to before
+    pub fn spawn_receive_thread(
+        mut self,
+        is_running: impl Fn() -> bool + Send + 'static,
+        on_error: impl FnOnce(ConnectionError) + Send + 'static,
+    ) -> JoinHandle<()> {
+        thread::spawn(move || {
+            while is_running() {
+                match self.recv() {
+                    Ok(()) | Err(ConnectionError::TryAgain(_)) => (),
+                    Err(e) => {
+                        on_error(e);
+
+                        return;
+                    }
+                }
+            }
+        })
+    }
+
+    fn recv(&mut self) -> ConResult {
         let shard_recv_state_mut = if let Some(state) = &mut self.shard_recv_state {
             state
         } else {
@@ -485,9 +1293,23 @@ impl StreamSocket {
                 packet_cursor: 0,
                 overwritten_data_backup: None,
                 should_discard: false,
+                direct_target: None,
             })
         };
 
+        if let Some(direct_stream) = self.direct_streams.get_mut(&shard_recv_state_mut.stream_id) {
+            direct_stream.recv_shard(
+                &mut *self.receive_socket,
+                shard_recv_state_mut,
+                self.max_packet_size - SHARD_PREFIX_SIZE,
+            )?;
+
+            // Mark current shard as read and allow for a new shard to be read
+            self.shard_recv_state = None;
+
+            return Ok(());
+        }
+
         let Some(components) = self
             .stream_recv_components
             .get_mut(&shard_recv_state_mut.stream_id)
@@ -506,13 +1328,7 @@ impl StreamSocket {
             .get_mut(&shard_recv_state_mut.packet_index)
         {
             packet
-        } else if let Some(buffer) = components.used_buffer_receiver.try_recv().ok().or_else(|| {
-            // By default, try to dequeue a used buffer. In case none were found, recycle one of the
-            // in progress packets, chances are these buffers are "dead" because one of their shards
-            // has been dropped by the network.
-            let idx = *components.in_progress_packets.iter().next()?.0;
-            Some(components.in_progress_packets.remove(&idx).unwrap().buffer)
-        }) {
+        } else if let Some(buffer) = components.take_free_buffer() {
             // NB: Can't use entry pattern because we want to allow bailing out on the line above
             components.in_progress_packets.insert(
                 shard_recv_state_mut.packet_index,
@@ -529,8 +1345,25 @@ impl StreamSocket {
                 .in_progress_packets
                 .get_mut(&shard_recv_state_mut.packet_index)
                 .unwrap()
+        } else if components.overflow_policy == OverflowPolicy::Block {
+            // The shard is left in the socket and read once the receiver returns a buffer
+            if let Ok(buffer) = components
+                .used_buffer_receiver
+                .recv_timeout(BLOCKED_STREAM_POLL_INTERVAL)
+            {
+                components.used_buffer_sender.send(buffer).ok();
+            }
+
+            return alvr_common::try_again();
         } else {
             // This branch may be hit in case the thread related to the stream hangs for some reason
+            if components.last_discarded_index != Some(shard_recv_state_mut.packet_index) {
+                components.last_discarded_index = Some(shard_recv_state_mut.packet_index);
+                components
+                    .dropped_packets
+                    .fetch_add(1, atomic::Ordering::Relaxed);
+            }
+
             shard_recv_state_mut.should_discard = true;
             shard_recv_state_mut.packet_cursor = 0; // reset cursor from old shards
                                                     // always write at the start of the packet so the buffer doesn't grow much
@@ -558,31 +1391,12 @@ impl StreamSocket {
             }
         }
 
-        let sub_buffer = &mut in_progress_packet.buffer[packet_start_index..];
-
         // Read shard into the single contiguous buffer
-        {
-            // Backup the small section of bytes that will be overwritten by reading from socket.
-            if shard_recv_state_mut.overwritten_data_backup.is_none() {
-                shard_recv_state_mut.overwritten_data_backup =
-                    Some(sub_buffer[..SHARD_PREFIX_SIZE].try_into().unwrap())
-            }
-
-            // This loop may bail out at any time if a timeout is reached. This is correctly handled by
-            // the previous code.
-            while shard_recv_state_mut.packet_cursor < shard_recv_state_mut.shard_length {
-                let size = self.receive_socket.recv(
-                    &mut sub_buffer
-                        [shard_recv_state_mut.packet_cursor..shard_recv_state_mut.shard_length],
-                )?;
-                shard_recv_state_mut.packet_cursor += size;
-            }
-
-            // Restore backed up bytes
-            // Safety: overwritten_data_backup is always set just before receiving the packet
-            sub_buffer[..SHARD_PREFIX_SIZE]
-                .copy_from_slice(&shard_recv_state_mut.overwritten_data_backup.take().unwrap());
-        }
+        read_shard(
+            &mut *self.receive_socket,
+            shard_recv_state_mut,
+            &mut in_progress_packet.buffer[packet_start_index..],
+        )?;
 
         if !shard_recv_state_mut.should_discard {
             in_progress_packet
@@ -593,18 +1407,15 @@ impl StreamSocket {
         // Check if packet is complete and send
         if in_progress_packet.received_shard_indices.len() == shard_recv_state_mut.shards_count {
             let size = in_progress_packet.buffer_length;
-            components
-                .packet_queue
-                .send(ReconstructedPacket {
-                    index: shard_recv_state_mut.packet_index,
-                    buffer: components
-                        .in_progress_packets
-                        .remove(&shard_recv_state_mut.packet_index)
-                        .unwrap()
-                        .buffer,
-                    size,
-                })
-                .ok();
+            components.packet_queue.push(ReconstructedPacket {
+                index: shard_recv_state_mut.packet_index,
+                buffer: components
+                    .in_progress_packets
+                    .remove(&shard_recv_state_mut.packet_index)
+                    .unwrap()
+                    .buffer,
+                size,
+            });
 
             // Keep only shards with later packet index (using wrapping logic)
             while let Some((idx, _)) = components.in_progress_packets.iter().find(|(idx, _)| {
@@ -624,3 +1435,398 @@ impl StreamSocket {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, TcpStream};
+
+    const TIMEOUT: Duration = Duration::from_millis(50);
+    const MAX_PACKET_SIZE: usize = 1400;
+
+    fn loopback_pair() -> (StreamSocket, StreamSocket) {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let first = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (second, _) = listener.accept().unwrap();
+
+        let to_stream_socket = |socket: TcpStream| {
+            socket.set_read_timeout(Some(TIMEOUT)).unwrap();
+            StreamSocket {
+                max_packet_size: MAX_PACKET_SIZE,
+                send_socket: Arc::new(Mutex::new(SendSocket::new(Box::new(
+                    socket.try_clone().unwrap(),
+                )))),
+                receive_socket: Box::new(socket),
+                shard_recv_state: None,
+                stream_recv_components: HashMap::new(),
+                direct_streams: HashMap::new(),
+            }
+        };
+
+        (to_stream_socket(first), to_stream_socket(second))
+    }
+
+    fn queue_packet(sender: &mut StreamSender<u32>, header: u32, payload: &[u8]) {
+        let mut buffer = sender.get_buffer(&header).unwrap();
+        buffer
+            .get_range_mut(0, payload.len())
+            .copy_from_slice(payload);
+        sender.queue(buffer).unwrap();
+    }
+
+    #[test]
+    fn test_queued_packets_arrive_after_flush() {
+        let (sender_socket, mut receiver_socket) = loopback_pair();
+        let mut first_sender = sender_socket.request_stream::<u32>(1);
+        let mut second_sender = sender_socket.request_stream::<u32>(2);
+        let mut first_receiver =
+            receiver_socket.subscribe_to_stream::<u32>(1, 10, OverflowPolicy::DropNewest);
+        let mut second_receiver =
+            receiver_socket.subscribe_to_stream::<u32>(2, 10, OverflowPolicy::DropNewest);
+
+        queue_packet(&mut first_sender, 10, b"first");
+
+        // Nothing was written yet
+        assert!(matches!(
+            receiver_socket.recv(),
+            Err(alvr_common::ConnectionError::TryAgain(_))
+        ));
+
+        queue_packet(&mut second_sender, 20, b"second");
+        queue_packet(&mut first_sender, 11, &[]);
+        // Larger than one shard
+        let big_payload = (0..2 * MAX_PACKET_SIZE)
+            .map(|i| i as u8)
+            .collect::<Vec<_>>();
+        queue_packet(&mut second_sender, 21, &big_payload);
+
+        first_sender.flush().unwrap();
+
+        let mut received = vec![];
+        while received.len() < 4 {
+            receiver_socket.recv().ok();
+
+            for receiver in [&mut first_receiver, &mut second_receiver] {
+                if let Ok(data) = receiver.recv(Duration::ZERO) {
+                    assert!(!data.had_packet_loss());
+                    let (header, payload) = data.get().unwrap();
+                    received.push((header, payload.to_vec()));
+                }
+            }
+        }
+
+        assert_eq!(
+            received,
+            [
+                (10, b"first".to_vec()),
+                (20, b"second".to_vec()),
+                (11, vec![]),
+                (21, big_payload),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_send_writes_queued_packets_first() {
+        let (sender_socket, mut receiver_socket) = loopback_pair();
+        let mut queued_sender = sender_socket.request_stream::<u32>(1);
+        let mut video_sender = sender_socket.request_stream::<u32>(3);
+        let mut queued_receiver =
+            receiver_socket.subscribe_to_stream::<u32>(1, 10, OverflowPolicy::DropNewest);
+        let mut video_receiver =
+            receiver_socket.subscribe_to_stream::<u32>(3, 10, OverflowPolicy::Block);
+
+        queue_packet(&mut queued_sender, 1, b"small");
+        video_sender.send_header(&2).unwrap();
+
+        receiver_socket.recv().unwrap();
+        assert_eq!(
+            queued_receiver
+                .recv(Duration::ZERO)
+                .unwrap()
+                .get_header()
+                .unwrap(),
+            1
+        );
+        receiver_socket.recv().unwrap();
+        assert_eq!(
+            video_receiver
+                .recv(Duration::ZERO)
+                .unwrap()
+                .get_header()
+                .unwrap(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_queued_packet_is_written_when_idle() {
+        let (sender_socket, mut receiver_socket) = loopback_pair();
+        spawn_batch_flusher(&sender_socket.send_socket);
+        let mut sender = sender_socket.request_stream::<u32>(1);
+        let mut receiver =
+            receiver_socket.subscribe_to_stream::<u32>(1, 10, OverflowPolicy::DropNewest);
+
+        queue_packet(&mut sender, 1, b"idle");
+
+        // Nothing else is sent, the batch flusher writes the packet
+        receiver_socket.recv().unwrap();
+        assert_eq!(
+            receiver.recv(Duration::ZERO).unwrap().get_header().unwrap(),
+            1
+        );
+    }
+
+    // Reads the shards of single shard packets, which may not have arrived yet
+    fn recv_shards(socket: &mut StreamSocket, count: usize) {
+        let mut received = 0;
+        while received < count {
+            if socket.recv().is_ok() {
+                received += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn test_overflow_policies() {
+        let (sender_socket, mut receiver_socket) = loopback_pair();
+        let mut tracking_sender = sender_socket.request_stream::<u32>(1);
+        let mut statistics_sender = sender_socket.request_stream::<u32>(2);
+        let mut tracking_receiver =
+            receiver_socket.subscribe_to_stream::<u32>(1, 2, OverflowPolicy::DropOldest);
+        let mut statistics_receiver =
+            receiver_socket.subscribe_to_stream::<u32>(2, 2, OverflowPolicy::DropNewest);
+
+        for header in 0..4 {
+            tracking_sender.send_header(&header).unwrap();
+            statistics_sender.send_header(&header).unwrap();
+        }
+        recv_shards(&mut receiver_socket, 8);
+
+        let recv_headers = |receiver: &mut StreamReceiver<u32>| {
+            (0..2)
+                .map(|_| receiver.recv(Duration::ZERO).unwrap().get_header().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(recv_headers(&mut tracking_receiver), [2, 3]);
+        assert_eq!(tracking_receiver.dropped_packets(), 2);
+        assert_eq!(recv_headers(&mut statistics_receiver), [0, 1]);
+        assert_eq!(statistics_receiver.dropped_packets(), 2);
+        assert!(tracking_receiver.recv(Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_blocked_stream_keeps_all_packets() {
+        let (sender_socket, mut receiver_socket) = loopback_pair();
+        let mut video_sender = sender_socket.request_stream::<u32>(3);
+        let mut video_receiver =
+            receiver_socket.subscribe_to_stream::<u32>(3, 1, OverflowPolicy::Block);
+
+        video_sender.send_header(&1).unwrap();
+        video_sender.send_header(&2).unwrap();
+        recv_shards(&mut receiver_socket, 1);
+
+        // The only buffer is held by the receiver
+        let data = video_receiver.recv(Duration::ZERO).unwrap();
+        assert!(matches!(
+            receiver_socket.recv(),
+            Err(alvr_common::ConnectionError::TryAgain(_))
+        ));
+        assert_eq!(data.get_header().unwrap(), 1);
+        drop(data);
+
+        recv_shards(&mut receiver_socket, 1);
+        let data = video_receiver.recv(Duration::ZERO).unwrap();
+        assert!(!data.had_packet_loss());
+        assert_eq!(data.get_header().unwrap(), 2);
+        assert_eq!(video_receiver.dropped_packets(), 0);
+    }
+
+    #[test]
+    fn test_receiver_fails_once_the_socket_is_dropped() {
+        let (_sender_socket, mut receiver_socket) = loopback_pair();
+        let mut receiver =
+            receiver_socket.subscribe_to_stream::<u32>(1, 10, OverflowPolicy::DropNewest);
+
+        assert!(matches!(
+            receiver.recv(Duration::ZERO),
+            Err(alvr_common::ConnectionError::TryAgain(_))
+        ));
+
+        let thread = receiver_socket.spawn_receive_thread(|| false, |_| ());
+        thread.join().unwrap();
+        assert!(matches!(
+            receiver.recv(TIMEOUT),
+            Err(alvr_common::ConnectionError::Other(_))
+        ));
+    }
+
+    #[test]
+    fn test_paced_packet_is_spread_over_duration() {
+        const PACING_DURATION: Duration = Duration::from_millis(40);
+
+        let (sender_socket, mut receiver_socket) = loopback_pair();
+        let mut video_sender = sender_socket.request_stream::<u32>(3);
+        let mut video_receiver =
+            receiver_socket.subscribe_to_stream::<u32>(3, 10, OverflowPolicy::Block);
+
+        let payload = (0..20 * MAX_PACKET_SIZE)
+            .map(|i| i as u8)
+            .collect::<Vec<_>>();
+        let mut buffer = video_sender.get_buffer(&7).unwrap();
+        buffer
+            .get_range_mut(0, payload.len())
+            .copy_from_slice(&payload);
+
+        // The last shard is written at the start of the last slot of the duration
+        let start = Instant::now();
+        let pacing_wait = video_sender.send_paced(buffer, PACING_DURATION).unwrap();
+        let elapsed = start.elapsed();
+        assert!(elapsed >= PACING_DURATION * 19 / 21);
+        // The time spent writing is not part of the pacing wait
+        assert!(pacing_wait <= elapsed);
+
+        loop {
+            receiver_socket.recv().ok();
+
+            if let Ok(data) = video_receiver.recv(Duration::ZERO) {
+                assert!(!data.had_packet_loss());
+                let (header, received_payload) = data.get().unwrap();
+                assert_eq!(header, 7);
+                assert_eq!(received_payload, payload);
+
+                break;
+            }
+        }
+    }
+
+    fn payload(size: usize) -> Vec<u8> {
+        (0..size).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_direct_stream_reads_payload_into_buffer() {
+        let (sender_socket, mut receiver_socket) = loopback_pair();
+        let mut video_sender = sender_socket.request_stream::<u32>(3);
+        let mut tracking_sender = sender_socket.request_stream::<u32>(1);
+        let mut video_receiver = receiver_socket.subscribe_to_direct_stream::<u32>(3);
+        let mut tracking_receiver =
+            receiver_socket.subscribe_to_stream::<u32>(1, 10, OverflowPolicy::DropNewest);
+
+        let is_running = Arc::new(atomic::AtomicBool::new(true));
+        let thread = receiver_socket.spawn_receive_thread(
+            {
+                let is_running = Arc::clone(&is_running);
+                move || is_running.load(atomic::Ordering::Relaxed)
+            },
+            |_| (),
+        );
+
+        let big_payload = payload(5 * MAX_PACKET_SIZE);
+        let mut payload_buffer = vec![];
+        for header in 0..3 {
+            let mut buffer = video_sender.get_buffer(&header).unwrap();
+            buffer
+                .get_range_mut(0, big_payload.len())
+                .copy_from_slice(&big_payload);
+            video_sender.send(buffer).unwrap();
+            tracking_sender.send_header(&(10 + header)).unwrap();
+
+            let packet = video_receiver.recv_header(TIMEOUT * 10).unwrap();
+            assert_eq!(packet.header, header);
+            assert!(!packet.had_packet_loss);
+            assert!(packet.max_payload_size >= big_payload.len());
+
+            payload_buffer.resize(packet.max_payload_size, 0);
+            let size = video_receiver
+                .recv_payload_into(&mut payload_buffer, TIMEOUT * 10)
+                .unwrap();
+            assert_eq!(&payload_buffer[..size], big_payload);
+
+            let data = tracking_receiver.recv(TIMEOUT * 10).unwrap();
+            assert_eq!(data.get_header().unwrap(), 10 + header);
+        }
+
+        is_running.store(false, atomic::Ordering::Relaxed);
+        thread.join().unwrap();
+    }
+
+    #[test]
+    fn test_direct_stream_keeps_packet_if_buffer_too_small() {
+        let (sender_socket, mut receiver_socket) = loopback_pair();
+        let mut video_sender = sender_socket.request_stream::<u32>(3);
+        let mut video_receiver = receiver_socket.subscribe_to_direct_stream::<u32>(3);
+
+        let small_payload = payload(100);
+        let mut buffer = video_sender.get_buffer(&1).unwrap();
+        buffer
+            .get_range_mut(0, small_payload.len())
+            .copy_from_slice(&small_payload);
+        video_sender.send(buffer).unwrap();
+        recv_shards(&mut receiver_socket, 1);
+
+        let packet = video_receiver.recv_header(Duration::ZERO).unwrap();
+        assert!(matches!(
+            video_receiver.recv_payload_into(&mut [0; 10], Duration::ZERO),
+            Err(alvr_common::ConnectionError::Other(_))
+        ));
+
+        let mut payload_buffer = vec![0; packet.max_payload_size];
+        let size = video_receiver
+            .recv_payload_into(&mut payload_buffer, Duration::ZERO)
+            .unwrap();
+        assert_eq!(&payload_buffer[..size], small_payload);
+    }
+
+    #[test]
+    fn test_direct_stream_timeout_invalidates_receiver() {
+        let (sender_socket, mut receiver_socket) = loopback_pair();
+        let mut video_sender = sender_socket.request_stream::<u32>(3);
+        let mut video_receiver = receiver_socket.subscribe_to_direct_stream::<u32>(3);
+
+        let big_payload = payload(3 * MAX_PACKET_SIZE);
+        for header in 0..2 {
+            let mut buffer = video_sender.get_buffer(&header).unwrap();
+            buffer
+                .get_range_mut(0, big_payload.len())
+                .copy_from_slice(&big_payload);
+            video_sender.send(buffer).unwrap();
+        }
+
+        // Only the first shard is read, the receive loop is not running afterwards
+        recv_shards(&mut receiver_socket, 1);
+        let packet = video_receiver.recv_header(Duration::ZERO).unwrap();
+        let mut payload_buffer = vec![0; packet.max_payload_size];
+        assert!(matches!(
+            video_receiver.recv_payload_into(&mut payload_buffer, TIMEOUT),
+            Err(alvr_common::ConnectionError::Other(_))
+        ));
+
+        // The rest of the packets are discarded
+        while receiver_socket.recv().is_ok() {}
+        assert!(matches!(
+            video_receiver.recv_header(Duration::ZERO),
+            Err(alvr_common::ConnectionError::Other(_))
+        ));
+    }
+
+    #[test]
+    fn test_direct_stream_fails_once_the_socket_is_dropped() {
+        let (_sender_socket, mut receiver_socket) = loopback_pair();
+        let mut video_receiver = receiver_socket.subscribe_to_direct_stream::<u32>(3);
+
+        assert!(matches!(
+            video_receiver.recv_header(Duration::ZERO),
+            Err(alvr_common::ConnectionError::TryAgain(_))
+        ));
+
+        let thread = receiver_socket.spawn_receive_thread(|| false, |_| ());
+        thread.join().unwrap();
+        for _ in 0..2 {
+            assert!(matches!(
+                video_receiver.recv_header(TIMEOUT),
+                Err(alvr_common::ConnectionError::Other(_))
+            ));
+        }
+    }
+}
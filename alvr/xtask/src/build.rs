@@ -258,6 +258,14 @@ pub fn build_streamer(
             build_layout.openvr_driver_manifest(),
         )
         .unwrap();
+
+        // copy dashboard translations
+        command::copy_recursive(
+            &sh,
+            &afs::crate_dir("dashboard").join("languages"),
+            &build_layout.languages_dir(),
+        )
+        .unwrap();
     }
 }
 